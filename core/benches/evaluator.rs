@@ -205,6 +205,7 @@ fn bench_vm_only(c: &mut Criterion) {
 
             let code = Code {
                 constants: vec![],
+                constant_types: vec![],
                 adapters: vec![],
                 generic_adapters: vec![],
                 instructions,
@@ -6,19 +6,21 @@ use core::cell::RefCell;
 
 use crate::{
     String, Vec,
+    analyzer::{AnalyzerOptions, DuplicateKeyPolicy, free_variables},
     analyzer::error::{TypeError, TypeErrorKind},
     analyzer::typed_expr::{self as typed_expr, Expr, ExprInner, LambdaInstantiations, TypedExpr},
     casting, format,
     parser::{self, BinaryOp, ComparisonOp, Span, UnaryOp},
     scope_stack::{self, ScopeStack},
     types::{
-        Type, TypeClassResolver, TypeScheme,
+        Type, TypeClassConstraint, TypeClassResolver, TypeConversionError, TypeScheme,
         manager::TypeManager,
         traits::{TypeKind, TypeView},
-        type_expr_to_type,
+        type_expr_to_type_with_aliases,
         unification::Unification,
     },
     values::dynamic::Value,
+    vec,
 };
 use hashbrown::DefaultHashBuilder;
 
@@ -30,100 +32,238 @@ pub fn analyze<'types, 'arena>(
     globals: &[(&'arena str, &'types Type<'types>)],
     variables: &[(&'arena str, &'types Type<'types>)],
 ) -> Result<&'arena TypedExpr<'types, 'arena>, TypeError> {
-    tracing::info!(
-        globals_count = globals.len(),
-        variables_count = variables.len(),
-        "Starting type analysis"
-    );
-
-    // Create annotation map for typed expressions
-    // We reuse the same source string since both ParsedExpr and TypedExpr are in the same arena
-    let typed_ann = arena.alloc(parser::AnnotatedSource::new(arena, expr.ann.source));
+    analyze_with_options(
+        type_manager,
+        arena,
+        expr,
+        globals,
+        variables,
+        AnalyzerOptions::default(),
+    )
+}
 
-    let mut analyzer = Analyzer {
+/// Like [`analyze`], with non-default [`AnalyzerOptions`].
+pub fn analyze_with_options<'types, 'arena>(
+    type_manager: &'types TypeManager<'types>,
+    arena: &'arena Bump,
+    expr: &'arena parser::ParsedExpr<'arena>,
+    globals: &[(&'arena str, &'types Type<'types>)],
+    variables: &[(&'arena str, &'types Type<'types>)],
+    options: AnalyzerOptions,
+) -> Result<&'arena TypedExpr<'types, 'arena>, TypeError> {
+    analyze_with_diagnostics(
         type_manager,
         arena,
-        scope_stack: ScopeStack::new(),
-        unification: Unification::new(type_manager),
-        type_class_resolver: TypeClassResolver::new(),
-        parsed_ann: expr.ann,
-        typed_ann,
-        current_span: None, // Initialize to None
-        env_vars_stack: Vec::new(),
-        polymorphic_lambdas: hashbrown::HashMap::new(),
-        pending_instantiations: hashbrown::HashMap::new(),
+        expr,
+        globals,
+        variables,
+        options,
+        &mut |_| {},
+    )
+    .map_err(first_error)
+}
+
+/// Like [`analyze_with_options`], but reports every error found instead of
+/// only the first one.
+///
+/// Each error is passed to `on_error` as soon as the analyzer finds it, so
+/// callers can stream diagnostics without waiting for the analysis to finish.
+/// On failure, the same errors are also returned in the order they were
+/// reported.
+///
+/// Warnings enabled in `options` (see [`TypeError::severity`]) are passed to
+/// `on_error` once the expression has been analyzed, but never returned and
+/// never fail the analysis.
+pub fn analyze_with_diagnostics<'types, 'arena>(
+    type_manager: &'types TypeManager<'types>,
+    arena: &'arena Bump,
+    expr: &'arena parser::ParsedExpr<'arena>,
+    globals: &[(&'arena str, &'types Type<'types>)],
+    variables: &[(&'arena str, &'types Type<'types>)],
+    options: AnalyzerOptions,
+    on_error: &mut dyn FnMut(&TypeError),
+) -> Result<&'arena TypedExpr<'types, 'arena>, Vec<TypeError>> {
+    let context = AnalysisContext {
+        globals: GlobalScope::new(type_manager, arena, globals),
+        variables,
+        constraints: &[],
     };
+    analyze_with_global_scope(type_manager, arena, expr, context, options, on_error)
+}
+
+/// Names visible to an analyzed expression besides its own bindings, for
+/// [`analyze_with_global_scope`] and [`analyze_partial_with_global_scope`].
+#[derive(Debug, Clone, Copy)]
+pub struct AnalysisContext<'types, 'arena, 'a> {
+    /// Globals, prepared by [`GlobalScope::new`].
+    pub globals: GlobalScope<'types, 'arena>,
+    /// Variables, such as the expression's parameters, sorted by name.
+    ///
+    /// Type variables in their types are quantified, so a variable holding
+    /// a polymorphic value (such as another expression's [`TypedExpr`]
+    /// result) can be used at several types.
+    pub variables: &'a [(&'arena str, &'types Type<'types>)],
+    /// Type class constraints on the type variables of `variables`, as in
+    /// [`TypedExpr::constraints`].
+    pub constraints: &'a [TypeClassConstraint<'types>],
+}
+
+/// Like [`analyze_with_diagnostics`], with globals already prepared by
+/// [`GlobalScope::new`].
+///
+/// Building the global scope is proportional to the number of globals, so
+/// callers analyzing many expressions against the same globals should build
+/// it once and pass it to each analysis in `context`.
+pub fn analyze_with_global_scope<'types, 'arena>(
+    type_manager: &'types TypeManager<'types>,
+    arena: &'arena Bump,
+    expr: &'arena parser::ParsedExpr<'arena>,
+    context: AnalysisContext<'types, 'arena, '_>,
+    options: AnalyzerOptions,
+    on_error: &mut dyn FnMut(&TypeError),
+) -> Result<&'arena TypedExpr<'types, 'arena>, Vec<TypeError>> {
+    let mut analyzer = Analyzer::new(type_manager, arena, expr, context, options);
+    analyzer.run(expr, on_error)
+}
+
+/// The type schemes of the globals (constants, packages, functions) visible
+/// to an analyzed expression, sorted by name.
+///
+/// The scope is immutable, so one instance can be shared by any number of
+/// analyses using the same type manager.
+#[derive(Debug, Clone, Copy)]
+pub struct GlobalScope<'types, 'arena> {
+    bindings: &'arena [(&'arena str, TypeScheme<'types, 'arena>)],
+}
 
-    // Push globals scope (constants, packages, functions)
-    if !globals.is_empty() {
+impl<'types, 'arena> GlobalScope<'types, 'arena> {
+    /// Builds the scope of `globals`, which must be sorted by name.
+    pub fn new(
+        type_manager: &'types TypeManager<'types>,
+        arena: &'arena Bump,
+        globals: &[(&'arena str, &'types Type<'types>)],
+    ) -> Self {
         // Wrap each type in a monomorphic TypeScheme
         // TODO: Accept TypeScheme as an argument.
-        let bindings: Vec<(&'arena str, TypeScheme<'types, 'arena>)> = globals
-            .iter()
-            .map(|(name, ty)| {
-                let empty_quantified = type_manager.alloc_u16_slice(&[]);
-                (*name, TypeScheme::new(empty_quantified, ty))
-            })
-            .collect();
-        let bindings_slice = arena.alloc_slice_fill_iter(bindings.into_iter());
-        analyzer
-            .scope_stack
-            .push(scope_stack::CompleteScope::from_sorted(bindings_slice));
+        let empty_quantified = type_manager.alloc_u16_slice(&[]);
+        let bindings = arena.alloc_slice_fill_iter(
+            globals
+                .iter()
+                .map(|(name, ty)| (*name, TypeScheme::new(empty_quantified, ty))),
+        );
+        GlobalScope { bindings }
     }
 
-    // Push variables scope (client-provided runtime variables)
-    if !variables.is_empty() {
-        // Wrap each type in a monomorphic TypeScheme
-        // TODO: Accept TypeScheme as an argument.
-        let bindings: Vec<(&'arena str, TypeScheme<'types, 'arena>)> = variables
-            .iter()
-            .map(|(name, ty)| {
-                let empty_quantified = type_manager.alloc_u16_slice(&[]);
-                (*name, TypeScheme::new(empty_quantified, ty))
-            })
-            .collect();
-        let bindings_slice = arena.alloc_slice_fill_iter(bindings.into_iter());
-        analyzer
-            .scope_stack
-            .push(scope_stack::CompleteScope::from_sorted(bindings_slice));
+    /// The number of globals in the scope.
+    pub fn len(&self) -> usize {
+        self.bindings.len()
     }
-    let result = analyzer.analyze_expr(expr)?;
 
-    // Check all type class constraints after unification
-    analyzer.finalize_constraints()?;
+    /// Whether the scope has no globals.
+    pub fn is_empty(&self) -> bool {
+        self.bindings.is_empty()
+    }
 
-    // Resolve all type variables in the expression tree
-    // This replaces type variables with their fully resolved types (e.g., _5 → Str)
-    // Type variables that aren't unified (e.g., generalized lambda body vars) remain unchanged
-    // We also track old→new pointer mappings to remap lambda_instantiations keys
-    let mut ptr_remap = hashbrown::HashMap::new();
-    let resolved_expr = analyzer.resolve_expr_types(result.expr, &mut ptr_remap);
+    /// Returns the type of the global `name`, if any.
+    pub fn lookup(&self, name: &str) -> Option<&'types Type<'types>> {
+        self.bindings
+            .binary_search_by_key(&name, |(global, _)| *global)
+            .ok()
+            .map(|index| self.bindings[index].1.ty)
+    }
+}
 
-    // Build final instantiation substitutions by resolving fresh vars to concrete types
-    let old_lambda_instantiations = analyzer.build_lambda_instantiations(arena);
+/// The error the fail-fast entry points report.
+fn first_error(errors: Vec<TypeError>) -> TypeError {
+    errors
+        .into_iter()
+        .next()
+        .expect("analysis fails with at least one error")
+}
 
-    // Remap the lambda_instantiations keys from old pointers to new pointers
-    // This is necessary because resolve_expr_types allocates new Expr nodes
-    let lambda_instantiations =
-        Analyzer::remap_lambda_instantiations(old_lambda_instantiations, &ptr_remap, arena);
+/// Result of [`analyze_partial`]: the analysis outcome together with the
+/// types of every sub-expression that type checked before it finished.
+pub struct PartialAnalysis<'types, 'arena> {
+    /// The same result [`analyze`] would have returned.
+    pub result: Result<&'arena TypedExpr<'types, 'arena>, TypeError>,
+    /// Span and type of each sub-expression that was successfully typed, in
+    /// the order the analyzer completed them.
+    pub node_types: Vec<(Span, &'types Type<'types>)>,
+}
 
-    // Create new TypedExpr with resolved expression and remapped instantiation info
-    let resolved_result = analyzer.arena.alloc(TypedExpr {
-        expr: resolved_expr,
-        ann: result.ann,
-        lambda_instantiations,
-    });
+impl<'types, 'arena> PartialAnalysis<'types, 'arena> {
+    /// Returns the type of the innermost typed sub-expression containing
+    /// `offset`, if any.
+    pub fn type_at(&self, offset: usize) -> Option<&'types Type<'types>> {
+        self.node_types
+            .iter()
+            .filter(|(span, _)| span.0.start <= offset && offset < span.0.end)
+            .min_by_key(|(span, _)| span.0.len())
+            .map(|(_, ty)| *ty)
+    }
+}
 
-    Ok(resolved_result)
+/// Like [`analyze`], but keeps the types of the sub-expressions that did type
+/// check even if the analysis as a whole fails.
+///
+/// This is meant for tooling such as hover on incomplete or ill-typed code:
+/// in `1 + "a"` the `1` is still known to be `Int`. Analysis goes on past a
+/// sub-expression that fails to type check, treating its type as unknown,
+/// so in `(1 + "a") + (2 + 3)` the `2 + 3` is known to be `Int` too. Combine with
+/// [`parser::parse_with_recovery`] to also cover code that does not parse.
+pub fn analyze_partial<'types, 'arena>(
+    type_manager: &'types TypeManager<'types>,
+    arena: &'arena Bump,
+    expr: &'arena parser::ParsedExpr<'arena>,
+    globals: &[(&'arena str, &'types Type<'types>)],
+    variables: &[(&'arena str, &'types Type<'types>)],
+) -> PartialAnalysis<'types, 'arena> {
+    let context = AnalysisContext {
+        globals: GlobalScope::new(type_manager, arena, globals),
+        variables,
+        constraints: &[],
+    };
+    analyze_partial_with_global_scope(
+        type_manager,
+        arena,
+        expr,
+        context,
+        AnalyzerOptions::default(),
+    )
+}
+
+/// Like [`analyze_partial`], with globals already prepared by
+/// [`GlobalScope::new`] and the given options.
+pub fn analyze_partial_with_global_scope<'types, 'arena>(
+    type_manager: &'types TypeManager<'types>,
+    arena: &'arena Bump,
+    expr: &'arena parser::ParsedExpr<'arena>,
+    context: AnalysisContext<'types, 'arena, '_>,
+    options: AnalyzerOptions,
+) -> PartialAnalysis<'types, 'arena> {
+    let mut analyzer = Analyzer::new(type_manager, arena, expr, context, options);
+    analyzer.node_types = Some(Vec::new());
+    analyzer.recovered_errors = Some(Vec::new());
+    let result = analyzer.run(expr, &mut |_| {}).map_err(first_error);
+    let node_types = analyzer
+        .node_types
+        .take()
+        .unwrap_or_default()
+        .into_iter()
+        .map(|(span, ty)| (span, analyzer.unification.fully_resolve(ty)))
+        .collect();
+    PartialAnalysis { result, node_types }
 }
 
 struct Analyzer<'types, 'arena> {
+    options: AnalyzerOptions,
     type_manager: &'types TypeManager<'types>,
     arena: &'arena Bump,
     scope_stack: ScopeStack<'arena, TypeScheme<'types, 'arena>>,
     unification: Unification<'types, &'types TypeManager<'types>>,
     type_class_resolver: TypeClassResolver<'types>,
     parsed_ann: &'arena parser::AnnotatedSource<'arena, parser::Expr<'arena>>,
+    pattern_ann: &'arena parser::AnnotatedSource<'arena, parser::Pattern<'arena>>,
     typed_ann: &'arena parser::AnnotatedSource<'arena, Expr<'types, 'arena>>,
     current_span: Option<Span>, // Track current expression span
     /// Stack of environment type variables from outer scopes.
@@ -138,19 +278,174 @@ struct Analyzer<'types, 'arena> {
     /// These will be resolved to concrete types after finalize_constraints
     pending_instantiations:
         hashbrown::HashMap<*const Expr<'types, 'arena>, Vec<hashbrown::HashMap<u16, u16>>>,
+    /// Quantified type variables of every generalized where binding.
+    generalized_vars: Vec<u16>,
+    /// Quantified type variables of the bindings used so far, each use
+    /// having instantiated them.
+    instantiated_vars: hashbrown::HashSet<u16>,
+    /// Span and type of every typed node, kept even if analysis later fails.
+    /// Only recorded for [`analyze_partial`], which is the only reader.
+    node_types: Option<Vec<(Span, &'types Type<'types>)>>,
+    /// Type aliases declared by enclosing where blocks, innermost last.
+    type_aliases: Vec<(&'arena str, &'types Type<'types>)>,
+    /// Warnings found so far, reported when the analysis finishes.
+    warnings: Vec<TypeError>,
+    /// Errors of the sub-expressions analysis went on past. Only recorded
+    /// for [`analyze_partial`]; other analyses stop at the first error.
+    recovered_errors: Option<Vec<TypeError>>,
 }
 
 impl<'types, 'arena> Analyzer<'types, 'arena> {
+    fn new(
+        type_manager: &'types TypeManager<'types>,
+        arena: &'arena Bump,
+        expr: &'arena parser::ParsedExpr<'arena>,
+        context: AnalysisContext<'types, 'arena, '_>,
+        options: AnalyzerOptions,
+    ) -> Self {
+        let AnalysisContext {
+            globals,
+            variables,
+            constraints,
+        } = context;
+        tracing::info!(
+            globals_count = globals.len(),
+            variables_count = variables.len(),
+            "Starting type analysis"
+        );
+
+        // Create annotation map for typed expressions
+        // We reuse the same source string since both ParsedExpr and TypedExpr are in the same arena
+        let typed_ann = arena.alloc(parser::AnnotatedSource::new(arena, expr.ann.source));
+
+        let mut analyzer = Analyzer {
+            options,
+            type_manager,
+            arena,
+            scope_stack: ScopeStack::new(),
+            unification: Unification::new(type_manager),
+            type_class_resolver: TypeClassResolver::new(),
+            parsed_ann: expr.ann,
+            pattern_ann: expr.pattern_ann,
+            typed_ann,
+            current_span: None, // Initialize to None
+            env_vars_stack: Vec::new(),
+            polymorphic_lambdas: hashbrown::HashMap::new(),
+            pending_instantiations: hashbrown::HashMap::new(),
+            generalized_vars: Vec::new(),
+            instantiated_vars: hashbrown::HashSet::new(),
+            node_types: None,
+            type_aliases: Vec::new(),
+            warnings: Vec::new(),
+            recovered_errors: None,
+        };
+
+        // Push globals scope (constants, packages, functions)
+        if !globals.is_empty() {
+            analyzer
+                .scope_stack
+                .push(scope_stack::CompleteScope::from_sorted(globals.bindings));
+        }
+
+        // Push variables scope (client-provided runtime variables)
+        if !variables.is_empty() {
+            // Generalize each type: nothing in scope shares its type variables
+            let env_vars = hashbrown::HashSet::new();
+            let bindings: Vec<(&'arena str, TypeScheme<'types, 'arena>)> = variables
+                .iter()
+                .map(|(name, ty)| {
+                    let scheme = analyzer.unification.generalize(ty, &env_vars);
+                    analyzer
+                        .generalized_vars
+                        .extend_from_slice(scheme.quantified);
+                    (*name, scheme)
+                })
+                .collect();
+            let bindings_slice = arena.alloc_slice_fill_iter(bindings.into_iter());
+            analyzer
+                .scope_stack
+                .push(scope_stack::CompleteScope::from_sorted(bindings_slice));
+        }
+        analyzer.type_class_resolver.add_constraints(constraints);
+        analyzer
+    }
+
+    /// Analyzes the whole expression and resolves all inferred types.
+    ///
+    /// Errors are passed to `on_error` as they are found. Analysis of the
+    /// expression itself stops at the first error, unless it recovers from
+    /// errors for [`analyze_partial`], while type class constraints are all
+    /// checked, so a failed run can report several.
+    fn run(
+        &mut self,
+        expr: &'arena parser::ParsedExpr<'arena>,
+        on_error: &mut dyn FnMut(&TypeError),
+    ) -> Result<&'arena TypedExpr<'types, 'arena>, Vec<TypeError>> {
+        let result = self.analyze_expr(expr);
+        for warning in core::mem::take(&mut self.warnings) {
+            on_error(&warning);
+        }
+        let result = result.map_err(|err| {
+            on_error(&err);
+            vec![err]
+        })?;
+        let recovered = self.recovered_errors.take().unwrap_or_default();
+        if !recovered.is_empty() {
+            for err in &recovered {
+                on_error(err);
+            }
+            return Err(recovered);
+        }
+
+        // Check all type class constraints after unification
+        self.finalize_constraints(on_error)?;
+
+        // Resolve all type variables in the expression tree
+        // This replaces type variables with their fully resolved types (e.g., _5 → Str)
+        // Type variables that aren't unified (e.g., generalized lambda body vars) remain unchanged
+        // We also track old→new pointer mappings to remap lambda_instantiations keys
+        let mut ptr_remap = hashbrown::HashMap::new();
+        let resolved_expr = self.resolve_expr_types(result.expr, &mut ptr_remap);
+
+        // Build final instantiation substitutions by resolving fresh vars to concrete types
+        let old_lambda_instantiations = self.build_lambda_instantiations(self.arena);
+
+        // Remap the lambda_instantiations keys from old pointers to new pointers
+        // This is necessary because resolve_expr_types allocates new Expr nodes
+        let lambda_instantiations =
+            Self::remap_lambda_instantiations(old_lambda_instantiations, &ptr_remap, self.arena);
+
+        // Constraints a polymorphic result still carries. Spans point into
+        // this source only, and are dropped, which also keeps the arena
+        // from holding allocations it would never free.
+        let constraints = self
+            .type_class_resolver
+            .constraints_on(resolved_expr.0, &self.unification);
+
+        // Create new TypedExpr with resolved expression and remapped instantiation info
+        let resolved_result = self.arena.alloc(TypedExpr {
+            expr: resolved_expr,
+            ann: result.ann,
+            lambda_instantiations,
+            constraints: self.arena.alloc_slice_fill_iter(constraints),
+        });
+
+        Ok(resolved_result)
+    }
+
     fn analyze_expr(
         &mut self,
         expr: &parser::ParsedExpr<'arena>,
     ) -> Result<&'arena mut TypedExpr<'types, 'arena>, TypeError> {
+        // Declared aliases stay in scope for the whole expression
+        self.declare_type_aliases(expr.type_aliases)?;
         let typed_expr = self.analyze(&expr.expr)?;
         // This is used internally, instantiations will be added at the top level
         Ok(self.arena.alloc(TypedExpr {
             expr: typed_expr,
             ann: self.typed_ann,
             lambda_instantiations: hashbrown::HashMap::new_in(self.arena),
+            constraints: &[],
         }))
     }
 
@@ -165,6 +460,9 @@ impl<'types, 'arena> Analyzer<'types, 'arena> {
         // Copy span from current_span to typed annotation
         if let Some(ref span) = self.current_span {
             self.typed_ann.add_span(typed_expr, span.clone());
+            if let Some(node_types) = &mut self.node_types {
+                node_types.push((span.clone(), typed_expr.0));
+            }
         }
         typed_expr
     }
@@ -248,19 +546,76 @@ impl<'types, 'arena> Analyzer<'types, 'arena> {
         })
     }
 
-    // Finalize type checking by resolving all type class constraints
-    fn finalize_constraints(&mut self) -> Result<(), TypeError> {
+    // Finalize type checking by resolving all type class constraints,
+    // reporting each unsatisfied one as it is found, then every indexing
+    // and cast whose operand type could not be inferred
+    fn finalize_constraints(
+        &mut self,
+        on_error: &mut dyn FnMut(&TypeError),
+    ) -> Result<(), Vec<TypeError>> {
+        let source = self.get_source();
+        let mut reported = Vec::new();
+        let mut errors = Vec::new();
         self.type_class_resolver
-            .resolve_all(&mut self.unification)
-            .map_err(|errors| {
-                // For now, just report the first error
-                // In the future, we can report all errors
-                if let Some(first_error) = errors.first() {
-                    TypeError::from_constraint_error(first_error.clone(), self.get_source())
-                } else {
-                    self.internal_error("Type class constraint error".to_string())
+            .resolve_each(&mut self.unification, |err| {
+                // Both operands of `a < b` carry the same constraint, so the
+                // same failure can come up more than once
+                if reported.contains(&err) {
+                    return;
                 }
-            })
+                reported.push(err.clone());
+                let err = TypeError::from_constraint_error(err, source.clone());
+                on_error(&err);
+                errors.push(err);
+            });
+        for err in self
+            .type_class_resolver
+            .settle_numeric_promotions(&self.generalized_vars, &mut self.unification)
+        {
+            let err = TypeError::from_constraint_error(err, source.clone());
+            on_error(&err);
+            errors.push(err);
+        }
+        for err in self
+            .type_class_resolver
+            .ambiguous_containers(&self.generalized_vars, &self.unification)
+        {
+            let err = TypeError::from_constraint_error(err, source.clone());
+            on_error(&err);
+            errors.push(err);
+        }
+        // The casts of a binding that is never used are never determined by
+        // an instantiation, so they count as undetermined too
+        let instantiated_vars: Vec<u16> = self
+            .generalized_vars
+            .iter()
+            .copied()
+            .filter(|var| self.instantiated_vars.contains(var))
+            .collect();
+        for (target_type, spans) in self
+            .type_class_resolver
+            .undetermined_casts(&instantiated_vars, &self.unification)
+        {
+            let mut err = TypeError::new(
+                TypeErrorKind::PolymorphicCast {
+                    target_type: format!("{}", target_type),
+                },
+                source.clone(),
+                spans[0].clone(),
+            );
+            for span in &spans[1..] {
+                err.context.push(crate::diagnostics::context::Context::InstantiatedHere {
+                    span: span.clone(),
+                });
+            }
+            on_error(&err);
+            errors.push(err);
+        }
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
     }
 
     /// Get the current environment type variables (union of all sets in the stack).
@@ -280,6 +635,11 @@ impl<'types, 'arena> Analyzer<'types, 'arena> {
         // Set current span for this expression from parsed annotations
         let old_span = self.current_span.clone();
         self.current_span = self.parsed_ann.span_of(expr);
+        let depths = (
+            self.scope_stack.depth(),
+            self.env_vars_stack.len(),
+            self.type_aliases.len(),
+        );
 
         let result = match expr {
             parser::Expr::Binary { op, left, right } => self.analyze_binary(*op, left, right),
@@ -290,6 +650,7 @@ impl<'types, 'arena> Analyzer<'types, 'arena> {
             parser::Expr::Unary { op, expr } => self.analyze_unary(*op, expr),
             parser::Expr::Call { callable, args } => self.analyze_call(callable, args),
             parser::Expr::Index { value, index } => self.analyze_index(value, index),
+            parser::Expr::Slice { value, start, end } => self.analyze_slice(value, *start, *end),
             parser::Expr::Field { value, field } => self.analyze_field(value, *field),
             parser::Expr::Cast { ty, expr } => self.analyze_cast(ty, expr),
             parser::Expr::Lambda { params, body } => self.analyze_lambda(params, body),
@@ -298,10 +659,15 @@ impl<'types, 'arena> Analyzer<'types, 'arena> {
                 then_branch,
                 else_branch,
             } => self.analyze_if(cond, then_branch, else_branch),
-            parser::Expr::Where { expr, bindings } => self.analyze_where(expr, bindings),
+            parser::Expr::Where {
+                expr,
+                bindings,
+                type_aliases,
+            } => self.analyze_where(expr, bindings, type_aliases),
             parser::Expr::Otherwise { primary, fallback } => {
                 self.analyze_otherwise(primary, fallback)
             }
+            parser::Expr::Coalesce { option, default } => self.analyze_coalesce(option, default),
             parser::Expr::Option { inner } => self.analyze_option(*inner),
             parser::Expr::Match { expr, arms } => self.analyze_match(expr, arms),
             parser::Expr::Record(items) => self.analyze_record(items),
@@ -311,6 +677,20 @@ impl<'types, 'arena> Analyzer<'types, 'arena> {
             parser::Expr::Literal(literal) => self.analyze_literal(literal),
             parser::Expr::Ident(ident) => self.analyze_ident(*ident),
         };
+        let result = match (result, &mut self.recovered_errors) {
+            (Err(err), Some(recovered)) => {
+                // Go on with a type that unifies with anything, dropping
+                // the scopes the failed analysis left open
+                recovered.push(err);
+                let (scopes, env_vars, type_aliases) = depths;
+                self.scope_stack.truncate(scopes);
+                self.env_vars_stack.truncate(env_vars);
+                self.type_aliases.truncate(type_aliases);
+                let ty = self.type_manager.fresh_type_var();
+                Ok(self.arena.alloc(Expr(ty, ExprInner::Ident(""))))
+            }
+            (result, _) => result,
+        };
 
         // Restore previous span
         self.current_span = old_span;
@@ -324,12 +704,32 @@ impl<'types, 'arena> Analyzer<'types, 'arena> {
         left: &'arena parser::Expr<'arena>,
         right: &'arena parser::Expr<'arena>,
     ) -> Result<&'arena mut Expr<'types, 'arena>, TypeError> {
-        let left = self.analyze(left)?;
-        let right = self.analyze(right)?;
+        let mut left = self.analyze(left)?;
+        let mut right = self.analyze(right)?;
+
+        if op == BinaryOp::Div && self.options.float_division {
+            left = self.promote_to_float(left);
+            right = self.promote_to_float(right);
+        }
 
         // Unify left and right to determine result type - point to right if mismatch
         let result_ty = self.expect_types_match(right, right.0, left.0)?;
 
+        if op.is_bitwise() {
+            // Bitwise operators take and return the same type, which must be Int
+            self.type_class_resolver
+                .add_bitwise_constraint(result_ty, self.get_span());
+            return Ok(self.alloc(
+                result_ty,
+                ExprInner::Binary {
+                    op,
+                    left,
+                    right,
+                    division: self.options.integer_division,
+                },
+            ));
+        }
+
         // Add relational Numeric constraint: Numeric(left, right, result)
         // The constraint resolver will verify and unify based on the numeric instance:
         //   - (Int, Int) => Int
@@ -341,7 +741,126 @@ impl<'types, 'arena> Analyzer<'types, 'arena> {
             self.get_span(),
         );
 
-        Ok(self.alloc(result_ty, ExprInner::Binary { op, left, right }))
+        Ok(self.alloc(
+            result_ty,
+            ExprInner::Binary {
+                op,
+                left,
+                right,
+                division: self.options.integer_division,
+            },
+        ))
+    }
+
+    /// Converts an operand of float division to `Float`.
+    ///
+    /// An operand whose type is not known yet is constrained to be numeric and
+    /// cast to `Float` once unification resolves it, so `/` always produces a
+    /// `Float`. An `Int` operand is cast right away, and operands of any other
+    /// type are left for the `Numeric` constraint to report.
+    fn promote_to_float(
+        &mut self,
+        operand: &'arena mut Expr<'types, 'arena>,
+    ) -> &'arena mut Expr<'types, 'arena> {
+        let operand_ty = self.unification.fully_resolve(operand.0);
+        if !matches!(operand_ty, Type::TypeVar(_)) {
+            return self.promote_int_to_float(operand);
+        }
+        // Attribute the constraints and the implicit cast to the operand.
+        let old_span = self.current_span.clone();
+        self.current_span = self.typed_ann.span_of(operand);
+        let span = self.get_span();
+        self.type_class_resolver.add_numeric_constraint(
+            operand_ty,
+            operand_ty,
+            operand_ty,
+            span.clone(),
+        );
+        self.type_class_resolver.add_castable_constraint(
+            operand_ty,
+            self.type_manager.float(),
+            span,
+        );
+        let cast = self.alloc(self.type_manager.float(), ExprInner::Cast { expr: operand });
+        self.current_span = old_span;
+        cast
+    }
+
+    /// Wraps an `Int` operand in a cast to `Float`, for float division and
+    /// mixed numeric comparisons.
+    ///
+    /// Operands whose type is not yet known to be `Int` are left unchanged.
+    fn promote_int_to_float(
+        &mut self,
+        operand: &'arena mut Expr<'types, 'arena>,
+    ) -> &'arena mut Expr<'types, 'arena> {
+        if !matches!(self.unification.fully_resolve(operand.0), Type::Int) {
+            return operand;
+        }
+        // Attribute the implicit cast to the operand it converts.
+        let old_span = self.current_span.clone();
+        self.current_span = self.typed_ann.span_of(operand);
+        let cast = self.alloc(self.type_manager.float(), ExprInner::Cast { expr: operand });
+        self.current_span = old_span;
+        cast
+    }
+
+    /// Converts the `Int` operand to `Float` when comparing an `Int` with a
+    /// `Float`.
+    ///
+    /// When an operand is a type variable that may still turn out to be the
+    /// other numeric type, both operands are cast to a common type decided by
+    /// a `NumericPromotion` constraint once unification resolves them.
+    fn promote_mixed_numeric_operands(
+        &mut self,
+        left: &'arena mut Expr<'types, 'arena>,
+        right: &'arena mut Expr<'types, 'arena>,
+    ) -> (
+        &'arena mut Expr<'types, 'arena>,
+        &'arena mut Expr<'types, 'arena>,
+    ) {
+        let left_ty = self.unification.fully_resolve(left.0);
+        let right_ty = self.unification.fully_resolve(right.0);
+        match (left_ty, right_ty) {
+            (Type::Int, Type::Float) => (self.promote_int_to_float(left), right),
+            (Type::Float, Type::Int) => (left, self.promote_int_to_float(right)),
+            (Type::TypeVar(a), Type::TypeVar(b)) if a != b => {
+                self.promote_to_common_type(left, right)
+            }
+            (Type::TypeVar(_), Type::Int | Type::Float)
+            | (Type::Int | Type::Float, Type::TypeVar(_)) => {
+                self.promote_to_common_type(left, right)
+            }
+            _ => (left, right),
+        }
+    }
+
+    /// Casts both operands of a mixed numeric comparison to the type they are
+    /// compared as, which is only known after unification.
+    fn promote_to_common_type(
+        &mut self,
+        left: &'arena mut Expr<'types, 'arena>,
+        right: &'arena mut Expr<'types, 'arena>,
+    ) -> (
+        &'arena mut Expr<'types, 'arena>,
+        &'arena mut Expr<'types, 'arena>,
+    ) {
+        let promoted = self.type_manager.fresh_type_var();
+        self.type_class_resolver.add_numeric_promotion_constraint(
+            left.0,
+            right.0,
+            promoted,
+            self.get_span(),
+        );
+
+        // Attribute each implicit cast to the operand it converts.
+        let old_span = self.current_span.clone();
+        self.current_span = self.typed_ann.span_of(left);
+        let left = self.alloc(promoted, ExprInner::Cast { expr: left });
+        self.current_span = self.typed_ann.span_of(right);
+        let right = self.alloc(promoted, ExprInner::Cast { expr: right });
+        self.current_span = old_span;
+        (left, right)
     }
 
     fn analyze_boolean(
@@ -378,8 +897,14 @@ impl<'types, 'arena> Analyzer<'types, 'arena> {
         left: &'arena parser::Expr<'arena>,
         right: &'arena parser::Expr<'arena>,
     ) -> Result<&'arena mut Expr<'types, 'arena>, TypeError> {
-        let left = self.analyze(left)?;
-        let right = self.analyze(right)?;
+        let mut left = self.analyze(left)?;
+        let mut right = self.analyze(right)?;
+
+        if self.options.mixed_numeric_comparison
+            && !matches!(op, ComparisonOp::In | ComparisonOp::NotIn)
+        {
+            (left, right) = self.promote_mixed_numeric_operands(left, right);
+        }
 
         // For equality operators (== and !=), any types can be compared
         // For ordering operators (<, >, <=, >=), operands must support Ord (Int, Float, Str, Bytes)
@@ -461,14 +986,43 @@ impl<'types, 'arena> Analyzer<'types, 'arena> {
         callable: &'arena parser::Expr<'arena>,
         args: &'arena [&'arena parser::Expr<'arena>],
     ) -> Result<&'arena mut Expr<'types, 'arena>, TypeError> {
+        // `ToOption` is a builtin, unless a binding of that name shadows it.
+        if matches!(callable, parser::Expr::Ident("ToOption"))
+            && self.scope_stack.lookup("ToOption").is_none()
+        {
+            return self.analyze_to_option(args);
+        }
+
         // 1. Analyze callable and its arguments.
         let callable = self.analyze(callable)?;
         let args_typed = self
             .arena
             .alloc_slice_try_fill_iter(args.iter().map(|arg| self.analyze(arg)))?;
 
-        // 2. Extract actual argument types.
-        let arg_types: Vec<_> = args_typed.iter().map(|arg| arg.0).collect();
+        // 2. Extract actual argument types, taking the declared parameter type
+        // instead for arguments the coercion policy converts at the call.
+        let coercion = self.options.argument_coercion;
+        let declared_params: Vec<_> = match self.unification.fully_resolve(callable.0).view() {
+            TypeKind::Function { params, .. } if params.len() == args_typed.len() => {
+                params.collect()
+            }
+            _ => Vec::new(),
+        };
+        let arg_types: Vec<_> = args_typed
+            .iter()
+            .enumerate()
+            .map(|(index, arg)| match declared_params.get(index) {
+                Some(&param)
+                    if coercion.allows(
+                        self.unification.fully_resolve(arg.0),
+                        self.unification.fully_resolve(param),
+                    ) =>
+                {
+                    param
+                }
+                _ => arg.0,
+            })
+            .collect();
 
         // 3. Create a fresh type variable for the return type.
         let ret_ty = self.type_manager.fresh_type_var();
@@ -500,7 +1054,27 @@ impl<'types, 'arena> Analyzer<'types, 'arena> {
         // 7. Resolve the return type through substitution
         let resolved_ret_ty = self.unification.resolve(result_ty);
 
-        // 8. Create the typed Call expression
+        // 8. Maps returned from calls must have hashable keys, just like map literals.
+        // Native function types cannot declare constraints, so check at the call site.
+        if let TypeKind::Map(key_ty, _) = resolved_ret_ty.view() {
+            let span = self.get_span();
+            self.type_class_resolver
+                .add_hashable_constraint(key_ty, span);
+        }
+
+        // 9. `Array.Sort` orders elements naturally, so they must support Ord.
+        if Self::is_package_function(callable, "Array", "Sort")
+            && let TypeKind::Array(element_ty) = resolved_ret_ty.view()
+        {
+            let span = self.get_span();
+            self.type_class_resolver
+                .add_ord_constraint(element_ty, span);
+        }
+
+        // 10. Check literal templates against the record they are filled from.
+        self.check_template_call(callable, args_typed)?;
+
+        // 11. Create the typed Call expression
         Ok(self.alloc(
             resolved_ret_ty,
             ExprInner::Call {
@@ -508,10 +1082,125 @@ impl<'types, 'arena> Analyzer<'types, 'arena> {
                 args: self
                     .arena
                     .alloc_slice_fill_iter(args_typed.into_iter().map(|arg| &**arg)),
+                coercion,
             },
         ))
     }
 
+    /// Analyzes the builtin `ToOption(cond, value)` as
+    /// `if cond then some value else none`, so `value` is only evaluated when
+    /// `cond` holds.
+    fn analyze_to_option(
+        &mut self,
+        args: &'arena [&'arena parser::Expr<'arena>],
+    ) -> Result<&'arena mut Expr<'types, 'arena>, TypeError> {
+        let [cond, value] = args else {
+            return self.error(TypeErrorKind::FunctionParamCountMismatch {
+                expected: 2,
+                found: args.len(),
+            });
+        };
+        let cond = self.analyze(cond)?;
+        self.expect_type_to_be(
+            cond,
+            cond.0,
+            self.type_manager.bool(),
+            "Condition of 'ToOption' must be Bool",
+        )?;
+        let value = self.analyze(value)?;
+
+        let result_ty = self.type_manager.option(value.0);
+        let then_branch = self.alloc(result_ty, ExprInner::Option { inner: Some(value) });
+        let else_branch = self.alloc(result_ty, ExprInner::Option { inner: None });
+        Ok(self.alloc(
+            result_ty,
+            ExprInner::If {
+                cond,
+                then_branch,
+                else_branch,
+            },
+        ))
+    }
+
+    /// Whether `callable` is `package.function`, as in `Array.Sort`.
+    fn is_package_function(callable: &Expr<'types, 'arena>, package: &str, function: &str) -> bool {
+        matches!(
+            &callable.1,
+            ExprInner::Field { value, field } if *field == function
+                && matches!(value.1, ExprInner::Ident(name) if name == package)
+        )
+    }
+
+    /// Validates a `String.Template(template, values)` call whose template is a
+    /// string literal: every placeholder must name a field of the record.
+    ///
+    /// Templates that are not literals are only checked at runtime.
+    fn check_template_call(
+        &self,
+        callable: &Expr<'types, 'arena>,
+        args: &[&'arena mut Expr<'types, 'arena>],
+    ) -> Result<(), TypeError> {
+        let ExprInner::Field {
+            value: package,
+            field: "Template",
+        } = &callable.1
+        else {
+            return Ok(());
+        };
+        let (ExprInner::Ident("String"), [template, values]) = (&package.1, args) else {
+            return Ok(());
+        };
+        let ExprInner::Constant(template_value) = &template.1 else {
+            return Ok(());
+        };
+        let Ok(template_str) = template_value.as_str() else {
+            return Ok(());
+        };
+
+        let span = self
+            .typed_ann
+            .span_of(&**template)
+            .unwrap_or_else(|| self.get_span());
+        let error = |kind| TypeError::new(kind, self.get_source(), span.clone());
+
+        let segments = crate::stdlib::string::parse_template(template_str).map_err(|err| {
+            error(TypeErrorKind::Other {
+                message: format!("Invalid template: {}", err),
+            })
+        })?;
+
+        let values_ty = self.unification.fully_resolve(values.0);
+        let available_fields: Vec<String> = match values_ty.view() {
+            TypeKind::Record(fields) => fields.map(|(name, _)| name.to_string()).collect(),
+            TypeKind::TypeVar(_) => return Ok(()),
+            _ => {
+                return Err(TypeError::new(
+                    TypeErrorKind::TypeMismatch {
+                        expected: "a record".to_string(),
+                        found: format!("{}", values_ty),
+                        context: Some("String.Template values must be a record".to_string()),
+                    },
+                    self.get_source(),
+                    self.typed_ann
+                        .span_of(&**values)
+                        .unwrap_or_else(|| self.get_span()),
+                ));
+            }
+        };
+
+        for segment in segments {
+            if let crate::stdlib::string::TemplateSegment::Field(name) = segment
+                && !available_fields.iter().any(|field| field == name)
+            {
+                return Err(error(TypeErrorKind::UnknownField {
+                    field: name.to_string(),
+                    available_fields,
+                }));
+            }
+        }
+        Ok(())
+    }
+
     fn analyze_index(
         &mut self,
         value: &'arena parser::Expr<'arena>,
@@ -547,6 +1236,16 @@ impl<'types, 'arena> Analyzer<'types, 'arena> {
                 )?;
                 self.type_manager.int()
             }
+            TypeKind::Str => {
+                // Strings are indexed by integers (counting characters), return Str
+                self.expect_type_to_be(
+                    index,
+                    index.0,
+                    self.type_manager.int(),
+                    "String index must be Int",
+                )?;
+                self.type_manager.str()
+            }
             TypeKind::TypeVar(_) => {
                 // Type variable not yet resolved - add relational Indexable constraint
                 // The constraint tracks: Indexable(container, index, result)
@@ -554,6 +1253,7 @@ impl<'types, 'arena> Analyzer<'types, 'arena> {
                 //   - Array[E]: index=Int, result=E
                 //   - Map[K,V]: index=K, result=V
                 //   - Bytes: index=Int, result=Int
+                //   - Str: index=Int, result=Str
 
                 let result_ty = self.type_manager.fresh_type_var();
 
@@ -577,6 +1277,49 @@ impl<'types, 'arena> Analyzer<'types, 'arena> {
         Ok(self.alloc(result_ty, ExprInner::Index { value, index }))
     }
 
+    fn analyze_slice(
+        &mut self,
+        value: &'arena parser::Expr<'arena>,
+        start: Option<&'arena parser::Expr<'arena>>,
+        end: Option<&'arena parser::Expr<'arena>>,
+    ) -> Result<&'arena mut Expr<'types, 'arena>, TypeError> {
+        let value = self.analyze(value)?;
+        let mut analyze_bound = |bound: Option<&'arena parser::Expr<'arena>>| {
+            let Some(bound) = bound else {
+                return Ok(None);
+            };
+            let bound = self.analyze(bound)?;
+            self.expect_type_to_be(
+                bound,
+                bound.0,
+                self.type_manager.int(),
+                "Slice bound must be Int",
+            )?;
+            Ok(Some(&*bound))
+        };
+        let start = analyze_bound(start)?;
+        let end = analyze_bound(end)?;
+
+        // Slicing yields a value of the same type as the sliced one
+        let result_ty = match value.0.view() {
+            TypeKind::Array(_) | TypeKind::Str => value.0,
+            TypeKind::TypeVar(_) => {
+                // Strings must be known to be strings; anything else being
+                // sliced is taken to be an array.
+                let array_ty = self.type_manager.array(self.type_manager.fresh_type_var());
+                self.expect_types_match(value, value.0, array_ty)?;
+                array_ty
+            }
+            _ => {
+                return self.error(TypeErrorKind::NotSliceable {
+                    ty: format!("{}", value.0),
+                });
+            }
+        };
+
+        Ok(self.alloc(result_ty, ExprInner::Slice { value, start, end }))
+    }
+
     fn analyze_field(
         &mut self,
         value: &'arena parser::Expr<'arena>,
@@ -606,12 +1349,17 @@ impl<'types, 'arena> Analyzer<'types, 'arena> {
                     })?
             }
             TypeKind::TypeVar(_) => {
-                // Cannot infer record type from field access alone
-                // TODO(row-polymorphism): With row polymorphism, we could infer
-                // "any record with at least field 'x' of some type"
-                return self.error(TypeErrorKind::CannotInferRecordType {
-                    field: field.to_string(),
-                });
+                // Type variable not yet resolved - add relational HasField constraint
+                // The value can be any record with at least this field; once the
+                // record type is known, the result is unified with the field's type.
+                let result_ty = self.type_manager.fresh_type_var();
+                self.type_class_resolver.add_has_field_constraint(
+                    value.0,
+                    self.type_manager.intern_str(field),
+                    result_ty,
+                    self.get_span(),
+                );
+                result_ty
             }
             _ => {
                 return self.error(TypeErrorKind::NotARecord {
@@ -631,7 +1379,7 @@ impl<'types, 'arena> Analyzer<'types, 'arena> {
     ) -> Result<&'arena mut Expr<'types, 'arena>, TypeError> {
         let analyzed_expr = self.analyze(expr)?;
         let source_type = analyzed_expr.0;
-        let target_type = match type_expr_to_type(self.type_manager, ty_expr) {
+        let target_type = match self.resolve_type_expr(ty_expr) {
             Ok(ty) => ty,
             Err(e) => {
                 return self.error(TypeErrorKind::InvalidTypeExpression {
@@ -640,22 +1388,20 @@ impl<'types, 'arena> Analyzer<'types, 'arena> {
             }
         };
 
-        // Check if source type is a type variable (polymorphic)
+        // A polymorphic source type is only known after unification: add a
+        // relational Castable constraint, checked once the source is resolved
         if matches!(source_type.view(), TypeKind::TypeVar(_)) {
-            let mut err = self.type_error(TypeErrorKind::PolymorphicCast {
-                target_type: format!("{}", target_type),
-            });
-
-            // Add context pointing to the expression with polymorphic type
-            if let Some(expr_span) = self.typed_ann.span_of(analyzed_expr) {
-                err.context
-                    .push(crate::diagnostics::context::Context::InferredHere {
-                        type_name: format!("{}", source_type),
-                        span: expr_span,
-                    });
-            }
-
-            return Err(err);
+            self.type_class_resolver.add_castable_constraint(
+                source_type,
+                target_type,
+                self.get_span(),
+            );
+            return Ok(self.alloc(
+                target_type,
+                ExprInner::Cast {
+                    expr: analyzed_expr,
+                },
+            ));
         }
 
         // Validate the cast using casting library
@@ -719,6 +1465,10 @@ impl<'types, 'arena> Analyzer<'types, 'arena> {
         }
         self.env_vars_stack.push(param_env_vars);
 
+        if self.options.warn_unused_parameters {
+            self.check_unused_parameters(params, body);
+        }
+
         let body = self.analyze(body)?;
 
         // Pop environment variables
@@ -755,6 +1505,31 @@ impl<'types, 'arena> Analyzer<'types, 'arena> {
         ))
     }
 
+    /// Record a warning for each parameter that `body` never references.
+    fn check_unused_parameters(
+        &mut self,
+        params: &'arena [&'arena str],
+        body: &'arena parser::Expr<'arena>,
+    ) {
+        let used = free_variables(body);
+        for param in params {
+            if param.starts_with('_') || used.contains(param) {
+                continue;
+            }
+            let span = self
+                .parsed_ann
+                .span_of_str(param)
+                .unwrap_or_else(|| self.get_span());
+            self.warnings.push(TypeError::new(
+                TypeErrorKind::UnusedParameter {
+                    name: param.to_string(),
+                },
+                self.get_source(),
+                span,
+            ));
+        }
+    }
+
     fn analyze_if(
         &mut self,
         cond: &'arena parser::Expr<'arena>,
@@ -786,11 +1561,68 @@ impl<'types, 'arena> Analyzer<'types, 'arena> {
         ))
     }
 
+    /// Converts a type expression, resolving names through the aliases in scope.
+    fn resolve_type_expr(
+        &self,
+        ty_expr: &parser::TypeExpr<'_>,
+    ) -> Result<&'types Type<'types>, TypeConversionError> {
+        let aliases = &self.type_aliases;
+        type_expr_to_type_with_aliases(self.type_manager, ty_expr, &|name| {
+            aliases
+                .iter()
+                .rev()
+                .find(|(alias, _)| *alias == name)
+                .map(|(_, ty)| *ty)
+        })
+    }
+
+    /// Brings a group of type aliases into scope, on top of the ones already
+    /// in scope.
+    ///
+    /// Aliases are resolved in order, so each one can use the previous ones.
+    /// The caller is responsible for truncating `type_aliases` when the group
+    /// goes out of scope.
+    fn declare_type_aliases(
+        &mut self,
+        type_aliases: &'arena [(&'arena str, parser::TypeExpr<'arena>)],
+    ) -> Result<(), TypeError> {
+        let group_depth = self.type_aliases.len();
+        for (name, ty_expr) in type_aliases.iter() {
+            if self.type_aliases[group_depth..]
+                .iter()
+                .any(|(alias, _)| alias == name)
+            {
+                return self.error(TypeErrorKind::DuplicateBinding {
+                    name: name.to_string(),
+                });
+            }
+            let ty = match self.resolve_type_expr(ty_expr) {
+                Ok(ty) => ty,
+                Err(_) if is_recursive_alias(name, type_aliases) => {
+                    return self.error(TypeErrorKind::RecursiveTypeAlias {
+                        name: name.to_string(),
+                    });
+                }
+                Err(e) => {
+                    return self.error(TypeErrorKind::InvalidTypeExpression {
+                        message: e.to_string(),
+                    });
+                }
+            };
+            self.type_aliases.push((*name, ty));
+        }
+        Ok(())
+    }
+
     fn analyze_where(
         &mut self,
         expr: &'arena parser::Expr<'arena>,
         bindings: &'arena [(&'arena str, &'arena parser::Expr<'arena>)],
+        type_aliases: &'arena [(&'arena str, parser::TypeExpr<'arena>)],
     ) -> Result<&'arena mut Expr<'types, 'arena>, TypeError> {
+        let alias_depth = self.type_aliases.len();
+        self.declare_type_aliases(type_aliases)?;
+
         // Extract binding names
         let names: Vec<&'arena str> = bindings.iter().map(|(name, _)| *name).collect();
 
@@ -813,6 +1645,7 @@ impl<'types, 'arena> Analyzer<'types, 'arena> {
             // Use current environment variables to prevent generalizing over lambda parameters
             let env_vars = self.get_env_vars();
             let mut scheme = self.unification.generalize(analyzed.0, &env_vars);
+            self.generalized_vars.extend_from_slice(scheme.quantified);
 
             // Track polymorphic lambdas for instantiation tracking
             // Store the lambda pointer directly in the TypeScheme
@@ -833,6 +1666,7 @@ impl<'types, 'arena> Analyzer<'types, 'arena> {
         self.scope_stack
             .pop()
             .map_err(|e| self.internal_error(format!("Failed to pop scope: {:?}", e)))?;
+        self.type_aliases.truncate(alias_depth);
 
         Ok(self.alloc(
             expr_typed.0,
@@ -859,6 +1693,27 @@ impl<'types, 'arena> Analyzer<'types, 'arena> {
         Ok(self.alloc(result_ty, ExprInner::Otherwise { primary, fallback }))
     }
 
+    fn analyze_coalesce(
+        &mut self,
+        option: &'arena parser::Expr<'arena>,
+        default: &'arena parser::Expr<'arena>,
+    ) -> Result<&'arena mut Expr<'types, 'arena>, TypeError> {
+        let option = self.analyze(option)?;
+        let default = self.analyze(default)?;
+
+        // Option[T] ?? T yields T
+        let inner_ty = self.type_manager.fresh_type_var();
+        self.expect_type_to_be(
+            option,
+            option.0,
+            self.type_manager.option(inner_ty),
+            "Left operand of `??` must be an Option",
+        )?;
+        let result_ty = self.expect_types_match(default, default.0, inner_ty)?;
+
+        Ok(self.alloc(result_ty, ExprInner::Coalesce { option, default }))
+    }
+
     fn analyze_option(
         &mut self,
         inner: Option<&'arena parser::Expr<'arena>>,
@@ -958,6 +1813,8 @@ impl<'types, 'arena> Analyzer<'types, 'arena> {
 
         let result_ty = result_ty.unwrap(); // Safe because we checked arms is not empty
 
+        self.check_reachability(matched_ty, arms, &typed_arms);
+
         // Check exhaustiveness for Bool and Option types
         self.check_exhaustiveness(matched_ty, &typed_arms)?;
 
@@ -976,10 +1833,112 @@ impl<'types, 'arena> Analyzer<'types, 'arena> {
             typed_expr::TypedPattern::Wildcard | typed_expr::TypedPattern::Var(_) => true,
             // For nested patterns, recursively check if inner pattern is catch-all
             typed_expr::TypedPattern::Some(inner) => Self::is_catch_all_pattern(inner),
+            typed_expr::TypedPattern::As { pattern, .. } => Self::is_catch_all_pattern(pattern),
             _ => false,
         }
     }
 
+    /// The pattern matched by `pattern`, looking through `as` bindings.
+    fn without_binding<'p>(
+        pattern: &'p typed_expr::TypedPattern<'types, 'arena>,
+    ) -> &'p typed_expr::TypedPattern<'types, 'arena> {
+        match pattern {
+            typed_expr::TypedPattern::As { pattern, .. } => Self::without_binding(pattern),
+            _ => pattern,
+        }
+    }
+
+    /// Record a warning for each arm that can only match values already
+    /// matched by an earlier arm.
+    fn check_reachability(
+        &mut self,
+        matched_ty: &'types Type<'types>,
+        arms: &'arena [parser::MatchArm<'arena>],
+        typed_arms: &[typed_expr::TypedMatchArm<'types, 'arena>],
+    ) {
+        let resolved_ty = self.unification.fully_resolve(matched_ty);
+        for (index, arm) in typed_arms.iter().enumerate() {
+            let previous: Vec<_> = typed_arms[..index].iter().map(|arm| arm.pattern).collect();
+            if !Self::is_pattern_covered(&previous, arm.pattern, resolved_ty) {
+                continue;
+            }
+            // Point at the whole arm, `pattern -> body`.
+            let pattern_span = self
+                .pattern_ann
+                .span_of(arms[index].pattern)
+                .unwrap_or_else(|| self.get_span());
+            let span = match self.parsed_ann.span_of(arms[index].body) {
+                Some(body_span) => Span::combine(&pattern_span, &body_span),
+                None => pattern_span.clone(),
+            };
+            let pattern = self.pattern_ann.snippet(pattern_span).to_string();
+            self.warnings.push(TypeError::new(
+                TypeErrorKind::UnreachablePattern { pattern },
+                self.get_source(),
+                span,
+            ));
+        }
+    }
+
+    /// Check if every value matched by `pattern` (of type `ty`) is already
+    /// matched by one of the `previous` patterns.
+    fn is_pattern_covered(
+        previous: &[&typed_expr::TypedPattern<'types, 'arena>],
+        pattern: &typed_expr::TypedPattern<'types, 'arena>,
+        ty: &'types Type<'types>,
+    ) -> bool {
+        use crate::types::traits::TypeKind;
+        use typed_expr::TypedPattern;
+
+        let previous: Vec<_> = previous.iter().map(|p| Self::without_binding(p)).collect();
+        if previous
+            .iter()
+            .any(|previous| matches!(previous, TypedPattern::Wildcard | TypedPattern::Var(_)))
+        {
+            return true;
+        }
+        match Self::without_binding(pattern) {
+            TypedPattern::Literal(value) => previous
+                .iter()
+                .any(|previous| matches!(previous, TypedPattern::Literal(other) if other == value)),
+            TypedPattern::None => previous
+                .iter()
+                .any(|previous| matches!(previous, TypedPattern::None)),
+            TypedPattern::Some(inner) => {
+                let TypeKind::Option(inner_ty) = ty.view() else {
+                    return false;
+                };
+                let previous_inner: Vec<_> = previous
+                    .iter()
+                    .filter_map(|previous| match previous {
+                        TypedPattern::Some(inner) => Some(*inner),
+                        _ => None,
+                    })
+                    .collect();
+                Self::is_pattern_covered(&previous_inner, inner, inner_ty)
+            }
+            TypedPattern::As { .. } => unreachable!("bindings are looked through above"),
+            // A catch-all is only covered when the previous patterns are exhaustive.
+            TypedPattern::Wildcard | TypedPattern::Var(_) => match ty.view() {
+                TypeKind::Bool => [true, false].iter().all(|expected| {
+                    previous.iter().any(|previous| {
+                        matches!(previous, TypedPattern::Literal(value)
+                            if value.as_bool().ok() == Some(*expected))
+                    })
+                }),
+                TypeKind::Option(_) => {
+                    Self::is_pattern_covered(&previous, &TypedPattern::None, ty)
+                        && Self::is_pattern_covered(
+                            &previous,
+                            &TypedPattern::Some(&TypedPattern::Wildcard),
+                            ty,
+                        )
+                }
+                _ => false,
+            },
+        }
+    }
+
     /// Check if the patterns in a match are exhaustive for Bool and Option types.
     /// For other types, we don't check exhaustiveness (would require wildcard).
     fn check_exhaustiveness(
@@ -1007,14 +1966,18 @@ impl<'types, 'arena> Analyzer<'types, 'arena> {
             TypeKind::Bool => {
                 // For Bool, we need both true and false patterns
                 let has_true = arms.iter().any(|arm| {
-                    if let typed_expr::TypedPattern::Literal(value) = arm.pattern {
+                    if let typed_expr::TypedPattern::Literal(value) =
+                        Self::without_binding(arm.pattern)
+                    {
                         value.as_bool().unwrap_or(false) == true
                     } else {
                         false
                     }
                 });
                 let has_false = arms.iter().any(|arm| {
-                    if let typed_expr::TypedPattern::Literal(value) = arm.pattern {
+                    if let typed_expr::TypedPattern::Literal(value) =
+                        Self::without_binding(arm.pattern)
+                    {
                         value.as_bool().unwrap_or(true) == false
                     } else {
                         false
@@ -1040,12 +2003,15 @@ impl<'types, 'arena> Analyzer<'types, 'arena> {
                 // For Option[T], we need both some and none patterns
                 // The some pattern must have a catch-all inner pattern (some _ or some x)
                 let has_some = arms.iter().any(|arm| {
-                    matches!(arm.pattern, typed_expr::TypedPattern::Some(inner)
+                    matches!(Self::without_binding(arm.pattern), typed_expr::TypedPattern::Some(inner)
                         if Self::is_catch_all_pattern(inner))
                 });
-                let has_none = arms
-                    .iter()
-                    .any(|arm| matches!(arm.pattern, typed_expr::TypedPattern::None));
+                let has_none = arms.iter().any(|arm| {
+                    matches!(
+                        Self::without_binding(arm.pattern),
+                        typed_expr::TypedPattern::None
+                    )
+                });
 
                 let mut missing = Vec::new();
                 if !has_some {
@@ -1081,6 +2047,10 @@ impl<'types, 'arena> Analyzer<'types, 'arena> {
             parser::Pattern::Var(name) => vars.push(name),
             parser::Pattern::Literal(_) => {}
             parser::Pattern::Some(inner) => self.collect_pattern_vars(inner, vars),
+            parser::Pattern::As { pattern, name } => {
+                self.collect_pattern_vars(pattern, vars);
+                vars.push(name);
+            }
             parser::Pattern::None => {}
         }
     }
@@ -1098,18 +2068,7 @@ impl<'types, 'arena> Analyzer<'types, 'arena> {
 
             parser::Pattern::Var(name) => {
                 // Variable pattern binds the matched value to a name
-                // Create a monomorphic type scheme (no quantified variables)
-                let type_scheme = TypeScheme::new(&[], expected_ty);
-
-                // Add binding to current scope
-                self.scope_stack
-                    .bind_in_current(name, type_scheme)
-                    .map_err(|_| {
-                        self.type_error(TypeErrorKind::DuplicateBinding {
-                            name: name.to_string(),
-                        })
-                    })?;
-
+                self.bind_pattern_var(name, expected_ty)?;
                 Ok(self.arena.alloc(typed_expr::TypedPattern::Var(name)))
             }
 
@@ -1212,9 +2171,38 @@ impl<'types, 'arena> Analyzer<'types, 'arena> {
 
                 Ok(self.arena.alloc(typed_expr::TypedPattern::None))
             }
+
+            parser::Pattern::As { pattern, name } => {
+                // Match the inner pattern, then bind the whole value to a name
+                let typed_pattern = self.analyze_pattern(pattern, expected_ty)?;
+                self.bind_pattern_var(name, expected_ty)?;
+                Ok(self.arena.alloc(typed_expr::TypedPattern::As {
+                    pattern: typed_pattern,
+                    name,
+                }))
+            }
         }
     }
 
+    /// Bind a name introduced by a pattern to the matched value's type.
+    fn bind_pattern_var(
+        &mut self,
+        name: &'arena str,
+        ty: &'types Type<'types>,
+    ) -> Result<(), TypeError> {
+        // Create a monomorphic type scheme (no quantified variables)
+        let type_scheme = TypeScheme::new(&[], ty);
+
+        // Add binding to current scope
+        self.scope_stack
+            .bind_in_current(name, type_scheme)
+            .map_err(|_| {
+                self.type_error(TypeErrorKind::DuplicateBinding {
+                    name: name.to_string(),
+                })
+            })
+    }
+
     fn analyze_record(
         &mut self,
         items: &'arena [(&'arena str, &'arena parser::Expr<'arena>)],
@@ -1250,7 +2238,13 @@ impl<'types, 'arena> Analyzer<'types, 'arena> {
             let value_ty = self.type_manager.fresh_type_var();
             let map_ty = self.type_manager.map(key_ty, value_ty);
 
-            return Ok(self.alloc(map_ty, ExprInner::Map { elements: &[] }));
+            return Ok(self.alloc(
+                map_ty,
+                ExprInner::Map {
+                    elements: &[],
+                    duplicate_keys: self.options.duplicate_map_keys,
+                },
+            ));
         }
 
         // Analyze all keys and values
@@ -1283,6 +2277,10 @@ impl<'types, 'arena> Analyzer<'types, 'arena> {
             self.expect_types_match(value, value.0, value_ty)?;
         }
 
+        if self.options.duplicate_map_keys == DuplicateKeyPolicy::Error {
+            self.check_duplicate_literal_keys(items, entries_slice)?;
+        }
+
         // Map keys must be hashable
         let span = self.get_span();
         self.type_class_resolver
@@ -1294,10 +2292,35 @@ impl<'types, 'arena> Analyzer<'types, 'arena> {
             map_ty,
             ExprInner::Map {
                 elements: entries_slice,
+                duplicate_keys: self.options.duplicate_map_keys,
             },
         ))
     }
 
+    /// Reject map literals that repeat a constant key.
+    ///
+    /// Computed keys can only be compared at runtime, where the evaluator
+    /// applies the same policy.
+    fn check_duplicate_literal_keys(
+        &mut self,
+        items: &'arena [(&'arena parser::Expr<'arena>, &'arena parser::Expr<'arena>)],
+        entries: &'arena [(&'arena Expr<'types, 'arena>, &'arena Expr<'types, 'arena>)],
+    ) -> Result<(), TypeError> {
+        let mut seen = BTreeSet::new();
+        for ((key_expr, _), (key, _)) in items.iter().zip(entries) {
+            let ExprInner::Constant(value) = &key.1 else {
+                continue;
+            };
+            if !seen.insert(*value) {
+                self.current_span = self.parsed_ann.span_of(key_expr);
+                return self.error(TypeErrorKind::DuplicateMapKey {
+                    key: value.to_string(),
+                });
+            }
+        }
+        Ok(())
+    }
+
     fn analyze_array(
         &mut self,
         exprs: &'arena [&'arena parser::Expr<'arena>],
@@ -1311,11 +2334,15 @@ impl<'types, 'arena> Analyzer<'types, 'arena> {
         }
 
         // Analyze all elements
-        let elements: Vec<&'arena mut Expr<'types, 'arena>> = exprs
+        let mut elements: Vec<&'arena mut Expr<'types, 'arena>> = exprs
             .iter()
             .map(|e| self.analyze(e))
             .collect::<Result<_, _>>()?;
 
+        if self.options.promote_to_option {
+            elements = self.promote_elements_to_option(elements);
+        }
+
         // Allocate in arena first, then do type checks on arena-allocated slice
         let elements_slice: &'arena [&'arena Expr<'types, 'arena>] = self
             .arena
@@ -1337,6 +2364,41 @@ impl<'types, 'arena> Analyzer<'types, 'arena> {
         ))
     }
 
+    /// If some elements are options and others are not, wraps the latter in
+    /// `some` so they can all share an `Option` element type.
+    fn promote_elements_to_option(
+        &mut self,
+        elements: Vec<&'arena mut Expr<'types, 'arena>>,
+    ) -> Vec<&'arena mut Expr<'types, 'arena>> {
+        let is_option = |this: &Self, expr: &Expr<'types, 'arena>| {
+            matches!(this.unification.fully_resolve(expr.0), Type::Option(_))
+        };
+        if !elements.iter().any(|e| is_option(self, e)) {
+            return elements;
+        }
+
+        let old_span = self.current_span.clone();
+        let promoted = elements
+            .into_iter()
+            .map(|element| {
+                if is_option(self, element) {
+                    return element;
+                }
+                // Attribute the implicit `some` to the element it wraps.
+                self.current_span = self.typed_ann.span_of(element);
+                let option_ty = self.type_manager.option(element.0);
+                self.alloc(
+                    option_ty,
+                    ExprInner::Option {
+                        inner: Some(element),
+                    },
+                )
+            })
+            .collect();
+        self.current_span = old_span;
+        promoted
+    }
+
     fn analyze_format_str(
         &mut self,
         _strs: &'arena [&'arena str],
@@ -1425,6 +2487,7 @@ impl<'types, 'arena> Analyzer<'types, 'arena> {
             let (ty, inst_subst) = self
                 .unification
                 .instantiate_with_subst(scheme, &mut self.type_class_resolver, instantiation_span);
+            self.instantiated_vars.extend(scheme.quantified.iter().copied());
 
             // If this identifier refers to a polymorphic lambda, record the instantiation
             // The lambda pointer is stored in the TypeScheme itself
@@ -1494,6 +2557,15 @@ impl<'types, 'arena> Analyzer<'types, 'arena> {
                     let fresh_var = self.type_manager.type_var(*fresh_var_id);
                     let concrete_ty = self.unification.fully_resolve(fresh_var);
 
+                    // Internal variables of the lambda's constraints (such as
+                    // the type a mixed comparison promotes to) are only
+                    // substituted once this instantiation determines them
+                    if !quantified_vars.contains(gen_var_id)
+                        && matches!(concrete_ty, Type::TypeVar(_))
+                    {
+                        continue;
+                    }
+
                     // Map: generalized var ID -> concrete type
                     substitution.insert(*gen_var_id, concrete_ty);
                 }
@@ -1564,10 +2636,16 @@ impl<'types, 'arena> Analyzer<'types, 'arena> {
         let old_span = self.typed_ann.span_of(expr);
 
         let resolved_inner = match &expr.1 {
-            ExprInner::Binary { op, left, right } => ExprInner::Binary {
+            ExprInner::Binary {
+                op,
+                left,
+                right,
+                division,
+            } => ExprInner::Binary {
                 op: *op,
                 left: self.resolve_expr_types(left, ptr_remap),
                 right: self.resolve_expr_types(right, ptr_remap),
+                division: *division,
             },
             ExprInner::Boolean { op, left, right } => ExprInner::Boolean {
                 op: *op,
@@ -1583,7 +2661,11 @@ impl<'types, 'arena> Analyzer<'types, 'arena> {
                 op: *op,
                 expr: self.resolve_expr_types(inner, ptr_remap),
             },
-            ExprInner::Call { callable, args } => {
+            ExprInner::Call {
+                callable,
+                args,
+                coercion,
+            } => {
                 let resolved_callable = self.resolve_expr_types(callable, ptr_remap);
                 let resolved_args: Vec<_> = args
                     .iter()
@@ -1592,12 +2674,18 @@ impl<'types, 'arena> Analyzer<'types, 'arena> {
                 ExprInner::Call {
                     callable: resolved_callable,
                     args: self.arena.alloc_slice_fill_iter(resolved_args.into_iter()),
+                    coercion: *coercion,
                 }
             }
             ExprInner::Index { value, index } => ExprInner::Index {
                 value: self.resolve_expr_types(value, ptr_remap),
                 index: self.resolve_expr_types(index, ptr_remap),
             },
+            ExprInner::Slice { value, start, end } => ExprInner::Slice {
+                value: self.resolve_expr_types(value, ptr_remap),
+                start: start.map(|bound| self.resolve_expr_types(bound, ptr_remap)),
+                end: end.map(|bound| self.resolve_expr_types(bound, ptr_remap)),
+            },
             ExprInner::Field { value, field } => ExprInner::Field {
                 value: self.resolve_expr_types(value, ptr_remap),
                 field,
@@ -1643,6 +2731,10 @@ impl<'types, 'arena> Analyzer<'types, 'arena> {
                 primary: self.resolve_expr_types(primary, ptr_remap),
                 fallback: self.resolve_expr_types(fallback, ptr_remap),
             },
+            ExprInner::Coalesce { option, default } => ExprInner::Coalesce {
+                option: self.resolve_expr_types(option, ptr_remap),
+                default: self.resolve_expr_types(default, ptr_remap),
+            },
             ExprInner::Option { inner } => ExprInner::Option {
                 inner: inner.map(|expr| self.resolve_expr_types(expr, ptr_remap)),
             },
@@ -1676,7 +2768,10 @@ impl<'types, 'arena> Analyzer<'types, 'arena> {
                         .alloc_slice_fill_iter(resolved_fields.into_iter()),
                 }
             }
-            ExprInner::Map { elements } => {
+            ExprInner::Map {
+                elements,
+                duplicate_keys,
+            } => {
                 let resolved_elements: Vec<_> = elements
                     .iter()
                     .map(|(key, value)| {
@@ -1690,6 +2785,7 @@ impl<'types, 'arena> Analyzer<'types, 'arena> {
                     elements: self
                         .arena
                         .alloc_slice_fill_iter(resolved_elements.into_iter()),
+                    duplicate_keys: *duplicate_keys,
                 }
             }
             ExprInner::Array { elements } => {
@@ -1756,6 +2852,54 @@ impl<'types, 'arena> Analyzer<'types, 'arena> {
                     .alloc(typed_expr::TypedPattern::Some(resolved_inner))
             }
             typed_expr::TypedPattern::None => self.arena.alloc(typed_expr::TypedPattern::None),
+            typed_expr::TypedPattern::As { pattern, name } => {
+                let resolved_pattern = self.resolve_pattern_types(pattern, _ptr_remap);
+                self.arena.alloc(typed_expr::TypedPattern::As {
+                    pattern: resolved_pattern,
+                    name,
+                })
+            }
+        }
+    }
+}
+
+/// Whether the alias `name` declared in `group` refers back to itself,
+/// directly or through other aliases of the same group.
+fn is_recursive_alias(name: &str, group: &[(&str, parser::TypeExpr<'_>)]) -> bool {
+    let mut pending = vec![name];
+    let mut visited: Vec<&str> = Vec::new();
+    while let Some(current) = pending.pop() {
+        if visited.contains(&current) {
+            continue;
+        }
+        visited.push(current);
+        let Some((_, ty_expr)) = group.iter().find(|(alias, _)| *alias == current) else {
+            continue;
+        };
+        let mut referenced = Vec::new();
+        collect_type_names(ty_expr, &mut referenced);
+        if referenced.contains(&name) {
+            return true;
+        }
+        pending.extend(referenced);
+    }
+    false
+}
+
+/// Collects the type names mentioned by `ty_expr`.
+fn collect_type_names<'a>(ty_expr: &parser::TypeExpr<'a>, out: &mut Vec<&'a str>) {
+    match ty_expr {
+        parser::TypeExpr::Path(path) => out.push(path),
+        parser::TypeExpr::Parametrized { path, params } => {
+            out.push(path);
+            for param in params.iter() {
+                collect_type_names(param, out);
+            }
+        }
+        parser::TypeExpr::Record(fields) => {
+            for (_, field_ty) in fields.iter() {
+                collect_type_names(field_ty, out);
+            }
         }
     }
 }
@@ -3,7 +3,7 @@ use crate::format;
 use crate::{
     analyzer::error::{TypeError, TypeErrorKind},
     parser,
-    types::{manager::TypeManager, TypeClassId},
+    types::{TypeClassId, manager::TypeManager},
 };
 use bumpalo::Bump;
 use std::sync::Once;
@@ -62,6 +62,10 @@ fn collect_lambda_pointers<'types, 'arena>(
         | typed_expr::ExprInner::Otherwise {
             primary: left,
             fallback: right,
+        }
+        | typed_expr::ExprInner::Coalesce {
+            option: left,
+            default: right,
         } => {
             collect_lambda_pointers(left, lambdas);
             collect_lambda_pointers(right, lambdas);
@@ -71,7 +75,13 @@ fn collect_lambda_pointers<'types, 'arena>(
         | typed_expr::ExprInner::Cast { expr: inner } => {
             collect_lambda_pointers(inner, lambdas);
         }
-        typed_expr::ExprInner::Call { callable, args } => {
+        typed_expr::ExprInner::Slice { value, start, end } => {
+            collect_lambda_pointers(value, lambdas);
+            for bound in start.iter().chain(end.iter()) {
+                collect_lambda_pointers(bound, lambdas);
+            }
+        }
+        typed_expr::ExprInner::Call { callable, args, .. } => {
             collect_lambda_pointers(callable, lambdas);
             for arg in *args {
                 collect_lambda_pointers(arg, lambdas);
@@ -100,7 +110,7 @@ fn collect_lambda_pointers<'types, 'arena>(
                 collect_lambda_pointers(value, lambdas);
             }
         }
-        typed_expr::ExprInner::Map { elements } => {
+        typed_expr::ExprInner::Map { elements, .. } => {
             for (key, value) in *elements {
                 collect_lambda_pointers(key, lambdas);
                 collect_lambda_pointers(value, lambdas);
@@ -153,7 +163,6 @@ fn test_arithmetic_operators_integers() {
 // ============================================================================
 
 #[test]
-#[ignore = "Type system limitation: generic indexing now defaults to Map for better map support; arrays with Int indexes conflict with Map[Int, V]"]
 fn test_index_in_generic_lambda() {
     let bump = Bump::new();
     let type_manager = TypeManager::new(&bump);
@@ -172,6 +181,40 @@ fn test_index_in_generic_lambda() {
     assert_eq!(result.unwrap().expr.0, type_manager.int());
 }
 
+#[test]
+fn test_index_on_ambiguous_container_fails() {
+    let bump = Bump::new();
+    let type_manager = TypeManager::new(&bump);
+
+    // Nothing determines whether 'c' is an array, a map, bytes or a string
+    for source in ["(c) => c[0]", "((f) => 1)((c) => c[0])"] {
+        let err = analyze_source(source, &type_manager, &bump).unwrap_err();
+        match &err.kind {
+            TypeErrorKind::ConstraintViolation {
+                type_class,
+                details,
+                ..
+            } => {
+                assert_eq!(*type_class, TypeClassId::Indexable, "{}", source);
+                assert!(
+                    details.contains("cannot infer the type of the indexed value"),
+                    "{}: {}",
+                    source,
+                    details
+                );
+            }
+            other => panic!(
+                "Expected ConstraintViolation for {}, got {:?}",
+                source, other
+            ),
+        }
+    }
+
+    // A polymorphic binding is resolved at each use instead, even if unused
+    let result = analyze_source("1 where { f = (c) => c[0] }", &type_manager, &bump);
+    assert!(result.is_ok(), "{:?}", result);
+}
+
 #[test]
 fn test_numeric_constraint_violation_with_source() {
     let bump = Bump::new();
@@ -334,7 +377,6 @@ fn test_nested_indexing_polymorphic_lambda() {
 }
 
 #[test]
-#[ignore = "Type system limitation: generic indexing now defaults to Map for better map support; arrays with Int indexes conflict with Map[Int, V]"]
 fn test_nested_array_indexing_with_generic() {
     let bump = Bump::new();
     let type_manager = TypeManager::new(&bump);
@@ -355,53 +397,94 @@ fn test_nested_array_indexing_with_generic() {
 }
 
 #[test]
-#[ignore = "Requires row polymorphism - cannot infer 'any record with field x'"]
 fn test_field_access_in_generic_lambda() {
     let bump = Bump::new();
     let type_manager = TypeManager::new(&bump);
 
-    // Lambda parameter 'r' should be constrained to "any record with field x"
-    // With row polymorphism: ((r) => r.x) :: {x :: Int | r} -> Int
-    // When called with {x: 42}, unified to Record{x: Int} -> Int
-    let source = "((r) => r.x)({x: 42})";
+    // Lambda parameter 'r' is constrained to "any record with field x"
+    // ((r) => r.x) :: HasField(r, x, a) => r -> a
+    // When called with {x = 42}, unified to Record{x: Int} -> Int
+    let source = "((r) => r.x)({x = 42})";
     let result = analyze_source(source, &type_manager, &bump);
 
     assert!(
         result.is_ok(),
-        "Field access on generic lambda parameter should work with row polymorphism: {:?}",
+        "Field access on generic lambda parameter should work: {:?}",
         result
     );
     assert_eq!(result.unwrap().expr.0, type_manager.int());
 }
 
 #[test]
-#[ignore = "Requires row polymorphism - nested case"]
 fn test_nested_generic_lambda_field_access() {
     let bump = Bump::new();
     let type_manager = TypeManager::new(&bump);
 
     // Higher-order function: pass a record processor function
-    // With row polymorphism: f :: {x :: Int | r} -> Int, result :: Int
+    // f :: {x: Int} -> Int, result :: Int
     // Nested generic lambda composition
     let source = "((f) => f({x = 1}))((r) => r.x)";
     let result = analyze_source(source, &type_manager, &bump);
 
     assert!(
         result.is_ok(),
-        "Nested generic lambda with field access should work with row polymorphism: {:?}",
+        "Nested generic lambda with field access should work: {:?}",
         result
     );
     assert_eq!(result.unwrap().expr.0, type_manager.int());
 }
 
 #[test]
-#[ignore = "Cast validation happens during lambda body analysis, before unification"]
+fn test_field_access_polymorphic_lambda_different_records() {
+    let bump = Bump::new();
+    let type_manager = TypeManager::new(&bump);
+
+    // Each instantiation of 'get_x' only requires its argument to have field x
+    let source =
+        r#"{ a = get_x({x = 1, y = 2}), b = get_x({x = "str"}) } where { get_x = (r) => r.x }"#;
+    let result = analyze_source(source, &type_manager, &bump);
+
+    assert!(result.is_ok(), "{:?}", result);
+    let typed_expr = result.unwrap();
+    let expected = type_manager.record(vec![("a", type_manager.int()), ("b", type_manager.str())]);
+    assert_eq!(typed_expr.expr.0, expected);
+
+    // The HasField type class makes the bytecode compiler monomorphize the lambda
+    for (_ptr, insts) in typed_expr.lambda_instantiations.iter() {
+        assert!(
+            insts.type_classes.contains(&TypeClassId::HasField),
+            "Lambda should have HasField constraint, got: {:?}",
+            insts.type_classes
+        );
+    }
+}
+
+#[test]
+fn test_field_access_generic_lambda_missing_field() {
+    let bump = Bump::new();
+    let type_manager = TypeManager::new(&bump);
+
+    for source in ["((r) => r.y)({x = 42})", "((r) => r.x)(42)"] {
+        let err = analyze_source(source, &type_manager, &bump).unwrap_err();
+        match &err.kind {
+            TypeErrorKind::ConstraintViolation { type_class, .. } => {
+                assert_eq!(*type_class, TypeClassId::HasField, "{}", source);
+            }
+            other => panic!(
+                "Expected ConstraintViolation for {}, got {:?}",
+                source, other
+            ),
+        }
+    }
+}
+
+#[test]
 fn test_cast_on_lambda_parameter() {
     let bump = Bump::new();
     let type_manager = TypeManager::new(&bump);
 
-    // Lambda parameter 'x' should allow cast with delayed validation
-    // With constraint system: generate cast constraint, validate after unification
+    // Lambda parameter 'x' allows a cast with delayed validation
+    // The cast adds a Castable constraint, validated after unification
     // When called with 42, x is unified to Int, then Int->Float cast validated
     let source = "((x) => x as Float)(42)";
     let result = analyze_source(source, &type_manager, &bump);
@@ -414,6 +497,36 @@ fn test_cast_on_lambda_parameter() {
     assert_eq!(result.unwrap().expr.0, type_manager.float());
 }
 
+#[test]
+fn test_cast_on_polymorphic_lambda_instantiations() {
+    let bump = Bump::new();
+    let type_manager = TypeManager::new(&bump);
+
+    // Each instantiation of 'f' validates the cast for its own argument type
+    let source = "{ a = f(1), b = f(2.5) } where { f = (x) => x as Float }";
+    let result = analyze_source(source, type_manager, &bump);
+
+    assert!(result.is_ok(), "{:?}", result);
+    let typed_expr = result.unwrap();
+    for (_ptr, insts) in typed_expr.lambda_instantiations.iter() {
+        assert!(
+            insts.type_classes.contains(&TypeClassId::Castable),
+            "Lambda should have Castable constraint, got: {:?}",
+            insts.type_classes
+        );
+    }
+
+    // An invalid cast is reported once the argument type is known
+    let err = analyze_source("((x) => x as Float)(\"1\")", type_manager, &bump).unwrap_err();
+    match &err.kind {
+        TypeErrorKind::ConstraintViolation { ty, type_class, .. } => {
+            assert_eq!(*type_class, TypeClassId::Castable);
+            assert_eq!(ty, "Str");
+        }
+        other => panic!("Expected ConstraintViolation, got {:?}", other),
+    }
+}
+
 #[test]
 fn test_index_in_where_bound_variable() {
     let bump = Bump::new();
@@ -463,6 +576,43 @@ fn test_arithmetic_operators_floats() {
     }
 }
 
+#[test]
+fn test_bitwise_operators_ints() {
+    let bump = Bump::new();
+    let type_manager = TypeManager::new(&bump);
+
+    for op in ["&", "|", "xor", "<<", ">>"] {
+        let source = format!("6 {} 3", op);
+        let result = analyze_source(&source, type_manager, &bump);
+        assert!(result.is_ok(), "Failed for operator {}", op);
+        assert_eq!(result.unwrap().expr.0, type_manager.int());
+    }
+}
+
+#[test]
+fn test_bitwise_operators_reject_non_int() {
+    let bump = Bump::new();
+    let type_manager = TypeManager::new(&bump);
+
+    for source in ["1.0 & 2.0", "1.5 << 2.0", "true | false", "\"a\" xor \"b\""] {
+        let err = analyze_source(source, type_manager, &bump).unwrap_err();
+        match err.kind {
+            TypeErrorKind::ConstraintViolation { type_class, .. } => {
+                assert_eq!(type_class, TypeClassId::Bitwise, "{}", source);
+            }
+            other => panic!("{}: expected Bitwise constraint error, got {:?}", source, other),
+        }
+    }
+}
+
+#[test]
+fn test_bitwise_operators_mixed_types_fail() {
+    let bump = Bump::new();
+    let type_manager = TypeManager::new(&bump);
+
+    assert!(analyze_source("1 & 2.0", type_manager, &bump).is_err());
+}
+
 #[test]
 fn test_arithmetic_mixed_types_fails() {
     let bump = Bump::new();
@@ -485,6 +635,44 @@ fn test_logical_operators() {
     }
 }
 
+#[test]
+fn test_containment_operators() {
+    let bump = Bump::new();
+    let type_manager = TypeManager::new(&bump);
+
+    for op in ["in", "not in"] {
+        for haystack in ["[1, 2, 3]", "{1: \"one\"}"] {
+            let source = format!("1 {} {}", op, haystack);
+            let result = analyze_source(&source, &type_manager, &bump);
+            assert!(result.is_ok(), "Failed for {}", source);
+            assert_eq!(result.unwrap().expr.0, type_manager.bool());
+        }
+        let source = format!("\"lo\" {} \"hello\"", op);
+        let result = analyze_source(&source, &type_manager, &bump);
+        assert_eq!(result.unwrap().expr.0, type_manager.bool());
+    }
+}
+
+#[test]
+fn test_not_in_mismatched_element_fails() {
+    let bump = Bump::new();
+    let type_manager = TypeManager::new(&bump);
+
+    let result = analyze_source("\"a\" not in [1, 2, 3]", &type_manager, &bump);
+    assert!(result.is_err());
+}
+
+#[test]
+fn test_containment_in_non_container_fails() {
+    let bump = Bump::new();
+    let type_manager = TypeManager::new(&bump);
+
+    for source in ["1 in 5", "1 not in 5", "\"a\" in {1: 2}", "1 in true"] {
+        let result = analyze_source(source, type_manager, &bump);
+        assert!(result.is_err(), "Expected {} to fail", source);
+    }
+}
+
 #[test]
 fn test_logical_operators_non_boolean_fails() {
     let bump = Bump::new();
@@ -994,6 +1182,22 @@ fn test_literals() {
     assert_eq!(str_result.unwrap().expr.0, type_manager.str());
 }
 
+#[test]
+fn test_scientific_notation_is_float() {
+    let bump = Bump::new();
+    let type_manager = TypeManager::new(&bump);
+
+    for source in ["1e3", "2.5e-3", "1E10", "-1.5e+3"] {
+        let result = analyze_source(source, type_manager, &bump);
+        assert_eq!(
+            result.unwrap().expr.0,
+            type_manager.float(),
+            "Wrong type for {}",
+            source
+        );
+    }
+}
+
 #[test]
 fn test_all_literal_types() {
     let bump = Bump::new();
@@ -1086,6 +1290,134 @@ fn test_where_duplicate_binding_fails() {
     assert!(result.is_err());
 }
 
+#[test]
+fn test_where_type_alias_in_cast() {
+    let bump = Bump::new();
+    let type_manager = TypeManager::new(&bump);
+
+    // Aliases are visible in the bindings and in the body
+    let result = analyze_source(
+        "xs as Ids where { type Id = Int, type Ids = Array[Id], xs = [1 as Id] }",
+        type_manager,
+        &bump,
+    )
+    .unwrap();
+    assert_eq!(result.expr.0, type_manager.array(type_manager.int()));
+
+    // Inner where blocks see (and may shadow) the aliases of outer ones
+    let result = analyze_source(
+        "(x as T where { type T = Float }) where { type T = Int, x = 1 }",
+        type_manager,
+        &bump,
+    )
+    .unwrap();
+    assert_eq!(result.expr.0, type_manager.float());
+}
+
+#[test]
+fn test_where_type_alias_not_visible_outside() {
+    let bump = Bump::new();
+    let type_manager = TypeManager::new(&bump);
+
+    let result = analyze_source(
+        "(x where { type Id = Int, x = 1 }) as Id",
+        type_manager,
+        &bump,
+    );
+    let err_string = format!("{:?}", result.unwrap_err());
+    assert!(err_string.contains("Unknown type: Id"));
+
+    let result = analyze_source(
+        "x where { x = (1 as Id where { type Id = Int }), y = 2 as Id }",
+        type_manager,
+        &bump,
+    );
+    let err_string = format!("{:?}", result.unwrap_err());
+    assert!(err_string.contains("Unknown type: Id"));
+}
+
+#[test]
+fn test_where_duplicate_type_alias_fails() {
+    let bump = Bump::new();
+    let type_manager = TypeManager::new(&bump);
+
+    let result = analyze_source(
+        "1 where { type T = Int, type T = Float }",
+        type_manager,
+        &bump,
+    );
+    assert!(result.is_err());
+}
+
+#[test]
+fn test_type_declaration_in_cast() {
+    let bump = Bump::new();
+    let type_manager = TypeManager::new(&bump);
+
+    // Aliases are transparent: a matching record casts to the alias
+    let source =
+        "type Point = Record[x: Float, y: Float]; p as Point where { p = { x = 1.0, y = 2.0 } }";
+    let parsed = parser::parse(&bump, source).unwrap();
+    let point = type_manager.record(vec![
+        ("x", type_manager.float()),
+        ("y", type_manager.float()),
+    ]);
+    let result = analyze(type_manager, &bump, parsed, &[], &[]).unwrap();
+    assert_eq!(result.expr.0, point);
+
+    // Declarations are visible inside where blocks, which may shadow them
+    let result = analyze_source(
+        "type T = Int; type Ts = Array[T]; [x as T] as Ts where { x = 1 }",
+        type_manager,
+        &bump,
+    )
+    .unwrap();
+    assert_eq!(result.expr.0, type_manager.array(type_manager.int()));
+    let result = analyze_source(
+        "type T = Int; 1.0 as T where { type T = Float }",
+        type_manager,
+        &bump,
+    )
+    .unwrap();
+    assert_eq!(result.expr.0, type_manager.float());
+}
+
+#[test]
+fn test_type_declaration_errors() {
+    let bump = Bump::new();
+    let type_manager = TypeManager::new(&bump);
+
+    let err = analyze_source("type Id = Int; 1 as Ids", type_manager, &bump).unwrap_err();
+    assert!(format!("{:?}", err).contains("Unknown type: Ids"));
+
+    let err = analyze_source("type T = Int; type T = Float; 1", type_manager, &bump).unwrap_err();
+    assert!(matches!(err.kind, TypeErrorKind::DuplicateBinding { .. }));
+
+    // Aliases referring back to themselves, directly or not
+    for source in [
+        "type List = Array[List]; []",
+        "type A = Array[B]; type B = Option[A]; 1",
+        "1 where { type P = Record[next: Option[P]] }",
+    ] {
+        let err = analyze_source(source, type_manager, &bump).unwrap_err();
+        assert!(
+            matches!(err.kind, TypeErrorKind::RecursiveTypeAlias { .. }),
+            "Unexpected error for '{}': {:?}",
+            source,
+            err
+        );
+        assert_eq!(err.to_diagnostic().code, Some("E025".to_string()));
+    }
+
+    // Only aliases of the same group can make a definition recursive
+    let result = analyze_source(
+        "1 as P where { type P = Array[P] } where { type P = Int }",
+        type_manager,
+        &bump,
+    );
+    assert!(result.is_err_and(|err| matches!(err.kind, TypeErrorKind::InvalidCast { .. })));
+}
+
 // ============================================================================
 // Lambdas and Functions
 // ============================================================================
@@ -1251,6 +1583,58 @@ fn test_indexing_non_indexable_fails() {
     assert!(result.is_err());
 }
 
+#[test]
+fn test_string_indexing() {
+    let bump = Bump::new();
+    let type_manager = TypeManager::new(&bump);
+
+    let result = analyze_source(r#""abc"[0]"#, type_manager, &bump).unwrap();
+    assert_eq!(result.expr.0, type_manager.str());
+
+    let result = analyze_source(r#"s[-1] where { s = "abc" }"#, type_manager, &bump).unwrap();
+    assert_eq!(result.expr.0, type_manager.str());
+}
+
+#[test]
+fn test_string_indexing_non_integer_fails() {
+    let bump = Bump::new();
+    let type_manager = TypeManager::new(&bump);
+
+    let result = analyze_source(r#""abc"["a"]"#, type_manager, &bump);
+    assert!(result.is_err());
+}
+
+#[test]
+fn test_slicing() {
+    let bump = Bump::new();
+    let type_manager = TypeManager::new(&bump);
+
+    let result = analyze_source("[1, 2, 3][1:]", type_manager, &bump).unwrap();
+    assert_eq!(result.expr.0, type_manager.array(type_manager.int()));
+
+    let result = analyze_source(r#""abc"[:-1]"#, type_manager, &bump).unwrap();
+    assert_eq!(result.expr.0, type_manager.str());
+
+    // A value of unknown type being sliced is taken to be an array
+    let result = analyze_source("((xs) => xs[1:2])([true])", type_manager, &bump).unwrap();
+    assert_eq!(result.expr.0, type_manager.array(type_manager.bool()));
+}
+
+#[test]
+fn test_slicing_errors() {
+    let bump = Bump::new();
+    let type_manager = TypeManager::new(&bump);
+
+    let result = analyze_source("{1: 2}[0:1]", type_manager, &bump);
+    assert!(matches!(
+        result.unwrap_err().kind,
+        TypeErrorKind::NotSliceable { .. }
+    ));
+
+    let result = analyze_source(r#"[1, 2, 3]["a":]"#, type_manager, &bump);
+    assert!(result.is_err());
+}
+
 // ============================================================================
 // Record Tests
 // ============================================================================
@@ -1456,6 +1840,84 @@ fn test_otherwise_with_array_indexing() {
     assert_eq!(result.unwrap().expr.0, type_manager.str());
 }
 
+#[test]
+fn test_coalesce_unwraps_option() {
+    let bump = Bump::new();
+    let type_manager = TypeManager::new(&bump);
+
+    let result = analyze_source("some 1 ?? 0", type_manager, &bump);
+    assert_eq!(result.unwrap().expr.0, type_manager.int());
+
+    let result = analyze_source("none ?? \"default\"", type_manager, &bump);
+    assert_eq!(result.unwrap().expr.0, type_manager.str());
+
+    let result = analyze_source("none ?? some 2 ?? 3", type_manager, &bump);
+    assert_eq!(result.unwrap().expr.0, type_manager.int());
+}
+
+#[test]
+fn test_coalesce_vs_otherwise() {
+    let bump = Bump::new();
+    let type_manager = TypeManager::new(&bump);
+
+    // `??` handles none: the left operand must be an option...
+    assert!(analyze_source("1 ?? 0", type_manager, &bump).is_err());
+    // ...of the default's type
+    assert!(analyze_source("some 1 ?? \"zero\"", type_manager, &bump).is_err());
+    assert!(analyze_source("some 1 ?? some 0", type_manager, &bump).is_err());
+
+    // `otherwise` handles errors: both sides have the same type, options
+    // included, and nothing is unwrapped
+    let result = analyze_source("1 otherwise 0", type_manager, &bump);
+    assert_eq!(result.unwrap().expr.0, type_manager.int());
+    let result = analyze_source("some 1 otherwise none", type_manager, &bump);
+    assert_eq!(
+        result.unwrap().expr.0,
+        type_manager.option(type_manager.int())
+    );
+    assert!(analyze_source("some 1 otherwise 0", type_manager, &bump).is_err());
+}
+
+#[test]
+fn test_pipe_has_type_of_explicit_call() {
+    let bump = Bump::new();
+    let type_manager = TypeManager::new(&bump);
+
+    let cases = [
+        (
+            "3 |> inc where { inc = (x) => x + 1 }",
+            "inc(3) where { inc = (x) => x + 1 }",
+        ),
+        (
+            "\"a\" |> pair(1) where { pair = (s, n) => { s = s, n = n } }",
+            "pair(\"a\", 1) where { pair = (s, n) => { s = s, n = n } }",
+        ),
+        (
+            "[1, 2] |> first |> wrap where { first = (xs) => xs[0], wrap = (x) => some x }",
+            "wrap(first([1, 2])) where { first = (xs) => xs[0], wrap = (x) => some x }",
+        ),
+    ];
+    for (piped, explicit) in cases {
+        let piped_ty = analyze_source(piped, type_manager, &bump).unwrap().expr.0;
+        let explicit_ty = analyze_source(explicit, type_manager, &bump)
+            .unwrap()
+            .expr
+            .0;
+        assert_eq!(piped_ty, explicit_ty, "{}", piped);
+    }
+}
+
+#[test]
+fn test_pipe_errors_point_at_source() {
+    let bump = Bump::new();
+    let type_manager = TypeManager::new(&bump);
+
+    let source = "\"a\" |> inc where { inc = (x) => x + 1 }";
+    let err = analyze_source(source, type_manager, &bump).unwrap_err();
+    let snippet = &source[err.span.0.clone()];
+    assert!(source.starts_with(snippet), "{:?}", snippet);
+}
+
 // ============================================================================
 // Cast Tests
 // ============================================================================
@@ -1533,7 +1995,6 @@ fn test_span_tracking_binary_expr() {
 }
 
 #[test]
-#[ignore = "Span tracking logic needs to be fixed in parser"]
 fn test_span_tracking_nested_expr() {
     let bump = Bump::new();
     let type_manager = TypeManager::new(&bump);
@@ -1542,7 +2003,6 @@ fn test_span_tracking_nested_expr() {
     let result = analyze_source(source, &type_manager, &bump).unwrap();
 
     // Root multiplication should span from first operand to last (0..11)
-    // Note: there is currently a bug in the span tracking logic.
     assert_eq!(
         result.ann.span_of(result.expr),
         Some(parser::Span::new(0, 11))
@@ -1563,23 +2023,76 @@ fn test_span_tracking_nested_expr() {
 }
 
 #[test]
-fn test_span_tracking_boolean_expr() {
+fn test_span_tracking_unary_expr() {
     let bump = Bump::new();
     let type_manager = TypeManager::new(&bump);
 
-    let source = "true and false";
-    let result = analyze_source(source, &type_manager, &bump).unwrap();
+    let source = "-(1 + 2)";
+    let result = analyze_source(source, type_manager, &bump).unwrap();
 
-    // Root should span the whole expression
+    // Negation spans the operator and its parenthesized operand (0..8)
     assert_eq!(
         result.ann.span_of(result.expr),
-        Some(parser::Span::new(0, 14))
+        Some(parser::Span::new(0, 8))
     );
 
-    // Verify operands have spans
-    if let typed_expr::ExprInner::Boolean { left, right, .. } = &result.expr.1 {
-        // Left "true" should be 0..4
-        let left_span = result.ann.span_of(left);
+    if let typed_expr::ExprInner::Unary { expr, .. } = &result.expr.1 {
+        // The operand "1 + 2" spans the content inside parens: 2..7
+        assert_eq!(result.ann.span_of(expr), Some(parser::Span::new(2, 7)));
+    } else {
+        panic!("Expected Unary expression");
+    }
+}
+
+#[test]
+fn test_span_tracking_call_expr() {
+    let bump = Bump::new();
+    let type_manager = TypeManager::new(&bump);
+
+    let source = "((x) => x * 2)(3) + 1";
+    let result = analyze_source(source, type_manager, &bump).unwrap();
+
+    // Root addition spans from the parenthesized lambda to "1" (0..21)
+    assert_eq!(
+        result.ann.span_of(result.expr),
+        Some(parser::Span::new(0, 21))
+    );
+
+    let typed_expr::ExprInner::Binary { left, .. } = &result.expr.1 else {
+        panic!("Expected Binary expression");
+    };
+    // The call spans from the opening paren of the callable to its own
+    // closing paren: 0..17
+    assert_eq!(result.ann.span_of(left), Some(parser::Span::new(0, 17)));
+
+    if let typed_expr::ExprInner::Call { callable, args, .. } = &left.1 {
+        // The lambda "(x) => x * 2" spans the content inside parens: 1..13
+        assert_eq!(result.ann.span_of(callable), Some(parser::Span::new(1, 13)));
+        // The argument "3" should be 15..16
+        assert_eq!(result.ann.span_of(args[0]), Some(parser::Span::new(15, 16)));
+    } else {
+        panic!("Expected Call expression");
+    }
+}
+
+#[test]
+fn test_span_tracking_boolean_expr() {
+    let bump = Bump::new();
+    let type_manager = TypeManager::new(&bump);
+
+    let source = "true and false";
+    let result = analyze_source(source, &type_manager, &bump).unwrap();
+
+    // Root should span the whole expression
+    assert_eq!(
+        result.ann.span_of(result.expr),
+        Some(parser::Span::new(0, 14))
+    );
+
+    // Verify operands have spans
+    if let typed_expr::ExprInner::Boolean { left, right, .. } = &result.expr.1 {
+        // Left "true" should be 0..4
+        let left_span = result.ann.span_of(left);
         assert_eq!(left_span, Some(parser::Span::new(0, 4)));
 
         // Right "false" should be 9..14
@@ -2136,6 +2649,61 @@ fn test_error_constraint_violation_ord() {
     }
 }
 
+#[test]
+fn test_option_ord() {
+    let bump = Bump::new();
+    let type_manager = TypeManager::new(&bump);
+
+    for source in [
+        "none < some 0",
+        "some 1 < some 2",
+        "some (some \"a\") >= none",
+        "lt(some 1, none) where { lt = (a, b) => a < b }",
+    ] {
+        let result = analyze_source(source, type_manager, &bump);
+        let typed = result.unwrap_or_else(|err| panic!("{}: {:?}", source, err));
+        assert_eq!(typed.expr.0, type_manager.bool(), "{}", source);
+    }
+}
+
+#[test]
+fn test_error_option_ord_requires_ord_element() {
+    let bump = Bump::new();
+    let type_manager = TypeManager::new(&bump);
+
+    let result = analyze_source("some true < none", type_manager, &bump);
+
+    let diagnostic = result.expect_err("Bool is not Ord").to_diagnostic();
+    assert_eq!(diagnostic.code, Some("E005".to_string()));
+    assert_eq!(
+        diagnostic.message,
+        "Type 'Option[Bool]' does not implement Ord"
+    );
+}
+
+#[test]
+fn test_to_option() {
+    let bump = Bump::new();
+    let type_manager = TypeManager::new(&bump);
+
+    let result = analyze_source("ToOption(1 < 2, \"yes\")", type_manager, &bump).unwrap();
+    assert_eq!(result.expr.0, type_manager.option(type_manager.str()));
+
+    let result = analyze_source("ToOption(1, 2)", type_manager, &bump);
+    assert!(
+        result
+            .expect_err("condition must be Bool")
+            .to_string()
+            .contains("Condition of 'ToOption' must be Bool")
+    );
+
+    let result = analyze_source("ToOption(true)", type_manager, &bump);
+    let diagnostic = result
+        .expect_err("ToOption takes 2 arguments")
+        .to_diagnostic();
+    assert!(diagnostic.message.contains("2"), "{}", diagnostic.message);
+}
+
 #[test]
 fn test_error_type_mismatch_binary_op() {
     let bump = Bump::new();
@@ -2204,8 +2772,8 @@ fn test_error_polymorphic_cast() {
     let bump = Bump::new();
     let type_manager = TypeManager::new(&bump);
 
-    // Try to cast a polymorphic value (lambda parameter)
-    let source = "f(1) where { f = (x) => x as Float }";
+    // Cast a value whose type is never determined: 'id' is never called
+    let source = "id where { id = (x) => x as Float }";
     let result = analyze_source(source, &type_manager, &bump);
 
     match result {
@@ -2216,14 +2784,25 @@ fn test_error_polymorphic_cast() {
             assert!(diagnostic.message.contains("Float"));
             // Should have 2 help messages
             assert_eq!(diagnostic.help.len(), 2);
-            assert!(diagnostic.help[0].contains("not yet supported"));
+            assert!(diagnostic.help[0].contains("could not be inferred"));
             assert!(diagnostic.help[1].contains("concrete type"));
-            // Verify context is present showing where type was inferred
+            // Verify context is present showing where the lambda was instantiated
             assert_eq!(diagnostic.related.len(), 1);
-            assert!(diagnostic.related[0].message.contains("inferred here"));
+            assert!(diagnostic.related[0].message.contains("instantiated here"));
         }
         Ok(_) => panic!("Expected PolymorphicCast error"),
     }
+
+    // A binding that is never used leaves its cast undetermined too
+    let err =
+        analyze_source("1 where { id = (x) => x as Float }", type_manager, &bump).unwrap_err();
+    let diagnostic = err.to_diagnostic();
+    assert_eq!(diagnostic.code, Some("E019".to_string()));
+    assert!(diagnostic.related.is_empty());
+
+    // A lambda that is never applied leaves its parameter type unknown
+    let err = analyze_source("(x) => x as Float", type_manager, &bump).unwrap_err();
+    assert_eq!(err.to_diagnostic().code, Some("E019".to_string()));
 }
 
 #[test]
@@ -2997,3 +3576,819 @@ fn test_exhaustiveness_nested_option_with_catch_all() {
         result
     );
 }
+
+#[test]
+fn test_as_pattern_binds_inner_and_whole_value() {
+    let bump = Bump::new();
+    let type_manager = TypeManager::new(&bump);
+
+    let source = r#"some(1) match { some x as whole -> { x = x, whole = whole }, none -> { x = 0, whole = none } }"#;
+    let result = analyze_source(source, type_manager, &bump).unwrap();
+    assert_eq!(
+        result.expr.0,
+        type_manager.record(vec![
+            ("whole", type_manager.option(type_manager.int())),
+            ("x", type_manager.int()),
+        ])
+    );
+
+    // `as` applies to nested patterns too, and counts as the inner pattern
+    // for exhaustiveness.
+    let source = r#"some("a") match { some ("a" as a) -> a, some _ -> "", none -> "" }"#;
+    let result = analyze_source(source, type_manager, &bump).unwrap();
+    assert_eq!(result.expr.0, type_manager.str());
+    let source = r#"true match { true as t -> t, false as f -> f }"#;
+    assert!(analyze_source(source, type_manager, &bump).is_ok());
+}
+
+#[test]
+fn test_as_pattern_errors() {
+    let bump = Bump::new();
+    let type_manager = TypeManager::new(&bump);
+
+    // The whole value and the inner binding are distinct names
+    let err = analyze_source(
+        "some(1) match { some x as x -> x, none -> 0 }",
+        type_manager,
+        &bump,
+    )
+    .unwrap_err();
+    assert!(matches!(err.kind, TypeErrorKind::DuplicateBinding { .. }));
+
+    // Not exhaustive: the binding does not widen the inner pattern
+    let err = analyze_source(
+        "some(1) match { some 1 as one -> 0, none -> 0 }",
+        type_manager,
+        &bump,
+    )
+    .unwrap_err();
+    assert!(matches!(
+        err.kind,
+        TypeErrorKind::NonExhaustivePatterns { .. }
+    ));
+
+    // Unreachable: the binding does not narrow the inner pattern either
+    let warnings = unreachable_pattern_warnings(
+        "some(1) match { some x as whole -> x, some 1 -> 0, none -> 0 }",
+    );
+    assert_eq!(warnings.len(), 1);
+}
+
+/// Analyzes `source`, which must type check, returning the unreachable
+/// patterns reported and the spans of their arms.
+fn unreachable_pattern_warnings(source: &str) -> Vec<(String, parser::Span)> {
+    let bump = Bump::new();
+    let type_manager = TypeManager::new(&bump);
+    let parsed = parser::parse(&bump, source).unwrap();
+    let mut warnings = Vec::new();
+    analyze_with_diagnostics(
+        type_manager,
+        &bump,
+        parsed,
+        &[],
+        &[],
+        AnalyzerOptions::default(),
+        &mut |err| {
+            assert_eq!(err.severity(), crate::api::Severity::Warning);
+            if let TypeErrorKind::UnreachablePattern { pattern } = &err.kind {
+                warnings.push((pattern.clone(), err.span.clone()));
+            }
+        },
+    )
+    .unwrap();
+    warnings
+}
+
+#[test]
+fn test_unreachable_pattern_after_catch_all_some() {
+    let source = r#"some(1) match { some x -> x, some 1 -> 0, none -> 0 }"#;
+    let warnings = unreachable_pattern_warnings(source);
+
+    assert_eq!(warnings.len(), 1);
+    let (pattern, span) = &warnings[0];
+    assert_eq!(pattern, "some 1");
+    assert_eq!(&source[span.0.clone()], "some 1 -> 0");
+}
+
+#[test]
+fn test_unreachable_pattern_is_a_warning() {
+    let source = "42 match { _ -> 1, 42 -> 2 }";
+    let warnings = unreachable_pattern_warnings(source);
+    assert_eq!(warnings.len(), 1);
+    assert_eq!(warnings[0].0, "42");
+
+    let bump = Bump::new();
+    let type_manager = TypeManager::new(&bump);
+    let parsed = parser::parse(&bump, source).unwrap();
+    let mut diagnostics = Vec::new();
+    analyze_with_diagnostics(
+        type_manager,
+        &bump,
+        parsed,
+        &[],
+        &[],
+        AnalyzerOptions::default(),
+        &mut |err| diagnostics.push(err.to_diagnostic()),
+    )
+    .unwrap();
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(diagnostics[0].severity, crate::api::Severity::Warning);
+    assert_eq!(diagnostics[0].code, Some("E023".to_string()));
+}
+
+#[test]
+fn test_unreachable_pattern_cases() {
+    let bump = Bump::new();
+    let type_manager = TypeManager::new(&bump);
+
+    let unreachable = [
+        (r#"1 match { _ -> 0, 1 -> 1 }"#, "1"),
+        (r#"1 match { x -> x, y -> y }"#, "y"),
+        (r#""a" match { "a" -> 0, "a" -> 1, _ -> 2 }"#, r#""a""#),
+        (r#"true match { true -> 0, false -> 1, _ -> 2 }"#, "_"),
+        (r#"some(1) match { none -> 0, some _ -> 1, x -> 2 }"#, "x"),
+        (
+            r#"some(1) match { none -> 0, none -> 1, some x -> x }"#,
+            "none",
+        ),
+    ];
+    for (source, pattern) in unreachable {
+        let warnings = unreachable_pattern_warnings(source);
+        assert!(
+            warnings.iter().any(|(found, _)| found == pattern),
+            "{}: expected '{}' to be unreachable, got {:?}",
+            source,
+            pattern,
+            warnings
+        );
+    }
+
+    let reachable = [
+        r#"some(1) match { some 1 -> 0, some x -> x, none -> 0 }"#,
+        r#"some(some(1)) match { some (some x) -> x, some none -> 0, none -> 0 }"#,
+        r#"true match { true -> 0, _ -> 1 }"#,
+        r#"1 match { 1 -> 0, 2 -> 1, _ -> 2 }"#,
+    ];
+    for source in reachable {
+        let result = analyze_source(source, type_manager, &bump);
+        assert!(result.is_ok(), "{}: {:?}", source, result);
+        assert!(
+            unreachable_pattern_warnings(source).is_empty(),
+            "{}",
+            source
+        );
+    }
+}
+
+#[test]
+fn test_partial_analysis_keeps_types_of_typed_subexpressions() {
+    let bump = Bump::new();
+    let type_manager = TypeManager::new(&bump);
+
+    let parsed = parser::parse(&bump, r#"1 + "a""#).unwrap();
+    let partial = analyze_partial(type_manager, &bump, parsed, &[], &[]);
+
+    assert!(partial.result.is_err());
+    assert_eq!(partial.type_at(0), Some(type_manager.int()));
+    assert_eq!(partial.type_at(5), Some(type_manager.str()));
+    // The failed addition itself has no type.
+    assert_eq!(partial.type_at(2), None);
+}
+
+#[test]
+fn test_partial_analysis_continues_past_type_errors() {
+    let bump = Bump::new();
+    let type_manager = TypeManager::new(&bump);
+
+    // Each case has an ill-typed part, then a part typed after it
+    let where_source = r#"x where { a = 1 + "x", x = 2.5 }"#;
+    let cases = [
+        (r#"(1 + "a") + (2 + 3)"#, "2 + 3", type_manager.int()),
+        (r#"[1 + "a", 2 * 3]"#, "2 * 3", type_manager.int()),
+        (where_source, "2.5", type_manager.float()),
+        (where_source, "x where", type_manager.float()),
+    ];
+    for (source, after, expected) in cases {
+        let parsed = parser::parse(&bump, source).unwrap();
+        let partial = analyze_partial(type_manager, &bump, parsed, &[], &[]);
+
+        assert!(partial.result.is_err(), "{}", source);
+        let offset = source.find(after).unwrap();
+        assert_eq!(partial.type_at(offset), Some(expected), "{}", source);
+    }
+}
+
+#[test]
+fn test_partial_analysis_of_incomplete_expression() {
+    let bump = Bump::new();
+    let type_manager = TypeManager::new(&bump);
+
+    let recovered = parser::parse_with_recovery(&bump, "1 +");
+    assert!(!recovered.errors.is_empty());
+
+    let parsed = recovered.expr.expect("should recover a partial tree");
+    let partial = analyze_partial(type_manager, &bump, parsed, &[], &[]);
+
+    // Hovering the `1` in `1 +` still yields `Int`.
+    assert_eq!(partial.type_at(0), Some(type_manager.int()));
+}
+
+#[test]
+fn test_partial_analysis_matches_analyze_on_success() {
+    let bump = Bump::new();
+    let type_manager = TypeManager::new(&bump);
+
+    let parsed = parser::parse(&bump, "x + 1 where { x = 2 }").unwrap();
+    let partial = analyze_partial(type_manager, &bump, parsed, &[], &[]);
+
+    assert_eq!(partial.type_at(0), Some(type_manager.int()));
+    let typed = partial.result.expect("should type check");
+    assert_eq!(typed.expr.0, type_manager.int());
+}
+
+fn analyze_with_option_promotion<'types, 'arena>(
+    source: &'arena str,
+    type_manager: &'types TypeManager<'types>,
+    arena: &'arena Bump,
+) -> Result<&'arena typed_expr::TypedExpr<'types, 'arena>, TypeError>
+where
+    'types: 'arena,
+{
+    let parsed = parser::parse(arena, source).unwrap();
+    let options = AnalyzerOptions {
+        promote_to_option: true,
+        ..Default::default()
+    };
+    analyze_with_options(type_manager, arena, parsed, &[], &[], options)
+}
+
+#[test]
+fn test_array_option_promotion() {
+    let bump = Bump::new();
+    let type_manager = TypeManager::new(&bump);
+    let expected = type_manager.array(type_manager.option(type_manager.int()));
+
+    let result = analyze_with_option_promotion("[some 1, none]", type_manager, &bump).unwrap();
+    assert_eq!(result.expr.0, expected);
+
+    let result = analyze_with_option_promotion("[1, none, 3]", type_manager, &bump).unwrap();
+    assert_eq!(result.expr.0, expected);
+
+    // Plain elements are wrapped in an explicit `some`.
+    let typed_expr::ExprInner::Array { elements } = &result.expr.1 else {
+        panic!("Expected array expression");
+    };
+    assert!(matches!(
+        elements[0].1,
+        typed_expr::ExprInner::Option { inner: Some(_) }
+    ));
+}
+
+#[test]
+fn test_array_option_promotion_is_opt_in() {
+    let bump = Bump::new();
+    let type_manager = TypeManager::new(&bump);
+
+    let result = analyze_source("[1, none]", type_manager, &bump);
+    assert!(result.is_err());
+}
+
+#[test]
+fn test_array_option_promotion_still_rejects_other_mismatches() {
+    let bump = Bump::new();
+    let type_manager = TypeManager::new(&bump);
+
+    let result = analyze_with_option_promotion("[1, true]", type_manager, &bump);
+    assert!(result.is_err());
+
+    let result = analyze_with_option_promotion(r#"[1, some "a"]"#, type_manager, &bump);
+    assert!(result.is_err());
+}
+
+fn analyze_with_float_division<'types, 'arena>(
+    source: &'arena str,
+    type_manager: &'types TypeManager<'types>,
+    arena: &'arena Bump,
+) -> Result<&'arena typed_expr::TypedExpr<'types, 'arena>, TypeError>
+where
+    'types: 'arena,
+{
+    let parsed = parser::parse(arena, source).unwrap();
+    let options = AnalyzerOptions {
+        float_division: true,
+        ..Default::default()
+    };
+    analyze_with_options(type_manager, arena, parsed, &[], &[], options)
+}
+
+#[test]
+fn test_float_division_of_ints() {
+    let bump = Bump::new();
+    let type_manager = TypeManager::new(&bump);
+
+    let result = analyze_with_float_division("5 / 2", type_manager, &bump).unwrap();
+    assert_eq!(result.expr.0, type_manager.float());
+
+    // Both operands are converted with an implicit cast.
+    let typed_expr::ExprInner::Binary { left, right, .. } = &result.expr.1 else {
+        panic!("Expected binary expression");
+    };
+    assert!(matches!(left.1, typed_expr::ExprInner::Cast { .. }));
+    assert!(matches!(right.1, typed_expr::ExprInner::Cast { .. }));
+
+    // Mixed operands are accepted since the Int side is converted.
+    let result = analyze_with_float_division("5 / 2.0", type_manager, &bump).unwrap();
+    assert_eq!(result.expr.0, type_manager.float());
+}
+
+#[test]
+fn test_float_division_of_unresolved_operands() {
+    let bump = Bump::new();
+    let type_manager = TypeManager::new(&bump);
+
+    // Operands are converted even when their type is only known after
+    // unification.
+    for source in [
+        "((a, b) => a / b)(5, 2)",
+        "((a) => a / 2)(5)",
+        "((a) => a / 2)(5.0)",
+        "half(5) where { half = (a) => a / 2 }",
+        "n / 2 where { n = 5 }",
+    ] {
+        let result = analyze_with_float_division(source, type_manager, &bump).unwrap();
+        assert_eq!(result.expr.0, type_manager.float(), "{}", source);
+    }
+
+    // Operands must still be numeric.
+    let result = analyze_with_float_division(r#"((a) => a / 2)("x")"#, type_manager, &bump);
+    assert!(result.is_err());
+}
+
+#[test]
+fn test_float_division_leaves_other_operators_alone() {
+    let bump = Bump::new();
+    let type_manager = TypeManager::new(&bump);
+
+    let result = analyze_with_float_division("5 * 2 - 1", type_manager, &bump).unwrap();
+    assert_eq!(result.expr.0, type_manager.int());
+
+    let result = analyze_with_float_division("5.0 / 2.0", type_manager, &bump).unwrap();
+    assert_eq!(result.expr.0, type_manager.float());
+}
+
+#[test]
+fn test_float_division_is_opt_in() {
+    let bump = Bump::new();
+    let type_manager = TypeManager::new(&bump);
+
+    let result = analyze_source("5 / 2", type_manager, &bump).unwrap();
+    assert_eq!(result.expr.0, type_manager.int());
+
+    let result = analyze_source("5 / 2.0", type_manager, &bump);
+    assert!(result.is_err());
+}
+
+fn analyze_with_mixed_numeric_comparison<'types, 'arena>(
+    source: &'arena str,
+    type_manager: &'types TypeManager<'types>,
+    arena: &'arena Bump,
+) -> Result<&'arena typed_expr::TypedExpr<'types, 'arena>, TypeError>
+where
+    'types: 'arena,
+{
+    let parsed = parser::parse(arena, source).unwrap();
+    let options = AnalyzerOptions {
+        mixed_numeric_comparison: true,
+        ..Default::default()
+    };
+    analyze_with_options(type_manager, arena, parsed, &[], &[], options)
+}
+
+#[test]
+fn test_mixed_numeric_comparison() {
+    let bump = Bump::new();
+    let type_manager = TypeManager::new(&bump);
+
+    let result = analyze_with_mixed_numeric_comparison("1 == 1.0", type_manager, &bump).unwrap();
+    assert_eq!(result.expr.0, type_manager.bool());
+
+    // Only the Int side is converted.
+    let typed_expr::ExprInner::Comparison { left, right, .. } = &result.expr.1 else {
+        panic!("Expected comparison expression");
+    };
+    assert!(matches!(left.1, typed_expr::ExprInner::Cast { .. }));
+    assert_eq!(left.0, type_manager.float());
+    assert!(matches!(right.1, typed_expr::ExprInner::Constant(_)));
+
+    let result = analyze_with_mixed_numeric_comparison("2.5 > 2", type_manager, &bump).unwrap();
+    let typed_expr::ExprInner::Comparison { left, right, .. } = &result.expr.1 else {
+        panic!("Expected comparison expression");
+    };
+    assert!(matches!(left.1, typed_expr::ExprInner::Constant(_)));
+    assert!(matches!(right.1, typed_expr::ExprInner::Cast { .. }));
+
+    // Comparisons of the same type are left alone.
+    let result = analyze_with_mixed_numeric_comparison("1 < 2", type_manager, &bump).unwrap();
+    let typed_expr::ExprInner::Comparison { left, right, .. } = &result.expr.1 else {
+        panic!("Expected comparison expression");
+    };
+    assert_eq!(left.0, type_manager.int());
+    assert_eq!(right.0, type_manager.int());
+
+    // Other types still have to match.
+    let result = analyze_with_mixed_numeric_comparison(r#"1 == "1""#, type_manager, &bump);
+    assert!(result.is_err());
+}
+
+#[test]
+fn test_mixed_numeric_comparison_of_lambda_params() {
+    let bump = Bump::new();
+    let type_manager = TypeManager::new(&bump);
+
+    // The promotion is decided once the parameter types are known.
+    for source in [
+        "((a) => a < 2.5)(2)",
+        "((a, b) => a < b)(2, 2.5)",
+        "((a, b) => a == b)(1.5, 1)",
+        "[lt(1, 2.5), lt(2.5, 1), lt(1, 2)] where { lt = (a, b) => a < b }",
+        r#"((a, b) => a < b)("a", "b")"#,
+    ] {
+        let result = analyze_with_mixed_numeric_comparison(source, type_manager, &bump);
+        assert!(result.is_ok(), "{}: {:?}", source, result.err());
+    }
+
+    let result =
+        analyze_with_mixed_numeric_comparison("((a) => a < 2.5)(2)", type_manager, &bump).unwrap();
+    let typed_expr::ExprInner::Call { callable, .. } = &result.expr.1 else {
+        panic!("Expected call expression");
+    };
+    let typed_expr::ExprInner::Lambda { body, .. } = &callable.1 else {
+        panic!("Expected lambda");
+    };
+    let typed_expr::ExprInner::Comparison { left, right, .. } = &body.1 else {
+        panic!("Expected comparison expression");
+    };
+    assert_eq!(left.0, type_manager.float());
+    assert_eq!(right.0, type_manager.float());
+
+    // Other types still have to match.
+    for source in [r#"((a) => a < 2.5)("x")"#, r#"((a, b) => a < b)(1, "x")"#] {
+        let result = analyze_with_mixed_numeric_comparison(source, type_manager, &bump);
+        assert!(result.is_err(), "{} should not type-check", source);
+    }
+}
+
+#[test]
+fn test_mixed_numeric_comparison_is_opt_in() {
+    let bump = Bump::new();
+    let type_manager = TypeManager::new(&bump);
+
+    for source in ["1 == 1.0", "2 < 2.5", "2.5 >= 2"] {
+        let result = analyze_source(source, type_manager, &bump);
+        assert!(result.is_err(), "{} should not type-check", source);
+    }
+}
+
+fn analyze_with_string_package<'a>(
+    source: &'a str,
+    type_manager: &'a TypeManager<'a>,
+    arena: &'a Bump,
+) -> Result<&'a typed_expr::TypedExpr<'a, 'a>, TypeError> {
+    let string = crate::stdlib::build_string_package(arena, type_manager).unwrap();
+    let parsed = parser::parse(arena, source).unwrap();
+    analyze(type_manager, arena, parsed, &[("String", string.ty)], &[])
+}
+
+#[test]
+fn test_template_literal_is_checked_against_record() {
+    let bump = Bump::new();
+    let type_manager = TypeManager::new(&bump);
+
+    let result = analyze_with_string_package(
+        r#"String.Template("Hello, {name}! You are {age}.", {name = "Ada", age = 36})"#,
+        type_manager,
+        &bump,
+    )
+    .unwrap();
+    assert_eq!(result.expr.0, type_manager.str());
+}
+
+#[test]
+fn test_template_literal_with_missing_field() {
+    let bump = Bump::new();
+    let type_manager = TypeManager::new(&bump);
+
+    let source = r#"String.Template("Hello, {nmae}!", {name = "Ada"})"#;
+    let err = analyze_with_string_package(source, type_manager, &bump).unwrap_err();
+    match &err.kind {
+        TypeErrorKind::UnknownField {
+            field,
+            available_fields,
+        } => {
+            assert_eq!(field, "nmae");
+            assert_eq!(available_fields, &["name"]);
+        }
+        other => panic!("Expected UnknownField, got {:?}", other),
+    }
+    // The error points at the template literal.
+    assert_eq!(&source[err.span.0], r#""Hello, {nmae}!""#);
+}
+
+#[test]
+fn test_template_literal_with_malformed_placeholder() {
+    let bump = Bump::new();
+    let type_manager = TypeManager::new(&bump);
+
+    let result = analyze_with_string_package(
+        r#"String.Template("{name", {name = 1})"#,
+        type_manager,
+        &bump,
+    );
+    assert!(result.is_err());
+}
+
+#[test]
+fn test_template_values_must_be_a_record() {
+    let bump = Bump::new();
+    let type_manager = TypeManager::new(&bump);
+
+    let result = analyze_with_string_package(r#"String.Template("{x}", 1)"#, type_manager, &bump);
+    assert!(matches!(
+        result.unwrap_err().kind,
+        TypeErrorKind::TypeMismatch { .. }
+    ));
+}
+
+#[test]
+fn test_template_from_non_literal_is_not_checked() {
+    let bump = Bump::new();
+    let type_manager = TypeManager::new(&bump);
+
+    // Only literal templates are known at compile time.
+    let result = analyze_with_string_package(
+        r#"String.Template(t, {name = "Ada"}) where { t = "{nmae}" }"#,
+        type_manager,
+        &bump,
+    );
+    assert!(result.is_ok());
+}
+
+fn analyze_with_unique_map_keys<'types, 'arena>(
+    source: &'arena str,
+    type_manager: &'types TypeManager<'types>,
+    arena: &'arena Bump,
+) -> Result<&'arena typed_expr::TypedExpr<'types, 'arena>, TypeError>
+where
+    'types: 'arena,
+{
+    let parsed = parser::parse(arena, source).unwrap();
+    let options = AnalyzerOptions {
+        duplicate_map_keys: DuplicateKeyPolicy::Error,
+        ..Default::default()
+    };
+    analyze_with_options(type_manager, arena, parsed, &[], &[], options)
+}
+
+#[test]
+fn test_duplicate_literal_map_keys_rejected() {
+    let bump = Bump::new();
+    let type_manager = TypeManager::new(&bump);
+
+    let err = analyze_with_unique_map_keys("{1: 10, 2: 20, 1: 30}", type_manager, &bump)
+        .unwrap_err();
+    assert!(matches!(err.kind, TypeErrorKind::DuplicateMapKey { .. }));
+    // Points at the repeated key.
+    assert_eq!(err.span, parser::Span(15..16));
+
+    let diagnostic = err.to_diagnostic();
+    assert_eq!(diagnostic.code, Some("E021".to_string()));
+    assert!(diagnostic.message.contains("Duplicate map key 1"));
+
+    let err = analyze_with_unique_map_keys(r#"{"a": 1, "a": 2}"#, type_manager, &bump).unwrap_err();
+    assert!(matches!(err.kind, TypeErrorKind::DuplicateMapKey { .. }));
+}
+
+#[test]
+fn test_duplicate_literal_map_keys_allowed_by_default() {
+    let bump = Bump::new();
+    let type_manager = TypeManager::new(&bump);
+
+    let result = analyze_source("{1: 10, 1: 20}", type_manager, &bump).unwrap();
+    let typed_expr::ExprInner::Map { duplicate_keys, .. } = &result.expr.1 else {
+        panic!("Expected map expression");
+    };
+    assert_eq!(*duplicate_keys, DuplicateKeyPolicy::LastWins);
+}
+
+#[test]
+fn test_computed_map_keys_checked_at_runtime() {
+    let bump = Bump::new();
+    let type_manager = TypeManager::new(&bump);
+
+    // Computed keys are only known at runtime, so the map records the policy.
+    let result =
+        analyze_with_unique_map_keys("{a: 1, b: 2} where { a = 1, b = 1 }", type_manager, &bump)
+            .unwrap();
+    let typed_expr::ExprInner::Where { expr, .. } = &result.expr.1 else {
+        panic!("Expected where expression");
+    };
+    let typed_expr::ExprInner::Map { duplicate_keys, .. } = &expr.1 else {
+        panic!("Expected map expression");
+    };
+    assert_eq!(*duplicate_keys, DuplicateKeyPolicy::Error);
+}
+
+/// Analyzes `source` with unused parameter warnings on, returning the
+/// warnings reported.
+fn unused_parameter_warnings(source: &str) -> Vec<crate::api::Diagnostic> {
+    let bump = Bump::new();
+    let type_manager = TypeManager::new(&bump);
+    let parsed = parser::parse(&bump, source).unwrap();
+    let options = AnalyzerOptions {
+        warn_unused_parameters: true,
+        ..Default::default()
+    };
+    let mut warnings = Vec::new();
+    analyze_with_diagnostics(type_manager, &bump, parsed, &[], &[], options, &mut |err| {
+        warnings.push(err.to_diagnostic());
+    })
+    .unwrap();
+    warnings
+}
+
+#[test]
+fn test_unused_lambda_parameter_warns() {
+    let warnings = unused_parameter_warnings("(x, y) => x");
+    assert_eq!(warnings.len(), 1);
+    let warning = &warnings[0];
+    assert_eq!(warning.message, "Unused parameter 'y'");
+    assert_eq!(warning.severity, crate::api::Severity::Warning);
+    assert_eq!(warning.code, Some("E024".to_string()));
+    // Points at the parameter.
+    assert_eq!(warning.span, parser::Span(4..5));
+}
+
+#[test]
+fn test_underscore_parameter_does_not_warn() {
+    assert!(unused_parameter_warnings("(x, _y) => x").is_empty());
+    // A parameter only used by a nested lambda is still used.
+    assert!(unused_parameter_warnings("(x) => (y) => x + y").is_empty());
+    // A parameter shadowed before its only use is not.
+    assert_eq!(
+        unused_parameter_warnings("(x) => (y) => y where { y = 1 }").len(),
+        2
+    );
+}
+
+#[test]
+fn test_unused_lambda_parameter_allowed_by_default() {
+    let bump = Bump::new();
+    let type_manager = TypeManager::new(&bump);
+    let parsed = parser::parse(&bump, "(x, y) => x").unwrap();
+    let mut reported = 0;
+    analyze_with_diagnostics(
+        type_manager,
+        &bump,
+        parsed,
+        &[],
+        &[],
+        AnalyzerOptions::default(),
+        &mut |_| reported += 1,
+    )
+    .unwrap();
+    assert_eq!(reported, 0);
+}
+
+fn analyze_with_array_package<'a>(
+    source: &'a str,
+    type_manager: &'a TypeManager<'a>,
+    arena: &'a Bump,
+) -> Result<&'a typed_expr::TypedExpr<'a, 'a>, TypeError> {
+    let array = crate::stdlib::build_array_package(arena, type_manager).unwrap();
+    let parsed = parser::parse(arena, source).unwrap();
+    analyze(type_manager, arena, parsed, &[("Array", array.ty)], &[])
+}
+
+#[test]
+fn test_array_sort_requires_ord_elements() {
+    let bump = Bump::new();
+    let type_manager = TypeManager::new(&bump);
+
+    let result = analyze_with_array_package("Array.Sort([3, 1, 2])", type_manager, &bump).unwrap();
+    assert_eq!(result.expr.0, type_manager.array(type_manager.int()));
+
+    let result = analyze_with_array_package("Array.Sort([{a = 2}, {a = 1}])", type_manager, &bump);
+    let diagnostic = result.expect_err("records are not Ord").to_diagnostic();
+    assert_eq!(diagnostic.code, Some("E005".to_string()));
+    assert_eq!(
+        diagnostic.message,
+        "Type 'Record[a: Int]' does not implement Ord"
+    );
+
+    // A comparator lifts the restriction
+    let result = analyze_with_array_package(
+        "Array.SortBy([{a = 2}, {a = 1}], (x, y) => true)",
+        type_manager,
+        &bump,
+    );
+    assert!(result.is_ok());
+}
+
+fn analyze_with_map_package<'a>(
+    source: &'a str,
+    type_manager: &'a TypeManager<'a>,
+    arena: &'a Bump,
+) -> Result<&'a typed_expr::TypedExpr<'a, 'a>, TypeError> {
+    let map = crate::stdlib::build_map_package(arena, type_manager).unwrap();
+    let parsed = parser::parse(arena, source).unwrap();
+    analyze(type_manager, arena, parsed, &[("Map", map.ty)], &[])
+}
+
+#[test]
+fn test_map_values_changes_value_type() {
+    let bump = Bump::new();
+    let type_manager = TypeManager::new(&bump);
+
+    let result = analyze_with_map_package(
+        r#"Map.MapValues({"a": 1, "b": 2}, (v) => v > 1)"#,
+        type_manager,
+        &bump,
+    )
+    .unwrap();
+    assert_eq!(
+        result.expr.0,
+        type_manager.map(type_manager.str(), type_manager.bool())
+    );
+
+    let result =
+        analyze_with_map_package("Map.MapValues({1: 2}, (v) => v * 10)", type_manager, &bump)
+            .unwrap();
+    assert_eq!(
+        result.expr.0,
+        type_manager.map(type_manager.int(), type_manager.int())
+    );
+
+    // The function must accept the map's values
+    let result = analyze_with_map_package(
+        r#"Map.MapValues({1: "a"}, (v) => v + 1)"#,
+        type_manager,
+        &bump,
+    );
+    assert!(result.is_err());
+}
+
+fn analyze_with_option_package<'a>(
+    source: &'a str,
+    type_manager: &'a TypeManager<'a>,
+    arena: &'a Bump,
+) -> Result<&'a typed_expr::TypedExpr<'a, 'a>, TypeError> {
+    let option = crate::stdlib::build_option_package(arena, type_manager).unwrap();
+    let parsed = parser::parse(arena, source).unwrap();
+    analyze(type_manager, arena, parsed, &[("Option", option.ty)], &[])
+}
+
+#[test]
+fn test_option_map_infers_result_type() {
+    let bump = Bump::new();
+    let type_manager = TypeManager::new(&bump);
+
+    let result =
+        analyze_with_option_package("Option.Map(some 3, (x) => x > 1)", type_manager, &bump)
+            .unwrap();
+    assert_eq!(result.expr.0, type_manager.option(type_manager.bool()));
+}
+
+#[test]
+fn test_option_get_or_else_unifies_default_with_inner_type() {
+    let bump = Bump::new();
+    let type_manager = TypeManager::new(&bump);
+
+    let result =
+        analyze_with_option_package("Option.GetOrElse(none, 5)", type_manager, &bump).unwrap();
+    assert_eq!(result.expr.0, type_manager.int());
+
+    let result =
+        analyze_with_option_package(r#"Option.GetOrElse(some 1, "a")"#, type_manager, &bump);
+    assert!(matches!(
+        result.unwrap_err().kind,
+        TypeErrorKind::TypeMismatch { .. }
+    ));
+}
+
+#[test]
+fn test_option_filter_requires_bool_predicate() {
+    let bump = Bump::new();
+    let type_manager = TypeManager::new(&bump);
+
+    let result = analyze_with_option_package(
+        r#"Option.Filter(some "a", (s) => s == "a")"#,
+        type_manager,
+        &bump,
+    )
+    .unwrap();
+    assert_eq!(result.expr.0, type_manager.option(type_manager.str()));
+
+    let result =
+        analyze_with_option_package("Option.Filter(some 1, (x) => x + 1)", type_manager, &bump);
+    assert!(result.is_err());
+}
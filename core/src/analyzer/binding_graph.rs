@@ -0,0 +1,261 @@
+//! Dependency graph among the bindings of a `where` expression.
+//!
+//! The graph records which bindings reference which other bindings of the
+//! same `where` block. It is computed on the parsed tree, so it is available
+//! even when the bindings are not in a valid order (and fail to type check).
+//! Tooling can use it to visualize dependencies, and it underpins topological
+//! ordering and cycle detection of bindings.
+
+use crate::{
+    Vec,
+    parser::{Expr, Pattern},
+};
+
+/// Dependency graph of the bindings of a single `where` block.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BindingGraph<'a> {
+    /// Binding names, in source order.
+    names: Vec<&'a str>,
+    /// For each binding, the indices of the bindings it references, in order
+    /// of first reference.
+    edges: Vec<Vec<usize>>,
+}
+
+impl<'a> BindingGraph<'a> {
+    /// Builds the dependency graph for the given `where` bindings.
+    ///
+    /// A binding depends on another if its value references the other's name,
+    /// unless the name is shadowed by an inner lambda parameter, `where`
+    /// binding or match pattern.
+    pub fn new(bindings: &[(&'a str, &'a Expr<'a>)]) -> Self {
+        let names: Vec<&'a str> = bindings.iter().map(|(name, _)| *name).collect();
+        let edges = bindings
+            .iter()
+            .map(|(_, value)| {
+                let mut references = Vec::new();
+                collect_references(value, &mut Vec::new(), &mut references);
+
+                let mut targets = Vec::new();
+                let indices = references
+                    .into_iter()
+                    .filter_map(|reference| names.iter().position(|name| *name == reference));
+                for index in indices {
+                    if !targets.contains(&index) {
+                        targets.push(index);
+                    }
+                }
+                targets
+            })
+            .collect();
+        Self { names, edges }
+    }
+
+    /// Returns the binding names, in source order.
+    pub fn names(&self) -> &[&'a str] {
+        &self.names
+    }
+
+    /// Returns the bindings directly referenced by `name`, or `None` if there
+    /// is no binding with that name.
+    pub fn dependencies(&self, name: &str) -> Option<Vec<&'a str>> {
+        let index = self.index_of(name)?;
+        Some(self.edges[index].iter().map(|&i| self.names[i]).collect())
+    }
+
+    /// Returns a dependency cycle, if any.
+    ///
+    /// The cycle is returned as the sequence of bindings along it, e.g.
+    /// `["a", "b"]` for `a = b, b = a`. Self references yield a single name.
+    pub fn find_cycle(&self) -> Option<Vec<&'a str>> {
+        self.topological_order().err()
+    }
+
+    /// Returns the bindings ordered so that every binding comes after the
+    /// bindings it depends on, or the first cycle found if there is none.
+    ///
+    /// Bindings that are already in a valid order keep their source order.
+    pub fn topological_order(&self) -> Result<Vec<&'a str>, Vec<&'a str>> {
+        let mut state = alloc::vec![VisitState::Unvisited; self.names.len()];
+        let mut path = Vec::new();
+        let mut order = Vec::with_capacity(self.names.len());
+        for index in 0..self.names.len() {
+            self.visit(index, &mut state, &mut path, &mut order)?;
+        }
+        Ok(order.into_iter().map(|i| self.names[i]).collect())
+    }
+
+    fn index_of(&self, name: &str) -> Option<usize> {
+        self.names.iter().position(|n| *n == name)
+    }
+
+    /// Depth-first post-order visit, tracking the current path to report cycles.
+    fn visit(
+        &self,
+        index: usize,
+        state: &mut [VisitState],
+        path: &mut Vec<usize>,
+        order: &mut Vec<usize>,
+    ) -> Result<(), Vec<&'a str>> {
+        match state[index] {
+            VisitState::Done => return Ok(()),
+            VisitState::InProgress => {
+                let start = path.iter().position(|&i| i == index).unwrap_or(0);
+                return Err(path[start..].iter().map(|&i| self.names[i]).collect());
+            }
+            VisitState::Unvisited => {}
+        }
+
+        state[index] = VisitState::InProgress;
+        path.push(index);
+        for &dependency in &self.edges[index] {
+            self.visit(dependency, state, path, order)?;
+        }
+        path.pop();
+        state[index] = VisitState::Done;
+        order.push(index);
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum VisitState {
+    Unvisited,
+    InProgress,
+    Done,
+}
+
+/// Returns the free identifiers referenced by `expr`, in order of first
+/// reference.
+pub(crate) fn free_variables<'a>(expr: &'a Expr<'a>) -> Vec<&'a str> {
+    let mut references = Vec::new();
+    collect_references(expr, &mut Vec::new(), &mut references);
+
+    let mut unique = Vec::new();
+    for reference in references {
+        if !unique.contains(&reference) {
+            unique.push(reference);
+        }
+    }
+    unique
+}
+
+/// Collects the free identifiers referenced by `expr`, skipping names bound
+/// by enclosing lambdas, `where` bindings and match patterns (`bound`).
+fn collect_references<'a>(expr: &'a Expr<'a>, bound: &mut Vec<&'a str>, out: &mut Vec<&'a str>) {
+    match expr {
+        Expr::Ident(name) => {
+            if !bound.contains(name) {
+                out.push(name);
+            }
+        }
+        Expr::Binary { left, right, .. }
+        | Expr::Boolean { left, right, .. }
+        | Expr::Comparison { left, right, .. } => {
+            collect_references(left, bound, out);
+            collect_references(right, bound, out);
+        }
+        Expr::Unary { expr, .. } | Expr::Cast { expr, .. } => {
+            collect_references(expr, bound, out);
+        }
+        Expr::Field { value, .. } => collect_references(value, bound, out),
+        Expr::Call { callable, args } => {
+            collect_references(callable, bound, out);
+            for arg in args.iter() {
+                collect_references(arg, bound, out);
+            }
+        }
+        Expr::Index { value, index } => {
+            collect_references(value, bound, out);
+            collect_references(index, bound, out);
+        }
+        Expr::Slice { value, start, end } => {
+            collect_references(value, bound, out);
+            for bound_expr in start.iter().chain(end.iter()) {
+                collect_references(bound_expr, bound, out);
+            }
+        }
+        Expr::Lambda { params, body } => {
+            let depth = bound.len();
+            bound.extend(params.iter().copied());
+            collect_references(body, bound, out);
+            bound.truncate(depth);
+        }
+        Expr::If {
+            cond,
+            then_branch,
+            else_branch,
+        } => {
+            collect_references(cond, bound, out);
+            collect_references(then_branch, bound, out);
+            collect_references(else_branch, bound, out);
+        }
+        Expr::Where { expr, bindings, .. } => {
+            // Bindings are sequential: each one sees the previous ones.
+            let depth = bound.len();
+            for (name, value) in bindings.iter() {
+                collect_references(value, bound, out);
+                bound.push(name);
+            }
+            collect_references(expr, bound, out);
+            bound.truncate(depth);
+        }
+        Expr::Otherwise { primary, fallback } => {
+            collect_references(primary, bound, out);
+            collect_references(fallback, bound, out);
+        }
+        Expr::Coalesce { option, default } => {
+            collect_references(option, bound, out);
+            collect_references(default, bound, out);
+        }
+        Expr::Option { inner } => {
+            if let Some(inner) = inner {
+                collect_references(inner, bound, out);
+            }
+        }
+        Expr::Match { expr, arms } => {
+            collect_references(expr, bound, out);
+            for arm in arms.iter() {
+                let depth = bound.len();
+                bind_pattern(arm.pattern, bound);
+                collect_references(arm.body, bound, out);
+                bound.truncate(depth);
+            }
+        }
+        Expr::Record(fields) => {
+            for (_, value) in fields.iter() {
+                collect_references(value, bound, out);
+            }
+        }
+        Expr::Map(entries) => {
+            for (key, value) in entries.iter() {
+                collect_references(key, bound, out);
+                collect_references(value, bound, out);
+            }
+        }
+        Expr::Array(elements) => {
+            for element in elements.iter() {
+                collect_references(element, bound, out);
+            }
+        }
+        Expr::FormatStr { exprs, .. } => {
+            for expr in exprs.iter() {
+                collect_references(expr, bound, out);
+            }
+        }
+        // Literal suffixes are units, not variable references.
+        Expr::Literal(_) => {}
+    }
+}
+
+/// Adds the variables bound by `pattern` to `bound`.
+fn bind_pattern<'a>(pattern: &'a Pattern<'a>, bound: &mut Vec<&'a str>) {
+    match pattern {
+        Pattern::Var(name) => bound.push(name),
+        Pattern::Some(inner) => bind_pattern(inner, bound),
+        Pattern::As { pattern, name } => {
+            bind_pattern(pattern, bound);
+            bound.push(name);
+        }
+        Pattern::Wildcard | Pattern::Literal(_) | Pattern::None => {}
+    }
+}
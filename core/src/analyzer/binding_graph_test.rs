@@ -0,0 +1,75 @@
+use super::BindingGraph;
+use crate::parser::{self, Expr};
+use bumpalo::Bump;
+
+/// Parses `source`, which must be a `where` expression, and builds the
+/// dependency graph of its bindings.
+fn graph_of<'a>(arena: &'a Bump, source: &'a str) -> BindingGraph<'a> {
+    let parsed = parser::parse(arena, source).unwrap();
+    let Expr::Where { bindings, .. } = parsed.expr else {
+        panic!("Expected a where expression, got {:?}", parsed.expr);
+    };
+    BindingGraph::new(bindings)
+}
+
+#[test]
+fn test_chain_has_no_cycle() {
+    let arena = Bump::new();
+    let graph = graph_of(&arena, "a where { a = b, b = c, c = 1 }");
+
+    assert_eq!(graph.names(), ["a", "b", "c"]);
+    assert_eq!(graph.dependencies("a"), Some(vec!["b"]));
+    assert_eq!(graph.dependencies("b"), Some(vec!["c"]));
+    assert_eq!(graph.dependencies("c"), Some(vec![]));
+    assert_eq!(graph.dependencies("d"), None);
+
+    assert_eq!(graph.find_cycle(), None);
+    assert_eq!(graph.topological_order(), Ok(vec!["c", "b", "a"]));
+}
+
+#[test]
+fn test_mutual_reference_is_a_cycle() {
+    let arena = Bump::new();
+    let graph = graph_of(&arena, "a where { a = b, b = a }");
+
+    assert_eq!(graph.find_cycle(), Some(vec!["a", "b"]));
+    assert!(graph.topological_order().is_err());
+}
+
+#[test]
+fn test_self_reference_is_a_cycle() {
+    let arena = Bump::new();
+    let graph = graph_of(&arena, "x where { x = x + 1 }");
+
+    assert_eq!(graph.find_cycle(), Some(vec!["x"]));
+}
+
+#[test]
+fn test_valid_order_is_preserved() {
+    let arena = Bump::new();
+    let graph = graph_of(&arena, "z where { x = 1, y = x * 2, z = x + y }");
+
+    assert_eq!(graph.dependencies("z"), Some(vec!["x", "y"]));
+    assert_eq!(graph.topological_order(), Ok(vec!["x", "y", "z"]));
+}
+
+#[test]
+fn test_shadowed_names_are_not_dependencies() {
+    let arena = Bump::new();
+    let graph = graph_of(
+        &arena,
+        "r where { \
+            f = (a) => a + 1, \
+            g = b where { b = 2 }, \
+            h = c match { some a -> a, none -> 0 }, \
+            a = 1, b = 2, c = some 3, \
+            r = f(a) \
+        }",
+    );
+
+    assert_eq!(graph.dependencies("f"), Some(vec![]));
+    assert_eq!(graph.dependencies("g"), Some(vec![]));
+    assert_eq!(graph.dependencies("h"), Some(vec!["c"]));
+    assert_eq!(graph.dependencies("r"), Some(vec!["f", "a"]));
+    assert_eq!(graph.find_cycle(), None);
+}
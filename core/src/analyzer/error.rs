@@ -62,13 +62,13 @@ pub enum TypeErrorKind {
     FunctionParamCountMismatch { expected: usize, found: usize },
     /// Cannot index into a non-indexable type
     NotIndexable { ty: String },
+    /// Cannot slice a type other than arrays and strings
+    NotSliceable { ty: String },
     /// Field does not exist on record
     UnknownField {
         field: String,
         available_fields: Vec<String>,
     },
-    /// Cannot infer record type for field access
-    CannotInferRecordType { field: String },
     /// Tried to access field on non-record type
     NotARecord { ty: String, field: String },
     /// Invalid type expression in cast
@@ -85,6 +85,10 @@ pub enum TypeErrorKind {
     DuplicateParameter { name: String },
     /// Duplicate binding name in where clause
     DuplicateBinding { name: String },
+    /// Type alias whose definition refers back to itself
+    RecursiveTypeAlias { name: String },
+    /// Map literal repeats a constant key
+    DuplicateMapKey { key: String },
     /// Type is not formattable in format string
     NotFormattable { ty: String },
     /// Unsupported language feature
@@ -94,6 +98,10 @@ pub enum TypeErrorKind {
         ty: String,
         missing_cases: Vec<String>,
     },
+    /// Match arm that can never be reached (a warning)
+    UnreachablePattern { pattern: String },
+    /// Lambda parameter never used in the body (a warning)
+    UnusedParameter { name: String },
     /// Generic type error (catch-all for other errors)
     Other { message: String },
 }
@@ -109,6 +117,16 @@ impl TypeError {
         }
     }
 
+    /// Whether this error fails the analysis or is only a warning.
+    pub fn severity(&self) -> Severity {
+        match self.kind {
+            TypeErrorKind::UnreachablePattern { .. } | TypeErrorKind::UnusedParameter { .. } => {
+                Severity::Warning
+            }
+            _ => Severity::Error,
+        }
+    }
+
     /// Convert to a Diagnostic for API boundary
     pub fn to_diagnostic(&self) -> Diagnostic {
         let (message, code, help) = match &self.kind {
@@ -204,6 +222,11 @@ impl TypeError {
                 Some("E009"),
                 vec!["Only arrays, maps, and bytes can be indexed".to_string()],
             ),
+            TypeErrorKind::NotSliceable { ty, .. } => (
+                format!("Cannot slice non-sliceable type '{}'", ty),
+                Some("E022"),
+                vec!["Only arrays and strings can be sliced".to_string()],
+            ),
             TypeErrorKind::UnknownField {
                 field,
                 available_fields,
@@ -217,14 +240,6 @@ impl TypeError {
                 Some("E010"),
                 vec!["Check the field name for typos".to_string()],
             ),
-            TypeErrorKind::CannotInferRecordType { field, .. } => (
-                format!(
-                    "Cannot infer record type for field access '.{}'. Row polymorphism not yet supported",
-                    field
-                ),
-                Some("E011"),
-                vec!["The value must have a concrete record type. Consider restructuring the code to avoid accessing fields on polymorphic values.".to_string()],
-            ),
             TypeErrorKind::NotARecord { ty, field, .. } => (
                 format!(
                     "Cannot access field '{}' on non-record type '{}'",
@@ -249,8 +264,8 @@ impl TypeError {
                 format!("Cannot cast polymorphic value to '{}'", target_type),
                 Some("E019"),
                 vec![
-                    "Casts on polymorphic types are not yet supported.".to_string(),
-                    "The value must have a concrete type to be cast.".to_string(),
+                    "The type of the value being cast could not be inferred.".to_string(),
+                    "The value must have a concrete type to be cast, for example by applying the lambda to an argument.".to_string(),
                 ],
             ),
             TypeErrorKind::DuplicateParameter { name, .. } => (
@@ -263,6 +278,19 @@ impl TypeError {
                 Some("E016"),
                 vec!["Each binding in a where clause must have a unique name".to_string()],
             ),
+            TypeErrorKind::RecursiveTypeAlias { name, .. } => (
+                format!("Recursive type alias '{}'", name),
+                Some("E025"),
+                vec![
+                    "A type alias cannot refer to itself, directly or through other aliases"
+                        .to_string(),
+                ],
+            ),
+            TypeErrorKind::DuplicateMapKey { key, .. } => (
+                format!("Duplicate map key {}", key),
+                Some("E021"),
+                vec!["Each key in a map literal must be unique".to_string()],
+            ),
             TypeErrorKind::NotFormattable { ty, .. } => (
                 format!("Cannot format type '{}' in format string", ty),
                 Some("E017"),
@@ -285,11 +313,24 @@ impl TypeError {
                 Some("E020"),
                 vec![format!("Missing cases: {}", missing_cases.join(", "))],
             ),
+            TypeErrorKind::UnreachablePattern { pattern, .. } => (
+                format!("Unreachable pattern '{}'", pattern),
+                Some("E023"),
+                vec!["Earlier arms already match every value this arm matches".to_string()],
+            ),
+            TypeErrorKind::UnusedParameter { name, .. } => (
+                format!("Unused parameter '{}'", name),
+                Some("E024"),
+                vec![format!(
+                    "Prefix the name with an underscore ('_{}') if it is intentionally unused",
+                    name
+                )],
+            ),
             TypeErrorKind::Other { message, .. } => (message.clone(), Some("E999"), vec![]),
         };
 
         Diagnostic {
-            severity: Severity::Error,
+            severity: self.severity(),
             message,
             span: self.span.clone(),
             related: self
@@ -1,9 +1,150 @@
 pub mod analyzer;
+mod binding_graph;
 pub mod typed_expr;
 pub mod error;
 
 #[cfg(test)]
 mod analyzer_test;
 
-pub use analyzer::analyze;
+#[cfg(test)]
+mod binding_graph_test;
+
+pub use analyzer::{
+    AnalysisContext, GlobalScope, PartialAnalysis, analyze, analyze_partial,
+    analyze_partial_with_global_scope, analyze_with_diagnostics, analyze_with_global_scope,
+    analyze_with_options,
+};
+pub use binding_graph::BindingGraph;
+pub(crate) use binding_graph::free_variables;
 pub use error::{TypeError, TypeErrorKind};
+
+use crate::types::Type;
+
+/// Options for configuring the analyzer.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct AnalyzerOptions {
+    /// Accept array literals mixing `Option[T]` and `T` elements by wrapping
+    /// the plain elements in `some`, so `[1, none]` is an `Array[Option[Int]]`.
+    ///
+    /// Off by default: arrays are otherwise strictly homogeneous.
+    pub promote_to_option: bool,
+
+    /// Make `/` on `Int` operands convert them to `Float` first, so `5 / 2`
+    /// is `2.5`. Integer division stays available via `Int.Quot` and `Int.Div`.
+    ///
+    /// Off by default: `/` on `Int` operands is integer division, rounded as
+    /// set by `integer_division`.
+    pub float_division: bool,
+
+    /// How `/` and `%` on `Int` operands round when an operand is negative.
+    pub integer_division: IntegerDivision,
+
+    /// Let comparisons mix `Int` and `Float` operands by converting the
+    /// `Int` one to `Float` first, so `1 == 1.0` is `true` and `2 < 2.5`
+    /// type-checks.
+    ///
+    /// Off by default: both operands of a comparison must have the same type.
+    pub mixed_numeric_comparison: bool,
+
+    /// What to do when a map is built with the same key more than once.
+    pub duplicate_map_keys: DuplicateKeyPolicy,
+
+    /// Whether function call arguments may differ from the declared
+    /// parameter types when they can be converted.
+    pub argument_coercion: ArgumentCoercion,
+
+    /// Report a warning for each lambda parameter that the lambda's body
+    /// never uses, as `y` in `(x, y) => x`. Parameters whose name starts
+    /// with `_` are exempt.
+    ///
+    /// Off by default. Warnings never fail the analysis.
+    pub warn_unused_parameters: bool,
+}
+
+/// How maps handle entries that repeat a key, as in `{1: 10, 1: 20}`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum DuplicateKeyPolicy {
+    /// Keep the value of the last entry, so `{1: 10, 1: 20}` is `{1: 20}`.
+    #[default]
+    LastWins,
+
+    /// Reject duplicate keys: repeated literal keys are a compile-time error,
+    /// and computed keys that collide fail at runtime.
+    Error,
+}
+
+/// How `/` and `%` on `Int` operands round, which only matters when an
+/// operand is negative. Every mode satisfies `a == (a / b) * b + a % b`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum IntegerDivision {
+    /// The remainder is never negative: `-7 / 2` is `-4` and `-7 % 2` is `1`,
+    /// while `7 / -2` is `-3` and `7 % -2` is `1`.
+    #[default]
+    Euclidean,
+
+    /// The quotient rounds toward zero, as in Rust and C: `-7 / 2` is `-3`
+    /// and `-7 % 2` is `-1`. The remainder has the sign of the dividend.
+    Truncated,
+
+    /// The quotient rounds toward negative infinity, as in Python: `-7 / 2`
+    /// is `-4` and `-7 % 2` is `1`. The remainder has the sign of the divisor.
+    Floored,
+}
+
+impl IntegerDivision {
+    /// The quotient of `a / b`.
+    ///
+    /// `b` must not be zero, and `a / b` must not be `i64::MIN / -1`.
+    pub fn quotient(self, a: i64, b: i64) -> i64 {
+        match self {
+            IntegerDivision::Euclidean => a.div_euclid(b),
+            IntegerDivision::Truncated => a / b,
+            IntegerDivision::Floored => {
+                let quotient = a / b;
+                if a % b != 0 && (a < 0) != (b < 0) {
+                    quotient - 1
+                } else {
+                    quotient
+                }
+            }
+        }
+    }
+
+    /// The remainder of `a / b`, under the same conditions as
+    /// [`quotient`](Self::quotient).
+    pub fn remainder(self, a: i64, b: i64) -> i64 {
+        match self {
+            IntegerDivision::Euclidean => a.rem_euclid(b),
+            IntegerDivision::Truncated => a % b,
+            IntegerDivision::Floored => {
+                let remainder = a % b;
+                if remainder != 0 && (remainder < 0) != (b < 0) {
+                    remainder + b
+                } else {
+                    remainder
+                }
+            }
+        }
+    }
+}
+
+/// How function calls treat arguments whose type differs from the parameter
+/// type declared by the function, as in `Math.Sqrt(4)`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum ArgumentCoercion {
+    /// Arguments must have exactly the declared parameter types.
+    #[default]
+    Strict,
+
+    /// Accept an `Int` argument for a `Float` parameter, converting it when
+    /// the function is called.
+    Lenient,
+}
+
+impl ArgumentCoercion {
+    /// Whether an argument of type `from` is accepted for a parameter of type
+    /// `to` (both fully resolved) and converted at the call.
+    pub fn allows(self, from: &Type<'_>, to: &Type<'_>) -> bool {
+        self == ArgumentCoercion::Lenient && matches!((from, to), (Type::Int, Type::Float))
+    }
+}
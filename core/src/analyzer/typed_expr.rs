@@ -1,8 +1,10 @@
 use crate::{
+    analyzer::{ArgumentCoercion, DuplicateKeyPolicy, IntegerDivision},
     parser::{AnnotatedSource, BinaryOp, BoolOp, ComparisonOp, UnaryOp},
     types::{
         Type,
         traits::{TypeKind, TypeView},
+        constraint_set::TypeClassConstraint,
         type_class::TypeClassId,
     },
     values::dynamic::Value,
@@ -33,6 +35,9 @@ pub struct TypedExpr<'types, 'arena> {
     /// This tracks how polymorphic lambdas are instantiated at different call sites
     /// Uses arena allocation to avoid leaks since TypedExpr is arena-allocated
     pub lambda_instantiations: HashMap<*const Expr<'types, 'arena>, LambdaInstantiations<'types, 'arena>, DefaultHashBuilder, &'arena bumpalo::Bump>,
+    /// Type class constraints on the type variables of the expression's type,
+    /// such as `Numeric` for `(a, b) => a + b`, without spans
+    pub constraints: &'arena [TypeClassConstraint<'types>],
 }
 
 #[derive(Debug, Clone)]
@@ -70,6 +75,8 @@ pub enum ExprInner<'types, 'arena> {
         op: BinaryOp,
         left: &'arena Expr<'types, 'arena>,
         right: &'arena Expr<'types, 'arena>,
+        /// How `/` and `%` round on `Int` operands; ignored otherwise.
+        division: IntegerDivision,
     },
     Boolean {
         op: BoolOp,
@@ -88,11 +95,20 @@ pub enum ExprInner<'types, 'arena> {
     Call {
         callable: &'arena Expr<'types, 'arena>,
         args: &'arena [&'arena Expr<'types, 'arena>],
+        /// How arguments whose type differs from the declared parameter
+        /// type are converted when the function is called.
+        coercion: ArgumentCoercion,
     },
     Index {
         value: &'arena Expr<'types, 'arena>,
         index: &'arena Expr<'types, 'arena>,
     },
+    /// Slice of an array or string, where either bound may be omitted
+    Slice {
+        value: &'arena Expr<'types, 'arena>,
+        start: Option<&'arena Expr<'types, 'arena>>,
+        end: Option<&'arena Expr<'types, 'arena>>,
+    },
     Field {
         value: &'arena Expr<'types, 'arena>,
         field: &'arena str,
@@ -118,6 +134,11 @@ pub enum ExprInner<'types, 'arena> {
         primary: &'arena Expr<'types, 'arena>,
         fallback: &'arena Expr<'types, 'arena>,
     },
+    /// Option default: the inner value of `option`, or `default` if it is none
+    Coalesce {
+        option: &'arena Expr<'types, 'arena>,
+        default: &'arena Expr<'types, 'arena>,
+    },
     /// Option constructor
     Option {
         inner: Option<&'arena Expr<'types, 'arena>>,
@@ -132,6 +153,8 @@ pub enum ExprInner<'types, 'arena> {
     },
     Map {
         elements: &'arena [(&'arena Expr<'types, 'arena>, &'arena Expr<'types, 'arena>)],
+        /// How entries with the same key are handled when the map is built.
+        duplicate_keys: DuplicateKeyPolicy,
     },
     Array {
         elements: &'arena [&'arena Expr<'types, 'arena>],
@@ -158,6 +181,11 @@ pub enum TypedPattern<'types, 'arena> {
     Some(&'arena TypedPattern<'types, 'arena>),
     /// None pattern `none` - matches Option::None
     None,
+    /// Binding pattern `p as name` - matches `p` and also binds the whole value to a name
+    As {
+        pattern: &'arena TypedPattern<'types, 'arena>,
+        name: &'arena str,
+    },
 }
 
 /// A single arm in a typed match expression.
@@ -0,0 +1,467 @@
+//! Owned artifacts of compiled expressions that can be moved across threads.
+
+use super::{
+    CompilationStats, CompileOptions, CompiledExpression, Engine, Error, RunOptions,
+    environment::LazyGlobal, purity::is_value_deterministic,
+};
+use crate::parser::{Attribute, AttributeValue, Span};
+use crate::types::{Type, manager::TypeManager};
+use crate::values::{RandomState, StepBudget, dynamic::Value};
+use crate::vm::{Code, VM};
+use crate::{String, ToString, Vec, format};
+use bumpalo::Bump;
+
+/// A compiled expression detached from the engine that produced it.
+///
+/// Unlike [`CompiledExpression`], an artifact owns all of its data, so it is
+/// `Send` and `Sync` and can be shipped to worker threads. It holds the
+/// expression compiled to serialized bytecode (see
+/// [`Code::to_bytes`]), together with the table of names the code is bound
+/// to: its parameters, which take the first locals, and the globals it
+/// references, which follow them. Globals are not part of the code: their
+/// types are recorded, and [`bind`](Self::bind) resolves them by name
+/// against the target engine's environment, failing if a global is missing
+/// or has a different type.
+///
+/// Binding decodes and verifies the bytecode, but does not parse, type check
+/// or compile the expression again, and the bound expression runs on the
+/// bytecode VM. Expressions the bytecode compiler does not support, or whose
+/// code holds values with no portable form, cannot be made into artifacts.
+///
+/// A rule compiled with [`Engine::compile_module`] carries the code of the
+/// rules it depends on, which are bound along with it.
+///
+/// # Example
+///
+/// ```
+/// use melbi_core::api::{Engine, EngineOptions};
+/// use melbi_core::values::dynamic::Value;
+/// use bumpalo::Bump;
+///
+/// let arena = Bump::new();
+/// let engine = Engine::new(EngineOptions::default(), &arena, |_arena, type_mgr, env| {
+///     env.register("base", Value::int(type_mgr, 40)).unwrap();
+/// });
+/// let int_ty = engine.type_manager().int();
+/// let artifact = engine
+///     .compile(Default::default(), "base + x", &[("x", int_ty)])
+///     .unwrap()
+///     .to_artifact()
+///     .unwrap();
+///
+/// let handle = std::thread::spawn(move || {
+///     let arena = Bump::new();
+///     let engine = Engine::new(EngineOptions::default(), &arena, |_arena, type_mgr, env| {
+///         env.register("base", Value::int(type_mgr, 40)).unwrap();
+///     });
+///     let expr = artifact.bind(&engine).unwrap();
+///     let val_arena = Bump::new();
+///     let x = Value::int(engine.type_manager(), 2);
+///     expr.run(Default::default(), &val_arena, &[x]).unwrap().as_int().unwrap()
+/// });
+/// assert_eq!(handle.join().unwrap(), 42);
+/// ```
+#[derive(Debug, Clone)]
+pub struct CompiledArtifact {
+    source: String,
+    /// Parameter names and serialized types, in declaration order.
+    params: Vec<(String, Vec<u8>)>,
+    /// Referenced globals and their serialized types.
+    globals: Vec<(String, Vec<u8>)>,
+    /// Code of the module rules the expression depends on, in dependency
+    /// order.
+    rules: Vec<ArtifactCode>,
+    /// Code of the expression itself.
+    code: ArtifactCode,
+    attributes: Vec<(String, ArtifactAttribute, Span)>,
+    compile_options: CompileOptions,
+    estimated_cost: u64,
+}
+
+static_assertions::assert_impl_all!(CompiledArtifact: Send, Sync);
+
+/// Serialized bytecode of the expression or of one of its rules, with the
+/// names bound to its locals.
+///
+/// The code runs with the parameters in its first locals, followed by the
+/// results of `rule_bindings`, then the values of `globals`.
+#[derive(Debug, Clone)]
+struct ArtifactCode {
+    /// Bytecode, as written by [`Code::to_bytes`].
+    bytecode: Vec<u8>,
+    /// Serialized type of the value the code returns.
+    return_type: Vec<u8>,
+    /// Rules the code references, as indices into the artifact's rules.
+    rule_bindings: Vec<usize>,
+    /// Globals the code references, as indices into the artifact's globals.
+    globals: Vec<usize>,
+}
+
+/// Owned form of an [`AttributeValue`].
+#[derive(Debug, Clone)]
+enum ArtifactAttribute {
+    Bool(bool),
+    Int(i64),
+    Float(f64),
+    Str(String),
+}
+
+impl CompiledArtifact {
+    pub(crate) fn from_expression(expr: &CompiledExpression<'_>) -> Result<Self, Error> {
+        let params = expr
+            .parameters()
+            .iter()
+            .map(|(name, ty)| Ok((name.to_string(), serialize_type(ty)?)))
+            .collect::<Result<_, Error>>()?;
+
+        let mut globals: Vec<(String, Vec<u8>)> = Vec::new();
+        let mut code_of = |rule: &CompiledExpression<'_>| -> Result<ArtifactCode, Error> {
+            let referenced = rule.referenced_globals();
+            let mut indices = Vec::with_capacity(referenced.len());
+            for (name, ty) in &referenced {
+                let index = match globals.iter().position(|(global, _)| global == name) {
+                    Some(index) => index,
+                    None => {
+                        globals.push((name.to_string(), serialize_type(ty)?));
+                        globals.len() - 1
+                    }
+                };
+                indices.push(index);
+            }
+            let names: Vec<&str> = referenced.iter().map(|(name, _)| *name).collect();
+            let rule_bindings = rule
+                .rule_bindings()
+                .iter()
+                .map(|name| {
+                    expr.dependencies()
+                        .iter()
+                        .position(|(dependency, _)| dependency == name)
+                        .expect("rules bound by name are dependencies")
+                })
+                .collect();
+            Ok(ArtifactCode {
+                bytecode: rule.to_portable_bytecode(&names)?,
+                return_type: serialize_type(rule.return_type())?,
+                rule_bindings,
+                globals: indices,
+            })
+        };
+        let rules = expr
+            .dependencies()
+            .iter()
+            .map(|(_, rule)| code_of(rule))
+            .collect::<Result<_, Error>>()?;
+        let code = code_of(expr)?;
+
+        let attributes = expr
+            .attributes()
+            .iter()
+            .map(|attribute| {
+                let value = match &attribute.value {
+                    AttributeValue::Bool(value) => ArtifactAttribute::Bool(*value),
+                    AttributeValue::Int(value) => ArtifactAttribute::Int(*value),
+                    AttributeValue::Float(value) => ArtifactAttribute::Float(*value),
+                    AttributeValue::Str(value) => ArtifactAttribute::Str(value.to_string()),
+                };
+                (attribute.name.to_string(), value, attribute.span.clone())
+            })
+            .collect();
+
+        Ok(Self {
+            source: expr.source().to_string(),
+            params,
+            globals,
+            rules,
+            code,
+            attributes,
+            compile_options: expr.compile_options().clone(),
+            estimated_cost: expr.estimated_cost(),
+        })
+    }
+
+    /// Get the source code of the expression.
+    pub fn source(&self) -> &str {
+        &self.source
+    }
+
+    /// Get the names of the expression's parameters.
+    pub fn param_names(&self) -> impl Iterator<Item = &str> {
+        self.params.iter().map(|(name, _)| name.as_str())
+    }
+
+    /// Get the names of the globals the expression references.
+    pub fn global_names(&self) -> impl Iterator<Item = &str> {
+        self.globals.iter().map(|(name, _)| name.as_str())
+    }
+
+    /// Bind the artifact to an engine, producing an expression ready to run.
+    ///
+    /// Resolves the referenced globals by name in the engine's environment
+    /// and decodes the bytecode, without compiling the expression again.
+    /// Fails if the engine's environment lacks one of the referenced globals
+    /// or defines it with a different type. Lazily registered globals are
+    /// built when the expression first runs, as for compiled expressions.
+    pub fn bind<'arena>(
+        &self,
+        engine: &Engine<'arena>,
+    ) -> Result<CompiledExpression<'arena>, Error> {
+        let type_mgr = engine.type_manager();
+        let arena = engine.arena();
+
+        let globals = self
+            .globals
+            .iter()
+            .map(|(name, encoded)| {
+                let expected = deserialize_type(type_mgr, encoded)?;
+                let global_ty = engine.global_type(name).ok_or_else(|| {
+                    Error::Api(format!(
+                        "Global `{}` is not defined in this environment",
+                        name
+                    ))
+                })?;
+                if !core::ptr::eq(global_ty, expected) {
+                    return Err(Error::Api(format!(
+                        "Global `{}` has type {} in this environment, expected {}",
+                        name, global_ty, expected
+                    )));
+                }
+                match engine.lazy_global(name) {
+                    Some(global) => Ok(BoundGlobal::Lazy(global)),
+                    None => Ok(BoundGlobal::Eager(
+                        engine
+                            .global_value(name)
+                            .expect("globals with a type have a value"),
+                    )),
+                }
+            })
+            .collect::<Result<Vec<_>, Error>>()?;
+
+        let params = self
+            .params
+            .iter()
+            .map(|(name, encoded)| {
+                Ok((
+                    &*arena.alloc_str(name),
+                    deserialize_type(type_mgr, encoded)?,
+                ))
+            })
+            .collect::<Result<Vec<_>, Error>>()?;
+        let param_types: Vec<_> = params.iter().map(|(_, ty)| *ty).collect();
+
+        let mut rules = Vec::with_capacity(self.rules.len());
+        for rule in &self.rules {
+            let rule = rule.bind(engine, &param_types, &rules, &globals)?;
+            rules.push(rule);
+        }
+        let code = self.code.bind(engine, &param_types, &rules, &globals)?;
+
+        let attributes: Vec<_> = self
+            .attributes
+            .iter()
+            .map(|(name, value, span)| Attribute {
+                name: arena.alloc_str(name),
+                value: match value {
+                    ArtifactAttribute::Bool(value) => AttributeValue::Bool(*value),
+                    ArtifactAttribute::Int(value) => AttributeValue::Int(*value),
+                    ArtifactAttribute::Float(value) => AttributeValue::Float(*value),
+                    ArtifactAttribute::Str(value) => AttributeValue::Str(arena.alloc_str(value)),
+                },
+                span: span.clone(),
+            })
+            .collect();
+
+        let program = BoundProgram {
+            artifact: self.clone(),
+            source: arena.alloc_str(&self.source),
+            rules,
+            code,
+        };
+        Ok(CompiledExpression::from_bound(
+            program,
+            type_mgr,
+            arena.alloc_slice_copy(&params),
+            self.compile_options.clone(),
+            engine.options().default_run_options,
+        )
+        .with_attributes(arena.alloc_slice_fill_iter(attributes)))
+    }
+}
+
+impl ArtifactCode {
+    /// Decode the code, with `rules` the rules bound before it and `globals`
+    /// the artifact's globals resolved in the engine.
+    fn bind<'arena>(
+        &self,
+        engine: &Engine<'arena>,
+        param_types: &[&'arena Type<'arena>],
+        rules: &[BoundCode<'arena>],
+        globals: &[BoundGlobal<'arena>],
+    ) -> Result<BoundCode<'arena>, Error> {
+        let type_mgr = engine.type_manager();
+        let globals: Vec<_> = self.globals.iter().map(|&global| globals[global]).collect();
+        let local_types: Vec<_> = param_types
+            .iter()
+            .copied()
+            .chain(
+                self.rule_bindings
+                    .iter()
+                    .map(|&rule| rules[rule].return_type),
+            )
+            .chain(globals.iter().map(BoundGlobal::ty))
+            .collect();
+        let code =
+            Code::from_bytes_with_params(engine.arena(), type_mgr, &self.bytecode, &local_types)
+                .map_err(|err| Error::Api(format!("Failed to deserialize bytecode: {}", err)))?;
+        Ok(BoundCode {
+            code,
+            return_type: deserialize_type(type_mgr, &self.return_type)?,
+            rule_bindings: self.rule_bindings.clone(),
+            globals,
+        })
+    }
+}
+
+/// An artifact bound to an engine, run on the bytecode VM.
+pub(crate) struct BoundProgram<'arena> {
+    /// The artifact the program was bound from.
+    artifact: CompiledArtifact,
+    source: &'arena str,
+    /// Code of the module rules, in dependency order.
+    rules: Vec<BoundCode<'arena>>,
+    code: BoundCode<'arena>,
+}
+
+/// Code of the expression or of one of its rules, with the values bound to
+/// its locals after the parameters.
+struct BoundCode<'arena> {
+    code: Code<'arena>,
+    return_type: &'arena Type<'arena>,
+    /// Rules the code references, as indices into the program's rules.
+    rule_bindings: Vec<usize>,
+    globals: Vec<BoundGlobal<'arena>>,
+}
+
+/// A global resolved in the engine's environment.
+#[derive(Clone, Copy)]
+enum BoundGlobal<'arena> {
+    Eager(Value<'arena, 'arena>),
+    /// Built when the code first runs.
+    Lazy(&'arena LazyGlobal<'arena>),
+}
+
+impl<'arena> BoundGlobal<'arena> {
+    fn ty(&self) -> &'arena Type<'arena> {
+        match self {
+            BoundGlobal::Eager(value) => value.ty,
+            BoundGlobal::Lazy(global) => global.ty,
+        }
+    }
+}
+
+impl<'arena> BoundProgram<'arena> {
+    pub(crate) fn artifact(&self) -> &CompiledArtifact {
+        &self.artifact
+    }
+
+    pub(crate) fn source(&self) -> &'arena str {
+        self.source
+    }
+
+    pub(crate) fn return_type(&self) -> &'arena Type<'arena> {
+        self.code.return_type
+    }
+
+    pub(crate) fn estimated_cost(&self) -> u64 {
+        self.artifact.estimated_cost
+    }
+
+    /// Report the size metrics of the expression's code, not counting the
+    /// rules it uses.
+    pub(crate) fn compilation_stats(&self) -> CompilationStats {
+        CompilationStats::from_code(&self.code.code)
+    }
+
+    /// Check that every function the code can reach through the globals is
+    /// deterministic. Lazily registered globals are not built, so those
+    /// holding functions count as non-deterministic.
+    pub(crate) fn globals_are_deterministic(&self) -> bool {
+        self.rules
+            .iter()
+            .chain(core::iter::once(&self.code))
+            .flat_map(|code| &code.globals)
+            .all(|global| match global {
+                BoundGlobal::Eager(value) => is_value_deterministic(value),
+                BoundGlobal::Lazy(global) => !global.ty.contains_function(),
+            })
+    }
+
+    /// Run the module rules in dependency order, then the expression.
+    ///
+    /// `args` must match the parameters, as for
+    /// [`CompiledExpression::run_unchecked`].
+    pub(crate) fn run<'value_arena>(
+        &self,
+        run_options: &RunOptions,
+        arena: &'value_arena Bump,
+        args: &[Value<'arena, 'value_arena>],
+    ) -> Result<Value<'arena, 'value_arena>, Error> {
+        // The rules and the code share one step budget
+        let steps = run_options.step_budget();
+        let mut rule_values = Vec::with_capacity(self.rules.len());
+        for rule in &self.rules {
+            let value = rule.run(run_options, &steps, arena, args, &rule_values)?;
+            rule_values.push(value);
+        }
+        self.code
+            .run(run_options, &steps, arena, args, &rule_values)
+    }
+}
+
+impl<'arena> BoundCode<'arena> {
+    fn run<'value_arena>(
+        &self,
+        run_options: &RunOptions,
+        steps: &StepBudget,
+        arena: &'value_arena Bump,
+        args: &[Value<'arena, 'value_arena>],
+        rule_values: &[Value<'arena, 'value_arena>],
+    ) -> Result<Value<'arena, 'value_arena>, Error> {
+        let mut locals = Vec::with_capacity(self.code.num_locals);
+        locals.extend(args.iter().map(Value::as_raw));
+        locals.extend(
+            self.rule_bindings
+                .iter()
+                .map(|&rule| rule_values[rule].as_raw()),
+        );
+        for global in &self.globals {
+            let value = match global {
+                BoundGlobal::Eager(value) => *value,
+                BoundGlobal::Lazy(global) => global.force()?,
+            };
+            locals.push(value.as_raw());
+        }
+
+        // Each rule starts its own random sequence, as on the evaluator. The
+        // VM has no call depth limit, so `max_depth` does not apply.
+        let random = RandomState::new(run_options.random_seed);
+        let mut vm = VM::new(arena, &self.code, locals, &[]);
+        vm.set_random(&random);
+        vm.set_steps(steps);
+        let raw = vm.run()?;
+        Ok(Value::from_raw_unchecked(self.return_type, raw))
+    }
+}
+
+fn serialize_type(ty: &Type<'_>) -> Result<Vec<u8>, Error> {
+    postcard::to_allocvec(ty)
+        .map_err(|err| Error::Api(format!("Failed to serialize type: {}", err)))
+}
+
+fn deserialize_type<'arena>(
+    type_mgr: &'arena TypeManager<'arena>,
+    bytes: &[u8],
+) -> Result<&'arena Type<'arena>, Error> {
+    type_mgr
+        .deserialize_type(bytes)
+        .map_err(|err| Error::Api(format!("Failed to deserialize type: {}", err)))
+}
@@ -0,0 +1,95 @@
+//! Static cost estimation for compiled expressions.
+//!
+//! The estimate is a weighted count of the operations in the typed tree. It
+//! is a heuristic for admission control, not a bound on the actual run time.
+
+use crate::{
+    analyzer::typed_expr::{Expr, ExprBuilder, ExprInner},
+    visitor::{TreeTransformer, TreeView},
+};
+
+/// Cost of a node that performs a single primitive operation.
+const NODE_COST: u64 = 1;
+
+/// Extra cost of a function call (native functions and lambdas alike).
+const CALL_COST: u64 = 10;
+
+/// Multiplier for lambda bodies.
+///
+/// Lambdas are mostly passed to functions like `Array.Map`, which run them
+/// once per element, so their body is weighted as if executed several times.
+const LAMBDA_BODY_FACTOR: u64 = 8;
+
+/// Computes the estimated cost of a typed expression tree.
+pub(crate) struct CostEstimator;
+
+impl<'types, 'arena> TreeTransformer<ExprBuilder<'types, 'arena>> for CostEstimator
+where
+    'types: 'arena,
+{
+    type Output = u64;
+
+    fn transform(&mut self, tree: &'arena Expr<'types, 'arena>) -> u64 {
+        let children: u64 = match tree.view() {
+            ExprInner::Constant(_) | ExprInner::Ident(_) => 0,
+            ExprInner::Binary { left, right, .. }
+            | ExprInner::Boolean { left, right, .. }
+            | ExprInner::Comparison { left, right, .. } => self.sum([left, right].into_iter()),
+            ExprInner::Unary { expr, .. } | ExprInner::Cast { expr } => self.transform(expr),
+            ExprInner::Call { callable, args, .. } => CALL_COST
+                .saturating_add(self.sum(core::iter::once(callable).chain(args.iter().copied()))),
+            ExprInner::Index { value, index } => self.sum([value, index].into_iter()),
+            ExprInner::Slice { value, start, end } => {
+                self.sum(core::iter::once(value).chain(start).chain(end))
+            }
+            ExprInner::Field { value, .. } => self.transform(value),
+            ExprInner::Lambda { body, .. } => {
+                self.transform(body).saturating_mul(LAMBDA_BODY_FACTOR)
+            }
+            ExprInner::If {
+                cond,
+                then_branch,
+                else_branch,
+            } => {
+                // Only one branch runs: charge the more expensive one.
+                let branches = self.transform(then_branch).max(self.transform(else_branch));
+                self.transform(cond).saturating_add(branches)
+            }
+            ExprInner::Where { expr, bindings } => {
+                self.sum(core::iter::once(expr).chain(bindings.iter().map(|(_, value)| *value)))
+            }
+            ExprInner::Otherwise { primary, fallback } => self.sum([primary, fallback].into_iter()),
+            ExprInner::Coalesce { option, default } => self.sum([option, default].into_iter()),
+            ExprInner::Option { inner } => inner.map_or(0, |inner| self.transform(inner)),
+            ExprInner::Match { expr, arms } => {
+                let arms_cost = arms
+                    .iter()
+                    .map(|arm| self.transform(arm.body))
+                    .max()
+                    .unwrap_or(0);
+                self.transform(expr)
+                    .saturating_add(arms.len() as u64)
+                    .saturating_add(arms_cost)
+            }
+            ExprInner::Record { fields } => self.sum(fields.iter().map(|(_, value)| *value)),
+            ExprInner::Map { elements, .. } => {
+                self.sum(elements.iter().flat_map(|(key, value)| [*key, *value]))
+            }
+            ExprInner::Array { elements } => self.sum(elements.iter().copied()),
+            ExprInner::FormatStr { exprs, .. } => self.sum(exprs.iter().copied()),
+        };
+        NODE_COST.saturating_add(children)
+    }
+}
+
+impl CostEstimator {
+    fn sum<'types, 'arena>(
+        &mut self,
+        exprs: impl Iterator<Item = &'arena Expr<'types, 'arena>>,
+    ) -> u64
+    where
+        'types: 'arena,
+    {
+        exprs.fold(0, |total, expr| total.saturating_add(self.transform(expr)))
+    }
+}
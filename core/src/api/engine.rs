@@ -1,9 +1,18 @@
 //! The Melbi compilation engine.
 
-use super::{CompileOptionsOverride, CompiledExpression, EngineOptions, EnvironmentBuilder, Error};
-use crate::types::{Type, manager::TypeManager};
+use super::{
+    CompileOptions, CompileOptionsOverride, CompiledExpression, Diagnostic, EngineOptions,
+    EnvironmentBuilder, Error, environment::LazyGlobal,
+};
+use crate::types::{Type, TypeClassConstraint, manager::TypeManager};
 use crate::values::dynamic::Value;
-use crate::{Vec, analyzer, parser};
+use crate::{
+    Vec,
+    analyzer::{self, AnalysisContext, BindingGraph, GlobalScope, PartialAnalysis},
+    format,
+    parser::{self, ParsedExpr},
+};
+use alloc::collections::BTreeMap;
 use bumpalo::Bump;
 
 /// The Melbi compilation and execution engine.
@@ -46,9 +55,10 @@ pub struct Engine<'arena> {
     arena: &'arena Bump,
     type_manager: &'arena TypeManager<'arena>,
     environment: &'arena [(&'arena str, Value<'arena, 'arena>)],
-    /// Precomputed globals for analyzer (name, type) pairs
-    /// TODO: Switch to TypeScheme when generic functions are supported
-    globals_for_analyzer: &'arena [(&'arena str, &'arena Type<'arena>)],
+    /// Globals whose values are built on first use
+    lazy_globals: &'arena [LazyGlobal<'arena>],
+    /// Type scope of all globals, built once and shared by every compilation
+    global_scope: GlobalScope<'arena, 'arena>,
     options: EngineOptions,
 }
 
@@ -85,21 +95,23 @@ impl<'arena> Engine<'arena> {
         // Build environment using the initialization closure
         let mut env_builder = EnvironmentBuilder::new(arena);
         init(arena, type_manager, &mut env_builder);
-        let environment = env_builder.build(arena);
+        let (environment, lazy_globals) = env_builder.build_with_lazy(arena);
 
-        // Precompute globals for analyzer (convert Value to Type)
-        // TODO: Switch to TypeScheme when generic functions are supported
-        let globals: Vec<(&'arena str, &'arena Type<'arena>)> = environment
+        // Precompute the analyzer's global scope (convert Value to Type)
+        let mut globals: Vec<(&'arena str, &'arena Type<'arena>)> = environment
             .iter()
             .map(|(name, value)| (*name, value.ty))
+            .chain(lazy_globals.iter().map(|global| (global.name, global.ty)))
             .collect();
-        let globals_for_analyzer = arena.alloc_slice_copy(&globals);
+        globals.sort_by_key(|(name, _)| *name);
+        let global_scope = GlobalScope::new(type_manager, arena, &globals);
 
         Self {
             arena,
             type_manager,
             environment,
-            globals_for_analyzer,
+            lazy_globals,
+            global_scope,
             options,
         }
     }
@@ -113,11 +125,35 @@ impl<'arena> Engine<'arena> {
 
     /// Access the global environment.
     ///
-    /// Returns a sorted slice of (name, value) pairs.
+    /// Returns a sorted slice of (name, value) pairs. Globals registered with
+    /// [`EnvironmentBuilder::register_lazy`] are not included.
     pub fn environment(&self) -> &[(&'arena str, Value<'arena, 'arena>)] {
         self.environment
     }
 
+    /// Get the type of a global, whether registered eagerly or lazily.
+    pub(crate) fn global_type(&self, name: &str) -> Option<&'arena Type<'arena>> {
+        self.global_scope.lookup(name)
+    }
+
+    /// Get the value of an eagerly registered global.
+    pub(crate) fn global_value(&self, name: &str) -> Option<Value<'arena, 'arena>> {
+        self.environment
+            .iter()
+            .find(|(global, _)| *global == name)
+            .map(|(_, value)| *value)
+    }
+
+    /// Get a lazily registered global, without building it.
+    pub(crate) fn lazy_global(&self, name: &str) -> Option<&'arena LazyGlobal<'arena>> {
+        self.lazy_globals.iter().find(|global| global.name == name)
+    }
+
+    /// Access the arena holding types, the environment and compiled expressions.
+    pub(crate) fn arena(&self) -> &'arena Bump {
+        self.arena
+    }
+
     /// Access the engine options.
     pub fn options(&self) -> &EngineOptions {
         &self.options
@@ -163,35 +199,330 @@ impl<'arena> Engine<'arena> {
         options_override: CompileOptionsOverride,
         source: &'arena str,
         params: &[(&'arena str, &'arena Type<'arena>)],
+    ) -> Result<CompiledExpression<'arena>, Error> {
+        self.compile_with_diagnostics(options_override, source, params, |_| {})
+    }
+
+    /// Compile a Melbi expression, passing each diagnostic to `on_diagnostic`
+    /// as soon as it is found.
+    ///
+    /// This is [`compile`](Self::compile) for tools that process diagnostics
+    /// incrementally, such as editors or linters emitting one JSON object per
+    /// diagnostic. On failure, the returned [`Error::Compilation`] holds the
+    /// same diagnostics, in the order they were passed to the callback.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use melbi_core::api::{Engine, EngineOptions};
+    /// use bumpalo::Bump;
+    ///
+    /// let arena = Bump::new();
+    /// let engine = Engine::new(EngineOptions::default(), &arena, |_,_,_| {});
+    ///
+    /// let mut messages = Vec::new();
+    /// let result = engine.compile_with_diagnostics(
+    ///     Default::default(),
+    ///     "{ a = true + false, b = \"a\" * \"b\" }",
+    ///     &[],
+    ///     |diagnostic| messages.push(diagnostic.message.clone()),
+    /// );
+    /// assert!(result.is_err());
+    /// assert_eq!(messages.len(), 2);
+    /// ```
+    pub fn compile_with_diagnostics(
+        &self,
+        options_override: CompileOptionsOverride,
+        source: &'arena str,
+        params: &[(&'arena str, &'arena Type<'arena>)],
+        on_diagnostic: impl FnMut(&Diagnostic),
     ) -> Result<CompiledExpression<'arena>, Error> {
         // Merge compilation options (defaults + provided)
-        let mut _options = self.options.default_compile_options.clone();
-        _options.override_with(&options_override);
-        // TODO: Use merged_options when CompileOptions has fields
+        let mut options = self.options.default_compile_options.clone();
+        options.override_with(&options_override);
+        self.compile_with_options(options, source, params, &[], on_diagnostic)
+    }
 
+    /// Compile a Melbi expression with fully resolved compile options,
+    /// ignoring the engine's defaults.
+    ///
+    /// `module` holds rules compiled earlier, in dependency order, that the
+    /// expression may reference by name, as in [`compile_module`](Self::compile_module).
+    pub(crate) fn compile_with_options(
+        &self,
+        options: CompileOptions,
+        source: &'arena str,
+        params: &[(&'arena str, &'arena Type<'arena>)],
+        module: &[(&'arena str, &'arena CompiledExpression<'arena>)],
+        mut on_diagnostic: impl FnMut(&Diagnostic),
+    ) -> Result<CompiledExpression<'arena>, Error> {
         // Parse the source
-        let parsed = parser::parse(self.arena, source)?;
+        let parsed = parser::parse_with_options(self.arena, source, options.parse_options())
+            .map_err(|err| {
+                on_diagnostic(&err.to_diagnostic());
+                Error::from(err)
+            })?;
+        self.compile_parsed(options, parsed, params, module, on_diagnostic)
+    }
+
+    /// Type check a parsed expression and build its compiled form.
+    ///
+    /// Rules of `module` the expression references are bound like
+    /// parameters, with the types they were compiled to, so diagnostics
+    /// point into the expression's own source. As with `where` bindings, a
+    /// rule of polymorphic type can be used at several types.
+    fn compile_parsed(
+        &self,
+        options: CompileOptions,
+        parsed: &'arena ParsedExpr<'arena>,
+        params: &[(&'arena str, &'arena Type<'arena>)],
+        module: &[(&'arena str, &'arena CompiledExpression<'arena>)],
+        mut on_diagnostic: impl FnMut(&Diagnostic),
+    ) -> Result<CompiledExpression<'arena>, Error> {
+        let free_variables = analyzer::free_variables(parsed.expr);
+
+        // Module rules the expression references, and every rule they need
+        let references: Vec<&(&'arena str, &'arena CompiledExpression<'arena>)> = module
+            .iter()
+            .filter(|(name, _)| free_variables.contains(name))
+            .collect();
+        let dependencies: Vec<(&'arena str, &'arena CompiledExpression<'arena>)> = module
+            .iter()
+            .filter(|(name, _)| {
+                references.iter().any(|(reference, rule)| {
+                    reference == name
+                        || rule
+                            .dependencies()
+                            .iter()
+                            .any(|(dependency, _)| dependency == name)
+                })
+            })
+            .copied()
+            .collect();
+        let rule_bindings: Vec<&'arena str> = references.iter().map(|(name, _)| *name).collect();
 
         // Prepare parameters for analysis - copy to arena
         // Since params is already (&str, &Type), we can just copy the slice directly
         let params_slice = self.arena.alloc_slice_copy(params);
 
-        // Type check the expression using precomputed globals
-        let typed_expr = analyzer::analyze(
+        // The analyzer looks variables up by name, so they must be sorted
+        let mut variables: Vec<(&'arena str, &'arena Type<'arena>)> = params
+            .iter()
+            .copied()
+            .chain(
+                references
+                    .iter()
+                    .map(|(name, rule)| (*name, rule.return_type())),
+            )
+            .collect();
+        variables.sort_by_key(|(name, _)| *name);
+
+        // Rules are polymorphic like where bindings: their type variables are
+        // instantiated at each use, subject to the rule's constraints
+        let constraints: Vec<TypeClassConstraint<'arena>> = references
+            .iter()
+            .flat_map(|(_, rule)| rule.constraints().iter().cloned())
+            .collect();
+
+        // Type check the expression using the precomputed global scope
+        let typed_expr = analyzer::analyze_with_global_scope(
             self.type_manager,
             self.arena,
             &parsed,
-            self.globals_for_analyzer,
-            params_slice,
+            AnalysisContext {
+                globals: self.global_scope,
+                variables: &variables,
+                constraints: &constraints,
+            },
+            options.analyzer_options(),
+            &mut |err| on_diagnostic(&err.to_diagnostic()),
         )?;
 
+        // Lazy globals the expression references, built when it first runs
+        let referenced_lazy_globals: Vec<&'arena LazyGlobal<'arena>> = free_variables
+            .into_iter()
+            .filter(|name| !variables.iter().any(|(variable, _)| variable == name))
+            .filter_map(|name| self.lazy_globals.iter().find(|global| global.name == name))
+            .collect();
+
         // Create compiled expression with default run options
         Ok(CompiledExpression::new(
             typed_expr,
             self.type_manager,
             params_slice,
             self.environment,
+            self.arena.alloc_slice_copy(&referenced_lazy_globals),
+            options,
             self.options.default_run_options.clone(),
+        )
+        .with_attributes(parsed.attributes)
+        .with_dependencies(
+            self.arena.alloc_slice_copy(&rule_bindings),
+            self.arena.alloc_slice_copy(&dependencies),
         ))
     }
+
+    /// Compile a Melbi expression, returning the expression (if compilation
+    /// succeeded) together with every diagnostic found.
+    ///
+    /// Unlike [`compile`](Self::compile), this never fails: errors are
+    /// reported as diagnostics alongside a `None` expression, and warnings
+    /// (e.g. from [`CompileOptions::warn_unused_parameters`](super::CompileOptions::warn_unused_parameters))
+    /// are returned even when compilation succeeds. This suits editors and
+    /// other tools that always display diagnostics.
+    ///
+    /// When the source parses but does not type check, the expression is
+    /// `None` and the returned [`PartialAnalysis`] holds the types of the
+    /// sub-expressions that did type check, e.g. for hover information. It
+    /// is `None` when compilation succeeds or the source does not parse.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use melbi_core::api::{Engine, EngineOptions};
+    /// use bumpalo::Bump;
+    ///
+    /// let arena = Bump::new();
+    /// let engine = Engine::new(EngineOptions::default(), &arena, |_,_,_| {});
+    ///
+    /// let source = "{ a = true + false, b = 2 }";
+    /// let (expr, analysis, diagnostics) =
+    ///     engine.compile_collecting_diagnostics(Default::default(), source, &[]);
+    /// assert!(expr.is_none());
+    /// assert_eq!(diagnostics.len(), 1);
+    ///
+    /// // The `2` still has a type
+    /// let analysis = analysis.unwrap();
+    /// let ty = analysis.type_at(source.find('2').unwrap()).unwrap();
+    /// assert_eq!(ty.to_string(), "Int");
+    /// ```
+    pub fn compile_collecting_diagnostics(
+        &self,
+        options_override: CompileOptionsOverride,
+        source: &'arena str,
+        params: &[(&'arena str, &'arena Type<'arena>)],
+    ) -> (
+        Option<CompiledExpression<'arena>>,
+        Option<PartialAnalysis<'arena, 'arena>>,
+        Vec<Diagnostic>,
+    ) {
+        let mut options = self.options.default_compile_options.clone();
+        options.override_with(&options_override);
+
+        let mut diagnostics = Vec::new();
+        let result =
+            self.compile_with_options(options.clone(), source, params, &[], |diagnostic| {
+                diagnostics.push(diagnostic.clone())
+            });
+        if let Ok(expr) = result {
+            return (Some(expr), None, diagnostics);
+        }
+
+        // Analyze the source again, keeping the types found before each error
+        let analysis = parser::parse_with_options(self.arena, source, options.parse_options())
+            .ok()
+            .map(|parsed| {
+                let mut variables = params.to_vec();
+                variables.sort_by_key(|(name, _)| *name);
+                analyzer::analyze_partial_with_global_scope(
+                    self.type_manager,
+                    self.arena,
+                    parsed,
+                    AnalysisContext {
+                        globals: self.global_scope,
+                        variables: &variables,
+                        constraints: &[],
+                    },
+                    options.analyzer_options(),
+                )
+            });
+        (None, analysis, diagnostics)
+    }
+
+    /// Compile a set of named rules together, as a module.
+    ///
+    /// Each rule is a `(name, source)` pair. Rules share the engine's globals
+    /// and the given parameters, and can reference each other's results by
+    /// name, like `where` bindings at module scope. Each rule is type checked
+    /// on its own source, with the rules it references bound to the types
+    /// they were compiled to, so diagnostics always point into the rule that
+    /// has the error. Running a rule first runs the rules it depends on, each
+    /// once, with the same arguments.
+    ///
+    /// Fails if two rules have the same name, if a rule has the name of a
+    /// parameter, if rules reference each other in a cycle, or if any rule
+    /// fails to compile.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use melbi_core::api::{Engine, EngineOptions};
+    /// use bumpalo::Bump;
+    ///
+    /// let arena = Bump::new();
+    /// let engine = Engine::new(EngineOptions::default(), &arena, |_,_,_| {});
+    /// let rules = engine
+    ///     .compile_module(Default::default(), &[("base", "40"), ("answer", "base + 2")], &[])
+    ///     .unwrap();
+    ///
+    /// let val_arena = Bump::new();
+    /// let result = rules["answer"].run(Default::default(), &val_arena, &[]).unwrap();
+    /// assert_eq!(result.as_int().unwrap(), 42);
+    /// ```
+    pub fn compile_module(
+        &self,
+        options_override: CompileOptionsOverride,
+        rules: &[(&'arena str, &'arena str)],
+        params: &[(&'arena str, &'arena Type<'arena>)],
+    ) -> Result<BTreeMap<&'arena str, CompiledExpression<'arena>>, Error> {
+        let mut options = self.options.default_compile_options.clone();
+        options.override_with(&options_override);
+
+        let mut parsed = Vec::with_capacity(rules.len());
+        for (index, (name, source)) in rules.iter().enumerate() {
+            if rules[..index].iter().any(|(other, _)| other == name) {
+                return Err(Error::Api(format!("Duplicate rule name `{}`", name)));
+            }
+            if params.iter().any(|(param, _)| param == name) {
+                return Err(Error::Api(format!(
+                    "Rule `{}` has the same name as a parameter",
+                    name
+                )));
+            }
+            parsed.push(parser::parse_with_options(
+                self.arena,
+                source,
+                options.parse_options(),
+            )?);
+        }
+
+        let expressions: Vec<_> = rules
+            .iter()
+            .zip(&parsed)
+            .map(|((name, _), rule)| (*name, rule.expr))
+            .collect();
+        let graph = BindingGraph::new(&expressions);
+        let order = graph.topological_order().map_err(|cycle| {
+            Error::Api(format!(
+                "Rules reference each other in a cycle: {} -> {}",
+                cycle.join(" -> "),
+                cycle[0]
+            ))
+        })?;
+
+        // Rules compiled so far, in dependency order.
+        let mut module = Vec::with_capacity(order.len());
+        let mut compiled = BTreeMap::new();
+        for name in order {
+            let index = rules
+                .iter()
+                .position(|(rule, _)| *rule == name)
+                .expect("topological order only contains rule names");
+            let expression =
+                self.compile_parsed(options.clone(), parsed[index], params, &module, |_| {})?;
+            module.push((name, &*self.arena.alloc(expression.clone())));
+            compiled.insert(name, expression);
+        }
+        Ok(compiled)
+    }
 }
@@ -1,8 +1,9 @@
 //! Environment builder for registering global values.
 
 use super::Error;
-use crate::{Vec, format, values::dynamic::Value};
+use crate::{Vec, format, types::Type, values::dynamic::Value};
 use bumpalo::Bump;
+use core::cell::OnceCell;
 
 /// Builder for constructing the global environment.
 ///
@@ -28,6 +29,45 @@ use bumpalo::Bump;
 pub struct EnvironmentBuilder<'arena> {
     arena: &'arena Bump,
     entries: Vec<(&'arena str, Value<'arena, 'arena>)>,
+    lazy_entries: Vec<LazyGlobal<'arena>>,
+}
+
+/// A global whose value is built the first time it is needed.
+///
+/// See [`EnvironmentBuilder::register_lazy`].
+pub(crate) struct LazyGlobal<'arena> {
+    pub name: &'arena str,
+    pub ty: &'arena Type<'arena>,
+    /// Kept in the arena, which never runs destructors, so it must not need
+    /// dropping: [`EnvironmentBuilder::register_lazy`] requires it to be
+    /// `Copy`.
+    init: &'arena dyn Fn() -> Value<'arena, 'arena>,
+    value: OnceCell<Value<'arena, 'arena>>,
+}
+
+impl<'arena> LazyGlobal<'arena> {
+    /// Get the value if it has been built already.
+    pub fn get(&self) -> Option<Value<'arena, 'arena>> {
+        self.value.get().copied()
+    }
+
+    /// Get the value, building it on first use.
+    ///
+    /// Fails if the builder produces a value of a type other than the one
+    /// the global was registered with.
+    pub fn force(&self) -> Result<Value<'arena, 'arena>, Error> {
+        if let Some(value) = self.value.get() {
+            return Ok(*value);
+        }
+        let value = (self.init)();
+        if !core::ptr::eq(value.ty, self.ty) {
+            return Err(Error::Api(format!(
+                "Lazy global '{}' was built with type {}, but registered with type {}",
+                self.name, value.ty, self.ty
+            )));
+        }
+        Ok(*self.value.get_or_init(|| value))
+    }
 }
 
 impl<'arena> EnvironmentBuilder<'arena> {
@@ -36,6 +76,7 @@ impl<'arena> EnvironmentBuilder<'arena> {
         Self {
             arena,
             entries: Vec::new(),
+            lazy_entries: Vec::new(),
         }
     }
 
@@ -62,20 +103,83 @@ impl<'arena> EnvironmentBuilder<'arena> {
     /// });
     /// ```
     pub fn register(&mut self, name: &str, value: Value<'arena, 'arena>) -> Result<(), Error> {
-        // Check if name already exists
-        if self
+        self.check_not_registered(name)?;
+        let name = self.arena.alloc_str(name);
+        self.entries.push((name, value));
+        Ok(())
+    }
+
+    /// Register a global whose value is built on demand.
+    ///
+    /// The type is needed up front to type-check expressions, but `init` only
+    /// runs the first time an expression that references the global is run,
+    /// and its result is cached for later runs. This keeps engine startup
+    /// cheap when there are large globals (e.g. configuration records) that
+    /// most expressions never use.
+    ///
+    /// Running an expression fails if `init` builds a value of a type other
+    /// than `ty`.
+    ///
+    /// `init` is kept in the engine's arena, which never runs destructors,
+    /// so it must be `Copy`: it may capture references and plain values, but
+    /// not owned data such as a `Vec` or an `Rc`, which would never be
+    /// freed. Allocate such data in the arena and capture a reference to it
+    /// instead.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a value with the same name has already been registered.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use melbi_core::api::{Engine, EngineOptions};
+    /// use melbi_core::values::dynamic::Value;
+    /// use bumpalo::Bump;
+    ///
+    /// let arena = Bump::new();
+    /// let engine = Engine::new(EngineOptions::default(), &arena, |arena, type_mgr, env| {
+    ///     let ty = type_mgr.record(vec![("limit", type_mgr.int())]);
+    ///     env.register_lazy("Config", ty, move || {
+    ///         Value::record(arena, ty, &[("limit", Value::int(type_mgr, 100))]).unwrap()
+    ///     })
+    ///     .expect("registration should succeed");
+    /// });
+    ///
+    /// let expr = engine.compile(Default::default(), "Config.limit", &[]).unwrap();
+    /// let val_arena = Bump::new();
+    /// let result = expr.run(Default::default(), &val_arena, &[]).unwrap();
+    /// assert_eq!(result.as_int().unwrap(), 100);
+    /// ```
+    pub fn register_lazy(
+        &mut self,
+        name: &str,
+        ty: &'arena Type<'arena>,
+        init: impl Fn() -> Value<'arena, 'arena> + Copy + 'arena,
+    ) -> Result<(), Error> {
+        self.check_not_registered(name)?;
+        self.lazy_entries.push(LazyGlobal {
+            name: self.arena.alloc_str(name),
+            ty,
+            init: self.arena.alloc(init),
+            value: OnceCell::new(),
+        });
+        Ok(())
+    }
+
+    fn check_not_registered(&self, name: &str) -> Result<(), Error> {
+        let registered = self
             .entries
             .iter()
-            .any(|(existing_name, _)| *existing_name == name)
-        {
+            .map(|(existing_name, _)| *existing_name)
+            .chain(self.lazy_entries.iter().map(|global| global.name))
+            .any(|existing_name| existing_name == name);
+        if registered {
             return Err(Error::Api(format!(
                 "Duplicate registration: '{}' is already registered in the environment",
                 name
             )));
         }
-
-        let name = self.arena.alloc_str(name);
-        self.entries.push((name, value));
         Ok(())
     }
 
@@ -85,13 +189,31 @@ impl<'arena> EnvironmentBuilder<'arena> {
     /// during lookups.
     ///
     /// This is useful when bypassing the `Engine` API and using `analyze`,
-    /// `Evaluator`, or `BytecodeCompiler` directly.
+    /// `Evaluator`, or `BytecodeCompiler` directly. Since those take plain
+    /// values, lazily registered globals are built here.
     pub fn build(
         mut self,
         arena: &'arena Bump,
     ) -> &'arena [(&'arena str, Value<'arena, 'arena>)] {
+        for global in core::mem::take(&mut self.lazy_entries) {
+            let value = (global.init)();
+            self.entries.push((global.name, value));
+        }
         // Sort by name for efficient binary search during lookup
         self.entries.sort_by_key(|(name, _)| *name);
         arena.alloc_slice_copy(&self.entries)
     }
+
+    /// Build the sorted environment slice, keeping lazy globals apart.
+    pub(crate) fn build_with_lazy(
+        mut self,
+        arena: &'arena Bump,
+    ) -> (
+        &'arena [(&'arena str, Value<'arena, 'arena>)],
+        &'arena [LazyGlobal<'arena>],
+    ) {
+        let lazy_entries = core::mem::take(&mut self.lazy_entries);
+        let lazy = arena.alloc_slice_fill_iter(lazy_entries);
+        (self.build(arena), lazy)
+    }
 }
@@ -6,7 +6,7 @@
 //! See docs/design/error-handling.md for the complete design.
 
 use crate::parser::Span;
-use crate::{String, ToString, Vec, format};
+use crate::{String, Vec, format};
 
 #[cfg(feature = "std")]
 use std::fmt;
@@ -40,7 +40,14 @@ pub enum Error {
     },
 
     /// Resource limits exceeded (e.g., stack overflow, iteration limit).
-    ResourceExceeded(String),
+    ///
+    /// Contains a diagnostic pointing at the expression being evaluated when
+    /// the limit was hit. Unlike runtime errors, these cannot be caught with
+    /// `otherwise`.
+    ResourceExceeded {
+        diagnostic: Diagnostic,
+        source: String,
+    },
 }
 
 impl fmt::Display for Error {
@@ -60,7 +67,9 @@ impl fmt::Display for Error {
             Error::Runtime { diagnostic, .. } => {
                 write!(f, "Runtime error: {}", diagnostic.message)
             }
-            Error::ResourceExceeded(msg) => write!(f, "Resource limit exceeded: {}", msg),
+            Error::ResourceExceeded { diagnostic, .. } => {
+                write!(f, "Resource limit exceeded: {}", diagnostic.message)
+            }
         }
     }
 }
@@ -164,7 +173,10 @@ impl From<crate::evaluator::ExecutionError> for Error {
                 diagnostic: err.to_diagnostic(),
                 source: err.source,
             },
-            ExecutionErrorKind::ResourceExceeded(e) => Error::ResourceExceeded(e.to_string()),
+            ExecutionErrorKind::ResourceExceeded(_) => Error::ResourceExceeded {
+                diagnostic: err.to_diagnostic(),
+                source: err.source,
+            },
             ExecutionErrorKind::Internal(e) => Error::Api(format!("Internal error: {}", e)),
         }
     }
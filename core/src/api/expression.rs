@@ -1,11 +1,20 @@
 //! Compiled Melbi expressions.
 
-use super::{Error, RunOptions, RunOptionsOverride};
+use super::{
+    CompilationStats, CompileOptions, CompiledArtifact, Error, RunOptions, RunOptionsOverride,
+    artifact::BoundProgram, cost::CostEstimator, environment::LazyGlobal, purity::PurityChecker,
+};
 use crate::analyzer::typed_expr::TypedExpr;
+use crate::compiler::BytecodeCompiler;
 use crate::evaluator::{Evaluator, EvaluatorOptions};
-use crate::types::{Type, manager::TypeManager};
-use crate::values::dynamic::Value;
-use crate::{Vec, format};
+use crate::parser::{Attribute, AttributeValue};
+use crate::types::{
+    Type, TypeClassConstraint, alpha_converter::AlphaConverter, manager::TypeManager,
+};
+use crate::values::{StepBudget, dynamic::Value};
+use crate::visitor::TreeTransformer;
+use crate::{String, Vec, format};
+use alloc::rc::Rc;
 use bumpalo::Bump;
 
 /// A compiled Melbi expression ready for execution.
@@ -50,9 +59,10 @@ use bumpalo::Bump;
 /// }.unwrap();
 /// assert_eq!(result.as_int().unwrap(), 42);
 /// ```
+#[derive(Clone)]
 pub struct CompiledExpression<'arena> {
-    /// The type-checked AST
-    typed_expr: &'arena TypedExpr<'arena, 'arena>,
+    /// What runs: the type-checked AST, or bytecode bound from an artifact
+    body: Body<'arena>,
 
     /// Type manager for creating values
     type_manager: &'arena TypeManager<'arena>,
@@ -63,8 +73,36 @@ pub struct CompiledExpression<'arena> {
     /// Global environment for evaluation
     environment: &'arena [(&'arena str, Value<'arena, 'arena>)],
 
+    /// Lazily registered globals the expression references
+    lazy_globals: &'arena [&'arena LazyGlobal<'arena>],
+
+    /// Options the expression was compiled with
+    compile_options: CompileOptions,
+
     /// Default run-time options
     default_run_options: RunOptions,
+
+    /// Attributes leading the source
+    attributes: &'arena [Attribute<'arena>],
+
+    /// Rules of the module the expression references directly, bound after
+    /// the parameters
+    rule_bindings: &'arena [&'arena str],
+
+    /// Rules of the module the expression depends on, directly or through
+    /// other rules, in dependency order
+    dependencies: &'arena [(&'arena str, &'arena CompiledExpression<'arena>)],
+}
+
+/// How a compiled expression runs.
+#[derive(Clone)]
+enum Body<'arena> {
+    /// The type-checked AST, run on the tree-walking evaluator.
+    Tree(&'arena TypedExpr<'arena, 'arena>),
+    /// Bytecode bound from a [`CompiledArtifact`], run on the VM. It holds
+    /// its module rules and globals itself, and is not allocated in the
+    /// arena so that its code is dropped with the last expression using it.
+    Bytecode(Rc<BoundProgram<'arena>>),
 }
 
 impl<'arena> CompiledExpression<'arena> {
@@ -76,17 +114,101 @@ impl<'arena> CompiledExpression<'arena> {
         type_manager: &'arena TypeManager<'arena>,
         params: &'arena [(&'arena str, &'arena Type<'arena>)],
         environment: &'arena [(&'arena str, Value<'arena, 'arena>)],
+        lazy_globals: &'arena [&'arena LazyGlobal<'arena>],
+        compile_options: CompileOptions,
         default_run_options: RunOptions,
     ) -> Self {
         Self {
-            typed_expr,
+            body: Body::Tree(typed_expr),
             type_manager,
             params,
             environment,
+            lazy_globals,
+            compile_options,
+            default_run_options,
+            attributes: &[],
+            rule_bindings: &[],
+            dependencies: &[],
+        }
+    }
+
+    /// Create an expression running bytecode bound from an artifact.
+    ///
+    /// This is called internally by [`CompiledArtifact::bind`].
+    pub(crate) fn from_bound(
+        program: BoundProgram<'arena>,
+        type_manager: &'arena TypeManager<'arena>,
+        params: &'arena [(&'arena str, &'arena Type<'arena>)],
+        compile_options: CompileOptions,
+        default_run_options: RunOptions,
+    ) -> Self {
+        Self {
+            body: Body::Bytecode(Rc::new(program)),
+            type_manager,
+            params,
+            environment: &[],
+            lazy_globals: &[],
+            compile_options,
             default_run_options,
+            attributes: &[],
+            rule_bindings: &[],
+            dependencies: &[],
+        }
+    }
+
+    /// Set the attributes parsed from the source.
+    pub(crate) fn with_attributes(mut self, attributes: &'arena [Attribute<'arena>]) -> Self {
+        self.attributes = attributes;
+        self
+    }
+
+    /// Set the module rules the expression references.
+    ///
+    /// `rule_bindings` are the rules referenced by name, which the expression
+    /// was type checked with as variables after its parameters, and
+    /// `dependencies` all the rules they need, in dependency order.
+    pub(crate) fn with_dependencies(
+        mut self,
+        rule_bindings: &'arena [&'arena str],
+        dependencies: &'arena [(&'arena str, &'arena CompiledExpression<'arena>)],
+    ) -> Self {
+        self.rule_bindings = rule_bindings;
+        self.dependencies = dependencies;
+        self
+    }
+
+    /// Get the module rules the expression depends on, in dependency order.
+    pub(crate) fn dependencies(
+        &self,
+    ) -> &'arena [(&'arena str, &'arena CompiledExpression<'arena>)] {
+        self.dependencies
+    }
+
+    /// Get the type class constraints on the type variables of the
+    /// expression's return type, for expressions that evaluate to a
+    /// polymorphic value.
+    pub(crate) fn constraints(&self) -> &'arena [TypeClassConstraint<'arena>] {
+        match &self.body {
+            Body::Tree(typed_expr) => typed_expr.constraints,
+            // Only used for the rules of a module, which are compiled from source.
+            Body::Bytecode(_) => &[],
         }
     }
 
+    /// Get the type-checked AST of an expression compiled from source.
+    fn typed_expr(&self) -> Option<&'arena TypedExpr<'arena, 'arena>> {
+        match &self.body {
+            Body::Tree(typed_expr) => Some(*typed_expr),
+            Body::Bytecode(_) => None,
+        }
+    }
+
+    /// Get the names of the module rules the expression references, bound
+    /// after its parameters.
+    pub(crate) fn rule_bindings(&self) -> &'arena [&'arena str] {
+        self.rule_bindings
+    }
+
     /// Execute the expression with runtime validation.
     ///
     /// This is the **safe dynamic API** - it validates:
@@ -179,7 +301,7 @@ impl<'arena> CompiledExpression<'arena> {
     /// # Safety
     ///
     /// The caller must ensure:
-    /// - Argument count matches `self.params().len()`
+    /// - Argument count matches `self.parameters().len()`
     /// - Each argument's type matches the corresponding parameter type
     /// - Arguments were created with the same TypeManager as the expression
     ///
@@ -233,12 +355,48 @@ impl<'arena> CompiledExpression<'arena> {
         let mut run_options = self.default_run_options.clone();
         run_options.override_with(&options_override);
 
+        // Bound bytecode runs its module rules itself.
+        if let Body::Bytecode(program) = &self.body {
+            return program.run(&run_options, arena, args);
+        }
+
+        // Run each module rule the expression depends on once, in dependency
+        // order, so later rules can use the results of earlier ones. They
+        // all count against the same step budget.
+        let steps: &StepBudget = arena.alloc(run_options.step_budget());
+        let mut rule_values = Vec::with_capacity(self.dependencies.len());
+        for (name, rule) in self.dependencies {
+            // SAFETY: Rules of a module share its parameters, and the caller
+            // guarantees that the arguments match them.
+            let value = unsafe { rule.evaluate(&run_options, steps, arena, args, &rule_values)? };
+            rule_values.push((*name, value));
+        }
+        unsafe { self.evaluate(&run_options, steps, arena, args, &rule_values) }
+    }
+
+    /// Evaluate the expression alone, given the results of the module rules
+    /// it references.
+    ///
+    /// # Safety
+    ///
+    /// As for [`run_unchecked`](Self::run_unchecked).
+    unsafe fn evaluate<'value_arena>(
+        &self,
+        run_options: &RunOptions,
+        steps: &'value_arena StepBudget,
+        arena: &'value_arena Bump,
+        args: &[Value<'arena, 'value_arena>],
+        rule_values: &[(&'arena str, Value<'arena, 'value_arena>)],
+    ) -> Result<Value<'arena, 'value_arena>, Error> {
         // Create evaluator options from execution options
         // TODO: EvaluatorOptions should use RunOptions directly or provide a From impl
-        // Note: EvaluatorOptions currently only supports max_depth
         // When EvaluatorOptions gains more fields, update this conversion
         let evaluator_opts = EvaluatorOptions {
             max_depth: run_options.max_depth,
+            random_seed: run_options.random_seed,
+            max_steps: run_options.max_steps,
+            #[cfg(feature = "std")]
+            deadline: run_options.deadline,
         };
 
         // Prepare variables for evaluation (params = args)
@@ -248,9 +406,29 @@ impl<'arena> CompiledExpression<'arena> {
             let name_in_value_arena: &'value_arena str = arena.alloc_str(name);
             variables.push((name_in_value_arena, *value));
         }
+        for name in self.rule_bindings {
+            let (_, value) = rule_values
+                .iter()
+                .find(|(rule, _)| rule == name)
+                .expect("module rules run before the rules that reference them");
+            variables.push((arena.alloc_str(name), *value));
+        }
+        variables.sort_by_key(|(name, _)| *name);
         let variables_slice = arena.alloc_slice_copy(&variables);
 
-        let globals: &[(&str, Value<'arena, 'value_arena>)] = self.environment;
+        // Build the lazy globals the expression uses on first run; later runs
+        // reuse the cached values.
+        let globals: &[(&str, Value<'arena, 'value_arena>)] = if self.lazy_globals.is_empty() {
+            self.environment
+        } else {
+            let mut globals = Vec::with_capacity(self.environment.len() + self.lazy_globals.len());
+            globals.extend_from_slice(self.environment);
+            for global in self.lazy_globals {
+                globals.push((global.name, global.force()?));
+            }
+            globals.sort_by_key(|(name, _)| *name);
+            arena.alloc_slice_copy(&globals)
+        };
 
         // Evaluate the expression
         // SAFETY: We transmute the expression lifetime to match the evaluator's arena lifetime.
@@ -258,8 +436,9 @@ impl<'arena> CompiledExpression<'arena> {
         // 1. The expression is only borrowed for the duration of eval()
         // 2. The actual data lives in 'arena which outlives 'value_arena in practice
         // 3. The evaluator doesn't store the expression reference
+        let typed_expr = self.typed_expr().expect("bound bytecode runs on the VM");
         let expr_for_eval: &'value_arena TypedExpr<'arena, 'value_arena> =
-            unsafe { core::mem::transmute(self.typed_expr) };
+            unsafe { core::mem::transmute(typed_expr) };
 
         // Create evaluator and execute
         let mut evaluator = Evaluator::new(
@@ -270,20 +449,298 @@ impl<'arena> CompiledExpression<'arena> {
             globals,
             variables_slice,
         );
+        evaluator.set_steps(steps);
 
         // Evaluate and convert errors to public Error type
         evaluator.eval().map_err(Error::from)
     }
 
+    /// Get the expression's parameters.
+    ///
+    /// Returns (name, type) pairs in declaration order, which is the order in
+    /// which [`run`](Self::run) expects its arguments. Hosts can use this to
+    /// discover the inputs an expression needs, e.g. to render an input form.
+    pub fn parameters(&self) -> &[(&'arena str, &'arena Type<'arena>)] {
+        self.params
+    }
+
     /// Get the expression's parameters.
     ///
     /// Returns a slice of (name, type) pairs.
+    #[deprecated(note = "renamed to `parameters`")]
     pub fn params(&self) -> &[(&'arena str, &'arena Type<'arena>)] {
-        self.params
+        self.parameters()
     }
 
     /// Get the expression's return type.
+    ///
+    /// This is the type the analyzer inferred for the whole expression, known
+    /// without running it, so hosts can check it against the type they expect.
     pub fn return_type(&self) -> &'arena Type<'arena> {
-        self.typed_expr.expr.0
+        match &self.body {
+            Body::Tree(typed_expr) => typed_expr.expr.0,
+            Body::Bytecode(program) => program.return_type(),
+        }
+    }
+
+    /// Get a human-readable signature combining the parameters and the
+    /// return type, such as `(event: Record[to: Str]) => Bool`, for generated
+    /// docs and tooling.
+    ///
+    /// Type variables are renumbered from `_0` in order of appearance, so the
+    /// signature doesn't depend on how many variables the analyzer created.
+    pub fn pretty_signature(&self) -> String {
+        let converter = AlphaConverter::new(self.type_manager, 0);
+        let params: Vec<String> = self
+            .params
+            .iter()
+            .map(|(name, ty)| format!("{}: {}", name, converter.convert(ty)))
+            .collect();
+        let return_type = converter.convert(self.return_type());
+        format!("({}) => {}", params.join(", "), return_type)
+    }
+
+    /// Get a static estimate of the cost of running the expression.
+    ///
+    /// The estimate weights each operation by its kind: function calls cost
+    /// more than primitive operations, and lambda bodies are weighted as if
+    /// run several times. It is a heuristic for comparing expressions (e.g.
+    /// to reject overly expensive rules), not a bound on execution time.
+    pub fn estimated_cost(&self) -> u64 {
+        let typed_expr = match &self.body {
+            Body::Tree(typed_expr) => typed_expr,
+            // Estimated on the source when the artifact was made.
+            Body::Bytecode(program) => return program.estimated_cost(),
+        };
+        // Each module rule the expression depends on runs once per run.
+        self.dependencies
+            .iter()
+            .filter_map(|(_, rule)| rule.typed_expr())
+            .fold(CostEstimator.transform(typed_expr.expr), |cost, rule| {
+                cost.saturating_add(CostEstimator.transform(rule.expr))
+            })
+    }
+
+    /// Check whether the expression always produces the same result for the
+    /// same arguments.
+    ///
+    /// This holds when every function the expression can call is
+    /// deterministic (see [`Function::is_deterministic`]): standard library
+    /// functions are, except for ones like `Math.Random`, while host
+    /// functions are only if registered as such. Hosts can use this to decide
+    /// whether the results of an expression may be cached.
+    ///
+    /// The check is static and conservative: `false` means the expression
+    /// *may* be non-deterministic.
+    ///
+    /// For an expression bound from a [`CompiledArtifact`], the check only
+    /// sees the globals and parameters the code is bound to, so any of them
+    /// holding a non-deterministic function makes it `false`.
+    ///
+    /// [`Function::is_deterministic`]: crate::values::Function::is_deterministic
+    pub fn is_deterministic(&self) -> bool {
+        if let Body::Bytecode(program) = &self.body {
+            return !self.params.iter().any(|(_, ty)| ty.contains_function())
+                && program.globals_are_deterministic();
+        }
+        core::iter::once(self)
+            .chain(self.dependencies.iter().map(|(_, rule)| *rule))
+            .all(|expr| {
+                let Some(typed_expr) = expr.typed_expr() else {
+                    return false;
+                };
+                PurityChecker {
+                    params: expr.params,
+                    environment: expr.environment,
+                    lazy_globals: expr.lazy_globals,
+                }
+                .transform(typed_expr.expr)
+            })
+    }
+
+    /// Compile the expression to bytecode and report its size metrics.
+    ///
+    /// Expressions compiled from source run on the tree-walking evaluator,
+    /// so the bytecode is only built for this report, while expressions
+    /// bound from a [`CompiledArtifact`] report the code they run. Unlike
+    /// [`run`](Self::run), this does not build the lazily registered globals
+    /// the expression references: those not built yet are compiled as
+    /// locals, like parameters, instead of constants. For a rule of a
+    /// module, the report covers that rule only, not the rules it uses.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use melbi_core::api::{Engine, EngineOptions};
+    /// use bumpalo::Bump;
+    ///
+    /// let arena = Bump::new();
+    /// let engine = Engine::new(EngineOptions::default(), &arena, |_,_,_| {});
+    /// let int_ty = engine.type_manager().int();
+    /// let expr = engine
+    ///     .compile(Default::default(), "x * 1000", &[("x", int_ty)])
+    ///     .unwrap();
+    ///
+    /// let stats = expr.compilation_stats().unwrap();
+    /// assert_eq!(stats.num_locals, 1);
+    /// assert_eq!(stats.call_sites, 0);
+    /// ```
+    pub fn compilation_stats(&self) -> Result<CompilationStats, Error> {
+        let typed_expr = match &self.body {
+            Body::Tree(typed_expr) => typed_expr,
+            Body::Bytecode(program) => return Ok(program.compilation_stats()),
+        };
+        let arena = Bump::new();
+        let mut globals = Vec::with_capacity(self.environment.len() + self.lazy_globals.len());
+        globals.extend_from_slice(self.environment);
+        let mut unbuilt = Vec::new();
+        for global in self.lazy_globals {
+            match global.get() {
+                Some(value) => globals.push((global.name, value)),
+                None => unbuilt.push(global.name),
+            }
+        }
+        globals.sort_by_key(|(name, _)| *name);
+        let params: Vec<&str> = self
+            .params
+            .iter()
+            .map(|(name, _)| *name)
+            .chain(self.rule_bindings.iter().copied())
+            .chain(unbuilt)
+            .collect();
+        // SAFETY: As in `run_unchecked`, the expression is only borrowed while
+        // compiling into the local arena, and the resulting code is only
+        // measured, never run, before the arena is dropped.
+        let typed_expr: &TypedExpr<'arena, '_> = unsafe { core::mem::transmute(*typed_expr) };
+        let code = BytecodeCompiler::compile_with_params(
+            self.type_manager,
+            &arena,
+            arena.alloc_slice_copy(&globals),
+            &params,
+            typed_expr,
+            &self.compile_options,
+        )?;
+        Ok(CompilationStats::from_code(&code))
+    }
+
+    /// Get the source code of the expression.
+    pub fn source(&self) -> &'arena str {
+        match &self.body {
+            Body::Tree(typed_expr) => typed_expr.ann.source,
+            Body::Bytecode(program) => program.source(),
+        }
+    }
+
+    /// Get the attributes leading the source, in source order.
+    ///
+    /// Attributes such as `@priority(10)` or `@name("spam-filter")` are
+    /// metadata for the host and do not affect evaluation.
+    pub fn attributes(&self) -> &[Attribute<'arena>] {
+        self.attributes
+    }
+
+    /// Get the value of the attribute named `name`, if present.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use melbi_core::api::{Engine, EngineOptions};
+    /// use melbi_core::parser::AttributeValue;
+    /// use bumpalo::Bump;
+    ///
+    /// let arena = Bump::new();
+    /// let engine = Engine::new(EngineOptions::default(), &arena, |_,_,_| {});
+    /// let expr = engine
+    ///     .compile(Default::default(), "@priority(10) @enabled 1 + 2", &[])
+    ///     .unwrap();
+    ///
+    /// assert_eq!(expr.attribute("priority"), Some(&AttributeValue::Int(10)));
+    /// assert_eq!(expr.attribute("enabled"), Some(&AttributeValue::Bool(true)));
+    /// assert_eq!(expr.attribute("name"), None);
+    /// ```
+    pub fn attribute(&self, name: &str) -> Option<&AttributeValue<'arena>> {
+        self.attributes
+            .iter()
+            .find(|attribute| attribute.name == name)
+            .map(|attribute| &attribute.value)
+    }
+
+    /// Get the options the expression was compiled with.
+    pub fn compile_options(&self) -> &CompileOptions {
+        &self.compile_options
+    }
+
+    /// Get the globals from the environment that the expression references.
+    pub(crate) fn referenced_globals(&self) -> Vec<(&'arena str, &'arena Type<'arena>)> {
+        let arena = Bump::new();
+        let parse_options = self.compile_options.parse_options();
+        let Ok(parsed) = crate::parser::parse_with_options(&arena, self.source(), parse_options)
+        else {
+            // Compiled expressions always parse.
+            return Vec::new();
+        };
+        crate::analyzer::free_variables(parsed.expr)
+            .into_iter()
+            .filter(|name| !self.params.iter().any(|(param, _)| param == name))
+            .filter(|name| !self.rule_bindings.contains(name))
+            .filter_map(|name| {
+                let eager = self
+                    .environment
+                    .iter()
+                    .find(|(global, _)| *global == name)
+                    .map(|(global, value)| (*global, value.ty));
+                let lazy = || {
+                    self.lazy_globals
+                        .iter()
+                        .find(|global| global.name == name)
+                        .map(|global| (global.name, global.ty))
+                };
+                eager.or_else(lazy)
+            })
+            .collect()
+    }
+
+    /// Compile the expression to serialized bytecode that takes `globals` as
+    /// locals after its parameters and rule bindings, so that the code holds
+    /// no values from the environment.
+    pub(crate) fn to_portable_bytecode(&self, globals: &[&str]) -> Result<Vec<u8>, Error> {
+        let typed_expr = self
+            .typed_expr()
+            .expect("artifacts are made from expressions compiled from source");
+        let arena = Bump::new();
+        let params: Vec<&str> = self
+            .params
+            .iter()
+            .map(|(name, _)| *name)
+            .chain(self.rule_bindings.iter().copied())
+            .chain(globals.iter().copied())
+            .collect();
+        // SAFETY: As in `compilation_stats`, the expression is only borrowed
+        // while compiling into the local arena, and the resulting code is
+        // only serialized before the arena is dropped.
+        let typed_expr: &TypedExpr<'arena, '_> = unsafe { core::mem::transmute(typed_expr) };
+        let code = BytecodeCompiler::compile_with_params(
+            self.type_manager,
+            &arena,
+            &[],
+            &params,
+            typed_expr,
+            &self.compile_options,
+        )?;
+        code.to_bytes()
+            .map_err(|err| Error::Api(format!("Failed to serialize bytecode: {}", err)))
+    }
+
+    /// Detach the expression from the engine, as an owned [`CompiledArtifact`]
+    /// that can be sent to other threads and bound to another engine.
+    ///
+    /// The artifact holds the expression compiled to bytecode, so binding it
+    /// does not compile the expression again. Fails if the expression cannot
+    /// be compiled to bytecode, or its code cannot be serialized.
+    pub fn to_artifact(&self) -> Result<CompiledArtifact, Error> {
+        match &self.body {
+            Body::Tree(_) => CompiledArtifact::from_expression(self),
+            Body::Bytecode(program) => Ok(program.artifact().clone()),
+        }
     }
 }
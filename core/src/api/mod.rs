@@ -32,16 +32,24 @@
 //! assert!((result.as_float().unwrap() - 6.28318).abs() < 0.0001);
 //! ```
 
+pub mod artifact;
+mod cost;
 pub mod engine;
 pub mod environment;
 pub mod error;
 pub mod expression;
 pub mod options;
+mod purity;
+mod stats;
 
+pub use artifact::CompiledArtifact;
 pub use engine::Engine;
 pub use environment::EnvironmentBuilder;
 pub use error::{Diagnostic, Error, RelatedInfo, Severity};
 pub use expression::CompiledExpression;
+pub use stats::CompilationStats;
 pub use options::{
-    CompileOptions, CompileOptionsOverride, EngineOptions, RunOptions, RunOptionsOverride,
+    ArgumentCoercion, CompileOptions, CompileOptionsOverride, DEFAULT_RANDOM_SEED,
+    DuplicateKeyPolicy, EngineOptions, InfixOperator, IntegerDivision, LanguageVersion,
+    OperatorPrecedence, RunOptions, RunOptionsOverride,
 };
@@ -1,5 +1,13 @@
 //! Configuration options for the Melbi engine.
 
+pub use crate::analyzer::{ArgumentCoercion, DuplicateKeyPolicy, IntegerDivision};
+pub use crate::parser::{InfixOperator, LanguageVersion, OperatorPrecedence};
+pub use crate::values::DEFAULT_RANDOM_SEED;
+
+use crate::analyzer::AnalyzerOptions;
+use crate::parser::ParseOptions;
+use crate::values::StepBudget;
+
 /// Configuration options for the Melbi engine.
 ///
 /// These options set the defaults for compilation and execution,
@@ -15,6 +23,7 @@
 ///     default_run_options: RunOptions {
 ///         max_depth: 500,
 ///         max_iterations: Some(10_000),
+///         ..Default::default()
 ///     },
 /// };
 /// ```
@@ -53,7 +62,86 @@ impl Default for EngineOptions {
 /// ```
 #[derive(Debug, Clone)]
 pub struct CompileOptions {
-    // Future: optimization level, type checking strictness, etc.
+    /// Accept array literals mixing `Option[T]` and `T` elements, wrapping
+    /// the plain elements in `some` (e.g. `[1, none]` is `Array[Option[Int]]`).
+    ///
+    /// Default: `false` (arrays are strictly homogeneous).
+    pub promote_to_option: bool,
+
+    /// Make `/` on `Int` operands produce a `Float` (true division), so
+    /// `5 / 2` is `2.5`. Integer division remains available via `Int.Quot`.
+    ///
+    /// Default: `false` (`/` on `Int` is integer division, rounded as set by
+    /// [`integer_division`](Self::integer_division)).
+    pub float_division: bool,
+
+    /// How `/` and `%` on `Int` operands round when an operand is negative.
+    /// Under [`IntegerDivision::Truncated`], `-7 / 2` is `-3` and `-7 % 2` is
+    /// `-1`; under [`IntegerDivision::Floored`] they are `-4` and `1`.
+    ///
+    /// Default: [`IntegerDivision::Euclidean`] (the remainder is never negative).
+    pub integer_division: IntegerDivision,
+
+    /// Let comparisons mix `Int` and `Float` operands, converting the `Int`
+    /// one to `Float` first, so `1 == 1.0` is `true`.
+    ///
+    /// Default: `false` (comparing an `Int` with a `Float` is a type error).
+    pub mixed_numeric_comparison: bool,
+
+    /// What to do when a map is built with the same key more than once.
+    /// Under [`DuplicateKeyPolicy::Error`], repeated literal keys fail to
+    /// compile and colliding computed keys fail at runtime.
+    ///
+    /// Default: [`DuplicateKeyPolicy::LastWins`].
+    pub duplicate_map_keys: DuplicateKeyPolicy,
+
+    /// Whether function calls accept arguments that can be converted to the
+    /// declared parameter type. Under [`ArgumentCoercion::Lenient`], an `Int`
+    /// can be passed where a function declares a `Float` parameter.
+    ///
+    /// Default: [`ArgumentCoercion::Strict`].
+    pub argument_coercion: ArgumentCoercion,
+
+    /// Which version of the syntax to accept. Pinning an older version
+    /// rejects constructs introduced after it, such as the pipe operator
+    /// (`|>`) under [`LanguageVersion::V1`].
+    ///
+    /// Default: [`LanguageVersion::LATEST`].
+    pub language_version: LanguageVersion,
+
+    /// Precedence of the infix operators, for embedders whose users expect
+    /// a different grouping (e.g. `and` binding tighter than `==`).
+    /// [`OperatorPrecedence::with_level`] only builds unambiguous tables.
+    ///
+    /// Default: [`OperatorPrecedence::DEFAULT`].
+    pub operator_precedence: OperatorPrecedence,
+
+    /// Report a warning diagnostic for each lambda parameter that is never
+    /// used, as `y` in `(x, y) => x`. Prefixing a parameter with `_` (as in
+    /// `_y`) marks it as intentionally unused. Warnings are only visible
+    /// through [`Engine::compile_with_diagnostics`](crate::api::Engine::compile_with_diagnostics)
+    /// and never make compilation fail.
+    ///
+    /// Default: `false`.
+    pub warn_unused_parameters: bool,
+
+    /// Run the peephole optimizer over the generated bytecode, removing
+    /// redundant instructions such as jumps to the next instruction and
+    /// `not` after an integer comparison. Only affects
+    /// [`BytecodeCompiler::compile_with_options`](crate::compiler::BytecodeCompiler::compile_with_options).
+    ///
+    /// Default: `true`.
+    pub peephole_optimization: bool,
+
+    /// Evaluate operations whose operands are all constants at compile time
+    /// and emit their result as a single constant, so `60 * 60 * 24` costs
+    /// nothing at runtime. Operations that would fail, such as `1 / 0`, are
+    /// left for the VM to report. Only affects
+    /// [`BytecodeCompiler::compile_with_options`](crate::compiler::BytecodeCompiler::compile_with_options).
+    ///
+    /// Default: `true`.
+    pub constant_folding: bool,
+    // Future: optimization level, etc.
 }
 
 impl CompileOptions {
@@ -61,17 +149,97 @@ impl CompileOptions {
     ///
     /// For each field, if `other` specifies a value (is `Some`), use it.
     /// Otherwise, keep the value from `self`.
-    pub fn override_with(&mut self, _other: &CompileOptionsOverride) {}
+    pub fn override_with(&mut self, other: &CompileOptionsOverride) {
+        if let Some(promote_to_option) = other.promote_to_option {
+            self.promote_to_option = promote_to_option;
+        }
+        if let Some(float_division) = other.float_division {
+            self.float_division = float_division;
+        }
+        if let Some(integer_division) = other.integer_division {
+            self.integer_division = integer_division;
+        }
+        if let Some(mixed_numeric_comparison) = other.mixed_numeric_comparison {
+            self.mixed_numeric_comparison = mixed_numeric_comparison;
+        }
+        if let Some(duplicate_map_keys) = other.duplicate_map_keys {
+            self.duplicate_map_keys = duplicate_map_keys;
+        }
+        if let Some(argument_coercion) = other.argument_coercion {
+            self.argument_coercion = argument_coercion;
+        }
+        if let Some(language_version) = other.language_version {
+            self.language_version = language_version;
+        }
+        if let Some(operator_precedence) = other.operator_precedence {
+            self.operator_precedence = operator_precedence;
+        }
+        if let Some(warn_unused_parameters) = other.warn_unused_parameters {
+            self.warn_unused_parameters = warn_unused_parameters;
+        }
+        if let Some(peephole_optimization) = other.peephole_optimization {
+            self.peephole_optimization = peephole_optimization;
+        }
+        if let Some(constant_folding) = other.constant_folding {
+            self.constant_folding = constant_folding;
+        }
+    }
+
+    /// Parser options matching these compile options.
+    pub(crate) fn parse_options(&self) -> ParseOptions {
+        ParseOptions {
+            language_version: self.language_version,
+            operator_precedence: self.operator_precedence,
+            ..Default::default()
+        }
+    }
+
+    /// Analyzer options matching these compile options.
+    pub(crate) fn analyzer_options(&self) -> AnalyzerOptions {
+        AnalyzerOptions {
+            promote_to_option: self.promote_to_option,
+            float_division: self.float_division,
+            integer_division: self.integer_division,
+            mixed_numeric_comparison: self.mixed_numeric_comparison,
+            duplicate_map_keys: self.duplicate_map_keys,
+            argument_coercion: self.argument_coercion,
+            warn_unused_parameters: self.warn_unused_parameters,
+        }
+    }
 }
 
 impl Default for CompileOptions {
     fn default() -> Self {
-        Self {}
+        Self {
+            promote_to_option: false,
+            float_division: false,
+            integer_division: IntegerDivision::Euclidean,
+            mixed_numeric_comparison: false,
+            duplicate_map_keys: DuplicateKeyPolicy::LastWins,
+            argument_coercion: ArgumentCoercion::Strict,
+            language_version: LanguageVersion::LATEST,
+            operator_precedence: OperatorPrecedence::DEFAULT,
+            warn_unused_parameters: false,
+            peephole_optimization: true,
+            constant_folding: true,
+        }
     }
 }
 
 #[derive(Debug, Clone, Copy, Default)]
-pub struct CompileOptionsOverride {}
+pub struct CompileOptionsOverride {
+    pub promote_to_option: Option<bool>,
+    pub float_division: Option<bool>,
+    pub integer_division: Option<IntegerDivision>,
+    pub mixed_numeric_comparison: Option<bool>,
+    pub duplicate_map_keys: Option<DuplicateKeyPolicy>,
+    pub argument_coercion: Option<ArgumentCoercion>,
+    pub language_version: Option<LanguageVersion>,
+    pub operator_precedence: Option<OperatorPrecedence>,
+    pub warn_unused_parameters: Option<bool>,
+    pub peephole_optimization: Option<bool>,
+    pub constant_folding: Option<bool>,
+}
 
 /// Configuration options for expression execution.
 ///
@@ -88,6 +256,7 @@ pub struct CompileOptionsOverride {}
 /// let options = RunOptions {
 ///     max_depth: 500,
 ///     max_iterations: None,
+///     ..Default::default()
 /// };
 /// ```
 #[derive(Debug, Clone, Copy)]
@@ -106,6 +275,35 @@ pub struct RunOptions {
     /// TODO: Consider using a custom enum like `IterationLimit { Unlimited, Limited(usize) }`
     /// instead of nested Option for better ergonomics.
     pub max_iterations: Option<usize>,
+
+    /// Seed for `Math.Random`.
+    ///
+    /// Runs with the same seed and arguments produce the same sequence of
+    /// random numbers. The default is the fixed [`DEFAULT_RANDOM_SEED`], not
+    /// OS entropy, so evaluation stays deterministic unless the host opts in
+    /// by passing a varying seed.
+    pub random_seed: u64,
+
+    /// Maximum number of evaluation steps, bounding the total work of a run.
+    ///
+    /// A step is one evaluated expression node, including the nodes of
+    /// lambdas called by native functions. `None` means unlimited (the
+    /// default). Exceeding the limit fails with [`Error::ResourceExceeded`].
+    ///
+    /// [`Error::ResourceExceeded`]: crate::api::Error::ResourceExceeded
+    pub max_steps: Option<u64>,
+
+    /// Point in time after which execution is aborted with
+    /// [`Error::ResourceExceeded`]. `None` means no deadline (the default).
+    ///
+    /// The clock is only read every few thousand steps, so execution may run
+    /// slightly past the deadline. Native functions are not interrupted: a
+    /// single long call only notices the deadline once it returns. Without
+    /// the `std` feature there is no clock; use `max_steps` instead.
+    ///
+    /// [`Error::ResourceExceeded`]: crate::api::Error::ResourceExceeded
+    #[cfg(feature = "std")]
+    pub deadline: Option<std::time::Instant>,
 }
 
 impl RunOptions {
@@ -120,6 +318,25 @@ impl RunOptions {
         if let Some(max_iterations) = other.max_iterations {
             self.max_iterations = max_iterations;
         }
+        if let Some(random_seed) = other.random_seed {
+            self.random_seed = random_seed;
+        }
+        if let Some(max_steps) = other.max_steps {
+            self.max_steps = max_steps;
+        }
+        #[cfg(feature = "std")]
+        if let Some(deadline) = other.deadline {
+            self.deadline = deadline;
+        }
+    }
+
+    /// The step budget of one execution: `max_steps` and the deadline bound
+    /// everything a run does, including the module rules it depends on.
+    pub(crate) fn step_budget(&self) -> StepBudget {
+        let steps = StepBudget::new(self.max_steps);
+        #[cfg(feature = "std")]
+        let steps = steps.with_deadline(self.deadline);
+        steps
     }
 }
 
@@ -128,6 +345,10 @@ impl Default for RunOptions {
         Self {
             max_depth: 1000,
             max_iterations: None, // Unlimited by default
+            random_seed: DEFAULT_RANDOM_SEED,
+            max_steps: None, // Unlimited by default
+            #[cfg(feature = "std")]
+            deadline: None,
         }
     }
 }
@@ -136,4 +357,8 @@ impl Default for RunOptions {
 pub struct RunOptionsOverride {
     pub max_depth: Option<usize>,
     pub max_iterations: Option<Option<usize>>,
+    pub random_seed: Option<u64>,
+    pub max_steps: Option<Option<u64>>,
+    #[cfg(feature = "std")]
+    pub deadline: Option<Option<std::time::Instant>>,
 }
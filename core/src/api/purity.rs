@@ -0,0 +1,152 @@
+//! Static check that a compiled expression is deterministic.
+//!
+//! An expression is deterministic when every function it can reach reports
+//! [`Function::is_deterministic`]. Lambdas written in the expression itself
+//! are checked through their bodies; functions coming from the environment
+//! are looked up by following the global and field accesses in the tree
+//! (e.g. `Math.Sqrt`). Functions passed in as parameters are host callbacks
+//! and are never considered deterministic, and neither are functions in
+//! lazily registered globals, which the check does not build.
+//!
+//! The check is conservative: a local binding that shadows a global is
+//! treated as the global, which can only report a deterministic expression
+//! as non-deterministic, never the opposite.
+//!
+//! [`Function::is_deterministic`]: crate::values::Function::is_deterministic
+
+use super::environment::LazyGlobal;
+use crate::{
+    Vec,
+    analyzer::typed_expr::{Expr, ExprBuilder, ExprInner},
+    types::Type,
+    values::dynamic::Value,
+    visitor::{TreeTransformer, TreeView},
+};
+
+/// Decides whether a typed expression only reaches deterministic functions.
+pub(crate) struct PurityChecker<'arena> {
+    /// Parameters of the expression.
+    pub params: &'arena [(&'arena str, &'arena Type<'arena>)],
+    /// Globals the expression was compiled against.
+    pub environment: &'arena [(&'arena str, Value<'arena, 'arena>)],
+    /// Lazily registered globals the expression references.
+    pub lazy_globals: &'arena [&'arena LazyGlobal<'arena>],
+}
+
+impl<'arena> TreeTransformer<ExprBuilder<'arena, 'arena>> for PurityChecker<'arena> {
+    type Output = bool;
+
+    fn transform(&mut self, tree: &'arena Expr<'arena, 'arena>) -> bool {
+        match tree.view() {
+            ExprInner::Constant(_) => true,
+            ExprInner::Ident(name) => self.is_name_deterministic(name, &[]),
+            ExprInner::Field { value, field } => {
+                // Follow field accesses on a global as far as possible, so
+                // that `Math.Sqrt` only checks `Sqrt` and not all of `Math`.
+                let mut path = Vec::from([field]);
+                let mut base = value;
+                while let ExprInner::Field { value, field } = &base.1 {
+                    path.push(*field);
+                    base = value;
+                }
+                match &base.1 {
+                    ExprInner::Ident(name) => {
+                        path.reverse();
+                        self.is_name_deterministic(name, &path)
+                    }
+                    _ => self.transform(base),
+                }
+            }
+            ExprInner::Binary { left, right, .. }
+            | ExprInner::Boolean { left, right, .. }
+            | ExprInner::Comparison { left, right, .. } => self.all([left, right].into_iter()),
+            ExprInner::Unary { expr, .. } | ExprInner::Cast { expr } => self.transform(expr),
+            ExprInner::Call { callable, args, .. } => {
+                self.all(core::iter::once(callable).chain(args.iter().copied()))
+            }
+            ExprInner::Index { value, index } => self.all([value, index].into_iter()),
+            ExprInner::Slice { value, start, end } => {
+                self.all(core::iter::once(value).chain(start).chain(end))
+            }
+            ExprInner::Lambda { body, .. } => self.transform(body),
+            ExprInner::If {
+                cond,
+                then_branch,
+                else_branch,
+            } => self.all([cond, then_branch, else_branch].into_iter()),
+            ExprInner::Where { expr, bindings } => {
+                self.all(core::iter::once(expr).chain(bindings.iter().map(|(_, value)| *value)))
+            }
+            ExprInner::Otherwise { primary, fallback } => self.all([primary, fallback].into_iter()),
+            ExprInner::Coalesce { option, default } => self.all([option, default].into_iter()),
+            ExprInner::Option { inner } => inner.is_none_or(|inner| self.transform(inner)),
+            ExprInner::Match { expr, arms } => {
+                self.all(core::iter::once(expr).chain(arms.iter().map(|arm| arm.body)))
+            }
+            ExprInner::Record { fields } => self.all(fields.iter().map(|(_, value)| *value)),
+            ExprInner::Map { elements, .. } => {
+                self.all(elements.iter().flat_map(|(key, value)| [*key, *value]))
+            }
+            ExprInner::Array { elements } => self.all(elements.iter().copied()),
+            ExprInner::FormatStr { exprs, .. } => self.all(exprs.iter().copied()),
+        }
+    }
+}
+
+impl<'arena> PurityChecker<'arena> {
+    fn all(&mut self, mut exprs: impl Iterator<Item = &'arena Expr<'arena, 'arena>>) -> bool {
+        exprs.all(|expr| self.transform(expr))
+    }
+
+    /// Check the value reached by `name.path[0].path[1]...`.
+    fn is_name_deterministic(&self, name: &str, path: &[&str]) -> bool {
+        // Parameters shadow globals.
+        if let Some((_, ty)) = self.params.iter().find(|(param, _)| *param == name) {
+            return !ty.contains_function();
+        }
+        if let Some(global) = self.lazy_globals.iter().find(|global| global.name == name) {
+            return !global.ty.contains_function();
+        }
+        let Some((_, global)) = self.environment.iter().find(|(global, _)| *global == name) else {
+            // A local binding: its definition is checked where it appears.
+            return true;
+        };
+        let mut value = *global;
+        for field in path {
+            let Some(field_value) = value.as_record().ok().and_then(|record| record.get(field))
+            else {
+                break;
+            };
+            value = field_value;
+        }
+        is_value_deterministic(&value)
+    }
+}
+
+/// Whether every function reachable from `value` is deterministic.
+pub(super) fn is_value_deterministic(value: &Value<'_, '_>) -> bool {
+    if !value.ty.contains_function() {
+        return true;
+    }
+    match value.ty {
+        Type::Function { .. } => value
+            .as_function()
+            .is_ok_and(|function| function.is_deterministic()),
+        Type::Record(_) => value.as_record().is_ok_and(|record| {
+            record
+                .iter()
+                .all(|(_, field)| is_value_deterministic(&field))
+        }),
+        Type::Array(_) => value
+            .as_array()
+            .is_ok_and(|array| array.iter().all(|element| is_value_deterministic(&element))),
+        Type::Map(_, _) => value.as_map().is_ok_and(|map| {
+            map.iter()
+                .all(|(key, value)| is_value_deterministic(&key) && is_value_deterministic(&value))
+        }),
+        Type::Option(_) => value
+            .as_option()
+            .is_ok_and(|inner| inner.is_none_or(|inner| is_value_deterministic(&inner))),
+        _ => true,
+    }
+}
@@ -0,0 +1,56 @@
+//! Size and complexity metrics of compiled expressions.
+
+use crate::vm::{Code, Instruction, LambdaKind};
+
+/// Metrics about the bytecode of a compiled expression.
+///
+/// Meant for rule-management tools, e.g. to display or limit the size of
+/// the rules users write. Returned by
+/// [`CompiledExpression::compilation_stats`](super::CompiledExpression::compilation_stats).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct CompilationStats {
+    /// Number of instructions, including the bodies of lambdas.
+    pub instruction_count: usize,
+    /// Number of local variable slots of the top-level code, counting one
+    /// slot per parameter.
+    pub num_locals: usize,
+    /// Number of entries in the constant pools, including those of lambdas.
+    pub constant_pool_size: usize,
+    /// Maximum depth of the operand stack of the top-level code.
+    pub max_stack_depth: usize,
+    /// Number of function calls in the bytecode, including the bodies of
+    /// lambdas. Native functions and lambdas are called alike, through a
+    /// function adapter, so both are counted.
+    pub call_sites: usize,
+}
+
+impl CompilationStats {
+    /// Collects the metrics of `code`.
+    pub(crate) fn from_code(code: &Code<'_>) -> Self {
+        let mut stats = CompilationStats {
+            num_locals: code.num_locals,
+            max_stack_depth: code.max_stack_size,
+            ..Default::default()
+        };
+        stats.add_sizes(code);
+        stats
+    }
+
+    /// Adds the sizes of `code` and of its lambdas.
+    fn add_sizes(&mut self, code: &Code<'_>) {
+        self.instruction_count += code.instructions.len();
+        self.constant_pool_size += code.constants.len();
+        self.call_sites += code
+            .instructions
+            .iter()
+            .filter(|instr| matches!(instr, Instruction::Call(_)))
+            .count();
+        for lambda in &code.lambdas {
+            // Polymorphic lambdas only point at their instantiations, which
+            // are lambdas of `code` themselves.
+            if let LambdaKind::Mono { code } = &lambda.kind {
+                self.add_sizes(code);
+            }
+        }
+    }
+}
@@ -4,7 +4,11 @@ use alloc::boxed::Box;
 
 use crate::{
     Vec,
-    analyzer::typed_expr::{Expr, ExprBuilder, LambdaInstantiations, TypedExpr},
+    analyzer::{
+        ArgumentCoercion, DuplicateKeyPolicy, IntegerDivision,
+        typed_expr::{Expr, ExprBuilder, LambdaInstantiations, TypedExpr},
+    },
+    api::CompileOptions,
     parser::ComparisonOp,
     scope_stack::{CompleteScope, IncompleteScope, ScopeStack},
     types::{
@@ -16,13 +20,16 @@ use crate::{
     values::dynamic::Value,
     visitor::TreeTransformer,
     vm::{
-        ArrayContainsAdapter, CastAdapter, Code, FormatStrAdapter, FunctionAdapter,
-        GenericAdapter, Instruction, LambdaCode, LambdaKind,
+        ArrayContainsAdapter, CastAdapter, Code, FormatStrAdapter, FunctionAdapter, GenericAdapter,
+        Instruction, LambdaCode, LambdaKind, MakeMapAdapter, MapContainsAdapter, MapGetAdapter,
+        OptionCompareAdapter,
     },
 };
 use bumpalo::Bump;
 
-use super::error::CompileError;
+use super::{
+    constant_folding::fold_constant, dead_bindings::unused_bindings, error::CompileError, peephole,
+};
 
 /// A pending jump that needs to be patched to the next match arm.
 ///
@@ -118,6 +125,14 @@ pub struct BytecodeCompiler<'types, 'arena> {
     /// Used to resolve type variables to concrete types.
     /// None for top-level code and monomorphic lambdas.
     monomorphism: Option<Unification<'types, &'types TypeManager<'types>>>,
+
+    /// Whether to run the peephole optimizer over the emitted instructions.
+    /// Inherited by the compilers of nested lambdas.
+    peephole_optimization: bool,
+
+    /// Whether to evaluate operations on constants at compile time.
+    /// Inherited by the compilers of nested lambdas.
+    constant_folding: bool,
 }
 
 impl<'types, 'arena> BytecodeCompiler<'types, 'arena> {
@@ -168,6 +183,8 @@ impl<'types, 'arena> BytecodeCompiler<'types, 'arena> {
             lambdas: alloc::vec::Vec::new(),
             lambda_instantiations,
             monomorphism: None,
+            peephole_optimization: true,
+            constant_folding: true,
         }
     }
 
@@ -215,6 +232,8 @@ impl<'types, 'arena> BytecodeCompiler<'types, 'arena> {
             lambdas: alloc::vec::Vec::new(),
             lambda_instantiations: None, // Lambda compilers don't need instantiation info
             monomorphism,
+            peephole_optimization: true,
+            constant_folding: true,
         }
     }
 
@@ -222,16 +241,14 @@ impl<'types, 'arena> BytecodeCompiler<'types, 'arena> {
     ///
     /// Converts Value constants (with type info) to RawValue for VM execution.
     pub fn finalize(self) -> Code<'types> {
-        // Convert Values to RawValues for VM
+        // Convert Values to RawValues for VM, keeping their types apart
         // TODO: In debug mode, we could keep Values for better error messages
-        let raw_constants = self
-            .constants
-            .into_iter()
-            .map(|value| value.as_raw())
-            .collect();
+        let raw_constants = self.constants.iter().map(|value| value.as_raw()).collect();
+        let constant_types = self.constants.iter().map(|value| value.ty).collect();
 
         Code {
             constants: raw_constants,
+            constant_types,
             adapters: self.adapters,
             generic_adapters: self.generic_adapters,
             instructions: self.instructions,
@@ -253,6 +270,45 @@ impl<'types, 'arena> BytecodeCompiler<'types, 'arena> {
         arena: &'arena Bump,
         globals: &'arena [(&'arena str, Value<'types, 'arena>)],
         typed_expr: &'arena TypedExpr<'types, 'arena>,
+    ) -> Result<Code<'types>, CompileError> {
+        Self::compile_with_options(
+            type_mgr,
+            arena,
+            globals,
+            typed_expr,
+            &CompileOptions::default(),
+        )
+    }
+
+    /// Like [`compile`](Self::compile), but with custom compile options.
+    ///
+    /// Only the bytecode-related options (such as
+    /// [`CompileOptions::peephole_optimization`] and
+    /// [`CompileOptions::constant_folding`]) apply here; the others
+    /// configure the analysis that produced `typed_expr`.
+    pub fn compile_with_options(
+        type_mgr: &'types TypeManager<'types>,
+        arena: &'arena Bump,
+        globals: &'arena [(&'arena str, Value<'types, 'arena>)],
+        typed_expr: &'arena TypedExpr<'types, 'arena>,
+        options: &CompileOptions,
+    ) -> Result<Code<'types>, CompileError> {
+        Self::compile_with_params(type_mgr, arena, globals, &[], typed_expr, options)
+    }
+
+    /// Like [`compile_with_options`](Self::compile_with_options), for an
+    /// expression analyzed with the variables `params`.
+    ///
+    /// The parameters, which must be distinct, take the first local slots in
+    /// order, so the code must be run with the arguments as its initial
+    /// locals (see [`VM::new`](crate::vm::VM::new)).
+    pub fn compile_with_params(
+        type_mgr: &'types TypeManager<'types>,
+        arena: &'arena Bump,
+        globals: &'arena [(&'arena str, Value<'types, 'arena>)],
+        params: &[&'arena str],
+        typed_expr: &'arena TypedExpr<'types, 'arena>,
+        options: &CompileOptions,
     ) -> Result<Code<'types>, CompileError> {
         let lambda_instantiations = if typed_expr.lambda_instantiations.is_empty() {
             None
@@ -260,10 +316,27 @@ impl<'types, 'arena> BytecodeCompiler<'types, 'arena> {
             Some(&typed_expr.lambda_instantiations)
         };
         let mut compiler = Self::new(type_mgr, arena, globals, lambda_instantiations);
+        compiler.peephole_optimization = options.peephole_optimization;
+        compiler.constant_folding = options.constant_folding;
+        if !params.is_empty() {
+            compiler
+                .scope_stack
+                .push(IncompleteScope::new(arena, params).expect("Duplicate parameter names"));
+            for &param in params {
+                let local_idx = compiler.allocate_local()?;
+                compiler
+                    .scope_stack
+                    .bind_in_current(param, ScopeEntry::Local(local_idx))
+                    .expect("Parameter binding");
+            }
+        }
         compiler.transform(typed_expr.expr)?;
         debug_assert_eq!(compiler.current_stack_depth, 1);
         // Emit Return instruction to signal end of execution
         compiler.emit(Instruction::Return);
+        if compiler.peephole_optimization {
+            peephole::optimize(&mut compiler.instructions);
+        }
         Ok(compiler.finalize())
     }
 
@@ -405,6 +478,8 @@ impl<'types, 'arena> BytecodeCompiler<'types, 'arena> {
         // Create fresh compiler for lambda
         let mut lambda_compiler =
             BytecodeCompiler::new_for_lambda(self.type_mgr, self.arena, captures, monomorphism);
+        lambda_compiler.peephole_optimization = self.peephole_optimization;
+        lambda_compiler.constant_folding = self.constant_folding;
 
         // Set up parameters as locals (in order)
         // Parameters are passed by the caller via VM locals
@@ -426,6 +501,9 @@ impl<'types, 'arena> BytecodeCompiler<'types, 'arena> {
 
         // Emit Return
         lambda_compiler.emit(Instruction::Return);
+        if lambda_compiler.peephole_optimization {
+            peephole::optimize(&mut lambda_compiler.instructions);
+        }
 
         // Return the compiled LambdaCode
         let num_captures = captures.len();
@@ -433,9 +511,10 @@ impl<'types, 'arena> BytecodeCompiler<'types, 'arena> {
         let code = Code {
             constants: lambda_compiler
                 .constants
-                .into_iter()
+                .iter()
                 .map(|v| v.as_raw())
                 .collect(),
+            constant_types: lambda_compiler.constants.iter().map(|v| v.ty).collect(),
             adapters: lambda_compiler.adapters,
             generic_adapters: lambda_compiler.generic_adapters,
             instructions: lambda_compiler.instructions,
@@ -470,6 +549,40 @@ impl<'types, 'arena> BytecodeCompiler<'types, 'arena> {
         index.try_into().map_err(|_| CompileError::TooManyConstants)
     }
 
+    /// Emit the instructions pushing a constant, using immediate encodings
+    /// for small integers and booleans.
+    fn compile_constant(&mut self, value: Value<'types, 'arena>) -> Result<(), CompileError> {
+        if let Ok(i) = value.as_int() {
+            // Use immediate encoding for small integers
+            if i >= i8::MIN as i64 && i <= i8::MAX as i64 {
+                self.emit(Instruction::ConstInt(i as i8));
+                self.push_stack();
+            } else if i >= 0 && i <= u8::MAX as i64 {
+                self.emit(Instruction::ConstUInt(i as u8));
+                self.push_stack();
+            } else {
+                // Large integer - use constant pool
+                let const_index = self.add_constant(value)?;
+                self.emit_with_arg(Instruction::ConstLoad, const_index);
+                self.push_stack();
+            }
+        } else if let Ok(b) = value.as_bool() {
+            // Use immediate encoding for booleans
+            if b {
+                self.emit(Instruction::ConstBool(1));
+            } else {
+                self.emit(Instruction::ConstBool(0));
+            }
+            self.push_stack();
+        } else {
+            // Other types (float, string, etc.) - use constant pool
+            let const_index = self.add_constant(value)?;
+            self.emit_with_arg(Instruction::ConstLoad, const_index);
+            self.push_stack();
+        }
+        Ok(())
+    }
+
     // === Jump Patching Infrastructure ===
 
     /// Reserve space for a jump instruction and return its index.
@@ -651,6 +764,23 @@ impl<'types, 'arena> BytecodeCompiler<'types, 'arena> {
                 // Stack effect: option consumed
                 self.pop_stack();
             }
+
+            TypedPattern::As { pattern, name } => {
+                // Binding pattern: bind a copy of the value, then match the inner pattern
+                self.emit(Instruction::DupN(0));
+                self.push_stack();
+
+                let index = self.allocate_local()?;
+                self.emit_with_arg(Instruction::StoreLocal, index);
+                self.pop_stack();
+
+                // Bind in the current scope (caller must have pushed a scope)
+                self.scope_stack
+                    .bind_in_current(name, ScopeEntry::Local(index))
+                    .expect("Pattern binding");
+
+                fail_jumps = self.compile_pattern(pattern, value_type)?;
+            }
         }
 
         Ok(fail_jumps)
@@ -671,41 +801,28 @@ where
             visitor::TreeView,
         };
 
+        // Operations on constants are evaluated now and emitted as one constant
+        let lookup_global = |name: &'arena str| match self.scope_stack.lookup(name) {
+            Some(ScopeEntry::Global(value)) => Some(*value),
+            _ => None,
+        };
+        if self.constant_folding
+            && let Some(value) = fold_constant(self.type_mgr, tree, &lookup_global)
+        {
+            return self.compile_constant(value);
+        }
+
         match tree.view() {
             // === Constants ===
-            ExprInner::Constant(value) => {
-                if let Ok(i) = value.as_int() {
-                    // Use immediate encoding for small integers
-                    if i >= i8::MIN as i64 && i <= i8::MAX as i64 {
-                        self.emit(Instruction::ConstInt(i as i8));
-                        self.push_stack();
-                    } else if i >= 0 && i <= u8::MAX as i64 {
-                        self.emit(Instruction::ConstUInt(i as u8));
-                        self.push_stack();
-                    } else {
-                        // Large integer - use constant pool
-                        let const_index = self.add_constant(value)?;
-                        self.emit_with_arg(Instruction::ConstLoad, const_index);
-                        self.push_stack();
-                    }
-                } else if let Ok(b) = value.as_bool() {
-                    // Use immediate encoding for booleans
-                    if b {
-                        self.emit(Instruction::ConstBool(1));
-                    } else {
-                        self.emit(Instruction::ConstBool(0));
-                    }
-                    self.push_stack();
-                } else {
-                    // Other types (float, string, etc.) - use constant pool
-                    let const_index = self.add_constant(value)?;
-                    self.emit_with_arg(Instruction::ConstLoad, const_index);
-                    self.push_stack();
-                }
-            }
+            ExprInner::Constant(value) => self.compile_constant(value)?,
 
             // === Binary Operations ===
-            ExprInner::Binary { op, left, right } => {
+            ExprInner::Binary {
+                op,
+                left,
+                right,
+                division,
+            } => {
                 // Compile left operand
                 self.transform(left)?;
 
@@ -714,24 +831,46 @@ where
 
                 // Emit operation instruction (pops 2, pushes 1)
                 self.pop_stack_n(2);
+
                 let op_byte = match op {
                     BinaryOp::Add => b'+',
                     BinaryOp::Sub => b'-',
                     BinaryOp::Mul => b'*',
                     BinaryOp::Div => b'/',
+                    BinaryOp::Mod => b'%',
                     BinaryOp::Pow => b'^',
+                    BinaryOp::BitwiseAnd => b'&',
+                    BinaryOp::BitwiseOr => b'|',
+                    BinaryOp::BitwiseXor => b'^',
+                    BinaryOp::ShiftLeft => b'<',
+                    BinaryOp::ShiftRight => b'>',
                 };
 
                 // Check if this is a float or int operation based on the result type
                 // Use resolve_type to handle polymorphic lambdas
                 let resolved_type = self.resolve_type(tree.0);
-                match resolved_type.view() {
-                    TypeKind::Float => self.emit(Instruction::FloatBinOp(op_byte)),
-                    TypeKind::Int => self.emit(Instruction::IntBinOp(op_byte)),
-                    _ => panic!(
-                        "Binary operation on non-numeric type: {} (type checker bug)",
-                        resolved_type
-                    ),
+                if op.is_bitwise() {
+                    // Bitwise operators only apply to Int
+                    self.emit(Instruction::IntBitOp(op_byte));
+                } else {
+                    match resolved_type.view() {
+                        TypeKind::Float => self.emit(Instruction::FloatBinOp(op_byte)),
+                        TypeKind::Int => {
+                            // Integer `/` and `%` have an opcode per rounding mode
+                            let op_byte = match (op, division) {
+                                (BinaryOp::Div, IntegerDivision::Truncated) => b'q',
+                                (BinaryOp::Mod, IntegerDivision::Truncated) => b'r',
+                                (BinaryOp::Div, IntegerDivision::Floored) => b'f',
+                                (BinaryOp::Mod, IntegerDivision::Floored) => b'm',
+                                _ => op_byte,
+                            };
+                            self.emit(Instruction::IntBinOp(op_byte))
+                        }
+                        _ => panic!(
+                            "Binary operation on non-numeric type: {} (type checker bug)",
+                            resolved_type
+                        ),
+                    }
                 }
                 self.push_stack();
             }
@@ -791,12 +930,24 @@ where
                             self.generic_adapters.push(Box::new(adapter));
                             self.emit_with_arg(Instruction::CallGenericAdapter, adapter_index as u32);
                         }
-                        TypeKind::Map(_, _) => {
-                            self.emit(Instruction::MapHas);
-                            if op == ComparisonOp::NotIn {
-                                self.emit(Instruction::Not);
+                        TypeKind::Map(key_type, _) => match key_type.view() {
+                            // MapHas compares raw integers, which is only correct for Int keys.
+                            TypeKind::Int => {
+                                self.emit(Instruction::MapHas);
+                                if op == ComparisonOp::NotIn {
+                                    self.emit(Instruction::Not);
+                                }
                             }
-                        }
+                            _ => {
+                                let adapter = MapContainsAdapter::new(key_type, op);
+                                let adapter_index = self.generic_adapters.len();
+                                self.generic_adapters.push(Box::new(adapter));
+                                self.emit_with_arg(
+                                    Instruction::CallGenericAdapter,
+                                    adapter_index as u32,
+                                );
+                            }
+                        },
                         _ => panic!(
                             "Containment on unsupported type: {} (type checker bug)",
                             haystack_type
@@ -809,6 +960,12 @@ where
                         TypeKind::Int => self.emit(Instruction::IntCmpOp(op)),
                         TypeKind::Str => self.emit(Instruction::StringCmpOp(op)),
                         TypeKind::Bytes => self.emit(Instruction::BytesCmpOp(op)),
+                        TypeKind::Option(_) => {
+                            let adapter = OptionCompareAdapter::new(resolved_type, op);
+                            let adapter_index = self.generic_adapters.len();
+                            self.generic_adapters.push(Box::new(adapter));
+                            self.emit_with_arg(Instruction::CallGenericAdapter, adapter_index as u32);
+                        }
                         _ => panic!(
                             "Comparison on unsupported type: {} (type checker bug)",
                             resolved_type
@@ -954,8 +1111,14 @@ where
                         .expect("Duplicate binding names (should be caught by type checker)"),
                 );
 
-                // Compile all bindings first (in order)
-                for (name, value_expr) in bindings.iter() {
+                // Compile all bindings first (in order), leaving out the ones
+                // that are never used and can be skipped without changing the result
+                let unused = unused_bindings(expr, bindings);
+                for ((name, value_expr), unused) in bindings.iter().zip(unused) {
+                    if unused {
+                        continue;
+                    }
+
                     // Compile the value expression
                     self.transform(value_expr)?;
                     self.pop_stack();
@@ -982,7 +1145,7 @@ where
             ExprInner::Index { value, index } => {
                 use crate::types::traits::TypeKind;
 
-                // Compile the value expression (array, map, or bytes)
+                // Compile the value expression (array, map, bytes, or string)
                 self.transform(value)?;
 
                 // Resolve the container type (applies substitution for polymorphic lambdas)
@@ -1021,17 +1184,60 @@ where
                     TypeKind::Array(_) => {
                         self.emit(Instruction::ArrayGet);
                     }
-                    TypeKind::Map(_, _) => {
-                        self.emit(Instruction::MapGet);
-                    }
+                    TypeKind::Map(key_type, _) => match key_type.view() {
+                        // MapGet compares raw integers, which is only correct for Int keys.
+                        TypeKind::Int => self.emit(Instruction::MapGet),
+                        _ => {
+                            let adapter = MapGetAdapter::new(key_type);
+                            let adapter_index = self.generic_adapters.len();
+                            self.generic_adapters.push(Box::new(adapter));
+                            self.emit_with_arg(
+                                Instruction::CallGenericAdapter,
+                                adapter_index as u32,
+                            );
+                        }
+                    },
                     TypeKind::Bytes => {
                         self.emit(Instruction::BytesGet);
                     }
+                    TypeKind::Str => {
+                        self.emit(Instruction::StrGet);
+                    }
                     _ => panic!("Index operation on non-indexable type (type checker bug)"),
                 }
                 self.push_stack(); // Push result
             }
 
+            ExprInner::Slice { value, start, end } => {
+                let container_type = self.resolve_type(value.0);
+                self.transform(value)?;
+
+                // Omitted bounds span the whole value; the VM clamps the end.
+                match start {
+                    Some(start) => self.transform(start)?,
+                    None => {
+                        self.emit(Instruction::ConstInt(0));
+                        self.push_stack();
+                    }
+                }
+                match end {
+                    Some(end) => self.transform(end)?,
+                    None => {
+                        let const_index = self.add_constant(Value::int(self.type_mgr, i64::MAX))?;
+                        self.emit_with_arg(Instruction::ConstLoad, const_index);
+                        self.push_stack();
+                    }
+                }
+
+                self.pop_stack_n(3); // Pop end, start and container
+                match container_type.view() {
+                    TypeKind::Array(_) => self.emit(Instruction::ArraySlice),
+                    TypeKind::Str => self.emit(Instruction::StrSlice),
+                    _ => panic!("Slice operation on non-sliceable type (type checker bug)"),
+                }
+                self.push_stack(); // Push result
+            }
+
             // === Field Access ===
             ExprInner::Field { value, field } => {
                 use crate::types::traits::TypeKind;
@@ -1088,7 +1294,10 @@ where
             }
 
             // === Map Construction ===
-            ExprInner::Map { elements } => {
+            ExprInner::Map {
+                elements,
+                duplicate_keys,
+            } => {
                 // Compile all key-value pairs
                 // Each pair pushes key then value onto the stack
                 for (key_expr, value_expr) in elements.iter() {
@@ -1100,8 +1309,27 @@ where
                 let num_pairs = elements.len();
                 self.pop_stack_n(num_pairs * 2);
 
-                // Emit MakeMap instruction
-                self.emit_with_arg(Instruction::MakeMap, num_pairs as u32);
+                let TypeKind::Map(key_type, _) = self.resolve_type(tree.0).view() else {
+                    panic!("Map literal with non-map type (type checker bug)");
+                };
+                let unique = matches!(duplicate_keys, DuplicateKeyPolicy::Error);
+                match key_type.view() {
+                    // MakeMap sorts keys as raw integers, which is only correct for Int keys.
+                    TypeKind::Int => {
+                        let make_map = if unique {
+                            Instruction::MakeMapUnique
+                        } else {
+                            Instruction::MakeMap
+                        };
+                        self.emit_with_arg(make_map, num_pairs as u32);
+                    }
+                    _ => {
+                        let adapter = MakeMapAdapter::new(key_type, num_pairs, unique);
+                        let adapter_index = self.generic_adapters.len();
+                        self.generic_adapters.push(Box::new(adapter));
+                        self.emit_with_arg(Instruction::CallGenericAdapter, adapter_index as u32);
+                    }
+                }
                 self.push_stack();
             }
 
@@ -1144,6 +1372,23 @@ where
             }
 
             // === Option Construction ===
+            ExprInner::Coalesce { option, default } => {
+                self.transform(option)?;
+
+                // If some: unwrap the inner value in place and skip the default
+                let default_jump = self.jump_placeholder(Instruction::MatchSomeOrJump);
+                let end_jump = self.jump_placeholder(Instruction::JumpForward);
+
+                // If none: the option was consumed, push the default instead
+                let default_label = self.label();
+                self.patch_jump(default_jump, default_label, Instruction::MatchSomeOrJump)?;
+                self.pop_stack();
+                self.transform(default)?;
+
+                let end_label = self.label();
+                self.patch_jump(end_jump, end_label, Instruction::JumpForward)?;
+            }
+
             ExprInner::Option { inner } => {
                 match inner {
                     Some(value_expr) => {
@@ -1163,7 +1408,11 @@ where
             }
 
             // === Function Calls ===
-            ExprInner::Call { callable, args } => {
+            ExprInner::Call {
+                callable,
+                args,
+                coercion,
+            } => {
                 use crate::types::traits::{TypeKind, TypeView};
 
                 // 1. Compile arguments first (they go on stack before function)
@@ -1176,13 +1425,22 @@ where
 
                 // 3. Extract parameter types from callable's function type
                 let param_types: alloc::vec::Vec<_> = match callable.0.view() {
-                    TypeKind::Function { params, .. } => params.collect(),
+                    TypeKind::Function { params, .. } => {
+                        params.map(|param| self.resolve_type(param)).collect()
+                    }
                     _ => panic!("Call on non-function (should be caught by type checker)"),
                 };
 
-                // 4. Create and store the adapter
+                // 4. Create and store the adapter, with the argument types it
+                // converts from under the call's coercion policy
                 // TODO: Deduplicate adapters with same parameter types
-                let adapter = FunctionAdapter::new(self.type_mgr, param_types);
+                let adapter = if coercion == ArgumentCoercion::Strict {
+                    FunctionAdapter::new(self.type_mgr, param_types)
+                } else {
+                    let arg_types = args.iter().map(|arg| self.resolve_type(arg.0)).collect();
+                    FunctionAdapter::new(self.type_mgr, param_types)
+                        .with_coercion(arg_types, coercion)
+                };
                 let adapter_index = self.adapters.len();
                 self.adapters.push(adapter);
 
@@ -1196,9 +1454,10 @@ where
                 // Compile the expression to cast
                 self.transform(inner_expr)?;
 
-                // Get source and target types
-                let source_type = inner_expr.0;
-                let target_type = tree.0;
+                // Get source and target types (both are resolved for
+                // polymorphic lambdas)
+                let source_type = self.resolve_type(inner_expr.0);
+                let target_type = self.resolve_type(tree.0);
 
                 // Create cast adapter and store it
                 let adapter = CastAdapter::new(self.type_mgr, source_type, target_type);
@@ -1374,7 +1633,8 @@ where
                 }
 
                 // 2. Collect expression types for the adapter
-                let expr_types: alloc::vec::Vec<_> = exprs.iter().map(|e| e.0).collect();
+                let expr_types: alloc::vec::Vec<_> =
+                    exprs.iter().map(|e| self.resolve_type(e.0)).collect();
 
                 // 3. Create and store FormatStrAdapter (copies strings internally)
                 let adapter = FormatStrAdapter::new(self.type_mgr, &expr_types, strs);
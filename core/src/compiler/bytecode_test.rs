@@ -1,39 +1,125 @@
 //! Tests for the bytecode compiler.
 
 use crate::{
-    analyzer,
-    compiler::BytecodeCompiler,
-    evaluator::ExecutionError,
+    analyzer::{self, AnalyzerOptions, ArgumentCoercion, DuplicateKeyPolicy, IntegerDivision},
+    api::CompileOptions,
+    compiler::{BytecodeCompiler, peephole},
+    evaluator::{ExecutionError, ExecutionErrorKind, RuntimeError},
     parser::{self, ComparisonOp},
-    stdlib::math::build_math_package,
+    stdlib::{
+        array::build_array_package,
+        assert::{build_assert, build_expect},
+        bytes::build_bytes_package,
+        iterate::build_iterate,
+        map::build_map_package,
+        math::build_math_package,
+        option::build_option_package,
+        string::build_string_package,
+    },
     types::manager::TypeManager,
     values::{RawValue, dynamic::Value},
-    vm::{Code, Instruction, VM},
+    vm::{
+        BYTECODE_FORMAT_VERSION, Code, DecodeError, DisasmOperand, EncodeError, FunctionAdapter,
+        Instruction, VM,
+    },
 };
 use bumpalo::Bump;
 
 /// Helper function to compile and run a source expression.
 /// Returns the compiled bytecode and the VM execution result as a safe Value.
 ///
-/// This helper includes the Math package by default, so all tests can use
-/// `Math.Sin`, `Math.Sqrt`, etc. without any extra setup.
+/// This helper includes the Math, Array, Map, Option and String packages and the `Assert`,
+/// `Expect` and `Iterate` functions by default, so all tests can use `Math.Sin`,
+/// `Array.Chunk`, etc. without any extra setup.
 fn compile_and_run<'a>(
     arena: &'a Bump,
     type_manager: &'a TypeManager<'a>,
     source: &str,
+) -> (Code<'a>, Result<Value<'a, 'a>, ExecutionError>) {
+    compile_and_run_with_options(arena, type_manager, source, AnalyzerOptions::default())
+}
+
+/// Like [`compile_and_run`], but with custom analyzer options.
+///
+/// Constant folding and the peephole optimizer are disabled, so that tests
+/// inspecting the generated instructions see the code emitted for each
+/// operator.
+fn compile_and_run_with_options<'a>(
+    arena: &'a Bump,
+    type_manager: &'a TypeManager<'a>,
+    source: &str,
+    options: AnalyzerOptions,
+) -> (Code<'a>, Result<Value<'a, 'a>, ExecutionError>) {
+    compile_and_run_with_compile_options(
+        arena,
+        type_manager,
+        source,
+        options,
+        &CompileOptions {
+            constant_folding: false,
+            peephole_optimization: false,
+            ..Default::default()
+        },
+    )
+}
+
+/// Like [`compile_and_run_with_options`], but also with custom compile options.
+fn compile_and_run_with_compile_options<'a>(
+    arena: &'a Bump,
+    type_manager: &'a TypeManager<'a>,
+    source: &str,
+    options: AnalyzerOptions,
+    compile_options: &CompileOptions,
 ) -> (Code<'a>, Result<Value<'a, 'a>, ExecutionError>) {
     // Build Math package (available to all tests)
     let math = build_math_package(arena, type_manager).unwrap();
+    let array = build_array_package(arena, type_manager).unwrap();
+    let assert = build_assert(arena, type_manager).unwrap();
+    let bytes = build_bytes_package(arena, type_manager).unwrap();
+    let expect = build_expect(arena, type_manager).unwrap();
+    let iterate = build_iterate(arena, type_manager).unwrap();
+    let map = build_map_package(arena, type_manager).unwrap();
+    let option = build_option_package(arena, type_manager).unwrap();
+    let string = build_string_package(arena, type_manager).unwrap();
 
     // Globals for analyzer (types only)
-    let globals_types = &[("Math", math.ty)];
+    let globals_types = &[
+        ("Array", array.ty),
+        ("Assert", assert.ty),
+        ("Bytes", bytes.ty),
+        ("Expect", expect.ty),
+        ("Iterate", iterate.ty),
+        ("Map", map.ty),
+        ("Math", math.ty),
+        ("Option", option.ty),
+        ("String", string.ty),
+    ];
     // Globals for compiler (values)
-    let globals_values = arena.alloc_slice_copy(&[("Math", math)]);
+    let globals_values = arena.alloc_slice_copy(&[
+        ("Array", array),
+        ("Assert", assert),
+        ("Bytes", bytes),
+        ("Expect", expect),
+        ("Iterate", iterate),
+        ("Map", map),
+        ("Math", math),
+        ("Option", option),
+        ("String", string),
+    ]);
 
     let parsed = parser::parse(arena, source).unwrap();
-    let typed = analyzer::analyze(type_manager, arena, &parsed, globals_types, &[]).unwrap();
+    let typed =
+        analyzer::analyze_with_options(type_manager, arena, &parsed, globals_types, &[], options)
+            .unwrap();
     let result_type = typed.expr.0;
-    let code = BytecodeCompiler::compile(type_manager, arena, globals_values, typed).unwrap();
+    let code = BytecodeCompiler::compile_with_options(
+        type_manager,
+        arena,
+        globals_values,
+        typed,
+        compile_options,
+    )
+    .unwrap();
     let result = VM::execute(arena, &code).map(|raw| Value::from_raw_unchecked(result_type, raw));
     (code, result)
 }
@@ -787,6 +873,134 @@ fn test_float_operations() {
     }
 }
 
+#[test]
+fn test_float_division_option() {
+    let arena = Bump::new();
+    let type_manager = TypeManager::new(&arena);
+    let options = AnalyzerOptions {
+        float_division: true,
+        ..Default::default()
+    };
+
+    let (code, result) = compile_and_run_with_options(&arena, type_manager, "5 / 2", options);
+    assert_eq!(result.unwrap().as_float().unwrap(), 2.5);
+    assert!(code.instructions.contains(&Instruction::FloatBinOp(b'/')));
+    assert!(!code.instructions.contains(&Instruction::IntBinOp(b'/')));
+
+    // Other operators keep their integer semantics.
+    let (_code, result) = compile_and_run_with_options(&arena, type_manager, "7 - 2", options);
+    assert_eq!(result.unwrap().as_int().unwrap(), 5);
+
+    // Division by zero follows float semantics.
+    let (_code, result) = compile_and_run_with_options(&arena, type_manager, "1 / 0", options);
+    assert_eq!(result.unwrap().as_float().unwrap(), f64::INFINITY);
+
+    // Operands resolved through lambdas and where bindings are converted too.
+    for source in [
+        "((a, b) => a / b)(5, 2)",
+        "((a) => a / 2)(5)",
+        "((a) => a / 2)(5.0)",
+        "half(5) where { half = (a) => a / 2 }",
+        "n / 2 where { n = 5 }",
+    ] {
+        let (_code, result) = compile_and_run_with_options(&arena, type_manager, source, options);
+        assert_eq!(result.unwrap().as_float().unwrap(), 2.5, "{}", source);
+    }
+}
+
+#[test]
+fn test_integer_division_option() {
+    let arena = Bump::new();
+    let type_manager = TypeManager::new(&arena);
+    let run = |source, integer_division| {
+        let options = AnalyzerOptions {
+            integer_division,
+            ..Default::default()
+        };
+        let (code, result) = compile_and_run_with_options(&arena, type_manager, source, options);
+        (code, result.unwrap().as_int().unwrap())
+    };
+
+    let cases = [
+        (IntegerDivision::Euclidean, [-4, 1, -3, 1]),
+        (IntegerDivision::Truncated, [-3, -1, -3, 1]),
+        (IntegerDivision::Floored, [-4, 1, -4, -1]),
+    ];
+    for (division, [neg_quotient, neg_remainder, quotient, remainder]) in cases {
+        // Literal operands are folded at compile time.
+        assert_eq!(run("-7 / 2", division).1, neg_quotient, "{:?}", division);
+        assert_eq!(run("-7 % 2", division).1, neg_remainder, "{:?}", division);
+
+        // Operands bound by `where` are divided by the VM.
+        let (code, result) = run("a / b where { a = -7, b = 2 }", division);
+        assert_eq!(result, neg_quotient, "{:?}", division);
+        assert!(!code.instructions.contains(&Instruction::FloatBinOp(b'/')));
+        let (_code, result) = run("a % b where { a = -7, b = 2 }", division);
+        assert_eq!(result, neg_remainder, "{:?}", division);
+        let (_code, result) = run("a / b where { a = 7, b = -2 }", division);
+        assert_eq!(result, quotient, "{:?}", division);
+        let (_code, result) = run("a % b where { a = 7, b = -2 }", division);
+        assert_eq!(result, remainder, "{:?}", division);
+    }
+
+    let (code, _result) = run("a / b where { a = -7, b = 2 }", IntegerDivision::Truncated);
+    assert!(code.instructions.contains(&Instruction::IntBinOp(b'q')));
+    let (code, _result) = run("a % b where { a = -7, b = 2 }", IntegerDivision::Floored);
+    assert!(code.instructions.contains(&Instruction::IntBinOp(b'm')));
+}
+
+#[test]
+fn test_mixed_numeric_comparison_option() {
+    let arena = Bump::new();
+    let type_manager = TypeManager::new(&arena);
+    let options = AnalyzerOptions {
+        mixed_numeric_comparison: true,
+        ..Default::default()
+    };
+
+    // The Int operand is converted before a float comparison.
+    let (code, result) = compile_and_run_with_options(
+        &arena,
+        type_manager,
+        "a < b where { a = 2, b = 2.5 }",
+        options,
+    );
+    assert!(result.unwrap().as_bool().unwrap());
+    assert!(
+        code.instructions
+            .contains(&Instruction::FloatCmpOp(ComparisonOp::Lt))
+    );
+
+    let cases = [
+        ("1 == 1.0", true),
+        ("2 < 2.5", true),
+        ("3 <= 2.5", false),
+        ("2.5 > 2", true),
+        ("1.5 != 1", true),
+        ("a == b where { a = 1, b = 1.0 }", true),
+        ("a >= b where { a = 1.5, b = 2 }", false),
+        ("((a) => a < 2.5)(2)", true),
+        ("((a, b) => a < b)(2, 2.5)", true),
+        ("((a, b) => a == b)(1.5, 1)", false),
+        ("lt(3, 2.5) where { lt = (a, b) => a < b }", false),
+        ("lt(1, 2) and lt(2.5, 3) where { lt = (a, b) => a < b }", true),
+    ];
+    for (source, expected) in cases {
+        let (_code, result) = compile_and_run_with_options(&arena, type_manager, source, options);
+        assert_eq!(result.unwrap().as_bool().unwrap(), expected, "{}", source);
+    }
+}
+
+#[test]
+fn test_integer_division_by_default() {
+    let arena = Bump::new();
+    let type_manager = TypeManager::new(&arena);
+
+    let (code, result) = compile_and_run(&arena, type_manager, "5 / 2");
+    assert_eq!(result.unwrap().as_int().unwrap(), 2);
+    assert!(code.instructions.contains(&Instruction::IntBinOp(b'/')));
+}
+
 #[test]
 fn test_float_negation() {
     let arena = Bump::new();
@@ -1475,6 +1689,131 @@ fn test_map_indexing() {
     assert_eq!(result.unwrap().as_int().unwrap(), 100);
 }
 
+#[test]
+fn test_string_indexing() {
+    let arena = Bump::new();
+    let type_manager = TypeManager::new(&arena);
+
+    let (code, result) = compile_and_run(&arena, type_manager, r#""abc"[0]"#);
+
+    // Expected bytecode: ConstLoad(0), ConstInt(0), StrGet, Return
+    assert_eq!(code.instructions.len(), 4);
+    assert_eq!(code.instructions[2], Instruction::StrGet);
+    assert_eq!(code.instructions[3], Instruction::Return);
+
+    let result = result.unwrap();
+    assert!(core::ptr::eq(result.ty, type_manager.str()));
+    assert_eq!(result.as_str().unwrap(), "a");
+}
+
+#[test]
+fn test_vm_string_index_counts_characters() {
+    let arena = Bump::new();
+    let type_manager = TypeManager::new(&arena);
+
+    let (_code, result) = compile_and_run(&arena, type_manager, r#""héllo"[1]"#);
+    assert_eq!(result.unwrap().as_str().unwrap(), "é");
+
+    let (_code, result) = compile_and_run(&arena, type_manager, r#""héllo"[2]"#);
+    assert_eq!(result.unwrap().as_str().unwrap(), "l");
+}
+
+#[test]
+fn test_vm_string_negative_index() {
+    let arena = Bump::new();
+    let type_manager = TypeManager::new(&arena);
+
+    let (_code, result) = compile_and_run(&arena, type_manager, r#""abc"[-1]"#);
+    assert_eq!(result.unwrap().as_str().unwrap(), "c");
+}
+
+#[test]
+fn test_vm_string_index_out_of_bounds() {
+    let arena = Bump::new();
+    let type_manager = TypeManager::new(&arena);
+
+    let (_code, result) = compile_and_run(&arena, type_manager, r#""abc"[10]"#);
+    assert_eq!(
+        result.unwrap_err().kind,
+        ExecutionErrorKind::Runtime(RuntimeError::IndexOutOfBounds { index: 10, len: 3 })
+    );
+
+    let (_code, result) = compile_and_run(&arena, type_manager, r#""abc"[10] otherwise "x""#);
+    assert_eq!(result.unwrap().as_str().unwrap(), "x");
+}
+
+fn int_array(value: Value) -> Vec<i64> {
+    value
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|element| element.as_int().unwrap())
+        .collect()
+}
+
+#[test]
+fn test_vm_array_slice() {
+    let arena = Bump::new();
+    let type_manager = TypeManager::new(&arena);
+
+    let (code, result) = compile_and_run(&arena, type_manager, "[1, 2, 3, 4][1:3]");
+    assert!(code.instructions.contains(&Instruction::ArraySlice));
+    assert_eq!(int_array(result.unwrap()), [2, 3]);
+
+    let (_code, result) = compile_and_run(&arena, type_manager, "[1, 2, 3][-2:]");
+    assert_eq!(int_array(result.unwrap()), [2, 3]);
+
+    let (_code, result) = compile_and_run(&arena, type_manager, "[1, 2, 3, 4][:3]");
+    assert_eq!(int_array(result.unwrap()), [1, 2, 3]);
+
+    let (_code, result) = compile_and_run(&arena, type_manager, "[1, 2, 3][:]");
+    assert_eq!(int_array(result.unwrap()), [1, 2, 3]);
+
+    let (_code, result) = compile_and_run(
+        &arena,
+        type_manager,
+        "xs[i:i + 2] where { xs = [1, 2, 3], i = 1 }",
+    );
+    assert_eq!(int_array(result.unwrap()), [2, 3]);
+}
+
+#[test]
+fn test_vm_array_slice_clamps_bounds() {
+    let arena = Bump::new();
+    let type_manager = TypeManager::new(&arena);
+
+    let (_code, result) = compile_and_run(&arena, type_manager, "[1, 2, 3][1:100]");
+    assert_eq!(int_array(result.unwrap()), [2, 3]);
+
+    let (_code, result) = compile_and_run(&arena, type_manager, "[1, 2, 3][-100:1]");
+    assert_eq!(int_array(result.unwrap()), [1]);
+
+    let (_code, result) = compile_and_run(&arena, type_manager, "[1, 2, 3][2:1]");
+    assert!(int_array(result.unwrap()).is_empty());
+
+    let (_code, result) = compile_and_run(&arena, type_manager, "[1, 2, 3][5:]");
+    assert!(int_array(result.unwrap()).is_empty());
+}
+
+#[test]
+fn test_vm_string_slice() {
+    let arena = Bump::new();
+    let type_manager = TypeManager::new(&arena);
+
+    let (code, result) = compile_and_run(&arena, type_manager, r#""héllo"[1:4]"#);
+    assert!(code.instructions.contains(&Instruction::StrSlice));
+    assert_eq!(result.unwrap().as_str().unwrap(), "éll");
+
+    let (_code, result) = compile_and_run(&arena, type_manager, r#""héllo"[-2:]"#);
+    assert_eq!(result.unwrap().as_str().unwrap(), "lo");
+
+    let (_code, result) = compile_and_run(&arena, type_manager, r#""héllo"[:2]"#);
+    assert_eq!(result.unwrap().as_str().unwrap(), "hé");
+
+    let (_code, result) = compile_and_run(&arena, type_manager, r#""abc"[3:10]"#);
+    assert_eq!(result.unwrap().as_str().unwrap(), "");
+}
+
 #[test]
 fn test_vm_map_indexing() {
     let arena = Bump::new();
@@ -1526,29 +1865,91 @@ fn test_vm_array_negative_index_first() {
 }
 
 #[test]
-#[ignore = "Map string keys not implemented in VM (only integer keys work)"]
 fn test_vm_map_string_keys() {
     let arena = Bump::new();
     let type_manager = TypeManager::new(&arena);
 
     // Test: { "a": 100, "b": 200 }["a"] should return 100
-    let (_code, result) = compile_and_run(&arena, &type_manager, r#"{ "a": 100, "b": 200 }["a"]"#);
+    let (_code, result) = compile_and_run(&arena, type_manager, r#"{ "a": 100, "b": 200 }["a"]"#);
     assert_eq!(result.unwrap().as_int().unwrap(), 100);
 }
 
 #[test]
-#[ignore = "Map[Str, Str] not implemented in VM"]
 fn test_vm_map_string_to_string() {
     let arena = Bump::new();
     let type_manager = TypeManager::new(&arena);
 
     // Test: { "greeting": "hello", "farewell": "goodbye" }["greeting"]
-    let (_code, _result) = compile_and_run(
+    let (_code, result) = compile_and_run(
         &arena,
-        &type_manager,
+        type_manager,
         r#"{ "greeting": "hello", "farewell": "goodbye" }["greeting"]"#,
     );
-    // Result should be the string "hello" but string extraction not implemented yet
+    assert_eq!(result.unwrap().as_str().unwrap(), "hello");
+}
+
+#[test]
+fn test_vm_map_string_keys_computed() {
+    let arena = Bump::new();
+    let type_manager = TypeManager::new(&arena);
+
+    // Keys built at runtime must compare by content, not by address.
+    let (_code, result) = compile_and_run(
+        &arena,
+        type_manager,
+        r#"{ f"{a}{b}": 1, "xy": 2 }[f"{b}{a}"] where { a = "y", b = "x" }"#,
+    );
+    assert_eq!(result.unwrap().as_int().unwrap(), 2);
+}
+
+#[test]
+fn test_vm_map_string_keys_last_wins() {
+    let arena = Bump::new();
+    let type_manager = TypeManager::new(&arena);
+
+    let (_code, result) = compile_and_run(
+        &arena,
+        type_manager,
+        r#"{ "k": 1, "other": 0, "k": 2 }["k"]"#,
+    );
+    assert_eq!(result.unwrap().as_int().unwrap(), 2);
+}
+
+#[test]
+fn test_vm_map_string_key_not_found_otherwise() {
+    let arena = Bump::new();
+    let type_manager = TypeManager::new(&arena);
+
+    let (_code, result) = compile_and_run(&arena, type_manager, r#"{ "a": 1 }["b"] otherwise 0"#);
+    assert_eq!(result.unwrap().as_int().unwrap(), 0);
+
+    let (_code, result) = compile_and_run(&arena, type_manager, r#"{ "a": 1 }["b"]"#);
+    assert!(matches!(
+        result.unwrap_err().kind,
+        ExecutionErrorKind::Runtime(RuntimeError::KeyNotFound { .. })
+    ));
+}
+
+#[test]
+fn test_vm_map_string_keys_readable_from_host() {
+    let arena = Bump::new();
+    let type_manager = TypeManager::new(&arena);
+
+    // Maps built by the VM must be ordered like maps built anywhere else.
+    let (_code, result) = compile_and_run(
+        &arena,
+        type_manager,
+        r#"{ "zeta": 26, "alpha": 1, "mu": 12 }"#,
+    );
+    let result = result.unwrap();
+    let map = result.as_map().unwrap();
+    let key = Value::str(&arena, type_manager.str(), "mu");
+    assert_eq!(map.get(&key).unwrap().as_int().unwrap(), 12);
+    let keys: Vec<String> = map
+        .iter()
+        .map(|(key, _)| key.as_str().unwrap().to_string())
+        .collect();
+    assert_eq!(keys, ["alpha", "mu", "zeta"]);
 }
 
 #[test]
@@ -1581,6 +1982,50 @@ fn test_vm_bool_array_index() {
     assert_eq!(result.unwrap().as_bool().unwrap(), true);
 }
 
+#[test]
+fn test_vm_non_int_array_index_every_position() {
+    let arena = Bump::new();
+    let type_manager = TypeManager::new(&arena);
+
+    // Constant indices compile to ArrayGetConst
+    for (index, expected) in [1.5, 2.5, 3.5].into_iter().enumerate() {
+        let source = format!("[1.5, 2.5, 3.5][{}]", index);
+        let (code, result) = compile_and_run(&arena, type_manager, &source);
+        assert!(code.instructions.contains(&Instruction::ArrayGetConst(index as u8)));
+        assert_eq!(result.unwrap().as_float().unwrap(), expected, "{}", source);
+    }
+    for (index, expected) in ["a", "bc", ""].into_iter().enumerate() {
+        let source = format!(r#"["a", "bc", ""][{}]"#, index);
+        let (_code, result) = compile_and_run(&arena, type_manager, &source);
+        assert_eq!(result.unwrap().as_str().unwrap(), expected, "{}", source);
+    }
+    for (index, expected) in [false, true, false].into_iter().enumerate() {
+        let source = format!("[false, true, false][{}]", index);
+        let (_code, result) = compile_and_run(&arena, type_manager, &source);
+        assert_eq!(result.unwrap().as_bool().unwrap(), expected, "{}", source);
+    }
+}
+
+#[test]
+fn test_vm_non_int_array_index_dynamic() {
+    let arena = Bump::new();
+    let type_manager = TypeManager::new(&arena);
+
+    // Indices bound in `where` compile to ArrayGet
+    let (code, result) =
+        compile_and_run(&arena, type_manager, "[1.5, 2.5, 3.5][i] where { i = 2 }");
+    assert!(code.instructions.contains(&Instruction::ArrayGet));
+    assert_eq!(result.unwrap().as_float().unwrap(), 3.5);
+
+    let (_code, result) =
+        compile_and_run(&arena, type_manager, r#"["a", "b", "c"][i] where { i = 1 }"#);
+    assert_eq!(result.unwrap().as_str().unwrap(), "b");
+
+    let (_code, result) =
+        compile_and_run(&arena, type_manager, "[true, false, true][i] where { i = 1 }");
+    assert!(!result.unwrap().as_bool().unwrap());
+}
+
 #[test]
 fn test_vm_empty_map() {
     let arena = Bump::new();
@@ -1690,6 +2135,97 @@ fn test_vm_otherwise_array_success() {
     assert_eq!(result.unwrap().as_int().unwrap(), 20);
 }
 
+#[test]
+fn test_vm_map_duplicate_keys_last_wins() {
+    let arena = Bump::new();
+    let type_manager = TypeManager::new(&arena);
+
+    let (code, result) = compile_and_run(
+        &arena,
+        type_manager,
+        "{a: 10, b: 20, c: 30} where { a = 1, b = 2, c = 1 }",
+    );
+    assert!(code.instructions.contains(&Instruction::MakeMap(3)));
+    let map = result.unwrap();
+    let entries: Vec<(i64, i64)> = map
+        .as_map()
+        .unwrap()
+        .iter()
+        .map(|(key, value)| (key.as_int().unwrap(), value.as_int().unwrap()))
+        .collect();
+    assert_eq!(entries, [(1, 30), (2, 20)]);
+}
+
+#[test]
+fn test_vm_map_duplicate_keys_error_policy() {
+    let arena = Bump::new();
+    let type_manager = TypeManager::new(&arena);
+    let options = AnalyzerOptions {
+        duplicate_map_keys: DuplicateKeyPolicy::Error,
+        ..Default::default()
+    };
+
+    let (code, result) = compile_and_run_with_options(
+        &arena,
+        type_manager,
+        "{a: 10, b: 20} where { a = 1, b = 1 }",
+        options,
+    );
+    assert!(code.instructions.contains(&Instruction::MakeMapUnique(2)));
+    assert!(matches!(
+        result.unwrap_err().kind,
+        ExecutionErrorKind::Runtime(RuntimeError::DuplicateMapKey { .. })
+    ));
+
+    // Distinct computed keys are fine.
+    let (_code, result) = compile_and_run_with_options(
+        &arena,
+        type_manager,
+        "{a: 10, b: 20}[2] where { a = 1, b = 2 }",
+        options,
+    );
+    assert_eq!(result.unwrap().as_int().unwrap(), 20);
+
+    // The error is recoverable.
+    let (_code, result) = compile_and_run_with_options(
+        &arena,
+        type_manager,
+        "({a: 10, b: 20}[1] where { a = 1, b = 1 }) otherwise -1",
+        options,
+    );
+    assert_eq!(result.unwrap().as_int().unwrap(), -1);
+}
+
+#[test]
+fn test_vm_argument_coercion_policy() {
+    let arena = Bump::new();
+    let type_manager = TypeManager::new(&arena);
+    let source = "Math.Sqrt(16) + Math.Max(1.5, 2)";
+
+    // Strict by default: an Int argument for a Float parameter is a type error.
+    let parsed = parser::parse(&arena, source).unwrap();
+    let math = build_math_package(&arena, type_manager).unwrap();
+    let globals = &[("Math", math.ty)];
+    assert!(analyzer::analyze(type_manager, &arena, &parsed, globals, &[]).is_err());
+
+    // Lenient: Int arguments are converted by the call's adapter.
+    let options = AnalyzerOptions {
+        argument_coercion: ArgumentCoercion::Lenient,
+        ..Default::default()
+    };
+    let (_code, result) = compile_and_run_with_options(&arena, type_manager, source, options);
+    assert_eq!(result.unwrap().as_float().unwrap(), 6.0);
+
+    // Lambdas with Float parameters convert their arguments as well.
+    let (_code, result) = compile_and_run_with_options(
+        &arena,
+        type_manager,
+        "f(3) where { f = (x) => x * 0.5 }",
+        options,
+    );
+    assert_eq!(result.unwrap().as_float().unwrap(), 1.5);
+}
+
 #[test]
 fn test_vm_otherwise_map_key_not_found() {
     let arena = Bump::new();
@@ -1801,6 +2337,54 @@ fn test_vm_otherwise_chained() {
     assert_eq!(result.unwrap().as_int().unwrap(), 42);
 }
 
+// ============================================================================
+// Coalesce Tests
+// ============================================================================
+
+#[test]
+fn test_vm_coalesce() {
+    let arena = Bump::new();
+    let type_manager = TypeManager::new(&arena);
+
+    let (_code, result) = compile_and_run(&arena, type_manager, "some 5 ?? 0");
+    assert_eq!(result.unwrap().as_int().unwrap(), 5);
+
+    let (_code, result) = compile_and_run(&arena, type_manager, "none ?? 0");
+    assert_eq!(result.unwrap().as_int().unwrap(), 0);
+
+    let (_code, result) = compile_and_run(&arena, type_manager, "1 + (none ?? some 2 ?? 3) * 10");
+    assert_eq!(result.unwrap().as_int().unwrap(), 21);
+}
+
+#[test]
+fn test_vm_coalesce_in_lambda() {
+    let arena = Bump::new();
+    let type_manager = TypeManager::new(&arena);
+
+    let (_code, result) = compile_and_run(
+        &arena,
+        type_manager,
+        r#"[or_default(some "a"), or_default(none)] where { or_default = (o) => o ?? "-" }"#,
+    );
+    let result = result.unwrap();
+    let array = result.as_array().unwrap();
+    assert_eq!(array.get(0).unwrap().as_str().unwrap(), "a");
+    assert_eq!(array.get(1).unwrap().as_str().unwrap(), "-");
+}
+
+#[test]
+fn test_vm_coalesce_default_is_lazy() {
+    let arena = Bump::new();
+    let type_manager = TypeManager::new(&arena);
+
+    let (_code, result) = compile_and_run(&arena, type_manager, "some 1 ?? 1 / 0");
+    assert_eq!(result.unwrap().as_int().unwrap(), 1);
+
+    // Errors are not none: they propagate to `otherwise`
+    let (_code, result) = compile_and_run(&arena, type_manager, "some (1 / 0) ?? 0");
+    assert!(result.is_err());
+}
+
 // ============================================================================
 // Error Tests (Without Otherwise Handlers)
 // ============================================================================
@@ -1827,6 +2411,27 @@ fn test_vm_array_index_error_no_otherwise() {
     );
 }
 
+#[test]
+fn test_vm_map_string_keys_duplicate_error_policy() {
+    let arena = Bump::new();
+    let type_manager = TypeManager::new(&arena);
+    let options = AnalyzerOptions {
+        duplicate_map_keys: DuplicateKeyPolicy::Error,
+        ..Default::default()
+    };
+
+    let (_code, result) = compile_and_run_with_options(
+        &arena,
+        type_manager,
+        r#"{a: 10, b: 20} where { a = "k", b = "k" }"#,
+        options,
+    );
+    assert!(matches!(
+        result.unwrap_err().kind,
+        ExecutionErrorKind::Runtime(RuntimeError::DuplicateMapKey { .. })
+    ));
+}
+
 #[test]
 fn test_vm_map_key_error_no_otherwise() {
     let arena = Bump::new();
@@ -1887,6 +2492,92 @@ fn test_vm_float_division_by_zero_returns_inf() {
     );
 }
 
+#[test]
+fn test_vm_modulo() {
+    let arena = Bump::new();
+    let type_manager = TypeManager::new(&arena);
+
+    let (code, result) = compile_and_run(&arena, type_manager, "17 % 5");
+    assert!(code.instructions.contains(&Instruction::IntBinOp(b'%')));
+    assert_eq!(result.unwrap().as_int().unwrap(), 2);
+
+    let (code, result) = compile_and_run(&arena, type_manager, "7.5 % 2.0");
+    assert!(code.instructions.contains(&Instruction::FloatBinOp(b'%')));
+    assert_eq!(result.unwrap().as_float().unwrap(), 1.5);
+}
+
+#[test]
+fn test_vm_modulo_by_zero() {
+    let arena = Bump::new();
+    let type_manager = TypeManager::new(&arena);
+
+    // Integer modulo by zero errors like division, so `otherwise` can catch it
+    let (_code, result) = compile_and_run(&arena, type_manager, "10 % 0");
+    assert!(matches!(
+        result.unwrap_err().kind,
+        crate::evaluator::ExecutionErrorKind::Runtime(
+            crate::evaluator::RuntimeError::DivisionByZero {}
+        )
+    ));
+    let (_code, result) = compile_and_run(&arena, type_manager, "10 % 0 otherwise -1");
+    assert_eq!(result.unwrap().as_int().unwrap(), -1);
+
+    // Float modulo by zero is NaN (IEEE 754)
+    let (_code, result) = compile_and_run(&arena, type_manager, "10.0 % 0.0");
+    assert!(result.unwrap().as_float().unwrap().is_nan());
+}
+
+#[test]
+fn test_vm_bitwise_operators() {
+    let arena = Bump::new();
+    let type_manager = TypeManager::new(&arena);
+
+    for (source, expected) in [
+        ("12 & 10", 8),
+        ("12 | 10", 14),
+        ("12 xor 10", 6),
+        ("1 << 4", 16),
+        ("1 << 63", i64::MIN),
+        ("256 >> 4", 16),
+        ("-16 >> 2", -4),
+        ("flags & 4 where { flags = 6 }", 4),
+    ] {
+        let (code, result) = compile_and_run(&arena, type_manager, source);
+        assert!(
+            code.instructions
+                .iter()
+                .any(|instruction| matches!(instruction, Instruction::IntBitOp(_))),
+            "{}",
+            source
+        );
+        assert_eq!(result.unwrap().as_int().unwrap(), expected, "{}", source);
+    }
+}
+
+#[test]
+fn test_vm_shift_out_of_range() {
+    let arena = Bump::new();
+    let type_manager = TypeManager::new(&arena);
+
+    for source in ["1 << 64", "1 >> -1", "1 << n where { n = 100 }"] {
+        let (_code, result) = compile_and_run(&arena, type_manager, source);
+        assert!(
+            matches!(
+                result.unwrap_err().kind,
+                crate::evaluator::ExecutionErrorKind::Runtime(
+                    crate::evaluator::RuntimeError::ShiftOutOfRange { .. }
+                )
+            ),
+            "{}",
+            source
+        );
+    }
+
+    // The error can be recovered with `otherwise`
+    let (_code, result) = compile_and_run(&arena, type_manager, "1 << 64 otherwise -1");
+    assert_eq!(result.unwrap().as_int().unwrap(), -1);
+}
+
 // ============================================================================
 // Negative Array Indexing Tests
 // ============================================================================
@@ -2313,6 +3004,134 @@ fn test_ffi_math_sqrt() {
     assert!((value - 2.0).abs() < 1e-10, "Expected 2.0, got {}", value);
 }
 
+#[test]
+fn test_ffi_math_hypot_and_cbrt() {
+    let arena = Bump::new();
+    let type_manager = TypeManager::new(&arena);
+
+    let (_code, result) = compile_and_run(&arena, type_manager, "Math.Hypot(3.0, 4.0)");
+    assert_eq!(result.unwrap().as_float().unwrap(), 5.0);
+
+    let (_code, result) = compile_and_run(&arena, type_manager, "Math.Cbrt(27.0)");
+    assert_eq!(result.unwrap().as_float().unwrap(), 3.0);
+}
+
+#[test]
+fn test_ffi_bytes_round_trip() {
+    let arena = Bump::new();
+    let type_manager = TypeManager::new(&arena);
+
+    let (_code, result) = compile_and_run(
+        &arena,
+        type_manager,
+        "Bytes.ToArray(Bytes.FromArray([65, 66]))",
+    );
+    let array = result.unwrap().as_array().unwrap();
+    let values: Vec<i64> = array.iter().map(|value| value.as_int().unwrap()).collect();
+    assert_eq!(values, [65, 66]);
+
+    let (_code, result) = compile_and_run(&arena, type_manager, r#"Bytes.Concat(b"A", b"B")"#);
+    assert_eq!(result.unwrap().as_bytes().unwrap(), b"AB");
+}
+
+#[test]
+fn test_ffi_bytes_to_string() {
+    let arena = Bump::new();
+    let type_manager = TypeManager::new(&arena);
+
+    let (_code, result) = compile_and_run(&arena, type_manager, r#"Bytes.ToStringStrict(b"ok")"#);
+    let result = result.unwrap();
+    assert_eq!(result.as_option().unwrap().unwrap().as_str().unwrap(), "ok");
+
+    let (_code, result) =
+        compile_and_run(&arena, type_manager, r#"Bytes.ToStringStrict(b"o\xffk")"#);
+    assert!(result.unwrap().as_option().unwrap().is_none());
+
+    // The lossy variant always succeeds
+    let (_code, result) = compile_and_run(&arena, type_manager, r#"Bytes.ToString(b"o\xffk")"#);
+    assert_eq!(result.unwrap().as_str().unwrap(), "o\u{fffd}k");
+}
+
+#[test]
+fn test_ffi_bytes_from_array_out_of_range() {
+    let arena = Bump::new();
+    let type_manager = TypeManager::new(&arena);
+
+    let (_code, result) = compile_and_run(&arena, type_manager, "Bytes.FromArray([65, 256])");
+    assert!(matches!(
+        result.unwrap_err().kind,
+        ExecutionErrorKind::Runtime(RuntimeError::InvalidArgument { .. })
+    ));
+}
+
+#[test]
+fn test_ffi_math_ceil_pi() {
+    let arena = Bump::new();
+    let type_manager = TypeManager::new(&arena);
+
+    let (_code, result) = compile_and_run(&arena, type_manager, "Math.Ceil(Math.PI)");
+    assert_eq!(result.unwrap().as_int().unwrap(), 4);
+}
+
+#[test]
+fn test_ffi_math_round_half_away_from_zero() {
+    let arena = Bump::new();
+    let type_manager = TypeManager::new(&arena);
+
+    let (_code, result) = compile_and_run(&arena, type_manager, "Math.Round(2.5)");
+    assert_eq!(result.unwrap().as_int().unwrap(), 3);
+
+    let (_code, result) = compile_and_run(&arena, type_manager, "Math.Round(-2.5)");
+    assert_eq!(result.unwrap().as_int().unwrap(), -3);
+}
+
+#[test]
+fn test_ffi_math_log() {
+    let arena = Bump::new();
+    let type_manager = TypeManager::new(&arena);
+
+    let (_code, result) = compile_and_run(&arena, type_manager, "Math.Log(Math.E)");
+    let value = result.unwrap().as_float().unwrap();
+    assert!((value - 1.0).abs() < 1e-10, "Expected ~1.0, got {}", value);
+
+    let (_code, result) = compile_and_run(&arena, type_manager, "Math.Log10(1000.0)");
+    let value = result.unwrap().as_float().unwrap();
+    assert!((value - 3.0).abs() < 1e-10, "Expected ~3.0, got {}", value);
+}
+
+#[test]
+fn test_ffi_math_log_negative_is_nan() {
+    let arena = Bump::new();
+    let type_manager = TypeManager::new(&arena);
+
+    // Out-of-domain input yields NaN rather than a runtime error
+    let (_code, result) = compile_and_run(&arena, type_manager, "Math.Log(-1.0)");
+    assert!(result.unwrap().as_float().unwrap().is_nan());
+}
+
+#[test]
+fn test_ffi_math_random_advances_across_closures() {
+    let arena = Bump::new();
+    let type_manager = TypeManager::new(&arena);
+
+    // The closure runs in its own VM but shares the caller's generator
+    let (_code, result) = compile_and_run(
+        &arena,
+        type_manager,
+        "Array.Map([1, 2, 3], (x) => Math.Random())",
+    );
+    let result = result.unwrap();
+    let values: Vec<f64> = result
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|value| value.as_float().unwrap())
+        .collect();
+    assert!(values.iter().all(|value| (0.0..1.0).contains(value)));
+    assert_ne!(values[0], values[1]);
+    assert_ne!(values[1], values[2]);
+}
+
 #[test]
 fn test_ffi_math_sqrt_with_expression_arg() {
     let arena = Bump::new();
@@ -2419,187 +3238,737 @@ fn test_ffi_in_array() {
     assert!((array.get(2).unwrap().as_float().unwrap() - 3.0).abs() < 1e-10);
 }
 
+/// Convert an `Array[Array[Int]]` value into nested vectors for easy comparison.
+fn nested_ints(value: Value<'_, '_>) -> alloc::vec::Vec<alloc::vec::Vec<i64>> {
+    value
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|inner| {
+            inner
+                .as_array()
+                .unwrap()
+                .iter()
+                .map(|elem| elem.as_int().unwrap())
+                .collect()
+        })
+        .collect()
+}
+
 #[test]
-fn test_ffi_in_if_expression() {
+fn test_ffi_array_chunk() {
     let arena = Bump::new();
     let type_manager = TypeManager::new(&arena);
 
-    // Test: if Math.Sqrt(4.0) > 1.5 then 10.0 else 20.0
-    let (_code, result) = compile_and_run(
-        &arena,
-        &type_manager,
-        "if Math.Sqrt(4.0) > 1.5 then 10.0 else 20.0",
+    let (_code, result) = compile_and_run(&arena, &type_manager, "Array.Chunk([1, 2, 3, 4, 5], 2)");
+    assert_eq!(
+        nested_ints(result.unwrap()),
+        [vec![1, 2], vec![3, 4], vec![5]]
     );
-    let value = result.unwrap().as_float().unwrap();
-    assert!((value - 10.0).abs() < 1e-10, "Expected 10.0, got {}", value);
 }
 
 #[test]
-fn test_ffi_chained_method_calls() {
+fn test_ffi_array_windows() {
     let arena = Bump::new();
     let type_manager = TypeManager::new(&arena);
 
-    // Test: Math.Abs(Math.Sin(Math.PI)) should return ~0.0
-    let (_code, result) = compile_and_run(&arena, &type_manager, "Math.Abs(Math.Sin(Math.PI))");
-    let value = result.unwrap().as_float().unwrap();
-    assert!(value.abs() < 1e-10, "Expected ~0.0, got {}", value);
+    let (_code, result) = compile_and_run(&arena, &type_manager, "Array.Windows([1, 2, 3], 2)");
+    assert_eq!(nested_ints(result.unwrap()), [vec![1, 2], vec![2, 3]]);
 }
 
 #[test]
-fn test_ffi_complex_expression() {
+fn test_ffi_array_chunk_non_positive_size() {
     let arena = Bump::new();
     let type_manager = TypeManager::new(&arena);
 
-    // Test: Math.Sqrt(a * a + b * b) where { a = 3.0, b = 4.0 } should return 5.0 (Pythagorean triple)
-    let (_code, result) = compile_and_run(
-        &arena,
-        &type_manager,
-        "Math.Sqrt(a * a + b * b) where { a = 3.0, b = 4.0 }",
-    );
-    let value = result.unwrap().as_float().unwrap();
-    assert!((value - 5.0).abs() < 1e-10, "Expected 5.0, got {}", value);
+    let (_code, result) = compile_and_run(&arena, &type_manager, "Array.Chunk([1, 2, 3], 0)");
+    assert!(matches!(
+        result.unwrap_err().kind,
+        crate::evaluator::ExecutionErrorKind::Runtime(
+            crate::evaluator::RuntimeError::InvalidArgument { .. }
+        )
+    ));
 }
 
-// === WideArg Tests ===
-// These tests verify that the compiler correctly emits WideArg prefixes for large arguments
-// and that the VM correctly decodes them.
-
 #[test]
-fn test_wide_arg_many_constants() {
-    // Test with >255 constants to verify WideArg encoding for ConstLoad
+fn test_ffi_array_repeat() {
     let arena = Bump::new();
     let type_manager = TypeManager::new(&arena);
 
-    // Build an expression that creates many unique float constants (strings would need >255 unique values)
-    // We use a where-clause with many unique float values
-    // Formula: sum of first 260 unique floats (0.0 to 259.0), each accessed twice to force constant pool usage
-    // Then access constants[256], constants[257], constants[258], constants[259]
-    // These will require WideArg since their indices are > 255
-
-    // Generate: c256 + c257 + c258 + c259 where { c0 = 0.0, c1 = 1.0, ..., c259 = 259.0 }
-    let mut source = String::new();
-    source.push_str("c256 + c257 + c258 + c259 where {\n");
-    for i in 0..260 {
-        if i > 0 {
-            source.push_str(",\n");
-        }
-        source.push_str(&alloc::format!("    c{} = {}.0", i, i));
-    }
-    source.push_str("\n}");
-
-    let (code, result) = compile_and_run(&arena, &type_manager, &source);
-
-    // Verify that WideArg instructions are present for the large constant indices
-    let has_wide_arg = code
-        .instructions
+    let (_code, result) = compile_and_run(&arena, type_manager, "Array.Repeat(7, 3)");
+    let elements: Vec<i64> = result
+        .unwrap()
+        .as_array()
+        .unwrap()
         .iter()
-        .any(|i| matches!(i, Instruction::WideArg(_)));
-    assert!(
-        has_wide_arg,
-        "Expected WideArg instructions for constant indices > 255"
-    );
-
-    // Verify result: 256.0 + 257.0 + 258.0 + 259.0 = 1030.0
-    let value = result.unwrap().as_float().unwrap();
-    assert!(
-        (value - 1030.0).abs() < 1e-10,
-        "Expected 1030.0, got {}",
-        value
-    );
+        .map(|elem| elem.as_int().unwrap())
+        .collect();
+    assert_eq!(elements, [7, 7, 7]);
 }
 
 #[test]
-fn test_wide_arg_many_locals() {
-    // Test with >255 local variables to verify WideArg encoding for LoadLocal/StoreLocal
+fn test_ffi_array_repeat_exceeds_length_limit() {
     let arena = Bump::new();
     let type_manager = TypeManager::new(&arena);
 
-    // Generate: x256 + x257 + x258 + x259 where { x0 = 0, x1 = 1, ..., x259 = 259 }
-    let mut source = String::new();
-    source.push_str("x256 + x257 + x258 + x259 where {\n");
-    for i in 0..260 {
-        if i > 0 {
-            source.push_str(",\n");
-        }
-        source.push_str(&alloc::format!("    x{} = {}", i, i));
-    }
-    source.push_str("\n}");
+    let (_code, result) = compile_and_run(&arena, type_manager, "Array.Repeat(7, 1000000000)");
+    assert!(matches!(
+        result.unwrap_err().kind,
+        crate::evaluator::ExecutionErrorKind::ResourceExceeded(
+            crate::evaluator::ResourceExceededError::ArrayTooLarge {
+                len: 1_000_000_000,
+                ..
+            }
+        )
+    ));
+}
 
-    let (code, result) = compile_and_run(&arena, &type_manager, &source);
+#[test]
+fn test_ffi_iterate() {
+    let arena = Bump::new();
+    let type_manager = TypeManager::new(&arena);
 
-    // Verify we have 260 locals
-    assert_eq!(
-        code.num_locals, 260,
-        "Expected 260 local variables, got {}",
-        code.num_locals
-    );
+    let (_code, result) = compile_and_run(&arena, type_manager, "Iterate(1, 10, (x) => x * 2)");
+    assert_eq!(result.unwrap().as_int().unwrap(), 1024);
+}
 
-    // Verify that WideArg instructions are present for the large local indices
-    let has_wide_arg = code
-        .instructions
-        .iter()
-        .any(|i| matches!(i, Instruction::WideArg(_)));
-    assert!(
-        has_wide_arg,
-        "Expected WideArg instructions for local indices > 255"
-    );
+#[test]
+fn test_ffi_iterate_with_captured_variable() {
+    let arena = Bump::new();
+    let type_manager = TypeManager::new(&arena);
 
-    // Verify result: 256 + 257 + 258 + 259 = 1030
-    assert_eq!(result.unwrap().as_int().unwrap(), 1030);
+    let (_code, result) = compile_and_run(
+        &arena,
+        type_manager,
+        "Iterate(0, 1000, (x) => x + step) where { step = 3 }",
+    );
+    assert_eq!(result.unwrap().as_int().unwrap(), 3000);
 }
 
 #[test]
-fn test_wide_arg_large_array() {
-    // Test with a large array (>255 elements) to verify WideArg encoding for MakeArray
+fn test_ffi_iterate_many_steps_is_stack_safe() {
     let arena = Bump::new();
     let type_manager = TypeManager::new(&arena);
 
-    // Generate: [0, 1, 2, ..., 299][256]
-    // This creates an array with 300 elements (requires WideArg for MakeArray)
-    // and accesses element 256 (requires WideArg for ArrayGetConst)
-    let mut source = String::new();
-    source.push('[');
-    for i in 0..300 {
-        if i > 0 {
-            source.push_str(", ");
-        }
-        source.push_str(&alloc::format!("{}", i));
-    }
-    source.push_str("][256]");
-
-    let (code, result) = compile_and_run(&arena, &type_manager, &source);
+    // Far deeper than any recursion limit: steps run one after another.
+    let (_code, result) = compile_and_run(&arena, type_manager, "Iterate(0, 100000, (x) => x + 1)");
+    assert_eq!(result.unwrap().as_int().unwrap(), 100_000);
+}
 
-    // Verify that WideArg instructions are present
-    let has_wide_arg = code
-        .instructions
-        .iter()
-        .any(|i| matches!(i, Instruction::WideArg(_)));
-    assert!(
-        has_wide_arg,
-        "Expected WideArg instructions for array with 300 elements"
-    );
+#[test]
+fn test_ffi_iterate_exceeds_iteration_limit() {
+    let arena = Bump::new();
+    let type_manager = TypeManager::new(&arena);
 
-    // Verify result: element at index 256 is 256
-    assert_eq!(result.unwrap().as_int().unwrap(), 256);
+    let (_code, result) =
+        compile_and_run(&arena, type_manager, "Iterate(1, 1000000000, (x) => x * 2)");
+    assert!(matches!(
+        result.unwrap_err().kind,
+        crate::evaluator::ExecutionErrorKind::ResourceExceeded(
+            crate::evaluator::ResourceExceededError::TooManyIterations {
+                count: 1_000_000_000,
+                ..
+            }
+        )
+    ));
 }
 
 #[test]
-fn test_wide_arg_encoding_bytes() {
-    // Test that WideArg encoding works correctly for multi-byte values
-    // by verifying the bytecode structure directly
+fn test_vm_step_limit() {
+    use crate::values::StepBudget;
     use crate::vm::VM;
 
     let arena = Bump::new();
+    let type_manager = TypeManager::new(&arena);
 
-    // Create a code object manually with a large constant index (0x0102 = 258)
-    // This should be encoded as: WideArg(0x01), ConstLoad(0x02)
-    let mut constants = alloc::vec::Vec::new();
-    for i in 0..300i64 {
-        constants.push(RawValue::make_int(i));
-    }
+    let (code, _result) =
+        compile_and_run(&arena, type_manager, "Iterate([0], 100, (xs) => [xs[0] + 1])");
+    let run = |max_steps| {
+        let steps = StepBudget::new(max_steps);
+        let mut vm = VM::new(&arena, &code, Vec::new(), &[]);
+        vm.set_steps(&steps);
+        (vm.run(), steps.taken())
+    };
+
+    // Instructions of the lambda body count against the same budget
+    let (result, taken) = run(None);
+    assert!(result.is_ok());
+    assert!(taken > 100 * 3, "took only {} steps", taken);
+
+    let (result, _) = run(Some(taken));
+    assert!(result.is_ok());
+
+    let (result, _) = run(Some(taken - 1));
+    assert_eq!(
+        result.unwrap_err().kind,
+        crate::evaluator::ExecutionErrorKind::ResourceExceeded(
+            crate::evaluator::ResourceExceededError::TooManySteps {
+                max_steps: taken - 1
+            }
+        )
+    );
+}
+
+#[test]
+fn test_ffi_array_map() {
+    let arena = Bump::new();
+    let type_manager = TypeManager::new(&arena);
+
+    let (_code, result) = compile_and_run(&arena, type_manager, "Array.Map([1, 2, 3], (x) => x * 2)");
+    let elements: Vec<i64> = result
+        .unwrap()
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|elem| elem.as_int().unwrap())
+        .collect();
+    assert_eq!(elements, [2, 4, 6]);
+}
+
+#[test]
+fn test_ffi_array_search() {
+    let arena = Bump::new();
+    let type_manager = TypeManager::new(&arena);
+
+    let (_code, result) = compile_and_run(&arena, type_manager, "Array.IndexOf([10, 20, 30], 20)");
+    let index = result.unwrap().as_option().unwrap().unwrap();
+    assert_eq!(index.as_int().unwrap(), 1);
+
+    let (_code, result) = compile_and_run(&arena, type_manager, "Array.IndexOf([10, 20, 30], 25)");
+    assert!(result.unwrap().as_option().unwrap().is_none());
+
+    let (_code, result) = compile_and_run(&arena, type_manager, "Array.Contains([10, 20, 30], 30)");
+    assert!(result.unwrap().as_bool().unwrap());
+
+    let (_code, result) = compile_and_run(&arena, type_manager, "Array.Reverse([1, 2, 3])");
+    assert_eq!(int_array(result.unwrap()), [3, 2, 1]);
+}
+
+#[test]
+fn test_ffi_array_sort() {
+    let arena = Bump::new();
+    let type_manager = TypeManager::new(&arena);
+
+    let (_code, result) = compile_and_run(&arena, type_manager, "Array.Sort([3, 1, 2])");
+    assert_eq!(int_array(result.unwrap()), [1, 2, 3]);
+
+    let (_code, result) = compile_and_run(
+        &arena,
+        type_manager,
+        "Array.SortBy([3, 1, 2], (a, b) => a > b)",
+    );
+    assert_eq!(int_array(result.unwrap()), [3, 2, 1]);
+}
+
+#[test]
+fn test_ffi_array_map_changes_element_type() {
+    let arena = Bump::new();
+    let type_manager = TypeManager::new(&arena);
+
+    let (_code, result) = compile_and_run(&arena, type_manager, "Array.Map([1, 2, 3], (x) => x > 1)");
+    let result = result.unwrap();
+    assert!(core::ptr::eq(result.ty, type_manager.array(type_manager.bool())));
+    let elements: Vec<bool> = result
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|elem| elem.as_bool().unwrap())
+        .collect();
+    assert_eq!(elements, [false, true, true]);
+}
+
+#[test]
+fn test_ffi_array_map_with_captured_variable() {
+    let arena = Bump::new();
+    let type_manager = TypeManager::new(&arena);
+
+    let (_code, result) = compile_and_run(
+        &arena,
+        type_manager,
+        "Array.Map([1, 2, 3], (x) => x + offset) where { offset = 10 }",
+    );
+    let elements: Vec<i64> = result
+        .unwrap()
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|elem| elem.as_int().unwrap())
+        .collect();
+    assert_eq!(elements, [11, 12, 13]);
+}
+
+#[test]
+fn test_ffi_map_map_values() {
+    let arena = Bump::new();
+    let type_manager = TypeManager::new(&arena);
+
+    let (_code, result) = compile_and_run(
+        &arena,
+        type_manager,
+        "Map.MapValues({1: 2, 3: 4}, (v) => v * 10)",
+    );
+    let result = result.unwrap();
+    let entries: Vec<(i64, i64)> = result
+        .as_map()
+        .unwrap()
+        .iter()
+        .map(|(key, value)| (key.as_int().unwrap(), value.as_int().unwrap()))
+        .collect();
+    assert_eq!(entries, [(1, 20), (3, 40)]);
+    assert_eq!(result.to_string(), "{1: 20, 3: 40}");
+}
+
+#[test]
+fn test_ffi_map_map_values_changes_value_type() {
+    let arena = Bump::new();
+    let type_manager = TypeManager::new(&arena);
+
+    let (_code, result) = compile_and_run(
+        &arena,
+        type_manager,
+        r#"Map.MapValues({"b": 2, "a": 1}, (v) => v > 1)"#,
+    );
+    let result = result.unwrap();
+    assert!(core::ptr::eq(
+        result.ty,
+        type_manager.map(type_manager.str(), type_manager.bool())
+    ));
+    assert_eq!(result.to_string(), r#"{"a": false, "b": true}"#);
+}
+
+#[test]
+fn test_ffi_array_filter() {
+    let arena = Bump::new();
+    let type_manager = TypeManager::new(&arena);
+
+    let (_code, result) = compile_and_run(
+        &arena,
+        type_manager,
+        "Array.Filter([1, 2, 3, 4, 5], (x) => x % 2 == 1)",
+    );
+    let elements: Vec<i64> = result
+        .unwrap()
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|elem| elem.as_int().unwrap())
+        .collect();
+    assert_eq!(elements, [1, 3, 5]);
+}
+
+#[test]
+fn test_ffi_array_partition() {
+    let arena = Bump::new();
+    let type_manager = TypeManager::new(&arena);
+
+    let (_code, result) = compile_and_run(
+        &arena,
+        type_manager,
+        "Array.Partition([1, 2, 3, 4], (x) => x % 2 == 0)",
+    );
+    let result = result.unwrap();
+    let record = result.as_record().unwrap();
+    let elements = |field| -> Vec<i64> {
+        record
+            .get(field)
+            .unwrap()
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|elem| elem.as_int().unwrap())
+            .collect()
+    };
+    assert_eq!(elements("matched"), [2, 4]);
+    assert_eq!(elements("rest"), [1, 3]);
+}
+
+#[test]
+fn test_ffi_option_map() {
+    let arena = Bump::new();
+    let type_manager = TypeManager::new(&arena);
+
+    let (_code, result) = compile_and_run(&arena, type_manager, "Option.Map(some 3, (x) => x + 1)");
+    let result = result.unwrap();
+    assert!(core::ptr::eq(result.ty, type_manager.option(type_manager.int())));
+    assert_eq!(result.as_option().unwrap().unwrap().as_int().unwrap(), 4);
+
+    let (_code, result) = compile_and_run(&arena, type_manager, "Option.Map(none, (x) => x + 1)");
+    assert!(result.unwrap().as_option().unwrap().is_none());
+}
+
+#[test]
+fn test_ffi_option_get_or_else() {
+    let arena = Bump::new();
+    let type_manager = TypeManager::new(&arena);
+
+    let (_code, result) = compile_and_run(&arena, type_manager, "Option.GetOrElse(none, 5)");
+    assert_eq!(result.unwrap().as_int().unwrap(), 5);
+
+    let (_code, result) = compile_and_run(&arena, type_manager, "Option.GetOrElse(some 3, 5)");
+    assert_eq!(result.unwrap().as_int().unwrap(), 3);
+}
+
+#[test]
+fn test_ffi_option_is_some() {
+    let arena = Bump::new();
+    let type_manager = TypeManager::new(&arena);
+
+    let (_code, result) = compile_and_run(
+        &arena,
+        type_manager,
+        "[Option.IsSome(some 1), Option.IsSome(none)]",
+    );
+    let elements: Vec<bool> = result
+        .unwrap()
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|elem| elem.as_bool().unwrap())
+        .collect();
+    assert_eq!(elements, [true, false]);
+}
+
+#[test]
+fn test_ffi_option_filter_with_captured_variable() {
+    let arena = Bump::new();
+    let type_manager = TypeManager::new(&arena);
+
+    let (_code, result) = compile_and_run(
+        &arena,
+        type_manager,
+        "[Option.Filter(some 4, (x) => x > limit), Option.Filter(some 2, (x) => x > limit)] \
+         where { limit = 3 }",
+    );
+    let elements: Vec<Option<i64>> = result
+        .unwrap()
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|elem| elem.as_option().unwrap().map(|inner| inner.as_int().unwrap()))
+        .collect();
+    assert_eq!(elements, [Some(4), None]);
+}
+
+#[test]
+fn test_ffi_array_reduce() {
+    let arena = Bump::new();
+    let type_manager = TypeManager::new(&arena);
+
+    let (_code, result) = compile_and_run(
+        &arena,
+        type_manager,
+        "Array.Reduce([1, 2, 3, 4], 1, (acc, x) => acc * x)",
+    );
+    assert_eq!(result.unwrap().as_int().unwrap(), 24);
+}
+
+#[test]
+fn test_ffi_assert() {
+    let arena = Bump::new();
+    let type_manager = TypeManager::new(&arena);
+
+    let (_code, result) = compile_and_run(&arena, type_manager, r#"Assert(true, "ok")"#);
+    assert!(result.unwrap().as_bool().unwrap());
+
+    let (_code, result) = compile_and_run(&arena, type_manager, r#"Assert(false, "bad")"#);
+    assert_eq!(
+        result.unwrap_err().kind,
+        ExecutionErrorKind::Runtime(RuntimeError::AssertionFailed {
+            message: "bad".into()
+        })
+    );
+
+    let (_code, result) =
+        compile_and_run(&arena, type_manager, r#"Assert(false, "bad") otherwise false"#);
+    assert!(!result.unwrap().as_bool().unwrap());
+}
+
+#[test]
+fn test_ffi_expect() {
+    let arena = Bump::new();
+    let type_manager = TypeManager::new(&arena);
+
+    let (_code, result) = compile_and_run(
+        &arena,
+        type_manager,
+        r#"Expect(x * 2, x > 0, "x must be positive") where { x = 21 }"#,
+    );
+    assert_eq!(result.unwrap().as_int().unwrap(), 42);
+
+    let (_code, result) =
+        compile_and_run(&arena, type_manager, r#"Expect(1, false, "bad") otherwise 0"#);
+    assert_eq!(result.unwrap().as_int().unwrap(), 0);
+}
+
+#[test]
+fn test_ffi_string_count() {
+    let arena = Bump::new();
+    let type_manager = TypeManager::new(&arena);
+
+    let (_code, result) = compile_and_run(&arena, type_manager, r#"String.Count("banana", "a")"#);
+    assert_eq!(result.unwrap().as_int().unwrap(), 3);
+
+    // Occurrences do not overlap.
+    let (_code, result) = compile_and_run(&arena, type_manager, r#"String.Count("aaa", "aa")"#);
+    assert_eq!(result.unwrap().as_int().unwrap(), 1);
+}
+
+#[test]
+fn test_ffi_string_match() {
+    let arena = Bump::new();
+    let type_manager = TypeManager::new(&arena);
+    let groups = |value: Value| -> Vec<String> {
+        let groups = value.as_option().unwrap().expect("Expected a match");
+        groups
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|group| group.as_str().unwrap().to_string())
+            .collect()
+    };
+
+    let (_code, result) = compile_and_run(
+        &arena,
+        type_manager,
+        r#"String.Match("(\\d+)-(\\d+)", "12-34")"#,
+    );
+    assert_eq!(groups(result.unwrap()), ["12-34", "12", "34"]);
+
+    let (_code, result) = compile_and_run(
+        &arena,
+        type_manager,
+        r#"String.Match("(\\d+)-(\\d+)", "ab-cd")"#,
+    );
+    assert!(result.unwrap().as_option().unwrap().is_none());
+
+    // Invalid patterns are runtime errors that can be handled.
+    let (_code, result) = compile_and_run(
+        &arena,
+        type_manager,
+        r#"String.Match("(", "x") otherwise some ["fallback"]"#,
+    );
+    assert_eq!(groups(result.unwrap()), ["fallback"]);
+}
+
+#[test]
+fn test_ffi_array_to_map() {
+    let arena = Bump::new();
+    let type_manager = TypeManager::new(&arena);
+
+    let (_code, result) = compile_and_run(
+        &arena,
+        &type_manager,
+        "Array.ToMap([{key = 1, value = 10}, {key = 2, value = 20}])",
+    );
+    let map = result.unwrap().as_map().unwrap();
+    assert_eq!(map.len(), 2);
+    let get = |key| {
+        map.get(&Value::int(type_manager, key))
+            .map(|value| value.as_int().unwrap())
+    };
+    assert_eq!(get(1), Some(10));
+    assert_eq!(get(2), Some(20));
+}
+
+#[test]
+fn test_ffi_array_to_map_duplicate_keys() {
+    let arena = Bump::new();
+    let type_manager = TypeManager::new(&arena);
+
+    // The last entry for a key wins
+    let (_code, result) = compile_and_run(
+        &arena,
+        &type_manager,
+        "Array.ToMap([{key = 1, value = 10}, {key = 1, value = 20}])",
+    );
+    let map = result.unwrap().as_map().unwrap();
+    assert_eq!(map.len(), 1);
+    let value = map.get(&Value::int(type_manager, 1)).unwrap();
+    assert_eq!(value.as_int().unwrap(), 20);
+}
+
+#[test]
+fn test_ffi_in_if_expression() {
+    let arena = Bump::new();
+    let type_manager = TypeManager::new(&arena);
+
+    // Test: if Math.Sqrt(4.0) > 1.5 then 10.0 else 20.0
+    let (_code, result) = compile_and_run(
+        &arena,
+        &type_manager,
+        "if Math.Sqrt(4.0) > 1.5 then 10.0 else 20.0",
+    );
+    let value = result.unwrap().as_float().unwrap();
+    assert!((value - 10.0).abs() < 1e-10, "Expected 10.0, got {}", value);
+}
+
+#[test]
+fn test_ffi_chained_method_calls() {
+    let arena = Bump::new();
+    let type_manager = TypeManager::new(&arena);
+
+    // Test: Math.Abs(Math.Sin(Math.PI)) should return ~0.0
+    let (_code, result) = compile_and_run(&arena, &type_manager, "Math.Abs(Math.Sin(Math.PI))");
+    let value = result.unwrap().as_float().unwrap();
+    assert!(value.abs() < 1e-10, "Expected ~0.0, got {}", value);
+}
+
+#[test]
+fn test_ffi_complex_expression() {
+    let arena = Bump::new();
+    let type_manager = TypeManager::new(&arena);
+
+    // Test: Math.Sqrt(a * a + b * b) where { a = 3.0, b = 4.0 } should return 5.0 (Pythagorean triple)
+    let (_code, result) = compile_and_run(
+        &arena,
+        &type_manager,
+        "Math.Sqrt(a * a + b * b) where { a = 3.0, b = 4.0 }",
+    );
+    let value = result.unwrap().as_float().unwrap();
+    assert!((value - 5.0).abs() < 1e-10, "Expected 5.0, got {}", value);
+}
+
+// === WideArg Tests ===
+// These tests verify that the compiler correctly emits WideArg prefixes for large arguments
+// and that the VM correctly decodes them.
+
+#[test]
+fn test_wide_arg_many_constants() {
+    // Test with >255 constants to verify WideArg encoding for ConstLoad
+    let arena = Bump::new();
+    let type_manager = TypeManager::new(&arena);
+
+    // Build an expression that creates many unique float constants (strings would need >255 unique values)
+    // We use a where-clause with many unique float values
+    // Formula: sum of first 260 unique floats (0.0 to 259.0), each accessed twice to force constant pool usage
+    // Then access constants[256], constants[257], constants[258], constants[259]
+    // These will require WideArg since their indices are > 255
+
+    // Generate: c256 + c257 + c258 + c259 where { c0 = 0.0, c1 = c0 * 0.0 + 1.0, ..., c259 = c258 * 0.0 + 259.0 }
+    // Each binding uses the previous one, so none of them is left out as unused
+    let mut source = String::new();
+    source.push_str("c256 + c257 + c258 + c259 where {\n");
+    source.push_str("    c0 = 0.0");
+    for i in 1..260 {
+        source.push_str(",\n");
+        source.push_str(&alloc::format!("    c{} = c{} * 0.0 + {}.0", i, i - 1, i));
+    }
+    source.push_str("\n}");
+
+    let (code, result) = compile_and_run(&arena, &type_manager, &source);
+
+    // Verify that WideArg instructions are present for the large constant indices
+    let has_wide_arg = code
+        .instructions
+        .iter()
+        .any(|i| matches!(i, Instruction::WideArg(_)));
+    assert!(
+        has_wide_arg,
+        "Expected WideArg instructions for constant indices > 255"
+    );
+
+    // Verify result: 256.0 + 257.0 + 258.0 + 259.0 = 1030.0
+    let value = result.unwrap().as_float().unwrap();
+    assert!(
+        (value - 1030.0).abs() < 1e-10,
+        "Expected 1030.0, got {}",
+        value
+    );
+}
+
+#[test]
+fn test_wide_arg_many_locals() {
+    // Test with >255 local variables to verify WideArg encoding for LoadLocal/StoreLocal
+    let arena = Bump::new();
+    let type_manager = TypeManager::new(&arena);
+
+    // Generate: x256 + x257 + x258 + x259 where { x0 = 0, x1 = x0 + 1, ..., x259 = x258 + 1 }
+    // Each binding uses the previous one, so none of them is left out as unused
+    let mut source = String::new();
+    source.push_str("x256 + x257 + x258 + x259 where {\n");
+    source.push_str("    x0 = 0");
+    for i in 1..260 {
+        source.push_str(",\n");
+        source.push_str(&alloc::format!("    x{} = x{} + 1", i, i - 1));
+    }
+    source.push_str("\n}");
+
+    let (code, result) = compile_and_run(&arena, &type_manager, &source);
+
+    // Verify we have 260 locals
+    assert_eq!(
+        code.num_locals, 260,
+        "Expected 260 local variables, got {}",
+        code.num_locals
+    );
+
+    // Verify that WideArg instructions are present for the large local indices
+    let has_wide_arg = code
+        .instructions
+        .iter()
+        .any(|i| matches!(i, Instruction::WideArg(_)));
+    assert!(
+        has_wide_arg,
+        "Expected WideArg instructions for local indices > 255"
+    );
+
+    // Verify result: 256 + 257 + 258 + 259 = 1030
+    assert_eq!(result.unwrap().as_int().unwrap(), 1030);
+}
+
+#[test]
+fn test_wide_arg_large_array() {
+    // Test with a large array (>255 elements) to verify WideArg encoding for MakeArray
+    let arena = Bump::new();
+    let type_manager = TypeManager::new(&arena);
+
+    // Generate: [0, 1, 2, ..., 299][256]
+    // This creates an array with 300 elements (requires WideArg for MakeArray)
+    // and accesses element 256 (requires WideArg for ArrayGetConst)
+    let mut source = String::new();
+    source.push('[');
+    for i in 0..300 {
+        if i > 0 {
+            source.push_str(", ");
+        }
+        source.push_str(&alloc::format!("{}", i));
+    }
+    source.push_str("][256]");
+
+    let (code, result) = compile_and_run(&arena, &type_manager, &source);
+
+    // Verify that WideArg instructions are present
+    let has_wide_arg = code
+        .instructions
+        .iter()
+        .any(|i| matches!(i, Instruction::WideArg(_)));
+    assert!(
+        has_wide_arg,
+        "Expected WideArg instructions for array with 300 elements"
+    );
+
+    // Verify result: element at index 256 is 256
+    assert_eq!(result.unwrap().as_int().unwrap(), 256);
+}
+
+#[test]
+fn test_wide_arg_encoding_bytes() {
+    // Test that WideArg encoding works correctly for multi-byte values
+    // by verifying the bytecode structure directly
+    use crate::vm::VM;
+
+    let arena = Bump::new();
+
+    // Create a code object manually with a large constant index (0x0102 = 258)
+    // This should be encoded as: WideArg(0x01), ConstLoad(0x02)
+    let mut constants = alloc::vec::Vec::new();
+    for i in 0..300i64 {
+        constants.push(RawValue::make_int(i));
+    }
 
     let code = Code {
         constants,
+        constant_types: alloc::vec::Vec::new(),
         adapters: alloc::vec::Vec::new(),
         generic_adapters: alloc::vec::Vec::new(),
         instructions: alloc::vec![
@@ -2634,6 +4003,7 @@ fn test_wide_arg_three_byte_encoding() {
     // This should be encoded as: WideArg(0x01), WideArg(0x00), ConstLoad(0x01)
     let code = Code {
         constants,
+        constant_types: alloc::vec::Vec::new(),
         adapters: alloc::vec::Vec::new(),
         generic_adapters: alloc::vec::Vec::new(),
         instructions: alloc::vec![
@@ -2861,6 +4231,7 @@ fn test_wide_jump_vm_direct() {
 
     let code = Code {
         constants: alloc::vec::Vec::new(),
+        constant_types: alloc::vec::Vec::new(),
         adapters: alloc::vec::Vec::new(),
         generic_adapters: alloc::vec::Vec::new(),
         instructions,
@@ -2898,6 +4269,7 @@ fn test_wide_jump_pop_jump_if_false_vm_direct() {
 
     let code = Code {
         constants: alloc::vec::Vec::new(),
+        constant_types: alloc::vec::Vec::new(),
         adapters: alloc::vec::Vec::new(),
         generic_adapters: alloc::vec::Vec::new(),
         instructions,
@@ -3043,6 +4415,400 @@ fn test_bytes_greater_than_or_equal() {
     assert_eq!(result.unwrap().as_bool().unwrap(), true);
 }
 
+// ============================================================================
+// Option Comparison Tests
+// ============================================================================
+
+#[test]
+fn test_option_none_less_than_some() {
+    let arena = Bump::new();
+    let type_manager = TypeManager::new(&arena);
+
+    let (_code, result) = compile_and_run(&arena, type_manager, "none < some 0");
+    assert!(result.unwrap().as_bool().unwrap());
+
+    let (_code, result) = compile_and_run(&arena, type_manager, "some 0 <= none");
+    assert!(!result.unwrap().as_bool().unwrap());
+}
+
+#[test]
+fn test_option_some_compares_contents() {
+    let arena = Bump::new();
+    let type_manager = TypeManager::new(&arena);
+
+    let (_code, result) = compile_and_run(&arena, type_manager, "some 1 < some 2");
+    assert!(result.unwrap().as_bool().unwrap());
+
+    let (_code, result) = compile_and_run(&arena, type_manager, r#"some "b" >= some "a""#);
+    assert!(result.unwrap().as_bool().unwrap());
+
+    let (_code, result) = compile_and_run(&arena, type_manager, "some (some 1) > some none");
+    assert!(result.unwrap().as_bool().unwrap());
+}
+
+#[test]
+fn test_option_equality() {
+    let arena = Bump::new();
+    let type_manager = TypeManager::new(&arena);
+
+    let (_code, result) = compile_and_run(&arena, type_manager, "some 1 == some 1");
+    assert!(result.unwrap().as_bool().unwrap());
+
+    let (_code, result) = compile_and_run(&arena, type_manager, "some 1 != none");
+    assert!(result.unwrap().as_bool().unwrap());
+}
+
+#[test]
+fn test_option_comparison_in_polymorphic_lambda() {
+    let arena = Bump::new();
+    let type_manager = TypeManager::new(&arena);
+
+    let (_code, result) = compile_and_run(
+        &arena,
+        type_manager,
+        "[lt(none, some 1.5), lt(some 2.5, some 1.5)] where { lt = (a, b) => a < b }",
+    );
+    let result = result.unwrap();
+    let array = result.as_array().unwrap();
+    assert!(array.get(0).unwrap().as_bool().unwrap());
+    assert!(!array.get(1).unwrap().as_bool().unwrap());
+}
+
+// ============================================================================
+// ToOption Tests
+// ============================================================================
+
+#[test]
+fn test_to_option() {
+    let arena = Bump::new();
+    let type_manager = TypeManager::new(&arena);
+
+    let (code, result) = compile_and_run(&arena, type_manager, "ToOption(true, 5)");
+    let value = result.unwrap().as_option().unwrap().unwrap();
+    assert_eq!(value.as_int().unwrap(), 5);
+    // Compiled as a branch, not a call
+    assert!(
+        code.instructions
+            .iter()
+            .any(|i| matches!(i, Instruction::MakeOption(1)))
+    );
+    assert!(
+        !code
+            .instructions
+            .iter()
+            .any(|i| matches!(i, Instruction::Call(_)))
+    );
+
+    let (_code, result) = compile_and_run(&arena, type_manager, "ToOption(false, 5)");
+    assert!(result.unwrap().as_option().unwrap().is_none());
+}
+
+#[test]
+fn test_to_option_evaluates_value_only_when_true() {
+    let arena = Bump::new();
+    let type_manager = TypeManager::new(&arena);
+
+    let (_code, result) = compile_and_run(&arena, type_manager, "ToOption(1 > 2, 1 / 0)");
+    assert!(result.unwrap().as_option().unwrap().is_none());
+}
+
+#[test]
+fn test_to_option_can_be_shadowed() {
+    let arena = Bump::new();
+    let type_manager = TypeManager::new(&arena);
+
+    let (_code, result) = compile_and_run(
+        &arena,
+        type_manager,
+        "ToOption(true, 5) where { ToOption = (cond, value) => value }",
+    );
+    assert_eq!(result.unwrap().as_int().unwrap(), 5);
+}
+
+// ============================================================================
+// Serialization Tests
+// ============================================================================
+
+#[test]
+fn test_serialization_round_trip() {
+    let arena = Bump::new();
+    let type_manager = TypeManager::new(&arena);
+
+    for (source, expected) in [
+        ("(2 + 3) * 4", "20"),
+        ("1000 * 1000 + 1", "1000001"),
+        (r#"if 2.5 > 1.0 then "yes" else "no""#, "yes"),
+        (r#"b"\x00\xff" == b"\x00\xff""#, "true"),
+        ("(1 > 2) match { true -> 1, false -> 2 }", "2"),
+        ("(some 3) match { some x -> x * 2, none -> 0 }", "6"),
+        ("[1, 2, 3][5] otherwise -1", "-1"),
+        ("{ a = 1.5, b = [2] }.b[0]", "2"),
+        ("((x) => x * 2)(21)", "42"),
+        (r#"((name) => f"Hello, {name}!")("world")"#, "Hello, world!"),
+        ("add(41) where { y = 1, add = (x) => x + y }", "42"),
+        (
+            "f(1.5) > 2.0 and f(2) > 3 where { f = (x) => x + x }",
+            "true",
+        ),
+        (
+            r#"{ a = id(1), b = id("x") }.b where { id = (x) => x }"#,
+            "x",
+        ),
+        (r#"{ "a": 1, "b": 2 }["b"]"#, "2"),
+        (r#""c" in ["a", "b"]"#, "false"),
+        ("(3 as Float) / 2.0", "1.5"),
+    ] {
+        let (code, result) = compile_and_run(&arena, type_manager, source);
+        let result = result.unwrap();
+        assert_eq!(result.to_string(), expected);
+
+        let bytes = code.to_bytes().unwrap();
+        let decoded = Code::from_bytes(&arena, type_manager, &bytes).unwrap();
+        assert_eq!(decoded.instructions, code.instructions, "{}", source);
+        assert_eq!(decoded.constant_types, code.constant_types);
+
+        let raw = VM::execute(&arena, &decoded).unwrap();
+        let decoded_result = Value::from_raw_unchecked(result.ty, raw);
+        assert_eq!(decoded_result, result, "{}", source);
+    }
+}
+
+#[test]
+fn test_serialization_rejects_native_functions() {
+    let arena = Bump::new();
+    let type_manager = TypeManager::new(&arena);
+
+    let (code, _result) = compile_and_run(&arena, type_manager, "Math.Floor(2.5)");
+    assert_eq!(code.to_bytes().unwrap_err(), EncodeError::FunctionCall);
+}
+
+#[test]
+fn test_deserialization_errors() {
+    let arena = Bump::new();
+    let type_manager = TypeManager::new(&arena);
+    let (code, _result) = compile_and_run(&arena, type_manager, "(2 + 3) * 4");
+    let bytes = code.to_bytes().unwrap();
+    let decode = |bytes: &[u8]| Code::from_bytes(&arena, type_manager, bytes).map(|_| ());
+
+    assert_eq!(decode(b"not bytecode"), Err(DecodeError::NotBytecode));
+    assert_eq!(
+        decode(&bytes[..bytes.len() - 1]),
+        Err(DecodeError::UnexpectedEnd)
+    );
+
+    let mut newer = bytes.clone();
+    newer[4..6].copy_from_slice(&(BYTECODE_FORMAT_VERSION + 1).to_le_bytes());
+    assert_eq!(
+        decode(&newer),
+        Err(DecodeError::UnsupportedVersion {
+            version: BYTECODE_FORMAT_VERSION + 1
+        })
+    );
+
+    let mut trailing = bytes.clone();
+    trailing.push(0);
+    assert_eq!(decode(&trailing), Err(DecodeError::TrailingBytes));
+}
+
+#[test]
+fn test_deserialization_rejects_oversized_header() {
+    let arena = Bump::new();
+    let type_manager = TypeManager::new(&arena);
+    let decode = |bytes: &[u8]| Code::from_bytes(&arena, type_manager, bytes).map(|_| ());
+
+    // Magic, version, num_locals, max_stack_size, no constants, a single
+    // Halt, and no adapters or lambdas
+    let header = |num_locals: u32, max_stack_size: u32| {
+        let mut bytes = b"MLBC".to_vec();
+        bytes.extend_from_slice(&BYTECODE_FORMAT_VERSION.to_le_bytes());
+        bytes.extend_from_slice(&num_locals.to_le_bytes());
+        bytes.extend_from_slice(&max_stack_size.to_le_bytes());
+        bytes.extend_from_slice(&0u32.to_le_bytes());
+        bytes.extend_from_slice(&1u32.to_le_bytes());
+        bytes.extend_from_slice(&Instruction::Halt.to_bytes());
+        for _ in 0..3 {
+            bytes.extend_from_slice(&0u32.to_le_bytes());
+        }
+        bytes
+    };
+
+    assert_eq!(
+        decode(&header(u32::MAX, 0)),
+        Err(DecodeError::InvalidCode {
+            index: 0,
+            reason: "has more locals than it can store"
+        })
+    );
+    assert_eq!(
+        decode(&header(0, u32::MAX)),
+        Err(DecodeError::InvalidCode {
+            index: 0,
+            reason: "has a larger stack than it can fill"
+        })
+    );
+}
+
+#[test]
+fn test_deserialization_rejects_invalid_code() {
+    use crate::vm::LambdaKind;
+    use Instruction::*;
+
+    let arena = Bump::new();
+    let type_manager = TypeManager::new(&arena);
+    let decode = |instructions: &[Instruction]| {
+        let code = Code {
+            constants: vec![RawValue::make_int(7)],
+            constant_types: vec![type_manager.int()],
+            adapters: vec![],
+            generic_adapters: vec![],
+            instructions: instructions.to_vec(),
+            num_locals: 1,
+            max_stack_size: 2,
+            lambdas: vec![],
+        };
+        let bytes = code.to_bytes().unwrap();
+        match Code::from_bytes(&arena, type_manager, &bytes) {
+            Ok(_) => None,
+            Err(DecodeError::InvalidCode { index, .. }) => Some(index),
+            Err(error) => panic!("unexpected error: {}", error),
+        }
+    };
+
+    assert_eq!(decode(&[ConstLoad(0), Return]), None);
+    assert_eq!(decode(&[WideArg(0), ConstLoad(0), Return]), None);
+
+    // Jumps past the end, or between a WideArg prefix and its instruction
+    assert_eq!(decode(&[ConstBool(1), PopJumpIfFalse(2), Return]), Some(1));
+    assert_eq!(decode(&[ConstInt(1), JumpForward(200), Return]), Some(1));
+    assert_eq!(
+        decode(&[
+            ConstBool(1),
+            PopJumpIfFalse(1),
+            WideArg(0),
+            ConstLoad(0),
+            Return
+        ]),
+        Some(1)
+    );
+    // Out of range constants and locals
+    assert_eq!(decode(&[ConstLoad(1), Return]), Some(0));
+    assert_eq!(decode(&[WideArg(1), ConstLoad(0), Return]), Some(1));
+    assert_eq!(
+        decode(&[ConstInt(1), StoreLocal(1), ConstInt(2), Return]),
+        Some(1)
+    );
+    assert_eq!(decode(&[LoadLocal(0), Return]), Some(0));
+    // Missing Return
+    assert_eq!(decode(&[ConstInt(1)]), Some(0));
+    assert_eq!(decode(&[]), Some(0));
+    // WideArg prefixes on instructions without operands, or too many of them
+    assert_eq!(decode(&[ConstInt(1), WideArg(0), Return]), Some(2));
+    assert_eq!(
+        decode(&[
+            WideArg(0),
+            WideArg(0),
+            WideArg(0),
+            WideArg(0),
+            ConstLoad(0),
+            Return
+        ]),
+        Some(3)
+    );
+    // Stack overflow and underflow
+    assert_eq!(
+        decode(&[ConstInt(1), ConstInt(2), ConstInt(3), Pop, Pop, Return]),
+        Some(2)
+    );
+    assert_eq!(decode(&[ConstInt(1), IntBinOp(b'+'), Return]), Some(1));
+    assert_eq!(decode(&[ConstInt(1), ConstInt(2), Return]), Some(2));
+    // Values used as the wrong kind
+    assert_eq!(decode(&[ConstLoad(0), Not, Return]), Some(1));
+    assert_eq!(decode(&[ConstInt(1), RecordGet(0), Return]), Some(1));
+    assert_eq!(
+        decode(&[
+            ConstBool(1),
+            MakeOption(1),
+            MatchSomeOrJump(1),
+            IntBinOp(b'+'),
+            Return
+        ]),
+        Some(3)
+    );
+    // A branch that skips pushing the result
+    let lt = IntCmpOp(ComparisonOp::Lt);
+    assert_eq!(
+        decode(&[
+            ConstInt(1),
+            ConstInt(2),
+            lt,
+            PopJumpIfFalse(1),
+            ConstInt(3),
+            Return
+        ]),
+        Some(5)
+    );
+    // The same branch is fine when the condition is known
+    assert_eq!(
+        decode(&[ConstBool(1), PopJumpIfFalse(1), ConstInt(3), Return]),
+        None
+    );
+
+    // A polymorphic lambda whose type is not a function, or that has no
+    // instantiations
+    let decode_code = |code: &Code| {
+        let bytes = code.to_bytes().unwrap();
+        match Code::from_bytes(&arena, type_manager, &bytes) {
+            Ok(_) => panic!("tampered code was accepted"),
+            Err(DecodeError::InvalidCode { reason, .. }) => reason,
+            Err(error) => panic!("unexpected error: {}", error),
+        }
+    };
+    let source = r#"{ a = id(1), b = id("x") }.b where { id = (x) => x }"#;
+    let poly = |code: &Code| {
+        code.lambdas
+            .iter()
+            .position(|lambda| matches!(lambda.kind, LambdaKind::Poly { .. }))
+            .unwrap()
+    };
+
+    let (mut code, _result) = compile_and_run(&arena, type_manager, source);
+    let index = poly(&code);
+    code.lambdas[index].lambda_type = type_manager.int();
+    assert_eq!(
+        decode_code(&code),
+        "makes a closure of a type that is not a function"
+    );
+
+    let (mut code, _result) = compile_and_run(&arena, type_manager, source);
+    let index = poly(&code);
+    code.lambdas[index].kind = LambdaKind::Poly { monos: vec![] };
+    assert_eq!(decode_code(&code), "makes a closure without instantiations");
+}
+
+#[test]
+fn test_deserialization_rejects_invalid_lambdas() {
+    let arena = Bump::new();
+    let type_manager = TypeManager::new(&arena);
+    let decode = |code: &Code| {
+        let bytes = code.to_bytes().unwrap();
+        match Code::from_bytes(&arena, type_manager, &bytes) {
+            Ok(_) => panic!("tampered code was accepted"),
+            Err(DecodeError::InvalidCode { reason, .. }) => reason,
+            Err(error) => panic!("unexpected error: {}", error),
+        }
+    };
+
+    // A call that passes a string to a lambda taking an integer
+    let (mut code, _result) = compile_and_run(&arena, type_manager, "((x) => x + 1)(21)");
+    code.adapters[0] = FunctionAdapter::new(type_manager, vec![type_manager.str()]);
+    assert_eq!(decode(&code), "passes an argument of the wrong kind");
+
+    // A lambda whose body returns an integer where a string is declared
+    let (mut code, _result) = compile_and_run(&arena, type_manager, "((x) => x + 1)(21)");
+    code.lambdas[0].lambda_type = type_manager.function(&[type_manager.int()], type_manager.str());
+    assert_eq!(decode(&code), "returns a value of the wrong kind");
+}
+
 // ============================================================================
 // Cast Tests
 // ============================================================================
@@ -3434,7 +5200,29 @@ fn test_match_nested_some() {
 
     println!("Code: {:?}", _code);
 
-    assert_eq!(result.unwrap().as_int().unwrap(), 5);
+    assert_eq!(result.unwrap().as_int().unwrap(), 5);
+}
+
+#[test]
+fn test_match_as_pattern() {
+    let arena = Bump::new();
+    let type_manager = TypeManager::new(&arena);
+
+    // Both the inner value and the whole option are bound
+    let (_code, result) = compile_and_run(
+        &arena,
+        type_manager,
+        "opt match { some x as whole -> x + (whole ?? 0), none -> 0 } where { opt = some 5 }",
+    );
+    assert_eq!(result.unwrap().as_int().unwrap(), 10);
+
+    // A failed inner match falls through to the next arm
+    let (_code, result) = compile_and_run(
+        &arena,
+        type_manager,
+        "opt match { some 1 as one -> 0, some x as whole -> x, none -> -1 } where { opt = some 2 }",
+    );
+    assert_eq!(result.unwrap().as_int().unwrap(), 2);
 }
 
 #[test]
@@ -3851,6 +5639,58 @@ fn test_polymorphic_lambda_indexable() {
     );
 }
 
+#[test]
+fn test_polymorphic_lambda_field_access() {
+    use crate::vm::LambdaKind;
+
+    let arena = Bump::new();
+    let type_manager = TypeManager::new(&arena);
+
+    // Polymorphic lambda: get_x = (r) => r.x
+    // Field 'x' is at a different index in each record type
+    let (code, result) = compile_and_run(
+        &arena,
+        &type_manager,
+        "{ a = get_x({x = 1, y = 2}), b = get_x({w = 3, x = 4}) } where { get_x = (r) => r.x }",
+    );
+
+    // Result is a record { a = 1, b = 4 }
+    let result_val = result.unwrap();
+    let record = result_val.as_record().unwrap();
+    assert_eq!(record.get("a").unwrap().as_int().unwrap(), 1);
+    assert_eq!(record.get("b").unwrap().as_int().unwrap(), 4);
+
+    // Should have 3 lambdas: 2 Mono instantiations + 1 Poly entry (Poly is last)
+    assert_eq!(
+        code.lambdas.len(),
+        3,
+        "Should have 3 lambda entries (2 Mono + 1 Poly)"
+    );
+    assert!(
+        matches!(code.lambdas[2].kind, LambdaKind::Poly { .. }),
+        "Last should be Poly"
+    );
+}
+
+#[test]
+fn test_polymorphic_lambda_cast() {
+    let arena = Bump::new();
+    let type_manager = TypeManager::new(&arena);
+
+    // Polymorphic lambda: to_float = (x) => x as Float
+    // Each instantiation casts from its own argument type
+    let (_code, result) = compile_and_run(
+        &arena,
+        type_manager,
+        "{ a = to_float(2), b = to_float(2.5) } where { to_float = (x) => x as Float }",
+    );
+
+    let result_val = result.unwrap();
+    let record = result_val.as_record().unwrap();
+    assert_eq!(record.get("a").unwrap().as_float().unwrap(), 2.0);
+    assert_eq!(record.get("b").unwrap().as_float().unwrap(), 2.5);
+}
+
 #[test]
 fn test_monomorphic_lambda_single_instantiation() {
     use crate::vm::LambdaKind;
@@ -3917,6 +5757,24 @@ fn test_int_not_in_array() {
     assert_eq!(result.unwrap().as_bool().unwrap(), true);
 }
 
+#[test]
+fn test_int_not_in_array_single_check() {
+    let arena = Bump::new();
+    let type_manager = TypeManager::new(&arena);
+
+    let (code, result) = compile_and_run(&arena, type_manager, "5 not in [1, 2, 3]");
+    assert!(result.unwrap().as_bool().unwrap());
+
+    // The adapter negates the search itself: no separate `Not` is emitted
+    let adapter_calls = code
+        .instructions
+        .iter()
+        .filter(|instruction| matches!(instruction, Instruction::CallGenericAdapter(_)))
+        .count();
+    assert_eq!(adapter_calls, 1);
+    assert!(!code.instructions.contains(&Instruction::Not));
+}
+
 #[test]
 fn test_string_in_array_found() {
     let arena = Bump::new();
@@ -3987,3 +5845,493 @@ fn test_bool_in_array() {
     let (_, result) = compile_and_run(&arena, &type_manager, "true in [false, true, false]");
     assert_eq!(result.unwrap().as_bool().unwrap(), true);
 }
+
+// ============================================================================
+// Map Containment Tests
+// ============================================================================
+
+#[test]
+fn test_int_key_in_map() {
+    let arena = Bump::new();
+    let type_manager = TypeManager::new(&arena);
+    let map = r#"{1: "one", 5: "five", 9: "nine", 12: "twelve"}"#;
+
+    for (key, expected) in [(1, true), (5, true), (12, true), (0, false), (6, false)] {
+        let (code, result) = compile_and_run(&arena, type_manager, &format!("{} in {}", key, map));
+        assert!(code.instructions.contains(&Instruction::MapHas));
+        assert_eq!(
+            result.unwrap().as_bool().unwrap(),
+            expected,
+            "{} in map",
+            key
+        );
+
+        let (_, result) = compile_and_run(&arena, type_manager, &format!("{} not in {}", key, map));
+        assert_eq!(
+            result.unwrap().as_bool().unwrap(),
+            !expected,
+            "{} not in map",
+            key
+        );
+    }
+}
+
+#[test]
+fn test_string_key_in_map() {
+    let arena = Bump::new();
+    let type_manager = TypeManager::new(&arena);
+
+    let source = r#"[k in allowed, "carol" not in allowed] where {
+        allowed = {"alice": 1, "bob": 2},
+        k = "bob",
+    }"#;
+    let (code, result) = compile_and_run(&arena, type_manager, source);
+    assert!(!code.instructions.contains(&Instruction::MapHas));
+    let result = result.unwrap();
+    let flags = result.as_array().unwrap();
+    assert!(flags.get(0).unwrap().as_bool().unwrap());
+    assert!(flags.get(1).unwrap().as_bool().unwrap());
+
+    let (_, result) = compile_and_run(&arena, type_manager, r#""x" in {"a": 1}"#);
+    assert!(!result.unwrap().as_bool().unwrap());
+}
+
+#[test]
+fn test_float_non_finite_values() {
+    let arena = Bump::new();
+    let type_manager = TypeManager::new(&arena);
+
+    let tests = vec![
+        ("Math.NAN == Math.NAN", false),
+        ("Math.NAN != Math.NAN", true),
+        ("Math.NAN < 1.0 or Math.NAN >= 1.0", false),
+        ("-Math.INFINITY < -1.0e308", true),
+        ("Math.INFINITY == 1.0 / 0.0", true),
+        ("-0.0 == 0.0", true),
+        ("1.0 / -0.0 == -Math.INFINITY", true),
+    ];
+
+    for (expr, expected) in tests {
+        let (_code, result) = compile_and_run(&arena, &type_manager, expr);
+        assert_eq!(
+            result.unwrap().as_bool().unwrap(),
+            expected,
+            "Failed for expression: {}",
+            expr
+        );
+    }
+}
+
+// ============================================================================
+// Disassembly Tests
+// ============================================================================
+
+#[test]
+fn test_disassemble_addition() {
+    let arena = Bump::new();
+    let type_manager = TypeManager::new(&arena);
+
+    let (code, _result) = compile_and_run(&arena, type_manager, "1 + 2");
+    let listing: alloc::vec::Vec<_> = code
+        .disassemble()
+        .iter()
+        .map(|line| (line.offset, line.to_string(), line.stack_depth))
+        .collect();
+    assert_eq!(
+        listing,
+        [
+            (0, "ConstInt 1".into(), Some(1)),
+            (1, "ConstInt 2".into(), Some(2)),
+            (2, "IntBinOp +".into(), Some(1)),
+            (3, "Return".into(), Some(1)),
+        ]
+    );
+}
+
+#[test]
+fn test_disassemble_wide_args_and_jumps() {
+    let code = Code {
+        constants: alloc::vec::Vec::new(),
+        constant_types: alloc::vec::Vec::new(),
+        adapters: alloc::vec::Vec::new(),
+        generic_adapters: alloc::vec::Vec::new(),
+        instructions: alloc::vec![
+            Instruction::ConstBool(1),
+            Instruction::PopJumpIfFalse(3),
+            Instruction::WideArg(0x01),
+            Instruction::LoadLocal(0x02),
+            Instruction::JumpForward(1),
+            Instruction::ConstInt(7),
+            Instruction::Return,
+        ],
+        num_locals: 0x103,
+        max_stack_size: 1,
+        lambdas: alloc::vec::Vec::new(),
+    };
+    let lines = code.disassemble();
+    assert_eq!(lines.len(), 6);
+
+    // The prefix is folded into the instruction it extends
+    assert_eq!(lines[2].offset, 2);
+    assert_eq!(lines[2].operand, Some(DisasmOperand::Index(0x102)));
+    assert_eq!(lines[1].operand, Some(DisasmOperand::Target(5)));
+
+    // The else branch starts from the depth at the conditional jump
+    let depths: Vec<_> = lines.iter().map(|line| line.stack_depth).collect();
+    assert_eq!(depths, [Some(1), Some(0), Some(1), Some(1), Some(1), Some(1)]);
+}
+
+// ============================================================================
+// Constant Folding Tests
+// ============================================================================
+
+/// Like [`compile_and_run`], with constant folding enabled.
+fn compile_and_run_folded<'a>(
+    arena: &'a Bump,
+    type_manager: &'a TypeManager<'a>,
+    source: &str,
+) -> (Code<'a>, Result<Value<'a, 'a>, ExecutionError>) {
+    compile_and_run_with_compile_options(
+        arena,
+        type_manager,
+        source,
+        AnalyzerOptions::default(),
+        &CompileOptions::default(),
+    )
+}
+
+#[test]
+fn test_constant_folding_nested_operations() {
+    let arena = Bump::new();
+    let type_manager = TypeManager::new(&arena);
+
+    let (code, result) = compile_and_run_folded(&arena, type_manager, "(2 + 3) * 4 - -1");
+    assert_eq!(
+        code.instructions,
+        alloc::vec![Instruction::ConstInt(21), Instruction::Return]
+    );
+    assert_eq!(result.unwrap().as_int().unwrap(), 21);
+
+    let (code, result) = compile_and_run_folded(&arena, type_manager, "1.5 < 2.5 and not false");
+    assert_eq!(
+        code.instructions,
+        alloc::vec![Instruction::ConstBool(1), Instruction::Return]
+    );
+    assert!(result.unwrap().as_bool().unwrap());
+}
+
+#[test]
+fn test_constant_folding_keeps_division_by_zero() {
+    let arena = Bump::new();
+    let type_manager = TypeManager::new(&arena);
+
+    // `1 / 0` must still fail at runtime, not at compile time
+    let (code, result) = compile_and_run_folded(&arena, type_manager, "1 / 0");
+    assert_eq!(
+        code.instructions,
+        alloc::vec![
+            Instruction::ConstInt(1),
+            Instruction::ConstInt(0),
+            Instruction::IntBinOp(b'/'),
+            Instruction::Return,
+        ]
+    );
+    assert!(matches!(
+        result.unwrap_err().kind,
+        ExecutionErrorKind::Runtime(RuntimeError::DivisionByZero {})
+    ));
+
+    // ...so that `otherwise` can still recover from it
+    let (_code, result) =
+        compile_and_run_folded(&arena, type_manager, "(2 + 3) / (1 - 1) otherwise 7");
+    assert_eq!(result.unwrap().as_int().unwrap(), 7);
+}
+
+#[test]
+fn test_constant_folding_global_fields() {
+    let arena = Bump::new();
+    let type_manager = TypeManager::new(&arena);
+
+    let (code, result) = compile_and_run_folded(&arena, type_manager, "Math.PI * 2.0");
+    assert_eq!(
+        code.instructions,
+        alloc::vec![Instruction::ConstLoad(0), Instruction::Return]
+    );
+    assert_eq!(
+        result.unwrap().as_float().unwrap(),
+        core::f64::consts::PI * 2.0
+    );
+}
+
+#[test]
+fn test_constant_folding_skips_function_calls() {
+    let arena = Bump::new();
+    let type_manager = TypeManager::new(&arena);
+
+    let (code, result) = compile_and_run_folded(&arena, type_manager, "Math.Floor(2.5) + 1");
+    assert!(code.instructions.contains(&Instruction::IntBinOp(b'+')));
+    assert_eq!(result.unwrap().as_int().unwrap(), 3);
+}
+
+// ============================================================================
+// Peephole Optimization Tests
+// ============================================================================
+
+/// Compiles `source` with and without the peephole optimizer, and without
+/// constant folding.
+fn compile_with_and_without_peephole<'a>(
+    arena: &'a Bump,
+    type_manager: &'a TypeManager<'a>,
+    source: &str,
+) -> [(Code<'a>, Result<Value<'a, 'a>, ExecutionError>); 2] {
+    [true, false].map(|peephole_optimization| {
+        compile_and_run_with_compile_options(
+            arena,
+            type_manager,
+            source,
+            AnalyzerOptions::default(),
+            &CompileOptions {
+                peephole_optimization,
+                constant_folding: false,
+                ..Default::default()
+            },
+        )
+    })
+}
+
+#[test]
+fn test_peephole_rewrites_boolean_not() {
+    let arena = Bump::new();
+    let type_manager = TypeManager::new(&arena);
+
+    let [(code, result), _] =
+        compile_with_and_without_peephole(&arena, type_manager, "not (5 < 10)");
+
+    // Expected: ConstInt(5), ConstInt(10), IntCmpOp('>=')
+    assert_eq!(
+        code.instructions,
+        [
+            Instruction::ConstInt(5),
+            Instruction::ConstInt(10),
+            Instruction::IntCmpOp(ComparisonOp::Ge),
+            Instruction::Return,
+        ]
+    );
+    assert_eq!(code.max_stack_size, 2);
+    assert_eq!(result.unwrap().as_bool().unwrap(), false);
+}
+
+#[test]
+fn test_peephole_fuses_int_comparison_and_not() {
+    let arena = Bump::new();
+    let type_manager = TypeManager::new(&arena);
+
+    for (source, fused) in [
+        ("not (x < 10) where { x = 5 }", ComparisonOp::Ge),
+        ("not (x >= 10) where { x = 5 }", ComparisonOp::Lt),
+        ("not (x > 10) where { x = 5 }", ComparisonOp::Le),
+        ("not (x <= 10) where { x = 5 }", ComparisonOp::Gt),
+        ("not (x == 5) where { x = 5 }", ComparisonOp::Neq),
+        ("not (x != 5) where { x = 5 }", ComparisonOp::Eq),
+    ] {
+        let [(optimized, optimized_result), (plain, plain_result)] =
+            compile_with_and_without_peephole(&arena, type_manager, source);
+        assert_eq!(
+            optimized.instructions.len(),
+            plain.instructions.len() - 1,
+            "{}",
+            source
+        );
+        assert!(plain.instructions.contains(&Instruction::Not), "{}", source);
+        assert!(
+            !optimized.instructions.contains(&Instruction::Not),
+            "{}",
+            source
+        );
+        assert!(
+            optimized
+                .instructions
+                .contains(&Instruction::IntCmpOp(fused)),
+            "{}",
+            source
+        );
+        assert_eq!(
+            optimized_result.unwrap().as_bool().unwrap(),
+            plain_result.unwrap().as_bool().unwrap(),
+            "{}",
+            source
+        );
+    }
+}
+
+#[test]
+fn test_peephole_keeps_float_comparison_and_not() {
+    let arena = Bump::new();
+    let type_manager = TypeManager::new(&arena);
+
+    // `not (nan < 1.0)` is true but `nan >= 1.0` is false, so this can't be fused
+    let [(optimized, optimized_result), _] = compile_with_and_without_peephole(
+        &arena,
+        type_manager,
+        "not (x < 1.0) where { x = 0.0 / 0.0 }",
+    );
+    assert!(optimized.instructions.contains(&Instruction::Not));
+    assert!(optimized_result.unwrap().as_bool().unwrap());
+}
+
+#[test]
+fn test_peephole_fixes_jumps_over_fused_instructions() {
+    let arena = Bump::new();
+    let type_manager = TypeManager::new(&arena);
+
+    // The fused `not` sits between the conditional jump and its target
+    for x in [-1, 1, 5] {
+        let source = format!("if x > 0 then not (x < 3) else false where {{ x = {} }}", x);
+        let [(optimized, optimized_result), (plain, plain_result)] =
+            compile_with_and_without_peephole(&arena, type_manager, &source);
+        assert_eq!(optimized.instructions.len(), plain.instructions.len() - 1);
+        assert_eq!(
+            optimized_result.unwrap().as_bool().unwrap(),
+            plain_result.unwrap().as_bool().unwrap(),
+            "{}",
+            source
+        );
+    }
+}
+
+#[test]
+fn test_peephole_removes_jumps_to_next_instruction() {
+    let arena = Bump::new();
+
+    // if false then 1 else 2, with a no-op jump at the start of the then branch
+    let plain = alloc::vec![
+        Instruction::ConstBool(0),
+        Instruction::WideArg(0),
+        Instruction::PopJumpIfFalse(5), // to ConstInt(2)
+        Instruction::JumpForward(1),    // to ConstInt(1), skipping only padding
+        Instruction::Nop,
+        Instruction::ConstInt(1),
+        Instruction::JumpForward(2), // to Return
+        Instruction::Nop,
+        Instruction::ConstInt(2),
+        Instruction::Return,
+    ];
+    let mut optimized = plain.clone();
+    peephole::optimize(&mut optimized);
+    assert_eq!(
+        optimized,
+        alloc::vec![
+            Instruction::ConstBool(0),
+            Instruction::WideArg(0),
+            Instruction::PopJumpIfFalse(3),
+            Instruction::ConstInt(1),
+            Instruction::JumpForward(2),
+            Instruction::Nop,
+            Instruction::ConstInt(2),
+            Instruction::Return,
+        ]
+    );
+
+    let execute = |instructions| {
+        let code = Code {
+            constants: alloc::vec::Vec::new(),
+            constant_types: alloc::vec::Vec::new(),
+            adapters: alloc::vec::Vec::new(),
+            generic_adapters: alloc::vec::Vec::new(),
+            instructions,
+            num_locals: 0,
+            max_stack_size: 1,
+            lambdas: alloc::vec::Vec::new(),
+        };
+        VM::execute(&arena, &code).unwrap().as_int_unchecked()
+    };
+    assert_eq!(execute(optimized), 2);
+    assert_eq!(execute(plain), 2);
+}
+
+// ============================================================================
+// Dead Binding Tests
+// ============================================================================
+
+#[test]
+fn test_unused_pure_binding_is_left_out() {
+    let arena = Bump::new();
+    let type_manager = TypeManager::new(&arena);
+
+    let (code, result) = compile_and_run(&arena, type_manager, "y where { x = [1, 2, 3], y = 1 }");
+    assert_eq!(code.num_locals, 1);
+    assert_eq!(
+        code.instructions,
+        alloc::vec![
+            Instruction::ConstInt(1),
+            Instruction::StoreLocal(0),
+            Instruction::LoadLocal(0),
+            Instruction::Return,
+        ]
+    );
+    assert_eq!(result.unwrap().as_int().unwrap(), 1);
+
+    // `a` is only used by `b`, which is unused itself
+    let (code, result) =
+        compile_and_run(&arena, type_manager, "c where { a = 1, b = a + 1, c = 3 }");
+    assert_eq!(code.num_locals, 1);
+    assert_eq!(result.unwrap().as_int().unwrap(), 3);
+}
+
+#[test]
+fn test_unused_binding_with_effects_is_kept() {
+    let arena = Bump::new();
+    let type_manager = TypeManager::new(&arena);
+
+    // Function calls may have side effects
+    let (code, result) =
+        compile_and_run(&arena, type_manager, "y where { x = Math.Random(), y = 1 }");
+    assert_eq!(code.num_locals, 2);
+    assert!(code.instructions.contains(&Instruction::StoreLocal(0)));
+    assert_eq!(result.unwrap().as_int().unwrap(), 1);
+
+    // Leaving out a failing binding would hide its error
+    let (code, result) =
+        compile_and_run(&arena, type_manager, "y where { d = 0, x = 1 / d, y = 1 }");
+    assert_eq!(code.num_locals, 3);
+    assert!(matches!(
+        result.unwrap_err().kind,
+        ExecutionErrorKind::Runtime(RuntimeError::DivisionByZero {})
+    ));
+}
+
+#[test]
+fn test_compile_with_params() {
+    let arena = Bump::new();
+    let type_manager = TypeManager::new(&arena);
+    let int_ty = type_manager.int();
+
+    let parsed = parser::parse(&arena, "x * 10 + y").unwrap();
+    let typed = analyzer::analyze(
+        type_manager,
+        &arena,
+        parsed,
+        &[],
+        &[("x", int_ty), ("y", int_ty)],
+    )
+    .unwrap();
+    let code = BytecodeCompiler::compile_with_params(
+        type_manager,
+        &arena,
+        &[],
+        &["x", "y"],
+        typed,
+        &CompileOptions::default(),
+    )
+    .unwrap();
+
+    // Parameters are the first locals, in order
+    assert_eq!(code.num_locals, 2);
+    assert_eq!(code.instructions[0], Instruction::LoadLocal(0));
+    assert!(code.instructions.contains(&Instruction::LoadLocal(1)));
+
+    let locals = vec![RawValue::make_int(4), RawValue::make_int(2)];
+    let mut vm = VM::new(&arena, &code, locals, &[]);
+    let result = Value::from_raw_unchecked(int_ty, vm.run().unwrap());
+    assert_eq!(result.as_int().unwrap(), 42);
+}
@@ -0,0 +1,204 @@
+//! Compile-time evaluation of constant subexpressions.
+//!
+//! Operations whose operands are all known at compile time, such as
+//! `2 + 3 * 4` or `Math.PI * 2.0`, are evaluated by the compiler and emitted
+//! as a single constant. Folding follows the VM's semantics exactly (e.g.
+//! wrapping integer arithmetic), and gives up on any operation that would
+//! fail at runtime, such as `1 / 0`, so the error is still raised when (and
+//! only if) the expression is evaluated.
+//!
+//! Only pure operations are folded: arithmetic, bitwise, comparison and
+//! boolean operators, and fields of global records that hold a number or a
+//! boolean. Function calls are never folded, since functions like
+//! `Math.Random` are not pure.
+
+use crate::{
+    analyzer::{
+        IntegerDivision,
+        typed_expr::{Expr, ExprInner},
+    },
+    parser::{BinaryOp, BoolOp, ComparisonOp, UnaryOp},
+    types::manager::TypeManager,
+    values::dynamic::Value,
+};
+
+/// Scalar value known at compile time.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Scalar {
+    Int(i64),
+    Float(f64),
+    Bool(bool),
+}
+
+impl Scalar {
+    fn from_value(value: &Value<'_, '_>) -> Option<Self> {
+        if let Ok(int) = value.as_int() {
+            Some(Scalar::Int(int))
+        } else if let Ok(float) = value.as_float() {
+            Some(Scalar::Float(float))
+        } else if let Ok(bool) = value.as_bool() {
+            Some(Scalar::Bool(bool))
+        } else {
+            None
+        }
+    }
+
+    fn to_value<'types, 'arena>(
+        self,
+        type_mgr: &'types TypeManager<'types>,
+    ) -> Value<'types, 'arena> {
+        match self {
+            Scalar::Int(int) => Value::int(type_mgr, int),
+            Scalar::Float(float) => Value::float(type_mgr, float),
+            Scalar::Bool(bool) => Value::bool(type_mgr, bool),
+        }
+    }
+}
+
+/// Evaluates `expr` at compile time, if it is an operation on constants.
+///
+/// Plain constants are not folded (they are already constants), so this
+/// returns `None` for them. `lookup_global` resolves a name to the value of
+/// a global, or `None` if the name is not a global in the current scope
+/// (e.g. because a local shadows it).
+pub(super) fn fold_constant<'types, 'arena>(
+    type_mgr: &'types TypeManager<'types>,
+    expr: &Expr<'types, 'arena>,
+    lookup_global: &dyn Fn(&'arena str) -> Option<Value<'types, 'arena>>,
+) -> Option<Value<'types, 'arena>> {
+    match &expr.1 {
+        ExprInner::Constant(_) => None,
+        _ => evaluate(expr, lookup_global).map(|scalar| scalar.to_value(type_mgr)),
+    }
+}
+
+fn evaluate<'types, 'arena>(
+    expr: &Expr<'types, 'arena>,
+    lookup_global: &dyn Fn(&'arena str) -> Option<Value<'types, 'arena>>,
+) -> Option<Scalar> {
+    match &expr.1 {
+        ExprInner::Constant(value) => Scalar::from_value(value),
+        ExprInner::Binary {
+            op,
+            left,
+            right,
+            division,
+        } => binary(
+            *op,
+            *division,
+            evaluate(left, lookup_global)?,
+            evaluate(right, lookup_global)?,
+        ),
+        ExprInner::Unary { op, expr } => match (op, evaluate(expr, lookup_global)?) {
+            (UnaryOp::Neg, Scalar::Int(int)) => Some(Scalar::Int(int.wrapping_neg())),
+            (UnaryOp::Neg, Scalar::Float(float)) => Some(Scalar::Float(-float)),
+            (UnaryOp::Not, Scalar::Bool(bool)) => Some(Scalar::Bool(!bool)),
+            _ => None,
+        },
+        ExprInner::Comparison { op, left, right } => comparison(
+            *op,
+            evaluate(left, lookup_global)?,
+            evaluate(right, lookup_global)?,
+        ),
+        ExprInner::Boolean { op, left, right } => {
+            match (
+                evaluate(left, lookup_global)?,
+                evaluate(right, lookup_global)?,
+            ) {
+                (Scalar::Bool(left), Scalar::Bool(right)) => Some(Scalar::Bool(match op {
+                    BoolOp::And => left && right,
+                    BoolOp::Or => left || right,
+                })),
+                _ => None,
+            }
+        }
+        ExprInner::Field { value, field } => {
+            let ExprInner::Ident(name) = &value.1 else {
+                return None;
+            };
+            let record = lookup_global(name)?.as_record().ok()?;
+            Scalar::from_value(&record.get(field)?)
+        }
+        _ => None,
+    }
+}
+
+/// Applies `op` like the VM's `IntBinOp`, `FloatBinOp` and `IntBitOp` do,
+/// or returns `None` if it would fail.
+fn binary(op: BinaryOp, division: IntegerDivision, left: Scalar, right: Scalar) -> Option<Scalar> {
+    match (left, right) {
+        (Scalar::Int(a), Scalar::Int(b)) => {
+            let result = match op {
+                BinaryOp::Add => a.wrapping_add(b),
+                BinaryOp::Sub => a.wrapping_sub(b),
+                BinaryOp::Mul => a.wrapping_mul(b),
+                BinaryOp::Div | BinaryOp::Mod => {
+                    if b == 0 || (a == i64::MIN && b == -1) {
+                        return None;
+                    }
+                    if op == BinaryOp::Div {
+                        division.quotient(a, b)
+                    } else {
+                        division.remainder(a, b)
+                    }
+                }
+                BinaryOp::Pow => match u32::try_from(b) {
+                    Ok(exponent) => a.wrapping_pow(exponent),
+                    Err(_) => 0,
+                },
+                BinaryOp::BitwiseAnd => a & b,
+                BinaryOp::BitwiseOr => a | b,
+                BinaryOp::BitwiseXor => a ^ b,
+                BinaryOp::ShiftLeft | BinaryOp::ShiftRight => {
+                    if !(0..i64::BITS as i64).contains(&b) {
+                        return None;
+                    }
+                    if op == BinaryOp::ShiftLeft {
+                        a << b
+                    } else {
+                        a >> b
+                    }
+                }
+            };
+            Some(Scalar::Int(result))
+        }
+        (Scalar::Float(a), Scalar::Float(b)) => {
+            let result = match op {
+                BinaryOp::Add => a + b,
+                BinaryOp::Sub => a - b,
+                BinaryOp::Mul => a * b,
+                BinaryOp::Div => a / b,
+                BinaryOp::Mod => a % b,
+                BinaryOp::Pow => a.powf(b),
+                _ => return None,
+            };
+            Some(Scalar::Float(result))
+        }
+        _ => None,
+    }
+}
+
+/// Applies `op` like the VM's `IntCmpOp` and `FloatCmpOp` do.
+fn comparison(op: ComparisonOp, left: Scalar, right: Scalar) -> Option<Scalar> {
+    let ordering = match (left, right) {
+        (Scalar::Int(a), Scalar::Int(b)) => Some(a.cmp(&b)),
+        (Scalar::Float(a), Scalar::Float(b)) => a.partial_cmp(&b),
+        _ => return None,
+    };
+    let result = match op {
+        ComparisonOp::Eq => ordering == Some(core::cmp::Ordering::Equal),
+        ComparisonOp::Neq => ordering != Some(core::cmp::Ordering::Equal),
+        ComparisonOp::Lt => ordering == Some(core::cmp::Ordering::Less),
+        ComparisonOp::Gt => ordering == Some(core::cmp::Ordering::Greater),
+        ComparisonOp::Le => matches!(
+            ordering,
+            Some(core::cmp::Ordering::Less | core::cmp::Ordering::Equal)
+        ),
+        ComparisonOp::Ge => matches!(
+            ordering,
+            Some(core::cmp::Ordering::Greater | core::cmp::Ordering::Equal)
+        ),
+        ComparisonOp::In | ComparisonOp::NotIn => return None,
+    };
+    Some(Scalar::Bool(result))
+}
@@ -0,0 +1,204 @@
+//! Detection of `where` bindings whose value is never used.
+//!
+//! A binding can be left out of the bytecode when nothing in scope references
+//! it and evaluating it has no observable effect. Evaluation is observable
+//! when it calls a function (which may be a side-effecting FFI function such
+//! as `Math.Random`) or when it may fail at runtime (e.g. `1 / 0` or an
+//! out-of-bounds index), since leaving it out would hide the error.
+
+use crate::{
+    Vec,
+    analyzer::{
+        DuplicateKeyPolicy,
+        typed_expr::{Expr, ExprInner},
+    },
+    parser::BinaryOp,
+};
+
+/// Returns, for each of the `bindings` of a `where` with the given `body`,
+/// whether it can be left out because it is unused and evaluating it has no
+/// effect.
+///
+/// Bindings are sequential, so a binding only used by bindings that are left
+/// out is itself unused.
+pub(super) fn unused_bindings<'types, 'arena>(
+    body: &Expr<'types, 'arena>,
+    bindings: &[(&'arena str, &'arena Expr<'types, 'arena>)],
+) -> Vec<bool> {
+    let mut used = Vec::new();
+    collect_free_names(body, &mut Vec::new(), &mut used);
+
+    let mut unused = crate::vec![false; bindings.len()];
+    for (index, (name, value)) in bindings.iter().enumerate().rev() {
+        if !used.contains(name) && is_pure(value) {
+            unused[index] = true;
+        } else {
+            collect_free_names(value, &mut Vec::new(), &mut used);
+        }
+    }
+    unused
+}
+
+/// Collects the names referenced by `expr` that are not bound within it
+/// (nor in `bound`).
+fn collect_free_names<'arena>(
+    expr: &Expr<'_, 'arena>,
+    bound: &mut Vec<&'arena str>,
+    out: &mut Vec<&'arena str>,
+) {
+    match &expr.1 {
+        ExprInner::Ident(name) => {
+            if !bound.contains(name) && !out.contains(name) {
+                out.push(name);
+            }
+        }
+        ExprInner::Binary { left, right, .. }
+        | ExprInner::Boolean { left, right, .. }
+        | ExprInner::Comparison { left, right, .. } => {
+            collect_free_names(left, bound, out);
+            collect_free_names(right, bound, out);
+        }
+        ExprInner::Unary { expr, .. } | ExprInner::Cast { expr } => {
+            collect_free_names(expr, bound, out);
+        }
+        ExprInner::Field { value, .. } => collect_free_names(value, bound, out),
+        ExprInner::Call { callable, args, .. } => {
+            collect_free_names(callable, bound, out);
+            for arg in args.iter() {
+                collect_free_names(arg, bound, out);
+            }
+        }
+        ExprInner::Index { value, index } => {
+            collect_free_names(value, bound, out);
+            collect_free_names(index, bound, out);
+        }
+        ExprInner::Slice { value, start, end } => {
+            collect_free_names(value, bound, out);
+            for bound_expr in start.iter().chain(end.iter()) {
+                collect_free_names(bound_expr, bound, out);
+            }
+        }
+        // The analyzer already computed the free names of lambdas
+        ExprInner::Lambda { captures, .. } => {
+            for name in captures.iter() {
+                if !bound.contains(name) && !out.contains(name) {
+                    out.push(name);
+                }
+            }
+        }
+        ExprInner::If {
+            cond,
+            then_branch,
+            else_branch,
+        } => {
+            collect_free_names(cond, bound, out);
+            collect_free_names(then_branch, bound, out);
+            collect_free_names(else_branch, bound, out);
+        }
+        ExprInner::Where { expr, bindings } => {
+            let depth = bound.len();
+            for (name, value) in bindings.iter() {
+                collect_free_names(value, bound, out);
+                bound.push(name);
+            }
+            collect_free_names(expr, bound, out);
+            bound.truncate(depth);
+        }
+        ExprInner::Otherwise { primary, fallback } => {
+            collect_free_names(primary, bound, out);
+            collect_free_names(fallback, bound, out);
+        }
+        ExprInner::Coalesce { option, default } => {
+            collect_free_names(option, bound, out);
+            collect_free_names(default, bound, out);
+        }
+        ExprInner::Option { inner } => {
+            if let Some(inner) = inner {
+                collect_free_names(inner, bound, out);
+            }
+        }
+        ExprInner::Match { expr, arms } => {
+            collect_free_names(expr, bound, out);
+            for arm in arms.iter() {
+                let depth = bound.len();
+                bound.extend(arm.vars.iter().copied());
+                collect_free_names(arm.body, bound, out);
+                bound.truncate(depth);
+            }
+        }
+        ExprInner::Record { fields } => {
+            for (_, value) in fields.iter() {
+                collect_free_names(value, bound, out);
+            }
+        }
+        ExprInner::Map { elements, .. } => {
+            for (key, value) in elements.iter() {
+                collect_free_names(key, bound, out);
+                collect_free_names(value, bound, out);
+            }
+        }
+        ExprInner::Array { elements } => {
+            for element in elements.iter() {
+                collect_free_names(element, bound, out);
+            }
+        }
+        ExprInner::FormatStr { exprs, .. } => {
+            for expr in exprs.iter() {
+                collect_free_names(expr, bound, out);
+            }
+        }
+        ExprInner::Constant(_) => {}
+    }
+}
+
+/// Whether evaluating `expr` can neither fail nor have side effects.
+///
+/// This is conservative: calls, indexing, casts and matches are never pure,
+/// even when the particular ones in `expr` would be.
+fn is_pure(expr: &Expr<'_, '_>) -> bool {
+    match &expr.1 {
+        ExprInner::Constant(_) | ExprInner::Ident(_) | ExprInner::Lambda { .. } => true,
+        ExprInner::Binary {
+            op, left, right, ..
+        } => {
+            !matches!(
+                op,
+                BinaryOp::Div | BinaryOp::Mod | BinaryOp::ShiftLeft | BinaryOp::ShiftRight
+            ) && is_pure(left)
+                && is_pure(right)
+        }
+        ExprInner::Boolean { left, right, .. } | ExprInner::Comparison { left, right, .. } => {
+            is_pure(left) && is_pure(right)
+        }
+        ExprInner::Unary { expr, .. } => is_pure(expr),
+        ExprInner::Field { value, .. } => is_pure(value),
+        ExprInner::If {
+            cond,
+            then_branch,
+            else_branch,
+        } => is_pure(cond) && is_pure(then_branch) && is_pure(else_branch),
+        ExprInner::Where { expr, bindings } => {
+            bindings.iter().all(|(_, value)| is_pure(value)) && is_pure(expr)
+        }
+        ExprInner::Otherwise { primary, fallback } => is_pure(primary) && is_pure(fallback),
+        ExprInner::Coalesce { option, default } => is_pure(option) && is_pure(default),
+        ExprInner::Option { inner } => inner.is_none_or(is_pure),
+        ExprInner::Record { fields } => fields.iter().all(|(_, value)| is_pure(value)),
+        ExprInner::Map {
+            elements,
+            duplicate_keys,
+        } => {
+            *duplicate_keys == DuplicateKeyPolicy::LastWins
+                && elements
+                    .iter()
+                    .all(|(key, value)| is_pure(key) && is_pure(value))
+        }
+        ExprInner::Array { elements } => elements.iter().all(|element| is_pure(element)),
+        ExprInner::FormatStr { exprs, .. } => exprs.iter().all(|expr| is_pure(expr)),
+        ExprInner::Call { .. }
+        | ExprInner::Index { .. }
+        | ExprInner::Slice { .. }
+        | ExprInner::Cast { .. }
+        | ExprInner::Match { .. } => false,
+    }
+}
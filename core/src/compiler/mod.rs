@@ -9,10 +9,16 @@
 //! - Uses TreeTransformer pattern for AST traversal
 //! - Tracks stack depth precisely for debugging
 //! - Implements jump patching for control flow (if/else, boolean short-circuit)
+//! - Folds operations on constants into a single constant
+//! - Removes redundant instructions with a peephole pass
+//! - Leaves out unused `where` bindings that have no effect
 //! - Builds Code struct for VM execution
 
 mod bytecode;
+mod constant_folding;
+mod dead_bindings;
 mod error;
+mod peephole;
 
 #[cfg(test)]
 mod bytecode_test;
@@ -0,0 +1,138 @@
+//! Peephole optimization of generated bytecode.
+//!
+//! The compiler emits each construct independently, which sometimes leaves
+//! redundant sequences behind. This pass rewrites them in place:
+//!
+//! - `JumpForward` instructions whose target is the next non-`Nop`
+//!   instruction are removed, together with the padding they skip.
+//! - `IntCmpOp(op); Not` is fused into a single `IntCmpOp` with the inverted
+//!   operator (e.g. `not (a < b)` becomes `a >= b`). Integers are totally
+//!   ordered, so the inversion is exact; float comparisons are left alone
+//!   because of NaN.
+//!
+//! Removing instructions shifts everything after them, so every remaining
+//! jump is re-encoded against the new positions. Jumps only ever get
+//! shorter, so they keep fitting in the slots the compiler reserved for them.
+
+use crate::{
+    parser::ComparisonOp,
+    vm::{Instruction, get_jump_offset},
+};
+
+/// Optimizes `instructions` in place, preserving their behavior.
+pub(super) fn optimize(instructions: &mut alloc::vec::Vec<Instruction>) {
+    let jumps = decode_jumps(instructions);
+    let mut is_target = alloc::vec![false; instructions.len() + 1];
+    for jump in &jumps {
+        is_target[jump.target] = true;
+    }
+
+    let mut removed = alloc::vec![false; instructions.len()];
+    for jump in &jumps {
+        let skips_only_padding =
+            (jump.index + 1..jump.target).all(|index| instructions[index] == Instruction::Nop);
+        if matches!(instructions[jump.index], Instruction::JumpForward(_)) && skips_only_padding {
+            removed[jump.prefix_start..jump.target].fill(true);
+        }
+    }
+    for index in 0..instructions.len().saturating_sub(1) {
+        if let (Instruction::IntCmpOp(op), Instruction::Not) =
+            (instructions[index], instructions[index + 1])
+            && let Some(inverse) = invert(op)
+            && !is_target[index + 1]
+        {
+            instructions[index] = Instruction::IntCmpOp(inverse);
+            removed[index + 1] = true;
+        }
+    }
+    if !removed.contains(&true) {
+        return;
+    }
+
+    // New position of each old position; removed instructions map to the
+    // next instruction that is kept.
+    let mut new_position = alloc::vec::Vec::with_capacity(instructions.len() + 1);
+    let mut kept = 0;
+    for &is_removed in &removed {
+        new_position.push(kept);
+        if !is_removed {
+            kept += 1;
+        }
+    }
+    new_position.push(kept);
+
+    for jump in jumps.iter().filter(|jump| !removed[jump.index]) {
+        let offset = new_position[jump.target] - new_position[jump.index] - 1;
+        if jump.prefix_start < jump.index {
+            instructions[jump.index - 1] = Instruction::WideArg((offset >> 8) as u8);
+        }
+        instructions[jump.index] = with_jump_offset(instructions[jump.index], offset as u8);
+    }
+
+    let mut index = 0;
+    instructions.retain(|_| {
+        index += 1;
+        !removed[index - 1]
+    });
+}
+
+/// A jump instruction and the position it jumps to.
+struct Jump {
+    /// Position of the first `WideArg` prefix, or of the jump itself.
+    prefix_start: usize,
+    /// Position of the jump instruction.
+    index: usize,
+    /// Position of the instruction the jump lands on.
+    target: usize,
+}
+
+/// Finds the jumps in `instructions`, folding `WideArg` prefixes into their offsets.
+fn decode_jumps(instructions: &[Instruction]) -> alloc::vec::Vec<Jump> {
+    let mut jumps = alloc::vec::Vec::new();
+    let mut wide_arg: usize = 0;
+    let mut prefix_start = None;
+    for (index, &instr) in instructions.iter().enumerate() {
+        if let Instruction::WideArg(high) = instr {
+            prefix_start.get_or_insert(index);
+            wide_arg = (wide_arg | high as usize) << 8;
+            continue;
+        }
+        if let Some(low) = get_jump_offset(&instr) {
+            jumps.push(Jump {
+                prefix_start: prefix_start.unwrap_or(index),
+                index,
+                target: index + 1 + (wide_arg | low as usize),
+            });
+        }
+        prefix_start = None;
+        wide_arg = 0;
+    }
+    jumps
+}
+
+/// Same instruction as `instr`, which must be a jump, with a new offset.
+fn with_jump_offset(instr: Instruction, offset: u8) -> Instruction {
+    match instr {
+        Instruction::JumpForward(_) => Instruction::JumpForward(offset),
+        Instruction::PopJumpIfFalse(_) => Instruction::PopJumpIfFalse(offset),
+        Instruction::PopJumpIfTrue(_) => Instruction::PopJumpIfTrue(offset),
+        Instruction::PushOtherwise(_) => Instruction::PushOtherwise(offset),
+        Instruction::PopOtherwiseAndJump(_) => Instruction::PopOtherwiseAndJump(offset),
+        Instruction::MatchSomeOrJump(_) => Instruction::MatchSomeOrJump(offset),
+        Instruction::MatchNoneOrJump(_) => Instruction::MatchNoneOrJump(offset),
+        _ => unreachable!("not a jump instruction: {:?}", instr),
+    }
+}
+
+/// The operator giving `not (a op b)` for integers `a` and `b`.
+fn invert(op: ComparisonOp) -> Option<ComparisonOp> {
+    match op {
+        ComparisonOp::Eq => Some(ComparisonOp::Neq),
+        ComparisonOp::Neq => Some(ComparisonOp::Eq),
+        ComparisonOp::Lt => Some(ComparisonOp::Ge),
+        ComparisonOp::Ge => Some(ComparisonOp::Lt),
+        ComparisonOp::Gt => Some(ComparisonOp::Le),
+        ComparisonOp::Le => Some(ComparisonOp::Gt),
+        ComparisonOp::In | ComparisonOp::NotIn => None,
+    }
+}
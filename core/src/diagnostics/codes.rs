@@ -0,0 +1,532 @@
+//! Registry of diagnostic codes.
+//!
+//! Every diagnostic produced by the parser (`P***`), the analyzer (`E***`) and
+//! the evaluator (`R***`) carries a stable code. This module maps each code to
+//! a short title and a long-form explanation, so tools like the CLI and the
+//! LSP can offer an "explain this error" action.
+
+/// Documentation for a single diagnostic code.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DiagnosticCode {
+    /// The code itself, e.g. `"E002"`.
+    pub code: &'static str,
+    /// One-line summary of the diagnostic.
+    pub title: &'static str,
+    /// Long-form explanation, with examples where useful.
+    pub explanation: &'static str,
+}
+
+/// All known diagnostic codes, sorted by code.
+pub static REGISTRY: &[DiagnosticCode] = &[
+    // ------------------------------------------------------------------------
+    // Analyzer errors
+    // ------------------------------------------------------------------------
+    DiagnosticCode {
+        code: "E001",
+        title: "Type mismatch",
+        explanation: "\
+An expression has a different type than the one required by its context.
+
+For example, both branches of an `if` must have the same type:
+
+    if flag then 1 else \"one\"
+
+Convert one side explicitly (e.g. with `as`) or change the expression so
+that both types agree.",
+    },
+    DiagnosticCode {
+        code: "E002",
+        title: "Undefined variable",
+        explanation: "\
+A name is used that is not bound in the current scope.
+
+Names come from expression parameters, globals registered in the engine's
+environment, lambda parameters and `where` bindings:
+
+    x + y where { x = 1 }
+
+Here `y` is undefined. Check the spelling, declare the binding, or make sure
+the host registers the global before compiling.",
+    },
+    DiagnosticCode {
+        code: "E003",
+        title: "Unhandled error",
+        explanation: "\
+An operation that may fail at runtime is used in a position where errors are
+not allowed.
+
+Handle the failure with `otherwise` to provide a fallback value:
+
+    [1, 2, 3][i] otherwise 0",
+    },
+    DiagnosticCode {
+        code: "E004",
+        title: "Infinite type",
+        explanation: "\
+Type inference would require a type to contain itself, such as a value that
+is both `T` and `Array[T]`.
+
+This usually comes from applying a value to itself or from a recursive
+definition. Restructure the expression so each value has a finite type.",
+    },
+    DiagnosticCode {
+        code: "E005",
+        title: "Type class constraint not satisfied",
+        explanation: "\
+An operator or function requires a capability (a type class such as
+`Numeric`, `Ord` or `Hashable`) that the given type does not provide.
+
+For example, arithmetic is only defined on numbers:
+
+    \"a\" + \"b\"
+
+Use a function suited for the type instead, such as a format string for
+concatenation: f\"{a}{b}\".",
+    },
+    DiagnosticCode {
+        code: "E006",
+        title: "Record field count mismatch",
+        explanation: "\
+Two record types were expected to be the same but have a different number of
+fields.
+
+Records are structural: `{ a = 1 }` and `{ a = 1, b = 2 }` have different
+types. Make both records declare the same fields.",
+    },
+    DiagnosticCode {
+        code: "E007",
+        title: "Record field name mismatch",
+        explanation: "\
+Two record types were expected to be the same but their field names differ.
+
+    if flag then { a = 1 } else { b = 1 }
+
+Make both records use the same field names.",
+    },
+    DiagnosticCode {
+        code: "E008",
+        title: "Wrong number of arguments",
+        explanation: "\
+A function was called with a different number of arguments than it declares.
+
+    ((x, y) => x + y)(1)
+
+Pass exactly one argument per parameter.",
+    },
+    DiagnosticCode {
+        code: "E009",
+        title: "Type cannot be indexed",
+        explanation: "\
+The indexing operator `value[index]` was applied to a value that does not
+support it. Only arrays, maps, bytes and strings can be indexed.
+
+    42[0]",
+    },
+    DiagnosticCode {
+        code: "E010",
+        title: "Unknown record field",
+        explanation: "\
+A field was accessed that the record does not have.
+
+    { name = \"x\" }.nmae
+
+The diagnostic lists the available fields; check the field name for typos.",
+    },
+    DiagnosticCode {
+        code: "E012",
+        title: "Field access on non-record",
+        explanation: "\
+The field access operator `value.field` was applied to a value that is not a
+record.
+
+    (1).name
+
+Only records have fields. Packages registered as globals (such as `Math`)
+are records, so `Math.PI` is valid.",
+    },
+    DiagnosticCode {
+        code: "E013",
+        title: "Invalid type expression",
+        explanation: "\
+A type written in the source, for example the target of a cast, is not a
+valid type.
+
+    x as Aray[Int]
+
+Check the spelling of the type name and the number of type arguments.",
+    },
+    DiagnosticCode {
+        code: "E014",
+        title: "Invalid cast",
+        explanation: "\
+The cast `value as Type` is not allowed between these two types.
+
+Only specific conversions are supported, such as `Int` to `Float`, `Float`
+to `Int`, and `Str` to `Bytes` and back. Other conversions must be done with
+functions from the standard library.",
+    },
+    DiagnosticCode {
+        code: "E015",
+        title: "Duplicate parameter name",
+        explanation: "\
+A lambda declares the same parameter name more than once.
+
+    (x, x) => x
+
+Give each parameter a unique name.",
+    },
+    DiagnosticCode {
+        code: "E016",
+        title: "Duplicate binding name",
+        explanation: "\
+A `where` clause binds the same name more than once.
+
+    x where { x = 1, x = 2 }
+
+Give each binding a unique name.",
+    },
+    DiagnosticCode {
+        code: "E017",
+        title: "Type cannot be formatted",
+        explanation: "\
+A format string interpolates a value whose type has no textual
+representation, such as a function.
+
+    f\"{(x) => x}\"
+
+Interpolate the result of calling the function instead.",
+    },
+    DiagnosticCode {
+        code: "E018",
+        title: "Unsupported feature",
+        explanation: "\
+The expression uses a language feature that is recognized but not supported
+yet. The diagnostic includes a suggestion for an alternative.",
+    },
+    DiagnosticCode {
+        code: "E019",
+        title: "Cast of polymorphic value",
+        explanation: "\
+A cast was applied to a value whose type is never determined, for example the
+parameter of a lambda that is not applied to any argument:
+
+    (x) => x as Float
+
+Casts need a concrete source type. A lambda parameter gets one from the
+arguments the lambda is called with, so `((x) => x as Float)(42)` is valid.",
+    },
+    DiagnosticCode {
+        code: "E020",
+        title: "Non-exhaustive patterns",
+        explanation: "\
+A `match` expression does not cover every possible value of its subject.
+
+    opt match { some x -> x }
+
+Add arms for the missing cases listed in the diagnostic, or a wildcard arm
+`_ -> ...` to handle everything else.",
+    },
+    DiagnosticCode {
+        code: "E021",
+        title: "Duplicate map key",
+        explanation: "\
+A map literal lists the same key more than once while the engine is
+configured to reject duplicate keys.
+
+    { 1: \"one\", 1: \"uno\" }
+
+Remove one of the entries. With the default configuration the last entry
+wins instead.",
+    },
+    DiagnosticCode {
+        code: "E022",
+        title: "Type cannot be sliced",
+        explanation: "\
+The slicing operator `value[start:end]` was applied to a value that does not
+support it. Only arrays and strings can be sliced.
+
+    { a: 1 }[0:1]",
+    },
+    DiagnosticCode {
+        code: "E023",
+        title: "Unreachable pattern",
+        explanation: "\
+A `match` arm can never be taken because the arms before it already match
+every value its pattern matches. This is a warning: the expression still
+compiles, and the earlier arm is taken.
+
+    opt match { some x -> x, some 1 -> 0, none -> 0 }
+
+Remove the arm, or move it before the more general arm that hides it.",
+    },
+    DiagnosticCode {
+        code: "E024",
+        title: "Unused lambda parameter",
+        explanation: "\
+A lambda declares a parameter that its body never uses. This is a warning,
+only reported when the engine is configured to look for unused parameters.
+
+    (x, y) => x
+
+Remove the parameter if callers can be changed, or prefix its name with an
+underscore (`_y`) to mark it as intentionally unused.",
+    },
+    DiagnosticCode {
+        code: "E025",
+        title: "Recursive type alias",
+        explanation: "\
+A type alias refers to itself, directly or through other aliases declared
+alongside it. Aliases only give names to existing types and cannot define
+recursive types.
+
+    type List = Array[List]; []
+
+Spell out the type without referring back to the alias.",
+    },
+    DiagnosticCode {
+        code: "E999",
+        title: "Type error",
+        explanation: "\
+A type error that does not have a dedicated code yet. The diagnostic message
+describes the problem.",
+    },
+    // ------------------------------------------------------------------------
+    // Parse errors
+    // ------------------------------------------------------------------------
+    DiagnosticCode {
+        code: "P001",
+        title: "Unexpected token",
+        explanation: "\
+The parser found a token that cannot appear at this position.
+
+    1 + * 2
+
+The diagnostic lists what was expected instead.",
+    },
+    DiagnosticCode {
+        code: "P002",
+        title: "Unclosed delimiter",
+        explanation: "\
+An opening bracket, brace, parenthesis or quote has no matching closing one.
+
+    [1, 2, 3
+
+Add the missing closing delimiter.",
+    },
+    DiagnosticCode {
+        code: "P003",
+        title: "Invalid number literal",
+        explanation: "\
+A numeric literal is malformed or out of range.
+
+Integers must fit in a signed 64-bit integer, and floats in scientific
+notation need digits after the exponent marker:
+
+    1.5e3   // valid
+    1.5e    // invalid",
+    },
+    DiagnosticCode {
+        code: "P004",
+        title: "Nesting too deep",
+        explanation: "\
+The expression is nested more deeply than the parser allows. The limit
+protects the host from stack exhaustion on adversarial input.
+
+Split the expression using `where` bindings to reduce nesting.",
+    },
+    DiagnosticCode {
+        code: "P005",
+        title: "Misplaced digit separator",
+        explanation: "\
+A numeric literal has a digit separator `_` that is not between two
+digits. Separators may group digits anywhere inside a literal, but not
+at its end, next to another separator, after a radix prefix, or next to
+the decimal point or exponent marker:
+
+    1_000_000    // valid
+    0xFF_FF      // valid
+    1_           // invalid
+    1__000       // invalid
+    0x_FF        // invalid
+    1_.5         // invalid",
+    },
+    DiagnosticCode {
+        code: "P999",
+        title: "Syntax error",
+        explanation: "\
+A syntax error that does not have a dedicated code yet. The diagnostic
+message describes the problem.",
+    },
+    // ------------------------------------------------------------------------
+    // Runtime errors
+    // ------------------------------------------------------------------------
+    DiagnosticCode {
+        code: "R001",
+        title: "Division by zero",
+        explanation: "\
+A division or remainder operation had a zero divisor.
+
+This error can be caught with `otherwise`:
+
+    total / count otherwise 0",
+    },
+    DiagnosticCode {
+        code: "R002",
+        title: "Index out of bounds",
+        explanation: "\
+An array or bytes value was indexed outside the range `[0, length)`.
+
+This error can be caught with `otherwise`:
+
+    items[5] otherwise default",
+    },
+    DiagnosticCode {
+        code: "R003",
+        title: "Key not found",
+        explanation: "\
+A map was indexed with a key it does not contain.
+
+This error can be caught with `otherwise`:
+
+    config[\"timeout\"] otherwise 30",
+    },
+    DiagnosticCode {
+        code: "R004",
+        title: "Cast failed",
+        explanation: "\
+A cast that is valid for the types involved failed for this particular
+value, for example converting bytes that are not valid UTF-8 to `Str`.
+
+This error can be caught with `otherwise`.",
+    },
+    DiagnosticCode {
+        code: "R005",
+        title: "Stack overflow",
+        explanation: "\
+Evaluation exceeded the maximum nesting depth configured for the engine.
+
+This is a resource limit and cannot be caught with `otherwise`. Simplify the
+expression or raise `max_depth` in the run options.",
+    },
+    DiagnosticCode {
+        code: "R006",
+        title: "Internal error",
+        explanation: "\
+The interpreter reached a state that should be impossible for a well-typed
+expression. This is a bug in Melbi; please report it together with the
+expression that triggered it.",
+    },
+    DiagnosticCode {
+        code: "R007",
+        title: "Integer overflow",
+        explanation: "\
+An integer operation produced a result outside the signed 64-bit range, such
+as dividing the minimum integer by -1.
+
+This error can be caught with `otherwise`.",
+    },
+    DiagnosticCode {
+        code: "R008",
+        title: "Invalid argument",
+        explanation: "\
+A standard library function received an argument outside its accepted range,
+for example a non-positive size passed to `Array.Chunk`.
+
+This error can be caught with `otherwise`.",
+    },
+    DiagnosticCode {
+        code: "R009",
+        title: "Array too large",
+        explanation: "\
+A standard library function was asked to build an array longer than the
+allowed maximum, for example `Array.Repeat(0, 1000000000)`.
+
+This is a resource limit and cannot be caught with `otherwise`.",
+    },
+    DiagnosticCode {
+        code: "R010",
+        title: "Shift amount out of range",
+        explanation: "\
+A bit shift (`<<` or `>>`) was given a negative amount or an amount of 64 or
+more, which is not meaningful for 64-bit integers.
+
+This error can be caught with `otherwise`:
+
+    flags >> n otherwise 0",
+    },
+    DiagnosticCode {
+        code: "R011",
+        title: "Duplicate map key",
+        explanation: "\
+A map was built from computed keys and two of them turned out equal, while
+the engine is configured to reject duplicate keys.
+
+    { a: 1, b: 2 }
+
+fails when `a == b`. This error can be caught with `otherwise`.",
+    },
+    DiagnosticCode {
+        code: "R012",
+        title: "Assertion failed",
+        explanation: "\
+A call to `Assert` or `Expect` found its condition to be false. The
+diagnostic carries the message given to the call.
+
+    Expect(price, price >= 0, \"price must not be negative\")
+
+This error can be caught with `otherwise`.",
+    },
+    DiagnosticCode {
+        code: "R013",
+        title: "Too many iterations",
+        explanation: "\
+A call to `Iterate` asked for more steps than the allowed maximum, for
+example `Iterate(0, 1000000000, (x) => x + 1)`.
+
+This is a resource limit and cannot be caught with `otherwise`.",
+    },
+    DiagnosticCode {
+        code: "R014",
+        title: "Too many steps",
+        explanation: "\
+Evaluation took more steps than the host allowed with the `max_steps` run
+option. Every evaluated expression counts as a step, including the bodies of
+lambdas called by functions such as `Iterate` or `Array.Map`.
+
+This is a resource limit and cannot be caught with `otherwise`.",
+    },
+    DiagnosticCode {
+        code: "R015",
+        title: "Deadline exceeded",
+        explanation: "\
+Evaluation was still running when the deadline the host set with the
+`deadline` run option passed. The clock is checked periodically rather than
+after every step, so evaluation may run slightly past the deadline before it
+is stopped.
+
+This is a resource limit and cannot be caught with `otherwise`.",
+    },
+];
+
+/// Look up the documentation for a diagnostic code.
+pub fn lookup(code: &str) -> Option<&'static DiagnosticCode> {
+    REGISTRY.iter().find(|entry| entry.code == code)
+}
+
+/// Get the long-form explanation for a diagnostic code.
+///
+/// # Example
+///
+/// ```
+/// use melbi_core::diagnostics::explain;
+///
+/// assert!(explain("E002").unwrap().contains("not bound"));
+/// assert!(explain("X123").is_none());
+/// ```
+pub fn explain(code: &str) -> Option<&'static str> {
+    lookup(code).map(|entry| entry.explanation)
+}
+
+#[cfg(test)]
+#[path = "codes_test.rs"]
+mod codes_test;
@@ -0,0 +1,77 @@
+//! Tests for the diagnostic code registry.
+
+use super::{REGISTRY, explain, lookup};
+use crate::Vec;
+
+/// Extract every `Some("X000")` code literal from a source file.
+fn emitted_codes(source: &str) -> Vec<&str> {
+    source
+        .match_indices("Some(\"")
+        .filter_map(|(start, pattern)| {
+            let code = source.get(start + pattern.len()..start + pattern.len() + 4)?;
+            let mut chars = code.chars();
+            let is_code = chars.next().is_some_and(|c| c.is_ascii_uppercase())
+                && chars.all(|c| c.is_ascii_digit());
+            is_code.then_some(code)
+        })
+        .collect()
+}
+
+#[test]
+fn test_explain_known_code() {
+    let explanation = explain("E002").unwrap();
+    assert!(!explanation.trim().is_empty());
+    assert_eq!(lookup("E002").unwrap().title, "Undefined variable");
+}
+
+#[test]
+fn test_explain_unknown_code() {
+    assert!(explain("E000").is_none());
+    assert!(explain("").is_none());
+    assert!(explain("e002").is_none());
+}
+
+#[test]
+fn test_registry_is_sorted_and_unique() {
+    for pair in REGISTRY.windows(2) {
+        assert!(
+            pair[0].code < pair[1].code,
+            "{} must come before {}",
+            pair[0].code,
+            pair[1].code
+        );
+    }
+}
+
+#[test]
+fn test_registry_entries_are_documented() {
+    for entry in REGISTRY {
+        assert!(!entry.title.is_empty(), "{} has no title", entry.code);
+        assert!(
+            !entry.explanation.trim().is_empty(),
+            "{} has no explanation",
+            entry.code
+        );
+    }
+}
+
+#[test]
+fn test_every_emitted_code_is_registered() {
+    let sources = [
+        ("analyzer", include_str!("../analyzer/error.rs")),
+        ("parser", include_str!("../parser/error.rs")),
+        ("evaluator", include_str!("../evaluator/error.rs")),
+    ];
+    for (component, source) in sources {
+        let codes = emitted_codes(source);
+        assert!(!codes.is_empty(), "no codes found in {} errors", component);
+        for code in codes {
+            assert!(
+                lookup(code).is_some(),
+                "{} emits {} which is missing from the registry",
+                component,
+                code
+            );
+        }
+    }
+}
@@ -1,2 +1,5 @@
+pub mod codes;
 pub mod context;
 // Future: pub mod collection;
+
+pub use codes::{DiagnosticCode, explain, lookup};
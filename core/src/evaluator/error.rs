@@ -70,6 +70,18 @@ pub enum RuntimeError {
     /// TODO(effects): When effect system is implemented, mark fallible casts
     /// with `!` effect and make them catchable with `otherwise`.
     CastError { message: String },
+
+    /// Invalid argument passed to a native function (e.g., a non-positive size).
+    InvalidArgument { message: String },
+
+    /// Shift amount outside `[0, 64)` for `<<` or `>>`.
+    ShiftOutOfRange { amount: i64 },
+
+    /// Map built with the same key twice under `DuplicateKeyPolicy::Error`.
+    DuplicateMapKey { key_display: String },
+
+    /// `Assert` or `Expect` was called with a false condition.
+    AssertionFailed { message: String },
 }
 
 /// Resource limit exceeded errors that cannot be caught.
@@ -81,9 +93,20 @@ pub enum RuntimeError {
 pub enum ResourceExceededError {
     /// Evaluation recursion depth exceeded.
     StackOverflow { depth: usize, max_depth: usize },
+
+    /// A native function was asked to build an array beyond the length limit.
+    ArrayTooLarge { len: usize, max_len: usize },
+
+    /// `Iterate` was asked to run more steps than allowed.
+    TooManyIterations { count: usize, max_count: usize },
+
+    /// Evaluation took more steps than allowed.
+    TooManySteps { max_steps: u64 },
+
+    /// Evaluation was still running when the host's deadline passed.
+    DeadlineExceeded,
     // Future resource limits:
     // MemoryExceeded { bytes: usize, max_bytes: usize },
-    // TimeExceeded { millis: u64, max_millis: u64 },
 }
 
 /// Internal errors that indicate bugs in the compiler/interpreter (cannot be caught).
@@ -133,6 +156,28 @@ impl ExecutionError {
                 Some("R004"),
                 vec!["Verify the value can be safely converted to the target type".to_string()],
             ),
+            ExecutionErrorKind::Runtime(RuntimeError::InvalidArgument { message }) => (
+                format!("Invalid argument: {}", message),
+                Some("R008"),
+                vec!["Check the function's documented argument requirements".to_string()],
+            ),
+            ExecutionErrorKind::Runtime(RuntimeError::ShiftOutOfRange { amount }) => (
+                format!("Shift amount {} out of range", amount),
+                Some("R010"),
+                vec!["Shift amounts must be between 0 and 63".to_string()],
+            ),
+            ExecutionErrorKind::Runtime(RuntimeError::DuplicateMapKey { key_display }) => (
+                format!("Duplicate map key: {}", key_display),
+                Some("R011"),
+                vec!["Make sure computed map keys are distinct".to_string()],
+            ),
+            ExecutionErrorKind::Runtime(RuntimeError::AssertionFailed { message }) => (
+                format!("Assertion failed: {}", message),
+                Some("R012"),
+                vec![
+                    "Use 'otherwise' to provide a fallback value when the check fails".to_string(),
+                ],
+            ),
             ExecutionErrorKind::ResourceExceeded(ResourceExceededError::StackOverflow {
                 depth,
                 max_depth,
@@ -144,6 +189,40 @@ impl ExecutionError {
                 Some("R005"),
                 vec!["Reduce recursion depth or increase stack limit".to_string()],
             ),
+            ExecutionErrorKind::ResourceExceeded(ResourceExceededError::ArrayTooLarge {
+                len,
+                max_len,
+            }) => (
+                format!(
+                    "Array too large: length {} exceeds maximum of {}",
+                    len, max_len
+                ),
+                Some("R009"),
+                vec!["Build smaller arrays".to_string()],
+            ),
+            ExecutionErrorKind::ResourceExceeded(ResourceExceededError::TooManyIterations {
+                count,
+                max_count,
+            }) => (
+                format!(
+                    "Too many iterations: {} exceeds maximum of {}",
+                    count, max_count
+                ),
+                Some("R013"),
+                vec!["Iterate fewer times".to_string()],
+            ),
+            ExecutionErrorKind::ResourceExceeded(ResourceExceededError::TooManySteps {
+                max_steps,
+            }) => (
+                format!("Too many steps: evaluation exceeded {} steps", max_steps),
+                Some("R014"),
+                vec!["Simplify the expression or increase the step limit".to_string()],
+            ),
+            ExecutionErrorKind::ResourceExceeded(ResourceExceededError::DeadlineExceeded) => (
+                "Deadline exceeded: evaluation did not finish in time".to_string(),
+                Some("R015"),
+                vec!["Simplify the expression or allow more time".to_string()],
+            ),
             ExecutionErrorKind::Internal(InternalError::InvariantViolation { message }) => (
                 format!("Internal error: {}", message),
                 Some("R006"),
@@ -196,6 +275,18 @@ impl fmt::Display for RuntimeError {
             RuntimeError::CastError { message } => {
                 write!(f, "Cast error: {}", message)
             }
+            RuntimeError::InvalidArgument { message } => {
+                write!(f, "Invalid argument: {}", message)
+            }
+            RuntimeError::ShiftOutOfRange { amount } => {
+                write!(f, "Shift amount {} out of range", amount)
+            }
+            RuntimeError::DuplicateMapKey { key_display } => {
+                write!(f, "Duplicate map key: {}", key_display)
+            }
+            RuntimeError::AssertionFailed { message } => {
+                write!(f, "Assertion failed: {}", message)
+            }
         }
     }
 }
@@ -210,6 +301,26 @@ impl fmt::Display for ResourceExceededError {
                     depth, max_depth
                 )
             }
+            ResourceExceededError::ArrayTooLarge { len, max_len } => {
+                write!(
+                    f,
+                    "Array too large: length {} exceeds maximum of {}",
+                    len, max_len
+                )
+            }
+            ResourceExceededError::TooManyIterations { count, max_count } => {
+                write!(
+                    f,
+                    "Too many iterations: {} exceeds maximum of {}",
+                    count, max_count
+                )
+            }
+            ResourceExceededError::TooManySteps { max_steps } => {
+                write!(f, "Too many steps: evaluation exceeded {} steps", max_steps)
+            }
+            ResourceExceededError::DeadlineExceeded => {
+                write!(f, "Deadline exceeded: evaluation did not finish in time")
+            }
         }
     }
 }
@@ -1,19 +1,23 @@
 //! Core evaluation logic.
 
+use alloc::collections::BTreeSet;
 use alloc::string::ToString;
 use bumpalo::Bump;
 
 use crate::{
     Vec,
-    analyzer::typed_expr::{Expr, ExprInner, TypedExpr, TypedPattern},
+    analyzer::{
+        ArgumentCoercion, DuplicateKeyPolicy,
+        typed_expr::{Expr, ExprInner, TypedExpr, TypedPattern},
+    },
     evaluator::{
         EvaluatorOptions, ExecutionError, ExecutionErrorKind, InternalError::*,
-        ResourceExceededError::*, RuntimeError::*,
+        ResourceExceededError::*, RuntimeError::*, slice_range,
     },
     parser::{BoolOp, ComparisonOp},
     scope_stack::{self, ScopeStack},
     types::{Type, manager::TypeManager, unification::Unification},
-    values::{EvalLambda, dynamic::Value, function::FfiContext},
+    values::{EvalLambda, RandomState, StepBudget, dynamic::Value, function::FfiContext},
 };
 
 /// Evaluator for type-checked expressions.
@@ -29,6 +33,10 @@ pub struct Evaluator<'types, 'arena> {
     /// When evaluating a polymorphic lambda, this contains the unification
     /// of the lambda's parameter types with the concrete argument types.
     monomorphism: Option<Unification<'types, &'types TypeManager<'types>>>,
+    /// Random number generator shared with native functions and nested lambdas.
+    random: &'arena RandomState,
+    /// Step budget shared with native functions and nested lambdas.
+    steps: &'arena StepBudget,
 }
 
 impl<'types, 'arena> Evaluator<'types, 'arena> {
@@ -55,6 +63,12 @@ impl<'types, 'arena> Evaluator<'types, 'arena> {
             scope_stack.push(scope_stack::CompleteScope::from_sorted(bindings));
         }
 
+        let random = arena.alloc(RandomState::new(options.random_seed));
+        let steps = StepBudget::new(options.max_steps);
+        #[cfg(feature = "std")]
+        let steps = steps.with_deadline(options.deadline);
+        let steps = arena.alloc(steps);
+
         Self {
             options,
             arena,
@@ -63,6 +77,8 @@ impl<'types, 'arena> Evaluator<'types, 'arena> {
             scope_stack,
             depth: 0,
             monomorphism: None,
+            random,
+            steps,
         }
     }
 
@@ -85,6 +101,22 @@ impl<'types, 'arena> Evaluator<'types, 'arena> {
         self.monomorphism = Some(unification);
     }
 
+    /// Draw random numbers from an existing generator.
+    ///
+    /// Lambdas use this to continue the sequence of the execution that
+    /// called them, instead of starting over from the seed.
+    pub fn set_random(&mut self, random: &'arena RandomState) {
+        self.random = random;
+    }
+
+    /// Count steps against an existing budget.
+    ///
+    /// Lambdas use this so that their steps count against the limit of the
+    /// execution that called them.
+    pub fn set_steps(&mut self, steps: &'arena StepBudget) {
+        self.steps = steps;
+    }
+
     /// Resolve a type by applying monomorphization if present.
     /// This replaces type variables with concrete types when evaluating
     /// polymorphic lambda bodies.
@@ -125,6 +157,10 @@ impl<'types, 'arena> Evaluator<'types, 'arena> {
         &mut self,
         expr: &'arena Expr<'types, 'arena>,
     ) -> Result<Value<'types, 'arena>, ExecutionError> {
+        if let Err(error) = self.steps.take() {
+            return self.error(expr, error.into());
+        }
+
         // Check depth before recursing
         if self.depth >= self.options.max_depth {
             return self.error(
@@ -173,7 +209,12 @@ impl<'types, 'arena> Evaluator<'types, 'arena> {
                 }
             }
 
-            ExprInner::Binary { op, left, right } => {
+            ExprInner::Binary {
+                op,
+                left,
+                right,
+                division,
+            } => {
                 use crate::types::Type;
 
                 // Recursively evaluate operands (direct call to eval_expr, not eval)
@@ -185,7 +226,7 @@ impl<'types, 'arena> Evaluator<'types, 'arena> {
                     Type::Int => {
                         let l = left_val.as_int().expect("Type-checked as Int");
                         let r = right_val.as_int().expect("Type-checked as Int");
-                        let result = super::operators::eval_binary_int(*op, l, r)
+                        let result = super::operators::eval_binary_int(*op, *division, l, r)
                             .map_err(|e| self.add_error_context(expr, e))?;
                         Ok(Value::int(self.type_manager, result))
                     }
@@ -310,6 +351,9 @@ impl<'types, 'arena> Evaluator<'types, 'arena> {
                             let r = right_val.as_bytes().expect("Type-checked as Bytes");
                             super::operators::eval_comparison_bytes(*op, l, r)
                         }
+                        Type::Option(_) if !matches!(op, ComparisonOp::Eq | ComparisonOp::Neq) => {
+                            super::operators::eval_comparison_option(*op, left_val, right_val)
+                        }
                         _ => {
                             // For other types, we only support equality operators
                             match op {
@@ -535,6 +579,35 @@ impl<'types, 'arena> Evaluator<'types, 'arena> {
                             )
                         }
                     }
+                // Handle string indexing, by character
+                } else if let Ok(string) = indexed_value.as_str() {
+                    let original_index: i64 = index_value
+                        .as_int()
+                        .expect("Index with non-integer - analyzer should have caught this");
+                    let len = string.chars().count();
+
+                    let index = usize::try_from(if original_index < 0 {
+                        original_index + len as i64
+                    } else {
+                        original_index
+                    });
+                    let Some(ch) = index.ok().and_then(|index| string.chars().nth(index)) else {
+                        return self.error(
+                            expr,
+                            IndexOutOfBounds {
+                                index: original_index,
+                                len,
+                            }
+                            .into(),
+                        );
+                    };
+
+                    let mut buffer = [0; 4];
+                    Ok(Value::str(
+                        self.arena,
+                        self.type_manager.str(),
+                        ch.encode_utf8(&mut buffer),
+                    ))
                 } else {
                     unreachable!(
                         "Index operation on non-indexable type - analyzer should have caught this"
@@ -542,6 +615,45 @@ impl<'types, 'arena> Evaluator<'types, 'arena> {
                 }
             }
 
+            ExprInner::Slice { value, start, end } => {
+                let sliced_value = self.eval_expr(value)?;
+                let mut eval_bound = |bound: Option<&'arena Expr<'types, 'arena>>, default: i64| {
+                    let Some(bound) = bound else {
+                        return Ok(default);
+                    };
+                    Ok(self
+                        .eval_expr(bound)?
+                        .as_int()
+                        .expect("Slice bound with non-integer - analyzer should have caught this"))
+                };
+                // Omitted bounds span the whole value; clamping handles the end.
+                let start = eval_bound(*start, 0)?;
+                let end = eval_bound(*end, i64::MAX)?;
+
+                if let Ok(array) = sliced_value.as_array() {
+                    let range = slice_range(start, end, array.len());
+                    let elements: Vec<_> =
+                        array.iter().skip(range.start).take(range.len()).collect();
+                    Ok(Value::array(self.arena, sliced_value.ty, &elements)
+                        .expect("Array slice construction failed"))
+                } else if let Ok(string) = sliced_value.as_str() {
+                    // Bounds count characters, not bytes
+                    let range = slice_range(start, end, string.chars().count());
+                    let byte_offset = |char_index: usize| {
+                        string
+                            .char_indices()
+                            .nth(char_index)
+                            .map_or(string.len(), |(offset, _)| offset)
+                    };
+                    let slice = &string[byte_offset(range.start)..byte_offset(range.end)];
+                    Ok(Value::str(self.arena, sliced_value.ty, slice))
+                } else {
+                    unreachable!(
+                        "Slice operation on non-sliceable type - analyzer should have caught this"
+                    )
+                }
+            }
+
             ExprInner::FormatStr { strs, exprs } => {
                 // Invariant: strs.len() == exprs.len() + 1
                 // Format: strs[0] + value(exprs[0]) + strs[1] + value(exprs[1]) + ... + strs[n]
@@ -582,6 +694,15 @@ impl<'types, 'arena> Evaluator<'types, 'arena> {
                 }
             }
 
+            ExprInner::Coalesce { option, default } => {
+                // The default is only evaluated when the option is none
+                let option = self.eval_expr(option)?;
+                match option.as_option().expect("Type-checked as Option") {
+                    Some(value) => Ok(value),
+                    None => self.eval_expr(default),
+                }
+            }
+
             ExprInner::Option { inner } => {
                 // Resolve type (replaces type variables if evaluating polymorphic lambda)
                 let resolved_ty = self.resolve_type(expr.0);
@@ -630,7 +751,11 @@ impl<'types, 'arena> Evaluator<'types, 'arena> {
                 crate::casting::perform_cast(self.arena, value, resolved_ty, self.type_manager)
                     .map_err(|e| self.add_error_context(expr, e.into()))
             }
-            ExprInner::Call { callable, args } => {
+            ExprInner::Call {
+                callable,
+                args,
+                coercion,
+            } => {
                 // Evaluate the callable expression
                 let func_value = self.eval_expr(callable)?;
 
@@ -640,15 +765,42 @@ impl<'types, 'arena> Evaluator<'types, 'arena> {
                     .expect("Type checker guarantees callable is a Function");
 
                 // Evaluate all arguments
-                let arg_values: alloc::vec::Vec<Value<'types, 'arena>> = args
+                let mut arg_values: alloc::vec::Vec<Value<'types, 'arena>> = args
                     .iter()
                     .map(|arg| self.eval_expr(arg))
                     .collect::<Result<_, _>>()?;
 
+                // Convert arguments the analyzer accepted under a lenient policy
+                if *coercion != ArgumentCoercion::Strict {
+                    use crate::types::traits::{TypeKind, TypeView};
+
+                    let TypeKind::Function { params, .. } = self.resolve_type(callable.0).view()
+                    else {
+                        unreachable!("Type checker guarantees callable is a Function");
+                    };
+                    for (value, param_ty) in arg_values.iter_mut().zip(params) {
+                        let param_ty = self.resolve_type(param_ty);
+                        if coercion.allows(value.ty, param_ty) {
+                            *value = crate::casting::perform_cast(
+                                self.arena,
+                                *value,
+                                param_ty,
+                                self.type_manager,
+                            )
+                            .map_err(|e| self.add_error_context(expr, e.into()))?;
+                        }
+                    }
+                }
+
                 // Call the function via trait method
                 // SAFETY: The type checker guarantees the function type matches,
                 // arguments have correct types, and arity is correct.
-                let ctx = FfiContext::new(self.arena, self.type_manager);
+                let ctx = FfiContext::for_execution(
+                    self.arena,
+                    self.type_manager,
+                    self.random,
+                    self.steps,
+                );
                 unsafe { func.call_unchecked(&ctx, &arg_values) }
             }
             ExprInner::Lambda {
@@ -674,9 +826,13 @@ impl<'types, 'arena> Evaluator<'types, 'arena> {
                     ann: self.expr.ann,
                     // Evaluator doesn't need instantiation info (just for error reporting)
                     lambda_instantiations: hashbrown::HashMap::new_in(self.arena),
+                    constraints: &[],
                 });
 
-                let lambda = EvalLambda::new(expr.0, *params, body_typed, captures_slice);
+                let mut lambda = EvalLambda::new(expr.0, params, body_typed, captures_slice);
+                if let Some(info) = self.expr.lambda_instantiations.get(&expr.as_ptr()) {
+                    lambda = lambda.with_instantiations(&info.substitutions);
+                }
 
                 // Value::function returns Result, but should never fail because
                 // the type checker guarantees expr.0 is a Function type
@@ -684,12 +840,26 @@ impl<'types, 'arena> Evaluator<'types, 'arena> {
                     .expect("Type checker guarantees Function type");
                 Ok(fun)
             }
-            ExprInner::Map { elements } => {
+            ExprInner::Map {
+                elements,
+                duplicate_keys,
+            } => {
                 // Evaluate all key-value pairs
                 let mut pair_values: Vec<(Value<'types, 'arena>, Value<'types, 'arena>)> =
                     Vec::new();
+                let mut seen_keys = BTreeSet::new();
                 for (key_expr, value_expr) in elements.iter() {
                     let key_value = self.eval_expr(key_expr)?;
+                    if *duplicate_keys == DuplicateKeyPolicy::Error && !seen_keys.insert(key_value)
+                    {
+                        return self.error(
+                            key_expr,
+                            DuplicateMapKey {
+                                key_display: alloc::format!("{}", key_value),
+                            }
+                            .into(),
+                        );
+                    }
                     let value_value = self.eval_expr(value_expr)?;
                     pair_values.push((key_value, value_value));
                 }
@@ -789,6 +959,14 @@ impl<'types, 'arena> Evaluator<'types, 'arena> {
                     Some(_) => Ok(None), // Value is Some, pattern is None - no match
                 }
             }
+            TypedPattern::As { pattern, name } => {
+                // Binding pattern matches like its inner pattern and also binds the value
+                let bindings = self.match_pattern(pattern, value)?;
+                Ok(bindings.map(|mut bindings| {
+                    bindings.push((*name, value));
+                    bindings
+                }))
+            }
         }
     }
 }
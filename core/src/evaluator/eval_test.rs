@@ -99,6 +99,7 @@ impl<'a> Runner<'a> {
         Evaluator::new(
             EvaluatorOptions {
                 max_depth: max_stack_depth,
+                ..Default::default()
             },
             self.arena,
             self.type_mgr,
@@ -263,6 +264,83 @@ fn test_int_division_i64_min_overflow() {
     ));
 }
 
+#[test]
+fn test_int_modulo() {
+    let arena = Bump::new();
+    let result = Runner::new(&arena).run("17 % 5", &[], &[]).unwrap();
+    assert_eq!(result.as_int().unwrap(), 2);
+}
+
+#[test]
+fn test_int_modulo_euclidean_negative() {
+    // Euclidean modulo: the result is never negative, matching `/`
+    // because -7 = -3 * 3 + 2 and 7 = -2 * (-3) + 1
+    let arena = Bump::new();
+    let result = Runner::new(&arena).run("-7 % 3", &[], &[]).unwrap();
+    assert_eq!(result.as_int().unwrap(), 2);
+    let result = Runner::new(&arena).run("7 % -3", &[], &[]).unwrap();
+    assert_eq!(result.as_int().unwrap(), 1);
+}
+
+#[test]
+fn test_int_modulo_by_zero() {
+    let arena = Bump::new();
+    let result = Runner::new(&arena).run("10 % 0", &[], &[]);
+    assert!(matches!(
+        result,
+        Err(ExecutionError {
+            kind: ExecutionErrorKind::Runtime(RuntimeError::DivisionByZero {}),
+            ..
+        })
+    ));
+}
+
+#[test]
+fn test_int_modulo_i64_min_overflow() {
+    let arena = Bump::new();
+    let result = Runner::new(&arena).run("-9223372036854775808 % -1", &[], &[]);
+    assert!(matches!(
+        result,
+        Err(ExecutionError {
+            kind: ExecutionErrorKind::Runtime(RuntimeError::IntegerOverflow {}),
+            ..
+        })
+    ));
+}
+
+#[test]
+fn test_int_bitwise_operators() {
+    let arena = Bump::new();
+    for (source, expected) in [
+        ("12 & 10", 8),
+        ("12 | 10", 14),
+        ("12 xor 10", 6),
+        ("1 << 10", 1024),
+        ("-16 >> 2", -4),
+    ] {
+        let result = Runner::new(&arena).run(source, &[], &[]).unwrap();
+        assert_eq!(result.as_int().unwrap(), expected, "{}", source);
+    }
+}
+
+#[test]
+fn test_int_shift_out_of_range() {
+    let arena = Bump::new();
+    let result = Runner::new(&arena).run("1 << 64", &[], &[]);
+    assert!(matches!(
+        result,
+        Err(ExecutionError {
+            kind: ExecutionErrorKind::Runtime(RuntimeError::ShiftOutOfRange { amount: 64 }),
+            ..
+        })
+    ));
+
+    let result = Runner::new(&arena)
+        .run("(1 >> -1) otherwise 0", &[], &[])
+        .unwrap();
+    assert_eq!(result.as_int().unwrap(), 0);
+}
+
 #[test]
 fn test_int_wrapping_overflow_add() {
     let arena = Bump::new();
@@ -321,6 +399,20 @@ fn test_float_division_by_zero() {
     assert!(result.as_float().unwrap().is_infinite());
 }
 
+#[test]
+fn test_float_modulo() {
+    let arena = Bump::new();
+    let result = Runner::new(&arena).run("7.5 % 2.0", &[], &[]).unwrap();
+    assert_eq!(result.as_float().unwrap(), 1.5);
+}
+
+#[test]
+fn test_float_modulo_by_zero() {
+    let arena = Bump::new();
+    let result = Runner::new(&arena).run("10.0 % 0.0", &[], &[]).unwrap();
+    assert!(result.as_float().unwrap().is_nan());
+}
+
 #[test]
 fn test_float_power() {
     let arena = Bump::new();
@@ -537,7 +629,10 @@ fn test_custom_stack_depth_limit() {
 
     // With custom limit of 100, this should succeed
     let result = Evaluator::new(
-        EvaluatorOptions { max_depth: 100 },
+        EvaluatorOptions {
+            max_depth: 100,
+            ..Default::default()
+        },
         &arena,
         type_manager,
         &typed,
@@ -549,7 +644,10 @@ fn test_custom_stack_depth_limit() {
 
     // But with limit of 40, it should fail
     let result = Evaluator::new(
-        EvaluatorOptions { max_depth: 40 },
+        EvaluatorOptions {
+            max_depth: 40,
+            ..Default::default()
+        },
         &arena,
         type_manager,
         &typed,
@@ -1359,6 +1457,68 @@ fn test_index_out_of_bounds_negative() {
     );
 }
 
+#[test]
+fn test_index_string() {
+    let arena = Bump::new();
+    let result = Runner::new(&arena).run(r#""héllo"[1]"#, &[], &[]).unwrap();
+    assert_eq!(result.as_str().unwrap(), "é");
+
+    let result = Runner::new(&arena).run(r#""abc"[-1]"#, &[], &[]).unwrap();
+    assert_eq!(result.as_str().unwrap(), "c");
+}
+
+#[test]
+fn test_index_string_out_of_bounds() {
+    let arena = Bump::new();
+    let result = Runner::new(&arena).run(r#""abc"[10]"#, &[], &[]);
+    assert_eq!(
+        &result.unwrap_err().kind,
+        &ExecutionErrorKind::Runtime(RuntimeError::IndexOutOfBounds { index: 10, len: 3 })
+    );
+
+    let result = Runner::new(&arena)
+        .run(r#""abc"[10] otherwise "x""#, &[], &[])
+        .unwrap();
+    assert_eq!(result.as_str().unwrap(), "x");
+}
+
+#[test]
+fn test_slice_array() {
+    let arena = Bump::new();
+    let slice = |source| {
+        Runner::new(&arena)
+            .run(source, &[], &[])
+            .unwrap()
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|element| element.as_int().unwrap())
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(slice("[1, 2, 3, 4][1:3]"), [2, 3]);
+    assert_eq!(slice("[1, 2, 3][-2:]"), [2, 3]);
+    assert_eq!(slice("[1, 2, 3][:1]"), [1]);
+    assert_eq!(slice("[1, 2, 3][1:100]"), [2, 3]);
+    assert!(slice("[1, 2, 3][2:1]").is_empty());
+}
+
+#[test]
+fn test_slice_string() {
+    let arena = Bump::new();
+    let result = Runner::new(&arena)
+        .run(r#""héllo"[1:4]"#, &[], &[])
+        .unwrap();
+    assert_eq!(result.as_str().unwrap(), "éll");
+
+    let result = Runner::new(&arena)
+        .run(r#""héllo"[-2:]"#, &[], &[])
+        .unwrap();
+    assert_eq!(result.as_str().unwrap(), "lo");
+
+    let result = Runner::new(&arena).run(r#""abc"[5:]"#, &[], &[]).unwrap();
+    assert_eq!(result.as_str().unwrap(), "");
+}
+
 #[test]
 fn test_index_nested_array() {
     let arena = Bump::new();
@@ -1468,6 +1628,22 @@ fn test_map_index_multiple_key_types() {
     assert_eq!(array.get(1).unwrap().as_str().unwrap(), "uno");
 }
 
+#[test]
+fn test_field_access_multiple_record_types() {
+    let arena = Bump::new();
+    let result = Runner::new(&arena)
+        .run(
+            r#"[f({x = 1, y = "one"}), f({w = 0.5, x = 2})] where { f = (r) => r.x }"#,
+            &[],
+            &[],
+        )
+        .unwrap();
+    let array = result.as_array().unwrap();
+    assert_eq!(array.len(), 2);
+    assert_eq!(array.get(0).unwrap().as_int().unwrap(), 1);
+    assert_eq!(array.get(1).unwrap().as_int().unwrap(), 2);
+}
+
 #[test]
 fn test_polymorphic_lambda_array_construction() {
     // Array construction in polymorphic lambda body with monomorphization
@@ -1706,6 +1882,17 @@ fn test_otherwise_no_error() {
     assert_eq!(result.as_int().unwrap(), 5);
 }
 
+#[test]
+fn test_otherwise_modulo_by_zero() {
+    let arena = Bump::new();
+    let result = Runner::new(&arena)
+        .run("(10 % 0) otherwise -1", &[], &[])
+        .unwrap();
+
+    // Primary fails (modulo by zero), return fallback
+    assert_eq!(result.as_int().unwrap(), -1);
+}
+
 #[test]
 fn test_otherwise_division_by_zero() {
     let arena = Bump::new();
@@ -1860,7 +2047,10 @@ fn test_otherwise_does_not_catch_stack_overflow() {
 
     // Use a very small depth limit to trigger stack overflow
     let result = Evaluator::new(
-        EvaluatorOptions { max_depth: 10 },
+        EvaluatorOptions {
+            max_depth: 10,
+            ..Default::default()
+        },
         &arena,
         type_manager,
         &typed,
@@ -1882,6 +2072,68 @@ fn test_otherwise_does_not_catch_stack_overflow() {
     }
 }
 
+// ================================
+// Coalesce Operator Tests
+// ================================
+
+#[test]
+fn test_coalesce() {
+    let arena = Bump::new();
+    let runner = Runner::new(&arena);
+
+    let result = runner.run("some 5 ?? 0", &[], &[]).unwrap();
+    assert_eq!(result.as_int().unwrap(), 5);
+
+    let result = runner.run("none ?? 0", &[], &[]).unwrap();
+    assert_eq!(result.as_int().unwrap(), 0);
+
+    // Right-associative: the first option holding a value wins
+    let result = runner.run("none ?? some 2 ?? 3", &[], &[]).unwrap();
+    assert_eq!(result.as_int().unwrap(), 2);
+}
+
+#[test]
+fn test_coalesce_default_is_lazy() {
+    let arena = Bump::new();
+    let result = Runner::new(&arena)
+        .run("some 1 ?? 1 / 0", &[], &[])
+        .unwrap();
+    assert_eq!(result.as_int().unwrap(), 1);
+}
+
+#[test]
+fn test_coalesce_does_not_catch_errors() {
+    let arena = Bump::new();
+    let runner = Runner::new(&arena);
+
+    // Unlike `otherwise`, `??` only handles none
+    let result = runner.run("some (1 / 0) ?? 0", &[], &[]);
+    assert!(result.is_err());
+
+    let result = runner
+        .run("some (1 / 0) ?? 0 otherwise -1", &[], &[])
+        .unwrap();
+    assert_eq!(result.as_int().unwrap(), -1);
+}
+
+#[test]
+fn test_option_ordering() {
+    let arena = Bump::new();
+    let runner = Runner::new(&arena);
+
+    let cases = [
+        ("none < some 0", true),
+        ("some 1 < some 2", true),
+        ("some 2 <= some 1", false),
+        ("none >= none", true),
+        ("some (some 1) > some none", true),
+    ];
+    for (source, expected) in cases {
+        let result = runner.run(source, &[], &[]).unwrap();
+        assert_eq!(result.as_bool().unwrap(), expected, "{}", source);
+    }
+}
+
 // ============================================================================
 // Cast Tests
 // ============================================================================
@@ -3009,6 +3261,19 @@ fn test_match_literal_int() {
     assert_eq!(result.as_str().unwrap(), "five");
 }
 
+#[test]
+fn test_match_as_pattern() {
+    let arena = Bump::new();
+    let result = Runner::new(&arena)
+        .run(
+            "some 5 match { some 1 as one -> 0, some x as whole -> x + (whole ?? 0), none -> 0 }",
+            &[],
+            &[],
+        )
+        .unwrap();
+    assert_eq!(result.as_int().unwrap(), 10);
+}
+
 #[test]
 fn test_match_literal_bool_true() {
     let arena = Bump::new();
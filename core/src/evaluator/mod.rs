@@ -41,6 +41,8 @@ mod operators;
 #[cfg(test)]
 mod eval_test;
 
+use crate::values::DEFAULT_RANDOM_SEED;
+
 pub use error::{
     ExecutionError, ExecutionErrorKind, InternalError, ResourceExceededError, RuntimeError,
 };
@@ -49,12 +51,26 @@ pub use error::{
 pub struct EvaluatorOptions {
     /// Maximum evaluation stack depth (for recursion protection).
     pub max_depth: usize,
+    /// Seed for the random number generator exposed to native functions.
+    pub random_seed: u64,
+    /// Maximum number of evaluated expression nodes, or `None` for no limit.
+    pub max_steps: Option<u64>,
+    /// Point in time after which evaluation fails, or `None` for no deadline.
+    #[cfg(feature = "std")]
+    pub deadline: Option<std::time::Instant>,
 }
 
 impl Default for EvaluatorOptions {
     fn default() -> Self {
-        Self { max_depth: 1000 }
+        Self {
+            max_depth: 1000,
+            random_seed: DEFAULT_RANDOM_SEED,
+            max_steps: None,
+            #[cfg(feature = "std")]
+            deadline: None,
+        }
     }
 }
 
 pub use eval::Evaluator;
+pub(crate) use operators::{eval_comparison_option, slice_range};
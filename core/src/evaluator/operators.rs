@@ -1,16 +1,39 @@
 //! Binary and unary operator implementations.
 
 use crate::{
+    analyzer::IntegerDivision,
     evaluator::{ExecutionErrorKind, RuntimeError::*},
     parser::{BinaryOp, ComparisonOp, UnaryOp},
+    types::Type,
+    values::dynamic::Value,
 };
 
+/// Resolve the bounds of a slice `[start:end]` of a sequence of length `len`.
+///
+/// Negative bounds count from the end, like negative indices. Bounds are
+/// clamped to the sequence rather than reported as errors, and an end before
+/// the start yields an empty range.
+pub(crate) fn slice_range(start: i64, end: i64, len: usize) -> core::ops::Range<usize> {
+    let resolve = |bound: i64| {
+        let bound = if bound < 0 {
+            bound.saturating_add(len as i64)
+        } else {
+            bound
+        };
+        bound.clamp(0, len as i64) as usize
+    };
+    let start = resolve(start);
+    start..resolve(end).max(start)
+}
+
 /// Evaluate a binary operation on two integers.
 ///
 /// Uses wrapping arithmetic to prevent panics on overflow.
-/// Division by zero returns an error.
+/// Division by zero and out-of-range shift amounts return an error.
+/// `/` and `%` round as set by `division`.
 pub(super) fn eval_binary_int(
     op: BinaryOp,
+    division: IntegerDivision,
     left: i64,
     right: i64,
 ) -> Result<i64, ExecutionErrorKind> {
@@ -24,7 +47,16 @@ pub(super) fn eval_binary_int(
             } else if left == i64::MIN && right == -1 {
                 Err(IntegerOverflow {}.into())
             } else {
-                Ok(left.div_euclid(right))
+                Ok(division.quotient(left, right))
+            }
+        }
+        BinaryOp::Mod => {
+            if right == 0 {
+                Err(DivisionByZero {}.into())
+            } else if left == i64::MIN && right == -1 {
+                Err(IntegerOverflow {}.into())
+            } else {
+                Ok(division.remainder(left, right))
             }
         }
         BinaryOp::Pow => {
@@ -41,6 +73,21 @@ pub(super) fn eval_binary_int(
                 Ok(left.wrapping_pow(right as u32))
             }
         }
+        BinaryOp::BitwiseAnd => Ok(left & right),
+        BinaryOp::BitwiseOr => Ok(left | right),
+        BinaryOp::BitwiseXor => Ok(left ^ right),
+        BinaryOp::ShiftLeft => Ok(left << shift_amount(right)?),
+        // Arithmetic shift: the sign bit is preserved
+        BinaryOp::ShiftRight => Ok(left >> shift_amount(right)?),
+    }
+}
+
+/// Validate the right operand of a shift, which must be in `[0, 64)`.
+fn shift_amount(amount: i64) -> Result<u32, ExecutionErrorKind> {
+    if (0..i64::BITS as i64).contains(&amount) {
+        Ok(amount as u32)
+    } else {
+        Err(ShiftOutOfRange { amount }.into())
     }
 }
 
@@ -53,7 +100,16 @@ pub(super) fn eval_binary_float(op: BinaryOp, left: f64, right: f64) -> f64 {
         BinaryOp::Sub => left - right,
         BinaryOp::Mul => left * right,
         BinaryOp::Div => left / right, // Division by zero produces inf
+        BinaryOp::Mod => left % right, // Modulo by zero produces NaN
         BinaryOp::Pow => left.powf(right),
+        BinaryOp::BitwiseAnd
+        | BinaryOp::BitwiseOr
+        | BinaryOp::BitwiseXor
+        | BinaryOp::ShiftLeft
+        | BinaryOp::ShiftRight => {
+            // Type checker should have caught this
+            unreachable!("Bitwise operator not valid for float")
+        }
     }
 }
 
@@ -163,6 +219,47 @@ pub(super) fn eval_comparison_bytes(op: ComparisonOp, left: &[u8], right: &[u8])
     }
 }
 
+/// Evaluate an ordering comparison (`<`, `>`, `<=`, `>=`) on two options.
+///
+/// `none` is less than any `some`, and two `some`s compare by their
+/// contents, which the type checker guarantees are ordered too.
+pub(crate) fn eval_comparison_option(op: ComparisonOp, left: Value, right: Value) -> bool {
+    let left = left.as_option().expect("Type-checked as Option");
+    let right = right.as_option().expect("Type-checked as Option");
+    match (left, right) {
+        (Some(left), Some(right)) => eval_ordering(op, left, right),
+        (left, right) => eval_comparison_int(op, left.is_some().into(), right.is_some().into()),
+    }
+}
+
+/// Evaluate an ordering comparison on two values of an ordered type.
+fn eval_ordering(op: ComparisonOp, left: Value, right: Value) -> bool {
+    match left.ty {
+        Type::Int => eval_comparison_int(
+            op,
+            left.as_int().expect("Type-checked as Int"),
+            right.as_int().expect("Type-checked as Int"),
+        ),
+        Type::Float => eval_comparison_float(
+            op,
+            left.as_float().expect("Type-checked as Float"),
+            right.as_float().expect("Type-checked as Float"),
+        ),
+        Type::Str => eval_comparison_string(
+            op,
+            left.as_str().expect("Type-checked as Str"),
+            right.as_str().expect("Type-checked as Str"),
+        ),
+        Type::Bytes => eval_comparison_bytes(
+            op,
+            left.as_bytes().expect("Type-checked as Bytes"),
+            right.as_bytes().expect("Type-checked as Bytes"),
+        ),
+        Type::Option(_) => eval_comparison_option(op, left, right),
+        _ => unreachable!("Ordering comparison on invalid type in type-checked expression"),
+    }
+}
+
 /// Evaluate a containment operation on strings (substring check).
 ///
 /// Returns true if the needle (left) is found within the haystack (right).
@@ -204,33 +301,38 @@ mod tests {
     use super::*;
     use crate::evaluator::RuntimeError;
 
+    /// `eval_binary_int` with the default rounding for `/` and `%`.
+    fn eval_int(op: BinaryOp, left: i64, right: i64) -> Result<i64, ExecutionErrorKind> {
+        eval_binary_int(op, IntegerDivision::default(), left, right)
+    }
+
     #[test]
     fn test_int_add() {
-        assert_eq!(eval_binary_int(BinaryOp::Add, 2, 3).unwrap(), 5);
-        assert_eq!(eval_binary_int(BinaryOp::Add, -5, 3).unwrap(), -2);
+        assert_eq!(eval_int(BinaryOp::Add, 2, 3).unwrap(), 5);
+        assert_eq!(eval_int(BinaryOp::Add, -5, 3).unwrap(), -2);
     }
 
     #[test]
     fn test_int_sub() {
-        assert_eq!(eval_binary_int(BinaryOp::Sub, 10, 4).unwrap(), 6);
-        assert_eq!(eval_binary_int(BinaryOp::Sub, 3, 10).unwrap(), -7);
+        assert_eq!(eval_int(BinaryOp::Sub, 10, 4).unwrap(), 6);
+        assert_eq!(eval_int(BinaryOp::Sub, 3, 10).unwrap(), -7);
     }
 
     #[test]
     fn test_int_mul() {
-        assert_eq!(eval_binary_int(BinaryOp::Mul, 3, 4).unwrap(), 12);
-        assert_eq!(eval_binary_int(BinaryOp::Mul, -2, 5).unwrap(), -10);
+        assert_eq!(eval_int(BinaryOp::Mul, 3, 4).unwrap(), 12);
+        assert_eq!(eval_int(BinaryOp::Mul, -2, 5).unwrap(), -10);
     }
 
     #[test]
     fn test_int_div() {
-        assert_eq!(eval_binary_int(BinaryOp::Div, 10, 2).unwrap(), 5);
-        assert_eq!(eval_binary_int(BinaryOp::Div, 7, 3).unwrap(), 2);
+        assert_eq!(eval_int(BinaryOp::Div, 10, 2).unwrap(), 5);
+        assert_eq!(eval_int(BinaryOp::Div, 7, 3).unwrap(), 2);
     }
 
     #[test]
     fn test_int_div_by_zero() {
-        let result = eval_binary_int(BinaryOp::Div, 10, 0);
+        let result = eval_int(BinaryOp::Div, 10, 0);
         assert!(matches!(
             result.as_ref().map(|_| ()),
             Err(crate::evaluator::ExecutionErrorKind::Runtime(
@@ -239,26 +341,105 @@ mod tests {
         ));
     }
 
+    #[test]
+    fn test_int_mod() {
+        assert_eq!(eval_int(BinaryOp::Mod, 17, 5).unwrap(), 2);
+        assert_eq!(eval_int(BinaryOp::Mod, -7, 3).unwrap(), 2);
+    }
+
+    #[test]
+    fn test_int_division_rounding_modes() {
+        let divide = |division, a, b| {
+            (
+                eval_binary_int(BinaryOp::Div, division, a, b).unwrap(),
+                eval_binary_int(BinaryOp::Mod, division, a, b).unwrap(),
+            )
+        };
+        assert_eq!(divide(IntegerDivision::Truncated, -7, 2), (-3, -1));
+        assert_eq!(divide(IntegerDivision::Floored, -7, 2), (-4, 1));
+        assert_eq!(divide(IntegerDivision::Floored, 7, -2), (-4, -1));
+        assert_eq!(divide(IntegerDivision::Floored, -7, -2), (3, -1));
+        assert_eq!(divide(IntegerDivision::Floored, -8, 2), (-4, 0));
+
+        // Every mode satisfies the division identity.
+        for division in [
+            IntegerDivision::Euclidean,
+            IntegerDivision::Truncated,
+            IntegerDivision::Floored,
+        ] {
+            for a in [-9, -7, -1, 0, 1, 7, 9, i64::MAX, i64::MIN] {
+                for b in [-3, -2, -1, 1, 2, 3] {
+                    if a == i64::MIN && b == -1 {
+                        continue;
+                    }
+                    let (quotient, remainder) = divide(division, a, b);
+                    assert_eq!(
+                        quotient.wrapping_mul(b).wrapping_add(remainder),
+                        a,
+                        "{:?}: {} / {}",
+                        division,
+                        a,
+                        b
+                    );
+                    assert!(remainder.abs() < b.abs());
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_int_mod_by_zero() {
+        let result = eval_int(BinaryOp::Mod, 10, 0);
+        assert!(matches!(
+            result.as_ref().map(|_| ()),
+            Err(crate::evaluator::ExecutionErrorKind::Runtime(
+                RuntimeError::DivisionByZero {}
+            ))
+        ));
+    }
+
+    #[test]
+    fn test_int_bitwise() {
+        assert_eq!(eval_int(BinaryOp::BitwiseAnd, 0b1100, 0b1010).unwrap(), 0b1000);
+        assert_eq!(eval_int(BinaryOp::BitwiseOr, 0b1100, 0b1010).unwrap(), 0b1110);
+        assert_eq!(eval_int(BinaryOp::BitwiseXor, 0b1100, 0b1010).unwrap(), 0b0110);
+        assert_eq!(eval_int(BinaryOp::ShiftLeft, 1, 63).unwrap(), i64::MIN);
+        assert_eq!(eval_int(BinaryOp::ShiftRight, -16, 2).unwrap(), -4);
+    }
+
+    #[test]
+    fn test_int_shift_out_of_range() {
+        for amount in [-1, 64, i64::MAX] {
+            let result = eval_int(BinaryOp::ShiftLeft, 1, amount);
+            assert!(matches!(
+                result,
+                Err(crate::evaluator::ExecutionErrorKind::Runtime(
+                    RuntimeError::ShiftOutOfRange { .. }
+                ))
+            ));
+        }
+    }
+
     #[test]
     fn test_int_pow() {
-        assert_eq!(eval_binary_int(BinaryOp::Pow, 2, 10).unwrap(), 1024);
-        assert_eq!(eval_binary_int(BinaryOp::Pow, 3, 3).unwrap(), 27);
-        assert_eq!(eval_binary_int(BinaryOp::Pow, 5, 0).unwrap(), 1);
+        assert_eq!(eval_int(BinaryOp::Pow, 2, 10).unwrap(), 1024);
+        assert_eq!(eval_int(BinaryOp::Pow, 3, 3).unwrap(), 27);
+        assert_eq!(eval_int(BinaryOp::Pow, 5, 0).unwrap(), 1);
     }
 
     #[test]
     fn test_int_pow_negative_exponent() {
         // Negative exponents for integers return 0 (floor semantics)
-        assert_eq!(eval_binary_int(BinaryOp::Pow, 2, -1).unwrap(), 0);
+        assert_eq!(eval_int(BinaryOp::Pow, 2, -1).unwrap(), 0);
     }
 
     #[test]
     fn test_int_wrapping_overflow() {
         // Test that we wrap on overflow rather than panic
-        let result = eval_binary_int(BinaryOp::Add, i64::MAX, 1).unwrap();
+        let result = eval_int(BinaryOp::Add, i64::MAX, 1).unwrap();
         assert_eq!(result, i64::MIN);
 
-        let result = eval_binary_int(BinaryOp::Mul, i64::MAX, 2).unwrap();
+        let result = eval_int(BinaryOp::Mul, i64::MAX, 2).unwrap();
         assert_eq!(result, -2);
     }
 
@@ -280,6 +461,18 @@ mod tests {
         assert!(result.is_infinite() && result.is_sign_positive());
     }
 
+    #[test]
+    fn test_float_mod() {
+        assert_eq!(eval_binary_float(BinaryOp::Mod, 7.5, 2.0), 1.5);
+        assert_eq!(eval_binary_float(BinaryOp::Mod, -7.5, 2.0), -1.5);
+    }
+
+    #[test]
+    fn test_float_mod_by_zero() {
+        // Float modulo by zero produces NaN (IEEE 754)
+        assert!(eval_binary_float(BinaryOp::Mod, 10.0, 0.0).is_nan());
+    }
+
     #[test]
     fn test_float_pow() {
         assert_eq!(eval_binary_float(BinaryOp::Pow, 2.0, 3.0), 8.0);
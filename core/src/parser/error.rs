@@ -23,6 +23,8 @@ pub enum ParseErrorKind {
     UnclosedDelimiter { delimiter: char },
     /// Invalid number literal
     InvalidNumber { text: String },
+    /// Digit separator (`_`) not placed between two digits
+    InvalidDigitSeparator { text: String },
     /// Maximum nesting depth exceeded
     MaxDepthExceeded { depth: usize, max_depth: usize },
     /// Other parse errors (catch-all for Pest errors we don't specifically handle)
@@ -60,6 +62,11 @@ impl ParseError {
                 Some("P003"),
                 vec!["Check the number format".to_string()],
             ),
+            ParseErrorKind::InvalidDigitSeparator { text, .. } => (
+                format!("Invalid digit separator in number literal '{}'", text),
+                Some("P005"),
+                vec!["Place each '_' between two digits, as in 1_000".to_string()],
+            ),
             ParseErrorKind::MaxDepthExceeded { max_depth, .. } => (
                 format!(
                     "Expression nesting depth exceeds maximum of {} levels",
@@ -112,6 +119,19 @@ pub fn convert_pest_error(err: pest::error::Error<Rule>, source: &str) -> ParseE
         pest::error::InputLocation::Span((start, end)) => Span(start..end),
     };
 
+    let malformed_number = match err.variant {
+        ErrorVariant::ParsingError { .. } => malformed_exponent_span(source, span.0.start),
+        ErrorVariant::CustomError { .. } => None,
+    };
+    if let Some(number_span) = malformed_number {
+        let text = source[number_span.0.clone()].to_string();
+        return ParseError::new(
+            ParseErrorKind::InvalidNumber { text },
+            source.to_string(),
+            number_span,
+        );
+    }
+
     let kind = match err.variant {
         ErrorVariant::ParsingError {
             positives,
@@ -124,6 +144,15 @@ pub fn convert_pest_error(err: pest::error::Error<Rule>, source: &str) -> ParseE
             ParseErrorKind::UnexpectedToken { expected, found }
         }
         ErrorVariant::CustomError { message } => {
+            if message.starts_with("Invalid digit separator") {
+                let text = source[span.0.clone()].to_string();
+                return ParseError::new(
+                    ParseErrorKind::InvalidDigitSeparator { text },
+                    source.to_string(),
+                    span,
+                );
+            }
+
             // Check if it's a depth error
             if message.contains("nesting depth") {
                 // Try to extract current depth: try "depth" first, then "of" as fallback
@@ -165,6 +194,41 @@ pub fn convert_pest_error(err: pest::error::Error<Rule>, source: &str) -> ParseE
     ParseError::new(kind, source.to_string(), span)
 }
 
+/// Returns the span of a number literal whose exponent has no digits (e.g.
+/// `1e`, `1.5e+`) if the grammar stopped at its exponent marker `pos`.
+///
+/// The grammar only accepts complete exponents, so such literals fail at the
+/// `e`; this lets us report the whole literal instead of an unexpected token.
+fn malformed_exponent_span(source: &str, pos: usize) -> Option<Span> {
+    let bytes = source.as_bytes();
+    if !matches!(bytes.get(pos), Some(b'e' | b'E')) {
+        return None;
+    }
+
+    let is_word_byte = |b: &u8| b.is_ascii_alphanumeric() || *b == b'_';
+    let start = bytes[..pos]
+        .iter()
+        .rposition(|b| !(b.is_ascii_digit() || *b == b'_' || *b == b'.'))
+        .map_or(0, |i| i + 1);
+    if !bytes[start..pos].iter().any(u8::is_ascii_digit) {
+        return None;
+    }
+    // Part of an identifier such as `x1e`, not a number.
+    if start > 0 && is_word_byte(&bytes[start - 1]) {
+        return None;
+    }
+
+    let mut end = pos + 1;
+    if matches!(bytes.get(end), Some(b'+' | b'-')) {
+        end += 1;
+    }
+    // `3eV` is a number followed by an identifier, not a missing exponent.
+    if bytes.get(end).is_some_and(is_word_byte) {
+        return None;
+    }
+    Some(Span(start..end))
+}
+
 /// Format expected rules in a human-readable way
 fn format_expected_rules(rules: &[Rule]) -> String {
     if rules.is_empty() {
@@ -12,8 +12,6 @@ fn test_decimal_integers() {
         ("123", 123),
         ("1_000", 1000),
         ("1_000_000", 1_000_000),
-        ("1__000", 1000), // multiple underscores
-        ("42_", 42),      // trailing underscore
     ];
 
     for (input, expected) in cases {
@@ -39,9 +37,6 @@ fn test_binary_integers() {
         ("0b1", 1),
         ("0b1010", 0b1010),
         ("0b1111_0000", 0b1111_0000),
-        ("0b_1010", 0b1010),   // underscore after prefix
-        ("0b1010_", 0b1010),   // trailing underscore
-        ("0b__1010", 0b1010),  // multiple underscores after prefix
         ("0b1_0_1_0", 0b1010), // underscores between digits
     ];
 
@@ -68,8 +63,6 @@ fn test_octal_integers() {
         ("0o7", 7),
         ("0o755", 0o755),
         ("0o7654_3210", 0o7654_3210),
-        ("0o_755", 0o755), // underscore after prefix
-        ("0o755_", 0o755), // trailing underscore
     ];
 
     for (input, expected) in cases {
@@ -96,10 +89,9 @@ fn test_hexadecimal_integers() {
         ("0x1A3F", 0x1A3F),
         ("0xDEADBEEF", 0xDEADBEEF_u64 as i64),
         ("0xDEAD_BEEF", 0xDEAD_BEEF_u64 as i64),
-        ("0x_DEAD_BEEF", 0xDEAD_BEEF_u64 as i64), // underscore after prefix
-        ("0xABCD_", 0xABCD),                      // trailing underscore
-        ("0xabcd", 0xabcd),                       // lowercase
-        ("0xAbCd", 0xAbCd),                       // mixed case
+        ("0xA_B_C_D", 0xABCD),
+        ("0xabcd", 0xabcd), // lowercase
+        ("0xAbCd", 0xAbCd), // mixed case
     ];
 
     for (input, expected) in cases {
@@ -116,6 +108,57 @@ fn test_hexadecimal_integers() {
     }
 }
 
+#[test]
+fn test_invalid_digit_separators() {
+    use crate::parser::ParseErrorKind;
+
+    let arena = Bump::new();
+
+    let cases = [
+        "1_",      // trailing
+        "1__000",  // double
+        "1_000_",  // trailing after valid separators
+        "0x_FF",   // leading after prefix
+        "0b1010_", // trailing in binary
+        "0o7__7",  // double in octal
+        "0xAB_",   // trailing in hexadecimal
+        "1_.5",    // before the decimal point
+        "1.5_",    // trailing in fraction
+        "1_e10",   // before the exponent
+        "1e10_",   // trailing in exponent
+        "-1__0",   // double in negative literal
+        "1__0`m`", // double with suffix
+        "x match { 1__0 -> 1, _ -> 2 }",
+    ];
+
+    for input in cases {
+        let err = parse(&arena, input).unwrap_err();
+        assert!(
+            matches!(&err.kind, ParseErrorKind::InvalidDigitSeparator { .. }),
+            "Unexpected error kind for input {}: {:?}",
+            input,
+            err.kind
+        );
+    }
+
+    let err = parse(&arena, "x + 1__000").unwrap_err();
+    assert!(
+        matches!(&err.kind, ParseErrorKind::InvalidDigitSeparator { text } if text == "1__000"),
+        "Unexpected error kind: {:?}",
+        err.kind
+    );
+    assert_eq!(err.to_diagnostic().code.as_deref(), Some("P005"));
+}
+
+#[test]
+fn test_leading_underscore_is_identifier() {
+    let arena = Bump::new();
+
+    // `_1` is an identifier, not a number with a leading separator.
+    let parsed = parse(&arena, "_1").unwrap();
+    assert_eq!(*parsed.expr, Expr::Ident("_1"));
+}
+
 #[test]
 fn test_integers_with_suffix() {
     let arena = Bump::new();
@@ -219,7 +262,7 @@ fn test_float_literals() {
         ("1.0", 1.0),
         ("1_000.5", 1000.5),
         ("1_000.5_000", 1000.5),
-        ("1.5_", 1.5), // trailing underscore
+        ("1.234_567", 1.234_567),
     ];
 
     for (input, expected) in cases {
@@ -242,6 +285,8 @@ fn test_float_with_exponent() {
 
     let cases = [
         ("1e10", 1e10),
+        ("1e9", 1e9),
+        ("1E10", 1e10),
         ("1e-10", 1e-10),
         ("1e+10", 1e10),
         ("1.0e10", 1.0e10),
@@ -251,7 +296,13 @@ fn test_float_with_exponent() {
         ("1.6E-19", 1.6e-19), // capital E
         ("1_000e10", 1000e10),
         ("1_000.0e+3", 1000.0e3),
+        ("1e1_0", 1e10),
         ("3.e10", 3.0e10), // no fractional part before exponent
+        ("1.5e3", 1500.0),
+        ("1.5e+3", 1500.0),
+        ("2.5e-3", 2.5e-3),
+        ("2E-4", 0.0002),
+        ("2e-1", 0.2),
     ];
 
     for (input, expected) in cases {
@@ -268,6 +319,42 @@ fn test_float_with_exponent() {
     }
 }
 
+#[test]
+fn test_float_malformed_exponent() {
+    use crate::parser::ParseErrorKind;
+
+    let arena = Bump::new();
+
+    let cases = [
+        ("1e", 0..2),
+        ("1E", 0..2),
+        ("1.5e+", 0..5),
+        (".5E-", 0..4),
+        ("-2e", 1..3),
+        ("x + 1e * 2", 4..6),
+    ];
+
+    for (input, span) in cases {
+        let err = parse(&arena, input).unwrap_err();
+        assert!(
+            matches!(&err.kind, ParseErrorKind::InvalidNumber { text } if *text == input[span.clone()]),
+            "Unexpected error kind for input {}: {:?}",
+            input,
+            err.kind
+        );
+        assert_eq!(err.span.0, span, "Wrong span for input: {}", input);
+    }
+}
+
+#[test]
+fn test_exponent_marker_followed_by_identifier_is_not_malformed_float() {
+    let arena = Bump::new();
+
+    // `1else` is the integer `1` followed by the `else` keyword.
+    let parsed = parse(&arena, "if true then 1else 2").unwrap();
+    assert!(matches!(parsed.expr, Expr::If { .. }));
+}
+
 #[test]
 fn test_floats_with_suffix() {
     let arena = Bump::new();
@@ -2,17 +2,26 @@ mod parsed_expr;
 pub mod parser;
 mod syntax;
 pub mod error;
+mod tokens;
+mod version;
 
 // Re-export the parser and rule enum for external use
 pub use parser::ExpressionParser;
 pub use parser::Rule;
 pub use parser::parse;
 pub use parser::parse_with_max_depth;
+pub use parser::{Associativity, InfixOperator, OperatorPrecedence, PrecedenceError};
+pub use parser::{ParseOptions, parse_with_options};
+pub use parser::{PartialParse, parse_with_recovery, parse_with_recovery_and_options};
 
-pub use parsed_expr::{Expr, Literal, MatchArm, ParsedExpr, Pattern, TypeExpr};
+pub use parsed_expr::{
+    Attribute, AttributeValue, Expr, Literal, MatchArm, ParsedExpr, Pattern, TypeExpr,
+};
 pub use syntax::AnnotatedSource;
 pub use syntax::{BinaryOp, BoolOp, ComparisonOp, Span, UnaryOp};
 pub use error::{ParseError, ParseErrorKind};
+pub use tokens::{TokenKind, tokenize};
+pub use version::{LanguageVersion, SyntaxFeature};
 
 #[cfg(test)]
 mod literals_test;
@@ -25,3 +34,6 @@ mod rule_valid_test;
 
 #[cfg(test)]
 mod precedence_test;
+
+#[cfg(test)]
+mod tokens_test;
@@ -1,5 +1,7 @@
 use super::parser::ExpressionParser;
 use super::parser::Rule;
+use super::parser::parse_with_recovery;
+use bumpalo::Bump;
 use pest::Parser;
 use pest::error::Error;
 
@@ -148,3 +150,195 @@ fn test_pattern_matching_syntax() {
             .unwrap_or_else(|e| panic!("Failed to parse '{}': {}", expr, e));
     }
 }
+
+#[test]
+fn test_pipe_spans() {
+    use crate::parser::{self, Expr};
+
+    let arena = Bump::new();
+    let source = "x |> f(1)";
+    let parsed = parser::parse(&arena, source).unwrap();
+    let Expr::Call { callable, args } = parsed.expr else {
+        panic!("Expected a call, got {:?}", parsed.expr);
+    };
+
+    let snippet = |expr| parsed.ann.snippet(parsed.ann.span_of(expr).unwrap());
+    assert_eq!(snippet(parsed.expr), "x |> f(1)");
+    assert_eq!(snippet(*callable), "f");
+    assert_eq!(snippet(args[0]), "x");
+    assert_eq!(snippet(args[1]), "1");
+}
+
+#[test]
+fn test_language_version_gates_syntax() {
+    use crate::parser::{LanguageVersion, ParseOptions, parse_with_options};
+
+    let arena = Bump::new();
+    let v1 = ParseOptions {
+        language_version: LanguageVersion::V1,
+        ..Default::default()
+    };
+    for source in [
+        "x |> f",
+        "p where { type P = Int, p = 1 }",
+        "type P = Int; 1 as P",
+    ] {
+        assert!(parse_with_options(&arena, source, ParseOptions::default()).is_ok());
+        let err = parse_with_options(&arena, source, v1).unwrap_err();
+        assert!(
+            err.to_diagnostic().message.contains("requires language version 2"),
+            "Unexpected error for '{}': {:?}",
+            source,
+            err
+        );
+    }
+    assert!(parse_with_options(&arena, "f(x) where { x = 1 }", v1).is_ok());
+}
+
+#[test]
+fn test_parse_with_recovery_valid_input() {
+    let arena = Bump::new();
+    let result = parse_with_recovery(&arena, "1 + 2");
+    assert!(result.errors.is_empty());
+    assert!(result.expr.is_some());
+}
+
+#[test]
+fn test_parse_with_recovery_trailing_operator() {
+    use crate::parser::{Expr, Literal};
+
+    let arena = Bump::new();
+    let result = parse_with_recovery(&arena, "1 +");
+
+    assert_eq!(result.errors.len(), 1);
+    let diagnostic = result.errors[0].to_diagnostic();
+    assert_eq!(diagnostic.span.0.start, 3);
+
+    let parsed = result.expr.expect("Expected a partial tree");
+    assert_eq!(
+        *parsed.expr,
+        Expr::Literal(Literal::Int {
+            value: 1,
+            suffix: None
+        })
+    );
+}
+
+#[test]
+fn test_parse_with_recovery_closes_delimiters() {
+    let arena = Bump::new();
+    let cases = [
+        ("[1, 2", "[1, 2]"),
+        ("f(1, [2, 3", "f(1, [2, 3])"),
+        ("{a = 1, b = 2 +", "{a = 1, b = 2  }"),
+        ("x where { y = 1, z =", "x where { y = 1,    }"),
+        ("[1, 2 // two", "[1, 2 // two\n]"),
+    ];
+
+    for (source, repaired) in cases {
+        let result = parse_with_recovery(&arena, source);
+        assert_eq!(result.errors.len(), 1, "Expected an error for '{}'", source);
+        let parsed = result
+            .expr
+            .unwrap_or_else(|| panic!("Expected a partial tree for '{}'", source));
+        assert_eq!(parsed.ann.source, repaired);
+    }
+}
+
+#[test]
+fn test_parse_with_recovery_skips_malformed_elements() {
+    let arena = Bump::new();
+    let cases = [
+        ("[1 +, 2, 3]", "[     2, 3]", 1),
+        ("f(1, 2 * , 3)", "f(1,       3)", 1),
+        ("[1 +, 2, * 3, 4]", "[     2,      4]", 2),
+        ("{ a = 1 +, b = 2 }", "{          b = 2 }", 1),
+        ("[(1 +), 2]", "[       2]", 1),
+        ("[1, , 2]", "[1,   2]", 1),
+        ("f(1 +, 2) + g(3, 4", "f(     2) + g(3, 4)", 2),
+    ];
+
+    for (source, repaired, errors) in cases {
+        let result = parse_with_recovery(&arena, source);
+        assert_eq!(
+            result.errors.len(),
+            errors,
+            "Errors for '{}': {:?}",
+            source,
+            result.errors
+        );
+        let parsed = result
+            .expr
+            .unwrap_or_else(|| panic!("Expected a partial tree for '{}'", source));
+        assert_eq!(parsed.ann.source, repaired);
+    }
+}
+
+#[test]
+fn test_parse_with_recovery_keeps_spans() {
+    use crate::parser::Expr;
+
+    let arena = Bump::new();
+    let source = "[1 +, two, 3 * * 4, four]";
+    let result = parse_with_recovery(&arena, source);
+
+    // One error per malformed element, pointing into the input
+    let starts: Vec<usize> = result.errors.iter().map(|e| e.span.0.start).collect();
+    assert_eq!(starts, [4, 15]);
+    assert!(result.errors.iter().all(|e| e.source == source));
+
+    let parsed = result.expr.expect("Expected a partial tree");
+    let Expr::Array(elements) = parsed.expr else {
+        panic!("Expected an array, got {:?}", parsed.expr);
+    };
+    let snippets: Vec<&str> = elements
+        .iter()
+        .map(|element| &source[parsed.ann.span_of(element).unwrap().0])
+        .collect();
+    assert_eq!(snippets, ["two", "four"]);
+}
+
+#[test]
+fn test_parse_with_recovery_stops_after_many_regions() {
+    use super::parser::MAX_RECOVERED_REGIONS;
+    use crate::parser::Expr;
+
+    // Each region costs a parse of the whole input, so a large malformed
+    // input is cut after the first regions instead of taking quadratic time.
+    let arena = Bump::new();
+    let source = format!("[{}2]", "1 +, ".repeat(8000));
+    let result = parse_with_recovery(&arena, &source);
+
+    assert_eq!(result.errors.len(), MAX_RECOVERED_REGIONS);
+    let starts: Vec<usize> = result.errors.iter().map(|e| e.span.0.start).collect();
+    assert_eq!(starts[..2], [4, 9]);
+    let parsed = result.expr.expect("Expected a partial tree");
+    assert_eq!(*parsed.expr, Expr::Array(&[]));
+}
+
+#[test]
+fn test_parse_with_recovery_uses_options() {
+    use crate::parser::{LanguageVersion, ParseOptions, parse_with_recovery_and_options};
+
+    let arena = Bump::new();
+    let source = "[x |> f, 2]";
+    let v1 = ParseOptions {
+        language_version: LanguageVersion::V1,
+        ..Default::default()
+    };
+
+    let result = parse_with_recovery_and_options(&arena, source, ParseOptions::default());
+    assert!(result.errors.is_empty());
+
+    let result = parse_with_recovery_and_options(&arena, source, v1);
+    assert_eq!(result.errors.len(), 1);
+    assert_eq!(result.expr.unwrap().ann.source, "[        2]");
+}
+
+#[test]
+fn test_parse_with_recovery_unrecoverable() {
+    let arena = Bump::new();
+    let result = parse_with_recovery(&arena, "if x then else y");
+    assert_eq!(result.errors.len(), 1);
+    assert!(result.expr.is_none());
+}
@@ -1,10 +1,37 @@
-use crate::parser::{BinaryOp, BoolOp, ComparisonOp, UnaryOp, syntax::AnnotatedSource};
+use crate::parser::{BinaryOp, BoolOp, ComparisonOp, Span, UnaryOp, syntax::AnnotatedSource};
 use serde::Serialize;
 
 #[derive(Debug)]
 pub struct ParsedExpr<'a> {
     pub expr: &'a Expr<'a>,
     pub ann: &'a AnnotatedSource<'a, Expr<'a>>,
+    /// Spans of the patterns in match arms
+    pub pattern_ann: &'a AnnotatedSource<'a, Pattern<'a>>,
+    /// Attributes leading the source, in source order
+    pub attributes: &'a [Attribute<'a>],
+    /// Type aliases declared before the expression (`type Id = Int; ...`),
+    /// in source order
+    pub type_aliases: &'a [(&'a str, TypeExpr<'a>)],
+}
+
+/// Rule attribute such as `@priority(10)` or `@name("spam-filter")`
+///
+/// Attributes are metadata for hosts (e.g. to order or identify rules) and do
+/// not affect evaluation. A bare `@name` has the value `true`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Attribute<'a> {
+    pub name: &'a str,
+    pub value: AttributeValue<'a>,
+    pub span: Span,
+}
+
+/// Value of a rule attribute: a scalar literal
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub enum AttributeValue<'a> {
+    Bool(bool),
+    Int(i64),
+    Float(f64),
+    Str(&'a str),
 }
 
 #[derive(Serialize, Debug, Clone, PartialEq)]
@@ -36,6 +63,12 @@ pub enum Expr<'a> {
         value: &'a Expr<'a>,
         index: &'a Expr<'a>,
     },
+    /// Slice: `value[start:end]`, where either bound may be omitted
+    Slice {
+        value: &'a Expr<'a>,
+        start: Option<&'a Expr<'a>>,
+        end: Option<&'a Expr<'a>>,
+    },
     Field {
         value: &'a Expr<'a>,
         field: &'a str,
@@ -53,14 +86,22 @@ pub enum Expr<'a> {
         then_branch: &'a Expr<'a>,
         else_branch: &'a Expr<'a>,
     },
+    /// Where block: `expr where { type T = ..., x = ... }`
     Where {
         expr: &'a Expr<'a>,
         bindings: &'a [(&'a str, &'a Expr<'a>)],
+        /// Type aliases declared in the block, in source order
+        type_aliases: &'a [(&'a str, TypeExpr<'a>)],
     },
     Otherwise {
         primary: &'a Expr<'a>,
         fallback: &'a Expr<'a>,
     },
+    /// Option default: `option ?? default`
+    Coalesce {
+        option: &'a Expr<'a>,
+        default: &'a Expr<'a>,
+    },
     /// Option constructor: `some expr` or `none`
     /// Inner is Some(expr) for `some expr`, None for `none`
     Option {
@@ -160,4 +201,9 @@ pub enum Pattern<'a> {
     Some(&'a Pattern<'a>),
     /// None pattern `none` - matches Option::None
     None,
+    /// Binding pattern `p as name` - matches `p` and also binds the whole value to a name
+    As {
+        pattern: &'a Pattern<'a>,
+        name: &'a str,
+    },
 }
@@ -6,69 +6,24 @@ use pest::iterators::Pair;
 use pest::pratt_parser::{Assoc, Op, PrattParser};
 use pest_derive::Parser;
 
-use crate::parser::error::{ParseError, convert_pest_error};
+use crate::parser::error::{ParseError, ParseErrorKind, convert_pest_error};
 use crate::parser::parsed_expr::TypeExpr;
 use crate::parser::syntax::AnnotatedSource;
+use crate::parser::tokens::{TokenKind, tokenize};
+use crate::parser::version::{LanguageVersion, SyntaxFeature};
 use crate::parser::{
-    BinaryOp, BoolOp, ComparisonOp, Expr, Literal, MatchArm, ParsedExpr, Pattern, UnaryOp,
-    syntax::Span,
+    Attribute, AttributeValue, BinaryOp, BoolOp, ComparisonOp, Expr, Literal, MatchArm, ParsedExpr,
+    Pattern, UnaryOp, syntax::Span,
 };
-use crate::{Vec, format};
+use crate::{String, Vec, format, vec};
 
 lazy_static! {
-    // Note: precedence is defined lowest to highest.
-    static ref PRATT_PARSER: PrattParser<Rule> = PrattParser::new()
-        // (lowest precedence)
-        // Lambda, where, and match operators.
-        .op(Op::prefix(Rule::lambda_op))                 // `(...) =>`
-        .op(Op::postfix(Rule::where_op) |
-            Op::postfix(Rule::match_op))                 // `where {}`, `match {}`
-
-        // Fallback (error handling) operator.
-        .op(Op::infix(Rule::otherwise_op, Assoc::Right)) // `otherwise`
-
-        // Logical operators.
-        .op(Op::prefix(Rule::if_op))                     // `if`
-        .op(Op::infix(Rule::or, Assoc::Left))            // `or`
-        .op(Op::infix(Rule::and, Assoc::Left))           // `and`
-        .op(Op::prefix(Rule::not))                       // `not`
-
-        // Comparison operators.
-        .op(
-            Op::infix(Rule::eq, Assoc::Left) |
-            Op::infix(Rule::neq, Assoc::Left) |
-            Op::infix(Rule::lt, Assoc::Left) |
-            Op::infix(Rule::gt, Assoc::Left) |
-            Op::infix(Rule::le, Assoc::Left) |
-            Op::infix(Rule::ge, Assoc::Left) |
-            Op::infix(Rule::in_op, Assoc::Left) |
-            Op::infix(Rule::not_in, Assoc::Left)
-        )                                               // `==`, `!=`, `<`, `>`, `<=`, `>=`, `in`, `not in`
-
-        // Arithmetic operators.
-        .op(
-            Op::infix(Rule::add, Assoc::Left) |
-            Op::infix(Rule::sub, Assoc::Left)
-        )                                               // `+`, `-`
-        .op(
-            Op::infix(Rule::mul, Assoc::Left) |
-            Op::infix(Rule::div, Assoc::Left)
-        )                                               // `*`, `/`
-        .op(Op::prefix(Rule::neg) |
-            Op::prefix(Rule::some_op))                   // `-`, `some`
-        .op(Op::infix(Rule::pow, Assoc::Right))          // `^` (right-assoc))
-
-        // Postfix operators.
-        .op(Op::postfix(Rule::call_op))                  // `()`
-        .op(Op::postfix(Rule::index_op))                 // `[]`
-        .op(Op::postfix(Rule::field_op))                 // `.`
-        .op(Op::postfix(Rule::cast_op))                  // `as`
-        // (highest precedence)
-        ;
+    static ref PRATT_PARSER: PrattParser<Rule> = OperatorPrecedence::DEFAULT.pratt_parser();
 
     // Pattern Pratt parser (for pattern matching)
     // Note: Currently only has prefix operators for Phase 3
     static ref PATTERN_PRATT_PARSER: PrattParser<Rule> = PrattParser::new()
+        .op(Op::postfix(Rule::pattern_as))               // `p as name` binding
         .op(Op::prefix(Rule::pattern_some))              // `some` pattern
         ;
 }
@@ -77,12 +32,352 @@ lazy_static! {
 #[grammar = "parser/expression.pest"]
 pub struct ExpressionParser;
 
+/// How a chain of operators with the same precedence is grouped.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Associativity {
+    /// `a - b - c` is `(a - b) - c`.
+    Left,
+    /// `a ^ b ^ c` is `a ^ (b ^ c)`.
+    Right,
+}
+
+/// The infix operators, whose precedence can be configured through
+/// [`OperatorPrecedence`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum InfixOperator {
+    Otherwise,
+    Or,
+    And,
+    Eq,
+    Neq,
+    Lt,
+    Gt,
+    Le,
+    Ge,
+    In,
+    NotIn,
+    Pipe,
+    Coalesce,
+    BitwiseOr,
+    BitwiseXor,
+    BitwiseAnd,
+    ShiftLeft,
+    ShiftRight,
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Mod,
+    Pow,
+}
+
+impl InfixOperator {
+    /// All infix operators, from the lowest default precedence to the highest.
+    pub const ALL: [InfixOperator; 24] = [
+        InfixOperator::Otherwise,
+        InfixOperator::Or,
+        InfixOperator::And,
+        InfixOperator::Eq,
+        InfixOperator::Neq,
+        InfixOperator::Lt,
+        InfixOperator::Gt,
+        InfixOperator::Le,
+        InfixOperator::Ge,
+        InfixOperator::In,
+        InfixOperator::NotIn,
+        InfixOperator::Pipe,
+        InfixOperator::Coalesce,
+        InfixOperator::BitwiseOr,
+        InfixOperator::BitwiseXor,
+        InfixOperator::BitwiseAnd,
+        InfixOperator::ShiftLeft,
+        InfixOperator::ShiftRight,
+        InfixOperator::Add,
+        InfixOperator::Sub,
+        InfixOperator::Mul,
+        InfixOperator::Div,
+        InfixOperator::Mod,
+        InfixOperator::Pow,
+    ];
+
+    /// The operator as written in source, e.g. `+` or `not in`.
+    pub fn token(self) -> &'static str {
+        match self {
+            InfixOperator::Otherwise => "otherwise",
+            InfixOperator::Or => "or",
+            InfixOperator::And => "and",
+            InfixOperator::Eq => "==",
+            InfixOperator::Neq => "!=",
+            InfixOperator::Lt => "<",
+            InfixOperator::Gt => ">",
+            InfixOperator::Le => "<=",
+            InfixOperator::Ge => ">=",
+            InfixOperator::In => "in",
+            InfixOperator::NotIn => "not in",
+            InfixOperator::Pipe => "|>",
+            InfixOperator::Coalesce => "??",
+            InfixOperator::BitwiseOr => "|",
+            InfixOperator::BitwiseXor => "xor",
+            InfixOperator::BitwiseAnd => "&",
+            InfixOperator::ShiftLeft => "<<",
+            InfixOperator::ShiftRight => ">>",
+            InfixOperator::Add => "+",
+            InfixOperator::Sub => "-",
+            InfixOperator::Mul => "*",
+            InfixOperator::Div => "/",
+            InfixOperator::Mod => "%",
+            InfixOperator::Pow => "^",
+        }
+    }
+
+    /// How chains of this operator are grouped. This is fixed: only the
+    /// precedence can be configured.
+    pub fn associativity(self) -> Associativity {
+        match self {
+            InfixOperator::Otherwise | InfixOperator::Coalesce | InfixOperator::Pow => {
+                Associativity::Right
+            }
+            _ => Associativity::Left,
+        }
+    }
+
+    fn rule(self) -> Rule {
+        match self {
+            InfixOperator::Otherwise => Rule::otherwise_op,
+            InfixOperator::Or => Rule::or,
+            InfixOperator::And => Rule::and,
+            InfixOperator::Eq => Rule::eq,
+            InfixOperator::Neq => Rule::neq,
+            InfixOperator::Lt => Rule::lt,
+            InfixOperator::Gt => Rule::gt,
+            InfixOperator::Le => Rule::le,
+            InfixOperator::Ge => Rule::ge,
+            InfixOperator::In => Rule::in_op,
+            InfixOperator::NotIn => Rule::not_in,
+            InfixOperator::Pipe => Rule::pipe,
+            InfixOperator::Coalesce => Rule::coalesce,
+            InfixOperator::BitwiseOr => Rule::bitwise_or,
+            InfixOperator::BitwiseXor => Rule::bitwise_xor,
+            InfixOperator::BitwiseAnd => Rule::bitwise_and,
+            InfixOperator::ShiftLeft => Rule::shift_left,
+            InfixOperator::ShiftRight => Rule::shift_right,
+            InfixOperator::Add => Rule::add,
+            InfixOperator::Sub => Rule::sub,
+            InfixOperator::Mul => Rule::mul,
+            InfixOperator::Div => Rule::div,
+            InfixOperator::Mod => Rule::mod_op,
+            InfixOperator::Pow => Rule::pow,
+        }
+    }
+}
+
+impl core::fmt::Display for InfixOperator {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "`{}`", self.token())
+    }
+}
+
+/// The precedence levels the parser uses for infix operators. Operators
+/// with a higher level bind tighter: with the default levels, `a + b * c`
+/// is `a + (b * c)`.
+///
+/// The prefix operators sit at fixed levels in between: `if` at
+/// [`IF_LEVEL`](Self::IF_LEVEL), `not` at [`NOT_LEVEL`](Self::NOT_LEVEL),
+/// and `-` and `some` at [`NEGATION_LEVEL`](Self::NEGATION_LEVEL). Lambdas,
+/// `where` and `match` always bind loosest, and calls, indexing, field
+/// access and `as` always bind tightest.
+///
+/// A table starts from [`DEFAULT`](Self::DEFAULT) and is changed with
+/// [`with_level`](Self::with_level). That method rejects levels that would
+/// make grouping ambiguous, so every table is valid.
+///
+/// ```
+/// use melbi_core::parser::{InfixOperator, OperatorPrecedence};
+///
+/// // Make `and` bind tighter than comparisons
+/// let precedence = OperatorPrecedence::DEFAULT
+///     .with_level(InfixOperator::And, 70)
+///     .unwrap();
+/// assert_eq!(precedence.level(InfixOperator::And), 70);
+/// assert_eq!(precedence.level(InfixOperator::Eq), 60);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct OperatorPrecedence {
+    /// Level of each operator, indexed by its position in [`InfixOperator::ALL`].
+    levels: [u8; InfixOperator::ALL.len()],
+}
+
+impl OperatorPrecedence {
+    /// Level of the prefix `if ... then ... else` operator.
+    pub const IF_LEVEL: u8 = 20;
+
+    /// Level of the prefix `not` operator.
+    pub const NOT_LEVEL: u8 = 50;
+
+    /// Level of the prefix `-` and `some` operators.
+    pub const NEGATION_LEVEL: u8 = 140;
+
+    /// The precedence of Melbi's grammar, from `otherwise` (10) to `^` (150).
+    pub const DEFAULT: Self = Self {
+        levels: [
+            10,  // otherwise
+            30,  // or
+            40,  // and
+            60,  // ==
+            60,  // !=
+            60,  // <
+            60,  // >
+            60,  // <=
+            60,  // >=
+            60,  // in
+            60,  // not in
+            70,  // |>
+            80,  // ??
+            90,  // |
+            90,  // xor
+            100, // &
+            110, // <<
+            110, // >>
+            120, // +
+            120, // -
+            130, // *
+            130, // /
+            130, // %
+            150, // ^
+        ],
+    };
+
+    /// The level of `operator`.
+    pub fn level(&self, operator: InfixOperator) -> u8 {
+        self.levels[operator as usize]
+    }
+
+    /// Each infix operator with its level.
+    pub fn iter(&self) -> impl Iterator<Item = (InfixOperator, u8)> + '_ {
+        InfixOperator::ALL
+            .iter()
+            .map(|&operator| (operator, self.level(operator)))
+    }
+
+    /// Returns this table with `operator` moved to `level`.
+    ///
+    /// Fails if `level` is one of the prefix operator levels, or if an
+    /// operator with a different associativity is already at `level`: mixing
+    /// them would leave `a op1 b op2 c` without a single grouping.
+    pub fn with_level(
+        mut self,
+        operator: InfixOperator,
+        level: u8,
+    ) -> Result<Self, PrecedenceError> {
+        if [Self::IF_LEVEL, Self::NOT_LEVEL, Self::NEGATION_LEVEL].contains(&level) {
+            return Err(PrecedenceError::PrefixLevel { operator, level });
+        }
+        if let Some((other, _)) = self.iter().find(|&(other, other_level)| {
+            other_level == level && other.associativity() != operator.associativity()
+        }) {
+            return Err(PrecedenceError::MixedAssociativity {
+                operator,
+                other,
+                level,
+            });
+        }
+        self.levels[operator as usize] = level;
+        Ok(self)
+    }
+
+    /// Builds the Pratt parser implementing this table.
+    fn pratt_parser(&self) -> PrattParser<Rule> {
+        // Lambda, where, and match operators bind loosest.
+        let mut parser = PrattParser::new()
+            .op(Op::prefix(Rule::lambda_op))
+            .op(Op::postfix(Rule::where_op) | Op::postfix(Rule::match_op));
+
+        let mut levels: Vec<u8> = self.levels.to_vec();
+        levels.extend([Self::IF_LEVEL, Self::NOT_LEVEL, Self::NEGATION_LEVEL]);
+        levels.sort_unstable();
+        levels.dedup();
+        for level in levels {
+            let op = match level {
+                Self::IF_LEVEL => Op::prefix(Rule::if_op),
+                Self::NOT_LEVEL => Op::prefix(Rule::not),
+                Self::NEGATION_LEVEL => Op::prefix(Rule::neg) | Op::prefix(Rule::some_op),
+                _ => self
+                    .iter()
+                    .filter(|&(_, operator_level)| operator_level == level)
+                    .map(|(operator, _)| {
+                        let assoc = match operator.associativity() {
+                            Associativity::Left => Assoc::Left,
+                            Associativity::Right => Assoc::Right,
+                        };
+                        Op::infix(operator.rule(), assoc)
+                    })
+                    .reduce(|ops, op| ops | op)
+                    .expect("every level has an operator"),
+            };
+            parser = parser.op(op);
+        }
+
+        // Postfix operators bind tightest.
+        parser
+            .op(Op::postfix(Rule::call_op))
+            .op(Op::postfix(Rule::index_op) | Op::postfix(Rule::slice_op))
+            .op(Op::postfix(Rule::field_op))
+            .op(Op::postfix(Rule::cast_op))
+    }
+}
+
+impl Default for OperatorPrecedence {
+    fn default() -> Self {
+        Self::DEFAULT
+    }
+}
+
+/// A precedence level rejected by [`OperatorPrecedence::with_level`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PrecedenceError {
+    /// The level is taken by a prefix operator.
+    PrefixLevel { operator: InfixOperator, level: u8 },
+    /// An operator with a different associativity already has the level.
+    MixedAssociativity {
+        operator: InfixOperator,
+        other: InfixOperator,
+        level: u8,
+    },
+}
+
+impl core::fmt::Display for PrecedenceError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            PrecedenceError::PrefixLevel { operator, level } => write!(
+                f,
+                "Cannot move {} to precedence {}, which is reserved for a prefix operator",
+                operator, level
+            ),
+            PrecedenceError::MixedAssociativity {
+                operator,
+                other,
+                level,
+            } => write!(
+                f,
+                "Cannot move {} to precedence {}: {} is there with a different associativity",
+                operator, level, other
+            ),
+        }
+    }
+}
+
+impl core::error::Error for PrecedenceError {}
+
 struct ParseContext<'a, 'input> {
     arena: &'a Bump,
     original_source: &'input str, // To "transfer" slices to the arena allocated string.
     ann: &'a AnnotatedSource<'a, Expr<'a>>,
+    pattern_ann: &'a AnnotatedSource<'a, Pattern<'a>>,
     depth: core::cell::Cell<usize>,
     max_depth: usize,
+    language_version: LanguageVersion,
+    /// Parser for a non-default operator precedence.
+    custom_pratt_parser: Option<PrattParser<Rule>>,
 }
 
 impl<'a, 'input> ParseContext<'a, 'input> {
@@ -112,6 +407,27 @@ impl<'a, 'input> ParseContext<'a, 'input> {
         Ok(())
     }
 
+    fn check_feature(
+        &self,
+        feature: SyntaxFeature,
+        span: pest::Span,
+    ) -> Result<(), pest::error::Error<Rule>> {
+        if self.language_version.supports(feature) {
+            return Ok(());
+        }
+        Err(pest::error::Error::new_from_span(
+            pest::error::ErrorVariant::CustomError {
+                message: format!(
+                    "{} requires language version {} or later, but version {} is in use",
+                    feature.description(),
+                    feature.since(),
+                    self.language_version
+                ),
+            },
+            span,
+        ))
+    }
+
     fn parse_expr(&self, pair: Pair<Rule>) -> Result<&'a Expr<'a>, pest::error::Error<Rule>> {
         self.check_depth(&pair)?;
         let result = match pair.as_rule() {
@@ -142,44 +458,131 @@ impl<'a, 'input> ParseContext<'a, 'input> {
 
     fn parse_main(&self, pair: Pair<Rule>) -> Result<&'a Expr<'a>, pest::error::Error<Rule>> {
         let span = pair.as_span();
-        self.parse_expr(pair.into_inner().next().ok_or_else(|| {
-            pest::error::Error::new_from_span(
-                pest::error::ErrorVariant::CustomError {
-                    message: "missing expected pair in rule".to_string(),
+        let mut inner = pair.into_inner();
+        self.parse_expr(
+            inner
+                .find(|inner| inner.as_rule() == Rule::expression)
+                .ok_or_else(|| {
+                    pest::error::Error::new_from_span(
+                        pest::error::ErrorVariant::CustomError {
+                            message: "missing expected pair in rule".to_string(),
+                        },
+                        span,
+                    )
+                })?,
+        )
+    }
+
+    /// Parse the attributes leading a `main` pair.
+    fn parse_attributes(
+        &self,
+        pair: Pair<Rule>,
+    ) -> Result<&'a [Attribute<'a>], pest::error::Error<Rule>> {
+        let mut attributes: Vec<Attribute<'a>> = Vec::new();
+        for attribute in pair.into_inner() {
+            if attribute.as_rule() != Rule::attribute {
+                continue;
+            }
+            let pest_span = attribute.as_span();
+            let error = |message: String| {
+                pest::error::Error::new_from_span(
+                    pest::error::ErrorVariant::CustomError { message },
+                    pest_span,
+                )
+            };
+            let mut inner = attribute.into_inner();
+            let name = self.reslice(inner.next().unwrap().as_str());
+            if attributes.iter().any(|existing| existing.name == name) {
+                return Err(error(format!("Duplicate attribute `@{}`", name)));
+            }
+            let value = match inner.next() {
+                None => AttributeValue::Bool(true),
+                Some(value) => match self.parse_expr(value)? {
+                    Expr::Literal(Literal::Bool(value)) => AttributeValue::Bool(*value),
+                    Expr::Literal(Literal::Int {
+                        value,
+                        suffix: None,
+                    }) => AttributeValue::Int(*value),
+                    Expr::Literal(Literal::Float {
+                        value,
+                        suffix: None,
+                    }) => AttributeValue::Float(*value),
+                    Expr::Literal(Literal::Str(value)) => AttributeValue::Str(value),
+                    _ => {
+                        return Err(error(format!(
+                            "Attribute `@{}` must have a plain literal value",
+                            name
+                        )));
+                    }
                 },
-                span,
-            )
-        })?)
+            };
+            attributes.push(Attribute {
+                name,
+                value,
+                span: pest_span.into(),
+            });
+        }
+        Ok(self.arena.alloc_slice_fill_iter(attributes))
+    }
+
+    /// Parse the type alias declarations leading a `main` pair.
+    fn parse_type_declarations(
+        &self,
+        pair: Pair<Rule>,
+    ) -> Result<&'a [(&'a str, TypeExpr<'a>)], pest::error::Error<Rule>> {
+        let alias_pairs: Vec<_> = pair
+            .into_inner()
+            .filter(|inner| inner.as_rule() == Rule::type_alias)
+            .collect();
+        if let Some(alias) = alias_pairs.first() {
+            self.check_feature(SyntaxFeature::TypeDeclaration, alias.as_span())?;
+        }
+        let aliases_iter = alias_pairs.into_iter().map(|p| self.parse_type_alias(p));
+        Ok(self.arena.alloc_slice_try_fill_iter(aliases_iter)?)
     }
 
     fn parse_expression(&self, pair: Pair<Rule>) -> Result<&'a Expr<'a>, pest::error::Error<Rule>> {
-        PRATT_PARSER
-            .map_primary(|primary| self.parse_expr(primary))
+        // Each operand is paired with the source it was parsed from, which
+        // for a parenthesized group includes the parentheses. Operators span
+        // their operands' sources, while the group itself keeps the span of
+        // its content.
+        self.custom_pratt_parser
+            .as_ref()
+            .unwrap_or(&PRATT_PARSER)
+            .map_primary(|primary| {
+                let span = Span::from(primary.as_span());
+                Ok((self.parse_expr(primary)?, span))
+            })
             .map_prefix(|op, rhs| {
-                let rhs_value = rhs?;
-                let span =
-                    Span::combine(&op.as_span().into(), &self.ann.span_of(rhs_value).unwrap());
-                match op.as_rule() {
+                let (rhs_value, rhs_span) = rhs?;
+                let span = Span::combine(&op.as_span().into(), &rhs_span);
+                let source_span = span.clone();
+                let expr = match op.as_rule() {
                     Rule::neg | Rule::not => self.parse_unary_op(op, rhs_value, span),
                     Rule::if_op => self.parse_if_expr(op, rhs_value, span),
                     Rule::lambda_op => self.parse_lambda_expr(op, rhs_value, span),
                     Rule::some_op => self.parse_some_expr(rhs_value, span),
                     _ => unreachable!("Unknown prefix operator: {:?}", op.as_rule()),
-                }
+                }?;
+                Ok((expr, source_span))
             })
             .map_infix(|lhs, op, rhs| {
-                let lhs_expr = lhs?;
-                let rhs_expr = rhs?;
-                let span = Span::combine(
-                    &self.ann.span_of(lhs_expr).unwrap(),
-                    &self.ann.span_of(rhs_expr).unwrap(),
-                );
-                match op.as_rule() {
+                let (lhs_expr, lhs_span) = lhs?;
+                let (rhs_expr, rhs_span) = rhs?;
+                let span = Span::combine(&lhs_span, &rhs_span);
+                let source_span = span.clone();
+                let expr = match op.as_rule() {
                     Rule::add
                     | Rule::sub
                     | Rule::mul
                     | Rule::div
+                    | Rule::mod_op
                     | Rule::pow
+                    | Rule::bitwise_and
+                    | Rule::bitwise_or
+                    | Rule::bitwise_xor
+                    | Rule::shift_left
+                    | Rule::shift_right
                     | Rule::and
                     | Rule::or => self.parse_binary_op(op, lhs_expr, rhs_expr, span),
                     Rule::eq
@@ -191,24 +594,33 @@ impl<'a, 'input> ParseContext<'a, 'input> {
                     | Rule::in_op
                     | Rule::not_in => self.parse_comparison_op(op, lhs_expr, rhs_expr, span),
                     Rule::otherwise_op => self.parse_otherwise_expr(lhs_expr, rhs_expr, span),
+                    Rule::coalesce => self.parse_coalesce_expr(lhs_expr, rhs_expr, span),
+                    Rule::pipe => {
+                        self.check_feature(SyntaxFeature::Pipe, op.as_span())?;
+                        self.parse_pipe_expr(lhs_expr, rhs_expr, span)
+                    }
                     _ => unreachable!("Unknown binary operator: {:?}", op.as_rule()),
-                }
+                }?;
+                Ok((expr, source_span))
             })
             .map_postfix(|lhs, op| {
-                let lhs_expr = lhs?;
-                let span =
-                    Span::combine(&self.ann.span_of(lhs_expr).unwrap(), &op.as_span().into());
-                match op.as_rule() {
+                let (lhs_expr, lhs_span) = lhs?;
+                let span = Span::combine(&lhs_span, &op.as_span().into());
+                let source_span = span.clone();
+                let expr = match op.as_rule() {
                     Rule::call_op => self.parse_call_expr(lhs_expr, op, span),
                     Rule::index_op => self.parse_index_expr(lhs_expr, op, span),
+                    Rule::slice_op => self.parse_slice_expr(lhs_expr, op, span),
                     Rule::field_op => self.parse_field_expr(lhs_expr, op, span),
                     Rule::cast_op => self.parse_cast_expr(lhs_expr, op, span),
                     Rule::where_op => self.parse_where_expr(lhs_expr, op, span),
                     Rule::match_op => self.parse_match_expr(lhs_expr, op, span),
                     _ => unreachable!("Unknown postfix operator: {:?}", op.as_rule()),
-                }
+                }?;
+                Ok((expr, source_span))
             })
             .parse(pair.into_inner())
+            .map(|(expr, _)| expr)
     }
 
     // Helper to allocate an expression with its span
@@ -311,7 +723,13 @@ impl<'a, 'input> ParseContext<'a, 'input> {
                     Rule::sub => BinaryOp::Sub,
                     Rule::mul => BinaryOp::Mul,
                     Rule::div => BinaryOp::Div,
+                    Rule::mod_op => BinaryOp::Mod,
                     Rule::pow => BinaryOp::Pow,
+                    Rule::bitwise_and => BinaryOp::BitwiseAnd,
+                    Rule::bitwise_or => BinaryOp::BitwiseOr,
+                    Rule::bitwise_xor => BinaryOp::BitwiseXor,
+                    Rule::shift_left => BinaryOp::ShiftLeft,
+                    Rule::shift_right => BinaryOp::ShiftRight,
                     _ => unreachable!("Unknown binary operator: {:?}", op.as_rule()),
                 };
                 Ok(self.alloc_with_span(
@@ -363,6 +781,34 @@ impl<'a, 'input> ParseContext<'a, 'input> {
         Ok(self.alloc_with_span(Expr::Otherwise { primary, fallback }, span))
     }
 
+    fn parse_coalesce_expr(
+        &self,
+        option: &'a Expr<'a>,
+        default: &'a Expr<'a>,
+        span: Span,
+    ) -> Result<&'a Expr<'a>, pest::error::Error<Rule>> {
+        Ok(self.alloc_with_span(Expr::Coalesce { option, default }, span))
+    }
+
+    /// Desugar `value |> f` into `f(value)` and `value |> f(args...)` into
+    /// `f(value, args...)`, so the rest of the pipeline only sees calls.
+    fn parse_pipe_expr(
+        &self,
+        value: &'a Expr<'a>,
+        function: &'a Expr<'a>,
+        span: Span,
+    ) -> Result<&'a Expr<'a>, pest::error::Error<Rule>> {
+        let (callable, args) = match function {
+            Expr::Call { callable, args } => (*callable, *args),
+            _ => (function, &[][..]),
+        };
+        let mut all_args = Vec::with_capacity(args.len() + 1);
+        all_args.push(value);
+        all_args.extend_from_slice(args);
+        let args = self.arena.alloc_slice_copy(&all_args);
+        Ok(self.alloc_with_span(Expr::Call { callable, args }, span))
+    }
+
     // Postfix operators
     fn parse_call_expr(
         &self,
@@ -390,6 +836,26 @@ impl<'a, 'input> ParseContext<'a, 'input> {
         Ok(self.alloc_with_span(Expr::Index { value, index }, span))
     }
 
+    fn parse_slice_expr(
+        &self,
+        value: &'a Expr<'a>,
+        op: Pair<Rule>,
+        span: Span,
+    ) -> Result<&'a Expr<'a>, pest::error::Error<Rule>> {
+        let mut start = None;
+        let mut end = None;
+        for bound in op.into_inner() {
+            let rule = bound.as_rule();
+            let expr = self.parse_expr(bound.into_inner().next().unwrap())?;
+            match rule {
+                Rule::slice_start => start = Some(expr),
+                Rule::slice_end => end = Some(expr),
+                _ => unreachable!("Unknown slice bound: {:?}", rule),
+            }
+        }
+        Ok(self.alloc_with_span(Expr::Slice { value, start, end }, span))
+    }
+
     fn parse_field_expr(
         &self,
         value: &'a Expr<'a>,
@@ -526,9 +992,51 @@ impl<'a, 'input> ParseContext<'a, 'input> {
         op: Pair<Rule>,
         span: Span,
     ) -> Result<&'a Expr<'a>, pest::error::Error<Rule>> {
-        let bindings_iter = op.into_inner().map(|p| self.parse_binding(p));
+        let (alias_pairs, binding_pairs): (Vec<_>, Vec<_>) = op
+            .into_inner()
+            .partition(|p| p.as_rule() == Rule::type_alias);
+        if let Some(alias) = alias_pairs.first() {
+            self.check_feature(SyntaxFeature::WhereTypeAlias, alias.as_span())?;
+        }
+        let bindings_iter = binding_pairs.into_iter().map(|p| self.parse_binding(p));
         let bindings = self.arena.alloc_slice_try_fill_iter(bindings_iter)?;
-        Ok(self.alloc_with_span(Expr::Where { expr, bindings }, span))
+        let aliases_iter = alias_pairs.into_iter().map(|p| self.parse_type_alias(p));
+        let type_aliases = self.arena.alloc_slice_try_fill_iter(aliases_iter)?;
+        Ok(self.alloc_with_span(
+            Expr::Where {
+                expr,
+                bindings,
+                type_aliases,
+            },
+            span,
+        ))
+    }
+
+    fn parse_type_alias(
+        &self,
+        pair: Pair<Rule>,
+    ) -> Result<(&'a str, TypeExpr<'a>), pest::error::Error<Rule>> {
+        let span = pair.as_span();
+        // Skip the `type` keyword
+        let mut inner = pair.into_inner().skip(1);
+        let name = inner.next().ok_or_else(|| {
+            pest::error::Error::new_from_span(
+                pest::error::ErrorVariant::CustomError {
+                    message: "missing type alias name".to_string(),
+                },
+                span,
+            )
+        })?;
+        let type_expr_pair = inner.next().ok_or_else(|| {
+            pest::error::Error::new_from_span(
+                pest::error::ErrorVariant::CustomError {
+                    message: "missing type alias definition".to_string(),
+                },
+                span,
+            )
+        })?;
+        let ty = self.parse_type_expr(type_expr_pair)?;
+        Ok((self.reslice(name.as_str()), ty))
     }
 
     fn parse_match_expr(
@@ -556,8 +1064,13 @@ impl<'a, 'input> ParseContext<'a, 'input> {
     fn parse_pattern(&self, pair: Pair<Rule>) -> Result<&'a Pattern<'a>, pest::error::Error<Rule>> {
         self.check_depth(&pair)?;
 
+        // The pattern rule also captures the whitespace before `->`.
+        let start = pair.as_span().start();
+        let span = Span::new(start, start + pair.as_str().trim_end().len());
+
         // Since pattern_primary is silent, the inner pairs of pattern are the actual
-        // pattern components (pattern_wildcard, pattern_var, etc.) plus any prefix operators
+        // pattern components (pattern_wildcard, pattern_var, etc.) plus any prefix
+        // and postfix operators
         let result = PATTERN_PRATT_PARSER
             .map_primary(|primary| self.parse_pattern_primary(primary))
             .map_prefix(|op, rhs| {
@@ -570,7 +1083,24 @@ impl<'a, 'input> ParseContext<'a, 'input> {
                     _ => unreachable!("Unknown prefix pattern operator: {:?}", op.as_rule()),
                 }
             })
+            .map_postfix(|lhs, op| {
+                let lhs_pattern = lhs?;
+                match op.as_rule() {
+                    Rule::pattern_as => {
+                        let name = self.reslice(op.into_inner().next().unwrap().as_str());
+                        let pattern = self.arena.alloc(Pattern::As {
+                            pattern: lhs_pattern,
+                            name,
+                        });
+                        Ok(pattern)
+                    }
+                    _ => unreachable!("Unknown postfix pattern operator: {:?}", op.as_rule()),
+                }
+            })
             .parse(pair.into_inner());
+        if let Ok(pattern) = result {
+            self.pattern_ann.add_span(pattern, span);
+        }
 
         self.depth.set(self.depth.get() - 1);
         result
@@ -622,9 +1152,11 @@ impl<'a, 'input> ParseContext<'a, 'input> {
         let mut inner = pair.into_inner();
         let integer_number = inner.next().unwrap();
 
-        let number_str = integer_number.as_str().replace('_', "");
+        let number_text = integer_number.as_str();
         let mut inner_num = integer_number.into_inner();
         let integer_type = inner_num.next().unwrap();
+        let is_hex = integer_type.as_rule() == Rule::hex_integer;
+        let number_str = remove_digit_separators(number_text, is_hex, pair_span)?;
 
         let value = match integer_type.as_rule() {
             Rule::dec_integer => i64::from_str_radix(&number_str, 10),
@@ -666,9 +1198,7 @@ impl<'a, 'input> ParseContext<'a, 'input> {
         let mut inner = pair.into_inner();
         let float_number = inner.next().unwrap();
 
-        let value: f64 = float_number
-            .as_str()
-            .replace('_', "")
+        let value: f64 = remove_digit_separators(float_number.as_str(), false, pair_span)?
             .parse()
             .map_err(|_| {
                 pest::error::Error::new_from_span(
@@ -756,11 +1286,13 @@ impl<'a, 'input> ParseContext<'a, 'input> {
 
         // The integer_number is ${ "-"? ~ integer_literal }
         // So we can get the full signed string
-        let number_str = integer_number.as_str().replace('_', "");
+        let number_text = integer_number.as_str();
 
         // And check what kind of integer it is from the inner tokens
         let mut inner_num = integer_number.into_inner();
         let integer_type = inner_num.next().unwrap();
+        let is_hex = integer_type.as_rule() == Rule::hex_integer;
+        let number_str = remove_digit_separators(number_text, is_hex, pair_span)?;
 
         let value = match integer_type.as_rule() {
             Rule::dec_integer => i64::from_str_radix(&number_str, 10),
@@ -799,9 +1331,7 @@ impl<'a, 'input> ParseContext<'a, 'input> {
         let float_number = inner.next().unwrap();
 
         // The float_number is ${ "-"? ~ float_literal }, so we can get the full signed string
-        let value: f64 = float_number
-            .as_str()
-            .replace('_', "")
+        let value: f64 = remove_digit_separators(float_number.as_str(), false, pair_span)?
             .parse()
             .map_err(|_| {
                 pest::error::Error::new_from_span(
@@ -1058,10 +1588,77 @@ impl<'a, 'input> ParseContext<'a, 'input> {
     }
 }
 
+/// Removes `_` digit separators from the text of a number literal.
+///
+/// The grammar accepts underscores anywhere after the first digit so that
+/// misplaced separators can be reported here with a descriptive error. A
+/// separator must sit between two digits, where hexadecimal literals count
+/// `A-F` as digits: `1_000` is valid, `1_`, `1__000`, `0x_FF` and `1_.5` are
+/// not.
+fn remove_digit_separators(
+    text: &str,
+    is_hex: bool,
+    span: pest::Span,
+) -> Result<String, pest::error::Error<Rule>> {
+    let is_digit = |byte: Option<&u8>| match byte {
+        Some(b) if is_hex => b.is_ascii_hexdigit(),
+        Some(b) => b.is_ascii_digit(),
+        None => false,
+    };
+    let bytes = text.as_bytes();
+    for (index, _) in text.match_indices('_') {
+        let before = index.checked_sub(1).and_then(|i| bytes.get(i));
+        if !is_digit(before) || !is_digit(bytes.get(index + 1)) {
+            return Err(pest::error::Error::new_from_span(
+                pest::error::ErrorVariant::CustomError {
+                    message: format!(
+                        "Invalid digit separator in number literal '{}': \
+                         '_' must be placed between two digits",
+                        text
+                    ),
+                },
+                span,
+            ));
+        }
+    }
+    Ok(text.replace('_', ""))
+}
+
 /// Default maximum nesting depth for expression parsing.
 /// This prevents stack overflow from deeply nested expressions like `(((((...(1)...)))))`.
 const DEFAULT_MAX_PARSE_DEPTH: usize = 500;
 
+/// Options controlling what the parser accepts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ParseOptions {
+    /// How deeply expressions can be nested (e.g., parentheses, arrays, etc.)
+    /// before returning an error.
+    ///
+    /// Default: 500.
+    pub max_depth: usize,
+
+    /// Which optional syntax is accepted. Constructs introduced after this
+    /// version fail to parse.
+    ///
+    /// Default: [`LanguageVersion::LATEST`].
+    pub language_version: LanguageVersion,
+
+    /// Precedence of the infix operators.
+    ///
+    /// Default: [`OperatorPrecedence::DEFAULT`].
+    pub operator_precedence: OperatorPrecedence,
+}
+
+impl Default for ParseOptions {
+    fn default() -> Self {
+        Self {
+            max_depth: DEFAULT_MAX_PARSE_DEPTH,
+            language_version: LanguageVersion::LATEST,
+            operator_precedence: OperatorPrecedence::DEFAULT,
+        }
+    }
+}
+
 /// Parses a Melbi expression with the default maximum nesting depth.
 ///
 /// For custom depth limits, use [`parse_with_max_depth`].
@@ -1069,7 +1666,7 @@ pub fn parse<'a, 'i>(arena: &'a Bump, source: &'i str) -> Result<&'a ParsedExpr<
 where
     'i: 'a,
 {
-    parse_with_max_depth(arena, source, DEFAULT_MAX_PARSE_DEPTH)
+    parse_with_options(arena, source, ParseOptions::default())
 }
 
 /// Parses a Melbi expression with a custom maximum nesting depth.
@@ -1085,6 +1682,25 @@ pub fn parse_with_max_depth<'a, 'i>(
     source: &'i str,
     max_depth: usize,
 ) -> Result<&'a ParsedExpr<'a>, ParseError>
+where
+    'i: 'a,
+{
+    parse_with_options(
+        arena,
+        source,
+        ParseOptions {
+            max_depth,
+            ..Default::default()
+        },
+    )
+}
+
+/// Parses a Melbi expression with the given [`ParseOptions`].
+pub fn parse_with_options<'a, 'i>(
+    arena: &'a Bump,
+    source: &'i str,
+    options: ParseOptions,
+) -> Result<&'a ParsedExpr<'a>, ParseError>
 where
     'i: 'a,
 {
@@ -1097,18 +1713,318 @@ where
         arena,
         original_source: source, // To "transfer" slices to the arena allocated string.
         ann: arena.alloc(AnnotatedSource::new(arena, source)),
+        pattern_ann: arena.alloc(AnnotatedSource::new(arena, source)),
         depth: core::cell::Cell::new(0),
-        max_depth,
+        max_depth: options.max_depth,
+        language_version: options.language_version,
+        custom_pratt_parser: (options.operator_precedence != OperatorPrecedence::DEFAULT)
+            .then(|| options.operator_precedence.pratt_parser()),
     };
+    let attributes = context
+        .parse_attributes(pair.clone())
+        .map_err(|e| convert_pest_error(e, source))?;
+    let type_aliases = context
+        .parse_type_declarations(pair.clone())
+        .map_err(|e| convert_pest_error(e, source))?;
     let expr = context
         .parse_expr(pair)
         .map_err(|e| convert_pest_error(e, source))?;
     Ok(arena.alloc(ParsedExpr {
         expr,
         ann: context.ann,
+        pattern_ann: context.pattern_ann,
+        attributes,
+        type_aliases,
     }))
 }
 
+/// Most malformed regions [`parse_with_recovery`] skips before giving up on
+/// the rest of the input. Each region costs a parse of the whole input.
+pub(super) const MAX_RECOVERED_REGIONS: usize = 20;
+
+/// Most attempts [`parse_with_recovery`] makes to parse a repaired input,
+/// counting those that drop one more token of the same region.
+const MAX_RECOVERY_ATTEMPTS: usize = 100;
+
+/// Result of parsing with error recovery.
+#[derive(Debug)]
+pub struct PartialParse<'a> {
+    /// The (possibly partial) expression, if anything could be recovered.
+    /// When `errors` is non-empty, spans refer to `expr.ann.source`, which
+    /// is the repaired source text. Skipped regions are blanked out rather
+    /// than removed, so offsets into it are also offsets into the input.
+    pub expr: Option<&'a ParsedExpr<'a>>,
+    /// One error per malformed region of the input, in source order. Empty
+    /// if parsing succeeded.
+    pub errors: Vec<ParseError>,
+}
+
+/// Parses a Melbi expression, recovering a partial tree on syntax errors.
+///
+/// Like [`parse_with_recovery_and_options`], with default options.
+pub fn parse_with_recovery<'a, 'i>(arena: &'a Bump, source: &'i str) -> PartialParse<'a>
+where
+    'i: 'a,
+{
+    parse_with_recovery_and_options(arena, source, ParseOptions::default())
+}
+
+/// Parses a Melbi expression with the given [`ParseOptions`], recovering a
+/// partial tree on syntax errors.
+///
+/// Each syntax error starts a malformed region, which is skipped up to the
+/// next `,` or `;` at the same nesting level, or the delimiter closing the
+/// enclosing `(`, `[` or `{`. Inside delimiters, the malformed element is
+/// dropped along with its separator, so `[1 +, 2, 3]` recovers `[2, 3]`
+/// and reports one error. Outside of any delimiters, the input is cut at
+/// the error and the incomplete construct left at the end is dropped, so
+/// `1 +` recovers `1`. Delimiters left open at the end of the input are
+/// closed: `[1, 2` recovers `[1, 2]`.
+///
+/// Every skipped region costs a parse of the whole input, so after 20
+/// regions the input is cut at the last one: the tree covers what comes
+/// before it, and errors past it are not reported.
+///
+/// This is intended for tooling (diagnostics, completion) on incomplete input.
+/// Use [`parse_with_options`] when the input must be valid.
+pub fn parse_with_recovery_and_options<'a, 'i>(
+    arena: &'a Bump,
+    source: &'i str,
+    options: ParseOptions,
+) -> PartialParse<'a>
+where
+    'i: 'a,
+{
+    let mut error = match parse_with_options(arena, source, options) {
+        Ok(expr) => {
+            return PartialParse {
+                expr: Some(expr),
+                errors: Vec::new(),
+            };
+        }
+        Err(error) => error,
+    };
+
+    // Attempts that fail are parsed in a scratch arena, so that only the
+    // recovered tree ends up in the caller's.
+    let mut scratch = Bump::new();
+    let mut recovery = Recovery::new(source);
+    let mut errors = Vec::new();
+    for _ in 0..MAX_RECOVERY_ATTEMPTS {
+        let pos = error.span.0.start;
+        let recoverable = !matches!(error.kind, ParseErrorKind::MaxDepthExceeded { .. });
+        let mut give_up = false;
+        if recovery.starts_region(pos) {
+            let end = error.span.0.end.min(source.len());
+            error.span = Span::new(pos.min(end), end);
+            error.source = source.to_string();
+            errors.push(error);
+            give_up = errors.len() == MAX_RECOVERED_REGIONS;
+        }
+        if !recoverable {
+            return PartialParse { expr: None, errors };
+        }
+        if give_up {
+            recovery.cut(pos);
+        } else if !recovery.skip(pos) {
+            return PartialParse { expr: None, errors };
+        }
+
+        let repaired = recovery.repaired_source();
+        scratch.reset();
+        match parse_with_options(&scratch, &repaired, options) {
+            Ok(_) => {
+                tracing::debug!(repaired = %repaired, "Recovered partial expression");
+                let repaired = arena.alloc_str(&repaired);
+                let expr = parse_with_options(arena, repaired, options).ok();
+                return PartialParse { expr, errors };
+            }
+            Err(next) => error = next,
+        }
+    }
+    PartialParse { expr: None, errors }
+}
+
+/// The tokens of an input being repaired by [`parse_with_recovery`], and
+/// which of them have been skipped so far.
+struct Recovery<'s> {
+    source: &'s str,
+    /// Spans of the tokens, without comments.
+    tokens: Vec<Span>,
+    /// Whether each token is still part of the repaired source.
+    kept: Vec<bool>,
+    /// Spans of the comments, kept as they are.
+    comments: Vec<Span>,
+    /// Whether delimiters left open at the end of the input are closed.
+    close_delimiters: bool,
+    /// Where parsing resumed after the last skipped region. Errors up to
+    /// there are part of that region.
+    resumed_at: Option<usize>,
+}
+
+impl<'s> Recovery<'s> {
+    fn new(source: &'s str) -> Self {
+        let (comments, tokens): (Vec<_>, Vec<_>) = tokenize(source)
+            .into_iter()
+            .partition(|(_, kind)| *kind == TokenKind::Comment);
+        let tokens: Vec<Span> = tokens.into_iter().map(|(span, _)| span).collect();
+        Self {
+            source,
+            kept: vec![true; tokens.len()],
+            tokens,
+            comments: comments.into_iter().map(|(span, _)| span).collect(),
+            close_delimiters: false,
+            resumed_at: None,
+        }
+    }
+
+    /// Whether an error at `pos` is in a new malformed region, rather than
+    /// a consequence of skipping the previous one.
+    fn starts_region(&self, pos: usize) -> bool {
+        self.resumed_at.is_none_or(|resumed_at| pos > resumed_at)
+    }
+
+    /// Skips the malformed region around an error at `pos`. Returns `false`
+    /// if there is nothing left to skip.
+    fn skip(&mut self, pos: usize) -> bool {
+        let kept: Vec<usize> = (0..self.tokens.len()).filter(|&i| self.kept[i]).collect();
+        let text = |k: usize| self.tokens[kept[k]].str_of(self.source);
+
+        // The error is at the end of the input: close the delimiters left
+        // open, then drop the incomplete construct one token at a time.
+        let Some(error_at) = kept.iter().position(|&i| self.tokens[i].0.end > pos) else {
+            self.resumed_at = Some(usize::MAX);
+            if !self.close_delimiters {
+                self.close_delimiters = true;
+                if !self.open_delimiters().is_empty() {
+                    return true;
+                }
+            }
+            let Some(&last) = kept.last() else {
+                return false;
+            };
+            self.kept[last] = false;
+            return true;
+        };
+
+        // The innermost group open at the error, and its last separator.
+        let mut groups: Vec<(usize, usize)> = Vec::new();
+        for k in 0..error_at {
+            match text(k) {
+                "(" | "[" | "{" => groups.push((k, k)),
+                ")" | "]" | "}" => {
+                    groups.pop();
+                }
+                "," | ";" => {
+                    if let Some((_, separator)) = groups.last_mut() {
+                        *separator = k;
+                    }
+                }
+                _ => {}
+            }
+        }
+        // Where the malformed element ends, at the same nesting level.
+        let mut depth = 0;
+        let mut end = kept.len();
+        for k in error_at..kept.len() {
+            match text(k) {
+                "(" | "[" | "{" => depth += 1,
+                ")" | "]" | "}" | "," | ";" if depth == 0 => {
+                    end = k;
+                    break;
+                }
+                ")" | "]" | "}" => depth -= 1,
+                _ => {}
+            }
+        }
+
+        let region = match groups.last() {
+            // Outside of any delimiters, cut the input at the error.
+            None => error_at..kept.len(),
+            Some(&(_, separator)) if separator + 1 < end => {
+                // Drop the whole element, with the separator following it.
+                let with_separator = end < kept.len() && text(end) == ",";
+                separator + 1..end + usize::from(with_separator)
+            }
+            // A separator or closing delimiter with nothing to drop before
+            // it: drop the separator, or the whole group.
+            Some(&(opener, _)) => match text(error_at) {
+                ")" | "]" | "}" => opener..error_at + 1,
+                _ => error_at..error_at + 1,
+            },
+        };
+        for k in region.clone() {
+            self.kept[kept[k]] = false;
+        }
+        self.resumed_at = Some(
+            kept.get(region.end)
+                .map_or(usize::MAX, |&i| self.tokens[i].0.start),
+        );
+        true
+    }
+
+    /// Drops every token from an error at `pos` to the end of the input.
+    fn cut(&mut self, pos: usize) {
+        for (span, kept) in self.tokens.iter().zip(&mut self.kept) {
+            if span.0.end > pos {
+                *kept = false;
+            }
+        }
+        self.resumed_at = Some(usize::MAX);
+    }
+
+    /// Spans of the tokens still part of the repaired source.
+    fn kept_tokens(&self) -> impl Iterator<Item = &Span> {
+        self.tokens
+            .iter()
+            .zip(&self.kept)
+            .filter(|(_, kept)| **kept)
+            .map(|(span, _)| span)
+    }
+
+    /// The closing delimiters of the groups left open by the kept tokens,
+    /// innermost first.
+    fn open_delimiters(&self) -> Vec<char> {
+        let mut open = Vec::new();
+        for span in self.kept_tokens() {
+            match span.str_of(self.source) {
+                "(" => open.push(')'),
+                "[" => open.push(']'),
+                "{" => open.push('}'),
+                ")" | "]" | "}" => {
+                    open.pop();
+                }
+                _ => {}
+            }
+        }
+        open.reverse();
+        open
+    }
+
+    /// The input with the skipped tokens replaced by spaces, followed by
+    /// the closing delimiters once they are needed.
+    fn repaired_source(&self) -> String {
+        let mut bytes = self.source.as_bytes().to_vec();
+        for (span, kept) in self.tokens.iter().zip(&self.kept) {
+            if !kept {
+                bytes[span.0.clone()].fill(b' ');
+            }
+        }
+        let mut repaired =
+            String::from_utf8(bytes).expect("tokens start and end at character boundaries");
+        if self.close_delimiters {
+            let closing = self.open_delimiters();
+            // A trailing comment would swallow delimiters on its line.
+            let last_kept = self.kept_tokens().last().map_or(0, |span| span.0.end);
+            if !closing.is_empty() && self.comments.iter().any(|c| c.0.start >= last_kept) {
+                repaired.push('\n');
+            }
+            repaired.extend(closing);
+        }
+        repaired
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -1143,7 +2059,6 @@ mod tests {
     }
 
     #[test]
-    #[ignore = "Grouped expression spans are incorrect - they exclude parentheses"]
     fn test_grouped_expression_span_bug() {
         let arena = Bump::new();
         let input = "1 + (2 + 3) * 4";
@@ -1161,10 +2076,9 @@ mod tests {
             assert_eq!(ann.snippet(ann.span_of(left).unwrap()), "1");
             assert_eq!(ann.span_of(left), Some(Span::new(0, 1)));
 
-            // Right operand "(2 + 3) * 4" should span 4..15
-            // But currently the grouped expression spans exclude the parentheses,
-            // so it spans 5..11 instead of 4..11
-            assert_eq!(ann.snippet(ann.span_of(right).unwrap()), "(2 + 3 * 4");
+            // Right operand "(2 + 3) * 4" spans 4..15, including the
+            // parentheses of its left operand
+            assert_eq!(ann.snippet(ann.span_of(right).unwrap()), "(2 + 3) * 4");
             assert_eq!(ann.span_of(right), Some(Span::new(4, 15)));
 
             // The multiplication
@@ -1174,10 +2088,10 @@ mod tests {
                 right: mul_right,
             } = *right
             {
-                // The grouped "(2 + 3)" should span 4..11 (including parens)
-                // But currently it spans 5..10 (excluding parens)
-                assert_eq!(ann.snippet(ann.span_of(mul_left).unwrap()), "(2 + 3)");
-                assert_eq!(ann.span_of(mul_left), Some(Span::new(4, 11)));
+                // The grouped expression has no node of its own: "2 + 3"
+                // keeps the span of the content, 5..10
+                assert_eq!(ann.snippet(ann.span_of(mul_left).unwrap()), "2 + 3");
+                assert_eq!(ann.span_of(mul_left), Some(Span::new(5, 10)));
 
                 // The "4" should span 14..15
                 assert_eq!(ann.snippet(ann.span_of(mul_right).unwrap()), "4");
@@ -1499,11 +2413,12 @@ mod tests {
                         }))
                     ),
                 ],
+                type_aliases: &[],
             }
         );
 
         assert_eq!(parsed.ann.span_of(parsed.expr), Some(Span::new(0, 28)));
-        let Expr::Where { expr, bindings } = parsed.expr else {
+        let Expr::Where { expr, bindings, .. } = parsed.expr else {
             panic!("Expected Where expression");
         };
         assert_eq!(parsed.ann.span_of(expr), Some(Span::new(0, 5)));
@@ -1597,12 +2512,86 @@ mod tests {
                         }))
                     ),
                 ],
+                type_aliases: &[],
             }
         );
 
         assert_eq!(parsed.ann.span_of(parsed.expr), Some(Span::new(0, 29)));
     }
 
+    #[test]
+    fn test_where_expr_with_type_aliases() {
+        let arena = Bump::new();
+        let input = "x as Ids where { type Id = Int, x = [1], type Ids = Array[Id] }";
+        let parsed = parse(&arena, input).unwrap();
+
+        let Expr::Where {
+            bindings,
+            type_aliases,
+            ..
+        } = parsed.expr
+        else {
+            panic!("Expected Where expression");
+        };
+        assert_eq!(bindings.len(), 1);
+        assert_eq!(bindings[0].0, "x");
+        assert_eq!(
+            *type_aliases,
+            [
+                ("Id", TypeExpr::Path("Int")),
+                (
+                    "Ids",
+                    TypeExpr::Parametrized {
+                        path: "Array",
+                        params: &[TypeExpr::Path("Id")],
+                    }
+                ),
+            ]
+        );
+
+        // `type` is only a keyword at the start of a where item
+        let parsed = parse(&arena, "typeX where { typeX = 1, type = 2 }").unwrap();
+        let Expr::Where {
+            bindings,
+            type_aliases,
+            ..
+        } = parsed.expr
+        else {
+            panic!("Expected Where expression");
+        };
+        assert_eq!(bindings.len(), 2);
+        assert!(type_aliases.is_empty());
+    }
+
+    #[test]
+    fn test_type_declarations() {
+        let arena = Bump::new();
+        let input = "@name(\"p\") type Id = Int; type Ids = Array[Id];\nxs as Ids";
+        let parsed = parse(&arena, input).unwrap();
+
+        assert_eq!(parsed.attributes.len(), 1);
+        assert_eq!(
+            *parsed.type_aliases,
+            [
+                ("Id", TypeExpr::Path("Int")),
+                (
+                    "Ids",
+                    TypeExpr::Parametrized {
+                        path: "Array",
+                        params: &[TypeExpr::Path("Id")],
+                    }
+                ),
+            ]
+        );
+        assert!(matches!(parsed.expr, Expr::Cast { .. }));
+
+        // The `;` is required, and `type` alone is still an identifier
+        assert!(parse(&arena, "type Id = Int 1").is_err());
+        let parsed = parse(&arena, "type").unwrap();
+        assert!(parsed.type_aliases.is_empty());
+        assert_eq!(*parsed.expr, Expr::Ident("type"));
+    }
+
     #[test]
     fn test_function_call() {
         let arena = Bump::new();
@@ -1665,6 +2654,95 @@ mod tests {
         assert_eq!(parsed.ann.span_of(index), Some(Span::new(4, 6)));
     }
 
+    #[test]
+    fn test_attributes() {
+        let arena = Bump::new();
+        let input = "@priority(10) @name(\"spam-filter\")\n@weight(-0.5) @enabled\nscore > 3";
+        let parsed = parse(&arena, input).unwrap();
+
+        let attributes: Vec<_> = parsed
+            .attributes
+            .iter()
+            .map(|attribute| (attribute.name, attribute.value.clone()))
+            .collect();
+        assert_eq!(
+            attributes,
+            vec![
+                ("priority", AttributeValue::Int(10)),
+                ("name", AttributeValue::Str("spam-filter")),
+                ("weight", AttributeValue::Float(-0.5)),
+                ("enabled", AttributeValue::Bool(true)),
+            ]
+        );
+        assert_eq!(parsed.attributes[0].span, Span::new(0, 13));
+        assert!(matches!(parsed.expr, Expr::Comparison { .. }));
+        assert_eq!(parsed.ann.span_of(parsed.expr), Some(Span::new(58, 67)));
+
+        let parsed = parse(&arena, "1 + 2").unwrap();
+        assert!(parsed.attributes.is_empty());
+    }
+
+    #[test]
+    fn test_attribute_errors() {
+        let arena = Bump::new();
+        // Duplicate names
+        assert!(parse(&arena, "@priority(1) @priority(2) 1").is_err());
+        // Values must be plain literals
+        assert!(parse(&arena, "@priority(1 + 2) 1").is_err());
+        assert!(parse(&arena, "@timeout(5`s`) 1").is_err());
+        // Attributes only lead the source
+        assert!(parse(&arena, "1 @priority(1)").is_err());
+        assert!(parse(&arena, "@priority(1)").is_err());
+    }
+
+    #[test]
+    fn test_slice_access() {
+        let arena = Bump::new();
+        let parsed = parse(&arena, "arr[1:-1]").unwrap();
+        let Expr::Slice {
+            value,
+            start: Some(start),
+            end: Some(end),
+        } = parsed.expr
+        else {
+            panic!("Expected Slice expression with both bounds");
+        };
+        assert_eq!(parsed.ann.span_of(parsed.expr), Some(Span::new(0, 9)));
+        assert_eq!(parsed.ann.span_of(value), Some(Span::new(0, 3)));
+        assert_eq!(parsed.ann.span_of(start), Some(Span::new(4, 5)));
+        assert_eq!(parsed.ann.span_of(end), Some(Span::new(6, 8)));
+
+        let parsed = parse(&arena, "arr[:2]").unwrap();
+        assert!(matches!(
+            parsed.expr,
+            Expr::Slice {
+                start: None,
+                end: Some(_),
+                ..
+            }
+        ));
+
+        let parsed = parse(&arena, "arr[2:]").unwrap();
+        assert!(matches!(
+            parsed.expr,
+            Expr::Slice {
+                start: Some(_),
+                end: None,
+                ..
+            }
+        ));
+
+        let parsed = parse(&arena, "arr[:]").unwrap();
+        assert!(matches!(
+            parsed.expr,
+            Expr::Slice {
+                start: None,
+                end: None,
+                ..
+            }
+        ));
+    }
+
     #[test]
     fn test_attr_access() {
         let arena = Bump::new();
@@ -2446,8 +3524,13 @@ mod tests {
                 }))
             }
         );
-        // Note: Grouped expressions exclude parentheses in spans
-        assert_eq!(parsed.ann.span_of(parsed.expr), Some(Span::new(0, 11)));
+        // The operand's parentheses are part of the `some` expression
+        assert_eq!(parsed.ann.span_of(parsed.expr), Some(Span::new(0, 12)));
+        let Expr::Option { inner: Some(inner) } = parsed.expr else {
+            panic!("Expected option expression, got {:?}", parsed.expr);
+        };
+        // ... but the grouped expression only spans their content
+        assert_eq!(parsed.ann.span_of(inner), Some(Span::new(6, 11)));
     }
 
     #[test]
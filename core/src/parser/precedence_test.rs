@@ -1,4 +1,8 @@
-use crate::parser::Expr;
+use crate::ToString;
+use crate::parser::{
+    ComparisonOp, Expr, InfixOperator, OperatorPrecedence, ParseOptions, PrecedenceError, UnaryOp,
+    parse_with_options,
+};
 use bumpalo::Bump;
 
 use super::parser::parse;
@@ -35,6 +39,30 @@ fn test_multiplication_vs_division() {
     );
 }
 
+#[test]
+fn test_modulo_vs_multiplication_and_addition() {
+    let arena = Bump::new();
+    assert_eq!(ast(&arena, "a * b % c"), ast(&arena, "(a * b) % c"));
+    assert_eq!(ast(&arena, "a % b / c"), ast(&arena, "(a % b) / c"));
+    assert_eq!(ast(&arena, "a + b % c"), ast(&arena, "a + (b % c)"));
+    assert_eq!(ast(&arena, "a % b ^ c"), ast(&arena, "a % (b ^ c)"));
+}
+
+#[test]
+fn test_bitwise_precedence() {
+    let arena = Bump::new();
+    // Shifts bind looser than arithmetic
+    assert_eq!(ast(&arena, "a << b + c"), ast(&arena, "a << (b + c)"));
+    // `&` binds tighter than `|` and `xor`, which bind tighter than comparisons
+    assert_eq!(ast(&arena, "a | b & c"), ast(&arena, "a | (b & c)"));
+    assert_eq!(ast(&arena, "a xor b & c"), ast(&arena, "a xor (b & c)"));
+    assert_eq!(ast(&arena, "a & b << c"), ast(&arena, "a & (b << c)"));
+    assert_eq!(ast(&arena, "a & b == c"), ast(&arena, "(a & b) == c"));
+    assert_eq!(ast(&arena, "a | b xor c"), ast(&arena, "(a | b) xor c"));
+    // Shifts are not confused with comparisons
+    assert_eq!(ast(&arena, "a >> b < c"), ast(&arena, "(a >> b) < c"));
+}
+
 #[test]
 fn test_addition_vs_multiplication() {
     let arena = Bump::new();
@@ -162,6 +190,57 @@ fn test_where_vs_binary() {
     );
 }
 
+#[test]
+fn test_coalesce_precedence() {
+    let arena = Bump::new();
+    // `??` binds looser than arithmetic and tighter than comparisons
+    assert_eq!(ast(&arena, "a ?? b + c"), ast(&arena, "a ?? (b + c)"));
+    assert_eq!(ast(&arena, "a ?? b == c"), ast(&arena, "(a ?? b) == c"));
+    assert_eq!(
+        ast(&arena, "a ?? b otherwise c"),
+        ast(&arena, "(a ?? b) otherwise c")
+    );
+    // Right-associative
+    assert_eq!(ast(&arena, "a ?? b ?? c"), ast(&arena, "a ?? (b ?? c)"));
+}
+
+#[test]
+fn test_pipe_desugars_to_call() {
+    let arena = Bump::new();
+    assert_eq!(ast(&arena, "x |> f"), ast(&arena, "f(x)"));
+    assert_eq!(ast(&arena, "x |> f(1, 2)"), ast(&arena, "f(x, 1, 2)"));
+    assert_eq!(ast(&arena, "x |> f()"), ast(&arena, "f(x)"));
+    assert_eq!(
+        ast(&arena, "x |> Math.Abs |> Math.Sqrt"),
+        ast(&arena, "Math.Sqrt(Math.Abs(x))")
+    );
+}
+
+#[test]
+fn test_pipe_precedence() {
+    let arena = Bump::new();
+    // `|>` binds looser than arithmetic and `??`, and tighter than comparisons
+    assert_eq!(ast(&arena, "a + b |> f"), ast(&arena, "(a + b) |> f"));
+    assert_eq!(ast(&arena, "a ?? b |> f"), ast(&arena, "(a ?? b) |> f"));
+    assert_eq!(ast(&arena, "a | b |> f"), ast(&arena, "(a | b) |> f"));
+    assert_eq!(ast(&arena, "a |> f == b"), ast(&arena, "(a |> f) == b"));
+    // Left-associative
+    assert_eq!(ast(&arena, "a |> f |> g"), ast(&arena, "(a |> f) |> g"));
+}
+
+#[test]
+fn test_pattern_as_binds_loosest() {
+    let arena = Bump::new();
+    assert_eq!(
+        ast(&arena, "x match { some y as z -> z }"),
+        ast(&arena, "x match { (some y) as z -> z }")
+    );
+    assert_ne!(
+        ast(&arena, "x match { some y as z -> z }"),
+        ast(&arena, "x match { some (y as z) -> z }")
+    );
+}
+
 #[test]
 fn test_otherwise_vs_binary() {
     let arena = Bump::new();
@@ -469,6 +548,31 @@ fn test_not_in_operator_basic() {
     );
 }
 
+#[test]
+fn test_not_in_is_a_single_comparison() {
+    let arena = Bump::new();
+    // `a not in b` is one comparison, not a negated `in`
+    assert_eq!(
+        ast(&arena, "a not in b"),
+        &Expr::Comparison {
+            op: ComparisonOp::NotIn,
+            left: &Expr::Ident("a"),
+            right: &Expr::Ident("b"),
+        }
+    );
+    assert_eq!(
+        ast(&arena, "not (a in b)"),
+        &Expr::Unary {
+            op: UnaryOp::Not,
+            expr: &Expr::Comparison {
+                op: ComparisonOp::In,
+                left: &Expr::Ident("a"),
+                right: &Expr::Ident("b"),
+            },
+        }
+    );
+}
+
 #[test]
 fn test_in_vs_logical_and() {
     let arena = Bump::new();
@@ -607,3 +711,121 @@ fn test_some_vs_cast() {
     // Postfix `as` should bind tighter than prefix `some`
     assert_eq!(ast(&arena, "some a as String"), ast(&arena, "some (a as String)"));
 }
+
+// Like `ast`, but parsing with the given operator precedence.
+fn ast_with<'a>(
+    arena: &'a Bump,
+    source: &'a str,
+    operator_precedence: OperatorPrecedence,
+) -> &'a Expr<'a> {
+    let options = ParseOptions {
+        operator_precedence,
+        ..Default::default()
+    };
+    parse_with_options(arena, source, options)
+        .unwrap_or_else(|e| panic!("Expression parsing failed: {}\n{}", source, e))
+        .expr
+}
+
+#[test]
+fn test_default_precedence_table() {
+    let precedence = OperatorPrecedence::default();
+    assert_eq!(precedence, OperatorPrecedence::DEFAULT);
+    assert!(precedence.level(InfixOperator::Add) < precedence.level(InfixOperator::Mul));
+    assert!(precedence.level(InfixOperator::And) < precedence.level(InfixOperator::Eq));
+    assert_eq!(precedence.iter().count(), InfixOperator::ALL.len());
+}
+
+#[test]
+fn test_precedence_override_changes_grouping() {
+    let arena = Bump::new();
+    let source = "a == b and c";
+    assert_eq!(ast(&arena, source), ast(&arena, "(a == b) and c"));
+
+    // Moving `and` above the comparisons makes it bind tighter.
+    let precedence = OperatorPrecedence::DEFAULT
+        .with_level(InfixOperator::And, 65)
+        .unwrap();
+    assert_eq!(
+        ast_with(&arena, source, precedence),
+        ast(&arena, "a == (b and c)")
+    );
+    // Other operators are unaffected.
+    assert_eq!(
+        ast_with(&arena, "a + b * c or d", precedence),
+        ast(&arena, "(a + (b * c)) or d")
+    );
+}
+
+#[test]
+fn test_precedence_override_merges_levels() {
+    let arena = Bump::new();
+    // With `+` at the level of `*`, they group left to right.
+    let precedence = OperatorPrecedence::DEFAULT
+        .with_level(InfixOperator::Add, 130)
+        .unwrap();
+    assert_eq!(
+        ast_with(&arena, "a + b * c", precedence),
+        ast(&arena, "(a + b) * c")
+    );
+    assert_eq!(
+        ast_with(&arena, "a * b + c", precedence),
+        ast(&arena, "(a * b) + c")
+    );
+}
+
+#[test]
+fn test_precedence_override_around_prefix_operators() {
+    let arena = Bump::new();
+    // Moving `+` below `not` makes `not` apply to the left operand only.
+    let precedence = OperatorPrecedence::DEFAULT
+        .with_level(InfixOperator::Add, 45)
+        .unwrap();
+    assert_eq!(
+        ast_with(&arena, "not a + b", precedence),
+        ast(&arena, "(not a) + b")
+    );
+    assert_eq!(ast(&arena, "not a + b"), ast(&arena, "not (a + b)"));
+}
+
+#[test]
+fn test_precedence_override_rejects_prefix_levels() {
+    for level in [
+        OperatorPrecedence::IF_LEVEL,
+        OperatorPrecedence::NOT_LEVEL,
+        OperatorPrecedence::NEGATION_LEVEL,
+    ] {
+        assert_eq!(
+            OperatorPrecedence::DEFAULT.with_level(InfixOperator::Mul, level),
+            Err(PrecedenceError::PrefixLevel {
+                operator: InfixOperator::Mul,
+                level
+            })
+        );
+    }
+}
+
+#[test]
+fn test_precedence_override_rejects_mixed_associativity() {
+    // `^` is right-associative and `*` left-associative, so `a ^ b * c`
+    // would have no single grouping.
+    let err = OperatorPrecedence::DEFAULT
+        .with_level(InfixOperator::Pow, 130)
+        .unwrap_err();
+    assert!(matches!(
+        err,
+        PrecedenceError::MixedAssociativity {
+            operator: InfixOperator::Pow,
+            level: 130,
+            ..
+        }
+    ));
+    assert!(err.to_string().contains("different associativity"));
+
+    // Operators with the same associativity can share a level.
+    assert!(
+        OperatorPrecedence::DEFAULT
+            .with_level(InfixOperator::Coalesce, 10)
+            .is_ok()
+    );
+}
@@ -76,10 +76,19 @@ rule_examples! {
         "{add = (a, b) => a + b}.add(1, 2)",
     ],
     index_op => ["arr[0]", "matrix[1][2]", "map[key]"],
+    attribute => ["@priority(10) 1", "@name(\"spam\") @enabled x", "@weight(-0.5)\nx"],
+    slice_op => ["arr[1:3]", "arr[:3]", "arr[2:]", "arr[:]", "arr[-2:][0]"],
     neg => ["- 1", "-a"],
     not => ["not true", "-not x"],
     in_op => ["5 in [1, 2, 3]", "\"lo\" in \"hello\"", "key in map"],
     not_in => ["5 not in [1, 2, 3]", "\"x\" not in \"hello\"", "key not in map"],
+    coalesce => ["a ?? 0", "m[k] ?? \"\" otherwise \"\"", "a ?? b ?? c"],
+    pipe => ["x |> f", "x |> Math.Abs |> Math.Sqrt", "x |> f(1, 2)"],
+    pattern_as => [
+        "x match { some y as whole -> whole, none -> none }",
+        "x match { (some y) as whole -> y, _ -> 0 }",
+        "x match { some (1 as one) -> one, _ -> 0 }",
+    ],
     otherwise_op => ["1 / 0 otherwise -1", "map[key] otherwise \"\""],
     type_expr => [
         "value as Integer",
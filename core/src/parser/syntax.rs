@@ -30,6 +30,13 @@ impl<'a, T> AnnotatedSource<'a, T> {
     pub fn snippet(&self, span: Span) -> &str {
         &self.source[span.0]
     }
+    /// Span of `text` if it is a slice of `source`, such as an identifier
+    /// taken from the parsed tree.
+    pub fn span_of_str(&self, text: &str) -> Option<Span> {
+        let start = (text.as_ptr() as usize).checked_sub(self.source.as_ptr() as usize)?;
+        let end = start + text.len();
+        (end <= self.source.len()).then_some(Span(start..end))
+    }
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -60,7 +67,27 @@ pub enum BinaryOp {
     Sub,
     Mul,
     Div,
+    Mod,
     Pow,
+    BitwiseAnd,
+    BitwiseOr,
+    BitwiseXor,
+    ShiftLeft,
+    ShiftRight,
+}
+
+impl BinaryOp {
+    /// Returns true for the bitwise operators, which only apply to `Int`.
+    pub fn is_bitwise(self) -> bool {
+        matches!(
+            self,
+            BinaryOp::BitwiseAnd
+                | BinaryOp::BitwiseOr
+                | BinaryOp::BitwiseXor
+                | BinaryOp::ShiftLeft
+                | BinaryOp::ShiftRight
+        )
+    }
 }
 
 #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Serialize)]
@@ -0,0 +1,240 @@
+//! Parser-only tokenization for syntax highlighting.
+//!
+//! Semantic highlighting normally relies on a successful analysis, but editors
+//! still want basic highlighting while the document is broken. This module
+//! provides a lightweight scanner that never fails: malformed regions (such as
+//! unterminated strings or stray characters) are reported as tokens and
+//! scanning resumes right after them.
+//!
+//! The scanner mirrors the lexical structure of `expression.pest`, but it does
+//! not validate the syntax of the expression.
+
+use crate::Vec;
+use crate::parser::Span;
+
+/// Lexical category of a token, as needed for syntax highlighting.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum TokenKind {
+    /// Reserved word (`if`, `where`, `and`, `true`, `none`, ...).
+    Keyword,
+    /// Unquoted or quoted (`` `name` ``) identifier.
+    Identifier,
+    /// Integer or float literal.
+    Number,
+    /// String or format string literal (possibly unterminated).
+    String,
+    /// Bytes literal (possibly unterminated).
+    Bytes,
+    /// Symbolic operator (`+`, `==`, `=>`, `->`, ...).
+    Operator,
+    /// Delimiters and separators (`(`, `]`, `,`, `.`, `:`, `@`, ...).
+    Punctuation,
+    /// Line comment.
+    Comment,
+    /// Character that cannot start any token.
+    Unknown,
+}
+
+/// Reserved words, kept in sync with `reserved_words` in `expression.pest`.
+const KEYWORDS: &[&str] = &[
+    "if",
+    "then",
+    "else",
+    "true",
+    "false",
+    "not",
+    "and",
+    "or",
+    "xor",
+    "in",
+    "otherwise",
+    "as",
+    "where",
+    "match",
+    "some",
+    "none",
+];
+
+/// Symbolic operators, longest first so that `==` wins over `=`.
+const OPERATORS: &[&str] = &[
+    "=>", "->", "==", "!=", "<=", ">=", "<<", ">>", "<", ">", "=", "+", "-", "*", "/", "%", "^",
+    "??", "|>", "&", "|",
+];
+
+/// Tokenizes `source` without parsing it.
+///
+/// Returns the spans and kinds of all tokens in source order, skipping
+/// whitespace. This never fails, which makes it suitable as a fallback for
+/// syntax highlighting when parsing or analysis does not succeed.
+pub fn tokenize(source: &str) -> Vec<(Span, TokenKind)> {
+    let mut scanner = Scanner {
+        source,
+        bytes: source.as_bytes(),
+        pos: 0,
+        tokens: Vec::new(),
+    };
+    scanner.run();
+    scanner.tokens
+}
+
+struct Scanner<'a> {
+    source: &'a str,
+    bytes: &'a [u8],
+    pos: usize,
+    tokens: Vec<(Span, TokenKind)>,
+}
+
+impl Scanner<'_> {
+    fn run(&mut self) {
+        while let Some(c) = self.peek(0) {
+            let start = self.pos;
+            let kind = match c {
+                b' ' | b'\t' | b'\n' | b'\r' => {
+                    self.pos += 1;
+                    continue;
+                }
+                b'/' if self.peek(1) == Some(b'/') => {
+                    self.skip_while(|c| c != b'\n');
+                    TokenKind::Comment
+                }
+                b'"' | b'\'' => {
+                    self.scan_quoted(c);
+                    TokenKind::String
+                }
+                b'f' if matches!(self.peek(1), Some(b'"' | b'\'')) => {
+                    self.pos += 1;
+                    self.scan_quoted(self.bytes[self.pos]);
+                    TokenKind::String
+                }
+                b'b' if matches!(self.peek(1), Some(b'"' | b'\'')) => {
+                    self.pos += 1;
+                    self.scan_quoted(self.bytes[self.pos]);
+                    TokenKind::Bytes
+                }
+                b'`' => self.scan_quoted_ident(),
+                b'0'..=b'9' => {
+                    self.scan_number();
+                    TokenKind::Number
+                }
+                b'.' if self.peek(1).is_some_and(|c| c.is_ascii_digit())
+                    && !self.follows_operand() =>
+                {
+                    self.scan_number();
+                    TokenKind::Number
+                }
+                c if c.is_ascii_alphabetic() || c == b'_' => {
+                    self.skip_while(|c| c.is_ascii_alphanumeric() || c == b'_');
+                    if KEYWORDS.contains(&&self.source[start..self.pos]) {
+                        TokenKind::Keyword
+                    } else {
+                        TokenKind::Identifier
+                    }
+                }
+                b'(' | b')' | b'[' | b']' | b'{' | b'}' | b',' | b'.' | b':' | b'@' => {
+                    self.pos += 1;
+                    TokenKind::Punctuation
+                }
+                _ => self.scan_operator(),
+            };
+            self.tokens.push((Span::new(start, self.pos), kind));
+        }
+    }
+
+    fn peek(&self, offset: usize) -> Option<u8> {
+        self.bytes.get(self.pos + offset).copied()
+    }
+
+    fn skip_while(&mut self, predicate: impl Fn(u8) -> bool) {
+        while self.peek(0).is_some_and(&predicate) {
+            self.pos += 1;
+        }
+    }
+
+    /// Whether the previous token ends an operand, in which case a `.` is a
+    /// field access rather than the start of a float like `.5`.
+    fn follows_operand(&self) -> bool {
+        match self.tokens.last() {
+            Some((span, TokenKind::Punctuation)) => {
+                matches!(&self.source[span.0.clone()], ")" | "]" | "}")
+            }
+            Some((_, kind)) => !matches!(
+                kind,
+                TokenKind::Operator | TokenKind::Keyword | TokenKind::Comment
+            ),
+            None => false,
+        }
+    }
+
+    /// Scans a string-like literal starting at the opening quote.
+    ///
+    /// Unterminated literals extend to the end of the input.
+    fn scan_quoted(&mut self, quote: u8) {
+        self.pos += 1;
+        while let Some(c) = self.peek(0) {
+            self.pos += 1;
+            if c == b'\\' {
+                if self.peek(0).is_some() {
+                    self.pos += 1;
+                }
+            } else if c == quote {
+                return;
+            }
+        }
+    }
+
+    /// Scans a quoted identifier. A lone backtick is reported as unknown.
+    fn scan_quoted_ident(&mut self) -> TokenKind {
+        let start = self.pos;
+        self.pos += 1;
+        self.skip_while(|c| {
+            c.is_ascii_alphanumeric() || matches!(c, b'-' | b'_' | b'.' | b':' | b'/')
+        });
+        if self.pos > start + 1 && self.peek(0) == Some(b'`') {
+            self.pos += 1;
+            TokenKind::Identifier
+        } else {
+            self.pos = start + 1;
+            TokenKind::Unknown
+        }
+    }
+
+    /// Scans an integer or float literal, including radix prefixes, digit
+    /// separators, fractional parts and exponents.
+    fn scan_number(&mut self) {
+        let is_radix =
+            self.peek(0) == Some(b'0') && matches!(self.peek(1), Some(b'b' | b'o' | b'x'));
+        if is_radix {
+            self.pos += 2;
+            self.skip_while(|c| c.is_ascii_hexdigit() || c == b'_');
+            return;
+        }
+        self.skip_while(|c| c.is_ascii_digit() || c == b'_');
+        if self.peek(0) == Some(b'.') && !self.peek(1).is_some_and(|c| c.is_ascii_alphabetic()) {
+            self.pos += 1;
+            self.skip_while(|c| c.is_ascii_digit() || c == b'_');
+        }
+        if matches!(self.peek(0), Some(b'e' | b'E')) {
+            let digits_at = if matches!(self.peek(1), Some(b'+' | b'-')) {
+                2
+            } else {
+                1
+            };
+            if self.peek(digits_at).is_some_and(|c| c.is_ascii_digit()) {
+                self.pos += digits_at;
+                self.skip_while(|c| c.is_ascii_digit() || c == b'_');
+            }
+        }
+    }
+
+    fn scan_operator(&mut self) -> TokenKind {
+        let rest = &self.source[self.pos..];
+        if let Some(op) = OPERATORS.iter().find(|op| rest.starts_with(*op)) {
+            self.pos += op.len();
+            return TokenKind::Operator;
+        }
+        // Consume a whole (possibly multi-byte) character.
+        let len = rest.chars().next().map_or(1, char::len_utf8);
+        self.pos += len;
+        TokenKind::Unknown
+    }
+}
@@ -0,0 +1,183 @@
+use super::parser::parse;
+use super::tokens::{TokenKind, tokenize};
+use crate::Vec;
+use bumpalo::Bump;
+
+/// Tokenizes `source` and returns each token's text alongside its kind.
+fn tokens(source: &str) -> Vec<(&str, TokenKind)> {
+    tokenize(source)
+        .into_iter()
+        .map(|(span, kind)| (span.str_of(source), kind))
+        .collect()
+}
+
+#[test]
+fn test_tokenize_simple_expression() {
+    use TokenKind::*;
+    assert_eq!(
+        tokens("if x > 1 then \"big\" else b'small'"),
+        [
+            ("if", Keyword),
+            ("x", Identifier),
+            (">", Operator),
+            ("1", Number),
+            ("then", Keyword),
+            ("\"big\"", String),
+            ("else", Keyword),
+            ("b'small'", Bytes),
+        ]
+    );
+}
+
+#[test]
+fn test_tokenize_numbers() {
+    use TokenKind::*;
+    assert_eq!(
+        tokens("1_000 0xFF 3.14 1e-10 2.5E+3"),
+        [
+            ("1_000", Number),
+            ("0xFF", Number),
+            ("3.14", Number),
+            ("1e-10", Number),
+            ("2.5E+3", Number),
+        ]
+    );
+    assert_eq!(
+        tokens("x * .5"),
+        [("x", Identifier), ("*", Operator), (".5", Number)]
+    );
+    // A dot after an operand is a field access, not a float.
+    assert_eq!(
+        tokens("a.b[0].c"),
+        [
+            ("a", Identifier),
+            (".", Punctuation),
+            ("b", Identifier),
+            ("[", Punctuation),
+            ("0", Number),
+            ("]", Punctuation),
+            (".", Punctuation),
+            ("c", Identifier),
+        ]
+    );
+}
+
+#[test]
+fn test_tokenize_operators_and_punctuation() {
+    use TokenKind::*;
+    assert_eq!(
+        tokens("(x, y) => x == y"),
+        [
+            ("(", Punctuation),
+            ("x", Identifier),
+            (",", Punctuation),
+            ("y", Identifier),
+            (")", Punctuation),
+            ("=>", Operator),
+            ("x", Identifier),
+            ("==", Operator),
+            ("y", Identifier),
+        ]
+    );
+    assert_eq!(
+        tokens("x match { some v -> v, none -> 0 }"),
+        [
+            ("x", Identifier),
+            ("match", Keyword),
+            ("{", Punctuation),
+            ("some", Keyword),
+            ("v", Identifier),
+            ("->", Operator),
+            ("v", Identifier),
+            (",", Punctuation),
+            ("none", Keyword),
+            ("->", Operator),
+            ("0", Number),
+            ("}", Punctuation),
+        ]
+    );
+    assert_eq!(
+        tokens("a % b"),
+        [("a", Identifier), ("%", Operator), ("b", Identifier)]
+    );
+    assert_eq!(
+        tokens("a & b | c xor d << 1 >> 2 |> f"),
+        [
+            ("a", Identifier),
+            ("&", Operator),
+            ("b", Identifier),
+            ("|", Operator),
+            ("c", Identifier),
+            ("xor", Keyword),
+            ("d", Identifier),
+            ("<<", Operator),
+            ("1", Number),
+            (">>", Operator),
+            ("2", Number),
+            ("|>", Operator),
+            ("f", Identifier),
+        ]
+    );
+}
+
+#[test]
+fn test_tokenize_identifiers_and_keywords() {
+    use TokenKind::*;
+    assert_eq!(
+        tokens("iffy `my-var` format f\"{x}\" // note"),
+        [
+            ("iffy", Identifier),
+            ("`my-var`", Identifier),
+            ("format", Identifier),
+            ("f\"{x}\"", String),
+            ("// note", Comment),
+        ]
+    );
+}
+
+#[test]
+fn test_tokenize_unparsable_source() {
+    use TokenKind::*;
+    let arena = Bump::new();
+    let source = "if x and then 1 + $ where { y = }";
+    assert!(parse(&arena, source).is_err());
+
+    assert_eq!(
+        tokens(source),
+        [
+            ("if", Keyword),
+            ("x", Identifier),
+            ("and", Keyword),
+            ("then", Keyword),
+            ("1", Number),
+            ("+", Operator),
+            ("$", Unknown),
+            ("where", Keyword),
+            ("{", Punctuation),
+            ("y", Identifier),
+            ("=", Operator),
+            ("}", Punctuation),
+        ]
+    );
+}
+
+#[test]
+fn test_tokenize_unterminated_literals() {
+    use TokenKind::*;
+    assert_eq!(
+        tokens("1 + \"abc"),
+        [("1", Number), ("+", Operator), ("\"abc", String)]
+    );
+    assert_eq!(tokens("` x"), [("`", Unknown), ("x", Identifier)]);
+    assert_eq!(tokens("é"), [("é", Unknown)]);
+}
+
+#[test]
+fn test_tokenize_spans_cover_source_positions() {
+    let source = "a  +\n  b";
+    let spans: Vec<_> = tokenize(source)
+        .into_iter()
+        .map(|(span, _)| span.0)
+        .collect();
+    assert_eq!(spans, [0..1, 3..4, 7..8]);
+}
@@ -0,0 +1,71 @@
+//! Language versions and the optional syntax they enable.
+//!
+//! Syntax added after the first release is gated on a [`LanguageVersion`],
+//! so hosts that store expressions can pin the version they were written
+//! against and keep rejecting newer constructs. The grammar always accepts
+//! every production; the parser checks the version before building the
+//! corresponding expression.
+
+/// Version of the Melbi syntax accepted by the parser.
+///
+/// Versions are ordered: each one accepts everything the previous one did.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub enum LanguageVersion {
+    /// The original syntax.
+    V1,
+    /// Adds the pipe operator (`value |> f`) and type aliases, either in
+    /// `where` blocks (`where { type Point = Record[x: Int, y: Int] }`) or
+    /// declared before the expression (`type Id = Int; 1 as Id`).
+    #[default]
+    V2,
+}
+
+impl LanguageVersion {
+    /// The most recent version, accepting all syntax.
+    pub const LATEST: LanguageVersion = LanguageVersion::V2;
+
+    /// Whether this version accepts `feature`.
+    pub fn supports(self, feature: SyntaxFeature) -> bool {
+        self >= feature.since()
+    }
+}
+
+impl core::fmt::Display for LanguageVersion {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            LanguageVersion::V1 => write!(f, "1"),
+            LanguageVersion::V2 => write!(f, "2"),
+        }
+    }
+}
+
+/// Syntax that is only accepted from a given [`LanguageVersion`] on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SyntaxFeature {
+    /// `value |> f` and `value |> f(args...)`.
+    Pipe,
+    /// `type Name = ...` entries in `where` blocks.
+    WhereTypeAlias,
+    /// `type Name = ...;` declarations before the expression.
+    TypeDeclaration,
+}
+
+impl SyntaxFeature {
+    /// The first version accepting this feature.
+    pub fn since(self) -> LanguageVersion {
+        match self {
+            SyntaxFeature::Pipe
+            | SyntaxFeature::WhereTypeAlias
+            | SyntaxFeature::TypeDeclaration => LanguageVersion::V2,
+        }
+    }
+
+    /// Human-readable description, used in error messages.
+    pub fn description(self) -> &'static str {
+        match self {
+            SyntaxFeature::Pipe => "The pipe operator `|>`",
+            SyntaxFeature::WhereTypeAlias => "Type aliases in `where` blocks",
+            SyntaxFeature::TypeDeclaration => "Type alias declarations",
+        }
+    }
+}
@@ -187,6 +187,16 @@ impl<'a, T: Copy> ScopeStack<'a, T> {
         Ok(())
     }
 
+    /// Number of scopes on the stack.
+    pub fn depth(&self) -> usize {
+        self.scopes.len()
+    }
+
+    /// Pop scopes until only `depth` remain.
+    pub fn truncate(&mut self, depth: usize) {
+        self.scopes.truncate(depth);
+    }
+
     /// Look up a name, searching scopes from innermost to outermost.
     ///
     /// Returns the first matching value found, or None if not found in any scope.
@@ -1,19 +1,27 @@
+use super::NativeFunction;
 use crate::{
-    evaluator::ExecutionError,
+    evaluator::{ExecutionError, ResourceExceededError, RuntimeError},
+    format,
+    parser::Span,
     types::{
-        Type,
         manager::TypeManager,
         traits::{TypeKind, TypeView},
     },
     values::{
-        dynamic::{RecordBuilder, Value},
+        dynamic::Value,
         from_raw::TypeError,
-        function::{AnnotatedFunction, FfiContext, Function},
+        function::{AnnotatedFunction, FfiContext},
     },
 };
-use alloc::{vec, vec::Vec};
+use alloc::{string::String, vec, vec::Vec};
 use bumpalo::Bump;
 
+/// Maximum length of arrays built from a requested size, like `Array.Repeat`.
+///
+/// Larger requests fail with a resource-exceeded error instead of exhausting
+/// memory.
+pub const MAX_GENERATED_ARRAY_LEN: usize = 1 << 20;
+
 // ============================================================================
 // Basic Functions
 // ============================================================================
@@ -51,6 +59,58 @@ fn array_is_empty<'types, 'arena>(
     Ok(Value::bool(ctx.type_mgr(), arr.is_empty()))
 }
 
+// ============================================================================
+// Search Functions
+// ============================================================================
+
+/// Check if an array contains a value
+///
+/// Polymorphic - works with arrays of any element type. Elements are
+/// compared with the same equality as the `in` operator, so
+/// `Array.Contains(arr, x)` is `x in arr`.
+///
+/// # Examples
+/// - `Array.Contains([1, 2, 3], 2)` → `true`
+/// - `Array.Contains(["a"], "b")` → `false`
+/// - `Array.Contains([], 1)` → `false`
+fn array_contains<'types, 'arena>(
+    ctx: &FfiContext<'types, 'arena>,
+    args: &[Value<'types, 'arena>],
+) -> Result<Value<'types, 'arena>, ExecutionError> {
+    debug_assert_eq!(args.len(), 2);
+    let arr = args[0].as_array().expect("Expected array");
+    let found = arr.iter().any(|elem| elem == args[1]);
+    Ok(Value::bool(ctx.type_mgr(), found))
+}
+
+/// Find the index of the first element equal to a value
+///
+/// Polymorphic - works with arrays of any element type. Elements are
+/// compared with the same equality as the `in` operator.
+///
+/// # Examples
+/// - `Array.IndexOf([10, 20, 30], 20)` → `some 1`
+/// - `Array.IndexOf([1, 2, 1], 1)` → `some 0` (first match)
+/// - `Array.IndexOf([1, 2, 3], 4)` → `none`
+fn array_index_of<'types, 'arena>(
+    ctx: &FfiContext<'types, 'arena>,
+    args: &[Value<'types, 'arena>],
+) -> Result<Value<'types, 'arena>, ExecutionError> {
+    debug_assert_eq!(args.len(), 2);
+    let arr = args[0].as_array().expect("Expected array");
+    let type_mgr = ctx.type_mgr();
+
+    let index = arr
+        .iter()
+        .position(|elem| elem == args[1])
+        .map(|index| Value::int(type_mgr, index as i64));
+
+    Ok(
+        Value::optional(ctx.arena(), type_mgr.option(type_mgr.int()), index)
+            .expect("Type error in Array.IndexOf: option construction failed"),
+    )
+}
+
 // ============================================================================
 // Slice Functions
 // ============================================================================
@@ -276,6 +336,199 @@ fn array_reverse<'types, 'arena>(
     )
 }
 
+/// Build an array with `n` copies of a value
+///
+/// Polymorphic - works with values of any type.
+///
+/// # Edge Cases
+///
+/// - `n == 0` returns an empty array
+/// - Fails with an invalid argument error if `n < 0`
+/// - Fails with a resource-exceeded error if `n` is above
+///   [`MAX_GENERATED_ARRAY_LEN`]
+///
+/// # Examples
+/// - `Array.Repeat(7, 3)` → `[7, 7, 7]`
+/// - `Array.Repeat("a", 0)` → `[]`
+fn array_repeat<'types, 'arena>(
+    ctx: &FfiContext<'types, 'arena>,
+    args: &[Value<'types, 'arena>],
+) -> Result<Value<'types, 'arena>, ExecutionError> {
+    debug_assert_eq!(args.len(), 2);
+    let value = args[0];
+    let count = args[1].as_int().expect("Expected int");
+
+    let error = |kind| ExecutionError {
+        kind,
+        // TODO: Add proper source and span information for native functions
+        source: String::new(),
+        span: Span(0..0),
+    };
+    if count < 0 {
+        return Err(error(
+            RuntimeError::InvalidArgument {
+                message: format!("Array.Repeat count must not be negative, got {}", count),
+            }
+            .into(),
+        ));
+    }
+    let len = count as usize;
+    if len > MAX_GENERATED_ARRAY_LEN {
+        return Err(error(
+            ResourceExceededError::ArrayTooLarge {
+                len,
+                max_len: MAX_GENERATED_ARRAY_LEN,
+            }
+            .into(),
+        ));
+    }
+
+    let elements = vec![value; len];
+    Ok(
+        Value::array(ctx.arena(), ctx.type_mgr().array(value.ty), &elements)
+            .expect("Type error in Array.Repeat: array construction failed"),
+    )
+}
+
+/// Split an array into non-overlapping chunks of size `n`
+///
+/// Polymorphic - works with arrays of any element type.
+///
+/// # Edge Cases
+///
+/// - The last chunk is shorter if the length is not a multiple of `n`
+/// - Empty array: `Array.Chunk([], 2)` → `[]`
+/// - Fails with an invalid argument error if `n <= 0`
+///
+/// # Examples
+/// - `Array.Chunk([1,2,3,4,5], 2)` → `[[1, 2], [3, 4], [5]]`
+/// - `Array.Chunk([1,2,3], 5)` → `[[1, 2, 3]]`
+fn array_chunk<'types, 'arena>(
+    ctx: &FfiContext<'types, 'arena>,
+    args: &[Value<'types, 'arena>],
+) -> Result<Value<'types, 'arena>, ExecutionError> {
+    debug_assert_eq!(args.len(), 2);
+    let arr = args[0].as_array().expect("Expected array");
+    let size = positive_size(&args[1], "Array.Chunk")?;
+
+    let elements: Vec<Value<'types, 'arena>> = arr.iter().collect();
+    nested_array(ctx, &args[0], elements.chunks(size))
+}
+
+/// Collect all overlapping windows of size `n` from an array
+///
+/// Polymorphic - works with arrays of any element type.
+///
+/// # Edge Cases
+///
+/// - If `n` is larger than the array length, returns an empty array
+/// - Fails with an invalid argument error if `n <= 0`
+///
+/// # Examples
+/// - `Array.Windows([1,2,3], 2)` → `[[1, 2], [2, 3]]`
+/// - `Array.Windows([1,2,3], 4)` → `[]`
+fn array_windows<'types, 'arena>(
+    ctx: &FfiContext<'types, 'arena>,
+    args: &[Value<'types, 'arena>],
+) -> Result<Value<'types, 'arena>, ExecutionError> {
+    debug_assert_eq!(args.len(), 2);
+    let arr = args[0].as_array().expect("Expected array");
+    let size = positive_size(&args[1], "Array.Windows")?;
+
+    let elements: Vec<Value<'types, 'arena>> = arr.iter().collect();
+    nested_array(ctx, &args[0], elements.windows(size))
+}
+
+/// Extract a strictly positive size argument, failing with `InvalidArgument` otherwise.
+fn positive_size(value: &Value<'_, '_>, function: &str) -> Result<usize, ExecutionError> {
+    let size = value.as_int().expect("Expected int");
+    if size <= 0 {
+        return Err(ExecutionError {
+            kind: RuntimeError::InvalidArgument {
+                message: format!("{} size must be positive, got {}", function, size),
+            }
+            .into(),
+            // TODO: Add proper source and span information for native functions
+            source: String::new(),
+            span: Span(0..0),
+        });
+    }
+    Ok(size as usize)
+}
+
+/// Build an `Array[Array[T]]` from groups of elements of `array`.
+fn nested_array<'types, 'arena, 'a>(
+    ctx: &FfiContext<'types, 'arena>,
+    array: &Value<'types, 'arena>,
+    groups: impl Iterator<Item = &'a [Value<'types, 'arena>]>,
+) -> Result<Value<'types, 'arena>, ExecutionError>
+where
+    'types: 'a,
+    'arena: 'a,
+{
+    let inner_ty = array.ty;
+    let groups: Vec<Value<'types, 'arena>> = groups
+        .map(|group| {
+            Value::array(ctx.arena(), inner_ty, group)
+                .expect("Type error in nested array: inner array construction failed")
+        })
+        .collect();
+    Ok(
+        Value::array(ctx.arena(), ctx.type_mgr().array(inner_ty), &groups)
+            .expect("Type error in nested array: outer array construction failed"),
+    )
+}
+
+/// Build a map from an array of key/value records
+///
+/// Polymorphic - works with any hashable key type and any value type.
+///
+/// # Edge Cases
+///
+/// - Duplicate keys: the last entry wins
+/// - Empty array: `Array.ToMap([])` → `{}`
+///
+/// # Examples
+/// - `Array.ToMap([{key = 1, value = 10}, {key = 2, value = 20}])` → `{1: 10, 2: 20}`
+/// - `Array.ToMap([{key = "a", value = 1}, {key = "a", value = 2}])` → `{"a": 2}`
+fn array_to_map<'types, 'arena>(
+    ctx: &FfiContext<'types, 'arena>,
+    args: &[Value<'types, 'arena>],
+) -> Result<Value<'types, 'arena>, ExecutionError> {
+    debug_assert_eq!(args.len(), 1);
+    let arr = args[0].as_array().expect("Expected array");
+
+    // Get key and value types from the entry record type
+    let (key_ty, value_ty) = match args[0].ty.view() {
+        TypeKind::Array(entry_ty) => match entry_ty.view() {
+            TypeKind::Record(mut fields) => {
+                let (_, key_ty) = fields.next().expect("Expected key field");
+                let (_, value_ty) = fields.next().expect("Expected value field");
+                (key_ty, value_ty)
+            }
+            _ => panic!("Expected record type"),
+        },
+        _ => panic!("Expected array type"),
+    };
+
+    let pairs: Vec<(Value<'types, 'arena>, Value<'types, 'arena>)> = arr
+        .iter()
+        .map(|entry| {
+            let record = entry.as_record().expect("Expected record");
+            (
+                record.get("key").expect("Expected key field"),
+                record.get("value").expect("Expected value field"),
+            )
+        })
+        .collect();
+
+    // Value::map keeps the last value for duplicate keys
+    Ok(
+        Value::map(ctx.arena(), ctx.type_mgr().map(key_ty, value_ty), &pairs)
+            .expect("Type error in Array.ToMap: map construction failed"),
+    )
+}
+
 // ============================================================================
 // Higher-Order Functions
 // ============================================================================
@@ -314,64 +567,194 @@ fn array_map<'types, 'arena>(
     )
 }
 
-// ============================================================================
-// Package Registration
-// ============================================================================
+/// Keep the elements of an array for which a predicate returns true
+///
+/// Polymorphic - filters Array[T] using a predicate (T) => Bool.
+///
+/// # Examples
+/// - `Array.Filter([1, 2, 3, 4], (x) => x % 2 == 0)` → `[2, 4]`
+/// - `Array.Filter(["a", "", "b"], (s) => s != "")` → `["a", "b"]`
+/// - `Array.Filter([], (x) => true)` → `[]`
+fn array_filter<'types, 'arena>(
+    ctx: &FfiContext<'types, 'arena>,
+    args: &[Value<'types, 'arena>],
+) -> Result<Value<'types, 'arena>, ExecutionError> {
+    debug_assert_eq!(args.len(), 2);
+    let arr = args[0].as_array().expect("Expected array");
+    let func = args[1].as_function().expect("Expected function");
+
+    let mut results = Vec::new();
+    for elem in arr.iter() {
+        let keep = unsafe { func.call_unchecked(ctx, &[elem]) }?;
+        if keep.as_bool().expect("Expected bool") {
+            results.push(elem);
+        }
+    }
 
-/// Helper struct to wrap a native function pointer and implement the Function trait
-struct NativeFunction<'types> {
-    name: &'static str,
-    ty: &'types Type<'types>,
-    ptr: fn(
-        &FfiContext<'types, 'types>,
-        &[Value<'types, 'types>],
-    ) -> Result<Value<'types, 'types>, ExecutionError>,
+    Ok(Value::array(ctx.arena(), args[0].ty, &results)
+        .expect("Type error in Array.Filter: array construction failed"))
 }
 
-impl<'types> Function<'types, 'types> for NativeFunction<'types> {
-    fn ty(&self) -> &'types Type<'types> {
-        self.ty
-    }
+/// Split an array into the elements for which a predicate returns true and
+/// the rest
+///
+/// Polymorphic - partitions Array[T] using a predicate (T) => Bool. Both
+/// groups keep the original order.
+///
+/// # Examples
+/// - `Array.Partition([1, 2, 3, 4], (x) => x % 2 == 0)` → `{matched = [2, 4], rest = [1, 3]}`
+/// - `Array.Partition(["a", ""], (s) => s != "")` → `{matched = ["a"], rest = [""]}`
+/// - `Array.Partition([], (x) => true)` → `{matched = [], rest = []}`
+fn array_partition<'types, 'arena>(
+    ctx: &FfiContext<'types, 'arena>,
+    args: &[Value<'types, 'arena>],
+) -> Result<Value<'types, 'arena>, ExecutionError> {
+    debug_assert_eq!(args.len(), 2);
+    let arr = args[0].as_array().expect("Expected array");
+    let func = args[1].as_function().expect("Expected function");
 
-    unsafe fn call_unchecked(
-        &self,
-        ctx: &FfiContext<'types, 'types>,
-        args: &[Value<'types, 'types>],
-    ) -> Result<Value<'types, 'types>, ExecutionError> {
-        (self.ptr)(ctx, args)
+    let mut matched = Vec::new();
+    let mut rest = Vec::new();
+    for elem in arr.iter() {
+        let is_match = unsafe { func.call_unchecked(ctx, &[elem]) }?;
+        if is_match.as_bool().expect("Expected bool") {
+            matched.push(elem);
+        } else {
+            rest.push(elem);
+        }
     }
+
+    let matched = Value::array(ctx.arena(), args[0].ty, &matched)
+        .expect("Type error in Array.Partition: array construction failed");
+    let rest = Value::array(ctx.arena(), args[0].ty, &rest)
+        .expect("Type error in Array.Partition: array construction failed");
+    Ok(Value::record_builder(ctx.type_mgr())
+        .field("matched", matched)
+        .field("rest", rest)
+        .build(ctx.arena())
+        .expect("Type error in Array.Partition: record construction failed"))
 }
 
-impl<'types> AnnotatedFunction<'types> for NativeFunction<'types> {
-    fn name(&self) -> &str {
-        self.name
-    }
+/// Fold an array into a single value
+///
+/// Polymorphic - combines the elements of Array[T] into an accumulator of
+/// type A, starting from `init` and applying (A, T) => A left to right.
+///
+/// # Examples
+/// - `Array.Reduce([1, 2, 3], 0, (acc, x) => acc + x)` → `6`
+/// - `Array.Reduce(["a", "b"], "", (acc, s) => f"{acc}{s}")` → `"ab"`
+/// - `Array.Reduce([], 10, (acc, x) => acc + x)` → `10`
+fn array_reduce<'types, 'arena>(
+    ctx: &FfiContext<'types, 'arena>,
+    args: &[Value<'types, 'arena>],
+) -> Result<Value<'types, 'arena>, ExecutionError> {
+    debug_assert_eq!(args.len(), 3);
+    let arr = args[0].as_array().expect("Expected array");
+    let func = args[2].as_function().expect("Expected function");
 
-    fn location(&self) -> (&str, &str, &str, u32, u32) {
-        (
-            "melbi-core",
-            env!("CARGO_PKG_VERSION"),
-            file!(),
-            line!(),
-            column!(),
-        )
+    let mut acc = args[1];
+    for elem in arr.iter() {
+        acc = unsafe { func.call_unchecked(ctx, &[acc, elem]) }?;
     }
+    Ok(acc)
+}
 
-    fn doc(&self) -> Option<&str> {
-        None
-    }
+// ============================================================================
+// Sorting
+// ============================================================================
+
+/// Sort an array in natural order
+///
+/// Polymorphic - works with arrays of any element type that supports Ord
+/// (the types `<` accepts). The sort is stable.
+///
+/// # Examples
+/// - `Array.Sort([3, 1, 2])` → `[1, 2, 3]`
+/// - `Array.Sort(["b", "a"])` → `["a", "b"]`
+/// - `Array.Sort([])` → `[]`
+fn array_sort<'types, 'arena>(
+    ctx: &FfiContext<'types, 'arena>,
+    args: &[Value<'types, 'arena>],
+) -> Result<Value<'types, 'arena>, ExecutionError> {
+    debug_assert_eq!(args.len(), 1);
+    let arr = args[0].as_array().expect("Expected array");
+
+    let mut result: Vec<Value<'types, 'arena>> = arr.iter().collect();
+    result.sort();
+
+    Ok(Value::array(ctx.arena(), args[0].ty, &result)
+        .expect("Type error in Array.Sort: array construction failed"))
+}
 
-    fn register(
-        self,
-        arena: &'types Bump,
-        builder: RecordBuilder<'types, 'types>,
-    ) -> Result<RecordBuilder<'types, 'types>, TypeError> {
-        let name = self.name;
-        let fn_value = Value::function(arena, self)?;
-        Ok(builder.field(name, fn_value))
+/// Sort an array with a comparator
+///
+/// Polymorphic - sorts Array[T] using `before: (T, T) => Bool`, which
+/// returns whether its first argument goes before its second. The sort is
+/// stable: elements neither of which goes before the other keep their
+/// relative order. Comparators that are not consistent never fail, but
+/// leave the elements in an unspecified order.
+///
+/// # Examples
+/// - `Array.SortBy([1, 3, 2], (a, b) => a > b)` → `[3, 2, 1]`
+/// - `Array.SortBy(["bb", "a"], (a, b) => String.Len(a) < String.Len(b))` → `["a", "bb"]`
+fn array_sort_by<'types, 'arena>(
+    ctx: &FfiContext<'types, 'arena>,
+    args: &[Value<'types, 'arena>],
+) -> Result<Value<'types, 'arena>, ExecutionError> {
+    debug_assert_eq!(args.len(), 2);
+    let arr = args[0].as_array().expect("Expected array");
+    let func = args[1].as_function().expect("Expected function");
+
+    let elements: Vec<Value<'types, 'arena>> = arr.iter().collect();
+    let result = merge_sort(elements, |a, b| {
+        let before = unsafe { func.call_unchecked(ctx, &[*a, *b]) }?;
+        Ok(before.as_bool().expect("Expected bool"))
+    })?;
+
+    Ok(Value::array(ctx.arena(), args[0].ty, &result)
+        .expect("Type error in Array.SortBy: array construction failed"))
+}
+
+/// Stable bottom-up merge sort, taking an element from the right run only
+/// when it goes `before` the next one in the left run.
+///
+/// Unlike the standard library sorts, it never panics on inconsistent
+/// comparisons, and it stops at the first comparison that fails.
+fn merge_sort<T: Copy, E>(
+    mut elements: Vec<T>,
+    mut before: impl FnMut(&T, &T) -> Result<bool, E>,
+) -> Result<Vec<T>, E> {
+    let len = elements.len();
+    let mut buffer = Vec::with_capacity(len);
+    let mut width = 1;
+    while width < len {
+        buffer.clear();
+        for start in (0..len).step_by(2 * width) {
+            let middle = (start + width).min(len);
+            let end = (start + 2 * width).min(len);
+            let (mut left, mut right) = (start, middle);
+            while left < middle && right < end {
+                if before(&elements[right], &elements[left])? {
+                    buffer.push(elements[right]);
+                    right += 1;
+                } else {
+                    buffer.push(elements[left]);
+                    left += 1;
+                }
+            }
+            buffer.extend_from_slice(&elements[left..middle]);
+            buffer.extend_from_slice(&elements[right..end]);
+        }
+        core::mem::swap(&mut elements, &mut buffer);
+        width *= 2;
     }
+    Ok(elements)
 }
 
+// ============================================================================
+// Package Registration
+// ============================================================================
+
 pub fn build_array_package<'arena>(
     arena: &'arena Bump,
     type_mgr: &'arena TypeManager<'arena>,
@@ -399,6 +782,26 @@ pub fn build_array_package<'arena>(
     }
     .register(arena, builder)?;
 
+    // Contains: forall T. (Array<T>, T) -> Bool
+    let t = type_mgr.fresh_type_var();
+    let contains_ty = type_mgr.function(&[type_mgr.array(t), t], type_mgr.bool());
+    builder = NativeFunction {
+        name: "Contains",
+        ty: contains_ty,
+        ptr: array_contains,
+    }
+    .register(arena, builder)?;
+
+    // IndexOf: forall T. (Array<T>, T) -> Option<Int>
+    let t = type_mgr.fresh_type_var();
+    let index_of_ty = type_mgr.function(&[type_mgr.array(t), t], type_mgr.option(type_mgr.int()));
+    builder = NativeFunction {
+        name: "IndexOf",
+        ty: index_of_ty,
+        ptr: array_index_of,
+    }
+    .register(arena, builder)?;
+
     // Slice: forall T. (Array<T>, Int, Int) -> Array<T>
     let t = type_mgr.fresh_type_var();
     let slice_ty = type_mgr.function(
@@ -457,6 +860,54 @@ pub fn build_array_package<'arena>(
     }
     .register(arena, builder)?;
 
+    // Repeat: forall T. (T, Int) -> Array<T>
+    let t = type_mgr.fresh_type_var();
+    let repeat_ty = type_mgr.function(&[t, type_mgr.int()], type_mgr.array(t));
+    builder = NativeFunction {
+        name: "Repeat",
+        ty: repeat_ty,
+        ptr: array_repeat,
+    }
+    .register(arena, builder)?;
+
+    // Chunk: forall T. (Array<T>, Int) -> Array<Array<T>>
+    let t = type_mgr.fresh_type_var();
+    let chunk_ty = type_mgr.function(
+        &[type_mgr.array(t), type_mgr.int()],
+        type_mgr.array(type_mgr.array(t)),
+    );
+    builder = NativeFunction {
+        name: "Chunk",
+        ty: chunk_ty,
+        ptr: array_chunk,
+    }
+    .register(arena, builder)?;
+
+    // Windows: forall T. (Array<T>, Int) -> Array<Array<T>>
+    let t = type_mgr.fresh_type_var();
+    let windows_ty = type_mgr.function(
+        &[type_mgr.array(t), type_mgr.int()],
+        type_mgr.array(type_mgr.array(t)),
+    );
+    builder = NativeFunction {
+        name: "Windows",
+        ty: windows_ty,
+        ptr: array_windows,
+    }
+    .register(arena, builder)?;
+
+    // ToMap: forall K, V. Array<{key: K, value: V}> -> Map<K, V>
+    let k = type_mgr.fresh_type_var();
+    let v = type_mgr.fresh_type_var();
+    let entry_ty = type_mgr.record(vec![("key", k), ("value", v)]);
+    let to_map_ty = type_mgr.function(&[type_mgr.array(entry_ty)], type_mgr.map(k, v));
+    builder = NativeFunction {
+        name: "ToMap",
+        ty: to_map_ty,
+        ptr: array_to_map,
+    }
+    .register(arena, builder)?;
+
     // Map: forall T, U. (Array<T>, (T) => U) -> Array<U>
     let t = type_mgr.fresh_type_var();
     let u = type_mgr.fresh_type_var();
@@ -469,6 +920,66 @@ pub fn build_array_package<'arena>(
     }
     .register(arena, builder)?;
 
+    // Filter: forall T. (Array<T>, (T) => Bool) -> Array<T>
+    let t = type_mgr.fresh_type_var();
+    let predicate_ty = type_mgr.function(&[t], type_mgr.bool());
+    let filter_ty = type_mgr.function(&[type_mgr.array(t), predicate_ty], type_mgr.array(t));
+    builder = NativeFunction {
+        name: "Filter",
+        ty: filter_ty,
+        ptr: array_filter,
+    }
+    .register(arena, builder)?;
+
+    // Partition: forall T. (Array<T>, (T) => Bool) -> {matched: Array<T>, rest: Array<T>}
+    let t = type_mgr.fresh_type_var();
+    let predicate_ty = type_mgr.function(&[t], type_mgr.bool());
+    let groups_ty = type_mgr.record(vec![
+        ("matched", type_mgr.array(t)),
+        ("rest", type_mgr.array(t)),
+    ]);
+    let partition_ty = type_mgr.function(&[type_mgr.array(t), predicate_ty], groups_ty);
+    builder = NativeFunction {
+        name: "Partition",
+        ty: partition_ty,
+        ptr: array_partition,
+    }
+    .register(arena, builder)?;
+
+    // Reduce: forall T, A. (Array<T>, A, (A, T) => A) -> A
+    let t = type_mgr.fresh_type_var();
+    let a = type_mgr.fresh_type_var();
+    let step_ty = type_mgr.function(&[a, t], a);
+    let reduce_ty = type_mgr.function(&[type_mgr.array(t), a, step_ty], a);
+    builder = NativeFunction {
+        name: "Reduce",
+        ty: reduce_ty,
+        ptr: array_reduce,
+    }
+    .register(arena, builder)?;
+
+    // Sort: forall T: Ord. Array<T> -> Array<T>
+    // The Ord constraint is added by the analyzer at each call.
+    let t = type_mgr.fresh_type_var();
+    let sort_ty = type_mgr.function(&[type_mgr.array(t)], type_mgr.array(t));
+    builder = NativeFunction {
+        name: "Sort",
+        ty: sort_ty,
+        ptr: array_sort,
+    }
+    .register(arena, builder)?;
+
+    // SortBy: forall T. (Array<T>, (T, T) => Bool) -> Array<T>
+    let t = type_mgr.fresh_type_var();
+    let before_ty = type_mgr.function(&[t, t], type_mgr.bool());
+    let sort_by_ty = type_mgr.function(&[type_mgr.array(t), before_ty], type_mgr.array(t));
+    builder = NativeFunction {
+        name: "SortBy",
+        ty: sort_by_ty,
+        ptr: array_sort_by,
+    }
+    .register(arena, builder)?;
+
     builder.build(arena)
 }
 
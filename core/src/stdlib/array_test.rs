@@ -21,12 +21,23 @@ fn test_array_package_builds() {
     assert!(!record.is_empty());
     assert!(record.get("Len").is_some());
     assert!(record.get("IsEmpty").is_some());
+    assert!(record.get("Contains").is_some());
+    assert!(record.get("IndexOf").is_some());
     assert!(record.get("Slice").is_some());
     assert!(record.get("Concat").is_some());
     assert!(record.get("Flatten").is_some());
     assert!(record.get("Zip").is_some());
     assert!(record.get("Reverse").is_some());
     assert!(record.get("Map").is_some());
+    assert!(record.get("Chunk").is_some());
+    assert!(record.get("Windows").is_some());
+    assert!(record.get("ToMap").is_some());
+    assert!(record.get("Repeat").is_some());
+    assert!(record.get("Filter").is_some());
+    assert!(record.get("Partition").is_some());
+    assert!(record.get("Reduce").is_some());
+    assert!(record.get("Sort").is_some());
+    assert!(record.get("SortBy").is_some());
 }
 
 /// Evaluates a Melbi expression with all standard packages (Array, Math, String).
@@ -126,6 +137,74 @@ fn test_is_empty() {
     );
 }
 
+// ============================================================================
+// Contains and IndexOf Tests
+// ============================================================================
+
+#[test]
+fn test_contains() {
+    let arena = Bump::new();
+
+    assert!(
+        eval(&arena, "Array.Contains([1, 2, 3], 2)")
+            .unwrap()
+            .as_bool()
+            .unwrap()
+    );
+    assert!(
+        !eval(&arena, "Array.Contains([\"a\", \"b\"], \"c\")")
+            .unwrap()
+            .as_bool()
+            .unwrap()
+    );
+    assert!(
+        !eval(&arena, "Array.Contains([], 1)")
+            .unwrap()
+            .as_bool()
+            .unwrap()
+    );
+
+    // Same answer as the `in` operator, including for nested values
+    assert!(
+        eval(
+            &arena,
+            "Array.Contains(xs, [2]) == ([2] in xs) where { xs = [[1], [2]] }"
+        )
+        .unwrap()
+        .as_bool()
+        .unwrap()
+    );
+}
+
+#[test]
+fn test_index_of() {
+    let arena = Bump::new();
+
+    let result = eval(&arena, "Array.IndexOf([10, 20, 30], 20)").unwrap();
+    let index = result.as_option().unwrap().unwrap();
+    assert_eq!(index.as_int().unwrap(), 1);
+
+    // First match wins
+    let result = eval(&arena, "Array.IndexOf([1, 2, 1, 2], 2)").unwrap();
+    let index = result.as_option().unwrap().unwrap();
+    assert_eq!(index.as_int().unwrap(), 1);
+
+    let result = eval(&arena, "Array.IndexOf([10, 20, 30], 40)").unwrap();
+    assert!(result.as_option().unwrap().is_none());
+
+    let result = eval(&arena, "Array.IndexOf([], \"a\")").unwrap();
+    assert!(result.as_option().unwrap().is_none());
+}
+
+#[test]
+fn test_contains_and_index_of_type_errors() {
+    let arena = Bump::new();
+
+    // The value must have the array's element type
+    assert!(eval(&arena, "Array.Contains([1, 2], \"1\")").is_err());
+    assert!(eval(&arena, "Array.IndexOf([1, 2], 1.0)").is_err());
+}
+
 // ============================================================================
 // Reverse Tests
 // ============================================================================
@@ -168,6 +247,162 @@ fn test_reverse() {
             .as_bool()
             .unwrap()
     );
+
+    // The original array is left untouched
+    assert!(
+        eval(
+            &arena,
+            "Array.Reverse(xs) == [3, 2, 1] and xs == [1, 2, 3] where { xs = [1, 2, 3] }"
+        )
+        .unwrap()
+        .as_bool()
+        .unwrap()
+    );
+}
+
+// ============================================================================
+// Chunk and Windows Tests
+// ============================================================================
+
+#[test]
+fn test_chunk() {
+    let arena = Bump::new();
+
+    // Last chunk is shorter when the length is not a multiple of n
+    assert!(
+        eval(
+            &arena,
+            "Array.Chunk([1, 2, 3, 4, 5], 2) == [[1, 2], [3, 4], [5]]"
+        )
+        .unwrap()
+        .as_bool()
+        .unwrap()
+    );
+
+    // Chunk size larger than the array
+    assert!(
+        eval(&arena, "Array.Chunk([1, 2, 3], 5) == [[1, 2, 3]]")
+            .unwrap()
+            .as_bool()
+            .unwrap()
+    );
+
+    // Empty array
+    assert!(
+        eval(&arena, "Array.Len(Array.Chunk([], 3)) == 0")
+            .unwrap()
+            .as_bool()
+            .unwrap()
+    );
+}
+
+#[test]
+fn test_windows() {
+    let arena = Bump::new();
+
+    assert!(
+        eval(&arena, "Array.Windows([1, 2, 3], 2) == [[1, 2], [2, 3]]")
+            .unwrap()
+            .as_bool()
+            .unwrap()
+    );
+
+    assert!(
+        eval(
+            &arena,
+            "Array.Windows([\"a\", \"b\"], 1) == [[\"a\"], [\"b\"]]"
+        )
+        .unwrap()
+        .as_bool()
+        .unwrap()
+    );
+
+    // Window larger than the array
+    assert!(
+        eval(&arena, "Array.Len(Array.Windows([1, 2, 3], 4)) == 0")
+            .unwrap()
+            .as_bool()
+            .unwrap()
+    );
+}
+
+#[test]
+fn test_chunk_and_windows_reject_non_positive_size() {
+    let arena = Bump::new();
+
+    for source in [
+        "Array.Chunk([1, 2, 3], 0)",
+        "Array.Chunk([1, 2, 3], -1)",
+        "Array.Windows([1, 2, 3], 0)",
+        "Array.Windows([1, 2, 3], -2)",
+    ] {
+        match eval(&arena, source) {
+            Err(Error::Runtime { diagnostic, .. }) => {
+                assert_eq!(diagnostic.code.as_deref(), Some("R008"), "{}", source);
+            }
+            other => panic!(
+                "{}: expected invalid argument error, got {:?}",
+                source, other
+            ),
+        }
+    }
+
+    // The error can be recovered with `otherwise`
+    assert!(
+        eval(
+            &arena,
+            "Array.Len(Array.Chunk([1, 2], 0) otherwise []) == 0"
+        )
+        .unwrap()
+        .as_bool()
+        .unwrap()
+    );
+}
+
+// ============================================================================
+// Repeat Tests
+// ============================================================================
+
+#[test]
+fn test_repeat() {
+    let arena = Bump::new();
+
+    let result = eval(&arena, "Array.Repeat(7, 3) == [7, 7, 7]").unwrap();
+    assert!(result.as_bool().unwrap());
+
+    let result = eval(&arena, r#"Array.Repeat("ab", 2) == ["ab", "ab"]"#).unwrap();
+    assert!(result.as_bool().unwrap());
+
+    let result = eval(&arena, "Array.Len(Array.Repeat(1.5, 0))").unwrap();
+    assert_eq!(result.as_int().unwrap(), 0);
+}
+
+#[test]
+fn test_repeat_negative_count() {
+    let arena = Bump::new();
+
+    match eval(&arena, "Array.Repeat(1, -1)") {
+        Err(Error::Runtime { diagnostic, .. }) => {
+            assert_eq!(diagnostic.code.as_deref(), Some("R008"));
+        }
+        other => panic!("expected invalid argument error, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_repeat_exceeds_length_limit() {
+    let arena = Bump::new();
+
+    let result = eval(&arena, "Array.Repeat(0, 1000000000)");
+    assert!(
+        matches!(result, Err(Error::ResourceExceeded { .. })),
+        "expected resource exceeded error, got {:?}",
+        result
+    );
+
+    // Resource limits cannot be recovered with `otherwise`
+    let result = eval(&arena, "Array.Repeat(0, 1000000000) otherwise []");
+    assert!(matches!(result, Err(Error::ResourceExceeded { .. })));
 }
 
 // ============================================================================
@@ -356,6 +591,330 @@ fn test_map_runtime_error_propagation() {
     assert!(format!("{:?}", result.err()).contains("IndexOutOfBounds"));
 }
 
+// ============================================================================
+// Filter Tests
+// ============================================================================
+
+#[test]
+fn test_filter() {
+    let arena = Bump::new();
+
+    assert!(
+        eval(
+            &arena,
+            "Array.Filter([1, 2, 3, 4], (x) => x % 2 == 0) == [2, 4]"
+        )
+        .unwrap()
+        .as_bool()
+        .unwrap()
+    );
+
+    // Keeps the original order
+    assert!(
+        eval(
+            &arena,
+            "Array.Filter([\"b\", \"\", \"a\"], (s) => s != \"\") == [\"b\", \"a\"]"
+        )
+        .unwrap()
+        .as_bool()
+        .unwrap()
+    );
+
+    // Nothing matches
+    assert!(
+        eval(&arena, "Array.Filter([1, 2, 3], (x) => x > 5) == []")
+            .unwrap()
+            .as_bool()
+            .unwrap()
+    );
+
+    // Predicate capturing a binding
+    assert!(
+        eval(
+            &arena,
+            "Array.Filter([1, 5, 10], (x) => x >= limit) == [5, 10] where { limit = 5 }"
+        )
+        .unwrap()
+        .as_bool()
+        .unwrap()
+    );
+}
+
+#[test]
+fn test_filter_empty_array() {
+    let arena = Bump::new();
+    assert!(
+        eval(&arena, "Array.IsEmpty(Array.Filter([], (x) => x > 0))")
+            .unwrap()
+            .as_bool()
+            .unwrap()
+    );
+}
+
+#[test]
+fn test_filter_type_errors() {
+    let arena = Bump::new();
+
+    // Predicate must return Bool
+    assert!(
+        format!(
+            "{:?}",
+            eval(&arena, "Array.Filter([1, 2, 3], (x) => x + 1)").err()
+        )
+        .contains("Type mismatch")
+    );
+
+    // Predicate parameter type must match array element type
+    assert!(
+        format!(
+            "{:?}",
+            eval(&arena, "Array.Filter([1, 2, 3], (s) => String.IsEmpty(s))").err()
+        )
+        .contains("Type mismatch")
+    );
+}
+
+// ============================================================================
+// Partition Tests
+// ============================================================================
+
+#[test]
+fn test_partition() {
+    let arena = Bump::new();
+
+    let result = eval(&arena, "Array.Partition([1, 2, 3, 4], (x) => x % 2 == 0)").unwrap();
+    let record = result.as_record().unwrap();
+    assert_eq!(record.get("matched").unwrap().to_string(), "[2, 4]");
+    assert_eq!(record.get("rest").unwrap().to_string(), "[1, 3]");
+
+    // Keeps the original order within each group
+    assert!(
+        eval(
+            &arena,
+            "Array.Partition([\"b\", \"\", \"a\", \"\"], (s) => s != \"\") == {matched = [\"b\", \"a\"], rest = [\"\", \"\"]}"
+        )
+        .unwrap()
+        .as_bool()
+        .unwrap()
+    );
+
+    // Empty array
+    assert!(
+        eval(
+            &arena,
+            "Array.Partition([], (x) => x > 0) == {matched = [], rest = []}"
+        )
+        .unwrap()
+        .as_bool()
+        .unwrap()
+    );
+}
+
+#[test]
+fn test_partition_type_errors() {
+    let arena = Bump::new();
+
+    // Predicate must return Bool
+    assert!(
+        format!(
+            "{:?}",
+            eval(&arena, "Array.Partition([1, 2, 3], (x) => x + 1)").err()
+        )
+        .contains("Type mismatch")
+    );
+}
+
+// ============================================================================
+// Reduce Tests
+// ============================================================================
+
+#[test]
+fn test_reduce() {
+    let arena = Bump::new();
+
+    assert_eq!(
+        eval(&arena, "Array.Reduce([1, 2, 3, 4], 0, (acc, x) => acc + x)")
+            .unwrap()
+            .as_int()
+            .unwrap(),
+        10
+    );
+
+    // Folds left to right
+    assert_eq!(
+        eval(
+            &arena,
+            "Array.Reduce([\"a\", \"b\", \"c\"], \">\", (acc, s) => f\"{acc}{s}\")"
+        )
+        .unwrap()
+        .as_str()
+        .unwrap(),
+        ">abc"
+    );
+
+    // Accumulator of a different type than the elements
+    assert_eq!(
+        eval(
+            &arena,
+            "Array.Reduce([\"a\", \"bb\", \"ccc\"], 0, (acc, s) => acc + String.Len(s))"
+        )
+        .unwrap()
+        .as_int()
+        .unwrap(),
+        6
+    );
+}
+
+#[test]
+fn test_reduce_empty_array_returns_init() {
+    let arena = Bump::new();
+    assert_eq!(
+        eval(&arena, "Array.Reduce([], 10, (acc, x) => acc + x)")
+            .unwrap()
+            .as_int()
+            .unwrap(),
+        10
+    );
+}
+
+#[test]
+fn test_reduce_type_errors() {
+    let arena = Bump::new();
+
+    // Step function must return the accumulator type
+    assert!(
+        format!(
+            "{:?}",
+            eval(&arena, "Array.Reduce([1, 2, 3], 0, (acc, x) => x > acc)").err()
+        )
+        .contains("Type mismatch")
+    );
+
+    // Initial value must match the accumulator type
+    assert!(
+        format!(
+            "{:?}",
+            eval(&arena, "Array.Reduce([1, 2, 3], \"\", (acc, x) => acc + x)").err()
+        )
+        .contains("Type mismatch")
+    );
+}
+
+// ============================================================================
+// Sort Tests
+// ============================================================================
+
+#[test]
+fn test_sort() {
+    let arena = Bump::new();
+
+    assert!(
+        eval(&arena, "Array.Sort([3, 1, 2]) == [1, 2, 3]")
+            .unwrap()
+            .as_bool()
+            .unwrap()
+    );
+    assert!(
+        eval(&arena, "Array.Sort([2.5, -1.0, 0.5]) == [-1.0, 0.5, 2.5]")
+            .unwrap()
+            .as_bool()
+            .unwrap()
+    );
+    assert!(
+        eval(&arena, r#"Array.Sort(["b", "c", "a"]) == ["a", "b", "c"]"#)
+            .unwrap()
+            .as_bool()
+            .unwrap()
+    );
+    assert!(
+        eval(
+            &arena,
+            "Array.Sort([some 2, none, some 1]) == [none, some 1, some 2]"
+        )
+        .unwrap()
+        .as_bool()
+        .unwrap()
+    );
+    assert!(
+        eval(&arena, "Array.IsEmpty(Array.Sort([]))")
+            .unwrap()
+            .as_bool()
+            .unwrap()
+    );
+}
+
+#[test]
+fn test_sort_requires_ord_elements() {
+    let arena = Bump::new();
+
+    let err = eval(&arena, "Array.Sort([{a = 2}, {a = 1}])").unwrap_err();
+    assert!(format!("{:?}", err).contains("does not implement Ord"));
+
+    let err = eval(&arena, "Array.Sort([true, false])").unwrap_err();
+    assert!(format!("{:?}", err).contains("does not implement Ord"));
+}
+
+#[test]
+fn test_sort_by() {
+    let arena = Bump::new();
+
+    assert!(
+        eval(
+            &arena,
+            "Array.SortBy([1, 3, 2], (a, b) => a > b) == [3, 2, 1]"
+        )
+        .unwrap()
+        .as_bool()
+        .unwrap()
+    );
+
+    // Elements can be sorted by a computed key
+    assert!(
+        eval(
+            &arena,
+            r#"Array.SortBy(["ccc", "a", "bb"], (a, b) => String.Len(a) < String.Len(b)) == ["a", "bb", "ccc"]"#
+        )
+        .unwrap()
+        .as_bool()
+        .unwrap()
+    );
+}
+
+#[test]
+fn test_sort_by_is_stable() {
+    let arena = Bump::new();
+
+    // Strings of the same length keep their relative order
+    assert!(
+        eval(
+            &arena,
+            r#"Array.SortBy(["cc", "a", "bb", "d", "ee"], (a, b) => String.Len(a) < String.Len(b)) == ["a", "d", "cc", "bb", "ee"]"#
+        )
+        .unwrap()
+        .as_bool()
+        .unwrap()
+    );
+}
+
+#[test]
+fn test_sort_by_comparator_errors() {
+    let arena = Bump::new();
+
+    // Inconsistent comparators don't fail
+    assert!(
+        eval(
+            &arena,
+            "Array.Len(Array.SortBy([3, 1, 2], (a, b) => true)) == 3"
+        )
+        .unwrap()
+        .as_bool()
+        .unwrap()
+    );
+
+    // Errors raised by the comparator are propagated
+    assert!(eval(&arena, "Array.SortBy([3, 1, 2], (a, b) => a / (b - b) < 0)").is_err());
+}
+
 // ============================================================================
 // Slice Tests
 // ============================================================================
@@ -631,6 +1190,71 @@ fn test_zip_second_empty() {
     );
 }
 
+// ============================================================================
+// ToMap Tests
+// ============================================================================
+
+#[test]
+fn test_to_map() {
+    let arena = Bump::new();
+
+    assert!(
+        eval(
+            &arena,
+            "Array.ToMap([{key = 1, value = 10}, {key = 2, value = 20}]) == {1: 10, 2: 20}"
+        )
+        .unwrap()
+        .as_bool()
+        .unwrap()
+    );
+
+    // Keys and values computed from other data
+    assert!(
+        eval(
+            &arena,
+            "Array.ToMap(Array.Map([\"a\", \"bb\"], (s) => {key = s, value = String.Len(s)}))[\"bb\"] == 2"
+        )
+        .unwrap()
+        .as_bool()
+        .unwrap()
+    );
+
+    // Empty array
+    assert!(
+        eval(&arena, "Array.ToMap([]) == {}")
+            .unwrap()
+            .as_bool()
+            .unwrap()
+    );
+}
+
+#[test]
+fn test_to_map_duplicate_keys_last_wins() {
+    let arena = Bump::new();
+
+    assert!(
+        eval(
+            &arena,
+            "Array.ToMap([{key = 1, value = 10}, {key = 2, value = 20}, {key = 1, value = 30}]) == {1: 30, 2: 20}"
+        )
+        .unwrap()
+        .as_bool()
+        .unwrap()
+    );
+}
+
+#[test]
+fn test_to_map_requires_hashable_keys() {
+    let arena = Bump::new();
+
+    let result = eval(&arena, "Array.ToMap([{key = (x) => x, value = 1}])");
+    assert!(
+        matches!(result, Err(Error::Compilation { .. })),
+        "Expected compilation error, got {:?}",
+        result
+    );
+}
+
 // ============================================================================
 // Composition and Chaining Tests
 // ============================================================================
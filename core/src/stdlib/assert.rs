@@ -0,0 +1,105 @@
+//! Assertion Builtins
+//!
+//! Provides `Assert` and `Expect`, top-level functions that let rules check
+//! their own invariants. A failed check raises a runtime error carrying the
+//! given message, which can be caught with `otherwise`.
+
+use super::NativeFunction;
+use crate::{
+    evaluator::{ExecutionError, RuntimeError},
+    parser::Span,
+    types::manager::TypeManager,
+    values::{dynamic::Value, from_raw::TypeError, function::FfiContext},
+};
+use alloc::string::{String, ToString};
+use bumpalo::Bump;
+
+/// Fail with `AssertionFailed` unless `cond` holds.
+fn check(cond: &Value<'_, '_>, message: &Value<'_, '_>) -> Result<(), ExecutionError> {
+    if cond.as_bool().expect("Expected bool") {
+        return Ok(());
+    }
+    Err(ExecutionError {
+        kind: RuntimeError::AssertionFailed {
+            message: message.as_str().expect("Expected string").to_string(),
+        }
+        .into(),
+        // TODO: Add proper source and span information for native functions
+        source: String::new(),
+        span: Span(0..0),
+    })
+}
+
+/// Check a condition, failing with a message if it does not hold
+///
+/// Returns `true` when the condition holds.
+///
+/// # Examples
+/// - `Assert(1 < 2, "ordered")` → `true`
+/// - `Assert(false, "bad")` → error: `Assertion failed: bad`
+/// - `Assert(false, "bad") otherwise false` → `false`
+fn assert<'types, 'arena>(
+    ctx: &FfiContext<'types, 'arena>,
+    args: &[Value<'types, 'arena>],
+) -> Result<Value<'types, 'arena>, ExecutionError> {
+    debug_assert_eq!(args.len(), 2);
+    check(&args[0], &args[1])?;
+    Ok(Value::bool(ctx.type_mgr(), true))
+}
+
+/// Pass a value through, failing with a message if a condition does not hold
+///
+/// Polymorphic - works with values of any type.
+///
+/// # Examples
+/// - `Expect(price, price >= 0, "negative price")` → `price`
+/// - `Expect(0, false, "bad") otherwise -1` → `-1`
+fn expect<'types, 'arena>(
+    _ctx: &FfiContext<'types, 'arena>,
+    args: &[Value<'types, 'arena>],
+) -> Result<Value<'types, 'arena>, ExecutionError> {
+    debug_assert_eq!(args.len(), 3);
+    check(&args[1], &args[2])?;
+    Ok(args[0])
+}
+
+// ============================================================================
+// Registration
+// ============================================================================
+
+/// Build the `Assert` function: `(Bool, Str) -> Bool`.
+pub fn build_assert<'arena>(
+    arena: &'arena Bump,
+    type_mgr: &'arena TypeManager<'arena>,
+) -> Result<Value<'arena, 'arena>, TypeError> {
+    let ty = type_mgr.function(&[type_mgr.bool(), type_mgr.str()], type_mgr.bool());
+    Value::function(
+        arena,
+        NativeFunction {
+            name: "Assert",
+            ty,
+            ptr: assert,
+        },
+    )
+}
+
+/// Build the `Expect` function: `forall T. (T, Bool, Str) -> T`.
+pub fn build_expect<'arena>(
+    arena: &'arena Bump,
+    type_mgr: &'arena TypeManager<'arena>,
+) -> Result<Value<'arena, 'arena>, TypeError> {
+    let t = type_mgr.fresh_type_var();
+    let ty = type_mgr.function(&[t, type_mgr.bool(), type_mgr.str()], t);
+    Value::function(
+        arena,
+        NativeFunction {
+            name: "Expect",
+            ty,
+            ptr: expect,
+        },
+    )
+}
+
+#[cfg(test)]
+#[path = "assert_test.rs"]
+mod assert_test;
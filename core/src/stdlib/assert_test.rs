@@ -0,0 +1,109 @@
+//! Tests for the assertion functions
+
+use super::{build_assert, build_expect};
+use crate::{
+    api::{CompileOptionsOverride, Engine, EngineOptions, Error},
+    values::dynamic::Value,
+};
+use bumpalo::Bump;
+
+/// Evaluates a Melbi expression with `Assert` and `Expect` registered.
+fn eval<'a>(arena: &'a Bump, source: &'a str) -> Result<Value<'a, 'a>, Error> {
+    let options = EngineOptions::default();
+
+    let engine = Engine::new(options, arena, |arena, type_mgr, env| {
+        let assert = build_assert(arena, type_mgr).unwrap();
+        env.register("Assert", assert).unwrap();
+        let expect = build_expect(arena, type_mgr).unwrap();
+        env.register("Expect", expect).unwrap();
+    });
+
+    let compile_opts = CompileOptionsOverride::default();
+    let expr = engine.compile(compile_opts, source, &[])?;
+    expr.run(Default::default(), arena, &[])
+}
+
+#[test]
+fn test_assert() {
+    let arena = Bump::new();
+
+    assert!(
+        eval(&arena, r#"Assert(1 < 2, "ordered")"#)
+            .unwrap()
+            .as_bool()
+            .unwrap()
+    );
+
+    let result = eval(&arena, r#"Assert(2 < 1, "numbers must be ordered")"#);
+    match result {
+        Err(Error::Runtime { diagnostic, .. }) => {
+            assert_eq!(diagnostic.code.as_deref(), Some("R012"));
+            assert_eq!(
+                diagnostic.message,
+                "Assertion failed: numbers must be ordered"
+            );
+        }
+        other => panic!("Expected runtime error, got {:?}", other.map(|_| ())),
+    }
+}
+
+#[test]
+fn test_assert_caught_by_otherwise() {
+    let arena = Bump::new();
+    assert!(
+        !eval(&arena, r#"Assert(false, "bad") otherwise false"#)
+            .unwrap()
+            .as_bool()
+            .unwrap()
+    );
+}
+
+#[test]
+fn test_expect() {
+    let arena = Bump::new();
+
+    assert_eq!(
+        eval(
+            &arena,
+            r#"Expect(price, price >= 0, "negative price") where { price = 42 }"#
+        )
+        .unwrap()
+        .as_int()
+        .unwrap(),
+        42
+    );
+
+    // Works with any value type
+    assert_eq!(
+        eval(&arena, r#"Expect("ok", true, "unused")"#)
+            .unwrap()
+            .as_str()
+            .unwrap(),
+        "ok"
+    );
+
+    assert_eq!(
+        eval(&arena, r#"Expect(0, false, "bad") otherwise -1"#)
+            .unwrap()
+            .as_int()
+            .unwrap(),
+        -1
+    );
+}
+
+#[test]
+fn test_assertion_type_errors() {
+    let arena = Bump::new();
+
+    // Condition must be Bool
+    assert!(matches!(
+        eval(&arena, r#"Assert(1, "bad")"#),
+        Err(Error::Compilation { .. })
+    ));
+
+    // Message must be Str
+    assert!(matches!(
+        eval(&arena, "Expect(1, true, 2)"),
+        Err(Error::Compilation { .. })
+    ));
+}
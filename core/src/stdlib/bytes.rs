@@ -0,0 +1,160 @@
+//! Bytes Package
+//!
+//! Provides functions for working with binary data.
+//!
+//! Functions:
+//! - `Concat(a, b)`: Bytes of `a` followed by bytes of `b`
+//! - `FromArray(array)`: Bytes from an array of byte values (0–255)
+//! - `ToArray(bytes)`: Array of byte values
+//! - `ToString(bytes)`: UTF-8 decoding, replacing invalid sequences with U+FFFD
+//! - `ToStringStrict(bytes)`: UTF-8 decoding, `none` if the bytes are not valid UTF-8
+//!
+//! Unlike the `bytes as Str` cast, which fails at run time on invalid UTF-8
+//! (reporting the offset of the first invalid byte), neither conversion
+//! fails: `ToString` always yields a string and `ToStringStrict` lets rules
+//! detect bad input.
+
+use crate::{
+    String, Vec,
+    evaluator::RuntimeError,
+    format,
+    types::manager::TypeManager,
+    values::{
+        dynamic::Value,
+        from_raw::TypeError,
+        typed::{Array, Optional, Str},
+    },
+};
+use alloc::borrow::Cow;
+use bumpalo::Bump;
+use melbi_macros::melbi_fn;
+
+// ============================================================================
+// Construction
+// ============================================================================
+
+/// Concatenate two byte strings
+///
+/// # Examples
+/// - `Bytes.Concat(b"ab", b"cd")` → `b"abcd"`
+#[melbi_fn(name = "Concat")]
+fn bytes_concat<'a>(
+    arena: &'a Bump,
+    _type_mgr: &'a TypeManager,
+    a: &'a [u8],
+    b: &'a [u8],
+) -> &'a [u8] {
+    arena.alloc_slice_copy(&[a, b].concat())
+}
+
+/// Build bytes from an array of byte values
+///
+/// Errors:
+/// - InvalidArgument if an element is outside `0..=255`
+///
+/// # Examples
+/// - `Bytes.FromArray([65, 66])` → `b"AB"`
+#[melbi_fn(name = "FromArray")]
+fn bytes_from_array<'a>(
+    arena: &'a Bump,
+    _type_mgr: &'a TypeManager,
+    array: Array<'a, i64>,
+) -> Result<&'a [u8], RuntimeError> {
+    let mut bytes = Vec::with_capacity(array.len());
+    for (index, value) in array.iter().enumerate() {
+        let byte = u8::try_from(value).map_err(|_| RuntimeError::InvalidArgument {
+            message: format!(
+                "Byte value must be between 0 and 255, got {} at index {}",
+                value, index
+            ),
+        })?;
+        bytes.push(byte);
+    }
+    Ok(arena.alloc_slice_copy(&bytes))
+}
+
+// ============================================================================
+// Conversion
+// ============================================================================
+
+/// Get the byte values of a byte string
+///
+/// # Examples
+/// - `Bytes.ToArray(b"AB")` → `[65, 66]`
+#[melbi_fn(name = "ToArray")]
+fn bytes_to_array<'a>(
+    arena: &'a Bump,
+    _type_mgr: &'a TypeManager,
+    bytes: &'a [u8],
+) -> Array<'a, i64> {
+    Array::from_iter(arena, bytes.iter().map(|&byte| i64::from(byte)))
+}
+
+/// Decode a byte string as UTF-8, replacing invalid sequences
+///
+/// Each invalid sequence becomes the replacement character U+FFFD, so the
+/// conversion always succeeds. Valid input is not copied.
+///
+/// # Examples
+/// - `Bytes.ToString(b"hi")` → `"hi"`
+/// - `Bytes.ToString(b"a\xffb")` → `"a\u{fffd}b"`
+#[melbi_fn(name = "ToString")]
+fn bytes_to_string<'a>(arena: &'a Bump, _type_mgr: &'a TypeManager, bytes: &'a [u8]) -> Str<'a> {
+    match String::from_utf8_lossy(bytes) {
+        Cow::Borrowed(s) => Str::from_borrowed_str(arena, s),
+        Cow::Owned(s) => Str::from_string(arena, s),
+    }
+}
+
+/// Decode a byte string as UTF-8, or `none` if it is not valid UTF-8
+///
+/// # Examples
+/// - `Bytes.ToStringStrict(b"hi")` → `some "hi"`
+/// - `Bytes.ToStringStrict(b"a\xffb")` → `none`
+#[melbi_fn(name = "ToStringStrict")]
+fn bytes_to_string_strict<'a>(
+    arena: &'a Bump,
+    _type_mgr: &'a TypeManager,
+    bytes: &'a [u8],
+) -> Optional<'a, Str<'a>> {
+    match core::str::from_utf8(bytes) {
+        Ok(s) => Optional::some(arena, Str::from_borrowed_str(arena, s)),
+        Err(_) => Optional::none(),
+    }
+}
+
+// ============================================================================
+// Package Builder
+// ============================================================================
+
+/// Build the Bytes package as a record containing byte string functions.
+///
+/// # Example
+///
+/// ```ignore
+/// let bytes = build_bytes_package(arena, type_mgr)?;
+/// env.register("Bytes", bytes)?;
+/// ```
+pub fn build_bytes_package<'arena>(
+    arena: &'arena Bump,
+    type_mgr: &'arena TypeManager<'arena>,
+) -> Result<Value<'arena, 'arena>, TypeError> {
+    use crate::values::function::AnnotatedFunction;
+
+    let mut builder = Value::record_builder(type_mgr);
+
+    // Construction
+    builder = Concat::new(type_mgr).register(arena, builder)?;
+    builder = FromArray::new(type_mgr).register(arena, builder)?;
+
+    // Conversion
+    builder = ToArray::new(type_mgr).register(arena, builder)?;
+    builder = ToString::new(type_mgr).register(arena, builder)?;
+    builder = ToStringStrict::new(type_mgr).register(arena, builder)?;
+
+    builder.build(arena)
+}
+
+#[cfg(test)]
+#[path = "bytes_test.rs"]
+mod bytes_test;
@@ -0,0 +1,118 @@
+//! Tests for the Bytes package
+
+use super::build_bytes_package;
+use crate::{
+    api::{CompileOptionsOverride, Engine, EngineOptions, Error},
+    types::manager::TypeManager,
+    values::dynamic::Value,
+};
+use bumpalo::Bump;
+
+#[test]
+fn test_bytes_package_builds() {
+    let arena = Bump::new();
+    let type_mgr = TypeManager::new(&arena);
+
+    let bytes = build_bytes_package(&arena, type_mgr).unwrap();
+    let record = bytes.as_record().unwrap();
+
+    assert!(record.get("Concat").is_some());
+    assert!(record.get("FromArray").is_some());
+    assert!(record.get("ToArray").is_some());
+    assert!(record.get("ToString").is_some());
+    assert!(record.get("ToStringStrict").is_some());
+}
+
+/// Evaluates a Melbi expression with the Bytes package.
+fn eval<'a>(arena: &'a Bump, source: &'a str) -> Result<Value<'a, 'a>, Error> {
+    let options = EngineOptions::default();
+
+    let engine = Engine::new(options, arena, |arena, type_mgr, env| {
+        let bytes = build_bytes_package(arena, type_mgr).unwrap();
+        env.register("Bytes", bytes).unwrap();
+    });
+
+    let compile_opts = CompileOptionsOverride::default();
+    let expr = engine.compile(compile_opts, source, &[])?;
+    expr.run(Default::default(), arena, &[])
+}
+
+/// Evaluates an expression returning `Array[Int]`.
+fn eval_ints(arena: &Bump, source: &'static str) -> Vec<i64> {
+    let result = eval(arena, source).unwrap();
+    let array = result.as_array().unwrap();
+    array.iter().map(|value| value.as_int().unwrap()).collect()
+}
+
+#[test]
+fn test_concat() {
+    let arena = Bump::new();
+
+    let result = eval(&arena, r#"Bytes.Concat(b"ab", b"cd")"#).unwrap();
+    assert_eq!(result.as_bytes().unwrap(), b"abcd");
+
+    let result = eval(&arena, r#"Bytes.Concat(b"", b"")"#).unwrap();
+    assert_eq!(result.as_bytes().unwrap(), b"");
+}
+
+#[test]
+fn test_from_array() {
+    let arena = Bump::new();
+
+    let result = eval(&arena, "Bytes.FromArray([65, 66, 0, 255])").unwrap();
+    assert_eq!(result.as_bytes().unwrap(), b"AB\x00\xff");
+
+    let result = eval(&arena, "Bytes.FromArray([])").unwrap();
+    assert_eq!(result.as_bytes().unwrap(), b"");
+}
+
+#[test]
+fn test_from_array_out_of_range() {
+    let arena = Bump::new();
+
+    assert!(eval(&arena, "Bytes.FromArray([256])").is_err());
+    assert!(eval(&arena, "Bytes.FromArray([1, -1])").is_err());
+    // The error can be caught with otherwise
+    let result = eval(&arena, r#"Bytes.FromArray([300]) otherwise b"""#).unwrap();
+    assert_eq!(result.as_bytes().unwrap(), b"");
+}
+
+#[test]
+fn test_to_array() {
+    let arena = Bump::new();
+
+    assert_eq!(eval_ints(&arena, r#"Bytes.ToArray(b"AB")"#), [65, 66]);
+    assert_eq!(eval_ints(&arena, r#"Bytes.ToArray(b"\xff")"#), [255]);
+    assert!(eval_ints(&arena, r#"Bytes.ToArray(b"")"#).is_empty());
+}
+
+#[test]
+fn test_to_string() {
+    let arena = Bump::new();
+
+    let result = eval(&arena, r#"Bytes.ToString(b"caf\xc3\xa9")"#).unwrap();
+    assert_eq!(result.as_str().unwrap(), "café");
+
+    // Invalid sequences are replaced rather than failing
+    let result = eval(&arena, r#"Bytes.ToString(b"a\xffb")"#).unwrap();
+    assert_eq!(result.as_str().unwrap(), "a\u{fffd}b");
+
+    let result = eval(&arena, r#"Bytes.ToString(b"")"#).unwrap();
+    assert_eq!(result.as_str().unwrap(), "");
+}
+
+#[test]
+fn test_to_string_strict() {
+    let arena = Bump::new();
+
+    let result = eval(&arena, r#"Bytes.ToStringStrict(b"caf\xc3\xa9")"#).unwrap();
+    let value = result.as_option().unwrap().unwrap();
+    assert_eq!(value.as_str().unwrap(), "café");
+
+    // Truncated multi-byte sequence
+    let result = eval(&arena, r#"Bytes.ToStringStrict(b"caf\xc3")"#).unwrap();
+    assert!(result.as_option().unwrap().is_none());
+
+    let result = eval(&arena, r#"Bytes.ToStringStrict(b"\xff")"#).unwrap();
+    assert!(result.as_option().unwrap().is_none());
+}
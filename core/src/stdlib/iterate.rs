@@ -0,0 +1,102 @@
+//! Iteration Builtin
+//!
+//! Provides `Iterate`, a top-level function for bounded iteration. Melbi has
+//! no loops or recursion, so `Iterate` is the way to express fixed-step
+//! computations such as numeric convergence. The steps run in a plain host
+//! loop, so the nesting depth stays constant no matter how many steps run.
+
+use super::NativeFunction;
+use crate::{
+    evaluator::{ExecutionError, ResourceExceededError, RuntimeError},
+    format,
+    parser::Span,
+    types::manager::TypeManager,
+    values::{dynamic::Value, from_raw::TypeError, function::FfiContext},
+};
+use alloc::string::String;
+use bumpalo::Bump;
+
+/// Maximum number of steps a single `Iterate` call may run.
+///
+/// Larger requests fail with a resource-exceeded error instead of running
+/// for an unbounded amount of time.
+pub const MAX_ITERATIONS: usize = 1 << 20;
+
+/// Apply a function to a value a fixed number of times
+///
+/// Polymorphic - works with values of any type.
+///
+/// # Edge Cases
+///
+/// - `n == 0` returns `init` unchanged
+/// - Fails with an invalid argument error if `n < 0`
+/// - Fails with a resource-exceeded error if `n` is above [`MAX_ITERATIONS`]
+///
+/// # Examples
+/// - `Iterate(1, 10, (x) => x * 2)` → `1024`
+/// - `Iterate("a", 2, (s) => f"{s}{s}")` → `"aaaa"`
+fn iterate<'types, 'arena>(
+    ctx: &FfiContext<'types, 'arena>,
+    args: &[Value<'types, 'arena>],
+) -> Result<Value<'types, 'arena>, ExecutionError> {
+    debug_assert_eq!(args.len(), 3);
+    let count = args[1].as_int().expect("Expected int");
+    let step = args[2].as_function().expect("Expected function");
+
+    let error = |kind| ExecutionError {
+        kind,
+        // TODO: Add proper source and span information for native functions
+        source: String::new(),
+        span: Span(0..0),
+    };
+    if count < 0 {
+        return Err(error(
+            RuntimeError::InvalidArgument {
+                message: format!("Iterate count must not be negative, got {}", count),
+            }
+            .into(),
+        ));
+    }
+    let count = count as usize;
+    if count > MAX_ITERATIONS {
+        return Err(error(
+            ResourceExceededError::TooManyIterations {
+                count,
+                max_count: MAX_ITERATIONS,
+            }
+            .into(),
+        ));
+    }
+
+    let mut value = args[0];
+    for _ in 0..count {
+        value = unsafe { step.call_unchecked(ctx, &[value]) }?;
+    }
+    Ok(value)
+}
+
+// ============================================================================
+// Registration
+// ============================================================================
+
+/// Build the `Iterate` function: `forall T. (T, Int, (T) => T) -> T`.
+pub fn build_iterate<'arena>(
+    arena: &'arena Bump,
+    type_mgr: &'arena TypeManager<'arena>,
+) -> Result<Value<'arena, 'arena>, TypeError> {
+    let t = type_mgr.fresh_type_var();
+    let step_ty = type_mgr.function(&[t], t);
+    let ty = type_mgr.function(&[t, type_mgr.int(), step_ty], t);
+    Value::function(
+        arena,
+        NativeFunction {
+            name: "Iterate",
+            ty,
+            ptr: iterate,
+        },
+    )
+}
+
+#[cfg(test)]
+#[path = "iterate_test.rs"]
+mod iterate_test;
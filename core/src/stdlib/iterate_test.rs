@@ -0,0 +1,97 @@
+//! Tests for the iteration builtin
+
+use super::{MAX_ITERATIONS, build_iterate};
+use crate::{
+    api::{CompileOptionsOverride, Engine, EngineOptions, Error},
+    format,
+    values::dynamic::Value,
+};
+use bumpalo::Bump;
+
+/// Evaluates a Melbi expression with `Iterate` registered.
+fn eval<'a>(arena: &'a Bump, source: &'a str) -> Result<Value<'a, 'a>, Error> {
+    let options = EngineOptions::default();
+
+    let engine = Engine::new(options, arena, |arena, type_mgr, env| {
+        let iterate = build_iterate(arena, type_mgr).unwrap();
+        env.register("Iterate", iterate).unwrap();
+    });
+
+    let compile_opts = CompileOptionsOverride::default();
+    let expr = engine.compile(compile_opts, source, &[])?;
+    expr.run(Default::default(), arena, &[])
+}
+
+#[test]
+fn test_iterate() {
+    let arena = Bump::new();
+
+    let result = eval(&arena, "Iterate(1, 10, (x) => x * 2)").unwrap();
+    assert_eq!(result.as_int().unwrap(), 1024);
+
+    let result = eval(&arena, r#"Iterate("a", 2, (s) => f"{s}{s}")"#).unwrap();
+    assert_eq!(result.as_str().unwrap(), "aaaa");
+}
+
+#[test]
+fn test_iterate_zero_times() {
+    let arena = Bump::new();
+
+    let result = eval(&arena, "Iterate(7, 0, (x) => x + 1)").unwrap();
+    assert_eq!(result.as_int().unwrap(), 7);
+}
+
+#[test]
+fn test_iterate_converges() {
+    let arena = Bump::new();
+
+    // Newton's method for the square root of 2
+    let result = eval(&arena, "Iterate(1.0, 6, (x) => (x + 2.0 / x) / 2.0)").unwrap();
+    assert!((result.as_float().unwrap() - core::f64::consts::SQRT_2).abs() < 1e-12);
+}
+
+#[test]
+fn test_iterate_negative_count() {
+    let arena = Bump::new();
+
+    let result = eval(&arena, "Iterate(1, -1, (x) => x)");
+    match result {
+        Err(Error::Runtime { diagnostic, .. }) => {
+            assert_eq!(diagnostic.code.as_deref(), Some("R008"));
+        }
+        other => panic!("Expected runtime error, got {:?}", other.map(|_| ())),
+    }
+
+    let result = eval(&arena, "Iterate(1, -1, (x) => x) otherwise 0").unwrap();
+    assert_eq!(result.as_int().unwrap(), 0);
+}
+
+#[test]
+fn test_iterate_too_many_steps() {
+    let arena = Bump::new();
+
+    let source = format!("Iterate(0, {}, (x) => x + 1)", MAX_ITERATIONS + 1);
+    let result = eval(&arena, arena.alloc_str(&source));
+    match result {
+        Err(Error::ResourceExceeded { diagnostic, .. }) => {
+            assert_eq!(diagnostic.code.as_deref(), Some("R013"));
+        }
+        other => panic!("Expected resource error, got {:?}", other.map(|_| ())),
+    }
+
+    // Resource limits cannot be caught
+    let source = format!(
+        "Iterate(0, {}, (x) => x + 1) otherwise 0",
+        MAX_ITERATIONS + 1
+    );
+    assert!(eval(&arena, arena.alloc_str(&source)).is_err());
+}
+
+#[test]
+fn test_iterate_step_error_propagates() {
+    let arena = Bump::new();
+
+    // 4 -> 2 -> 6 -> 1 -> division by zero
+    let result = eval(&arena, "Iterate(4, 5, (x) => 6 / (x - 1)) otherwise -1").unwrap();
+    assert_eq!(result.as_int().unwrap(), -1);
+}
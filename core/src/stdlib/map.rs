@@ -0,0 +1,247 @@
+//! Map Package
+//!
+//! Provides map inspection and transformation functions for Melbi.
+//!
+//! Functions: Size, IsEmpty, Has, Keys, Values, MapValues
+//!
+//! Maps are stored sorted by key, so `Keys` and `Values` return their
+//! elements in ascending key order, regardless of the order in which the
+//! entries were written. This keeps results deterministic.
+
+use super::NativeFunction;
+use crate::{
+    Vec,
+    evaluator::ExecutionError,
+    types::{
+        manager::TypeManager,
+        traits::{TypeKind, TypeView},
+    },
+    values::{
+        dynamic::Value,
+        from_raw::TypeError,
+        function::{AnnotatedFunction, FfiContext},
+    },
+};
+use bumpalo::Bump;
+
+// ============================================================================
+// Basic Functions
+// ============================================================================
+
+/// Get the number of entries in a map
+///
+/// Polymorphic - works with maps of any key and value types.
+///
+/// # Examples
+/// - `Map.Size({1: 2, 3: 4})` → `2`
+/// - `Map.Size({})` → `0`
+fn map_size<'types, 'arena>(
+    ctx: &FfiContext<'types, 'arena>,
+    args: &[Value<'types, 'arena>],
+) -> Result<Value<'types, 'arena>, ExecutionError> {
+    debug_assert_eq!(args.len(), 1);
+    let map = args[0].as_map().expect("Expected map");
+    Ok(Value::int(ctx.type_mgr(), map.len() as i64))
+}
+
+/// Check if a map is empty
+///
+/// Polymorphic - works with maps of any key and value types.
+///
+/// # Examples
+/// - `Map.IsEmpty({})` → `true`
+/// - `Map.IsEmpty({"a": 1})` → `false`
+fn map_is_empty<'types, 'arena>(
+    ctx: &FfiContext<'types, 'arena>,
+    args: &[Value<'types, 'arena>],
+) -> Result<Value<'types, 'arena>, ExecutionError> {
+    debug_assert_eq!(args.len(), 1);
+    let map = args[0].as_map().expect("Expected map");
+    Ok(Value::bool(ctx.type_mgr(), map.is_empty()))
+}
+
+/// Check if a map contains a key
+///
+/// Polymorphic - works with maps of any key and value types.
+///
+/// # Examples
+/// - `Map.Has({1: 10}, 1)` → `true`
+/// - `Map.Has({1: 10}, 2)` → `false`
+fn map_has<'types, 'arena>(
+    ctx: &FfiContext<'types, 'arena>,
+    args: &[Value<'types, 'arena>],
+) -> Result<Value<'types, 'arena>, ExecutionError> {
+    debug_assert_eq!(args.len(), 2);
+    let map = args[0].as_map().expect("Expected map");
+    Ok(Value::bool(ctx.type_mgr(), map.get(&args[1]).is_some()))
+}
+
+// ============================================================================
+// Access Functions
+// ============================================================================
+
+/// Get the keys of a map, in ascending order
+///
+/// Polymorphic - works with maps of any key and value types.
+///
+/// # Examples
+/// - `Map.Keys({2: "b", 1: "a"})` → `[1, 2]`
+/// - `Map.Keys({})` → `[]`
+fn map_keys<'types, 'arena>(
+    ctx: &FfiContext<'types, 'arena>,
+    args: &[Value<'types, 'arena>],
+) -> Result<Value<'types, 'arena>, ExecutionError> {
+    debug_assert_eq!(args.len(), 1);
+    let map = args[0].as_map().expect("Expected map");
+    let keys: Vec<_> = map.iter().map(|(key, _)| key).collect();
+    Ok(
+        Value::array(ctx.arena(), ctx.type_mgr().array(map.key_type()), &keys)
+            .expect("Type error in Map.Keys: array construction failed"),
+    )
+}
+
+/// Get the values of a map, in ascending order of their keys
+///
+/// Polymorphic - works with maps of any key and value types.
+///
+/// # Examples
+/// - `Map.Values({2: "b", 1: "a"})` → `["a", "b"]`
+/// - `Map.Values({})` → `[]`
+fn map_values<'types, 'arena>(
+    ctx: &FfiContext<'types, 'arena>,
+    args: &[Value<'types, 'arena>],
+) -> Result<Value<'types, 'arena>, ExecutionError> {
+    debug_assert_eq!(args.len(), 1);
+    let map = args[0].as_map().expect("Expected map");
+    let values: Vec<_> = map.iter().map(|(_, value)| value).collect();
+    Ok(
+        Value::array(ctx.arena(), ctx.type_mgr().array(map.value_type()), &values)
+            .expect("Type error in Map.Values: array construction failed"),
+    )
+}
+
+// ============================================================================
+// Higher-Order Functions
+// ============================================================================
+
+/// Apply a function to each value of a map, keeping the keys
+///
+/// Polymorphic - transforms Map[K, V] to Map[K, W] using a function (V) => W.
+/// The function is called in ascending key order.
+///
+/// # Examples
+/// - `Map.MapValues({1: 2, 3: 4}, (v) => v * 10)` → `{1: 20, 3: 40}`
+/// - `Map.MapValues({"a": 1}, (v) => v > 0)` → `{"a": true}`
+/// - `Map.MapValues({}, (v) => v)` → `{}`
+fn map_map_values<'types, 'arena>(
+    ctx: &FfiContext<'types, 'arena>,
+    args: &[Value<'types, 'arena>],
+) -> Result<Value<'types, 'arena>, ExecutionError> {
+    debug_assert_eq!(args.len(), 2);
+    let map = args[0].as_map().expect("Expected map");
+    let func = args[1].as_function().expect("Expected function");
+
+    let mut pairs = Vec::with_capacity(map.len());
+    for (key, value) in map.iter() {
+        let result = unsafe { func.call_unchecked(ctx, &[value]) }?;
+        pairs.push((key, result));
+    }
+
+    // Get result value type from function's return type
+    let result_value_ty = match args[1].ty.view() {
+        TypeKind::Function { ret, .. } => ret,
+        _ => panic!("Expected function type"),
+    };
+
+    Ok(Value::map(
+        ctx.arena(),
+        ctx.type_mgr().map(map.key_type(), result_value_ty),
+        &pairs,
+    )
+    .expect("Type error in Map.MapValues: map construction failed"))
+}
+
+// ============================================================================
+// Package Registration
+// ============================================================================
+
+pub fn build_map_package<'arena>(
+    arena: &'arena Bump,
+    type_mgr: &'arena TypeManager<'arena>,
+) -> Result<Value<'arena, 'arena>, TypeError> {
+    let mut builder = Value::record_builder(type_mgr);
+
+    // Size: forall K, V. Map<K, V> -> Int
+    let k = type_mgr.fresh_type_var();
+    let v = type_mgr.fresh_type_var();
+    let size_ty = type_mgr.function(&[type_mgr.map(k, v)], type_mgr.int());
+    builder = NativeFunction {
+        name: "Size",
+        ty: size_ty,
+        ptr: map_size,
+    }
+    .register(arena, builder)?;
+
+    // IsEmpty: forall K, V. Map<K, V> -> Bool
+    let k = type_mgr.fresh_type_var();
+    let v = type_mgr.fresh_type_var();
+    let is_empty_ty = type_mgr.function(&[type_mgr.map(k, v)], type_mgr.bool());
+    builder = NativeFunction {
+        name: "IsEmpty",
+        ty: is_empty_ty,
+        ptr: map_is_empty,
+    }
+    .register(arena, builder)?;
+
+    // Has: forall K, V. (Map<K, V>, K) -> Bool
+    let k = type_mgr.fresh_type_var();
+    let v = type_mgr.fresh_type_var();
+    let has_ty = type_mgr.function(&[type_mgr.map(k, v), k], type_mgr.bool());
+    builder = NativeFunction {
+        name: "Has",
+        ty: has_ty,
+        ptr: map_has,
+    }
+    .register(arena, builder)?;
+
+    // Keys: forall K, V. Map<K, V> -> Array<K>
+    let k = type_mgr.fresh_type_var();
+    let v = type_mgr.fresh_type_var();
+    let keys_ty = type_mgr.function(&[type_mgr.map(k, v)], type_mgr.array(k));
+    builder = NativeFunction {
+        name: "Keys",
+        ty: keys_ty,
+        ptr: map_keys,
+    }
+    .register(arena, builder)?;
+
+    // Values: forall K, V. Map<K, V> -> Array<V>
+    let k = type_mgr.fresh_type_var();
+    let v = type_mgr.fresh_type_var();
+    let values_ty = type_mgr.function(&[type_mgr.map(k, v)], type_mgr.array(v));
+    builder = NativeFunction {
+        name: "Values",
+        ty: values_ty,
+        ptr: map_values,
+    }
+    .register(arena, builder)?;
+
+    // MapValues: forall K, V, W. (Map<K, V>, (V) => W) -> Map<K, W>
+    let k = type_mgr.fresh_type_var();
+    let v = type_mgr.fresh_type_var();
+    let w = type_mgr.fresh_type_var();
+    let fn_ty = type_mgr.function(&[v], w);
+    let map_values_ty = type_mgr.function(&[type_mgr.map(k, v), fn_ty], type_mgr.map(k, w));
+    builder = NativeFunction {
+        name: "MapValues",
+        ty: map_values_ty,
+        ptr: map_map_values,
+    }
+    .register(arena, builder)?;
+
+    builder.build(arena)
+}
+
+#[cfg(test)]
+#[path = "map_test.rs"]
+mod map_test;
@@ -0,0 +1,177 @@
+//! Tests for the Map package
+
+use super::build_map_package;
+use crate::{
+    api::{CompileOptionsOverride, Engine, EngineOptions, Error},
+    stdlib::{build_array_package, build_string_package},
+    types::manager::TypeManager,
+    values::dynamic::Value,
+};
+use bumpalo::Bump;
+
+#[test]
+fn test_map_package_builds() {
+    let arena = Bump::new();
+    let type_mgr = TypeManager::new(&arena);
+
+    let map = build_map_package(&arena, type_mgr).unwrap();
+    let record = map.as_record().unwrap();
+
+    assert!(record.get("Size").is_some());
+    assert!(record.get("IsEmpty").is_some());
+    assert!(record.get("Has").is_some());
+    assert!(record.get("Keys").is_some());
+    assert!(record.get("Values").is_some());
+    assert!(record.get("MapValues").is_some());
+}
+
+/// Evaluates a Melbi expression with the Map, Array and String packages.
+fn eval<'a>(arena: &'a Bump, source: &'a str) -> Result<Value<'a, 'a>, Error> {
+    let options = EngineOptions::default();
+
+    let engine = Engine::new(options, arena, |arena, type_mgr, env| {
+        let map = build_map_package(arena, type_mgr).unwrap();
+        env.register("Map", map).unwrap();
+        let array = build_array_package(arena, type_mgr).unwrap();
+        env.register("Array", array).unwrap();
+        let string = build_string_package(arena, type_mgr).unwrap();
+        env.register("String", string).unwrap();
+    });
+
+    let compile_opts = CompileOptionsOverride::default();
+    let expr = engine.compile(compile_opts, source, &[])?;
+    expr.run(Default::default(), arena, &[])
+}
+
+#[test]
+fn test_size() {
+    let arena = Bump::new();
+
+    assert_eq!(
+        eval(&arena, "Map.Size({1: 2, 3: 4})")
+            .unwrap()
+            .as_int()
+            .unwrap(),
+        2
+    );
+    assert_eq!(eval(&arena, "Map.Size({})").unwrap().as_int().unwrap(), 0);
+    assert_eq!(
+        eval(&arena, r#"Map.Size({"a": true})"#)
+            .unwrap()
+            .as_int()
+            .unwrap(),
+        1
+    );
+}
+
+#[test]
+fn test_is_empty() {
+    let arena = Bump::new();
+
+    assert!(eval(&arena, "Map.IsEmpty({})").unwrap().as_bool().unwrap());
+    assert!(
+        !eval(&arena, "Map.IsEmpty({1: 2})")
+            .unwrap()
+            .as_bool()
+            .unwrap()
+    );
+}
+
+#[test]
+fn test_is_empty_family_in_conditionals() {
+    let arena = Bump::new();
+
+    assert!(
+        eval(&arena, "Array.IsEmpty([])")
+            .unwrap()
+            .as_bool()
+            .unwrap()
+    );
+    assert!(
+        eval(&arena, r#"String.IsEmpty("")"#)
+            .unwrap()
+            .as_bool()
+            .unwrap()
+    );
+
+    let result = eval(
+        &arena,
+        r#"if Map.IsEmpty(m) or Array.IsEmpty(a) then 0 else Map.Size(m) where { m = {"x": 1}, a = [1] }"#,
+    )
+    .unwrap();
+    assert_eq!(result.as_int().unwrap(), 1);
+}
+
+#[test]
+fn test_has() {
+    let arena = Bump::new();
+
+    assert!(
+        eval(&arena, "Map.Has({1: 10}, 1)")
+            .unwrap()
+            .as_bool()
+            .unwrap()
+    );
+    assert!(
+        !eval(&arena, "Map.Has({1: 10}, 2)")
+            .unwrap()
+            .as_bool()
+            .unwrap()
+    );
+    assert!(
+        eval(&arena, r#"Map.Has({"a": 1, "b": 2}, "b")"#)
+            .unwrap()
+            .as_bool()
+            .unwrap()
+    );
+    assert!(!eval(&arena, "Map.Has({}, 1)").unwrap().as_bool().unwrap());
+}
+
+#[test]
+fn test_keys_are_sorted() {
+    let arena = Bump::new();
+
+    let result = eval(&arena, r#"Map.Keys({2: "b", 1: "a"})"#).unwrap();
+    let keys: Vec<i64> = result
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|key| key.as_int().unwrap())
+        .collect();
+    assert_eq!(keys, [1, 2]);
+
+    let result = eval(&arena, r#"Map.Keys({"b": 1, "c": 2, "a": 3})"#).unwrap();
+    let keys: Vec<String> = result
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|key| key.as_str().unwrap().to_string())
+        .collect();
+    assert_eq!(keys, ["a", "b", "c"]);
+}
+
+#[test]
+fn test_values_follow_key_order() {
+    let arena = Bump::new();
+
+    let result = eval(&arena, r#"Map.Values({2: "b", 1: "a"})"#).unwrap();
+    let values: Vec<String> = result
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|value| value.as_str().unwrap().to_string())
+        .collect();
+    assert_eq!(values, ["a", "b"]);
+}
+
+#[test]
+fn test_keys_and_values_of_empty_map() {
+    let arena = Bump::new();
+
+    let result = eval(
+        &arena,
+        "Array.Len(Map.Keys({})) + Array.Len(Map.Values({}))",
+    )
+    .unwrap();
+    assert_eq!(result.as_int().unwrap(), 0);
+}
@@ -3,12 +3,21 @@
 //! Provides mathematical functions and constants for Melbi.
 //!
 //! Constants: PI, E, TAU, INFINITY, NAN
-//! Functions: Abs, Min, Max, Clamp, Floor, Ceil, Round, Sqrt, Pow,
-//!            Sin, Cos, Tan, Asin, Acos, Atan, Atan2, Log, Log10, Exp
+//!
+//! Melbi has no literal syntax for non-finite floats: `Math.INFINITY`,
+//! `-Math.INFINITY` and `Math.NAN` are the way to write them. They follow
+//! IEEE 754, so `Math.NAN == Math.NAN` is `false`.
+//!
+//! Functions: Abs, Min, Max, Clamp, Floor, Ceil, Round, Sqrt, Cbrt, Hypot,
+//!            Pow, Sin, Cos, Tan, Asin, Acos, Atan, Atan2, Log, Log10, Exp, Random
+//!
+//! `Math.Random` is deterministic: it draws from a generator seeded by
+//! `RunOptions::random_seed`, which defaults to a fixed constant rather than
+//! OS entropy.
 
 use crate::{
     types::manager::TypeManager,
-    values::{dynamic::Value, from_raw::TypeError},
+    values::{dynamic::Value, from_raw::TypeError, function::FfiContext},
 };
 use bumpalo::Bump;
 use melbi_macros::melbi_fn;
@@ -58,6 +67,9 @@ fn math_ceil(value: f64) -> i64 {
 }
 
 /// Round to nearest integer
+///
+/// Halfway cases round away from zero, like `f64::round`: `Math.Round(2.5)` is
+/// `3` and `Math.Round(-2.5)` is `-3`. This is not banker's rounding.
 #[melbi_fn(name = "Round")]
 fn math_round(value: f64) -> i64 {
     value.round() as i64
@@ -74,6 +86,20 @@ fn math_sqrt(value: f64) -> f64 {
     value.sqrt()
 }
 
+/// Cube root
+#[melbi_fn(name = "Cbrt")]
+fn math_cbrt(value: f64) -> f64 {
+    // Unlike Pow(x, 1.0 / 3.0), defined for negative values
+    value.cbrt()
+}
+
+/// Length of the hypotenuse - sqrt(x² + y²)
+#[melbi_fn(name = "Hypot")]
+fn math_hypot(x: f64, y: f64) -> f64 {
+    // Avoids the overflow and underflow of squaring large or small values
+    x.hypot(y)
+}
+
 /// Power function - base^exp
 #[melbi_fn(name = "Pow")]
 fn math_pow(base: f64, exp: f64) -> f64 {
@@ -131,6 +157,8 @@ fn math_atan2(y: f64, x: f64) -> f64 {
 // ============================================================================
 
 /// Natural logarithm (base e)
+///
+/// Follows IEEE 754: negative inputs give NaN and zero gives negative infinity.
 #[melbi_fn(name = "Log")]
 fn math_log(value: f64) -> f64 {
     value.ln()
@@ -148,6 +176,19 @@ fn math_exp(value: f64) -> f64 {
     value.exp()
 }
 
+// ============================================================================
+// Random Numbers
+// ============================================================================
+
+/// Pseudo-random float in `[0, 1)`
+///
+/// Each call advances the execution's generator, so repeated runs with the
+/// same seed produce the same sequence.
+#[melbi_fn(name = "Random", deterministic = false)]
+fn math_random(ctx: &FfiContext) -> f64 {
+    ctx.random().next_float()
+}
+
 // ============================================================================
 // Package Builder
 // ============================================================================
@@ -158,9 +199,10 @@ fn math_exp(value: f64) -> f64 {
 /// - Constants: PI, E, TAU, INFINITY, NAN
 /// - Basic operations: Abs, Min, Max, Clamp
 /// - Rounding: Floor, Ceil, Round
-/// - Exponentiation: Sqrt, Pow
+/// - Exponentiation: Sqrt, Cbrt, Hypot, Pow
 /// - Trigonometry: Sin, Cos, Tan, Asin, Acos, Atan, Atan2
 /// - Logarithms: Log, Log10, Exp
+/// - Random numbers: Random
 ///
 /// # Example
 ///
@@ -196,6 +238,8 @@ pub fn build_math_package<'arena>(
 
     // Exponentiation
     builder = Sqrt::new(type_mgr).register(arena, builder)?;
+    builder = Cbrt::new(type_mgr).register(arena, builder)?;
+    builder = Hypot::new(type_mgr).register(arena, builder)?;
     builder = Pow::new(type_mgr).register(arena, builder)?;
 
     // Trigonometry
@@ -212,6 +256,9 @@ pub fn build_math_package<'arena>(
     builder = Log10::new(type_mgr).register(arena, builder)?;
     builder = Exp::new(type_mgr).register(arena, builder)?;
 
+    // Random numbers
+    builder = Random::new(type_mgr).register(arena, builder)?;
+
     builder.build(arena)
 }
 
@@ -2,7 +2,8 @@
 
 use super::build_math_package;
 use crate::{
-    api::{CompileOptionsOverride, Engine, EngineOptions},
+    Vec,
+    api::{CompileOptionsOverride, DEFAULT_RANDOM_SEED, Engine, EngineOptions, RunOptionsOverride},
     types::manager::TypeManager,
     values::dynamic::Value,
 };
@@ -134,6 +135,15 @@ fn test_math_rounding() {
     test_math_expr("Math.Round(3.6)", |r: Value| {
         assert_eq!(r.as_int().unwrap(), 4);
     });
+
+    // Halfway cases round away from zero
+    test_math_expr("Math.Round(2.5)", |r: Value| {
+        assert_eq!(r.as_int().unwrap(), 3);
+    });
+
+    test_math_expr("Math.Round(-2.5)", |r: Value| {
+        assert_eq!(r.as_int().unwrap(), -3);
+    });
 }
 
 #[test]
@@ -147,6 +157,29 @@ fn test_math_sqrt() {
     });
 }
 
+#[test]
+fn test_math_cbrt() {
+    test_math_expr("Math.Cbrt(27.0)", |r: Value| {
+        assert_eq!(r.as_float().unwrap(), 3.0);
+    });
+
+    test_math_expr("Math.Cbrt(-8.0)", |r: Value| {
+        assert_eq!(r.as_float().unwrap(), -2.0);
+    });
+}
+
+#[test]
+fn test_math_hypot() {
+    test_math_expr("Math.Hypot(3.0, 4.0)", |r: Value| {
+        assert_eq!(r.as_float().unwrap(), 5.0);
+    });
+
+    // Squaring the arguments would overflow to infinity
+    test_math_expr("Math.Hypot(1e200, 1e200)", |r: Value| {
+        assert!(r.as_float().unwrap().is_finite());
+    });
+}
+
 #[test]
 fn test_math_pow() {
     test_math_expr("Math.Pow(2.0, 3.0)", |r: Value| {
@@ -238,3 +271,131 @@ fn test_math_composition() {
         assert!((r.as_float().unwrap() - 0.5).abs() < 1e-10);
     });
 }
+
+#[test]
+fn test_math_non_finite_constants() {
+    test_math_expr("-Math.INFINITY", |r: Value| {
+        let value = r.as_float().unwrap();
+        assert!(value.is_infinite());
+        assert!(value.is_sign_negative());
+    });
+
+    test_math_expr("Math.INFINITY == 1.0 / 0.0", |r: Value| {
+        assert!(r.as_bool().unwrap());
+    });
+
+    test_math_expr("-Math.INFINITY < -1.0e308", |r: Value| {
+        assert!(r.as_bool().unwrap());
+    });
+}
+
+#[test]
+fn test_math_nan_comparisons() {
+    test_math_expr("Math.NAN == Math.NAN", |r: Value| {
+        assert!(!r.as_bool().unwrap());
+    });
+
+    test_math_expr("Math.NAN != Math.NAN", |r: Value| {
+        assert!(r.as_bool().unwrap());
+    });
+
+    test_math_expr("Math.NAN < 1.0 or Math.NAN >= 1.0", |r: Value| {
+        assert!(!r.as_bool().unwrap());
+    });
+
+    test_math_expr("Math.NAN == 0.0 / 0.0", |r: Value| {
+        assert!(!r.as_bool().unwrap());
+    });
+}
+
+#[test]
+fn test_negative_zero() {
+    test_math_expr("-0.0", |r: Value| {
+        let value = r.as_float().unwrap();
+        assert_eq!(value, 0.0);
+        assert!(value.is_sign_negative());
+    });
+
+    test_math_expr("-0.0 == 0.0", |r: Value| {
+        assert!(r.as_bool().unwrap());
+    });
+
+    test_math_expr("1.0 / -0.0", |r: Value| {
+        assert_eq!(r.as_float().unwrap(), f64::NEG_INFINITY);
+    });
+}
+
+// Run a source expression producing `Array[Float]` with the given random seed
+fn random_sequence(source: &str, random_seed: Option<u64>) -> Vec<f64> {
+    let arena = Bump::new();
+    let engine = Engine::new(EngineOptions::default(), &arena, |arena, type_mgr, env| {
+        let math = build_math_package(arena, type_mgr).unwrap();
+        env.register("Math", math).unwrap();
+    });
+    let expr = engine
+        .compile(Default::default(), source, &[])
+        .expect("compilation should succeed");
+
+    let val_arena = Bump::new();
+    let run_options = RunOptionsOverride {
+        random_seed,
+        ..Default::default()
+    };
+    let result = expr
+        .run(run_options, &val_arena, &[])
+        .expect("execution should succeed");
+    result
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|value| value.as_float().unwrap())
+        .collect()
+}
+
+#[test]
+fn test_math_random_in_unit_interval() {
+    let values = random_sequence("[Math.Random(), Math.Random(), Math.Random()]", None);
+    assert_eq!(values.len(), 3);
+    for value in &values {
+        assert!((0.0..1.0).contains(value), "{} not in [0, 1)", value);
+    }
+    // Each call advances the generator
+    assert_ne!(values[0], values[1]);
+    assert_ne!(values[1], values[2]);
+}
+
+#[test]
+fn test_math_random_is_deterministic() {
+    let source = "[Math.Random(), Math.Random(), Math.Random()]";
+
+    assert_eq!(
+        random_sequence(source, Some(42)),
+        random_sequence(source, Some(42))
+    );
+    assert_ne!(
+        random_sequence(source, Some(42)),
+        random_sequence(source, Some(43))
+    );
+
+    // Without a seed, the fixed default seed is used
+    assert_eq!(random_sequence(source, None), random_sequence(source, None));
+    assert_eq!(
+        random_sequence(source, None),
+        random_sequence(source, Some(DEFAULT_RANDOM_SEED))
+    );
+}
+
+#[test]
+fn test_math_random_sequence_continues_into_lambdas() {
+    // Calls inside a lambda draw from the same generator as the caller
+    let values = random_sequence(
+        "[Math.Random(), f(), f()] where { f = () => Math.Random() }",
+        Some(7),
+    );
+    assert_ne!(values[0], values[1]);
+    assert_ne!(values[1], values[2]);
+    assert_eq!(
+        values,
+        random_sequence("[Math.Random(), Math.Random(), Math.Random()]", Some(7))
+    );
+}
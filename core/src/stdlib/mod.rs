@@ -2,30 +2,111 @@
 //!
 //! This module provides the standard library packages for Melbi, including:
 //! - Int: Integer arithmetic operations (Quot, Rem, Div, Mod)
+//! - Bytes: Binary data functions (Concat, FromArray, ToArray)
 //! - Math: Mathematical functions and constants
 //! - String: String manipulation functions
-//! - Array: Array operations (future)
-//! - Option: Option utilities (future)
+//! - Array: Array operations
+//! - Map: Map operations
+//! - Option: Option combinators (Map, GetOrElse, IsSome, Filter)
+//! - Assert, Expect: top-level functions for checking invariants
+//! - Iterate: top-level function for bounded iteration
 //!
 //! Each package is implemented as a record containing functions and constants.
 //! Packages are built using native Rust functions (FFI) and registered in the
 //! global environment before user code executes.
 
 use crate::api::{EnvironmentBuilder, Error};
-use crate::types::manager::TypeManager;
+use crate::evaluator::ExecutionError;
+use crate::types::{Type, manager::TypeManager};
+use crate::values::{
+    dynamic::{RecordBuilder, Value},
+    from_raw::TypeError,
+    function::{AnnotatedFunction, FfiContext, Function},
+};
 use bumpalo::Bump;
 
 pub mod array;
+pub mod assert;
+pub mod bytes;
 pub mod int;
+pub mod iterate;
+pub mod map;
 pub mod math;
+pub mod option;
 pub mod string;
 
 // Re-export for convenience
 pub use array::build_array_package;
+pub use assert::{build_assert, build_expect};
+pub use bytes::build_bytes_package;
 pub use int::build_int_package;
+pub use iterate::build_iterate;
+pub use map::build_map_package;
 pub use math::build_math_package;
+pub use option::build_option_package;
 pub use string::build_string_package;
 
+/// Helper struct to wrap a native function pointer and implement the Function trait
+///
+/// Used by packages with polymorphic functions (Array, Map), whose signatures
+/// are built from type variables instead of derived by `#[melbi_fn]`.
+struct NativeFunction<'types> {
+    name: &'static str,
+    ty: &'types Type<'types>,
+    ptr: fn(
+        &FfiContext<'types, 'types>,
+        &[Value<'types, 'types>],
+    ) -> Result<Value<'types, 'types>, ExecutionError>,
+}
+
+impl<'types> Function<'types, 'types> for NativeFunction<'types> {
+    fn ty(&self) -> &'types Type<'types> {
+        self.ty
+    }
+
+    fn is_deterministic(&self) -> bool {
+        true
+    }
+
+    unsafe fn call_unchecked(
+        &self,
+        ctx: &FfiContext<'types, 'types>,
+        args: &[Value<'types, 'types>],
+    ) -> Result<Value<'types, 'types>, ExecutionError> {
+        (self.ptr)(ctx, args)
+    }
+}
+
+impl<'types> AnnotatedFunction<'types> for NativeFunction<'types> {
+    fn name(&self) -> &str {
+        self.name
+    }
+
+    fn location(&self) -> (&str, &str, &str, u32, u32) {
+        (
+            "melbi-core",
+            env!("CARGO_PKG_VERSION"),
+            file!(),
+            line!(),
+            column!(),
+        )
+    }
+
+    fn doc(&self) -> Option<&str> {
+        None
+    }
+
+    fn register(
+        self,
+        arena: &'types Bump,
+        builder: RecordBuilder<'types, 'types>,
+    ) -> Result<RecordBuilder<'types, 'types>, TypeError> {
+        let name = self.name;
+        let fn_value = Value::function(arena, self)?;
+        Ok(builder.field(name, fn_value))
+    }
+}
+
 /// Register all standard library packages in the environment.
 ///
 /// This is a convenience function that registers all "default" standard library
@@ -62,14 +143,38 @@ pub fn register_stdlib<'arena>(
         .map_err(|_| Error::Api("Failed to build Array package".into()))?;
     env.register("Array", array)?;
 
+    // Register Map package
+    let map = build_map_package(arena, type_mgr)
+        .map_err(|_| Error::Api("Failed to build Map package".into()))?;
+    env.register("Map", map)?;
+
+    // Register Option package
+    let option = build_option_package(arena, type_mgr)
+        .map_err(|_| Error::Api("Failed to build Option package".into()))?;
+    env.register("Option", option)?;
+
     // Register Int package
     let int_pkg = build_int_package(arena, type_mgr)
         .map_err(|_| Error::Api("Failed to build Int package".into()))?;
     env.register("Int", int_pkg)?;
 
-    // Future packages will be added here:
-    // - Option package
-    // - etc.
+    // Register Bytes package
+    let bytes = build_bytes_package(arena, type_mgr)
+        .map_err(|_| Error::Api("Failed to build Bytes package".into()))?;
+    env.register("Bytes", bytes)?;
+
+    // Register assertion functions
+    let assert = build_assert(arena, type_mgr)
+        .map_err(|_| Error::Api("Failed to build Assert function".into()))?;
+    env.register("Assert", assert)?;
+    let expect = build_expect(arena, type_mgr)
+        .map_err(|_| Error::Api("Failed to build Expect function".into()))?;
+    env.register("Expect", expect)?;
+
+    // Register bounded iteration
+    let iterate = build_iterate(arena, type_mgr)
+        .map_err(|_| Error::Api("Failed to build Iterate function".into()))?;
+    env.register("Iterate", iterate)?;
 
     Ok(())
 }
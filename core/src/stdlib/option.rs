@@ -0,0 +1,181 @@
+//! Option Package
+//!
+//! Provides combinators for Melbi's `Option` type, complementing the `some` /
+//! `none` constructors and pattern matching.
+//!
+//! Functions: Map, GetOrElse, IsSome, Filter
+
+use super::NativeFunction;
+use crate::{
+    evaluator::ExecutionError,
+    types::{
+        manager::TypeManager,
+        traits::{TypeKind, TypeView},
+    },
+    values::{
+        dynamic::Value,
+        from_raw::TypeError,
+        function::{AnnotatedFunction, FfiContext},
+    },
+};
+use bumpalo::Bump;
+
+// ============================================================================
+// Transformation
+// ============================================================================
+
+/// Apply a function to the value inside an option, if there is one
+///
+/// Polymorphic - maps Option[T] to Option[U] using a function (T) => U.
+///
+/// # Examples
+/// - `Option.Map(some 3, (x) => x + 1)` → `some 4`
+/// - `Option.Map(none, (x) => x + 1)` → `none`
+fn option_map<'types, 'arena>(
+    ctx: &FfiContext<'types, 'arena>,
+    args: &[Value<'types, 'arena>],
+) -> Result<Value<'types, 'arena>, ExecutionError> {
+    debug_assert_eq!(args.len(), 2);
+    let opt = args[0].as_option().expect("Expected option");
+    let func = args[1].as_function().expect("Expected function");
+
+    let result = match opt {
+        Some(value) => Some(unsafe { func.call_unchecked(ctx, &[value]) }?),
+        None => None,
+    };
+
+    // Get result inner type from function's return type
+    let result_inner_ty = match args[1].ty.view() {
+        TypeKind::Function { ret, .. } => ret,
+        _ => panic!("Expected function type"),
+    };
+
+    Ok(
+        Value::optional(ctx.arena(), ctx.type_mgr().option(result_inner_ty), result)
+            .expect("Type error in Option.Map: option construction failed"),
+    )
+}
+
+/// Keep the value inside an option only if it satisfies a predicate
+///
+/// Polymorphic - filters Option[T] using a predicate (T) => Bool.
+///
+/// # Examples
+/// - `Option.Filter(some 4, (x) => x % 2 == 0)` → `some 4`
+/// - `Option.Filter(some 3, (x) => x % 2 == 0)` → `none`
+/// - `Option.Filter(none, (x) => true)` → `none`
+fn option_filter<'types, 'arena>(
+    ctx: &FfiContext<'types, 'arena>,
+    args: &[Value<'types, 'arena>],
+) -> Result<Value<'types, 'arena>, ExecutionError> {
+    debug_assert_eq!(args.len(), 2);
+    let opt = args[0].as_option().expect("Expected option");
+    let func = args[1].as_function().expect("Expected function");
+
+    let Some(value) = opt else {
+        return Ok(args[0]);
+    };
+    let keep = unsafe { func.call_unchecked(ctx, &[value]) }?;
+    if keep.as_bool().expect("Predicate must return bool") {
+        Ok(args[0])
+    } else {
+        Ok(Value::optional(ctx.arena(), args[0].ty, None)
+            .expect("Type error in Option.Filter: option construction failed"))
+    }
+}
+
+// ============================================================================
+// Inspection
+// ============================================================================
+
+/// Get the value inside an option, or a default if there is none
+///
+/// Polymorphic - works with options of any type.
+///
+/// # Examples
+/// - `Option.GetOrElse(some 3, 5)` → `3`
+/// - `Option.GetOrElse(none, 5)` → `5`
+fn option_get_or_else<'types, 'arena>(
+    _ctx: &FfiContext<'types, 'arena>,
+    args: &[Value<'types, 'arena>],
+) -> Result<Value<'types, 'arena>, ExecutionError> {
+    debug_assert_eq!(args.len(), 2);
+    let opt = args[0].as_option().expect("Expected option");
+    Ok(opt.unwrap_or(args[1]))
+}
+
+/// Check if an option holds a value
+///
+/// Polymorphic - works with options of any type.
+///
+/// # Examples
+/// - `Option.IsSome(some 1)` → `true`
+/// - `Option.IsSome(none)` → `false`
+fn option_is_some<'types, 'arena>(
+    ctx: &FfiContext<'types, 'arena>,
+    args: &[Value<'types, 'arena>],
+) -> Result<Value<'types, 'arena>, ExecutionError> {
+    debug_assert_eq!(args.len(), 1);
+    let opt = args[0].as_option().expect("Expected option");
+    Ok(Value::bool(ctx.type_mgr(), opt.is_some()))
+}
+
+// ============================================================================
+// Package Registration
+// ============================================================================
+
+pub fn build_option_package<'arena>(
+    arena: &'arena Bump,
+    type_mgr: &'arena TypeManager<'arena>,
+) -> Result<Value<'arena, 'arena>, TypeError> {
+    let mut builder = Value::record_builder(type_mgr);
+
+    // Map: forall T, U. (Option<T>, (T) => U) -> Option<U>
+    let t = type_mgr.fresh_type_var();
+    let u = type_mgr.fresh_type_var();
+    let fn_ty = type_mgr.function(&[t], u);
+    let map_ty = type_mgr.function(&[type_mgr.option(t), fn_ty], type_mgr.option(u));
+    builder = NativeFunction {
+        name: "Map",
+        ty: map_ty,
+        ptr: option_map,
+    }
+    .register(arena, builder)?;
+
+    // GetOrElse: forall T. (Option<T>, T) -> T
+    let t = type_mgr.fresh_type_var();
+    let get_or_else_ty = type_mgr.function(&[type_mgr.option(t), t], t);
+    builder = NativeFunction {
+        name: "GetOrElse",
+        ty: get_or_else_ty,
+        ptr: option_get_or_else,
+    }
+    .register(arena, builder)?;
+
+    // IsSome: forall T. Option<T> -> Bool
+    let t = type_mgr.fresh_type_var();
+    let is_some_ty = type_mgr.function(&[type_mgr.option(t)], type_mgr.bool());
+    builder = NativeFunction {
+        name: "IsSome",
+        ty: is_some_ty,
+        ptr: option_is_some,
+    }
+    .register(arena, builder)?;
+
+    // Filter: forall T. (Option<T>, (T) => Bool) -> Option<T>
+    let t = type_mgr.fresh_type_var();
+    let pred_ty = type_mgr.function(&[t], type_mgr.bool());
+    let filter_ty = type_mgr.function(&[type_mgr.option(t), pred_ty], type_mgr.option(t));
+    builder = NativeFunction {
+        name: "Filter",
+        ty: filter_ty,
+        ptr: option_filter,
+    }
+    .register(arena, builder)?;
+
+    builder.build(arena)
+}
+
+#[cfg(test)]
+#[path = "option_test.rs"]
+mod option_test;
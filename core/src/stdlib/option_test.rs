@@ -0,0 +1,148 @@
+//! Tests for the Option package
+
+use super::build_option_package;
+use crate::{
+    api::{CompileOptionsOverride, Engine, EngineOptions, Error},
+    types::manager::TypeManager,
+    values::dynamic::Value,
+};
+use bumpalo::Bump;
+
+#[test]
+fn test_option_package_builds() {
+    let arena = Bump::new();
+    let type_mgr = TypeManager::new(&arena);
+
+    let option = build_option_package(&arena, type_mgr).unwrap();
+    let record = option.as_record().unwrap();
+
+    assert!(record.get("Map").is_some());
+    assert!(record.get("GetOrElse").is_some());
+    assert!(record.get("IsSome").is_some());
+    assert!(record.get("Filter").is_some());
+}
+
+/// Evaluates a Melbi expression with the Option package.
+fn eval<'a>(arena: &'a Bump, source: &'a str) -> Result<Value<'a, 'a>, Error> {
+    let options = EngineOptions::default();
+
+    let engine = Engine::new(options, arena, |arena, type_mgr, env| {
+        let option = build_option_package(arena, type_mgr).unwrap();
+        env.register("Option", option).unwrap();
+    });
+
+    let compile_opts = CompileOptionsOverride::default();
+    let expr = engine.compile(compile_opts, source, &[])?;
+    expr.run(Default::default(), arena, &[])
+}
+
+/// Evaluates an expression returning `Option[Int]`.
+fn eval_int_option(arena: &Bump, source: &'static str) -> Option<i64> {
+    eval(arena, source)
+        .unwrap()
+        .as_option()
+        .unwrap()
+        .map(|value| value.as_int().unwrap())
+}
+
+#[test]
+fn test_map() {
+    let arena = Bump::new();
+
+    assert_eq!(
+        eval_int_option(&arena, "Option.Map(some 3, (x) => x + 1)"),
+        Some(4)
+    );
+    assert_eq!(
+        eval_int_option(&arena, "Option.Map(none, (x) => x + 1)"),
+        None
+    );
+}
+
+#[test]
+fn test_map_changes_inner_type() {
+    let arena = Bump::new();
+
+    let result = eval(&arena, "Option.Map(some 3, (x) => x > 1)").unwrap();
+    let inner = result.as_option().unwrap().unwrap();
+    assert!(inner.as_bool().unwrap());
+}
+
+#[test]
+fn test_get_or_else() {
+    let arena = Bump::new();
+
+    assert_eq!(
+        eval(&arena, "Option.GetOrElse(none, 5)")
+            .unwrap()
+            .as_int()
+            .unwrap(),
+        5
+    );
+    assert_eq!(
+        eval(&arena, "Option.GetOrElse(some 3, 5)")
+            .unwrap()
+            .as_int()
+            .unwrap(),
+        3
+    );
+}
+
+#[test]
+fn test_is_some() {
+    let arena = Bump::new();
+
+    assert!(
+        eval(&arena, "Option.IsSome(some 1)")
+            .unwrap()
+            .as_bool()
+            .unwrap()
+    );
+    assert!(
+        !eval(&arena, "Option.IsSome(none)")
+            .unwrap()
+            .as_bool()
+            .unwrap()
+    );
+}
+
+#[test]
+fn test_filter() {
+    let arena = Bump::new();
+
+    assert_eq!(
+        eval_int_option(&arena, "Option.Filter(some 4, (x) => x % 2 == 0)"),
+        Some(4)
+    );
+    assert_eq!(
+        eval_int_option(&arena, "Option.Filter(some 3, (x) => x % 2 == 0)"),
+        None
+    );
+    assert_eq!(
+        eval_int_option(&arena, "Option.Filter(none, (x) => x > 0)"),
+        None
+    );
+}
+
+#[test]
+fn test_combinators_compose() {
+    let arena = Bump::new();
+
+    let result = eval(
+        &arena,
+        "Option.GetOrElse(Option.Map(Option.Filter(some 10, (x) => x > 5), (x) => x * 2), 0)",
+    )
+    .unwrap();
+    assert_eq!(result.as_int().unwrap(), 20);
+}
+
+#[test]
+fn test_map_error_propagates() {
+    let arena = Bump::new();
+
+    let result = eval(&arena, "Option.Map(some 0, (x) => 1 / x)");
+    assert!(result.is_err());
+    // The error can be caught with otherwise
+    let result = eval(&arena, "Option.Map(some 0, (x) => 1 / x) otherwise none");
+    assert!(result.unwrap().as_option().unwrap().is_none());
+}
@@ -4,21 +4,34 @@
 //!
 //! Design notes:
 //! - String.Len returns UTF-8 codepoint count (not byte count)
-//! - Upper/Lower are ASCII-only to keep binary size minimal
+//! - Upper/Lower are ASCII-only to keep binary size minimal; ToUpper/ToLower
+//!   apply the full Unicode case mapping and may change the byte length
+//! - String.Reverse works on grapheme clusters, so combining marks stay attached
 //! - For full Unicode support, use the Unicode package
 //! - Format strings (f"...") are built into the language, not library functions
+//! - String.Template fills `{field}` placeholders from a record; when the template
+//!   is a literal, the analyzer checks its placeholders against the record type
+//! - String.Match uses the syntax of the `regex` crate; compiled patterns are
+//!   cached when `std` is available
 
+use super::NativeFunction;
 use crate::{
-    Vec,
+    String, Vec,
+    evaluator::{ExecutionError, RuntimeError},
+    format,
+    parser::Span,
     types::manager::TypeManager,
     values::{
         dynamic::Value,
         from_raw::TypeError,
+        function::FfiContext,
         typed::{Array, Optional, Str},
     },
 };
 use bumpalo::Bump;
 use melbi_macros::melbi_fn;
+use regex_automata::meta::Regex;
+use unicode_segmentation::UnicodeSegmentation;
 
 // ============================================================================
 // Inspection Functions
@@ -42,6 +55,15 @@ fn string_contains(haystack: Str, needle: Str) -> bool {
     haystack.contains(needle.as_ref())
 }
 
+/// Count non-overlapping occurrences of needle in haystack
+///
+/// An empty needle matches before and after every codepoint, so the count is
+/// `String.Len(haystack) + 1`.
+#[melbi_fn(name = "Count")]
+fn string_count(haystack: Str, needle: Str) -> i64 {
+    haystack.matches(needle.as_ref()).count() as i64
+}
+
 /// Check if string starts with prefix
 #[melbi_fn(name = "StartsWith")]
 fn string_starts_with(s: Str, prefix: Str) -> bool {
@@ -55,7 +77,7 @@ fn string_ends_with(s: Str, suffix: Str) -> bool {
 }
 
 // ============================================================================
-// Transformation Functions (ASCII-only for minimal binary size)
+// Transformation Functions
 // ============================================================================
 
 /// Convert string to uppercase (ASCII-only)
@@ -72,6 +94,20 @@ fn string_lower<'a>(arena: &'a Bump, _type_mgr: &'a TypeManager, s: Str<'a>) ->
     Str::from_str(arena, &lower)
 }
 
+/// Convert string to uppercase using the Unicode case mapping
+///
+/// The result may be longer than the input, e.g. `"straße"` becomes `"STRASSE"`.
+#[melbi_fn(name = "ToUpper")]
+fn string_to_upper<'a>(arena: &'a Bump, _type_mgr: &'a TypeManager, s: Str<'a>) -> Str<'a> {
+    Str::from_string(arena, s.to_uppercase())
+}
+
+/// Convert string to lowercase using the Unicode case mapping
+#[melbi_fn(name = "ToLower")]
+fn string_to_lower<'a>(arena: &'a Bump, _type_mgr: &'a TypeManager, s: Str<'a>) -> Str<'a> {
+    Str::from_string(arena, s.to_lowercase())
+}
+
 /// Trim whitespace from both ends
 #[melbi_fn(name = "Trim")]
 fn string_trim<'a>(arena: &'a Bump, _type_mgr: &'a TypeManager, s: Str<'a>) -> Str<'a> {
@@ -93,6 +129,17 @@ fn string_trim_end<'a>(arena: &'a Bump, _type_mgr: &'a TypeManager, s: Str<'a>)
     Str::from_borrowed_str(arena, trimmed)
 }
 
+/// Reverse a string by grapheme cluster
+///
+/// Reversing by grapheme cluster (user-perceived character) rather than by
+/// codepoint keeps sequences such as `e` + combining acute accent together,
+/// instead of moving the accent onto the neighbouring character.
+#[melbi_fn(name = "Reverse")]
+fn string_reverse<'a>(arena: &'a Bump, _type_mgr: &'a TypeManager, s: Str<'a>) -> Str<'a> {
+    let reversed: String = s.as_str().graphemes(true).rev().collect();
+    Str::from_str(arena, &reversed)
+}
+
 /// Replace all occurrences of pattern with replacement
 #[melbi_fn(name = "Replace")]
 fn string_replace<'a>(
@@ -256,6 +303,185 @@ fn string_to_float<'a>(
     }
 }
 
+// ============================================================================
+// Regular Expressions
+// ============================================================================
+
+/// Maximum number of compiled patterns kept by [`compile_pattern`]. The cache
+/// is cleared when it fills up.
+#[cfg(any(feature = "std", test))]
+const MAX_CACHED_PATTERNS: usize = 64;
+
+#[cfg(any(feature = "std", test))]
+lazy_static::lazy_static! {
+    static ref PATTERN_CACHE: std::sync::Mutex<alloc::collections::BTreeMap<String, Regex>> =
+        Default::default();
+}
+
+/// Compile a regular expression, reusing the result of earlier calls with
+/// the same pattern when possible.
+fn compile_pattern(pattern: &str) -> Result<Regex, RuntimeError> {
+    let compile = |pattern: &str| {
+        Regex::new(pattern).map_err(|err| RuntimeError::InvalidArgument {
+            message: format!("Invalid pattern {:?}: {}", pattern, err),
+        })
+    };
+
+    #[cfg(any(feature = "std", test))]
+    {
+        // Compiling does not panic, so the lock cannot be poisoned.
+        let mut cache = PATTERN_CACHE.lock().expect("Pattern cache lock poisoned");
+        if let Some(regex) = cache.get(pattern) {
+            return Ok(regex.clone());
+        }
+        let regex = compile(pattern)?;
+        if cache.len() >= MAX_CACHED_PATTERNS {
+            cache.clear();
+        }
+        cache.insert(String::from(pattern), regex.clone());
+        Ok(regex)
+    }
+    #[cfg(not(any(feature = "std", test)))]
+    compile(pattern)
+}
+
+/// Match a regular expression against a string
+///
+/// Returns the capture groups of the leftmost match, with group 0 being the
+/// whole match, or `none` if the pattern does not match. Groups that did not
+/// participate in the match are empty strings.
+///
+/// Errors:
+/// - InvalidArgument if the pattern is not a valid regular expression
+///
+/// # Examples
+/// - `String.Match("(\\d+)-(\\d+)", "12-34")` → `some ["12-34", "12", "34"]`
+/// - `String.Match("a(x)?", "a")` → `some ["a", ""]`
+/// - `String.Match("\\d", "abc")` → `none`
+#[melbi_fn(name = "Match")]
+fn string_match<'a>(
+    arena: &'a Bump,
+    _type_mgr: &'a TypeManager,
+    pattern: Str<'a>,
+    s: Str<'a>,
+) -> Result<Optional<'a, Array<'a, Str<'a>>>, RuntimeError> {
+    let regex = compile_pattern(pattern.as_str())?;
+    let haystack = s.as_str();
+    let mut captures = regex.create_captures();
+    regex.captures(haystack, &mut captures);
+    if !captures.is_match() {
+        return Ok(Optional::none());
+    }
+
+    let groups: Vec<Str<'a>> = (0..captures.group_len())
+        .map(|index| match captures.get_group(index) {
+            Some(span) => Str::from_borrowed_str(arena, &haystack[span.range()]),
+            None => Str::from_str(arena, ""),
+        })
+        .collect();
+    Ok(Optional::some(arena, Array::new(arena, &groups)))
+}
+
+// ============================================================================
+// Templates
+// ============================================================================
+
+/// A piece of a `String.Template` template.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum TemplateSegment<'a> {
+    /// Literal text, copied as is.
+    Text(&'a str),
+    /// A `{field}` placeholder, replaced by the record field's value.
+    Field(&'a str),
+}
+
+/// Split a template into literal text and `{field}` placeholders.
+///
+/// `{{` and `}}` stand for literal braces. Whitespace around a field name is
+/// ignored, so `{ name }` is the same as `{name}`.
+pub(crate) fn parse_template(template: &str) -> Result<Vec<TemplateSegment<'_>>, String> {
+    let mut segments = Vec::new();
+    let mut text_start = 0;
+    let mut chars = template.char_indices().peekable();
+
+    while let Some((pos, c)) = chars.next() {
+        match c {
+            '{' | '}' if chars.peek().map(|(_, next)| *next) == Some(c) => {
+                // Escaped brace: keep the first one as text, skip the second.
+                segments.push(TemplateSegment::Text(&template[text_start..pos + 1]));
+                chars.next();
+                text_start = pos + 2;
+            }
+            '{' => {
+                let Some(end) = template[pos..].find('}').map(|offset| pos + offset) else {
+                    return Err(format!("unclosed placeholder at offset {}", pos));
+                };
+                let name = template[pos + 1..end].trim();
+                if name.is_empty() {
+                    return Err(format!("empty placeholder at offset {}", pos));
+                }
+                if name.contains('{') {
+                    return Err(format!("unclosed placeholder at offset {}", pos));
+                }
+                segments.push(TemplateSegment::Text(&template[text_start..pos]));
+                segments.push(TemplateSegment::Field(name));
+                while chars.next_if(|(next_pos, _)| *next_pos <= end).is_some() {}
+                text_start = end + 1;
+            }
+            '}' => return Err(format!("unmatched '}}' at offset {}", pos)),
+            _ => {}
+        }
+    }
+    segments.push(TemplateSegment::Text(&template[text_start..]));
+    segments.retain(|segment| *segment != TemplateSegment::Text(""));
+    Ok(segments)
+}
+
+/// Fill the `{field}` placeholders of a template with fields of a record
+///
+/// Values are formatted the same way as in format strings. When the template
+/// is a string literal, placeholders are checked against the record type at
+/// compile time; otherwise a missing field or a malformed template is a
+/// runtime error that can be handled with `otherwise`.
+///
+/// # Examples
+/// - `String.Template("Hello, {name}!", {name = "Ada"})` → `"Hello, Ada!"`
+/// - `String.Template("{{{n}}}", {n = 1})` → `"{1}"`
+fn string_template<'types, 'arena>(
+    ctx: &FfiContext<'types, 'arena>,
+    args: &[Value<'types, 'arena>],
+) -> Result<Value<'types, 'arena>, ExecutionError> {
+    use core::fmt::Write;
+
+    debug_assert_eq!(args.len(), 2);
+    let template = args[0].as_str().expect("Expected string");
+    let invalid_argument = |message: String| ExecutionError {
+        kind: RuntimeError::InvalidArgument { message }.into(),
+        // TODO: Add proper source and span information for native functions
+        source: String::new(),
+        span: Span(0..0),
+    };
+    let record = args[1]
+        .as_record()
+        .map_err(|_| invalid_argument(String::from("String.Template values must be a record")))?;
+    let segments = parse_template(template)
+        .map_err(|err| invalid_argument(format!("String.Template: {}", err)))?;
+
+    let mut result = String::new();
+    for segment in segments {
+        match segment {
+            TemplateSegment::Text(text) => result.push_str(text),
+            TemplateSegment::Field(name) => {
+                let value = record.get(name).ok_or_else(|| {
+                    invalid_argument(format!("String.Template: no field '{}'", name))
+                })?;
+                write!(result, "{}", value).expect("Writing to String should not fail");
+            }
+        }
+    }
+    Ok(Value::str(ctx.arena(), ctx.type_mgr().str(), &result))
+}
+
 // ============================================================================
 // Package Builder
 // ============================================================================
@@ -263,11 +489,14 @@ fn string_to_float<'a>(
 /// Build the String package as a record containing all string functions.
 ///
 /// The package includes:
-/// - Inspection: Len (codepoints), IsEmpty, Contains, StartsWith, EndsWith
-/// - Transformation: Upper (ASCII), Lower (ASCII), Trim variants, Replace
+/// - Inspection: Len (codepoints), IsEmpty, Contains, Count, StartsWith, EndsWith
+/// - Transformation: Upper (ASCII), Lower (ASCII), ToUpper, ToLower, Trim variants,
+///   Reverse, Replace
 /// - Splitting/Joining: Split, Join
 /// - Extraction: Substring
 /// - Parsing: ToInt, ToFloat
+/// - Regular expressions: Match
+/// - Templates: Template
 ///
 /// # Example
 ///
@@ -287,15 +516,19 @@ pub fn build_string_package<'arena>(
     builder = Len::new(type_mgr).register(arena, builder)?;
     builder = IsEmpty::new(type_mgr).register(arena, builder)?;
     builder = Contains::new(type_mgr).register(arena, builder)?;
+    builder = Count::new(type_mgr).register(arena, builder)?;
     builder = StartsWith::new(type_mgr).register(arena, builder)?;
     builder = EndsWith::new(type_mgr).register(arena, builder)?;
 
     // Transformation
     builder = Upper::new(type_mgr).register(arena, builder)?;
     builder = Lower::new(type_mgr).register(arena, builder)?;
+    builder = ToUpper::new(type_mgr).register(arena, builder)?;
+    builder = ToLower::new(type_mgr).register(arena, builder)?;
     builder = Trim::new(type_mgr).register(arena, builder)?;
     builder = TrimStart::new(type_mgr).register(arena, builder)?;
     builder = TrimEnd::new(type_mgr).register(arena, builder)?;
+    builder = Reverse::new(type_mgr).register(arena, builder)?;
     builder = Replace::new(type_mgr).register(arena, builder)?;
     builder = ReplaceN::new(type_mgr).register(arena, builder)?;
 
@@ -310,6 +543,20 @@ pub fn build_string_package<'arena>(
     builder = ToInt::new(type_mgr).register(arena, builder)?;
     builder = ToFloat::new(type_mgr).register(arena, builder)?;
 
+    // Regular Expressions
+    builder = Match::new(type_mgr).register(arena, builder)?;
+
+    // Templates
+    // Template: forall R. (Str, R) -> Str, where R is a record
+    let r = type_mgr.fresh_type_var();
+    let template_ty = type_mgr.function(&[type_mgr.str(), r], type_mgr.str());
+    builder = NativeFunction {
+        name: "Template",
+        ty: template_ty,
+        ptr: string_template,
+    }
+    .register(arena, builder)?;
+
     builder.build(arena)
 }
 
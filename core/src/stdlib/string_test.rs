@@ -1,8 +1,9 @@
 //! Tests for the String package
 
-use super::build_string_package;
+use super::{TemplateSegment, build_string_package, compile_pattern, parse_template};
 use crate::{
     api::{CompileOptionsOverride, Engine, EngineOptions},
+    evaluator::RuntimeError,
     types::manager::TypeManager,
     values::dynamic::Value,
 };
@@ -91,6 +92,37 @@ fn test_string_contains() {
     });
 }
 
+#[test]
+fn test_string_count() {
+    test_string_expr("String.Count(\"banana\", \"an\")", |r: Value| {
+        assert_eq!(r.as_int().unwrap(), 2);
+    });
+
+    test_string_expr("String.Count(\"aaaa\", \"aa\")", |r: Value| {
+        assert_eq!(r.as_int().unwrap(), 2);
+    });
+
+    test_string_expr("String.Count(\"hello\", \"xyz\")", |r: Value| {
+        assert_eq!(r.as_int().unwrap(), 0);
+    });
+
+    test_string_expr("String.Count(\"\", \"a\")", |r: Value| {
+        assert_eq!(r.as_int().unwrap(), 0);
+    });
+}
+
+#[test]
+fn test_string_count_empty_needle() {
+    // An empty needle matches at every codepoint boundary.
+    test_string_expr("String.Count(\"héllo\", \"\")", |r: Value| {
+        assert_eq!(r.as_int().unwrap(), 6);
+    });
+
+    test_string_expr("String.Count(\"\", \"\")", |r: Value| {
+        assert_eq!(r.as_int().unwrap(), 1);
+    });
+}
+
 #[test]
 fn test_string_starts_with() {
     test_string_expr("String.StartsWith(\"hello\", \"hel\")", |r: Value| {
@@ -147,6 +179,37 @@ fn test_string_lower() {
     });
 }
 
+#[test]
+fn test_string_to_upper() {
+    test_string_expr("String.ToUpper(\"café\")", |r: Value| {
+        assert_eq!(r.as_str().unwrap(), "CAFÉ");
+    });
+
+    // 'ß' has no single-character uppercase form, so the result grows
+    test_string_expr("String.ToUpper(\"straße\")", |r: Value| {
+        assert_eq!(r.as_str().unwrap(), "STRASSE");
+    });
+
+    test_string_expr("String.Len(String.ToUpper(\"straße\"))", |r: Value| {
+        assert_eq!(r.as_int().unwrap(), 7);
+    });
+}
+
+#[test]
+fn test_string_to_lower() {
+    test_string_expr("String.ToLower(\"CAFÉ\")", |r: Value| {
+        assert_eq!(r.as_str().unwrap(), "café");
+    });
+
+    test_string_expr("String.ToLower(\"STRASSE\")", |r: Value| {
+        assert_eq!(r.as_str().unwrap(), "strasse");
+    });
+
+    test_string_expr("String.ToLower(\"ΣΟΦΊΑ\")", |r: Value| {
+        assert_eq!(r.as_str().unwrap(), "σοφία");
+    });
+}
+
 #[test]
 fn test_string_trim() {
     test_string_expr("String.Trim(\"  hello  \")", |r: Value| {
@@ -160,6 +223,15 @@ fn test_string_trim() {
     test_string_expr("String.Trim(\"  \")", |r: Value| {
         assert_eq!(r.as_str().unwrap(), "");
     });
+
+    test_string_expr("String.Trim(\"\\t\\n straße \\r\\n\")", |r: Value| {
+        assert_eq!(r.as_str().unwrap(), "straße");
+    });
+
+    // Interior whitespace is preserved
+    test_string_expr("String.Trim(\"\\ta\\tb\\n\")", |r: Value| {
+        assert_eq!(r.as_str().unwrap(), "a\tb");
+    });
 }
 
 #[test]
@@ -176,6 +248,27 @@ fn test_string_trim_end() {
     });
 }
 
+#[test]
+fn test_string_reverse() {
+    test_string_expr("String.Reverse(\"abc\")", |r: Value| {
+        assert_eq!(r.as_str().unwrap(), "cba");
+    });
+
+    test_string_expr("String.Reverse(\"\")", |r: Value| {
+        assert_eq!(r.as_str().unwrap(), "");
+    });
+
+    // Multibyte characters stay intact
+    test_string_expr("String.Reverse(\"áé\")", |r: Value| {
+        assert_eq!(r.as_str().unwrap(), "éá");
+    });
+
+    // Combining marks stay attached to their base character
+    test_string_expr("String.Reverse(\"e\u{0301}a\")", |r: Value| {
+        assert_eq!(r.as_str().unwrap(), "ae\u{0301}");
+    });
+}
+
 #[test]
 fn test_string_replace() {
     test_string_expr(
@@ -215,6 +308,35 @@ fn test_string_split() {
         let arr = r.as_array().unwrap();
         assert_eq!(arr.len(), 5); // Splits into chars
     });
+
+    // Empty delimiter splits into codepoints, not bytes
+    test_string_expr("String.Split(\"héé\", \"\")", |r: Value| {
+        let parts: Vec<String> = r
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|part| part.as_str().unwrap().to_string())
+            .collect();
+        assert_eq!(parts, ["h", "é", "é"]);
+    });
+
+    // Leading and trailing delimiters produce empty parts
+    test_string_expr("String.Split(\",a,\", \",\")", |r: Value| {
+        let parts: Vec<String> = r
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|part| part.as_str().unwrap().to_string())
+            .collect();
+        assert_eq!(parts, ["", "a", ""]);
+    });
+
+    // Delimiter not found
+    test_string_expr("String.Split(\"abc\", \";\")", |r: Value| {
+        let arr = r.as_array().unwrap();
+        assert_eq!(arr.len(), 1);
+        assert_eq!(arr.get(0).unwrap().as_str().unwrap(), "abc");
+    });
 }
 
 #[test]
@@ -226,6 +348,28 @@ fn test_string_join() {
     test_string_expr("String.Join([\"hello\"], \",\")", |r: Value| {
         assert_eq!(r.as_str().unwrap(), "hello");
     });
+
+    test_string_expr("String.Join([], \",\")", |r: Value| {
+        assert_eq!(r.as_str().unwrap(), "");
+    });
+}
+
+#[test]
+fn test_string_split_join_round_trip() {
+    test_string_expr(
+        "String.Join(String.Split(\"a,b,c\", \",\"), \"-\")",
+        |r: Value| {
+            assert_eq!(r.as_str().unwrap(), "a-b-c");
+        },
+    );
+
+    // Splitting a runtime string and joining it back gives the original
+    test_string_expr(
+        "String.Join(String.Split(s, \", \"), \", \") == s where { s = f\"{1}, {2}, {3}\" }",
+        |r: Value| {
+            assert!(r.as_bool().unwrap());
+        },
+    );
 }
 
 #[test]
@@ -329,3 +473,134 @@ fn test_string_composition() {
         },
     );
 }
+
+#[test]
+fn test_parse_template() {
+    use TemplateSegment::{Field, Text};
+
+    assert_eq!(
+        parse_template("Hi {name}, { n }!").unwrap(),
+        [
+            Text("Hi "),
+            Field("name"),
+            Text(", "),
+            Field("n"),
+            Text("!")
+        ]
+    );
+    assert_eq!(
+        parse_template("{{x}} {y}").unwrap(),
+        [Text("{"), Text("x}"), Text(" "), Field("y")]
+    );
+    assert_eq!(parse_template("").unwrap(), []);
+
+    assert!(parse_template("{name").is_err());
+    assert!(parse_template("{}").is_err());
+    assert!(parse_template("a } b").is_err());
+    assert!(parse_template("{a{b}").is_err());
+}
+
+#[test]
+fn test_string_template() {
+    test_string_expr(
+        r#"String.Template("Hello, {name}! You are {age}.", {name = "Ada", age = 36})"#,
+        |r: Value| {
+            assert_eq!(r.as_str().unwrap(), "Hello, Ada! You are 36.");
+        },
+    );
+
+    // Escaped braces and repeated placeholders
+    test_string_expr(
+        r#"String.Template("{{{n}}} = {n}", {n = 1.5})"#,
+        |r: Value| {
+            assert_eq!(r.as_str().unwrap(), "{1.5} = 1.5");
+        },
+    );
+
+    // Templates are values: they can come from bindings
+    test_string_expr(
+        r#"String.Template(greeting, {name = "Bob"}) where { greeting = "Hi {name}" }"#,
+        |r: Value| {
+            assert_eq!(r.as_str().unwrap(), "Hi Bob");
+        },
+    );
+}
+
+#[test]
+fn test_string_template_runtime_errors() {
+    // Non-literal templates are checked at runtime, and errors can be handled.
+    test_string_expr(
+        r#"String.Template(t, {name = "Bob"}) otherwise "fallback" where { t = "Hi {nmae}" }"#,
+        |r: Value| {
+            assert_eq!(r.as_str().unwrap(), "fallback");
+        },
+    );
+
+    test_string_expr(
+        r#"String.Template(t, {name = "Bob"}) otherwise "fallback" where { t = "Hi {name" }"#,
+        |r: Value| {
+            assert_eq!(r.as_str().unwrap(), "fallback");
+        },
+    );
+}
+
+// Helper to collect the groups of a `String.Match` result
+fn match_groups(result: Value) -> Option<alloc::vec::Vec<alloc::string::String>> {
+    result.as_option().unwrap().map(|groups| {
+        groups
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|group| group.as_str().unwrap().into())
+            .collect()
+    })
+}
+
+#[test]
+fn test_string_match() {
+    test_string_expr(r#"String.Match("(\\d+)-(\\d+)", "12-34")"#, |r: Value| {
+        assert_eq!(match_groups(r).unwrap(), ["12-34", "12", "34"]);
+    });
+
+    // The leftmost match is returned
+    test_string_expr(r#"String.Match("[a-z]+", "12 ab cd")"#, |r: Value| {
+        assert_eq!(match_groups(r).unwrap(), ["ab"]);
+    });
+
+    // Groups that do not participate in the match are empty
+    test_string_expr(r#"String.Match("a(x)?(b)", "ab")"#, |r: Value| {
+        assert_eq!(match_groups(r).unwrap(), ["ab", "", "b"]);
+    });
+
+    test_string_expr(r#"String.Match("\\d", "abc")"#, |r: Value| {
+        assert_eq!(match_groups(r), None);
+    });
+}
+
+#[test]
+fn test_string_match_invalid_pattern() {
+    test_string_expr(
+        r#"(String.Match("(", "x") otherwise none) == none"#,
+        |r: Value| {
+            assert!(r.as_bool().unwrap());
+        },
+    );
+
+    let err = compile_pattern("a{2").unwrap_err();
+    match err {
+        RuntimeError::InvalidArgument { message } => {
+            assert!(message.contains("a{2"), "{}", message)
+        }
+        other => panic!("Unexpected error: {:?}", other),
+    }
+}
+
+#[test]
+fn test_compile_pattern_reuses_compiled_patterns() {
+    let pattern = "cached-(pattern)";
+    let regex = compile_pattern(pattern).unwrap();
+    assert_eq!(regex.captures_len(), 2);
+
+    let cache = super::PATTERN_CACHE.lock().unwrap();
+    assert!(cache.contains_key(pattern));
+}
@@ -1,3 +1,4 @@
 /// Syntax utilities for Melbi literals and expressions.
 pub mod bytes_literal;
+pub mod position;
 pub mod string_literal;
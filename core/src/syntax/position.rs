@@ -0,0 +1,164 @@
+//! Conversions between byte offsets and editor positions.
+//!
+//! Spans produced by the parser are byte offsets into the source, while
+//! editors (LSP clients, Monaco) count columns in UTF-16 code units. These
+//! helpers convert between the two so that diagnostics and hovers land on the
+//! right characters in non-ASCII sources.
+//!
+//! Lines are separated by `\n`. Byte offsets that fall inside a multi-byte
+//! character, and columns that fall inside a surrogate pair, snap back to the
+//! start of that character.
+
+/// Zero-based line and column, with the column counted in UTF-16 code units.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LineColumn {
+    pub line: u32,
+    pub column: u32,
+}
+
+impl LineColumn {
+    pub fn new(line: u32, column: u32) -> Self {
+        Self { line, column }
+    }
+}
+
+/// Clamps `offset` to the source and moves it back to a character boundary.
+fn floor_char_boundary(source: &str, offset: usize) -> usize {
+    let mut offset = offset.min(source.len());
+    while !source.is_char_boundary(offset) {
+        offset -= 1;
+    }
+    offset
+}
+
+/// Converts a byte offset into a line and UTF-16 column.
+///
+/// Offsets past the end of the source map to the end of the source.
+pub fn offset_to_line_column(source: &str, offset: usize) -> LineColumn {
+    let before = &source[..floor_char_boundary(source, offset)];
+    let line_start = before.rfind('\n').map_or(0, |index| index + 1);
+    let line = before.bytes().filter(|&byte| byte == b'\n').count();
+    let column = before[line_start..].encode_utf16().count();
+    LineColumn::new(line as u32, column as u32)
+}
+
+/// Converts a line and UTF-16 column into a byte offset.
+///
+/// Returns `None` if the line does not exist. Columns past the end of the
+/// line map to the end of the line, as the LSP specification requires.
+pub fn line_column_to_offset(source: &str, position: LineColumn) -> Option<usize> {
+    let mut line_start = 0;
+    for _ in 0..position.line {
+        line_start += source[line_start..].find('\n')? + 1;
+    }
+    let line_end = source[line_start..]
+        .find('\n')
+        .map_or(source.len(), |index| line_start + index);
+
+    let mut units = 0;
+    for (index, ch) in source[line_start..line_end].char_indices() {
+        let width = ch.len_utf16() as u32;
+        if position.column < units + width {
+            return Some(line_start + index);
+        }
+        units += width;
+    }
+    Some(line_end)
+}
+
+/// Converts a byte offset into an offset in UTF-16 code units from the start
+/// of the source, as used by JavaScript strings.
+pub fn offset_to_utf16(source: &str, offset: usize) -> usize {
+    source[..floor_char_boundary(source, offset)]
+        .encode_utf16()
+        .count()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ascii_round_trip() {
+        let source = "a + b\nwhere { a = 1 }";
+        for offset in 0..=source.len() {
+            let position = offset_to_line_column(source, offset);
+            assert_eq!(line_column_to_offset(source, position), Some(offset));
+        }
+        assert_eq!(offset_to_line_column(source, 6), LineColumn::new(1, 0));
+        assert_eq!(offset_to_line_column(source, 12), LineColumn::new(1, 6));
+    }
+
+    #[test]
+    fn test_accented_characters() {
+        // 'é' is 2 bytes in UTF-8 but 1 UTF-16 code unit
+        let source = "\"café\" + x";
+        let x_offset = source.find('x').unwrap();
+        assert_eq!(x_offset, 10);
+        assert_eq!(
+            offset_to_line_column(source, x_offset),
+            LineColumn::new(0, 9)
+        );
+        assert_eq!(
+            line_column_to_offset(source, LineColumn::new(0, 9)),
+            Some(x_offset)
+        );
+        assert_eq!(offset_to_utf16(source, x_offset), 9);
+    }
+
+    #[test]
+    fn test_emoji() {
+        // '😀' is 4 bytes in UTF-8 and 2 UTF-16 code units
+        let source = "f\"😀\" + 1\ny 😀 z";
+        let one_offset = source.find('1').unwrap();
+        assert_eq!(
+            offset_to_line_column(source, one_offset),
+            LineColumn::new(0, 8)
+        );
+        assert_eq!(
+            line_column_to_offset(source, LineColumn::new(0, 8)),
+            Some(one_offset)
+        );
+
+        let z_offset = source.rfind('z').unwrap();
+        assert_eq!(
+            offset_to_line_column(source, z_offset),
+            LineColumn::new(1, 5)
+        );
+        assert_eq!(
+            line_column_to_offset(source, LineColumn::new(1, 5)),
+            Some(z_offset)
+        );
+        assert_eq!(offset_to_utf16(source, source.len()), 16);
+    }
+
+    #[test]
+    fn test_positions_inside_characters_snap_back() {
+        let source = "a😀b";
+        // Byte offsets inside the emoji map to its start
+        for offset in 2..5 {
+            assert_eq!(offset_to_line_column(source, offset), LineColumn::new(0, 1));
+            assert_eq!(offset_to_utf16(source, offset), 1);
+        }
+        // A column between the two halves of the surrogate pair does too
+        assert_eq!(
+            line_column_to_offset(source, LineColumn::new(0, 2)),
+            Some(1)
+        );
+        assert_eq!(
+            line_column_to_offset(source, LineColumn::new(0, 3)),
+            Some(5)
+        );
+    }
+
+    #[test]
+    fn test_out_of_range() {
+        let source = "ab\ncd";
+        assert_eq!(offset_to_line_column(source, 100), LineColumn::new(1, 2));
+        assert_eq!(
+            line_column_to_offset(source, LineColumn::new(0, 100)),
+            Some(2)
+        );
+        assert_eq!(line_column_to_offset(source, LineColumn::new(2, 0)), None);
+    }
+}
@@ -4,6 +4,10 @@
 ///   - Numeric(left, right, result): left + right => result
 ///   - Indexable(container, index, result): container[index] => result
 ///   - Hashable(ty): ty can be hashed
+///   - Bitwise(ty): ty supports bitwise operations
+///   - HasField(record, field, result): record.field => result
+///   - Castable(source, target): source as target
+///   - NumericPromotion(left, right, result): left and right compared as result
 ///
 /// During type inference, operations add relational constraints. After unification,
 /// the constraint solver verifies these relationships and may perform additional
@@ -53,6 +57,13 @@ pub enum TypeClassConstraint<'types> {
         spans: Vec<Span>,
     },
 
+    /// Bitwise type: ty supports bitwise operations
+    /// Instances: Int
+    Bitwise {
+        ty: &'types Type<'types>,
+        spans: Vec<Span>,
+    },
+
     /// Containment check: needle in haystack => bool
     /// Instances: (Str, Str), (Bytes, Bytes), (element, Array[element]), (key, Map[key, value])
     Containable {
@@ -60,6 +71,32 @@ pub enum TypeClassConstraint<'types> {
         haystack: &'types Type<'types>,
         spans: Vec<Span>,
     },
+
+    /// Field access: record.field => result
+    /// Instances: records that have the field, with result the field's type
+    HasField {
+        record: &'types Type<'types>,
+        field: &'types str,
+        result: &'types Type<'types>,
+        spans: Vec<Span>,
+    },
+
+    /// Cast: source as target
+    /// Instances: (Int, Float), (Float, Int), (Str, Bytes), (Bytes, Str), (T, T)
+    Castable {
+        source: &'types Type<'types>,
+        target: &'types Type<'types>,
+        spans: Vec<Span>,
+    },
+
+    /// Mixed numeric comparison: left and right are both converted to result
+    /// Instances: (Int, Float) => Float, (Float, Int) => Float, (T, T) => T
+    NumericPromotion {
+        left: &'types Type<'types>,
+        right: &'types Type<'types>,
+        result: &'types Type<'types>,
+        spans: Vec<Span>,
+    },
 }
 
 impl<'types> TypeClassConstraint<'types> {
@@ -72,7 +109,13 @@ impl<'types> TypeClassConstraint<'types> {
             TypeClassConstraint::Indexable { spans, .. } => spans.first().unwrap_or(&DEFAULT_SPAN),
             TypeClassConstraint::Hashable { spans, .. } => spans.first().unwrap_or(&DEFAULT_SPAN),
             TypeClassConstraint::Ord { spans, .. } => spans.first().unwrap_or(&DEFAULT_SPAN),
+            TypeClassConstraint::Bitwise { spans, .. } => spans.first().unwrap_or(&DEFAULT_SPAN),
             TypeClassConstraint::Containable { spans, .. } => spans.first().unwrap_or(&DEFAULT_SPAN),
+            TypeClassConstraint::HasField { spans, .. } => spans.first().unwrap_or(&DEFAULT_SPAN),
+            TypeClassConstraint::Castable { spans, .. } => spans.first().unwrap_or(&DEFAULT_SPAN),
+            TypeClassConstraint::NumericPromotion { spans, .. } => {
+                spans.first().unwrap_or(&DEFAULT_SPAN)
+            }
         }
     }
 
@@ -83,7 +126,85 @@ impl<'types> TypeClassConstraint<'types> {
             TypeClassConstraint::Indexable { spans, .. } => spans,
             TypeClassConstraint::Hashable { spans, .. } => spans,
             TypeClassConstraint::Ord { spans, .. } => spans,
+            TypeClassConstraint::Bitwise { spans, .. } => spans,
             TypeClassConstraint::Containable { spans, .. } => spans,
+            TypeClassConstraint::HasField { spans, .. } => spans,
+            TypeClassConstraint::Castable { spans, .. } => spans,
+            TypeClassConstraint::NumericPromotion { spans, .. } => spans,
+        }
+    }
+
+    /// Returns this constraint with `f` applied to each of its types, and
+    /// `spans` in place of its own.
+    pub fn map_types(
+        &self,
+        mut f: impl FnMut(&'types Type<'types>) -> &'types Type<'types>,
+        spans: Vec<Span>,
+    ) -> Self {
+        match self {
+            TypeClassConstraint::Numeric {
+                left,
+                right,
+                result,
+                ..
+            } => TypeClassConstraint::Numeric {
+                left: f(left),
+                right: f(right),
+                result: f(result),
+                spans,
+            },
+            TypeClassConstraint::Indexable {
+                container,
+                index,
+                result,
+                ..
+            } => TypeClassConstraint::Indexable {
+                container: f(container),
+                index: f(index),
+                result: f(result),
+                spans,
+            },
+            TypeClassConstraint::Hashable { ty, .. } => {
+                TypeClassConstraint::Hashable { ty: f(ty), spans }
+            }
+            TypeClassConstraint::Ord { ty, .. } => TypeClassConstraint::Ord { ty: f(ty), spans },
+            TypeClassConstraint::Bitwise { ty, .. } => {
+                TypeClassConstraint::Bitwise { ty: f(ty), spans }
+            }
+            TypeClassConstraint::Containable {
+                needle, haystack, ..
+            } => TypeClassConstraint::Containable {
+                needle: f(needle),
+                haystack: f(haystack),
+                spans,
+            },
+            TypeClassConstraint::HasField {
+                record,
+                field,
+                result,
+                ..
+            } => TypeClassConstraint::HasField {
+                record: f(record),
+                field,
+                result: f(result),
+                spans,
+            },
+            TypeClassConstraint::Castable { source, target, .. } => TypeClassConstraint::Castable {
+                source: f(source),
+                target: f(target),
+                spans,
+            },
+            TypeClassConstraint::NumericPromotion {
+                left,
+                right,
+                result,
+                ..
+            } => TypeClassConstraint::NumericPromotion {
+                left: f(left),
+                right: f(right),
+                result: f(result),
+                spans,
+            },
         }
     }
 
@@ -95,7 +216,11 @@ impl<'types> TypeClassConstraint<'types> {
             TypeClassConstraint::Indexable { .. } => TypeClassId::Indexable,
             TypeClassConstraint::Hashable { .. } => TypeClassId::Hashable,
             TypeClassConstraint::Ord { .. } => TypeClassId::Ord,
+            TypeClassConstraint::Bitwise { .. } => TypeClassId::Bitwise,
             TypeClassConstraint::Containable { .. } => TypeClassId::Containable,
+            TypeClassConstraint::HasField { .. } => TypeClassId::HasField,
+            TypeClassConstraint::Castable { .. } => TypeClassId::Castable,
+            TypeClassConstraint::NumericPromotion { .. } => TypeClassId::NumericPromotion,
         }
     }
 }
@@ -166,6 +291,14 @@ impl<'types> ConstraintSet<'types> {
         });
     }
 
+    /// Adds a bitwise constraint: ty must support bitwise operations
+    pub fn add_bitwise(&mut self, ty: &'types Type<'types>, span: Span) {
+        self.constraints.push(TypeClassConstraint::Bitwise {
+            ty,
+            spans: alloc::vec![span],
+        });
+    }
+
     /// Adds a containable constraint: needle in haystack
     pub fn add_containable(
         &mut self,
@@ -180,6 +313,53 @@ impl<'types> ConstraintSet<'types> {
         });
     }
 
+    /// Adds a field access constraint: record.field => result
+    pub fn add_has_field(
+        &mut self,
+        record: &'types Type<'types>,
+        field: &'types str,
+        result: &'types Type<'types>,
+        span: Span,
+    ) {
+        self.constraints.push(TypeClassConstraint::HasField {
+            record,
+            field,
+            result,
+            spans: alloc::vec![span],
+        });
+    }
+
+    /// Adds a cast constraint: source as target
+    pub fn add_castable(
+        &mut self,
+        source: &'types Type<'types>,
+        target: &'types Type<'types>,
+        span: Span,
+    ) {
+        self.constraints.push(TypeClassConstraint::Castable {
+            source,
+            target,
+            spans: alloc::vec![span],
+        });
+    }
+
+    /// Adds a numeric promotion constraint: left and right compared as result
+    pub fn add_numeric_promotion(
+        &mut self,
+        left: &'types Type<'types>,
+        right: &'types Type<'types>,
+        result: &'types Type<'types>,
+        span: Span,
+    ) {
+        self.constraints
+            .push(TypeClassConstraint::NumericPromotion {
+                left,
+                right,
+                result,
+                spans: alloc::vec![span],
+            });
+    }
+
     /// Returns an iterator over all constraints.
     pub fn iter(&self) -> impl Iterator<Item = &TypeClassConstraint<'types>> {
         self.constraints.iter()
@@ -51,9 +51,24 @@ impl core::error::Error for TypeConversionError {}
 pub fn type_expr_to_type<'types>(
     type_manager: &'types TypeManager<'types>,
     type_expr: &parser::TypeExpr<'_>,
+) -> Result<&'types Type<'types>, TypeConversionError> {
+    type_expr_to_type_with_aliases(type_manager, type_expr, &|_| None)
+}
+
+/// Like [`type_expr_to_type`], but resolves names through `aliases` first.
+///
+/// `aliases` maps a type name to the type it stands for, or `None` if the
+/// name is not an alias in scope.
+pub fn type_expr_to_type_with_aliases<'types>(
+    type_manager: &'types TypeManager<'types>,
+    type_expr: &parser::TypeExpr<'_>,
+    aliases: &dyn Fn(&str) -> Option<&'types Type<'types>>,
 ) -> Result<&'types Type<'types>, TypeConversionError> {
     match type_expr {
         parser::TypeExpr::Path(path) => {
+            if let Some(ty) = aliases(path) {
+                return Ok(ty);
+            }
             // Map type path to built-in types
             match *path {
                 "Int" => Ok(type_manager.int()),
@@ -75,7 +90,7 @@ pub fn type_expr_to_type<'types>(
                         got: params.len(),
                     });
                 }
-                let element_ty = type_expr_to_type(type_manager, &params[0])?;
+                let element_ty = type_expr_to_type_with_aliases(type_manager, &params[0], aliases)?;
                 Ok(type_manager.array(element_ty))
             }
             "Map" => {
@@ -86,8 +101,8 @@ pub fn type_expr_to_type<'types>(
                         got: params.len(),
                     });
                 }
-                let key_ty = type_expr_to_type(type_manager, &params[0])?;
-                let value_ty = type_expr_to_type(type_manager, &params[1])?;
+                let key_ty = type_expr_to_type_with_aliases(type_manager, &params[0], aliases)?;
+                let value_ty = type_expr_to_type_with_aliases(type_manager, &params[1], aliases)?;
                 Ok(type_manager.map(key_ty, value_ty))
             }
             "Option" => {
@@ -98,7 +113,7 @@ pub fn type_expr_to_type<'types>(
                         got: params.len(),
                     });
                 }
-                let inner_ty = type_expr_to_type(type_manager, &params[0])?;
+                let inner_ty = type_expr_to_type_with_aliases(type_manager, &params[0], aliases)?;
                 Ok(type_manager.option(inner_ty))
             }
             _ => Err(TypeConversionError::UnknownType {
@@ -110,7 +125,8 @@ pub fn type_expr_to_type<'types>(
                 fields
                     .iter()
                     .map(|(name, type_expr)| {
-                        let field_ty = type_expr_to_type(type_manager, type_expr)?;
+                        let field_ty =
+                            type_expr_to_type_with_aliases(type_manager, type_expr, aliases)?;
                         Ok::<_, TypeConversionError>((*name, field_ty))
                     })
                     .collect();
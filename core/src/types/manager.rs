@@ -1,5 +1,5 @@
 use crate::{
-    Vec,
+    String, Vec, format,
     types::{
         traits::{TypeKind, TypeView},
         types::{CompareTypeArgs, Type},
@@ -27,7 +27,7 @@ impl<'a> TypeManager<'a> {
         })
     }
 
-    pub(super) fn intern_str(&self, s: &str) -> &'a str {
+    pub(crate) fn intern_str(&self, s: &str) -> &'a str {
         if let Some(&interned_str) = self.interned_strs.borrow().get(s) {
             return interned_str;
         }
@@ -79,6 +79,29 @@ impl<'a> TypeManager<'a> {
         self.arena.alloc_slice_copy(slice)
     }
 
+    /// Returns a human-readable listing of all interned types.
+    ///
+    /// Each line shows a type and the address of its interned instance, sorted
+    /// by type for stable output. Useful when diagnosing interning issues, e.g.
+    /// when two structurally equal types are unexpectedly not pointer-equal.
+    pub fn dump_interned(&self) -> String {
+        use core::fmt::Write;
+
+        let mut entries: Vec<(String, *const Type<'a>)> = self
+            .intern_map()
+            .values()
+            .map(|&ty| (format!("{}", ty), ty as *const Type<'a>))
+            .collect();
+        entries.sort();
+
+        let mut out = format!("{} interned types\n", entries.len());
+        for (ty, ptr) in entries {
+            // Writing to a String cannot fail.
+            let _ = writeln!(out, "  {} @ {:p}", ty, ptr);
+        }
+        out
+    }
+
     // Factory methods for types.
     pub fn int(&self) -> &'a Type<'a> {
         if let Some(&interned_ty) = self.intern_map().get(&CompareTypeArgs(Type::Int)) {
@@ -488,3 +488,23 @@ fn test_record_with_strings_in_vec() {
         "Records created from String vecs should intern to the same type"
     );
 }
+
+#[test]
+fn test_dump_interned() {
+    let bump = Bump::new();
+    let manager = TypeManager::new(&bump);
+
+    let int1 = manager.int();
+    let int2 = manager.int();
+    assert!(core::ptr::eq(int1, int2));
+
+    let dump = manager.dump_interned();
+    assert!(dump.starts_with("1 interned types\n"), "{}", dump);
+    assert_eq!(dump.matches("Int @ ").count(), 1, "{}", dump);
+    assert!(dump.contains(&alloc::format!("Int @ {:p}", int1)), "{}", dump);
+
+    manager.array(int1);
+    let dump = manager.dump_interned();
+    assert!(dump.starts_with("2 interned types\n"), "{}", dump);
+    assert!(dump.contains("Array[Int] @ "), "{}", dump);
+}
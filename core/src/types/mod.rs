@@ -11,13 +11,14 @@ pub mod type_scheme;
 mod types;
 pub mod unification;
 
+mod schema;
 mod serialization;
 
 #[cfg(test)]
 mod manager_test;
 
 pub use constraint_set::{ConstraintSet, TypeClassConstraint};
-pub use from_parser::{TypeConversionError, type_expr_to_type};
+pub use from_parser::{TypeConversionError, type_expr_to_type, type_expr_to_type_with_aliases};
 pub use type_class::{TypeClassId, has_instance};
 pub use type_class_resolver::{ConstraintError, TypeClassResolver};
 pub use type_scheme::TypeScheme;
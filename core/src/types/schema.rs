@@ -0,0 +1,213 @@
+//! Machine-readable description of types.
+//!
+//! Hosts use the schema to document the inputs and outputs of an expression
+//! for consumers that are not written in Rust.
+
+use core::fmt::Write;
+
+use crate::{String, ToString, types::Type};
+
+impl Type<'_> {
+    /// Describe the type as a JSON document.
+    ///
+    /// Every node has a `kind` (e.g. `"Int"`, `"Array"`, `"Record"`) and a
+    /// `name` with the type as written in Melbi source. Composite types add
+    /// their components:
+    ///
+    /// - `Array`: `element`
+    /// - `Map`: `key` and `value`
+    /// - `Option`: `inner`
+    /// - `Record`: `fields`, a list of `{"name", "type"}` objects
+    /// - `Function`: `params` and `return`
+    /// - `Symbol`: `values`, the list of symbol names
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use melbi_core::types::manager::TypeManager;
+    /// use bumpalo::Bump;
+    ///
+    /// let arena = Bump::new();
+    /// let type_mgr = TypeManager::new(&arena);
+    /// let ty = type_mgr.array(type_mgr.int());
+    /// assert_eq!(
+    ///     ty.to_schema_json(),
+    ///     r#"{"kind":"Array","name":"Array[Int]","element":{"kind":"Int","name":"Int"}}"#
+    /// );
+    /// ```
+    pub fn to_schema_json(&self) -> String {
+        let mut out = String::new();
+        write_schema(&mut out, self);
+        out
+    }
+}
+
+fn write_schema(out: &mut String, ty: &Type<'_>) {
+    let kind = match ty {
+        Type::TypeVar(_) => "TypeVar",
+        Type::Int => "Int",
+        Type::Float => "Float",
+        Type::Bool => "Bool",
+        Type::Str => "Str",
+        Type::Bytes => "Bytes",
+        Type::Array(_) => "Array",
+        Type::Map(_, _) => "Map",
+        Type::Record(_) => "Record",
+        Type::Function { .. } => "Function",
+        Type::Symbol(_) => "Symbol",
+        Type::Option(_) => "Option",
+    };
+    out.push_str("{\"kind\":");
+    write_json_string(out, kind);
+    out.push_str(",\"name\":");
+    write_json_string(out, &ty.to_string());
+
+    match ty {
+        Type::TypeVar(_)
+        | Type::Int
+        | Type::Float
+        | Type::Bool
+        | Type::Str
+        | Type::Bytes => {}
+        Type::Array(elem) => {
+            out.push_str(",\"element\":");
+            write_schema(out, elem);
+        }
+        Type::Map(key, value) => {
+            out.push_str(",\"key\":");
+            write_schema(out, key);
+            out.push_str(",\"value\":");
+            write_schema(out, value);
+        }
+        Type::Option(inner) => {
+            out.push_str(",\"inner\":");
+            write_schema(out, inner);
+        }
+        Type::Record(fields) => {
+            out.push_str(",\"fields\":[");
+            for (i, (name, field_ty)) in fields.iter().enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+                out.push_str("{\"name\":");
+                write_json_string(out, name);
+                out.push_str(",\"type\":");
+                write_schema(out, field_ty);
+                out.push('}');
+            }
+            out.push(']');
+        }
+        Type::Function { params, ret } => {
+            out.push_str(",\"params\":[");
+            for (i, param) in params.iter().enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+                write_schema(out, param);
+            }
+            out.push_str("],\"return\":");
+            write_schema(out, ret);
+        }
+        Type::Symbol(parts) => {
+            out.push_str(",\"values\":[");
+            for (i, part) in parts.iter().enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+                write_json_string(out, part);
+            }
+            out.push(']');
+        }
+    }
+    out.push('}');
+}
+
+fn write_json_string(out: &mut String, s: &str) {
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if (c as u32) < 0x20 => {
+                let _ = write!(out, "\\u{:04x}", c as u32);
+            }
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::types::manager::TypeManager;
+    use crate::vec;
+    use bumpalo::Bump;
+
+    #[test]
+    fn test_primitive_schema() {
+        let arena = Bump::new();
+        let type_mgr = TypeManager::new(&arena);
+
+        assert_eq!(
+            type_mgr.int().to_schema_json(),
+            r#"{"kind":"Int","name":"Int"}"#
+        );
+    }
+
+    #[test]
+    fn test_record_schema_lists_fields() {
+        let arena = Bump::new();
+        let type_mgr = TypeManager::new(&arena);
+
+        let ty = type_mgr.record(vec![
+            ("tags", type_mgr.array(type_mgr.str())),
+            ("score", type_mgr.int()),
+        ]);
+
+        assert_eq!(
+            ty.to_schema_json(),
+            concat!(
+                r#"{"kind":"Record","name":"Record[score: Int, tags: Array[Str]]","fields":["#,
+                r#"{"name":"score","type":{"kind":"Int","name":"Int"}},"#,
+                r#"{"name":"tags","type":{"kind":"Array","name":"Array[Str]","#,
+                r#""element":{"kind":"Str","name":"Str"}}}"#,
+                r#"]}"#,
+            )
+        );
+    }
+
+    #[test]
+    fn test_function_map_and_symbol_schema() {
+        let arena = Bump::new();
+        let type_mgr = TypeManager::new(&arena);
+
+        let ty = type_mgr.function(
+            &[type_mgr.map(type_mgr.str(), type_mgr.option(type_mgr.float()))],
+            type_mgr.symbol(vec!["no", "yes"]),
+        );
+
+        assert_eq!(
+            ty.to_schema_json(),
+            concat!(
+                r#"{"kind":"Function","name":"(Map[Str, Option[Float]]) => Symbol[no|yes]","#,
+                r#""params":[{"kind":"Map","name":"Map[Str, Option[Float]]","#,
+                r#""key":{"kind":"Str","name":"Str"},"#,
+                r#""value":{"kind":"Option","name":"Option[Float]","#,
+                r#""inner":{"kind":"Float","name":"Float"}}}],"#,
+                r#""return":{"kind":"Symbol","name":"Symbol[no|yes]","values":["no","yes"]}}"#,
+            )
+        );
+    }
+
+    #[test]
+    fn test_schema_escapes_strings() {
+        let arena = Bump::new();
+        let type_mgr = TypeManager::new(&arena);
+
+        let ty = type_mgr.symbol(vec!["a\"b"]);
+        assert!(ty.to_schema_json().contains(r#""values":["a\"b"]"#));
+    }
+}
@@ -1,9 +1,35 @@
 use postcard::to_allocvec;
-use serde::Deserialize as _;
+use serde::Deserialize;
 use serde::de::{DeserializeSeed, Deserializer, EnumAccess, VariantAccess, Visitor};
 
 use crate::types::manager::TypeManager;
-use crate::{Vec, format, types::Type};
+use crate::{Vec, types::Type};
+
+/// Variant tag of a serialized `Type`, in declaration order.
+///
+/// Binary formats (postcard) identify variants by index, while self-describing
+/// ones (JSON) use the name; both are accepted.
+#[derive(Deserialize)]
+#[serde(variant_identifier)]
+enum TypeVariant {
+    TypeVar,
+    Int,
+    Float,
+    Bool,
+    Str,
+    Bytes,
+    Array,
+    Map,
+    Record,
+    Function,
+    Symbol,
+    Option,
+}
+
+const TYPE_VARIANTS: &[&str] = &[
+    "TypeVar", "Int", "Float", "Bool", "Str", "Bytes", "Array", "Map", "Record", "Function",
+    "Symbol", "Option",
+];
 
 impl<'de, 's, 'a> DeserializeSeed<'de> for &'s TypeManager<'a>
 where
@@ -15,7 +41,7 @@ where
     where
         D: Deserializer<'de>,
     {
-        deserializer.deserialize_enum("Type", &[], TypeVisitor { mgr: self })
+        deserializer.deserialize_enum("Type", TYPE_VARIANTS, TypeVisitor { mgr: self })
     }
 }
 
@@ -37,70 +63,52 @@ where
     where
         A: EnumAccess<'de>,
     {
-        use serde::de::Error;
-
-        // Read the variant discriminant
-        let (discriminant, variant) = data.variant::<u32>()?;
+        let (tag, variant) = data.variant::<TypeVariant>()?;
 
-        match discriminant {
-            0 => {
-                // TypeVar(u16)
+        match tag {
+            TypeVariant::TypeVar => {
                 let id = variant.newtype_variant_seed(U16Seed)?;
                 Ok(self.mgr.type_var(id))
             }
-            1 => {
-                // Int
+            TypeVariant::Int => {
                 variant.unit_variant()?;
                 Ok(self.mgr.int())
             }
-            2 => {
-                // Float
+            TypeVariant::Float => {
                 variant.unit_variant()?;
                 Ok(self.mgr.float())
             }
-            3 => {
-                // Bool
+            TypeVariant::Bool => {
                 variant.unit_variant()?;
                 Ok(self.mgr.bool())
             }
-            4 => {
-                // Str
+            TypeVariant::Str => {
                 variant.unit_variant()?;
                 Ok(self.mgr.str())
             }
-            5 => {
-                // Bytes
+            TypeVariant::Bytes => {
                 variant.unit_variant()?;
                 Ok(self.mgr.bytes())
             }
-            6 => {
-                // Array(&'a Type<'a>)
+            TypeVariant::Array => {
                 let inner = variant.newtype_variant_seed(self.mgr)?;
                 Ok(self.mgr.array(inner))
             }
-            7 => {
-                // Map(&'a Type<'a>, &'a Type<'a>)
+            TypeVariant::Map => {
                 let (key, val) = variant.tuple_variant(2, MapVisitor { mgr: self.mgr })?;
                 Ok(self.mgr.map(key, val))
             }
-            8 => {
-                // Record(&'a [(&'a str, &'a Type<'a>)])
-                variant.newtype_variant_seed(RecordFieldsSeed { mgr: self.mgr })
-            }
-            9 => {
-                // Function { params: &'a [&'a Type<'a>], ret: &'a Type<'a> }
+            TypeVariant::Record => variant.newtype_variant_seed(RecordFieldsSeed { mgr: self.mgr }),
+            TypeVariant::Function => {
                 let (params, ret) = variant
                     .struct_variant(&["params", "ret"], FunctionVisitor { mgr: self.mgr })?;
                 Ok(self.mgr.function(params, ret))
             }
-            10 => {
-                // Symbol(&'a [&'a str])
-                variant.newtype_variant_seed(SymbolPartsSeed { mgr: self.mgr })
+            TypeVariant::Symbol => variant.newtype_variant_seed(SymbolPartsSeed { mgr: self.mgr }),
+            TypeVariant::Option => {
+                let inner = variant.newtype_variant_seed(self.mgr)?;
+                Ok(self.mgr.option(inner))
             }
-            _ => Err(Error::custom(format!(
-                "unknown Type variant: {}",
-                discriminant
-            ))),
         }
     }
 }
@@ -135,17 +143,45 @@ where
     }
 }
 
-// Helper for deserializing strings (just returns borrowed string from deserializer)
-struct StrSeed;
+// Helper for deserializing strings, interned in the manager so that strings
+// which cannot be borrowed from the input (e.g. escaped JSON) work too
+struct StrSeed<'s, 'a> {
+    mgr: &'s TypeManager<'a>,
+}
 
-impl<'de> DeserializeSeed<'de> for StrSeed {
-    type Value = &'de str;
+impl<'de, 's, 'a> DeserializeSeed<'de> for StrSeed<'s, 'a>
+where
+    's: 'a,
+{
+    type Value = &'a str;
 
     fn deserialize<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
     where
         D: Deserializer<'de>,
     {
-        <&str>::deserialize(deserializer)
+        deserializer.deserialize_str(StrVisitor { mgr: self.mgr })
+    }
+}
+
+struct StrVisitor<'s, 'a> {
+    mgr: &'s TypeManager<'a>,
+}
+
+impl<'de, 's, 'a> Visitor<'de> for StrVisitor<'s, 'a>
+where
+    's: 'a,
+{
+    type Value = &'a str;
+
+    fn expecting(&self, formatter: &mut core::fmt::Formatter) -> core::fmt::Result {
+        formatter.write_str("a string")
+    }
+
+    fn visit_str<E>(self, value: &str) -> Result<Self::Value, E>
+    where
+        E: serde::de::Error,
+    {
+        Ok(self.mgr.intern_str(value))
     }
 }
 
@@ -220,7 +256,7 @@ impl<'de, 's, 'a> DeserializeSeed<'de> for RecordFieldSeed<'s, 'a>
 where
     's: 'a,
 {
-    type Value = (&'de str, &'a Type<'a>);
+    type Value = (&'a str, &'a Type<'a>);
 
     fn deserialize<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
     where
@@ -238,7 +274,7 @@ impl<'de, 's, 'a> Visitor<'de> for RecordFieldVisitor<'s, 'a>
 where
     's: 'a,
 {
-    type Value = (&'de str, &'a Type<'a>);
+    type Value = (&'a str, &'a Type<'a>);
 
     fn expecting(&self, formatter: &mut core::fmt::Formatter) -> core::fmt::Result {
         formatter.write_str("a tuple of (string, type)")
@@ -250,7 +286,7 @@ where
     {
         use serde::de::Error;
         let name = seq
-            .next_element_seed(StrSeed)?
+            .next_element_seed(StrSeed { mgr: self.mgr })?
             .ok_or_else(|| Error::custom("expected field name"))?;
         let ty = seq
             .next_element_seed(self.mgr)?
@@ -289,12 +325,43 @@ where
             .next_element_seed(self.mgr)?
             .ok_or_else(|| Error::custom("expected return type"))?;
 
-        // Now pass Vec to function() which will allocate the slice
-        let func = self.mgr.function(&params_vec, ret);
-        if let Type::Function { params, ret } = func {
-            Ok((params, ret))
-        } else {
-            unreachable!()
+        self.function_parts(&params_vec, ret)
+    }
+
+    // Self-describing formats (JSON) encode struct variants as maps
+    fn visit_map<A>(self, mut map: A) -> Result<Self::Value, A::Error>
+    where
+        A: serde::de::MapAccess<'de>,
+    {
+        use serde::de::Error;
+
+        let mut params_vec = None;
+        let mut ret = None;
+        while let Some(key) = map.next_key_seed(StrSeed { mgr: self.mgr })? {
+            match key {
+                "params" => {
+                    params_vec = Some(map.next_value_seed(TypeSliceSeed { mgr: self.mgr })?);
+                }
+                "ret" => ret = Some(map.next_value_seed(self.mgr)?),
+                _ => return Err(Error::unknown_field(key, &["params", "ret"])),
+            }
+        }
+        let params_vec = params_vec.ok_or_else(|| Error::missing_field("params"))?;
+        let ret = ret.ok_or_else(|| Error::missing_field("ret"))?;
+        self.function_parts(&params_vec, ret)
+    }
+}
+
+impl<'s, 'a> FunctionVisitor<'s, 'a> {
+    fn function_parts<E>(
+        &self,
+        params: &[&'a Type<'a>],
+        ret: &'a Type<'a>,
+    ) -> Result<(&'a [&'a Type<'a>], &'a Type<'a>), E> {
+        // function() allocates the params slice in the arena
+        match self.mgr.function(params, ret) {
+            Type::Function { params, ret } => Ok((params, ret)),
+            _ => unreachable!(),
         }
     }
 }
@@ -360,15 +427,20 @@ where
     where
         D: Deserializer<'de>,
     {
-        let parts = deserializer.deserialize_seq(SymbolPartsVisitor)?;
+        let parts = deserializer.deserialize_seq(SymbolPartsVisitor { mgr: self.mgr })?;
         Ok(self.mgr.symbol(parts))
     }
 }
 
-struct SymbolPartsVisitor;
+struct SymbolPartsVisitor<'s, 'a> {
+    mgr: &'s TypeManager<'a>,
+}
 
-impl<'de> Visitor<'de> for SymbolPartsVisitor {
-    type Value = crate::Vec<&'de str>;
+impl<'de, 's, 'a> Visitor<'de> for SymbolPartsVisitor<'s, 'a>
+where
+    's: 'a,
+{
+    type Value = crate::Vec<&'a str>;
 
     fn expecting(&self, formatter: &mut core::fmt::Formatter) -> core::fmt::Result {
         formatter.write_str("a sequence of strings")
@@ -379,8 +451,8 @@ impl<'de> Visitor<'de> for SymbolPartsVisitor {
         A: serde::de::SeqAccess<'de>,
     {
         use crate::Vec;
-        let mut parts: Vec<&'de str> = Vec::new();
-        while let Some(part) = seq.next_element_seed(StrSeed)? {
+        let mut parts: Vec<&'a str> = Vec::new();
+        while let Some(part) = seq.next_element_seed(StrSeed { mgr: self.mgr })? {
             parts.push(part);
         }
         Ok(parts)
@@ -401,3 +473,79 @@ impl<'a> TypeManager<'a> {
         self.deserialize(&mut deserializer)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bumpalo::Bump;
+
+    /// Serializes `ty` to JSON and reads it back into `target`.
+    fn json_round_trip<'a>(ty: &Type<'_>, target: &'a TypeManager<'a>) -> &'a Type<'a> {
+        let json = serde_json::to_string(ty).unwrap();
+        let mut deserializer = serde_json::Deserializer::from_str(&json);
+        target.deserialize(&mut deserializer).unwrap()
+    }
+
+    #[test]
+    fn test_json_round_trip_record() {
+        let bump = Bump::new();
+        let type_mgr = TypeManager::new(&bump);
+        let record = type_mgr.record(vec![
+            ("x", type_mgr.int()),
+            ("y", type_mgr.array(type_mgr.str())),
+        ]);
+
+        let json = serde_json::to_string(record).unwrap();
+        assert_eq!(json, r#"{"Record":[["x","Int"],["y",{"Array":"Str"}]]}"#);
+
+        // Into the same manager, the result is the interned type itself
+        assert!(core::ptr::eq(json_round_trip(record, type_mgr), record));
+
+        // Into another manager, it is structurally equal
+        let other_bump = Bump::new();
+        let other_mgr = TypeManager::new(&other_bump);
+        let copy = json_round_trip(record, other_mgr);
+        assert_eq!(copy.stable_hash(), record.stable_hash());
+        assert_eq!(format!("{}", copy), format!("{}", record));
+    }
+
+    #[test]
+    fn test_json_round_trip_all_kinds() {
+        let bump = Bump::new();
+        let type_mgr = TypeManager::new(&bump);
+        let types = [
+            type_mgr.type_var(3),
+            type_mgr.bytes(),
+            type_mgr.map(type_mgr.str(), type_mgr.float()),
+            type_mgr.option(type_mgr.bool()),
+            type_mgr.function(
+                &[type_mgr.int(), type_mgr.type_var(0)],
+                type_mgr.option(type_mgr.type_var(0)),
+            ),
+            type_mgr.symbol(vec!["error", "ok"]),
+            type_mgr.record(vec![("`quoted \"name\"`", type_mgr.int())]),
+            type_mgr.record(vec![]),
+        ];
+
+        for ty in types {
+            assert!(
+                core::ptr::eq(json_round_trip(ty, type_mgr), ty),
+                "JSON round trip changed {}",
+                ty
+            );
+        }
+    }
+
+    #[test]
+    fn test_postcard_round_trip_option() {
+        let bump = Bump::new();
+        let type_mgr = TypeManager::new(&bump);
+        let ty = type_mgr.array(type_mgr.option(type_mgr.int()));
+
+        let bytes = type_mgr.serialize_type(ty).unwrap();
+        assert!(core::ptr::eq(
+            type_mgr.deserialize_type(&bytes).unwrap(),
+            ty
+        ));
+    }
+}
@@ -5,6 +5,10 @@
 ///   - `Numeric` for arithmetic operations (+, -, *, /, ^)
 ///   - `Indexable` for index operations (arr[i])
 ///   - `Hashable` for use as Map keys
+///   - `Bitwise` for bitwise operations (&, |, xor, <<, >>)
+///   - `HasField` for field access (r.x) on values not yet known to be records
+///   - `Castable` for casts (x as Float) of values whose type is not yet known
+///   - `NumericPromotion` for comparisons of an Int with a Float (1 < 2.5)
 ///
 /// # Design
 ///
@@ -38,7 +42,7 @@ pub enum TypeClassId {
     Hashable,
 
     /// Ordering operations: <, >, <=, >=
-    /// Instances: Int, Float, Str, Bytes
+    /// Instances: Int, Float, Str, Bytes, Option[e] where e: Ord
     /// (Optional for MVP - enables sorting)
     Ord,
 
@@ -46,6 +50,25 @@ pub enum TypeClassId {
     /// Instances: (Str, Str), (Bytes, Bytes), (element, Array), (key, Map)
     /// Note: This is a relational constraint between two types (`has_instance` doesn't apply)
     Containable,
+
+    /// Bitwise operations: &, |, xor, <<, >>
+    /// Instances: Int
+    Bitwise,
+
+    /// Field access: value.field
+    /// Instances: records that have the field
+    /// Note: This is a relational constraint on the field name (`has_instance` doesn't apply)
+    HasField,
+
+    /// Cast operations: value as Type
+    /// Instances: (Int, Float), (Float, Int), (Str, Bytes), (Bytes, Str), any type to itself
+    /// Note: This is a relational constraint on the target type (`has_instance` doesn't apply)
+    Castable,
+
+    /// Mixed numeric comparisons: an Int operand compared with a Float
+    /// Instances: (Int, Float), (Float, Int), any type with itself
+    /// Note: This is a relational constraint between two types (`has_instance` doesn't apply)
+    NumericPromotion,
 }
 
 impl TypeClassId {
@@ -57,6 +80,10 @@ impl TypeClassId {
             TypeClassId::Hashable => "Hashable",
             TypeClassId::Ord => "Ord",
             TypeClassId::Containable => "Containable",
+            TypeClassId::Bitwise => "Bitwise",
+            TypeClassId::HasField => "HasField",
+            TypeClassId::Castable => "Castable",
+            TypeClassId::NumericPromotion => "NumericPromotion",
         }
     }
 
@@ -68,6 +95,10 @@ impl TypeClassId {
             TypeClassId::Hashable => "use as Map keys",
             TypeClassId::Ord => "comparison operations (<, >, <=, >=)",
             TypeClassId::Containable => "containment operations (in, not in)",
+            TypeClassId::Bitwise => "bitwise operations (&, |, xor, <<, >>)",
+            TypeClassId::HasField => "field access (value.field)",
+            TypeClassId::Castable => "cast operations (value as Type)",
+            TypeClassId::NumericPromotion => "comparisons of an Int with a Float",
         }
     }
 
@@ -75,12 +106,18 @@ impl TypeClassId {
     pub fn instances(self) -> &'static str {
         match self {
             TypeClassId::Numeric => "Int, Float",
-            TypeClassId::Indexable => "Array, Map, Bytes",
+            TypeClassId::Indexable => "Array, Map, Bytes, Str",
             TypeClassId::Hashable => {
                 "Int, Float, Bool, Str, Bytes, Symbol, Array (if elements are Hashable)"
             }
-            TypeClassId::Ord => "Int, Float, Str, Bytes",
+            TypeClassId::Ord => "Int, Float, Str, Bytes, Option (if the element is Ord)",
             TypeClassId::Containable => "(Str, Str), (Bytes, Bytes), (element, Array), (key, Map)",
+            TypeClassId::Bitwise => "Int",
+            TypeClassId::HasField => "records that have the accessed field",
+            TypeClassId::Castable => {
+                "(Int, Float), (Float, Int), (Str, Bytes), (Bytes, Str), any type to itself"
+            }
+            TypeClassId::NumericPromotion => "(Int, Float), (Float, Int), any type with itself",
         }
     }
 }
@@ -104,10 +141,11 @@ pub fn has_instance<'a>(ty: &'a Type<'a>, class: TypeClassId) -> bool {
         // Numeric: Int, Float
         (TypeKind::Int | TypeKind::Float, TypeClassId::Numeric) => true,
 
-        // Indexable: Array, Map, Bytes
+        // Indexable: Array, Map, Bytes, Str
         (TypeKind::Array(_), TypeClassId::Indexable) => true,
         (TypeKind::Map(_, _), TypeClassId::Indexable) => true,
         (TypeKind::Bytes, TypeClassId::Indexable) => true,
+        (TypeKind::Str, TypeClassId::Indexable) => true,
 
         // Hashable: Most types except Function, Record, Map
         (TypeKind::Int, TypeClassId::Hashable) => true,
@@ -128,6 +166,12 @@ pub fn has_instance<'a>(ty: &'a Type<'a>, class: TypeClassId) -> bool {
         (TypeKind::Str, TypeClassId::Ord) => true,
         (TypeKind::Bytes, TypeClassId::Ord) => true,
 
+        // Option[e] is Ord if e is Ord, with none before any some
+        (TypeKind::Option(elem_ty), TypeClassId::Ord) => has_instance(elem_ty, TypeClassId::Ord),
+
+        // Bitwise: Int only
+        (TypeKind::Int, TypeClassId::Bitwise) => true,
+
         // Type variables should be resolved before checking instances
         (TypeKind::TypeVar(_), _) => false,
 
@@ -165,6 +209,7 @@ mod tests {
         assert!(has_instance(map, TypeClassId::Indexable));
 
         assert!(has_instance(tm.bytes(), TypeClassId::Indexable));
+        assert!(has_instance(tm.str(), TypeClassId::Indexable));
         assert!(!has_instance(tm.int(), TypeClassId::Indexable));
     }
 
@@ -210,6 +255,17 @@ mod tests {
         assert!(!has_instance(tm.bool(), TypeClassId::Ord));
     }
 
+    #[test]
+    fn test_bitwise_instances() {
+        let bump = Bump::new();
+        let tm = TypeManager::new(&bump);
+
+        assert!(has_instance(tm.int(), TypeClassId::Bitwise));
+        assert!(!has_instance(tm.float(), TypeClassId::Bitwise));
+        assert!(!has_instance(tm.bool(), TypeClassId::Bitwise));
+        assert!(!has_instance(tm.bytes(), TypeClassId::Bitwise));
+    }
+
     #[test]
     fn test_type_class_names() {
         assert_eq!(TypeClassId::Numeric.name(), "Numeric");
@@ -217,5 +273,9 @@ mod tests {
         assert_eq!(TypeClassId::Hashable.name(), "Hashable");
         assert_eq!(TypeClassId::Ord.name(), "Ord");
         assert_eq!(TypeClassId::Containable.name(), "Containable");
+        assert_eq!(TypeClassId::Bitwise.name(), "Bitwise");
+        assert_eq!(TypeClassId::HasField.name(), "HasField");
+        assert_eq!(TypeClassId::Castable.name(), "Castable");
+        assert_eq!(TypeClassId::NumericPromotion.name(), "NumericPromotion");
     }
 }
@@ -106,6 +106,11 @@ impl<'types> TypeClassResolver<'types> {
         self.constraints.add_ord(ty, span);
     }
 
+    /// Adds a bitwise constraint: ty must support bitwise operations
+    pub fn add_bitwise_constraint(&mut self, ty: &'types Type<'types>, span: Span) {
+        self.constraints.add_bitwise(ty, span);
+    }
+
     /// Adds a containable constraint: needle in haystack
     pub fn add_containable_constraint(
         &mut self,
@@ -116,6 +121,47 @@ impl<'types> TypeClassResolver<'types> {
         self.constraints.add_containable(needle, haystack, span);
     }
 
+    /// Adds a field access constraint: record.field => result
+    pub fn add_has_field_constraint(
+        &mut self,
+        record: &'types Type<'types>,
+        field: &'types str,
+        result: &'types Type<'types>,
+        span: Span,
+    ) {
+        self.constraints.add_has_field(record, field, result, span);
+    }
+
+    /// Adds a cast constraint: source as target
+    pub fn add_castable_constraint(
+        &mut self,
+        source: &'types Type<'types>,
+        target: &'types Type<'types>,
+        span: Span,
+    ) {
+        self.constraints.add_castable(source, target, span);
+    }
+
+    /// Adds a numeric promotion constraint: left and right compared as result
+    pub fn add_numeric_promotion_constraint(
+        &mut self,
+        left: &'types Type<'types>,
+        right: &'types Type<'types>,
+        result: &'types Type<'types>,
+        span: Span,
+    ) {
+        self.constraints
+            .add_numeric_promotion(left, right, result, span);
+    }
+
+    /// Adds constraints carried over from another analysis, such as those of
+    /// a polymorphic type bound in the environment.
+    pub fn add_constraints(&mut self, constraints: &[TypeClassConstraint<'types>]) {
+        for constraint in constraints {
+            self.constraints.push(constraint.clone());
+        }
+    }
+
     /// Resolves all constraints with unification.
     ///
     /// This is called after type inference is complete. It:
@@ -139,12 +185,7 @@ impl<'types> TypeClassResolver<'types> {
         B: crate::types::traits::TypeBuilder<'types, Repr = &'types Type<'types>> + 'types,
     {
         let mut errors = Vec::new();
-
-        for constraint in self.constraints.iter() {
-            if let Err(err) = self.resolve_constraint(constraint, unification) {
-                errors.push(err);
-            }
-        }
+        self.resolve_each(unification, |err| errors.push(err));
 
         if errors.is_empty() {
             Ok(())
@@ -153,6 +194,23 @@ impl<'types> TypeClassResolver<'types> {
         }
     }
 
+    /// Like [`resolve_all`](Self::resolve_all), but passes each unsatisfied
+    /// constraint to `on_error` as soon as it is found instead of collecting
+    /// them.
+    pub fn resolve_each<B>(
+        &self,
+        unification: &mut Unification<'types, B>,
+        mut on_error: impl FnMut(ConstraintError),
+    ) where
+        B: crate::types::traits::TypeBuilder<'types, Repr = &'types Type<'types>> + 'types,
+    {
+        for constraint in self.constraints.iter() {
+            if let Err(err) = self.resolve_constraint(constraint, unification) {
+                on_error(err);
+            }
+        }
+    }
+
     /// Resolves a single constraint, performing unification if needed.
     fn resolve_constraint<B>(
         &self,
@@ -176,14 +234,36 @@ impl<'types> TypeClassResolver<'types> {
                 spans,
             } => self.resolve_numeric(*left, *right, *result, unification, spans),
             TypeClassConstraint::Hashable { ty, spans } => {
-                self.resolve_hashable(*ty, unification, spans)
+                self.resolve_instance(ty, TypeClassId::Hashable, unification, spans)
+            }
+            TypeClassConstraint::Ord { ty, spans } => {
+                self.resolve_instance(ty, TypeClassId::Ord, unification, spans)
+            }
+            TypeClassConstraint::Bitwise { ty, spans } => {
+                self.resolve_instance(ty, TypeClassId::Bitwise, unification, spans)
             }
-            TypeClassConstraint::Ord { ty, spans } => self.resolve_ord(*ty, unification, spans),
             TypeClassConstraint::Containable {
                 needle,
                 haystack,
                 spans,
             } => self.resolve_containable(*needle, *haystack, unification, spans),
+            TypeClassConstraint::HasField {
+                record,
+                field,
+                result,
+                spans,
+            } => self.resolve_has_field(record, field, result, unification, spans),
+            TypeClassConstraint::Castable {
+                source,
+                target,
+                spans,
+            } => self.resolve_castable(source, target, unification, spans),
+            TypeClassConstraint::NumericPromotion {
+                left,
+                right,
+                result,
+                spans,
+            } => self.resolve_numeric_promotion(left, right, result, unification, spans),
         }
     }
 
@@ -337,6 +417,37 @@ impl<'types> TypeClassResolver<'types> {
 
                 Ok(())
             }
+            TypeKind::Str => {
+                // Str: index must be Int, result is a single-character Str
+                let int_ty = unification.builder().int();
+                let str_ty = unification.builder().str();
+
+                unification
+                    .unifies_to(index_resolved, int_ty)
+                    .map_err(|_| ConstraintError {
+                        ty: format!("{}", container_resolved),
+                        type_class: TypeClassId::Indexable,
+                        details: format!(
+                            "string indexing requires Int index, found {}",
+                            index_resolved
+                        ),
+                        spans: spans.to_vec(),
+                    })?;
+
+                unification
+                    .unifies_to(result_resolved, str_ty)
+                    .map_err(|_| ConstraintError {
+                        ty: format!("{}", container_resolved),
+                        type_class: TypeClassId::Indexable,
+                        details: format!(
+                            "string indexing returns Str, but expected {}",
+                            result_resolved
+                        ),
+                        spans: spans.to_vec(),
+                    })?;
+
+                Ok(())
+            }
             TypeKind::TypeVar(_) => {
                 // Still unresolved - this is OK, constraint will be checked later
                 // This can happen in polymorphic contexts
@@ -413,10 +524,12 @@ impl<'types> TypeClassResolver<'types> {
         }
     }
 
-    /// Resolves a hashable constraint: ty must be hashable
-    fn resolve_hashable<B>(
+    /// Resolves a single-type constraint (e.g. Hashable, Ord): ty must have
+    /// an instance of `class`
+    fn resolve_instance<B>(
         &self,
         ty: &'types Type<'types>,
+        class: TypeClassId,
         unification: &mut Unification<'types, B>,
         spans: &[Span],
     ) -> Result<(), ConstraintError>
@@ -424,7 +537,6 @@ impl<'types> TypeClassResolver<'types> {
         B: crate::types::traits::TypeBuilder<'types, Repr = &'types Type<'types>> + 'types,
     {
         use crate::types::traits::TypeKind;
-        use crate::types::type_class::TypeClassId;
 
         // Resolve the type through substitution
         let resolved = unification.resolve(ty);
@@ -432,49 +544,21 @@ impl<'types> TypeClassResolver<'types> {
         // Check if it's still a type variable (polymorphic)
         match resolved.view() {
             TypeKind::TypeVar(_) => Ok(()), // Polymorphic, constraint will be checked at instantiation
+            // Option[e] is Ord if e is Ord, and e may itself still be polymorphic
+            TypeKind::Option(elem) if class == TypeClassId::Ord => self
+                .resolve_instance(elem, class, unification, spans)
+                .map_err(|err| ConstraintError {
+                    ty: format!("{}", unification.fully_resolve(resolved)),
+                    ..err
+                }),
             _ => {
-                // Check if the concrete type has the Hashable instance
-                if has_instance(resolved, TypeClassId::Hashable) {
+                // Check if the concrete type has the instance
+                if has_instance(resolved, class) {
                     Ok(())
                 } else {
                     Err(ConstraintError {
                         ty: format!("{}", resolved),
-                        type_class: TypeClassId::Hashable,
-                        details: String::new(),
-                        spans: spans.to_vec(),
-                    })
-                }
-            }
-        }
-    }
-
-    /// Resolves an ord constraint: ty must support ordering
-    fn resolve_ord<B>(
-        &self,
-        ty: &'types Type<'types>,
-        unification: &mut Unification<'types, B>,
-        spans: &[Span],
-    ) -> Result<(), ConstraintError>
-    where
-        B: crate::types::traits::TypeBuilder<'types, Repr = &'types Type<'types>> + 'types,
-    {
-        use crate::types::traits::TypeKind;
-        use crate::types::type_class::TypeClassId;
-
-        // Resolve the type through substitution
-        let resolved = unification.resolve(ty);
-
-        // Check if it's still a type variable (polymorphic)
-        match resolved.view() {
-            TypeKind::TypeVar(_) => Ok(()), // Polymorphic, constraint will be checked at instantiation
-            _ => {
-                // Check if the concrete type has the Ord instance
-                if has_instance(resolved, TypeClassId::Ord) {
-                    Ok(())
-                } else {
-                    Err(ConstraintError {
-                        ty: format!("{}", resolved),
-                        type_class: TypeClassId::Ord,
+                        type_class: class,
                         details: String::new(),
                         spans: spans.to_vec(),
                     })
@@ -598,6 +682,211 @@ impl<'types> TypeClassResolver<'types> {
         }
     }
 
+    /// Resolves a field access constraint: record.field => result
+    ///
+    /// Once the record type is known, unifies result with the type of the field.
+    /// The constraint plays the role of a row variable: it only requires the
+    /// record to have the field, whatever its other fields are.
+    fn resolve_has_field<B>(
+        &self,
+        record: &'types Type<'types>,
+        field: &str,
+        result: &'types Type<'types>,
+        unification: &mut Unification<'types, B>,
+        spans: &[Span],
+    ) -> Result<(), ConstraintError>
+    where
+        B: crate::types::traits::TypeBuilder<'types, Repr = &'types Type<'types>> + 'types,
+    {
+        use crate::types::traits::TypeKind;
+
+        tracing::debug!(
+            record = %record,
+            field = field,
+            result = %result,
+            "Resolving HasField constraint"
+        );
+
+        let record_resolved = unification.resolve(record);
+        let result_resolved = unification.resolve(result);
+
+        match record_resolved.view() {
+            TypeKind::Record(mut fields) => {
+                let Some((_, field_ty)) = fields.find(|(name, _)| *name == field) else {
+                    return Err(ConstraintError {
+                        ty: format!("{}", record_resolved),
+                        type_class: TypeClassId::HasField,
+                        details: format!("record has no field '{}'", field),
+                        spans: spans.to_vec(),
+                    });
+                };
+                unification
+                    .unifies_to(result_resolved, field_ty)
+                    .map_err(|_| ConstraintError {
+                        ty: format!("{}", record_resolved),
+                        type_class: TypeClassId::HasField,
+                        details: format!(
+                            "field '{}' has type {}, expected {}",
+                            field, field_ty, result_resolved
+                        ),
+                        spans: spans.to_vec(),
+                    })?;
+                Ok(())
+            }
+            TypeKind::TypeVar(_) => {
+                // Still unresolved - this is OK, constraint will be checked later
+                // This can happen in polymorphic contexts
+                Ok(())
+            }
+            _ => Err(ConstraintError {
+                ty: format!("{}", record_resolved),
+                type_class: TypeClassId::HasField,
+                details: format!("only records have fields, cannot access '{}'", field),
+                spans: spans.to_vec(),
+            }),
+        }
+    }
+
+    /// Resolves a cast constraint: source as target
+    ///
+    /// Once the source type is known, checks that it can be cast to the target.
+    fn resolve_castable<B>(
+        &self,
+        source: &'types Type<'types>,
+        target: &'types Type<'types>,
+        unification: &mut Unification<'types, B>,
+        spans: &[Span],
+    ) -> Result<(), ConstraintError>
+    where
+        B: crate::types::traits::TypeBuilder<'types, Repr = &'types Type<'types>> + 'types,
+    {
+        use crate::types::traits::TypeKind;
+
+        tracing::debug!(
+            source = %source,
+            target = %target,
+            "Resolving Castable constraint"
+        );
+
+        let source_resolved = unification.resolve(source);
+
+        match source_resolved.view() {
+            TypeKind::TypeVar(_) => {
+                // Still unresolved - this is OK, constraint will be checked later
+                // This can happen in polymorphic contexts
+                Ok(())
+            }
+            _ => crate::casting::validate_cast(source_resolved, target).map_err(|_| {
+                ConstraintError {
+                    ty: format!("{}", source_resolved),
+                    type_class: TypeClassId::Castable,
+                    details: format!("cannot cast to {}", target),
+                    spans: spans.to_vec(),
+                }
+            }),
+        }
+    }
+
+    /// Resolves a numeric promotion constraint: left and right compared as
+    /// result
+    ///
+    /// An `Int` compared with a `Float` is promoted, so result is `Float`.
+    /// Otherwise both operands must have the same type, which is the result.
+    /// While an operand is still a type variable that may turn out to be the
+    /// other numeric type, the constraint stays pending.
+    fn resolve_numeric_promotion<B>(
+        &self,
+        left: &'types Type<'types>,
+        right: &'types Type<'types>,
+        result: &'types Type<'types>,
+        unification: &mut Unification<'types, B>,
+        spans: &[Span],
+    ) -> Result<(), ConstraintError>
+    where
+        B: crate::types::traits::TypeBuilder<'types, Repr = &'types Type<'types>> + 'types,
+    {
+        use crate::types::traits::TypeKind;
+
+        let left_resolved = unification.resolve(left);
+        let right_resolved = unification.resolve(right);
+
+        let promoted = match (left_resolved.view(), right_resolved.view()) {
+            (TypeKind::Int, TypeKind::Float) | (TypeKind::Float, TypeKind::Int) => {
+                unification.builder().float()
+            }
+            (TypeKind::TypeVar(a), TypeKind::TypeVar(b)) if a != b => return Ok(()),
+            (TypeKind::TypeVar(_), TypeKind::Int | TypeKind::Float)
+            | (TypeKind::Int | TypeKind::Float, TypeKind::TypeVar(_)) => return Ok(()),
+            _ => {
+                unification
+                    .unifies_to(left_resolved, right_resolved)
+                    .map_err(|_| ConstraintError {
+                        ty: format!("{}", left_resolved),
+                        type_class: TypeClassId::NumericPromotion,
+                        details: format!(
+                            "operands must have the same type, found {} and {}",
+                            left_resolved, right_resolved
+                        ),
+                        spans: spans.to_vec(),
+                    })?;
+                unification.resolve(left_resolved)
+            }
+        };
+
+        let result_resolved = unification.resolve(result);
+        unification
+            .unifies_to(result_resolved, promoted)
+            .map(|_| ())
+            .map_err(|_| ConstraintError {
+                ty: format!("{}", promoted),
+                type_class: TypeClassId::NumericPromotion,
+                details: format!(
+                    "operands are compared as {}, but expected {}",
+                    promoted, result_resolved
+                ),
+                spans: spans.to_vec(),
+            })
+    }
+
+    /// Unifies both operands of every numeric promotion still pending once
+    /// all constraints are resolved, so they are compared as one type.
+    ///
+    /// Constraints mentioning any of `generalized_vars` belong to polymorphic
+    /// bindings and are resolved at each instantiation instead.
+    pub fn settle_numeric_promotions<B>(
+        &self,
+        generalized_vars: &[u16],
+        unification: &mut Unification<'types, B>,
+    ) -> Vec<ConstraintError>
+    where
+        B: crate::types::traits::TypeBuilder<'types, Repr = &'types Type<'types>> + 'types,
+    {
+        let mut errors = Vec::new();
+        for constraint in self.constraints.iter() {
+            let TypeClassConstraint::NumericPromotion {
+                left,
+                right,
+                result,
+                spans,
+            } = constraint
+            else {
+                continue;
+            };
+            if !self.is_undetermined(constraint, result, generalized_vars, unification) {
+                continue;
+            }
+            // Nothing decides the promotion anymore: compare the operands as
+            // the same type, which always unifies while one is a type variable
+            if unification.unifies_to(left, right).is_ok()
+                && let Err(err) =
+                    self.resolve_numeric_promotion(left, right, result, unification, spans)
+            {
+                errors.push(err);
+            }
+        }
+        errors
+    }
+
     /// Returns a reference to the constraint set.
     pub fn constraint_set(&self) -> &ConstraintSet<'types> {
         &self.constraints
@@ -622,12 +911,15 @@ impl<'types> TypeClassResolver<'types> {
     /// * `subst` - Substitution map from old type variables to fresh types
     /// * `unification` - Unification context for resolving types before checking
     /// * `instantiation_span` - The span of the call site where instantiation occurs
+    ///
+    /// Returns `subst` extended with the fresh variables of the internal ones.
     pub fn copy_constraints_with_subst<B>(
         &mut self,
         subst: &hashbrown::HashMap<u16, &'types Type<'types>>,
         unification: &crate::types::unification::Unification<'types, B>,
         instantiation_span: Span,
-    ) where
+    ) -> hashbrown::HashMap<u16, &'types Type<'types>>
+    where
         B: crate::types::traits::TypeBuilder<'types, Repr = &'types Type<'types>> + 'types,
     {
         // Collect constraints that mention any of the quantified variables.
@@ -700,10 +992,14 @@ impl<'types> TypeClassResolver<'types> {
                         spans: new_spans,
                     });
                 }
+                TypeClassConstraint::Bitwise { ty, .. } => {
+                    self.constraints.push(TypeClassConstraint::Bitwise {
+                        ty: unification.substitute(ty, &extended_subst),
+                        spans: new_spans,
+                    });
+                }
                 TypeClassConstraint::Containable {
-                    needle,
-                    haystack,
-                    ..
+                    needle, haystack, ..
                 } => {
                     self.constraints.push(TypeClassConstraint::Containable {
                         needle: unification.substitute(needle, &extended_subst),
@@ -711,8 +1007,44 @@ impl<'types> TypeClassResolver<'types> {
                         spans: new_spans,
                     });
                 }
+                TypeClassConstraint::HasField {
+                    record,
+                    field,
+                    result,
+                    ..
+                } => {
+                    self.constraints.push(TypeClassConstraint::HasField {
+                        record: unification.substitute(record, &extended_subst),
+                        field,
+                        result: unification.substitute(result, &extended_subst),
+                        spans: new_spans,
+                    });
+                }
+                TypeClassConstraint::Castable { source, target, .. } => {
+                    self.constraints.push(TypeClassConstraint::Castable {
+                        source: unification.substitute(source, &extended_subst),
+                        target,
+                        spans: new_spans,
+                    });
+                }
+                TypeClassConstraint::NumericPromotion {
+                    left,
+                    right,
+                    result,
+                    ..
+                } => {
+                    self.constraints
+                        .push(TypeClassConstraint::NumericPromotion {
+                            left: unification.substitute(left, &extended_subst),
+                            right: unification.substitute(right, &extended_subst),
+                            result: unification.substitute(result, &extended_subst),
+                            spans: new_spans,
+                        });
+                }
             }
         }
+
+        extended_subst
     }
 
     /// Collect type variables from a constraint that aren't in the substitution map,
@@ -749,7 +1081,7 @@ impl<'types> TypeClassResolver<'types> {
             TypeClassConstraint::Hashable { ty, .. } => {
                 self.collect_vars_from_type(*ty, unification, subst);
             }
-            TypeClassConstraint::Ord { ty, .. } => {
+            TypeClassConstraint::Ord { ty, .. } | TypeClassConstraint::Bitwise { ty, .. } => {
                 self.collect_vars_from_type(*ty, unification, subst);
             }
             TypeClassConstraint::Containable {
@@ -758,6 +1090,23 @@ impl<'types> TypeClassResolver<'types> {
                 self.collect_vars_from_type(*needle, unification, subst);
                 self.collect_vars_from_type(*haystack, unification, subst);
             }
+            TypeClassConstraint::HasField { record, result, .. } => {
+                self.collect_vars_from_type(record, unification, subst);
+                self.collect_vars_from_type(result, unification, subst);
+            }
+            TypeClassConstraint::Castable { source, .. } => {
+                self.collect_vars_from_type(source, unification, subst);
+            }
+            TypeClassConstraint::NumericPromotion {
+                left,
+                right,
+                result,
+                ..
+            } => {
+                self.collect_vars_from_type(left, unification, subst);
+                self.collect_vars_from_type(right, unification, subst);
+                self.collect_vars_from_type(result, unification, subst);
+            }
         }
     }
 
@@ -846,7 +1195,7 @@ impl<'types> TypeClassResolver<'types> {
             TypeClassConstraint::Hashable { ty, .. } => {
                 self.type_mentions_var_resolved(*ty, var_id, unification)
             }
-            TypeClassConstraint::Ord { ty, .. } => {
+            TypeClassConstraint::Ord { ty, .. } | TypeClassConstraint::Bitwise { ty, .. } => {
                 self.type_mentions_var_resolved(*ty, var_id, unification)
             }
             TypeClassConstraint::Containable {
@@ -855,6 +1204,23 @@ impl<'types> TypeClassResolver<'types> {
                 self.type_mentions_var_resolved(*needle, var_id, unification)
                     || self.type_mentions_var_resolved(*haystack, var_id, unification)
             }
+            TypeClassConstraint::HasField { record, result, .. } => {
+                self.type_mentions_var_resolved(record, var_id, unification)
+                    || self.type_mentions_var_resolved(result, var_id, unification)
+            }
+            TypeClassConstraint::Castable { source, .. } => {
+                self.type_mentions_var_resolved(source, var_id, unification)
+            }
+            TypeClassConstraint::NumericPromotion {
+                left,
+                right,
+                result,
+                ..
+            } => {
+                self.type_mentions_var_resolved(left, var_id, unification)
+                    || self.type_mentions_var_resolved(right, var_id, unification)
+                    || self.type_mentions_var_resolved(result, var_id, unification)
+            }
         }
     }
 
@@ -892,6 +1258,31 @@ impl<'types> TypeClassResolver<'types> {
         }
     }
 
+    /// Returns the constraints left on the type variables of `ty`, with their
+    /// types resolved and without spans.
+    ///
+    /// These are what a polymorphic type still requires of the types its
+    /// variables are instantiated with, e.g. `Numeric` for `(a, b) => a + b`.
+    pub fn constraints_on<B>(
+        &self,
+        ty: &'types Type<'types>,
+        unification: &Unification<'types, B>,
+    ) -> Vec<TypeClassConstraint<'types>>
+    where
+        B: crate::types::traits::TypeBuilder<'types, Repr = &'types Type<'types>> + 'types,
+    {
+        let vars = unification.free_type_vars(ty);
+        self.constraints
+            .iter()
+            .filter(|constraint| {
+                vars.iter().any(|&var_id| {
+                    self.constraint_mentions_var_resolved(constraint, var_id, unification)
+                })
+            })
+            .map(|constraint| constraint.map_types(|ty| unification.fully_resolve(ty), Vec::new()))
+            .collect()
+    }
+
     /// Finds all type classes that constrain any of the given type variables.
     ///
     /// This is used to determine which type classes a polymorphic lambda uses,
@@ -920,6 +1311,116 @@ impl<'types> TypeClassResolver<'types> {
         result
     }
 
+    /// Returns an error for every indexing whose container type is still
+    /// unknown once all constraints are resolved.
+    ///
+    /// Such a container could be an array, a map, bytes or a string, and
+    /// nothing is left to decide which.
+    pub fn ambiguous_containers<B>(
+        &self,
+        generalized_vars: &[u16],
+        unification: &Unification<'types, B>,
+    ) -> Vec<ConstraintError>
+    where
+        B: crate::types::traits::TypeBuilder<'types, Repr = &'types Type<'types>> + 'types,
+    {
+        self.constraints
+            .iter()
+            .filter_map(|constraint| match constraint {
+                TypeClassConstraint::Indexable {
+                    container, spans, ..
+                } if self.is_undetermined(constraint, container, generalized_vars, unification) => {
+                    Some(ConstraintError {
+                        ty: format!("{}", unification.resolve(container)),
+                        type_class: TypeClassId::Indexable,
+                        details: String::from(
+                            "cannot infer the type of the indexed value, \
+                             which must be used as a concrete Array, Map, Bytes or Str",
+                        ),
+                        spans: spans.clone(),
+                    })
+                }
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Returns the target type and spans of every cast whose source type is
+    /// still unknown once all constraints are resolved.
+    pub fn undetermined_casts<B>(
+        &self,
+        generalized_vars: &[u16],
+        unification: &Unification<'types, B>,
+    ) -> Vec<(&'types Type<'types>, &[Span])>
+    where
+        B: crate::types::traits::TypeBuilder<'types, Repr = &'types Type<'types>> + 'types,
+    {
+        self.constraints
+            .iter()
+            .filter_map(|constraint| match constraint {
+                TypeClassConstraint::Castable {
+                    source,
+                    target,
+                    spans,
+                } if self.is_undetermined(constraint, source, generalized_vars, unification) => {
+                    Some((*target, spans.as_slice()))
+                }
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Checks if `ty`, a type in `constraint`, is an unresolved type variable
+    /// that nothing will determine anymore.
+    ///
+    /// Constraints mentioning any of `generalized_vars` belong to polymorphic
+    /// bindings: their types are determined at each instantiation instead.
+    fn is_undetermined<B>(
+        &self,
+        constraint: &TypeClassConstraint<'types>,
+        ty: &'types Type<'types>,
+        generalized_vars: &[u16],
+        unification: &Unification<'types, B>,
+    ) -> bool
+    where
+        B: crate::types::traits::TypeBuilder<'types, Repr = &'types Type<'types>> + 'types,
+    {
+        use crate::types::traits::TypeKind;
+
+        matches!(unification.resolve(ty).view(), TypeKind::TypeVar(_))
+            && !generalized_vars.iter().any(|&var_id| {
+                self.constraint_mentions_var_resolved(constraint, var_id, unification)
+            })
+    }
+
+    /// Returns every class in `classes` that `ty` has no instance of.
+    ///
+    /// Unlike constraint resolution, which stops at the first failure, this
+    /// checks all requested classes so diagnostics can report them together.
+    /// Classes appear in the result in the order they were requested.
+    ///
+    /// `ty` should be resolved first: type variables have no instances. The
+    /// relational `Containable`, `HasField`, `Castable` and `NumericPromotion`
+    /// classes cannot be checked on a single type and are never reported.
+    pub fn missing_instances(
+        ty: &'types Type<'types>,
+        classes: &[TypeClassId],
+    ) -> Vec<TypeClassId> {
+        classes
+            .iter()
+            .copied()
+            .filter(|&class| {
+                !matches!(
+                    class,
+                    TypeClassId::Containable
+                        | TypeClassId::HasField
+                        | TypeClassId::Castable
+                        | TypeClassId::NumericPromotion
+                ) && !has_instance(ty, class)
+            })
+            .collect()
+    }
+
     /// Clears all constraints.
     pub fn clear(&mut self) {
         self.constraints.clear();
@@ -939,6 +1440,57 @@ mod tests {
     use crate::types::unification::Unification;
     use bumpalo::Bump;
 
+    #[test]
+    fn test_missing_instances_reports_all_failures() {
+        let bump = Bump::new();
+        let tm = TypeManager::new(&bump);
+        let function = tm.function(&[tm.int()], tm.int());
+
+        // Equality is universal (functions compare by reference), so the
+        // checkable classes a function fails are Ord, Hashable and Numeric.
+        let missing = TypeClassResolver::missing_instances(
+            function,
+            &[
+                TypeClassId::Ord,
+                TypeClassId::Hashable,
+                TypeClassId::Numeric,
+            ],
+        );
+        assert_eq!(
+            missing,
+            [
+                TypeClassId::Ord,
+                TypeClassId::Hashable,
+                TypeClassId::Numeric
+            ]
+        );
+    }
+
+    #[test]
+    fn test_missing_instances_skips_satisfied_classes() {
+        let bump = Bump::new();
+        let tm = TypeManager::new(&bump);
+        let classes = [
+            TypeClassId::Numeric,
+            TypeClassId::Indexable,
+            TypeClassId::Hashable,
+            TypeClassId::Ord,
+        ];
+
+        assert_eq!(
+            TypeClassResolver::missing_instances(tm.int(), &classes),
+            [TypeClassId::Indexable]
+        );
+        assert_eq!(
+            TypeClassResolver::missing_instances(tm.array(tm.str()), &classes),
+            [TypeClassId::Numeric, TypeClassId::Ord]
+        );
+        assert!(TypeClassResolver::missing_instances(tm.bool(), &[]).is_empty());
+        assert!(
+            TypeClassResolver::missing_instances(tm.str(), &[TypeClassId::Containable]).is_empty()
+        );
+    }
+
     #[test]
     fn test_indexable_array() {
         let bump = Bump::new();
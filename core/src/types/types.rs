@@ -6,8 +6,9 @@ use core::{
 };
 
 use crate::types::traits::display_type;
+use melbi_types::StableHasher;
 
-#[derive(Serialize, Clone, Hash)]
+#[derive(Serialize, Clone)]
 #[repr(C, u8)]
 pub enum Type<'a> {
     // Type variables.
@@ -51,6 +52,72 @@ impl<'a> Type<'a> {
         // directly without offsetting the pointer.
         unsafe { *<*const _>::from(self).cast::<u8>() }
     }
+
+    /// Whether values of this type can hold functions.
+    pub fn contains_function(&self) -> bool {
+        match self {
+            Type::Function { .. } => true,
+            Type::Array(element) | Type::Option(element) => element.contains_function(),
+            Type::Map(key, value) => key.contains_function() || value.contains_function(),
+            Type::Record(fields) => fields.iter().any(|(_, field)| field.contains_function()),
+            Type::TypeVar(_)
+            | Type::Int
+            | Type::Float
+            | Type::Bool
+            | Type::Str
+            | Type::Bytes
+            | Type::Symbol(_) => false,
+        }
+    }
+
+    /// Structural hash of this type.
+    ///
+    /// Only depends on the structure of the type, not on where it was
+    /// allocated, and is the same across runs and platforms.
+    pub fn stable_hash(&self) -> u64 {
+        let mut hasher = StableHasher::new();
+        self.write_stable_hash(&mut hasher);
+        hasher.finish()
+    }
+
+    fn write_stable_hash(&self, hasher: &mut StableHasher) {
+        hasher.write_u8(self.discriminant());
+        match self {
+            Type::Int | Type::Float | Type::Bool | Type::Str | Type::Bytes => {}
+            Type::TypeVar(id) => hasher.write_u16(*id),
+            Type::Array(elem) | Type::Option(elem) => elem.write_stable_hash(hasher),
+            Type::Map(key, val) => {
+                key.write_stable_hash(hasher);
+                val.write_stable_hash(hasher);
+            }
+            Type::Record(fields) => {
+                hasher.write_usize(fields.len());
+                for (name, ty) in *fields {
+                    hasher.write_str(name);
+                    ty.write_stable_hash(hasher);
+                }
+            }
+            Type::Function { params, ret } => {
+                hasher.write_usize(params.len());
+                for param in *params {
+                    param.write_stable_hash(hasher);
+                }
+                ret.write_stable_hash(hasher);
+            }
+            Type::Symbol(parts) => {
+                hasher.write_usize(parts.len());
+                for part in *parts {
+                    hasher.write_str(part);
+                }
+            }
+        }
+    }
+}
+
+impl Hash for Type<'_> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        state.write_u64(self.stable_hash());
+    }
 }
 
 pub(super) struct CompareTypeArgs<'a>(pub(super) Type<'a>);
@@ -199,4 +266,36 @@ mod tests {
         let ty = &Type::Int;
         assert_eq!(ty.discriminant(), 1);
     }
+
+    #[test]
+    fn test_stable_hash_is_structural() {
+        use crate::types::manager::TypeManager;
+        use bumpalo::Bump;
+        use core::hash::BuildHasher;
+
+        let arena1 = Bump::new();
+        let arena2 = Bump::new();
+        let tm1 = TypeManager::new(&arena1);
+        let tm2 = TypeManager::new(&arena2);
+
+        let record1 = tm1.record(vec![("a", tm1.array(tm1.int())), ("b", tm1.str())]);
+        let record2 = tm2.record(vec![("b", tm2.str()), ("a", tm2.array(tm2.int()))]);
+        assert!(!core::ptr::eq(record1, record2));
+        assert_eq!(record1.stable_hash(), record2.stable_hash());
+
+        let build_hasher = hashbrown::DefaultHashBuilder::default();
+        assert_eq!(
+            build_hasher.hash_one(record1),
+            build_hasher.hash_one(record2)
+        );
+    }
+
+    #[test]
+    fn test_stable_hash_distinguishes_types() {
+        let array_int = Type::Array(&Type::Int);
+        let array_str = Type::Array(&Type::Str);
+        let option_int = Type::Option(&Type::Int);
+        assert_ne!(array_int.stable_hash(), array_str.stable_hash());
+        assert_ne!(array_int.stable_hash(), option_int.stable_hash());
+    }
 }
@@ -562,7 +562,8 @@ impl<'a> Unification<'a, &'a TypeManager<'a>> {
     }
 
     /// Instantiate a type scheme and return both the type and the substitution map.
-    /// The substitution maps from generalized var ID to fresh type variable.
+    /// The substitution maps from generalized var ID to fresh type variable, and
+    /// also covers the internal variables of the constraints copied along.
     pub fn instantiate_with_subst<'arena>(
         &self,
         scheme: &TypeScheme<'a, 'arena>,
@@ -586,11 +587,12 @@ impl<'a> Unification<'a, &'a TypeManager<'a>> {
 
         // Copy constraints ONCE with the full substitution map
         // The instantiation_span is appended to each constraint's span chain
-        constraints.copy_constraints_with_subst(&inst_subst, self, instantiation_span);
+        let extended_subst =
+            constraints.copy_constraints_with_subst(&inst_subst, self, instantiation_span);
 
         // Apply substitution to the type
         let instantiated_ty = self.substitute(scheme.ty, &inst_subst);
-        (instantiated_ty, inst_subst)
+        (instantiated_ty, extended_subst)
     }
 }
 
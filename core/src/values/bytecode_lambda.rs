@@ -162,6 +162,8 @@ impl<'types, 'arena> Function<'types, 'arena> for BytecodeLambda<'types, 'arena>
 
         // Create VM with locals and captures, then execute
         let mut vm = VM::new(ctx.arena(), inst.code, locals, self.captures);
+        vm.set_random(ctx.random());
+        vm.set_steps(ctx.steps());
         let result = vm.run()?;
 
         tracing::trace!(result = ?result, "call_unchecked: result raw");
@@ -3,7 +3,7 @@
 use alloc::string::ToString;
 
 use crate::{
-    Vec,
+    String, Vec,
     syntax::{
         bytes_literal::{QuoteStyle as BytesQuoteStyle, escape_bytes},
         string_literal::{QuoteStyle, escape_string},
@@ -13,6 +13,7 @@ use crate::{
     types::traits::TypeView,
     values::{
         from_raw::TypeError,
+        from_value::FromValue,
         function::Function,
         raw::{ArrayData, MapData, MapEntry, RawValue, RecordData, Slice},
     },
@@ -863,6 +864,33 @@ impl<'ty_arena: 'value_arena, 'value_arena> Value<'ty_arena, 'value_arena> {
         })
     }
 
+    // ============================================================================
+    // Type Information
+    // ============================================================================
+
+    /// Get the name of the value's type, as written in Melbi source.
+    ///
+    /// For example `"Int"`, `"Array[Str]"` or `"Record[a: Int, b: Bool]"`.
+    pub fn type_name(&self) -> String {
+        self.ty.to_string()
+    }
+
+    // ============================================================================
+    // Comparison API
+    // ============================================================================
+
+    /// Structural equality, with the same semantics as Melbi's `==` operator.
+    ///
+    /// Unlike `PartialEq`, which returns `false` for values of different types,
+    /// this returns `TypeError::Mismatch` when the types are incompatible, since
+    /// such a comparison would be rejected by the type checker.
+    pub fn equals(&self, other: &Self) -> Result<bool, TypeError> {
+        if self.ty != other.ty {
+            return Err(TypeError::Mismatch);
+        }
+        Ok(self == other)
+    }
+
     // ============================================================================
     // Dynamic Extraction API
     // ============================================================================
@@ -1000,6 +1028,183 @@ impl<'ty_arena: 'value_arena, 'value_arena> Value<'ty_arena, 'value_arena> {
             _ => Err(TypeError::Mismatch),
         }
     }
+
+    /// Convert this value into a host Rust type.
+    ///
+    /// Nested values are converted recursively, e.g. an `Array[Int]` into a
+    /// `Vec<i64>` or a `Map[Str, Float]` into a `BTreeMap<String, f64>`.
+    /// Returns error if the value's type doesn't match `T`.
+    ///
+    /// See [`FromValue`] for implementing conversions into host structs.
+    pub fn try_into<T: FromValue<'ty_arena, 'value_arena>>(self) -> Result<T, TypeError> {
+        T::from_value(self)
+    }
+}
+
+#[cfg(feature = "json")]
+impl<'ty_arena: 'value_arena, 'value_arena> Value<'ty_arena, 'value_arena> {
+    /// Convert this value into structured JSON.
+    ///
+    /// - `Int` and `Float` become numbers. Floats that JSON can't represent
+    ///   (NaN and infinities) become `null`.
+    /// - `Bool` becomes a boolean and `Str` a string.
+    /// - `Bytes` becomes an array of numbers, one per byte.
+    /// - `Array` becomes an array and `Record` an object.
+    /// - `Map` with `Str` keys becomes an object. Any other key type can't be
+    ///   an object key, so the map becomes an array of `[key, value]` pairs
+    ///   instead, in the map's order.
+    /// - `Option` becomes its inner value, or `null` for none. Nested options
+    ///   are therefore ambiguous: `some none` and `none` both become `null`.
+    /// - Functions and symbols have no JSON counterpart and become `null`.
+    pub fn to_json(&self) -> serde_json::Value {
+        use serde_json::Value as Json;
+
+        match self.ty {
+            Type::Int => Json::from(self.raw.as_int_unchecked()),
+            Type::Float => serde_json::Number::from_f64(self.raw.as_float_unchecked())
+                .map_or(Json::Null, Json::Number),
+            Type::Bool => Json::Bool(self.raw.as_bool_unchecked()),
+            Type::Str => Json::String(self.raw.as_str_unchecked().to_string()),
+            Type::Bytes => Json::Array(
+                self.raw
+                    .as_bytes_unchecked()
+                    .iter()
+                    .map(|byte| Json::from(*byte))
+                    .collect(),
+            ),
+            Type::Array(_) => {
+                let array = self.as_array().unwrap();
+                Json::Array(array.iter().map(|elem| elem.to_json()).collect())
+            }
+            Type::Record(_) => {
+                let record = self.as_record().unwrap();
+                Json::Object(
+                    record
+                        .iter()
+                        .map(|(name, value)| (name.to_string(), value.to_json()))
+                        .collect(),
+                )
+            }
+            Type::Map(Type::Str, _) => {
+                let map = self.as_map().unwrap();
+                Json::Object(
+                    map.iter()
+                        .map(|(key, value)| (key.as_str().unwrap().to_string(), value.to_json()))
+                        .collect(),
+                )
+            }
+            Type::Map(_, _) => {
+                let map = self.as_map().unwrap();
+                Json::Array(
+                    map.iter()
+                        .map(|(key, value)| {
+                            Json::Array(crate::vec![key.to_json(), value.to_json()])
+                        })
+                        .collect(),
+                )
+            }
+            Type::Option(_) => match self.as_option().unwrap() {
+                Some(value) => value.to_json(),
+                None => Json::Null,
+            },
+            Type::Function { .. } | Type::Symbol(_) | Type::TypeVar(_) => Json::Null,
+        }
+    }
+
+    /// Build a value of type `ty` from JSON, validating it against the type.
+    ///
+    /// Accepts the JSON produced by [`Value::to_json`], except that `null`
+    /// is only accepted for `Option` types. Numbers must be integers for
+    /// `Int`, while `Float` accepts any number. Record fields missing from
+    /// the JSON object fail with `TypeError::MissingField`; fields the record
+    /// type doesn't declare are ignored.
+    ///
+    /// Returns `TypeError::Mismatch` for any other disagreement between the
+    /// JSON and the type, including function and symbol types, which have no
+    /// JSON representation.
+    pub fn from_json(
+        arena: &'value_arena bumpalo::Bump,
+        type_mgr: &'ty_arena TypeManager<'ty_arena>,
+        ty: &'ty_arena Type<'ty_arena>,
+        json: &serde_json::Value,
+    ) -> Result<Self, TypeError> {
+        use serde_json::Value as Json;
+
+        match (ty, json) {
+            (Type::Int, Json::Number(number)) => number
+                .as_i64()
+                .map(|value| Value::int(type_mgr, value))
+                .ok_or(TypeError::Mismatch),
+            (Type::Float, Json::Number(number)) => number
+                .as_f64()
+                .map(|value| Value::float(type_mgr, value))
+                .ok_or(TypeError::Mismatch),
+            (Type::Bool, Json::Bool(value)) => Ok(Value::bool(type_mgr, *value)),
+            (Type::Str, Json::String(value)) => Ok(Value::str(arena, ty, value)),
+            (Type::Bytes, Json::Array(elements)) => {
+                let bytes = elements
+                    .iter()
+                    .map(|element| {
+                        element
+                            .as_u64()
+                            .and_then(|byte| u8::try_from(byte).ok())
+                            .ok_or(TypeError::Mismatch)
+                    })
+                    .collect::<Result<Vec<u8>, _>>()?;
+                Ok(Value::bytes(arena, ty, &bytes))
+            }
+            (Type::Array(elem_ty), Json::Array(elements)) => {
+                let elements = elements
+                    .iter()
+                    .map(|element| Value::from_json(arena, type_mgr, elem_ty, element))
+                    .collect::<Result<Vec<_>, _>>()?;
+                Value::array(arena, ty, &elements)
+            }
+            (Type::Record(field_types), Json::Object(object)) => {
+                let fields = field_types
+                    .iter()
+                    .map(|(name, field_ty)| {
+                        let field = object
+                            .get(*name)
+                            .ok_or_else(|| TypeError::MissingField(name.to_string()))?;
+                        Ok((*name, Value::from_json(arena, type_mgr, field_ty, field)?))
+                    })
+                    .collect::<Result<Vec<_>, _>>()?;
+                Value::record(arena, ty, &fields)
+            }
+            (Type::Map(key_ty @ Type::Str, value_ty), Json::Object(object)) => {
+                let pairs = object
+                    .iter()
+                    .map(|(key, value)| {
+                        Ok((
+                            Value::str(arena, key_ty, key),
+                            Value::from_json(arena, type_mgr, value_ty, value)?,
+                        ))
+                    })
+                    .collect::<Result<Vec<_>, _>>()?;
+                Value::map(arena, ty, &pairs)
+            }
+            (Type::Map(key_ty, value_ty), Json::Array(elements)) => {
+                let pairs = elements
+                    .iter()
+                    .map(|element| match element.as_array().map(Vec::as_slice) {
+                        Some([key, value]) => Ok((
+                            Value::from_json(arena, type_mgr, key_ty, key)?,
+                            Value::from_json(arena, type_mgr, value_ty, value)?,
+                        )),
+                        _ => Err(TypeError::Mismatch),
+                    })
+                    .collect::<Result<Vec<_>, _>>()?;
+                Value::map(arena, ty, &pairs)
+            }
+            (Type::Option(_), Json::Null) => Value::optional(arena, ty, None),
+            (Type::Option(inner_ty), _) => {
+                let inner = Value::from_json(arena, type_mgr, inner_ty, json)?;
+                Value::optional(arena, ty, Some(inner))
+            }
+            _ => Err(TypeError::Mismatch),
+        }
+    }
 }
 
 // ============================================================================
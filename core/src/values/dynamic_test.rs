@@ -2,7 +2,10 @@
 //!
 //! This tests the new dynamic API that doesn't require compile-time type knowledge.
 
-use crate::{types::manager::TypeManager, values::dynamic::Value};
+use crate::{
+    types::manager::TypeManager,
+    values::{FromValue, TypeError, dynamic::Value},
+};
 use bumpalo::Bump;
 
 #[test]
@@ -40,6 +43,27 @@ fn test_dynamic_bool() {
     assert_eq!(result, true);
 }
 
+#[test]
+fn test_type_name() {
+    let arena = Bump::new();
+    let type_mgr = TypeManager::new(&arena);
+
+    assert_eq!(Value::int(type_mgr, 42).type_name(), "Int");
+    assert_eq!(Value::str(&arena, type_mgr.str(), "hi").type_name(), "Str");
+
+    let rec_ty = type_mgr.record(vec![("age", type_mgr.int()), ("name", type_mgr.str())]);
+    let rec = Value::record(
+        &arena,
+        rec_ty,
+        &[
+            ("age", Value::int(type_mgr, 30)),
+            ("name", Value::str(&arena, type_mgr.str(), "Alice")),
+        ],
+    )
+    .unwrap();
+    assert_eq!(rec.type_name(), "Record[age: Int, name: Str]");
+}
+
 #[test]
 fn test_dynamic_type_mismatch() {
     let arena = Bump::new();
@@ -771,6 +795,45 @@ fn test_different_types_inequality() {
     assert_ne!(float_val, bool_val);
 }
 
+#[test]
+fn test_equals_nested_records() {
+    let arena = Bump::new();
+    let type_mgr = TypeManager::new(&arena);
+
+    let make = |n: i64| {
+        let inner = Value::record_builder(type_mgr)
+            .field("a", Value::int(type_mgr, n))
+            .field("b", Value::str(&arena, type_mgr.str(), "x"))
+            .build(&arena)
+            .unwrap();
+        Value::record_builder(type_mgr)
+            .field("inner", inner)
+            .build(&arena)
+            .unwrap()
+    };
+
+    assert!(make(1).equals(&make(1)).unwrap());
+    assert!(!make(1).equals(&make(2)).unwrap());
+}
+
+#[test]
+fn test_equals_incompatible_types() {
+    let arena = Bump::new();
+    let type_mgr = TypeManager::new(&arena);
+
+    let record = Value::record_builder(type_mgr)
+        .field("a", Value::int(type_mgr, 1))
+        .build(&arena)
+        .unwrap();
+    let int_val = Value::int(type_mgr, 1);
+
+    assert!(matches!(record.equals(&int_val), Err(TypeError::Mismatch)));
+    assert!(matches!(
+        Value::int(type_mgr, 1).equals(&Value::float(type_mgr, 1.0)),
+        Err(TypeError::Mismatch)
+    ));
+}
+
 // ============================================================================
 // Hash Tests
 // ============================================================================
@@ -1554,3 +1617,335 @@ fn test_record_builder_vs_manual() {
     // Both methods should produce equal records
     assert_eq!(with_builder, manual);
 }
+
+// ============================================================================
+// Host Type Conversion Tests
+// ============================================================================
+
+#[test]
+fn test_try_into_scalars() {
+    let arena = Bump::new();
+    let type_mgr = TypeManager::new(&arena);
+
+    assert_eq!(Value::int(type_mgr, 42).try_into::<i64>().unwrap(), 42);
+    assert_eq!(Value::float(type_mgr, 1.5).try_into::<f64>().unwrap(), 1.5);
+    assert!(Value::bool(type_mgr, true).try_into::<bool>().unwrap());
+    let text: alloc::string::String = Value::str(&arena, type_mgr.str(), "hello")
+        .try_into()
+        .unwrap();
+    assert_eq!(text, "hello");
+
+    assert!(matches!(
+        Value::int(type_mgr, 42).try_into::<f64>(),
+        Err(TypeError::Mismatch)
+    ));
+}
+
+#[test]
+fn test_try_into_vec() {
+    let arena = Bump::new();
+    let type_mgr = TypeManager::new(&arena);
+
+    let array_ty = type_mgr.array(type_mgr.int());
+    let value = Value::array(
+        &arena,
+        array_ty,
+        &[
+            Value::int(type_mgr, 1),
+            Value::int(type_mgr, 2),
+            Value::int(type_mgr, 3),
+        ],
+    )
+    .unwrap();
+
+    let numbers: alloc::vec::Vec<i64> = value.try_into().unwrap();
+    assert_eq!(numbers, alloc::vec![1, 2, 3]);
+
+    // The element type is checked too
+    assert!(matches!(
+        value.try_into::<alloc::vec::Vec<bool>>(),
+        Err(TypeError::Mismatch)
+    ));
+}
+
+#[test]
+fn test_try_into_option_and_map() {
+    let arena = Bump::new();
+    let type_mgr = TypeManager::new(&arena);
+
+    let option_ty = type_mgr.option(type_mgr.int());
+    let some = Value::optional(&arena, option_ty, Some(Value::int(type_mgr, 7))).unwrap();
+    let none = Value::optional(&arena, option_ty, None).unwrap();
+    assert_eq!(some.try_into::<Option<i64>>().unwrap(), Some(7));
+    assert_eq!(none.try_into::<Option<i64>>().unwrap(), None);
+
+    let map_ty = type_mgr.map(type_mgr.str(), type_mgr.int());
+    let value = Value::map(
+        &arena,
+        map_ty,
+        &[
+            (
+                Value::str(&arena, type_mgr.str(), "a"),
+                Value::int(type_mgr, 1),
+            ),
+            (
+                Value::str(&arena, type_mgr.str(), "b"),
+                Value::int(type_mgr, 2),
+            ),
+        ],
+    )
+    .unwrap();
+
+    let map: hashbrown::HashMap<alloc::string::String, i64> = value.try_into().unwrap();
+    assert_eq!(map.len(), 2);
+    assert_eq!(map["a"], 1);
+    assert_eq!(map["b"], 2);
+}
+
+#[test]
+fn test_try_into_host_struct() {
+    #[derive(Debug, PartialEq)]
+    struct Person {
+        name: alloc::string::String,
+        scores: alloc::vec::Vec<i64>,
+    }
+
+    impl<'ty_arena: 'value_arena, 'value_arena> FromValue<'ty_arena, 'value_arena> for Person {
+        fn from_value(value: Value<'ty_arena, 'value_arena>) -> Result<Self, TypeError> {
+            let record = value.as_record()?;
+            let field = |name| record.get(name).ok_or(TypeError::Mismatch);
+            Ok(Person {
+                name: field("name")?.try_into()?,
+                scores: field("scores")?.try_into()?,
+            })
+        }
+    }
+
+    let arena = Bump::new();
+    let type_mgr = TypeManager::new(&arena);
+
+    let scores_ty = type_mgr.array(type_mgr.int());
+    let scores = Value::array(
+        &arena,
+        scores_ty,
+        &[Value::int(type_mgr, 90), Value::int(type_mgr, 85)],
+    )
+    .unwrap();
+    let value = Value::record_builder(type_mgr)
+        .field("name", Value::str(&arena, type_mgr.str(), "Alice"))
+        .field("scores", scores)
+        .build(&arena)
+        .unwrap();
+
+    let person: Person = value.try_into().unwrap();
+    assert_eq!(
+        person,
+        Person {
+            name: "Alice".into(),
+            scores: alloc::vec![90, 85],
+        }
+    );
+
+    // A record missing a field doesn't convert
+    let partial = Value::record_builder(type_mgr)
+        .field("name", Value::str(&arena, type_mgr.str(), "Bob"))
+        .build(&arena)
+        .unwrap();
+    assert!(matches!(
+        partial.try_into::<Person>(),
+        Err(TypeError::Mismatch)
+    ));
+}
+
+// ============================================================================
+// JSON Serialization Tests
+// ============================================================================
+
+#[cfg(feature = "json")]
+#[test]
+fn test_to_json_scalars() {
+    let arena = Bump::new();
+    let type_mgr = TypeManager::new(&arena);
+
+    assert_eq!(Value::int(type_mgr, 42).to_json(), serde_json::json!(42));
+    assert_eq!(
+        Value::float(type_mgr, 1.5).to_json(),
+        serde_json::json!(1.5)
+    );
+    assert_eq!(
+        Value::float(type_mgr, f64::NAN).to_json(),
+        serde_json::json!(null)
+    );
+    assert_eq!(
+        Value::bool(type_mgr, true).to_json(),
+        serde_json::json!(true)
+    );
+    assert_eq!(
+        Value::str(&arena, type_mgr.str(), "hi").to_json(),
+        serde_json::json!("hi")
+    );
+    assert_eq!(
+        Value::bytes(&arena, type_mgr.bytes(), b"ab").to_json(),
+        serde_json::json!([97, 98])
+    );
+}
+
+#[cfg(feature = "json")]
+#[test]
+fn test_to_json_nested_records_and_arrays() {
+    let arena = Bump::new();
+    let type_mgr = TypeManager::new(&arena);
+
+    let tags_ty = type_mgr.array(type_mgr.str());
+    let tags = Value::array(
+        &arena,
+        tags_ty,
+        &[
+            Value::str(&arena, type_mgr.str(), "a"),
+            Value::str(&arena, type_mgr.str(), "b"),
+        ],
+    )
+    .unwrap();
+    let nickname_ty = type_mgr.option(type_mgr.str());
+    let address = Value::record_builder(type_mgr)
+        .field("city", Value::str(&arena, type_mgr.str(), "Recife"))
+        .field("zip", Value::int(type_mgr, 50000))
+        .build(&arena)
+        .unwrap();
+    let person = Value::record_builder(type_mgr)
+        .field("address", address)
+        .field(
+            "nickname",
+            Value::optional(&arena, nickname_ty, None).unwrap(),
+        )
+        .field("tags", tags)
+        .build(&arena)
+        .unwrap();
+    let people = Value::array(&arena, type_mgr.array(person.ty), &[person]).unwrap();
+
+    assert_eq!(
+        people.to_json(),
+        serde_json::json!([{
+            "address": {"city": "Recife", "zip": 50000},
+            "nickname": null,
+            "tags": ["a", "b"],
+        }])
+    );
+}
+
+#[cfg(feature = "json")]
+#[test]
+fn test_to_json_maps() {
+    let arena = Bump::new();
+    let type_mgr = TypeManager::new(&arena);
+
+    let by_name_ty = type_mgr.map(type_mgr.str(), type_mgr.int());
+    let by_name = Value::map(
+        &arena,
+        by_name_ty,
+        &[(
+            Value::str(&arena, type_mgr.str(), "one"),
+            Value::int(type_mgr, 1),
+        )],
+    )
+    .unwrap();
+    assert_eq!(by_name.to_json(), serde_json::json!({"one": 1}));
+
+    // Keys that aren't strings can't be object keys
+    let by_number_ty = type_mgr.map(type_mgr.int(), type_mgr.bool());
+    let by_number = Value::map(
+        &arena,
+        by_number_ty,
+        &[
+            (Value::int(type_mgr, 2), Value::bool(type_mgr, false)),
+            (Value::int(type_mgr, 1), Value::bool(type_mgr, true)),
+        ],
+    )
+    .unwrap();
+    assert_eq!(
+        by_number.to_json(),
+        serde_json::json!([[1, true], [2, false]])
+    );
+}
+
+#[cfg(feature = "json")]
+#[test]
+fn test_from_json_record_schema() {
+    let arena = Bump::new();
+    let type_mgr = TypeManager::new(&arena);
+
+    let person_ty = type_mgr.record(vec![
+        ("age", type_mgr.int()),
+        ("height", type_mgr.float()),
+        ("name", type_mgr.str()),
+        ("nickname", type_mgr.option(type_mgr.str())),
+        ("scores", type_mgr.map(type_mgr.str(), type_mgr.int())),
+        ("tags", type_mgr.array(type_mgr.str())),
+    ]);
+    let json = serde_json::json!({
+        "age": 30,
+        "height": 1,
+        "name": "Alice",
+        "nickname": null,
+        "scores": {"math": 9, "art": 7},
+        "tags": ["a", "b"],
+        "ignored": true,
+    });
+
+    let value = Value::from_json(&arena, type_mgr, person_ty, &json).unwrap();
+    assert!(core::ptr::eq(value.ty, person_ty));
+    let record = value.as_record().unwrap();
+    assert_eq!(record.get("age").unwrap().as_int().unwrap(), 30);
+    // Float fields accept integral numbers
+    assert_eq!(record.get("height").unwrap().as_float().unwrap(), 1.0);
+    assert_eq!(record.get("name").unwrap().as_str().unwrap(), "Alice");
+    assert_eq!(record.get("nickname").unwrap().as_option().unwrap(), None);
+    let scores = record.get("scores").unwrap().as_map().unwrap();
+    assert_eq!(scores.len(), 2);
+    assert_eq!(record.get("tags").unwrap().as_array().unwrap().len(), 2);
+
+    // Converting back drops the fields the type doesn't declare
+    let mut expected = json.clone();
+    expected.as_object_mut().unwrap().remove("ignored");
+    expected["height"] = serde_json::json!(1.0);
+    assert_eq!(value.to_json(), expected);
+}
+
+#[cfg(feature = "json")]
+#[test]
+fn test_from_json_type_mismatch() {
+    let arena = Bump::new();
+    let type_mgr = TypeManager::new(&arena);
+
+    let int_ty = type_mgr.int();
+    let from_json = |ty, json| Value::from_json(&arena, type_mgr, ty, &json);
+
+    // Int rejects numbers that aren't integers
+    assert!(matches!(
+        from_json(int_ty, serde_json::json!(1.5)),
+        Err(TypeError::Mismatch)
+    ));
+    assert!(matches!(
+        from_json(int_ty, serde_json::json!("1")),
+        Err(TypeError::Mismatch)
+    ));
+    assert!(matches!(
+        from_json(int_ty, serde_json::json!(null)),
+        Err(TypeError::Mismatch)
+    ));
+    assert!(matches!(
+        from_json(type_mgr.array(int_ty), serde_json::json!([1, 2.5])),
+        Err(TypeError::Mismatch)
+    ));
+    assert!(matches!(
+        from_json(type_mgr.bytes(), serde_json::json!([1, 256])),
+        Err(TypeError::Mismatch)
+    ));
+
+    // Missing record fields are reported by name
+    let point_ty = type_mgr.record(vec![("x", int_ty), ("y", int_ty)]);
+    match from_json(point_ty, serde_json::json!({"x": 1})) {
+        Err(TypeError::MissingField(name)) => assert_eq!(name, "y"),
+        other => panic!("expected a missing field error, got {:?}", other),
+    }
+}
@@ -1,6 +1,8 @@
 #![allow(unsafe_code)]
 use core::marker::PhantomData;
 
+use crate::String;
+
 use crate::{
     types::{Type, manager::TypeManager},
     values::raw::{ArrayData, RawValue},
@@ -10,6 +12,8 @@ use crate::{
 pub enum TypeError {
     Mismatch,
     IndexOutOfBounds,
+    /// A record field required by the type is missing.
+    MissingField(String),
 }
 
 impl core::fmt::Display for TypeError {
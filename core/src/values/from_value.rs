@@ -0,0 +1,169 @@
+#![allow(unsafe_code)]
+//! Conversion of dynamic values into host Rust types.
+//!
+//! `FromValue` is what `Value::try_into` uses to turn a `Value` into a plain
+//! Rust value, checking the Melbi type along the way. It is implemented for
+//! the scalar types, for the arena-backed `Str` bridge type, and recursively
+//! for `Option`, `Vec` and maps. Host structs can implement it on top of
+//! `Value::as_record`:
+//!
+//! ```
+//! # use bumpalo::Bump;
+//! # use melbi_core::types::manager::TypeManager;
+//! # use melbi_core::values::{TypeError, dynamic::Value, from_value::FromValue};
+//! struct Point {
+//!     x: i64,
+//!     y: i64,
+//! }
+//!
+//! impl<'ty_arena: 'value_arena, 'value_arena> FromValue<'ty_arena, 'value_arena> for Point {
+//!     fn from_value(value: Value<'ty_arena, 'value_arena>) -> Result<Self, TypeError> {
+//!         let record = value.as_record()?;
+//!         let field = |name| record.get(name).ok_or(TypeError::Mismatch);
+//!         Ok(Point {
+//!             x: field("x")?.try_into()?,
+//!             y: field("y")?.try_into()?,
+//!         })
+//!     }
+//! }
+//!
+//! let arena = Bump::new();
+//! let type_mgr = TypeManager::new(&arena);
+//! let ty = type_mgr.record(vec![("x", type_mgr.int()), ("y", type_mgr.int())]);
+//! let value = Value::record(
+//!     &arena,
+//!     ty,
+//!     &[("x", Value::int(type_mgr, 1)), ("y", Value::int(type_mgr, 2))],
+//! )
+//! .unwrap();
+//!
+//! let point: Point = value.try_into().unwrap();
+//! assert_eq!((point.x, point.y), (1, 2));
+//! ```
+
+use alloc::collections::BTreeMap;
+use core::hash::{BuildHasher, Hash};
+
+use crate::{
+    String, ToString, Vec,
+    types::Type,
+    values::{TypeError, dynamic::Value, typed::RawConvertible, typed::Str},
+};
+
+/// Types that a dynamic `Value` can be converted into.
+///
+/// Conversions fail with `TypeError::Mismatch` when the value's type does
+/// not correspond to the target type.
+pub trait FromValue<'ty_arena: 'value_arena, 'value_arena>: Sized {
+    fn from_value(value: Value<'ty_arena, 'value_arena>) -> Result<Self, TypeError>;
+}
+
+impl<'ty_arena: 'value_arena, 'value_arena> FromValue<'ty_arena, 'value_arena>
+    for Value<'ty_arena, 'value_arena>
+{
+    fn from_value(value: Value<'ty_arena, 'value_arena>) -> Result<Self, TypeError> {
+        Ok(value)
+    }
+}
+
+impl<'ty_arena: 'value_arena, 'value_arena> FromValue<'ty_arena, 'value_arena> for i64 {
+    fn from_value(value: Value<'ty_arena, 'value_arena>) -> Result<Self, TypeError> {
+        value.as_int()
+    }
+}
+
+impl<'ty_arena: 'value_arena, 'value_arena> FromValue<'ty_arena, 'value_arena> for f64 {
+    fn from_value(value: Value<'ty_arena, 'value_arena>) -> Result<Self, TypeError> {
+        value.as_float()
+    }
+}
+
+impl<'ty_arena: 'value_arena, 'value_arena> FromValue<'ty_arena, 'value_arena> for bool {
+    fn from_value(value: Value<'ty_arena, 'value_arena>) -> Result<Self, TypeError> {
+        value.as_bool()
+    }
+}
+
+impl<'ty_arena: 'value_arena, 'value_arena> FromValue<'ty_arena, 'value_arena> for String {
+    fn from_value(value: Value<'ty_arena, 'value_arena>) -> Result<Self, TypeError> {
+        value.as_str().map(ToString::to_string)
+    }
+}
+
+impl<'ty_arena: 'value_arena, 'value_arena> FromValue<'ty_arena, 'value_arena>
+    for Str<'value_arena>
+{
+    fn from_value(value: Value<'ty_arena, 'value_arena>) -> Result<Self, TypeError> {
+        match value.ty {
+            // SAFETY: The type was just checked to be Str.
+            Type::Str => Ok(unsafe { Str::from_raw_value(value.as_raw()) }),
+            _ => Err(TypeError::Mismatch),
+        }
+    }
+}
+
+impl<'ty_arena: 'value_arena, 'value_arena, T> FromValue<'ty_arena, 'value_arena> for Option<T>
+where
+    T: FromValue<'ty_arena, 'value_arena>,
+{
+    fn from_value(value: Value<'ty_arena, 'value_arena>) -> Result<Self, TypeError> {
+        value.as_option()?.map(T::from_value).transpose()
+    }
+}
+
+impl<'ty_arena: 'value_arena, 'value_arena, T> FromValue<'ty_arena, 'value_arena> for Vec<T>
+where
+    T: FromValue<'ty_arena, 'value_arena>,
+{
+    fn from_value(value: Value<'ty_arena, 'value_arena>) -> Result<Self, TypeError> {
+        value.as_array()?.iter().map(T::from_value).collect()
+    }
+}
+
+impl<'ty_arena: 'value_arena, 'value_arena, K, V> FromValue<'ty_arena, 'value_arena>
+    for BTreeMap<K, V>
+where
+    K: FromValue<'ty_arena, 'value_arena> + Ord,
+    V: FromValue<'ty_arena, 'value_arena>,
+{
+    fn from_value(value: Value<'ty_arena, 'value_arena>) -> Result<Self, TypeError> {
+        value
+            .as_map()?
+            .iter()
+            .map(|(key, value)| Ok((K::from_value(key)?, V::from_value(value)?)))
+            .collect()
+    }
+}
+
+impl<'ty_arena: 'value_arena, 'value_arena, K, V, S> FromValue<'ty_arena, 'value_arena>
+    for hashbrown::HashMap<K, V, S>
+where
+    K: FromValue<'ty_arena, 'value_arena> + Eq + Hash,
+    V: FromValue<'ty_arena, 'value_arena>,
+    S: BuildHasher + Default,
+{
+    fn from_value(value: Value<'ty_arena, 'value_arena>) -> Result<Self, TypeError> {
+        value
+            .as_map()?
+            .iter()
+            .map(|(key, value)| Ok((K::from_value(key)?, V::from_value(value)?)))
+            .collect()
+    }
+}
+
+#[cfg(feature = "std")]
+impl<'ty_arena: 'value_arena, 'value_arena, K, V, S> FromValue<'ty_arena, 'value_arena>
+    for std::collections::HashMap<K, V, S>
+where
+    K: FromValue<'ty_arena, 'value_arena> + Eq + Hash,
+    V: FromValue<'ty_arena, 'value_arena>,
+    S: BuildHasher + Default,
+{
+    fn from_value(value: Value<'ty_arena, 'value_arena>) -> Result<Self, TypeError> {
+        value
+            .as_map()?
+            .iter()
+            .map(|(key, value)| Ok((K::from_value(key)?, V::from_value(value)?)))
+            .collect()
+    }
+}
@@ -4,6 +4,8 @@
 //! Supports native Rust functions, and will support closures, foreign language functions, etc.
 
 use super::dynamic::Value;
+use super::random::RandomState;
+use super::steps::StepBudget;
 use crate::ToString;
 use crate::evaluator::ExecutionError;
 use crate::types::{Type, manager::TypeManager};
@@ -22,7 +24,7 @@ use bumpalo::Bump;
 /// # Future Extensions
 ///
 /// This struct is designed to be extended with additional resources:
-/// - Execution limits (instruction count, memory budget)
+/// - Memory budget
 /// - Profiling/tracing hooks
 /// - External resource handles
 ///
@@ -39,13 +41,39 @@ use bumpalo::Bump;
 pub struct FfiContext<'types, 'arena> {
     arena: &'arena Bump,
     type_mgr: &'types TypeManager<'types>,
+    random: &'arena RandomState,
+    steps: &'arena StepBudget,
 }
 
 impl<'types, 'arena> FfiContext<'types, 'arena> {
     /// Create a new FFI context with the given arena and type manager.
+    ///
+    /// The context gets a fresh random number generator with the default
+    /// seed and an unlimited step budget. Use
+    /// [`for_execution`](Self::for_execution) to share the state of an
+    /// ongoing execution.
     #[inline]
     pub fn new(arena: &'arena Bump, type_mgr: &'types TypeManager<'types>) -> Self {
-        Self { arena, type_mgr }
+        let random = arena.alloc(RandomState::default());
+        let steps = arena.alloc(StepBudget::default());
+        Self::for_execution(arena, type_mgr, random, steps)
+    }
+
+    /// Create a new FFI context that draws random numbers from `random` and
+    /// counts steps against `steps`.
+    #[inline]
+    pub fn for_execution(
+        arena: &'arena Bump,
+        type_mgr: &'types TypeManager<'types>,
+        random: &'arena RandomState,
+        steps: &'arena StepBudget,
+    ) -> Self {
+        Self {
+            arena,
+            type_mgr,
+            random,
+            steps,
+        }
     }
 
     /// Get the arena for allocating values.
@@ -59,6 +87,18 @@ impl<'types, 'arena> FfiContext<'types, 'arena> {
     pub fn type_mgr(&self) -> &'types TypeManager<'types> {
         self.type_mgr
     }
+
+    /// Get the execution's random number generator.
+    #[inline]
+    pub fn random(&self) -> &'arena RandomState {
+        self.random
+    }
+
+    /// Get the execution's step budget.
+    #[inline]
+    pub fn steps(&self) -> &'arena StepBudget {
+        self.steps
+    }
 }
 
 // ============================================================================
@@ -85,6 +125,17 @@ pub trait Function<'types, 'arena> {
     /// in the safe `call()` wrapper (future feature).
     fn ty(&self) -> &'types Type<'types>;
 
+    /// Whether the function always returns the same result for the same
+    /// arguments, without observing anything outside of them.
+    ///
+    /// Hosts rely on this to cache the results of expressions. The default is
+    /// `false`, since nothing is known about arbitrary host callbacks.
+    /// Functions generated by `#[melbi_fn]` are deterministic unless declared
+    /// with `deterministic = false`.
+    fn is_deterministic(&self) -> bool {
+        false
+    }
+
     /// Call the function with the given arguments, without runtime type checking.
     ///
     /// # Safety
@@ -150,12 +201,27 @@ pub type NativeFn = for<'types, 'arena> fn(
 pub struct NativeFunction<'ty> {
     ty: &'ty Type<'ty>,
     func: NativeFn,
+    deterministic: bool,
 }
 
 impl<'ty> NativeFunction<'ty> {
     /// Create a new native function with its type signature.
+    ///
+    /// The function is treated as non-deterministic; see
+    /// [`deterministic`](Self::deterministic).
     pub fn new(ty: &'ty Type<'ty>, func: NativeFn) -> Self {
-        Self { ty, func }
+        Self {
+            ty,
+            func,
+            deterministic: false,
+        }
+    }
+
+    /// Declare that the function always returns the same result for the same
+    /// arguments (see [`Function::is_deterministic`]).
+    pub fn deterministic(mut self) -> Self {
+        self.deterministic = true;
+        self
     }
 }
 
@@ -164,6 +230,10 @@ impl<'types, 'arena> Function<'types, 'arena> for NativeFunction<'types> {
         self.ty
     }
 
+    fn is_deterministic(&self) -> bool {
+        self.deterministic
+    }
+
     #[allow(unsafe_code)]
     unsafe fn call_unchecked(
         &self,
@@ -4,7 +4,7 @@
 
 use super::dynamic::Value;
 use super::function::{FfiContext, Function};
-use crate::analyzer::typed_expr::TypedExpr;
+use crate::analyzer::typed_expr::{Substitution, TypedExpr};
 use crate::evaluator::{Evaluator, EvaluatorOptions, ExecutionError};
 use crate::scope_stack::CompleteScope;
 use crate::types::{Type, traits::TypeView, unification::Unification};
@@ -39,6 +39,10 @@ pub struct EvalLambda<'types, 'arena> {
 
     /// Captured variables from the enclosing scope
     captures: &'arena [(&'arena str, Value<'types, 'arena>)],
+
+    /// Substitutions of the instantiations the analyzer recorded, if the
+    /// lambda is polymorphic
+    instantiations: &'arena [Substitution<'types, 'arena>],
 }
 
 impl<'types, 'arena> EvalLambda<'types, 'arena> {
@@ -66,8 +70,23 @@ impl<'types, 'arena> EvalLambda<'types, 'arena> {
             params,
             body,
             captures,
+            instantiations: &[],
         }
     }
+
+    /// Monomorphize calls with the substitutions the analyzer recorded for
+    /// each instantiation of a polymorphic lambda.
+    ///
+    /// Unlike argument types, these also determine the type variables
+    /// internal to the body, such as the type a mixed numeric comparison
+    /// promotes its operands to.
+    pub fn with_instantiations(
+        mut self,
+        instantiations: &'arena [Substitution<'types, 'arena>],
+    ) -> Self {
+        self.instantiations = instantiations;
+        self
+    }
 }
 
 impl<'types, 'arena> Function<'types, 'arena> for EvalLambda<'types, 'arena> {
@@ -115,12 +134,30 @@ impl<'types, 'arena> Function<'types, 'arena> for EvalLambda<'types, 'arena> {
         } = self.ty.view()
         {
             let mut unification = Unification::new(type_mgr);
-            for (param_ty, arg) in param_types.zip(args.iter()) {
+            for (param_ty, arg) in param_types.clone().zip(args.iter()) {
                 let _ = unification.unifies_to(param_ty, arg.ty);
             }
-            evaluator.set_monomorphism(unification);
+
+            // Prefer the recorded instantiation these arguments belong to
+            let instantiation = self.instantiations.iter().find_map(|substitution| {
+                let subst = substitution.iter().map(|(&k, &v)| (k, v)).collect();
+                let instantiation = Unification::from_substitution(type_mgr, subst);
+                param_types
+                    .clone()
+                    .all(|param_ty| {
+                        core::ptr::eq(
+                            instantiation.fully_resolve(param_ty),
+                            unification.fully_resolve(param_ty),
+                        )
+                    })
+                    .then_some(instantiation)
+            });
+            evaluator.set_monomorphism(instantiation.unwrap_or(unification));
         }
 
+        evaluator.set_random(ctx.random());
+        evaluator.set_steps(ctx.steps());
+
         // Push captures scope
         if !self.captures.is_empty() {
             evaluator.push_scope(CompleteScope::from_sorted(self.captures));
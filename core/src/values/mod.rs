@@ -1,15 +1,21 @@
 pub mod bytecode_lambda;
 pub mod dynamic;
 pub mod from_raw;
+pub mod from_value;
 pub mod function;
 pub mod lambda;
+pub mod random;
 pub mod raw;
+pub mod steps;
 pub mod typed;
 pub use bytecode_lambda::{BytecodeLambda, LambdaInstantiation};
 pub use from_raw::TypeError;
+pub use from_value::FromValue;
 pub use function::{FfiContext, Function, NativeFn, NativeFunction};
 pub use lambda::EvalLambda;
+pub use random::{DEFAULT_RANDOM_SEED, RandomState};
 pub use raw::{ArrayData, MapData, RawValue, RecordData};
+pub use steps::StepBudget;
 pub use typed::{Array, Bridge, Optional, RawConvertible, Str};
 
 #[cfg(test)]
@@ -0,0 +1,98 @@
+//! Deterministic pseudo-random number generation for native functions.
+//!
+//! Melbi evaluation is pure: running the same expression with the same
+//! arguments and options always produces the same result. Random numbers are
+//! therefore drawn from a seeded generator owned by the execution, never from
+//! OS entropy. The seed comes from [`RunOptions::random_seed`], which defaults
+//! to [`DEFAULT_RANDOM_SEED`].
+//!
+//! [`RunOptions::random_seed`]: crate::api::RunOptions::random_seed
+
+use core::cell::Cell;
+
+/// Seed used when the host does not provide one.
+pub const DEFAULT_RANDOM_SEED: u64 = 0x9E37_79B9_7F4A_7C15;
+
+/// Per-execution pseudo-random number generator state (xorshift64*).
+///
+/// The state lives behind a `Cell` so that native functions can draw numbers
+/// through the shared [`FfiContext`](super::FfiContext). Nested calls (e.g.
+/// lambdas passed to `Array.Map`) share the same state, so the sequence
+/// continues across them instead of restarting.
+#[derive(Debug)]
+pub struct RandomState {
+    state: Cell<u64>,
+}
+
+impl RandomState {
+    /// Create a generator from a seed.
+    ///
+    /// Any seed is valid, including zero: the seed is scrambled before use so
+    /// that nearby seeds give unrelated sequences.
+    pub fn new(seed: u64) -> Self {
+        // xorshift gets stuck at zero, so that state must be avoided.
+        let scrambled = splitmix64(seed);
+        Self {
+            state: Cell::new(if scrambled == 0 { 1 } else { scrambled }),
+        }
+    }
+
+    /// Draw the next 64 random bits.
+    pub fn next_u64(&self) -> u64 {
+        let mut x = self.state.get();
+        x ^= x >> 12;
+        x ^= x << 25;
+        x ^= x >> 27;
+        self.state.set(x);
+        x.wrapping_mul(0x2545_F491_4F6C_DD1D)
+    }
+
+    /// Draw a float uniformly distributed in `[0, 1)`.
+    pub fn next_float(&self) -> f64 {
+        // Use the top 53 bits, which is the precision of an f64 mantissa.
+        (self.next_u64() >> 11) as f64 * (1.0 / (1u64 << 53) as f64)
+    }
+}
+
+impl Default for RandomState {
+    fn default() -> Self {
+        Self::new(DEFAULT_RANDOM_SEED)
+    }
+}
+
+fn splitmix64(seed: u64) -> u64 {
+    let mut z = seed.wrapping_add(0x9E37_79B9_7F4A_7C15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_same_seed_same_sequence() {
+        let a = RandomState::new(42);
+        let b = RandomState::new(42);
+        for _ in 0..100 {
+            assert_eq!(a.next_u64(), b.next_u64());
+        }
+    }
+
+    #[test]
+    fn test_different_seeds_differ() {
+        let a = RandomState::new(1);
+        let b = RandomState::new(2);
+        assert_ne!(a.next_u64(), b.next_u64());
+    }
+
+    #[test]
+    fn test_float_in_unit_interval() {
+        let random = RandomState::new(0);
+        for _ in 0..1000 {
+            let value = random.next_float();
+            assert!((0.0..1.0).contains(&value), "{} not in [0, 1)", value);
+        }
+    }
+}
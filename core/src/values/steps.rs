@@ -0,0 +1,128 @@
+//! Step budget bounding the total work of an execution.
+//!
+//! `max_depth` only bounds how deeply evaluation recurses, so a wide
+//! expression (e.g. a large `where` block, or a lambda mapped over a big
+//! array) can still run for a long time. The budget counts evaluation steps
+//! instead: one per evaluated expression node in the evaluator, and one per
+//! dispatched instruction in the VM. The limit comes from
+//! [`RunOptions::max_steps`], which defaults to unlimited.
+//!
+//! With the `std` feature, the budget can also carry a wall-clock deadline,
+//! from `RunOptions::deadline`. Reading the clock costs far more than a step,
+//! so it is only checked every [`DEADLINE_CHECK_INTERVAL`] steps. Without
+//! `std` there is no clock, and `max_steps` is the only way to bound how long
+//! an execution runs.
+//!
+//! [`RunOptions::max_steps`]: crate::api::RunOptions::max_steps
+
+use core::cell::Cell;
+
+use crate::evaluator::ResourceExceededError;
+
+/// Number of steps between two checks of the deadline.
+pub const DEADLINE_CHECK_INTERVAL: u64 = 1024;
+
+/// Per-execution count of evaluation steps, with an optional limit.
+///
+/// Like [`RandomState`](super::RandomState), the count lives behind a `Cell`
+/// and is shared through the [`FfiContext`](super::FfiContext), so steps
+/// taken inside lambdas called by native functions (e.g. `Array.Map`) count
+/// against the same limit as the rest of the execution.
+#[derive(Debug, Default)]
+pub struct StepBudget {
+    max_steps: Option<u64>,
+    taken: Cell<u64>,
+    #[cfg(feature = "std")]
+    deadline: Option<std::time::Instant>,
+}
+
+impl StepBudget {
+    /// Create a budget allowing at most `max_steps` steps, or any number of
+    /// steps if `None`.
+    pub fn new(max_steps: Option<u64>) -> Self {
+        Self {
+            max_steps,
+            taken: Cell::new(0),
+            #[cfg(feature = "std")]
+            deadline: None,
+        }
+    }
+
+    /// Also fail once `deadline` has passed, if one is given.
+    #[cfg(feature = "std")]
+    pub fn with_deadline(mut self, deadline: Option<std::time::Instant>) -> Self {
+        self.deadline = deadline;
+        self
+    }
+
+    /// Number of steps taken so far.
+    pub fn taken(&self) -> u64 {
+        self.taken.get()
+    }
+
+    /// Take one step, failing once the limit has been reached or, every
+    /// [`DEADLINE_CHECK_INTERVAL`] steps, if the deadline has passed.
+    #[inline]
+    pub fn take(&self) -> Result<(), ResourceExceededError> {
+        let taken = self.taken.get() + 1;
+        match self.max_steps {
+            Some(max_steps) if taken > max_steps => {
+                return Err(ResourceExceededError::TooManySteps { max_steps });
+            }
+            _ => self.taken.set(taken),
+        }
+        #[cfg(feature = "std")]
+        if taken.is_multiple_of(DEADLINE_CHECK_INTERVAL) {
+            self.check_deadline()?;
+        }
+        Ok(())
+    }
+
+    #[cfg(feature = "std")]
+    #[cold]
+    fn check_deadline(&self) -> Result<(), ResourceExceededError> {
+        match self.deadline {
+            Some(deadline) if std::time::Instant::now() >= deadline => {
+                Err(ResourceExceededError::DeadlineExceeded)
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_limit_is_inclusive() {
+        let steps = StepBudget::new(Some(3));
+        for _ in 0..3 {
+            assert!(steps.take().is_ok());
+        }
+        assert_eq!(
+            steps.take(),
+            Err(ResourceExceededError::TooManySteps { max_steps: 3 })
+        );
+        assert_eq!(steps.taken(), 3);
+    }
+
+    #[test]
+    fn test_unlimited() {
+        let steps = StepBudget::default();
+        for _ in 0..1000 {
+            assert!(steps.take().is_ok());
+        }
+        assert_eq!(steps.taken(), 1000);
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_deadline_is_checked_periodically() {
+        let steps = StepBudget::new(None).with_deadline(Some(std::time::Instant::now()));
+        for _ in 1..DEADLINE_CHECK_INTERVAL {
+            assert!(steps.take().is_ok());
+        }
+        assert_eq!(steps.take(), Err(ResourceExceededError::DeadlineExceeded));
+    }
+}
@@ -17,8 +17,8 @@ use crate::{
     evaluator::ExecutionErrorKind,
     parser::ComparisonOp,
     types::Type,
-    values::{RawValue, dynamic::Value},
-    vm::GenericAdapter,
+    values::{RandomState, RawValue, StepBudget, dynamic::Value},
+    vm::{AdapterSpec, GenericAdapter},
 };
 
 /// Adapter for array containment operations (`elem in array` / `elem not in array`).
@@ -42,7 +42,13 @@ impl<'t> GenericAdapter for ArrayContainsAdapter<'t> {
         2 // elem and array
     }
 
-    fn call(&self, _arena: &Bump, args: &[RawValue]) -> Result<RawValue, ExecutionErrorKind> {
+    fn call(
+        &self,
+        _arena: &Bump,
+        _random: &RandomState,
+        _steps: &StepBudget,
+        args: &[RawValue],
+    ) -> Result<RawValue, ExecutionErrorKind> {
         let elem_raw = args[0];
         let array_raw = args[1];
 
@@ -82,4 +88,11 @@ impl<'t> GenericAdapter for ArrayContainsAdapter<'t> {
             self.element_type
         )
     }
+
+    fn spec(&self) -> Option<AdapterSpec<'_>> {
+        Some(AdapterSpec::ArrayContains {
+            element_type: self.element_type,
+            op: self.op,
+        })
+    }
 }
@@ -5,8 +5,8 @@ use bumpalo::Bump;
 use crate::{
     evaluator::ExecutionErrorKind,
     types::{Type, manager::TypeManager},
-    values::{RawValue, dynamic::Value},
-    vm::GenericAdapter,
+    values::{RandomState, RawValue, StepBudget, dynamic::Value},
+    vm::{AdapterSpec, GenericAdapter},
 };
 
 /// Adapter for type cast operations (`value as Type`).
@@ -37,7 +37,13 @@ impl<'t> GenericAdapter for CastAdapter<'t> {
         1 // Just the value to cast
     }
 
-    fn call(&self, arena: &Bump, args: &[RawValue]) -> Result<RawValue, ExecutionErrorKind> {
+    fn call(
+        &self,
+        arena: &Bump,
+        _random: &RandomState,
+        _steps: &StepBudget,
+        args: &[RawValue],
+    ) -> Result<RawValue, ExecutionErrorKind> {
         let raw_value = args[0];
 
         // Convert RawValue to Value using source type
@@ -52,4 +58,11 @@ impl<'t> GenericAdapter for CastAdapter<'t> {
     fn name(&self) -> alloc::string::String {
         alloc::format!("Cast({} -> {})", self.source_type, self.target_type)
     }
+
+    fn spec(&self) -> Option<AdapterSpec<'_>> {
+        Some(AdapterSpec::Cast {
+            source_type: self.source_type,
+            target_type: self.target_type,
+        })
+    }
 }
@@ -1,16 +1,27 @@
 use alloc::boxed::Box;
 
+use bumpalo::Bump;
 use hashbrown::HashSet;
 
 use crate::{
-    Vec,
-    types::Type,
-    values::RawValue,
-    vm::{FunctionAdapter, GenericAdapter, Instruction},
+    String, ToString, Vec,
+    analyzer::ArgumentCoercion,
+    parser::ComparisonOp,
+    types::{Type, manager::TypeManager},
+    values::{RawValue, dynamic::Value},
+    vm::{
+        AdapterSpec, ArrayContainsAdapter, CastAdapter, FormatStrAdapter, FunctionAdapter,
+        GenericAdapter, Instruction, MakeMapAdapter, MapContainsAdapter, MapGetAdapter,
+        OptionCompareAdapter, instruction_set::Operand,
+    },
 };
 
 pub struct Code<'t> {
     pub constants: Vec<RawValue>,
+    /// Types of `constants`, in the same order. Only needed to serialize the
+    /// code with [`to_bytes`](Self::to_bytes); hand-built code may leave it
+    /// empty.
+    pub constant_types: Vec<&'t Type<'t>>,
     /// Function call adapters (specialized for performance).
     pub adapters: Vec<FunctionAdapter<'t>>,
     /// Generic adapters for other operations (Cast, FormatStr, etc.).
@@ -42,8 +53,772 @@ pub enum LambdaKind<'t> {
     Poly { monos: Vec<u32> },
 }
 
+/// Magic bytes at the start of serialized bytecode.
+const MAGIC: [u8; 4] = *b"MLBC";
+
+/// Version of the serialized bytecode format, bumped on incompatible changes.
+pub const BYTECODE_FORMAT_VERSION: u16 = 2;
+
+/// Tags of the constant kinds that can be serialized.
+const CONSTANT_INT: u8 = 0;
+const CONSTANT_FLOAT: u8 = 1;
+const CONSTANT_BOOL: u8 = 2;
+const CONSTANT_STR: u8 = 3;
+const CONSTANT_BYTES: u8 = 4;
+
+/// Tags of the argument coercion policies of function adapters.
+const COERCION_STRICT: u8 = 0;
+const COERCION_LENIENT: u8 = 1;
+
+/// Tags of the generic adapter kinds, one per [`AdapterSpec`] variant.
+const ADAPTER_ARRAY_CONTAINS: u8 = 0;
+const ADAPTER_CAST: u8 = 1;
+const ADAPTER_FORMAT_STR: u8 = 2;
+const ADAPTER_MAKE_MAP: u8 = 3;
+const ADAPTER_MAP_CONTAINS: u8 = 4;
+const ADAPTER_MAP_GET: u8 = 5;
+const ADAPTER_OPTION_COMPARE: u8 = 6;
+
+/// Tags of the lambda kinds.
+const LAMBDA_MONO: u8 = 0;
+const LAMBDA_POLY: u8 = 1;
+
+/// Most levels of lambdas nested in one another that can be deserialized.
+const MAX_LAMBDA_NESTING: usize = 256;
+
+/// Reasons compiled code cannot be serialized.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EncodeError {
+    /// The types of the constants are unknown (hand-built code).
+    MissingConstantTypes,
+    /// A constant of a type that cannot be serialized, such as a record
+    /// global.
+    UnsupportedConstant { ty: String },
+    /// The code refers to a native function, directly or through a package.
+    /// Native functions live in memory and cannot be serialized.
+    FunctionCall,
+    /// The code uses a generic adapter that has no portable form (see
+    /// [`GenericAdapter::spec`]).
+    GenericAdapter { name: String },
+}
+
+impl core::fmt::Display for EncodeError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            EncodeError::MissingConstantTypes => write!(f, "Constant types are unknown"),
+            EncodeError::UnsupportedConstant { ty } => {
+                write!(f, "Constants of type {} cannot be serialized", ty)
+            }
+            EncodeError::FunctionCall => write!(f, "Native functions cannot be serialized"),
+            EncodeError::GenericAdapter { name } => {
+                write!(f, "Adapter {} cannot be serialized", name)
+            }
+        }
+    }
+}
+
+/// Reasons a byte buffer is not valid serialized bytecode.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DecodeError {
+    /// The buffer does not start with the bytecode magic bytes.
+    NotBytecode,
+    /// The buffer was written with a different format version.
+    UnsupportedVersion { version: u16 },
+    /// The buffer ends in the middle of the code.
+    UnexpectedEnd,
+    /// Bytes follow the end of the code.
+    TrailingBytes,
+    /// An unknown constant kind.
+    InvalidConstant { tag: u8 },
+    /// A string that is not valid UTF-8.
+    InvalidUtf8,
+    /// A type that cannot be decoded.
+    InvalidType,
+    /// An unknown opcode, or an invalid operand for its instruction.
+    InvalidInstruction { index: usize, bytes: [u8; 2] },
+    /// An adapter of unknown kind, or with invalid operands.
+    InvalidAdapter { index: usize },
+    /// A lambda of unknown kind, or nested too deeply.
+    InvalidLambda { index: usize },
+    /// The instructions would make the VM run past the end of the code, read
+    /// constants or locals that do not exist, or use a value as the wrong
+    /// kind.
+    InvalidCode { index: usize, reason: &'static str },
+}
+
+impl core::fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            DecodeError::NotBytecode => write!(f, "Not Melbi bytecode"),
+            DecodeError::UnsupportedVersion { version } => write!(
+                f,
+                "Bytecode format version {} is not supported (expected {})",
+                version, BYTECODE_FORMAT_VERSION
+            ),
+            DecodeError::UnexpectedEnd => write!(f, "Bytecode ends unexpectedly"),
+            DecodeError::TrailingBytes => write!(f, "Unexpected bytes after the bytecode"),
+            DecodeError::InvalidConstant { tag } => write!(f, "Invalid constant kind {}", tag),
+            DecodeError::InvalidUtf8 => write!(f, "String is not valid UTF-8"),
+            DecodeError::InvalidType => write!(f, "Invalid type"),
+            DecodeError::InvalidInstruction { index, bytes } => write!(
+                f,
+                "Invalid instruction {:02X} {:02X} at {}",
+                bytes[0], bytes[1], index
+            ),
+            DecodeError::InvalidAdapter { index } => write!(f, "Invalid adapter {}", index),
+            DecodeError::InvalidLambda { index } => write!(f, "Invalid lambda {}", index),
+            DecodeError::InvalidCode { index, reason } => {
+                write!(f, "Invalid bytecode at {}: {}", index, reason)
+            }
+        }
+    }
+}
+
+impl<'t> Code<'t> {
+    /// Serialize the code into a portable byte buffer.
+    ///
+    /// The buffer starts with a header holding the format version
+    /// ([`BYTECODE_FORMAT_VERSION`]), followed by `num_locals`,
+    /// `max_stack_size`, the constants, the instructions, the function
+    /// adapters, the generic adapters and the lambdas, whose code is laid out
+    /// the same way. Types are stored in their postcard form, and integers
+    /// are little-endian.
+    ///
+    /// Constants must be `Int`, `Float`, `Bool`, `Str` or `Bytes`, and
+    /// generic adapters must have a [`spec`](GenericAdapter::spec), as all
+    /// built-in ones do. Code that refers to native functions, such as those
+    /// of the standard library, fails with [`EncodeError::FunctionCall`]:
+    /// they live in memory and have no portable form.
+    pub fn to_bytes(&self) -> Result<Vec<u8>, EncodeError> {
+        let mut bytes = Vec::new();
+        bytes.extend_from_slice(&MAGIC);
+        bytes.extend_from_slice(&BYTECODE_FORMAT_VERSION.to_le_bytes());
+        self.write(&mut bytes)?;
+        Ok(bytes)
+    }
+
+    /// Append the code to `bytes`, without the header.
+    fn write(&self, bytes: &mut Vec<u8>) -> Result<(), EncodeError> {
+        if self.constant_types.len() != self.constants.len() {
+            return Err(EncodeError::MissingConstantTypes);
+        }
+        write_len(bytes, self.num_locals);
+        write_len(bytes, self.max_stack_size);
+
+        write_len(bytes, self.constants.len());
+        for (constant, ty) in self.constants.iter().zip(&self.constant_types) {
+            match ty {
+                Type::Int => {
+                    bytes.push(CONSTANT_INT);
+                    bytes.extend_from_slice(&constant.as_int_unchecked().to_le_bytes());
+                }
+                Type::Float => {
+                    bytes.push(CONSTANT_FLOAT);
+                    bytes.extend_from_slice(&constant.as_float_unchecked().to_le_bytes());
+                }
+                Type::Bool => {
+                    bytes.push(CONSTANT_BOOL);
+                    bytes.push(constant.as_bool_unchecked() as u8);
+                }
+                Type::Str => {
+                    bytes.push(CONSTANT_STR);
+                    write_slice(bytes, constant.as_str_unchecked().as_bytes());
+                }
+                Type::Bytes => {
+                    bytes.push(CONSTANT_BYTES);
+                    write_slice(bytes, constant.as_bytes_unchecked());
+                }
+                _ if ty.contains_function() => return Err(EncodeError::FunctionCall),
+                _ => return Err(EncodeError::UnsupportedConstant { ty: ty.to_string() }),
+            }
+        }
+
+        write_len(bytes, self.instructions.len());
+        for instruction in &self.instructions {
+            bytes.extend_from_slice(&instruction.to_bytes());
+        }
+
+        write_len(bytes, self.adapters.len());
+        for adapter in &self.adapters {
+            write_types(bytes, adapter.param_types());
+            write_types(bytes, adapter.arg_types());
+            bytes.push(match adapter.coercion() {
+                ArgumentCoercion::Strict => COERCION_STRICT,
+                ArgumentCoercion::Lenient => COERCION_LENIENT,
+            });
+        }
+
+        write_len(bytes, self.generic_adapters.len());
+        for adapter in &self.generic_adapters {
+            let spec = adapter.spec().ok_or_else(|| EncodeError::GenericAdapter {
+                name: adapter.name(),
+            })?;
+            write_adapter(bytes, spec);
+        }
+
+        write_len(bytes, self.lambdas.len());
+        for lambda in &self.lambdas {
+            write_type(bytes, lambda.lambda_type);
+            write_len(bytes, lambda.num_captures as usize);
+            match &lambda.kind {
+                LambdaKind::Mono { code } => {
+                    bytes.push(LAMBDA_MONO);
+                    code.write(bytes)?;
+                }
+                LambdaKind::Poly { monos } => {
+                    bytes.push(LAMBDA_POLY);
+                    write_len(bytes, monos.len());
+                    for &mono in monos {
+                        write_len(bytes, mono as usize);
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Deserialize code written by [`to_bytes`](Self::to_bytes).
+    ///
+    /// Types are interned in `type_mgr`, and string and bytes constants are
+    /// allocated in `arena`. The code is verified before it is returned, so
+    /// that a truncated or tampered buffer fails with
+    /// [`DecodeError::InvalidCode`] instead of running outside the code or
+    /// misreading values in the VM.
+    pub fn from_bytes(
+        arena: &'t Bump,
+        type_mgr: &'t TypeManager<'t>,
+        bytes: &[u8],
+    ) -> Result<Self, DecodeError> {
+        Self::from_bytes_with_params(arena, type_mgr, bytes, &[])
+    }
+
+    /// Deserialize code that runs with values of types `params` in its
+    /// first locals, such as code compiled with
+    /// [`BytecodeCompiler::compile_with_params`](crate::compiler::BytecodeCompiler::compile_with_params).
+    ///
+    /// As [`from_bytes`](Self::from_bytes), with the parameters taken into
+    /// account when verifying the code.
+    pub fn from_bytes_with_params(
+        arena: &'t Bump,
+        type_mgr: &'t TypeManager<'t>,
+        bytes: &[u8],
+        params: &[&'t Type<'t>],
+    ) -> Result<Self, DecodeError> {
+        let mut reader = Reader { bytes };
+        if reader.take(MAGIC.len())? != MAGIC {
+            return Err(DecodeError::NotBytecode);
+        }
+        let version = u16::from_le_bytes(reader.array()?);
+        if version != BYTECODE_FORMAT_VERSION {
+            return Err(DecodeError::UnsupportedVersion { version });
+        }
+        let code = Code::read(&mut reader, arena, type_mgr, 0)?;
+        if !reader.bytes.is_empty() {
+            return Err(DecodeError::TrailingBytes);
+        }
+        super::verifier::verify(&code, params)?;
+        Ok(code)
+    }
+
+    /// Read code written by [`write`](Self::write), nested in `depth`
+    /// lambdas.
+    fn read(
+        reader: &mut Reader<'_>,
+        arena: &'t Bump,
+        type_mgr: &'t TypeManager<'t>,
+        depth: usize,
+    ) -> Result<Self, DecodeError> {
+        let num_locals = reader.len()?;
+        let max_stack_size = reader.len()?;
+
+        let num_constants = reader.len()?;
+        let mut constants = Vec::new();
+        let mut constant_types = Vec::new();
+        for _ in 0..num_constants {
+            let value = match reader.byte()? {
+                CONSTANT_INT => Value::int(type_mgr, i64::from_le_bytes(reader.array()?)),
+                CONSTANT_FLOAT => Value::float(type_mgr, f64::from_le_bytes(reader.array()?)),
+                CONSTANT_BOOL => Value::bool(type_mgr, reader.byte()? != 0),
+                CONSTANT_STR => Value::str(arena, type_mgr.str(), reader.str()?),
+                CONSTANT_BYTES => {
+                    let len = reader.len()?;
+                    Value::bytes(arena, type_mgr.bytes(), reader.take(len)?)
+                }
+                tag => return Err(DecodeError::InvalidConstant { tag }),
+            };
+            constants.push(value.as_raw());
+            constant_types.push(value.ty);
+        }
+
+        let num_instructions = reader.len()?;
+        let mut instructions = Vec::new();
+        for index in 0..num_instructions {
+            let bytes = reader.array()?;
+            let instruction = Instruction::from_bytes(bytes)
+                .ok_or(DecodeError::InvalidInstruction { index, bytes })?;
+            instructions.push(instruction);
+        }
+
+        let num_adapters = reader.len()?;
+        let mut adapters = Vec::new();
+        for index in 0..num_adapters {
+            let param_types = reader.types(type_mgr)?;
+            let arg_types = reader.types(type_mgr)?;
+            let coercion = match reader.byte()? {
+                COERCION_STRICT => ArgumentCoercion::Strict,
+                COERCION_LENIENT => ArgumentCoercion::Lenient,
+                _ => return Err(DecodeError::InvalidAdapter { index }),
+            };
+            let adapter = if arg_types.is_empty() {
+                FunctionAdapter::new(type_mgr, param_types)
+            } else if arg_types.len() == param_types.len() {
+                FunctionAdapter::new(type_mgr, param_types).with_coercion(arg_types, coercion)
+            } else {
+                return Err(DecodeError::InvalidAdapter { index });
+            };
+            adapters.push(adapter);
+        }
+
+        let num_generic_adapters = reader.len()?;
+        let mut generic_adapters = Vec::new();
+        for index in 0..num_generic_adapters {
+            generic_adapters.push(read_adapter(reader, type_mgr, index)?);
+        }
+
+        let num_lambdas = reader.len()?;
+        let mut lambdas = Vec::new();
+        for index in 0..num_lambdas {
+            let lambda_type = reader.ty(type_mgr)?;
+            let num_captures = reader.len()? as u32;
+            let kind = match reader.byte()? {
+                LAMBDA_MONO if depth < MAX_LAMBDA_NESTING => LambdaKind::Mono {
+                    code: Code::read(reader, arena, type_mgr, depth + 1)?,
+                },
+                LAMBDA_POLY => {
+                    let num_monos = reader.len()?;
+                    let mut monos = Vec::new();
+                    for _ in 0..num_monos {
+                        monos.push(reader.len()? as u32);
+                    }
+                    LambdaKind::Poly { monos }
+                }
+                _ => return Err(DecodeError::InvalidLambda { index }),
+            };
+            lambdas.push(LambdaCode {
+                lambda_type,
+                num_captures,
+                kind,
+            });
+        }
+
+        Ok(Code {
+            constants,
+            constant_types,
+            adapters,
+            generic_adapters,
+            instructions,
+            num_locals,
+            max_stack_size,
+            lambdas,
+        })
+    }
+
+    /// List the instructions with their decoded operands and the stack depth
+    /// after each one, for tools such as debuggers.
+    ///
+    /// `WideArg` prefixes are folded into the instruction they extend. Unlike
+    /// the `Debug` output, nested lambdas are not included; disassemble their
+    /// code separately.
+    pub fn disassemble(&self) -> Vec<DisasmLine> {
+        let mut lines = Vec::new();
+        // Stack depth at the instructions jumped to, recorded by the jumps
+        let mut jump_depths: hashbrown::HashMap<usize, usize> = hashbrown::HashMap::new();
+        let mut depth = Some(0);
+        let mut wide_arg: usize = 0;
+        let mut prefix_start = None;
+
+        for (addr, &instr) in self.instructions.iter().enumerate() {
+            if let Instruction::WideArg(high) = instr {
+                prefix_start.get_or_insert(addr);
+                wide_arg = (wide_arg | (high as usize)) << 8;
+                continue;
+            }
+            let offset = prefix_start.take().unwrap_or(addr);
+            let arg = wide_arg | (instr.to_bytes()[1] as usize);
+            wide_arg = 0;
+
+            // Code after an unconditional jump is only reached by jumping to it
+            let before = depth.or_else(|| jump_depths.get(&offset).copied());
+            let (pops, pushes) = self.stack_effect(instr, arg);
+            let after = before.map(|before| before.saturating_sub(pops) + pushes);
+
+            let target = get_jump_offset(&instr).map(|_| addr + 1 + arg);
+            if let (Some(target), Some(before), Some(after)) = (target, before, after) {
+                let target_depth = match instr {
+                    // Only pops the option when jumping
+                    Instruction::MatchSomeOrJump(_) => before.saturating_sub(1),
+                    _ => after,
+                };
+                jump_depths.entry(target).or_insert(target_depth);
+            }
+
+            lines.push(DisasmLine {
+                offset,
+                opcode: instr.name(),
+                operand: disasm_operand(instr, arg, target),
+                stack_depth: after,
+            });
+
+            depth = match instr {
+                Instruction::JumpForward(_)
+                | Instruction::PopOtherwiseAndJump(_)
+                | Instruction::Return
+                | Instruction::Halt => None,
+                _ => after,
+            };
+        }
+        lines
+    }
+
+    /// Number of values `instr` pops and pushes, given its full operand.
+    pub(super) fn stack_effect(&self, instr: Instruction, arg: usize) -> (usize, usize) {
+        use Instruction::*;
+        match instr {
+            ConstLoad(_) | ConstInt(_) | ConstUInt(_) | ConstBool(_) | DupN(_) | LoadLocal(_)
+            | LoadCapture(_) => (0, 1),
+            Pop | StoreLocal(_) | PopJumpIfFalse(_) | PopJumpIfTrue(_) | MatchNoneOrJump(_) => {
+                (1, 0)
+            }
+            Swap => (2, 2),
+            NegInt | NegFloat | Not | ArrayLen | ArrayGetConst(_) | MapLen | MapKeys
+            | MapValues | RecordGet(_) | BytesGetConst(_) | StringToBytes | BytesToString
+            | MatchSomeOrJump(_) => (1, 1),
+            IntBinOp(_) | IntCmpOp(_) | IntBitOp(_) | FloatBinOp(_) | FloatCmpOp(_) | And | Or
+            | EqBool | ArrayGet | ArrayConcat | ArrayAppend | MapGet | MapHas | MapRemove
+            | RecordMerge | StrGet | StringCmpOp(_) | BytesGet | BytesCmpOp(_) | Eq | NotEq => {
+                (2, 1)
+            }
+            ArraySlice | StrSlice | BytesSlice | MapInsert => (3, 1),
+            MakeArray(_) | MakeRecord(_) => (arg, 1),
+            MakeMap(_) | MakeMapUnique(_) => (2 * arg, 1),
+            StringFormat(_) => (arg + 1, 1),
+            MakeOption(0) => (0, 1),
+            MakeOption(_) => (1, 1),
+            Call(_) => (self.adapters.get(arg).map_or(0, |a| a.num_args()), 1),
+            CallGenericAdapter(_) => (
+                self.generic_adapters.get(arg).map_or(0, |a| a.num_args()),
+                1,
+            ),
+            MakeClosure(_) => (
+                self.lambdas.get(arg).map_or(0, |l| l.num_captures as usize),
+                1,
+            ),
+            // The result stays on the stack for the caller
+            Return => (0, 0),
+            Halt
+            | WideArg(_)
+            | JumpForward(_)
+            | PushOtherwise(_)
+            | PopOtherwise
+            | PopOtherwiseAndJump(_)
+            | Nop
+            | Breakpoint(_)
+            | CheckLimits
+            | Trace(_)
+            | InlineCache(_) => (0, 0),
+        }
+    }
+}
+
+/// One instruction of a [`Code::disassemble`] listing.
+///
+/// Displays as the opcode followed by the operand, if any, as in
+/// `ConstInt 1`, `IntBinOp +` or `JumpForward @12`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DisasmLine {
+    /// Index of the instruction in [`Code::instructions`], or of its first
+    /// `WideArg` prefix if it has any.
+    pub offset: usize,
+    /// Name of the instruction, as in `"ConstInt"`.
+    pub opcode: &'static str,
+    /// The operand, with any `WideArg` prefixes applied.
+    pub operand: Option<DisasmOperand>,
+    /// Number of values on the stack after the instruction runs and falls
+    /// through to the next one, or `None` if it is unreachable.
+    pub stack_depth: Option<usize>,
+}
+
+/// Decoded operand of a [`DisasmLine`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DisasmOperand {
+    /// A literal value or count, as in `ConstInt` and `MakeArray`.
+    Immediate(i64),
+    /// Index into the constants, locals, captures, adapters or lambdas, or of
+    /// an array element, byte or record field.
+    Index(usize),
+    /// Arithmetic or bitwise operator, as in `IntBinOp`.
+    Operator(char),
+    /// Comparison, as in `IntCmpOp`.
+    Comparison(ComparisonOp),
+    /// Offset of the instruction a jump goes to.
+    Target(usize),
+}
+
+impl core::fmt::Display for DisasmLine {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "{}", self.opcode)?;
+        if let Some(operand) = &self.operand {
+            write!(f, " {}", operand)?;
+        }
+        Ok(())
+    }
+}
+
+impl core::fmt::Display for DisasmOperand {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            DisasmOperand::Immediate(value) => write!(f, "{}", value),
+            DisasmOperand::Index(index) => write!(f, "{}", index),
+            DisasmOperand::Operator(op) => write!(f, "{}", op),
+            DisasmOperand::Comparison(op) => write!(f, "{:?}", op),
+            DisasmOperand::Target(target) => write!(f, "@{}", target),
+        }
+    }
+}
+
+/// Decode the operand of `instr`, given its full operand and jump target.
+fn disasm_operand(instr: Instruction, arg: usize, target: Option<usize>) -> Option<DisasmOperand> {
+    use Instruction::*;
+    if let Some(target) = target {
+        return Some(DisasmOperand::Target(target));
+    }
+    match instr {
+        ConstInt(value) => Some(DisasmOperand::Immediate(value as i64)),
+        ConstUInt(value) | ConstBool(value) | DupN(value) | MakeOption(value)
+        | Breakpoint(value) | Trace(value) | InlineCache(value) => {
+            Some(DisasmOperand::Immediate(value as i64))
+        }
+        MakeArray(_) | MakeMap(_) | MakeMapUnique(_) | MakeRecord(_) | StringFormat(_) => {
+            Some(DisasmOperand::Immediate(arg as i64))
+        }
+        ConstLoad(_)
+        | LoadLocal(_)
+        | StoreLocal(_)
+        | LoadCapture(_)
+        | Call(_)
+        | CallGenericAdapter(_)
+        | MakeClosure(_)
+        | ArrayGetConst(_)
+        | RecordGet(_)
+        | BytesGetConst(_) => Some(DisasmOperand::Index(arg)),
+        IntBinOp(op) | IntBitOp(op) | FloatBinOp(op) => Some(DisasmOperand::Operator(op as char)),
+        IntCmpOp(op) | FloatCmpOp(op) | StringCmpOp(op) | BytesCmpOp(op) => {
+            Some(DisasmOperand::Comparison(op))
+        }
+        _ => None,
+    }
+}
+
+/// Write a length or count as a little-endian `u32`.
+fn write_len(bytes: &mut Vec<u8>, len: usize) {
+    let len = u32::try_from(len).expect("bytecode sections are limited to u32::MAX entries");
+    bytes.extend_from_slice(&len.to_le_bytes());
+}
+
+/// Write a length-prefixed byte slice.
+fn write_slice(bytes: &mut Vec<u8>, data: &[u8]) {
+    write_len(bytes, data.len());
+    bytes.extend_from_slice(data);
+}
+
+/// Write a type in its postcard form, length-prefixed.
+fn write_type(bytes: &mut Vec<u8>, ty: &Type<'_>) {
+    let encoded = postcard::to_allocvec(ty).expect("types are always serializable");
+    write_slice(bytes, &encoded);
+}
+
+/// Write a count-prefixed list of types.
+fn write_types(bytes: &mut Vec<u8>, types: &[&Type<'_>]) {
+    write_len(bytes, types.len());
+    for ty in types {
+        write_type(bytes, ty);
+    }
+}
+
+/// Write a generic adapter as its tag followed by its operands.
+fn write_adapter(bytes: &mut Vec<u8>, spec: AdapterSpec<'_>) {
+    match spec {
+        AdapterSpec::ArrayContains { element_type, op } => {
+            bytes.push(ADAPTER_ARRAY_CONTAINS);
+            write_type(bytes, element_type);
+            bytes.push(op.encode());
+        }
+        AdapterSpec::Cast {
+            source_type,
+            target_type,
+        } => {
+            bytes.push(ADAPTER_CAST);
+            write_type(bytes, source_type);
+            write_type(bytes, target_type);
+        }
+        AdapterSpec::FormatStr { expr_types, strs } => {
+            bytes.push(ADAPTER_FORMAT_STR);
+            write_types(bytes, expr_types);
+            write_len(bytes, strs.len());
+            for part in strs {
+                write_slice(bytes, part.as_bytes());
+            }
+        }
+        AdapterSpec::MakeMap {
+            key_type,
+            num_pairs,
+            unique,
+        } => {
+            bytes.push(ADAPTER_MAKE_MAP);
+            write_type(bytes, key_type);
+            write_len(bytes, num_pairs);
+            bytes.push(unique as u8);
+        }
+        AdapterSpec::MapContains { key_type, op } => {
+            bytes.push(ADAPTER_MAP_CONTAINS);
+            write_type(bytes, key_type);
+            bytes.push(op.encode());
+        }
+        AdapterSpec::MapGet { key_type } => {
+            bytes.push(ADAPTER_MAP_GET);
+            write_type(bytes, key_type);
+        }
+        AdapterSpec::OptionCompare { option_type, op } => {
+            bytes.push(ADAPTER_OPTION_COMPARE);
+            write_type(bytes, option_type);
+            bytes.push(op.encode());
+        }
+    }
+}
+
+/// Read a generic adapter written by [`write_adapter`], the `index`th of
+/// its code.
+fn read_adapter<'t>(
+    reader: &mut Reader<'_>,
+    type_mgr: &'t TypeManager<'t>,
+    index: usize,
+) -> Result<Box<dyn GenericAdapter + 't>, DecodeError> {
+    let invalid = DecodeError::InvalidAdapter { index };
+    // `in` and `not in` are containment tests, the other operators compare
+    let containment = |op: &ComparisonOp| matches!(op, ComparisonOp::In | ComparisonOp::NotIn);
+
+    Ok(match reader.byte()? {
+        ADAPTER_ARRAY_CONTAINS => {
+            let element_type = reader.ty(type_mgr)?;
+            let op = reader.comparison_op()?.filter(containment).ok_or(invalid)?;
+            Box::new(ArrayContainsAdapter::new(element_type, op))
+        }
+        ADAPTER_CAST => {
+            let source_type = reader.ty(type_mgr)?;
+            let target_type = reader.ty(type_mgr)?;
+            Box::new(CastAdapter::new(type_mgr, source_type, target_type))
+        }
+        ADAPTER_FORMAT_STR => {
+            let expr_types = reader.types(type_mgr)?;
+            let num_strs = reader.len()?;
+            if num_strs != expr_types.len() + 1 {
+                return Err(invalid);
+            }
+            let mut strs = Vec::new();
+            for _ in 0..num_strs {
+                strs.push(reader.str()?);
+            }
+            Box::new(FormatStrAdapter::new(type_mgr, &expr_types, &strs))
+        }
+        ADAPTER_MAKE_MAP => {
+            let key_type = reader.ty(type_mgr)?;
+            let num_pairs = reader.len()?;
+            let unique = match reader.byte()? {
+                0 => false,
+                1 => true,
+                _ => return Err(invalid),
+            };
+            Box::new(MakeMapAdapter::new(key_type, num_pairs, unique))
+        }
+        ADAPTER_MAP_CONTAINS => {
+            let key_type = reader.ty(type_mgr)?;
+            let op = reader.comparison_op()?.filter(containment).ok_or(invalid)?;
+            Box::new(MapContainsAdapter::new(key_type, op))
+        }
+        ADAPTER_MAP_GET => Box::new(MapGetAdapter::new(reader.ty(type_mgr)?)),
+        ADAPTER_OPTION_COMPARE => {
+            let option_type = reader.ty(type_mgr)?;
+            let op = reader
+                .comparison_op()?
+                .filter(|op| !containment(op))
+                .ok_or(invalid)?;
+            Box::new(OptionCompareAdapter::new(option_type, op))
+        }
+        _ => return Err(invalid),
+    })
+}
+
+/// Cursor over serialized bytecode.
+struct Reader<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, len: usize) -> Result<&'a [u8], DecodeError> {
+        if self.bytes.len() < len {
+            return Err(DecodeError::UnexpectedEnd);
+        }
+        let (taken, rest) = self.bytes.split_at(len);
+        self.bytes = rest;
+        Ok(taken)
+    }
+
+    fn array<const N: usize>(&mut self) -> Result<[u8; N], DecodeError> {
+        let mut array = [0; N];
+        array.copy_from_slice(self.take(N)?);
+        Ok(array)
+    }
+
+    fn byte(&mut self) -> Result<u8, DecodeError> {
+        Ok(self.array::<1>()?[0])
+    }
+
+    fn len(&mut self) -> Result<usize, DecodeError> {
+        Ok(u32::from_le_bytes(self.array()?) as usize)
+    }
+
+    fn str(&mut self) -> Result<&'a str, DecodeError> {
+        let len = self.len()?;
+        core::str::from_utf8(self.take(len)?).map_err(|_| DecodeError::InvalidUtf8)
+    }
+
+    fn ty<'t>(&mut self, type_mgr: &'t TypeManager<'t>) -> Result<&'t Type<'t>, DecodeError> {
+        let len = self.len()?;
+        type_mgr
+            .deserialize_type(self.take(len)?)
+            .map_err(|_| DecodeError::InvalidType)
+    }
+
+    fn types<'t>(
+        &mut self,
+        type_mgr: &'t TypeManager<'t>,
+    ) -> Result<Vec<&'t Type<'t>>, DecodeError> {
+        let len = self.len()?;
+        let mut types = Vec::new();
+        for _ in 0..len {
+            types.push(self.ty(type_mgr)?);
+        }
+        Ok(types)
+    }
+
+    /// Read a comparison operator, or `None` if the byte is not one.
+    fn comparison_op(&mut self) -> Result<Option<ComparisonOp>, DecodeError> {
+        Ok(ComparisonOp::decode(self.byte()?))
+    }
+}
+
 /// Extract jump offset from an instruction, if it's a jump instruction.
-fn get_jump_offset(instr: &Instruction) -> Option<u8> {
+pub(crate) fn get_jump_offset(instr: &Instruction) -> Option<u8> {
     match instr {
         Instruction::JumpForward(offset)
         | Instruction::PopJumpIfFalse(offset)
@@ -8,8 +8,8 @@ use crate::{
     String, Vec,
     evaluator::ExecutionErrorKind,
     types::{Type, manager::TypeManager},
-    values::{RawValue, dynamic::Value},
-    vm::GenericAdapter,
+    values::{RandomState, RawValue, StepBudget, dynamic::Value},
+    vm::{AdapterSpec, GenericAdapter},
 };
 
 /// Adapter for format string operations (`f"Hello {name}"`).
@@ -44,7 +44,13 @@ impl<'t> GenericAdapter for FormatStrAdapter<'t> {
         self.expr_types.len()
     }
 
-    fn call(&self, arena: &Bump, args: &[RawValue]) -> Result<RawValue, ExecutionErrorKind> {
+    fn call(
+        &self,
+        arena: &Bump,
+        _random: &RandomState,
+        _steps: &StepBudget,
+        args: &[RawValue],
+    ) -> Result<RawValue, ExecutionErrorKind> {
         // Build result string: strs[0] + format(args[0]) + strs[1] + ...
         let mut result = String::new();
         result.push_str(&self.strs[0]);
@@ -70,4 +76,11 @@ impl<'t> GenericAdapter for FormatStrAdapter<'t> {
             alloc::format!("FormatStr({})", types.join(", "))
         }
     }
+
+    fn spec(&self) -> Option<AdapterSpec<'_>> {
+        Some(AdapterSpec::FormatStr {
+            expr_types: &self.expr_types,
+            strs: &self.strs,
+        })
+    }
 }
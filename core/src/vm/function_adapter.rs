@@ -2,9 +2,10 @@ use bumpalo::Bump;
 
 use crate::{
     Vec,
+    analyzer::ArgumentCoercion,
     evaluator::ExecutionErrorKind,
     types::{Type, manager::TypeManager},
-    values::{RawValue, dynamic::Value, function::FfiContext},
+    values::{RandomState, RawValue, StepBudget, dynamic::Value, function::FfiContext},
     vm::GenericAdapter,
 };
 
@@ -13,20 +14,56 @@ use crate::{
 /// we use a `FunctionAdapter` to add/remove types at the boundary between the
 /// VM and the host language. From VM -> host language: add types, and from
 /// host language -> VM: remove types.
+///
+/// Arguments whose call-site type differs from the declared parameter type
+/// are converted according to the call's [`ArgumentCoercion`] policy before
+/// the function is invoked.
 pub struct FunctionAdapter<'t> {
     type_mgr: &'t TypeManager<'t>,
     types: Vec<&'t Type<'t>>,
+    /// Call-site argument types, empty when arguments match `types` exactly.
+    arg_types: Vec<&'t Type<'t>>,
+    coercion: ArgumentCoercion,
 }
 
 impl<'t> FunctionAdapter<'t> {
     pub fn new(type_mgr: &'t TypeManager<'t>, types: Vec<&'t Type<'t>>) -> Self {
-        FunctionAdapter { type_mgr, types }
+        FunctionAdapter {
+            type_mgr,
+            types,
+            arg_types: Vec::new(),
+            coercion: ArgumentCoercion::Strict,
+        }
+    }
+
+    /// Convert arguments of the given call-site types to the parameter types
+    /// wherever `coercion` allows it.
+    pub fn with_coercion(
+        mut self,
+        arg_types: Vec<&'t Type<'t>>,
+        coercion: ArgumentCoercion,
+    ) -> Self {
+        debug_assert_eq!(arg_types.len(), self.types.len());
+        self.arg_types = arg_types;
+        self.coercion = coercion;
+        self
     }
 
     /// Get the parameter types for debugging.
     pub fn param_types(&self) -> &[&'t Type<'t>] {
         &self.types
     }
+
+    /// Get the call-site argument types, empty when they are the parameter
+    /// types.
+    pub fn arg_types(&self) -> &[&'t Type<'t>] {
+        &self.arg_types
+    }
+
+    /// Get the conversion policy for arguments of other types.
+    pub fn coercion(&self) -> ArgumentCoercion {
+        self.coercion
+    }
 }
 
 impl<'t> GenericAdapter for FunctionAdapter<'t> {
@@ -36,7 +73,13 @@ impl<'t> GenericAdapter for FunctionAdapter<'t> {
     }
 
     #[allow(unsafe_code)]
-    fn call(&self, arena: &Bump, args: &[RawValue]) -> Result<RawValue, ExecutionErrorKind> {
+    fn call(
+        &self,
+        arena: &Bump,
+        random: &RandomState,
+        steps: &StepBudget,
+        args: &[RawValue],
+    ) -> Result<RawValue, ExecutionErrorKind> {
         debug_assert_eq!(args.len(), self.num_args());
 
         // Last element is the function, rest are arguments
@@ -45,13 +88,21 @@ impl<'t> GenericAdapter for FunctionAdapter<'t> {
             .expect("args should contain at least the function");
         let func = *func;
 
-        let typed_args: Vec<_> = arguments
+        let typed_args = arguments
             .iter()
             .zip(self.types.iter())
-            .map(|(arg, ty)| Value::from_raw_unchecked(ty, *arg))
-            .collect();
+            .enumerate()
+            .map(|(index, (arg, ty))| match self.arg_types.get(index) {
+                Some(&arg_ty) if self.coercion.allows(arg_ty, ty) => {
+                    let value = Value::from_raw_unchecked(arg_ty, *arg);
+                    crate::casting::perform_cast(arena, value, ty, self.type_mgr)
+                        .map_err(ExecutionErrorKind::from)
+                }
+                _ => Ok(Value::from_raw_unchecked(ty, *arg)),
+            })
+            .collect::<Result<Vec<_>, _>>()?;
 
-        let ctx = FfiContext::new(arena, self.type_mgr);
+        let ctx = FfiContext::for_execution(arena, self.type_mgr, random, steps);
 
         unsafe {
             let func_ref = func.as_function_unchecked();
@@ -1,6 +1,12 @@
+use alloc::boxed::Box;
 use bumpalo::Bump;
 
-use crate::{evaluator::ExecutionErrorKind, values::RawValue};
+use crate::{
+    evaluator::ExecutionErrorKind,
+    parser::ComparisonOp,
+    types::Type,
+    values::{RandomState, RawValue, StepBudget},
+};
 
 /// A generic adapter for VM operations that need type information at runtime.
 ///
@@ -15,10 +21,59 @@ pub trait GenericAdapter {
     ///
     /// For `FunctionAdapter`: args includes the function as the last element.
     /// For `CastAdapter`: args contains exactly one element (the value to cast).
-    fn call(&self, arena: &Bump, args: &[RawValue]) -> Result<RawValue, ExecutionErrorKind>;
+    ///
+    /// `random` is the execution's random number generator and `steps` its
+    /// step budget, both forwarded to native functions.
+    fn call(
+        &self,
+        arena: &Bump,
+        random: &RandomState,
+        steps: &StepBudget,
+        args: &[RawValue],
+    ) -> Result<RawValue, ExecutionErrorKind>;
 
     /// Return a human-readable description of this adapter for debugging.
     ///
     /// Example: "ArrayContains(Str, Array[Str])" or "Cast(Int -> Float)"
     fn name(&self) -> alloc::string::String;
+
+    /// Describe this adapter for serialization, or `None` if it has no
+    /// portable form, in which case code using it cannot be serialized.
+    fn spec(&self) -> Option<AdapterSpec<'_>> {
+        None
+    }
+}
+
+/// The kind and operands of a built-in generic adapter, from which
+/// [`Code::from_bytes`](crate::vm::Code::from_bytes) rebuilds it.
+#[derive(Debug, Clone, Copy)]
+pub enum AdapterSpec<'a> {
+    ArrayContains {
+        element_type: &'a Type<'a>,
+        op: ComparisonOp,
+    },
+    Cast {
+        source_type: &'a Type<'a>,
+        target_type: &'a Type<'a>,
+    },
+    FormatStr {
+        expr_types: &'a [&'a Type<'a>],
+        strs: &'a [Box<str>],
+    },
+    MakeMap {
+        key_type: &'a Type<'a>,
+        num_pairs: usize,
+        unique: bool,
+    },
+    MapContains {
+        key_type: &'a Type<'a>,
+        op: ComparisonOp,
+    },
+    MapGet {
+        key_type: &'a Type<'a>,
+    },
+    OptionCompare {
+        option_type: &'a Type<'a>,
+        op: ComparisonOp,
+    },
 }
@@ -142,10 +142,17 @@ pub enum Instruction {
     /// - `b'+'` (0x2B): Addition
     /// - `b'-'` (0x2D): Subtraction
     /// - `b'*'` (0x2A): Multiplication
-    /// - `b'/'` (0x2F): Division (can error)
-    /// - `b'%'` (0x25): Modulo (can error)
+    /// - `b'/'` (0x2F): Euclidean division (can error)
+    /// - `b'%'` (0x25): Euclidean modulo (can error)
+    /// - `b'q'` (0x71): Truncated division (can error)
+    /// - `b'r'` (0x72): Truncated remainder (can error)
+    /// - `b'f'` (0x66): Floored division (can error)
+    /// - `b'm'` (0x6D): Floored modulo (can error)
     /// - `b'^'` (0x5E): Power (can error)
     ///
+    /// See [`IntegerDivision`](crate::analyzer::IntegerDivision) for the
+    /// rounding of each kind of division.
+    ///
     /// Stack: [..., a: Int, b: Int] -> [..., result: Int(|!)]
     IntBinOp(u8) = 0x10,
 
@@ -158,7 +165,21 @@ pub enum Instruction {
     /// Stack: [..., a: Int, b: Int] -> [..., result: Bool]
     IntCmpOp(ComparisonOp) = 0x14,
 
-    // 0x15-0x1F reserved for future int operations
+    /// Integer bitwise operation
+    ///
+    /// Operand encodes the operation:
+    /// - `b'&'`: Bitwise and
+    /// - `b'|'`: Bitwise or
+    /// - `b'^'`: Bitwise xor
+    /// - `b'<'`: Shift left (can error)
+    /// - `b'>'`: Arithmetic shift right (can error)
+    ///
+    /// Shifts error if the amount is outside `[0, 64)`.
+    ///
+    /// Stack: [..., a: Int, b: Int] -> [..., result: Int(|!)]
+    IntBitOp(u8) = 0x15,
+
+    // 0x16-0x1F reserved for future int operations
 
     // ========================================================================
     // Arithmetic - Float (0x20 - 0x2F)
@@ -170,6 +191,7 @@ pub enum Instruction {
     /// - `b'-'`: Subtraction
     /// - `b'*'`: Multiplication
     /// - `b'/'`: Division
+    /// - `b'%'`: Modulo (NaN for a zero divisor)
     /// - `b'^'`: Power
     ///
     /// Stack: [..., a: Float, b: Float] -> [..., result: Float]
@@ -298,7 +320,9 @@ pub enum Instruction {
     ArrayConcat = 0x64,
 
     /// Slice array
-    /// Stack: [..., arr: Array[T], start: Int, end: Int] -> [..., slice: Array[T]!]
+    ///
+    /// Negative bounds count from the end; out-of-range bounds are clamped.
+    /// Stack: [..., arr: Array[T], start: Int, end: Int] -> [..., slice: Array[T]]
     ArraySlice = 0x65,
 
     /// Append element to array (creates new array)
@@ -310,7 +334,7 @@ pub enum Instruction {
     // ========================================================================
     // Map Operations (0x70 - 0x7F)
     // ========================================================================
-    /// Make map with N key-value pairs
+    /// Make map with N key-value pairs (Int keys; see `MakeMapAdapter`)
     /// Operand: u8 count | Stack: [..., k1, v1, ..., kN, vN] -> [..., map]
     MakeMap(u8) = 0x70,
 
@@ -318,12 +342,12 @@ pub enum Instruction {
     /// Stack: [..., map: Map[K,V]] -> [..., size: Int]
     MapLen = 0x71,
 
-    /// Get value from map
+    /// Get value from map (Int keys; see `MapGetAdapter`)
     /// Stack: [..., map: Map[K,V], key: K] -> [..., value: V!]
     MapGet = 0x72,
 
-    /// Check if key exists
-    /// Stack: [..., map: Map[K,V], key: K] -> [..., exists: Bool]
+    /// Check if key exists, as in `key in map` (Int keys; see `MapContainsAdapter`)
+    /// Stack: [..., key: K, map: Map[K,V]] -> [..., exists: Bool]
     MapHas = 0x73,
 
     /// Insert key-value (creates new map)
@@ -342,7 +366,11 @@ pub enum Instruction {
     /// Stack: [..., map: Map[K,V]] -> [..., values: Array[V]]
     MapValues = 0x77,
 
-    // 0x78-0x7F reserved for map operations
+    /// Make map with N key-value pairs, failing if two keys are equal (Int keys)
+    /// Operand: u8 count | Stack: [..., k1, v1, ..., kN, vN] -> [..., map!]
+    MakeMapUnique(u8) = 0x78,
+
+    // 0x79-0x7F reserved for map operations
 
     // ========================================================================
     // Record Operations (0x80 - 0x8F)
@@ -366,6 +394,19 @@ pub enum Instruction {
     // ========================================================================
     /// String operations
 
+    /// Get the character at index, as a single-character string
+    ///
+    /// The index counts characters (not bytes); negative indices count from
+    /// the end. Stack: [..., str: String, index: Int] -> [..., char: String!]
+    StrGet = 0x90,
+
+    /// Slice a string by character positions
+    ///
+    /// Negative bounds count from the end; out-of-range bounds are clamped.
+    /// Stack: [..., str: String, start: Int, end: Int] -> [..., slice: String]
+    StrSlice = 0x91,
+
+    // 0x92-0x97 reserved for string operations
     /// Format string (f-string)
     /// Operand: u8 arg count | Stack: [..., args..., template] -> [..., result]
     StringFormat(u8) = 0x98,
@@ -484,6 +525,129 @@ static_assertions::assert_eq_size!(Instruction, [u8; 2]);
 impl Instruction {
     /// Size of an instruction in bytes
     pub const SIZE: usize = 2;
+
+    /// The opcode (tag) of this instruction.
+    pub fn opcode(self) -> u8 {
+        // SAFETY: with `#[repr(C, u8)]` the first byte is always the
+        // discriminant, so it is initialized for every variant.
+        unsafe { *(&self as *const Self).cast::<u8>() }
+    }
+}
+
+/// Operand types of instructions, as stored in the operand byte.
+pub(super) trait Operand: Sized {
+    /// Any valid operand, used to get the opcode of a variant.
+    const ANY: Self;
+
+    fn encode(self) -> u8;
+
+    fn decode(byte: u8) -> Option<Self>;
+}
+
+impl Operand for u8 {
+    const ANY: Self = 0;
+
+    fn encode(self) -> u8 {
+        self
+    }
+
+    fn decode(byte: u8) -> Option<Self> {
+        Some(byte)
+    }
+}
+
+impl Operand for i8 {
+    const ANY: Self = 0;
+
+    fn encode(self) -> u8 {
+        self as u8
+    }
+
+    fn decode(byte: u8) -> Option<Self> {
+        Some(byte as i8)
+    }
+}
+
+impl Operand for ComparisonOp {
+    const ANY: Self = ComparisonOp::Eq;
+
+    fn encode(self) -> u8 {
+        self as u8
+    }
+
+    fn decode(byte: u8) -> Option<Self> {
+        use ComparisonOp::*;
+        [Eq, Neq, Lt, Gt, Le, Ge, In, NotIn]
+            .into_iter()
+            .find(|op| *op as u8 == byte)
+    }
+}
+
+/// Implements [`Instruction::name`], [`Instruction::to_bytes`] and
+/// [`Instruction::from_bytes`] from the list of variants, grouped by operand
+/// type.
+macro_rules! impl_instruction_bytes {
+    (
+        without_operand: [$($plain:ident),* $(,)?],
+        with_operand: [$($variant:ident($operand:ty)),* $(,)?] $(,)?
+    ) => {
+        impl Instruction {
+            /// Name of the instruction, without its operand (e.g. `"ConstInt"`).
+            pub fn name(self) -> &'static str {
+                match self {
+                    $(Self::$plain => stringify!($plain),)*
+                    $(Self::$variant(_) => stringify!($variant),)*
+                }
+            }
+
+            /// Encode as the opcode followed by the operand, or zero for
+            /// instructions without one.
+            pub fn to_bytes(self) -> [u8; 2] {
+                let operand = match self {
+                    $(Self::$plain => 0,)*
+                    $(Self::$variant(operand) => <$operand as Operand>::encode(operand),)*
+                };
+                [self.opcode(), operand]
+            }
+
+            /// Decode an instruction encoded with [`to_bytes`](Self::to_bytes).
+            ///
+            /// Returns `None` for unknown opcodes and invalid operands.
+            pub fn from_bytes([opcode, operand]: [u8; 2]) -> Option<Self> {
+                $(
+                    if opcode == Self::$plain.opcode() {
+                        return Some(Self::$plain);
+                    }
+                )*
+                $(
+                    if opcode == Self::$variant(<$operand as Operand>::ANY).opcode() {
+                        return <$operand as Operand>::decode(operand).map(Self::$variant);
+                    }
+                )*
+                None
+            }
+        }
+    };
+}
+
+impl_instruction_bytes! {
+    without_operand: [
+        Halt, Pop, Swap, NegInt, NegFloat, And, Or, Not, EqBool, Return, PopOtherwise,
+        ArrayLen, ArrayGet, ArrayConcat, ArraySlice, ArrayAppend, MapLen, MapGet, MapHas,
+        MapInsert, MapRemove, MapKeys, MapValues, RecordMerge, StrGet, StrSlice, BytesGet,
+        BytesSlice, StringToBytes, BytesToString, Eq, NotEq, Nop, CheckLimits,
+    ],
+    with_operand: [
+        ConstLoad(u8), ConstInt(i8), ConstUInt(u8), ConstBool(u8), WideArg(u8), DupN(u8),
+        LoadLocal(u8), StoreLocal(u8), LoadCapture(u8), IntBinOp(u8), IntCmpOp(ComparisonOp),
+        IntBitOp(u8), FloatBinOp(u8), FloatCmpOp(ComparisonOp), JumpForward(u8),
+        PopJumpIfFalse(u8), PopJumpIfTrue(u8), Call(u8), PushOtherwise(u8),
+        PopOtherwiseAndJump(u8), MakeClosure(u8), MakeArray(u8), ArrayGetConst(u8),
+        MakeMap(u8), MakeMapUnique(u8), MakeRecord(u8), RecordGet(u8), StringFormat(u8),
+        StringCmpOp(ComparisonOp), BytesGetConst(u8), BytesCmpOp(ComparisonOp),
+        CallGenericAdapter(u8), MakeOption(u8), MatchSomeOrJump(u8), MatchNoneOrJump(u8),
+        Breakpoint(u8), Trace(u8), InlineCache(u8),
+    ],
 }
 
 impl fmt::Debug for Instruction {
@@ -492,6 +656,7 @@ impl fmt::Debug for Instruction {
             // Binary operations - show operator as char
             Self::IntBinOp(op) => write!(f, "IntBinOp({})", *op as char),
             Self::FloatBinOp(op) => write!(f, "FloatBinOp({})", *op as char),
+            Self::IntBitOp(op) => write!(f, "IntBitOp({})", *op as char),
 
             // Comparisons - use ComparisonOp's Debug
             Self::IntCmpOp(op) => write!(f, "IntCmpOp({:?})", op),
@@ -539,9 +704,12 @@ impl fmt::Debug for Instruction {
             Self::MapRemove => write!(f, "MapRemove"),
             Self::MapKeys => write!(f, "MapKeys"),
             Self::MapValues => write!(f, "MapValues"),
+            Self::MakeMapUnique(count) => write!(f, "MakeMapUnique({})", count),
             Self::MakeRecord(ty_idx) => write!(f, "MakeRecord({})", ty_idx),
             Self::RecordGet(idx) => write!(f, "RecordGet({})", idx),
             Self::RecordMerge => write!(f, "RecordMerge"),
+            Self::StrGet => write!(f, "StrGet"),
+            Self::StrSlice => write!(f, "StrSlice"),
             Self::StringFormat(argc) => write!(f, "StringFormat({})", argc),
             Self::BytesGet => write!(f, "BytesGet"),
             Self::BytesGetConst(idx) => write!(f, "BytesGetConst({})", idx),
@@ -607,4 +775,31 @@ mod tests {
         let debug = format!("{:?}", cmp);
         assert_eq!(debug, "IntCmpOp(Lt)");
     }
+
+    #[test]
+    fn test_bytes_round_trip() {
+        assert_eq!(Instruction::MakeOption(1).to_bytes(), [0xB7, 1]);
+        assert_eq!(Instruction::ConstInt(-1).to_bytes(), [0x02, 0xFF]);
+        assert_eq!(Instruction::Return.to_bytes(), [0x3E, 0]);
+
+        // Every decodable pair encodes back to itself
+        let mut opcodes = 0;
+        for opcode in 0..=u8::MAX {
+            for operand in [0, 1, 7, 0xFF] {
+                let Some(instruction) = Instruction::from_bytes([opcode, operand]) else {
+                    continue;
+                };
+                assert_eq!(instruction.opcode(), opcode);
+                let encoded = instruction.to_bytes();
+                assert_eq!(Instruction::from_bytes(encoded), Some(instruction));
+                if operand == 7 {
+                    opcodes += 1;
+                }
+            }
+        }
+        assert_eq!(opcodes, 72);
+
+        assert_eq!(Instruction::from_bytes([0xFF, 0]), None);
+        assert_eq!(Instruction::from_bytes([0x14, 0]), None); // IntCmpOp(0)
+    }
 }
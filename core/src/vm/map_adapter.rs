@@ -0,0 +1,225 @@
+//! Map construction and lookup adapters for the VM.
+//!
+//! The `MakeMap`, `MapGet` and `MapHas` instructions compare keys as raw integers, which
+//! is only correct for `Int` keys. Maps keyed by any other type (`Str`,
+//! `Float`, `Bytes`, ...) go through these adapters instead. They store the
+//! static key type and compare keys as typed `Value`s, so the resulting maps
+//! are ordered exactly like maps built by the evaluator or by native
+//! functions.
+//!
+//! # Performance
+//!
+//! Lookups use binary search over the sorted entries, O(log n) comparisons.
+
+use bumpalo::Bump;
+
+use crate::{
+    Vec,
+    evaluator::{ExecutionErrorKind, RuntimeError},
+    format,
+    parser::ComparisonOp,
+    types::Type,
+    values::{MapData, RandomState, RawValue, StepBudget, dynamic::Value, raw::MapEntry},
+    vm::{AdapterSpec, GenericAdapter},
+};
+
+/// Adapter for map literals with non-`Int` keys.
+///
+/// Pops `2 * num_pairs` values (`k1, v1, ..., kN, vN`) and pushes the map.
+/// Among equal keys the last entry wins, unless `unique` is set, in which
+/// case equal keys fail with `DuplicateMapKey`.
+pub struct MakeMapAdapter<'t> {
+    key_type: &'t Type<'t>,
+    num_pairs: usize,
+    unique: bool,
+}
+
+impl<'t> MakeMapAdapter<'t> {
+    pub fn new(key_type: &'t Type<'t>, num_pairs: usize, unique: bool) -> Self {
+        MakeMapAdapter {
+            key_type,
+            num_pairs,
+            unique,
+        }
+    }
+}
+
+impl<'t> GenericAdapter for MakeMapAdapter<'t> {
+    fn num_args(&self) -> usize {
+        self.num_pairs * 2
+    }
+
+    fn call(
+        &self,
+        arena: &Bump,
+        _random: &RandomState,
+        _steps: &StepBudget,
+        args: &[RawValue],
+    ) -> Result<RawValue, ExecutionErrorKind> {
+        let key = |raw| Value::from_raw_unchecked(self.key_type, raw);
+
+        let mut entries: Vec<MapEntry> = args
+            .chunks_exact(2)
+            .map(|pair| MapEntry {
+                key: pair[0],
+                value: pair[1],
+            })
+            .collect();
+
+        // The sort is stable, so among equal keys the last entry in source
+        // order comes last.
+        entries.sort_by(|a, b| key(a.key).cmp(&key(b.key)));
+
+        let mut deduplicated: Vec<MapEntry> = Vec::with_capacity(entries.len());
+        for entry in entries {
+            match deduplicated.last_mut() {
+                Some(last) if key(last.key) == key(entry.key) => {
+                    if self.unique {
+                        let key_display = format!("{}", key(entry.key));
+                        return Err(RuntimeError::DuplicateMapKey { key_display }.into());
+                    }
+                    *last = entry;
+                }
+                _ => deduplicated.push(entry),
+            }
+        }
+
+        Ok(MapData::new_with_sorted(arena, &deduplicated).as_raw_value())
+    }
+
+    fn name(&self) -> alloc::string::String {
+        let kind = if self.unique {
+            "MakeMapUnique"
+        } else {
+            "MakeMap"
+        };
+        format!("{}({}, Key = {})", kind, self.num_pairs, self.key_type)
+    }
+
+    fn spec(&self) -> Option<AdapterSpec<'_>> {
+        Some(AdapterSpec::MakeMap {
+            key_type: self.key_type,
+            num_pairs: self.num_pairs,
+            unique: self.unique,
+        })
+    }
+}
+
+/// Adapter for indexing maps with non-`Int` keys.
+///
+/// Stack: `[..., map, key] -> [..., value]`, failing with `KeyNotFound`.
+pub struct MapGetAdapter<'t> {
+    key_type: &'t Type<'t>,
+}
+
+impl<'t> MapGetAdapter<'t> {
+    pub fn new(key_type: &'t Type<'t>) -> Self {
+        MapGetAdapter { key_type }
+    }
+}
+
+impl<'t> GenericAdapter for MapGetAdapter<'t> {
+    fn num_args(&self) -> usize {
+        2 // map and key
+    }
+
+    fn call(
+        &self,
+        _arena: &Bump,
+        _random: &RandomState,
+        _steps: &StepBudget,
+        args: &[RawValue],
+    ) -> Result<RawValue, ExecutionErrorKind> {
+        let map = MapData::from_raw_value(args[0]);
+        let needle = Value::from_raw_unchecked(self.key_type, args[1]);
+
+        find(map, self.key_type, &needle).ok_or_else(|| {
+            let key_display = format!("{}", needle);
+            RuntimeError::KeyNotFound { key_display }.into()
+        })
+    }
+
+    fn name(&self) -> alloc::string::String {
+        format!("MapGet(Key = {})", self.key_type)
+    }
+
+    fn spec(&self) -> Option<AdapterSpec<'_>> {
+        Some(AdapterSpec::MapGet {
+            key_type: self.key_type,
+        })
+    }
+}
+
+/// Adapter for key presence in maps with non-`Int` keys (`key in map` /
+/// `key not in map`).
+///
+/// Stack: `[..., key, map] -> [..., result: Bool]`.
+pub struct MapContainsAdapter<'t> {
+    key_type: &'t Type<'t>,
+    op: ComparisonOp,
+}
+
+impl<'t> MapContainsAdapter<'t> {
+    pub fn new(key_type: &'t Type<'t>, op: ComparisonOp) -> Self {
+        debug_assert!(matches!(op, ComparisonOp::In | ComparisonOp::NotIn));
+        MapContainsAdapter { key_type, op }
+    }
+}
+
+impl<'t> GenericAdapter for MapContainsAdapter<'t> {
+    fn num_args(&self) -> usize {
+        2 // key and map
+    }
+
+    fn call(
+        &self,
+        _arena: &Bump,
+        _random: &RandomState,
+        _steps: &StepBudget,
+        args: &[RawValue],
+    ) -> Result<RawValue, ExecutionErrorKind> {
+        let needle = Value::from_raw_unchecked(self.key_type, args[0]);
+        let map = MapData::from_raw_value(args[1]);
+
+        let found = find(map, self.key_type, &needle).is_some();
+        Ok(RawValue::make_bool(match self.op {
+            ComparisonOp::In => found,
+            ComparisonOp::NotIn => !found,
+            _ => unreachable!("MapContainsAdapter only handles In/NotIn"),
+        }))
+    }
+
+    fn name(&self) -> alloc::string::String {
+        let op_str = match self.op {
+            ComparisonOp::In => "in",
+            ComparisonOp::NotIn => "not in",
+            _ => "?",
+        };
+        format!("MapContains(Key = {}, {})", self.key_type, op_str)
+    }
+
+    fn spec(&self) -> Option<AdapterSpec<'_>> {
+        Some(AdapterSpec::MapContains {
+            key_type: self.key_type,
+            op: self.op,
+        })
+    }
+}
+
+/// Binary search for `needle` among the keys of `map`, returning its value.
+fn find(map: MapData<'_>, key_type: &Type<'_>, needle: &Value<'_, '_>) -> Option<RawValue> {
+    let mut low = 0;
+    let mut high = map.length();
+    while low < high {
+        let mid = low + (high - low) / 2;
+        // SAFETY: `mid` is in `low..high`, which stays within `0..map.length()`.
+        let mid_key = Value::from_raw_unchecked(key_type, unsafe { map.get_key(mid) });
+        match mid_key.cmp(needle) {
+            core::cmp::Ordering::Less => low = mid + 1,
+            core::cmp::Ordering::Greater => high = mid,
+            // SAFETY: `mid` is in bounds, see above.
+            core::cmp::Ordering::Equal => return Some(unsafe { map.get_value(mid) }),
+        }
+    }
+    None
+}
@@ -5,16 +5,25 @@ mod format_str_adapter;
 mod function_adapter;
 mod generic_adapter;
 mod instruction_set;
+mod map_adapter;
+mod option_compare_adapter;
 mod runtime;
 mod stack;
+mod verifier;
 
 pub use array_contains_adapter::ArrayContainsAdapter;
 pub use cast_adapter::CastAdapter;
-pub use code::{Code, LambdaCode, LambdaKind};
+pub use code::{
+    BYTECODE_FORMAT_VERSION, Code, DecodeError, DisasmLine, DisasmOperand, EncodeError, LambdaCode,
+    LambdaKind,
+};
 pub use format_str_adapter::FormatStrAdapter;
 pub use function_adapter::FunctionAdapter;
-pub use generic_adapter::GenericAdapter;
+pub use generic_adapter::{AdapterSpec, GenericAdapter};
 pub use instruction_set::Instruction;
+pub use map_adapter::{MakeMapAdapter, MapContainsAdapter, MapGetAdapter};
+pub use option_compare_adapter::OptionCompareAdapter;
 pub use runtime::VM;
 
+pub(crate) use code::get_jump_offset;
 pub(crate) use stack::Stack;
@@ -0,0 +1,66 @@
+//! Option comparison adapter for the VM.
+//!
+//! Options have no dedicated comparison instruction: comparing them needs the
+//! element type to decode their contents, so the adapter keeps the option
+//! type and compares the operands as typed `Value`s.
+
+use bumpalo::Bump;
+
+use crate::{
+    evaluator::{ExecutionErrorKind, eval_comparison_option},
+    parser::ComparisonOp,
+    types::Type,
+    values::{RandomState, RawValue, StepBudget, dynamic::Value},
+    vm::{AdapterSpec, GenericAdapter},
+};
+
+/// Adapter for comparisons between two options (`==`, `!=`, `<`, `>`, `<=`, `>=`).
+///
+/// `none` is less than any `some`, and two `some`s compare by their contents.
+pub struct OptionCompareAdapter<'t> {
+    option_type: &'t Type<'t>,
+    op: ComparisonOp,
+}
+
+impl<'t> OptionCompareAdapter<'t> {
+    pub fn new(option_type: &'t Type<'t>, op: ComparisonOp) -> Self {
+        debug_assert!(!matches!(op, ComparisonOp::In | ComparisonOp::NotIn));
+        OptionCompareAdapter { option_type, op }
+    }
+}
+
+impl<'t> GenericAdapter for OptionCompareAdapter<'t> {
+    fn num_args(&self) -> usize {
+        2 // left and right
+    }
+
+    fn call(
+        &self,
+        _arena: &Bump,
+        _random: &RandomState,
+        _steps: &StepBudget,
+        args: &[RawValue],
+    ) -> Result<RawValue, ExecutionErrorKind> {
+        let left = Value::from_raw_unchecked(self.option_type, args[0]);
+        let right = Value::from_raw_unchecked(self.option_type, args[1]);
+
+        let result = match self.op {
+            ComparisonOp::Eq => left == right,
+            ComparisonOp::Neq => left != right,
+            op => eval_comparison_option(op, left, right),
+        };
+
+        Ok(RawValue::make_bool(result))
+    }
+
+    fn name(&self) -> alloc::string::String {
+        alloc::format!("OptionCompare({:?}, {})", self.op, self.option_type)
+    }
+
+    fn spec(&self) -> Option<AdapterSpec<'_>> {
+        Some(AdapterSpec::OptionCompare {
+            option_type: self.option_type,
+            op: self.op,
+        })
+    }
+}
@@ -8,10 +8,14 @@ use super::instruction_set::Instruction;
 
 use crate::{
     String, Vec,
-    evaluator::{ExecutionError, ExecutionErrorKind, RuntimeError},
+    analyzer::IntegerDivision,
+    evaluator::{ExecutionError, ExecutionErrorKind, RuntimeError, slice_range},
     format,
     parser::{ComparisonOp, Span},
-    values::{ArrayData, BytecodeLambda, LambdaInstantiation, MapData, RawValue, RecordData},
+    values::{
+        ArrayData, BytecodeLambda, LambdaInstantiation, MapData, RandomState, RawValue, RecordData,
+        StepBudget, raw::Slice,
+    },
     vm::{Code, GenericAdapter, LambdaKind, Stack},
 };
 
@@ -29,6 +33,10 @@ pub struct VM<'a, 'b, 'c> {
     otherwise_stack: Vec<OtherwiseBlock>,
     /// Captured values for the current closure (empty for top-level code)
     captures: &'a [RawValue],
+    /// Random number generator forwarded to native functions
+    random: &'a RandomState,
+    /// Step budget, charged once per dispatched instruction
+    steps: &'a StepBudget,
 }
 
 impl<'a, 'b, 'c> VM<'a, 'b, 'c> {
@@ -53,9 +61,27 @@ impl<'a, 'b, 'c> VM<'a, 'b, 'c> {
             locals,
             otherwise_stack: Vec::new(),
             captures,
+            random: arena.alloc(RandomState::default()),
+            steps: arena.alloc(StepBudget::default()),
         }
     }
 
+    /// Draw random numbers from an existing generator.
+    ///
+    /// Closures use this to continue the sequence of the execution that
+    /// called them, instead of starting over from the default seed.
+    pub fn set_random(&mut self, random: &'a RandomState) {
+        self.random = random;
+    }
+
+    /// Count steps against an existing budget.
+    ///
+    /// Closures use this so that their instructions count against the limit
+    /// of the execution that called them.
+    pub fn set_steps(&mut self, steps: &'a StepBudget) {
+        self.steps = steps;
+    }
+
     pub fn execute(arena: &'a Bump, code: &'b Code<'c>) -> Result<RawValue, ExecutionError> {
         let mut vm = VM::new(arena, code, Vec::new(), &[]);
         vm.run()
@@ -104,6 +130,7 @@ impl<'a, 'b, 'c> VM<'a, 'b, 'c> {
         let mut wide_arg: usize = 0;
         loop {
             self.ip = unsafe { self.ip.add(1) };
+            self.steps.take()?;
 
             use Instruction::*;
             match unsafe { *self.ip } {
@@ -149,35 +176,26 @@ impl<'a, 'b, 'c> VM<'a, 'b, 'c> {
                             .wrapping_mul(b.as_int_unchecked()),
                     );
                 }
-                IntBinOp(b'/') => {
-                    let b = self.stack.pop();
-                    let a = self.stack.pop();
-
-                    if b.as_int_unchecked() == 0 {
-                        return Err(RuntimeError::DivisionByZero {}.into());
-                    }
-                    if a.as_int_unchecked() == i64::MIN && b.as_int_unchecked() == -1 {
-                        return Err(RuntimeError::IntegerOverflow {}.into());
-                    }
-
-                    self.stack.push(RawValue::make_int(
-                        a.as_int_unchecked().div_euclid(b.as_int_unchecked()),
-                    ));
-                }
-                IntBinOp(b'%') => {
-                    let b = self.stack.pop();
-                    let a = self.stack.pop();
+                IntBinOp(op @ (b'/' | b'%' | b'q' | b'r' | b'f' | b'm')) => {
+                    let b = self.stack.pop().as_int_unchecked();
+                    let a = self.stack.pop().as_int_unchecked();
 
-                    if b.as_int_unchecked() == 0 {
+                    if b == 0 {
                         return Err(RuntimeError::DivisionByZero {}.into());
                     }
-                    if a.as_int_unchecked() == i64::MIN && b.as_int_unchecked() == -1 {
+                    if a == i64::MIN && b == -1 {
                         return Err(RuntimeError::IntegerOverflow {}.into());
                     }
 
-                    self.stack.push(RawValue::make_int(
-                        a.as_int_unchecked().rem_euclid(b.as_int_unchecked()),
-                    ));
+                    let result = match op {
+                        b'/' => IntegerDivision::Euclidean.quotient(a, b),
+                        b'%' => IntegerDivision::Euclidean.remainder(a, b),
+                        b'q' => IntegerDivision::Truncated.quotient(a, b),
+                        b'r' => IntegerDivision::Truncated.remainder(a, b),
+                        b'f' => IntegerDivision::Floored.quotient(a, b),
+                        _ => IntegerDivision::Floored.remainder(a, b),
+                    };
+                    self.stack.push(RawValue::make_int(result));
                 }
                 IntBinOp(b'^') => {
                     let b = self.stack.pop().as_int_unchecked();
@@ -192,6 +210,25 @@ impl<'a, 'b, 'c> VM<'a, 'b, 'c> {
                     self.stack.push(RawValue::make_int(result));
                 }
 
+                // Integer bitwise operations
+                IntBitOp(op) => {
+                    let b = self.stack.pop().as_int_unchecked();
+                    let a = self.stack.pop().as_int_unchecked();
+                    let result = match op {
+                        b'&' => a & b,
+                        b'|' => a | b,
+                        b'^' => a ^ b,
+                        b'<' | b'>' => {
+                            if !(0..i64::BITS as i64).contains(&b) {
+                                return Err(RuntimeError::ShiftOutOfRange { amount: b }.into());
+                            }
+                            if op == b'<' { a << b } else { a >> b }
+                        }
+                        _ => panic!("Unknown bitwise operation: {}", op as char),
+                    };
+                    self.stack.push(RawValue::make_int(result));
+                }
+
                 // Integer unary operations
                 NegInt => {
                     let a = self.stack.pop().as_int_unchecked();
@@ -241,6 +278,12 @@ impl<'a, 'b, 'c> VM<'a, 'b, 'c> {
                         self.stack[0].as_float_unchecked() / b.as_float_unchecked(),
                     );
                 }
+                FloatBinOp(b'%') => {
+                    let b = self.stack.pop();
+                    self.stack[0] = RawValue::make_float(
+                        self.stack[0].as_float_unchecked() % b.as_float_unchecked(),
+                    );
+                }
                 FloatBinOp(b'^') => {
                     let b = self.stack.pop();
                     let a = self.stack.pop();
@@ -273,6 +316,46 @@ impl<'a, 'b, 'c> VM<'a, 'b, 'c> {
                     self.stack.push(RawValue::make_bool(result));
                 }
 
+                StrGet => {
+                    let index_i64 = self.stack.pop().as_int_unchecked();
+                    let string = self.stack.pop().as_str_unchecked();
+                    let len = string.chars().count();
+                    let Some(index) = calculate_index(index_i64, len) else {
+                        return Err(RuntimeError::IndexOutOfBounds {
+                            index: index_i64,
+                            len,
+                        }
+                        .into());
+                    };
+                    // Borrow the character from the original string, no copy needed
+                    let (start, ch) = string
+                        .char_indices()
+                        .nth(index)
+                        .expect("Index should be in bounds after check");
+                    let char_str = &string.as_bytes()[start..start + ch.len_utf8()];
+                    self.stack
+                        .push(Slice::new(self.arena, char_str).as_raw_value());
+                }
+
+                StrSlice => {
+                    // Stack: [..., str, start, end] -> [..., slice]
+                    let end = self.stack.pop().as_int_unchecked();
+                    let start = self.stack.pop().as_int_unchecked();
+                    let string = self.stack.pop().as_str_unchecked();
+                    // Bounds count characters, not bytes
+                    let range = slice_range(start, end, string.chars().count());
+                    let byte_offset = |char_index: usize| {
+                        string
+                            .char_indices()
+                            .nth(char_index)
+                            .map_or(string.len(), |(offset, _)| offset)
+                    };
+                    let slice =
+                        &string.as_bytes()[byte_offset(range.start)..byte_offset(range.end)];
+                    self.stack
+                        .push(Slice::new(self.arena, slice).as_raw_value());
+                }
+
                 BytesGet => {
                     let index_i64 = self.stack.pop().as_int_unchecked();
                     let bytes = self.stack.pop().as_bytes_unchecked();
@@ -485,7 +568,7 @@ impl<'a, 'b, 'c> VM<'a, 'b, 'c> {
                     let num_args = adapter.num_args();
                     let args = self.stack.top_n(num_args);
 
-                    let result = adapter.call(self.arena, args)?;
+                    let result = adapter.call(self.arena, self.random, self.steps, args)?;
 
                     // Pop arguments from stack after the call
                     self.stack.pop_n(num_args);
@@ -501,7 +584,7 @@ impl<'a, 'b, 'c> VM<'a, 'b, 'c> {
                     let num_args = adapter.num_args();
                     let args = self.stack.top_n(num_args);
 
-                    let result = adapter.call(self.arena, args)?;
+                    let result = adapter.call(self.arena, self.random, self.steps, args)?;
 
                     // Pop arguments from stack after the call
                     self.stack.pop_n(num_args);
@@ -600,7 +683,19 @@ impl<'a, 'b, 'c> VM<'a, 'b, 'c> {
                     self.stack.push(element);
                 }
 
-                ArrayLen | ArrayConcat | ArraySlice | ArrayAppend => {
+                ArraySlice => {
+                    // Stack: [..., array, start, end] -> [..., slice]
+                    let end = self.stack.pop().as_int_unchecked();
+                    let start = self.stack.pop().as_int_unchecked();
+                    let array = ArrayData::from_raw_value(self.stack.pop());
+                    let elements: Vec<RawValue> = slice_range(start, end, array.length())
+                        .map(|index| unsafe { array.get_unchecked(index) })
+                        .collect();
+                    self.stack
+                        .push(ArrayData::new_with(self.arena, &elements).as_raw_value());
+                }
+
+                ArrayLen | ArrayConcat | ArrayAppend => {
                     todo!("Other array operations")
                 }
 
@@ -635,7 +730,7 @@ impl<'a, 'b, 'c> VM<'a, 'b, 'c> {
                     }
                 }
 
-                MakeMap(arg) => {
+                MakeMap(arg) | MakeMapUnique(arg) => {
                     // Stack: [..., key1, val1, key2, val2, ..., keyN, valN] -> [..., map]
                     use crate::Vec;
                     use crate::values::raw::MapEntry;
@@ -661,6 +756,28 @@ impl<'a, 'b, 'c> VM<'a, 'b, 'c> {
                     // TODO: Proper multi-type key comparison
                     entries.sort_by(|a, b| a.key.as_int_unchecked().cmp(&b.key.as_int_unchecked()));
 
+                    // The sort is stable, so among equal keys the last entry
+                    // in source order comes last.
+                    let unique = matches!(unsafe { *self.ip }, MakeMapUnique(_));
+                    let mut deduplicated: Vec<MapEntry> = Vec::with_capacity(num_pairs);
+                    for entry in entries {
+                        let key = entry.key.as_int_unchecked();
+                        match deduplicated.last_mut() {
+                            Some(last) if last.key.as_int_unchecked() == key => {
+                                if unique {
+                                    self.stack.pop_n(num_values);
+                                    let key_display = format!("{}", key);
+                                    return Err(
+                                        RuntimeError::DuplicateMapKey { key_display }.into()
+                                    );
+                                }
+                                *last = entry;
+                            }
+                            _ => deduplicated.push(entry),
+                        }
+                    }
+                    let entries = deduplicated;
+
                     // Create the map
                     let map = MapData::new_with_sorted(self.arena, &entries);
 
@@ -673,10 +790,24 @@ impl<'a, 'b, 'c> VM<'a, 'b, 'c> {
 
                 MapHas => {
                     // Stack: [..., key, map] -> [..., result: Bool]
-                    // For now, just push false (placeholder implementation)
-                    let _map = self.stack.pop();
-                    let _key = self.stack.pop();
-                    self.stack.push(RawValue::make_bool(false));
+                    let map = MapData::from_raw_value(self.stack.pop());
+                    let key = self.stack.pop();
+
+                    // Binary search: keys are sorted as raw integers, see `MakeMap`.
+                    let key = key.as_int_unchecked();
+                    let mut low = 0;
+                    let mut high = map.length();
+                    let mut found = false;
+                    while low < high && !found {
+                        let mid = low + (high - low) / 2;
+                        let mid_key = unsafe { map.get_key(mid) }.as_int_unchecked();
+                        match mid_key.cmp(&key) {
+                            core::cmp::Ordering::Less => low = mid + 1,
+                            core::cmp::Ordering::Greater => high = mid,
+                            core::cmp::Ordering::Equal => found = true,
+                        }
+                    }
+                    self.stack.push(RawValue::make_bool(found));
                 }
 
                 MapLen | MapInsert | MapRemove | MapKeys | MapValues => {
@@ -795,6 +926,7 @@ mod tests {
         use Instruction::*;
         let code = Code {
             constants: vec![RawValue::make_int(42)],
+            constant_types: vec![],
             adapters: vec![],
             generic_adapters: vec![],
             instructions: vec![ConstLoad(0), ConstInt(2), IntBinOp(b'*'), Return],
@@ -812,6 +944,7 @@ mod tests {
         use Instruction::*;
         let mut code = Code {
             constants: vec![RawValue::make_int(2)],
+            constant_types: vec![],
             adapters: vec![],
             generic_adapters: vec![],
             instructions: vec![
@@ -839,6 +972,7 @@ mod tests {
         // Test <
         let code = Code {
             constants: vec![],
+            constant_types: vec![],
             adapters: vec![],
             generic_adapters: vec![],
             instructions: vec![
@@ -858,6 +992,7 @@ mod tests {
         // Test ==
         let code = Code {
             constants: vec![],
+            constant_types: vec![],
             adapters: vec![],
             generic_adapters: vec![],
             instructions: vec![
@@ -880,6 +1015,7 @@ mod tests {
 
         let code = Code {
             constants: vec![RawValue::make_float(3.5), RawValue::make_float(2.0)],
+            constant_types: vec![],
             adapters: vec![],
             generic_adapters: vec![],
             instructions: vec![ConstLoad(0), ConstLoad(1), FloatBinOp(b'+'), Return],
@@ -899,6 +1035,7 @@ mod tests {
         // Test AND
         let code = Code {
             constants: vec![],
+            constant_types: vec![],
             adapters: vec![],
             generic_adapters: vec![],
             instructions: vec![ConstBool(1), ConstBool(0), And, Return],
@@ -913,6 +1050,7 @@ mod tests {
         // Test OR
         let code = Code {
             constants: vec![],
+            constant_types: vec![],
             adapters: vec![],
             generic_adapters: vec![],
             instructions: vec![ConstBool(1), ConstBool(0), Or, Return],
@@ -926,6 +1064,7 @@ mod tests {
         // Test NOT
         let code = Code {
             constants: vec![],
+            constant_types: vec![],
             adapters: vec![],
             generic_adapters: vec![],
             instructions: vec![ConstBool(0), Not, Return],
@@ -944,6 +1083,7 @@ mod tests {
         // Test Dup
         let code = Code {
             constants: vec![],
+            constant_types: vec![],
             adapters: vec![],
             generic_adapters: vec![],
             instructions: vec![ConstInt(42), DupN(0), IntBinOp(b'+'), Return],
@@ -958,6 +1098,7 @@ mod tests {
         // Test Swap
         let code = Code {
             constants: vec![],
+            constant_types: vec![],
             adapters: vec![],
             generic_adapters: vec![],
             instructions: vec![ConstInt(10), ConstInt(5), Swap, IntBinOp(b'-'), Return],
@@ -976,6 +1117,7 @@ mod tests {
         // Store and load local variable
         let code = Code {
             constants: vec![],
+            constant_types: vec![],
             adapters: vec![],
             generic_adapters: vec![],
             instructions: vec![
@@ -1002,6 +1144,7 @@ mod tests {
         // Unconditional jump
         let code = Code {
             constants: vec![],
+            constant_types: vec![],
             adapters: vec![],
             generic_adapters: vec![],
             instructions: vec![
@@ -1029,6 +1172,7 @@ mod tests {
         // JumpIfTrue - should jump
         let code = Code {
             constants: vec![],
+            constant_types: vec![],
             adapters: vec![],
             generic_adapters: vec![],
             instructions: vec![
@@ -1049,6 +1193,7 @@ mod tests {
         // JumpIfFalse - should not jump
         let code = Code {
             constants: vec![],
+            constant_types: vec![],
             adapters: vec![],
             generic_adapters: vec![],
             instructions: vec![
@@ -1073,6 +1218,7 @@ mod tests {
         // NegInt
         let code = Code {
             constants: vec![],
+            constant_types: vec![],
             adapters: vec![],
             generic_adapters: vec![],
             instructions: vec![ConstInt(42), NegInt, Return],
@@ -1095,6 +1241,7 @@ mod tests {
 
         let code = Code {
             constants: vec![],
+            constant_types: vec![],
             adapters: vec![],
             generic_adapters: vec![],
             instructions: vec![ConstInt(10), ConstInt(3), IntBinOp(b'/'), Return],
@@ -1115,6 +1262,7 @@ mod tests {
         // because -7 = -3 * 3 + 2 (remainder is non-negative)
         let code = Code {
             constants: vec![],
+            constant_types: vec![],
             adapters: vec![],
             generic_adapters: vec![],
             instructions: vec![ConstInt(-7), ConstInt(3), IntBinOp(b'/'), Return],
@@ -1135,6 +1283,7 @@ mod tests {
         // because 7 = -2 * (-3) + 1
         let code = Code {
             constants: vec![],
+            constant_types: vec![],
             adapters: vec![],
             generic_adapters: vec![],
             instructions: vec![ConstInt(7), ConstInt(-3), IntBinOp(b'/'), Return],
@@ -1155,6 +1304,7 @@ mod tests {
         // because -7 = 3 * (-3) + 2
         let code = Code {
             constants: vec![],
+            constant_types: vec![],
             adapters: vec![],
             generic_adapters: vec![],
             instructions: vec![ConstInt(-7), ConstInt(-3), IntBinOp(b'/'), Return],
@@ -1173,6 +1323,7 @@ mod tests {
 
         let code = Code {
             constants: vec![],
+            constant_types: vec![],
             adapters: vec![],
             generic_adapters: vec![],
             instructions: vec![ConstInt(10), ConstInt(0), IntBinOp(b'/'), Return],
@@ -1200,6 +1351,7 @@ mod tests {
         // Use constants pool for i64::MIN since ConstInt only takes i8
         let code = Code {
             constants: vec![RawValue::make_int(i64::MIN)],
+            constant_types: vec![],
             adapters: vec![],
             generic_adapters: vec![],
             instructions: vec![ConstLoad(0), ConstInt(-1), IntBinOp(b'/'), Return],
@@ -1229,6 +1381,7 @@ mod tests {
 
         let code = Code {
             constants: vec![],
+            constant_types: vec![],
             adapters: vec![],
             generic_adapters: vec![],
             instructions: vec![ConstInt(10), ConstInt(3), IntBinOp(b'%'), Return],
@@ -1247,6 +1400,7 @@ mod tests {
 
         let code = Code {
             constants: vec![],
+            constant_types: vec![],
             adapters: vec![],
             generic_adapters: vec![],
             instructions: vec![ConstInt(9), ConstInt(3), IntBinOp(b'%'), Return],
@@ -1267,6 +1421,7 @@ mod tests {
         // Truncated would give -1
         let code = Code {
             constants: vec![],
+            constant_types: vec![],
             adapters: vec![],
             generic_adapters: vec![],
             instructions: vec![ConstInt(-7), ConstInt(3), IntBinOp(b'%'), Return],
@@ -1286,6 +1441,7 @@ mod tests {
         // Euclidean: 7 % -3 = 1 (always non-negative)
         let code = Code {
             constants: vec![],
+            constant_types: vec![],
             adapters: vec![],
             generic_adapters: vec![],
             instructions: vec![ConstInt(7), ConstInt(-3), IntBinOp(b'%'), Return],
@@ -1305,6 +1461,7 @@ mod tests {
         // Euclidean: -7 % -3 = 2 (always non-negative)
         let code = Code {
             constants: vec![],
+            constant_types: vec![],
             adapters: vec![],
             generic_adapters: vec![],
             instructions: vec![ConstInt(-7), ConstInt(-3), IntBinOp(b'%'), Return],
@@ -1323,6 +1480,7 @@ mod tests {
 
         let code = Code {
             constants: vec![],
+            constant_types: vec![],
             adapters: vec![],
             generic_adapters: vec![],
             instructions: vec![ConstInt(10), ConstInt(0), IntBinOp(b'%'), Return],
@@ -1350,6 +1508,7 @@ mod tests {
         // Use constants pool for i64::MIN since ConstInt only takes i8
         let code = Code {
             constants: vec![RawValue::make_int(i64::MIN)],
+            constant_types: vec![],
             adapters: vec![],
             generic_adapters: vec![],
             instructions: vec![ConstLoad(0), ConstInt(-1), IntBinOp(b'%'), Return],
@@ -1380,6 +1539,7 @@ mod tests {
         // Get quotient: -7 / 3
         let code_div = Code {
             constants: vec![],
+            constant_types: vec![],
             adapters: vec![],
             generic_adapters: vec![],
             instructions: vec![ConstInt(-7), ConstInt(3), IntBinOp(b'/'), Return],
@@ -1393,6 +1553,7 @@ mod tests {
         // Get remainder: -7 % 3
         let code_mod = Code {
             constants: vec![],
+            constant_types: vec![],
             adapters: vec![],
             generic_adapters: vec![],
             instructions: vec![ConstInt(-7), ConstInt(3), IntBinOp(b'%'), Return],
@@ -1408,4 +1569,44 @@ mod tests {
         assert_eq!(quotient, -3);
         assert_eq!(remainder, 2);
     }
+
+    #[test]
+    fn test_int_division_rounding_ops() {
+        use Instruction::*;
+
+        let arena = Bump::new();
+        let run = |a, b, op| {
+            let code = Code {
+                constants: vec![],
+                constant_types: vec![],
+                adapters: vec![],
+                generic_adapters: vec![],
+                instructions: vec![ConstInt(a), ConstInt(b), IntBinOp(op), Return],
+                num_locals: 0,
+                max_stack_size: 2,
+                lambdas: vec![],
+            };
+            let mut vm = VM::new(&arena, &code, Vec::new(), &[]);
+            vm.run().map(|value| value.as_int_unchecked())
+        };
+
+        // Truncated: -7 = -3 * 2 - 1
+        assert_eq!(run(-7, 2, b'q').unwrap(), -3);
+        assert_eq!(run(-7, 2, b'r').unwrap(), -1);
+        // Floored: -7 = -4 * 2 + 1, 7 = -4 * -2 - 1
+        assert_eq!(run(-7, 2, b'f').unwrap(), -4);
+        assert_eq!(run(-7, 2, b'm').unwrap(), 1);
+        assert_eq!(run(7, -2, b'f').unwrap(), -4);
+        assert_eq!(run(7, -2, b'm').unwrap(), -1);
+
+        for op in [b'q', b'r', b'f', b'm'] {
+            assert!(matches!(
+                run(1, 0, op),
+                Err(ExecutionError {
+                    kind: ExecutionErrorKind::Runtime(RuntimeError::DivisionByZero {}),
+                    ..
+                })
+            ));
+        }
+    }
 }
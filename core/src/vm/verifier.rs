@@ -0,0 +1,1042 @@
+//! Verification of deserialized bytecode.
+//!
+//! The VM trusts its code: it moves the instruction pointer without bounds
+//! checks and reads values with the `as_*_unchecked` accessors of
+//! [`RawValue`](crate::values::RawValue). Code produced by the compiler is
+//! correct by construction, but code read from a byte buffer may have been
+//! truncated or tampered with, so [`verify`] checks it before it can run.
+//!
+//! All jumps go forward, so a single pass over the instructions visits each
+//! one after every path that reaches it. Paths are followed separately,
+//! tracking the kind of every value on the stack and in the locals, along
+//! with what the branches taken so far tell about `Bool` and `Option`
+//! values. The compiler relies on the latter: the last arm of a `match` on
+//! a `Bool` or an `Option` still checks its pattern, but the analyzer has
+//! proven that the check cannot fail.
+//!
+//! Functions are only created by `MakeClosure`, and the kinds of their
+//! parameters and results come from the types of their instantiations. Each
+//! lambda's code is checked after the code that creates it, with its
+//! parameters in the first locals and the kinds of the values it captures.
+
+use crate::{
+    Box, Vec,
+    parser::ComparisonOp,
+    types::Type,
+    vec,
+    vm::{AdapterSpec, Code, DecodeError, Instruction, LambdaKind, get_jump_offset},
+};
+
+/// Most `WideArg` prefixes a single instruction can have, enough for any
+/// 32-bit operand.
+const MAX_WIDE_PREFIXES: usize = 3;
+
+/// Most paths followed separately to an instruction before they are joined.
+const MAX_PATHS: usize = 16;
+
+/// What the verifier knows about the kind of a value.
+#[derive(Debug, Clone, PartialEq)]
+enum Kind {
+    /// No value at all, such as the elements of an empty array. Allowed
+    /// wherever any kind is expected, since it never reaches the VM.
+    Never,
+    Int,
+    Float,
+    Bool,
+    Str,
+    Bytes,
+    Array(Box<Kind>),
+    Map(Box<Kind>, Box<Kind>),
+    Record(Vec<Kind>),
+    Option(Box<Kind>),
+    /// A function, with the parameter and result kinds of each of its
+    /// instantiations.
+    Function(Vec<Signature>),
+    /// A value that may be moved around but not looked into, such as a
+    /// symbol or a value that has different kinds on different paths.
+    Unknown,
+}
+
+/// Parameter and result kinds of a function.
+type Signature = (Vec<Kind>, Kind);
+
+impl Kind {
+    fn of_type(ty: &Type) -> Kind {
+        match ty {
+            Type::Int => Kind::Int,
+            Type::Float => Kind::Float,
+            Type::Bool => Kind::Bool,
+            Type::Str => Kind::Str,
+            Type::Bytes => Kind::Bytes,
+            Type::Array(elem) => Kind::Array(Box::new(Kind::of_type(elem))),
+            Type::Map(key, value) => {
+                Kind::Map(Box::new(Kind::of_type(key)), Box::new(Kind::of_type(value)))
+            }
+            Type::Record(fields) => {
+                Kind::Record(fields.iter().map(|(_, ty)| Kind::of_type(ty)).collect())
+            }
+            Type::Option(inner) => Kind::Option(Box::new(Kind::of_type(inner))),
+            Type::Function { params, ret } => Kind::Function(vec![(
+                params.iter().map(|param| Kind::of_type(param)).collect(),
+                Kind::of_type(ret),
+            )]),
+            Type::Symbol(_) | Type::TypeVar(_) => Kind::Unknown,
+        }
+    }
+
+    /// The kind of the result of calling a function with these
+    /// `signatures` with arguments of kinds `args`. The instantiation is
+    /// chosen by the argument types, so those with the same argument kinds
+    /// must have the same result kind.
+    fn call_result(signatures: &[Signature], args: &[Kind]) -> Result<Kind, &'static str> {
+        let mut results = (signatures.iter())
+            .filter(|(params, _)| params == args)
+            .map(|(_, ret)| ret);
+        let result = (results.next()).ok_or("calls a function with the wrong argument kinds")?;
+        if results.any(|other| other != result) {
+            return Err("calls a function with ambiguous result kinds");
+        }
+        Ok(result.clone())
+    }
+
+    /// The signature of a function of type `ty`, if it is one.
+    fn signature(ty: &Type) -> Option<Signature> {
+        match Kind::of_type(ty) {
+            Kind::Function(mut signatures) => signatures.pop(),
+            _ => None,
+        }
+    }
+
+    /// The kind of a value that may come from either `self` or `other`.
+    fn join(self, other: Kind) -> Kind {
+        match (self, other) {
+            (Kind::Never, kind) | (kind, Kind::Never) => kind,
+            (Kind::Array(a), Kind::Array(b)) => Kind::Array(Box::new(a.join(*b))),
+            (Kind::Option(a), Kind::Option(b)) => Kind::Option(Box::new(a.join(*b))),
+            (Kind::Map(ak, av), Kind::Map(bk, bv)) => {
+                Kind::Map(Box::new(ak.join(*bk)), Box::new(av.join(*bv)))
+            }
+            (Kind::Record(a), Kind::Record(b)) if a.len() == b.len() => {
+                Kind::Record(a.into_iter().zip(b).map(|(a, b)| a.join(b)).collect())
+            }
+            (a, b) if a == b => a,
+            _ => Kind::Unknown,
+        }
+    }
+
+    fn join_all(kinds: impl IntoIterator<Item = Kind>) -> Kind {
+        kinds.into_iter().fold(Kind::Never, Kind::join)
+    }
+
+    /// Whether a value of this kind can be used as `expected`.
+    ///
+    /// Any value can be used as one that is not looked into, and a function
+    /// as one with fewer instantiations.
+    fn is(&self, expected: &Kind) -> bool {
+        match (self, expected) {
+            (Kind::Never, _) | (_, Kind::Unknown) => true,
+            (Kind::Array(a), Kind::Array(b)) | (Kind::Option(a), Kind::Option(b)) => a.is(b),
+            (Kind::Map(ak, av), Kind::Map(bk, bv)) => ak.is(bk) && av.is(bv),
+            (Kind::Record(a), Kind::Record(b)) => {
+                a.len() == b.len() && a.iter().zip(b).all(|(a, b)| a.is(b))
+            }
+            (Kind::Function(a), Kind::Function(b)) => b.iter().all(|(params, ret)| {
+                Kind::call_result(a, params).is_ok_and(|result| result.is(ret))
+            }),
+            _ => self == expected,
+        }
+    }
+}
+
+/// Identifies a value, so that what a branch learns about it also applies
+/// to its copies. Values are numbered after the instruction that produced
+/// them, which runs at most once since all jumps go forward.
+type Id = usize;
+
+/// What a path has learned about a value from the branches it took.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Fact {
+    /// A `Bool` with this value.
+    Bool(bool),
+    /// A `Bool` that is true when the `Bool` with the given id has the
+    /// given value, as computed by `EqBool`.
+    Equals(Id, bool),
+    /// An `Option` that is `none`.
+    None,
+    /// An `Option` that is `some`.
+    Some,
+}
+
+/// A value on the stack or in a local.
+#[derive(Debug, Clone, PartialEq)]
+struct Slot {
+    kind: Kind,
+    /// `None` when paths with different values were joined.
+    id: Option<Id>,
+}
+
+/// What the verifier knows along one path, before an instruction runs.
+#[derive(Debug, Clone, PartialEq)]
+struct State {
+    stack: Vec<Slot>,
+    /// `None` for locals that may not have been stored yet.
+    locals: Vec<Option<Slot>>,
+    /// Fallback instruction and stack depth of the active `otherwise`
+    /// handlers, innermost last.
+    handlers: Vec<(usize, usize)>,
+    /// Facts about the values in `stack` and `locals`, sorted by id.
+    facts: Vec<(Id, Fact)>,
+}
+
+impl State {
+    fn fact(&self, id: Option<Id>) -> Option<Fact> {
+        let id = id?;
+        let index = self.facts.binary_search_by_key(&id, |&(id, _)| id).ok()?;
+        Some(self.facts[index].1)
+    }
+
+    /// Record `fact` about the value `id`, returning `false` if the path
+    /// already knows otherwise and so is never taken.
+    fn learn(&mut self, id: Option<Id>, fact: Fact) -> bool {
+        let Some(id) = id else {
+            return true;
+        };
+        match self.facts.binary_search_by_key(&id, |&(id, _)| id) {
+            Ok(index) => match (self.facts[index].1, fact) {
+                (known, fact) if known == fact => true,
+                (Fact::Equals(other, value), Fact::Bool(result)) => {
+                    self.facts[index].1 = fact;
+                    self.learn(Some(other), Fact::Bool(value == result))
+                }
+                _ => false,
+            },
+            Err(index) => {
+                self.facts.insert(index, (id, fact));
+                true
+            }
+        }
+    }
+
+    fn push(&mut self, kind: Kind, id: Id) {
+        self.stack.push(Slot { kind, id: Some(id) });
+    }
+
+    fn pop(&mut self) -> Slot {
+        self.stack.pop().expect("checked against the stack effect")
+    }
+}
+
+/// Check that `code` cannot make the VM read outside the code, the
+/// constants or the locals, or read a value as the wrong kind.
+///
+/// The code runs with values of types `params` in its first locals.
+/// Generic adapters must have a [`spec`](crate::vm::GenericAdapter::spec),
+/// as deserialized ones do.
+pub(super) fn verify(code: &Code<'_>, params: &[&Type<'_>]) -> Result<(), DecodeError> {
+    let params = params.iter().map(|ty| Kind::of_type(ty)).collect();
+    verify_code(code, params, Vec::new(), None)
+}
+
+/// Check code that runs with `params` in its first locals and `captures`,
+/// and returns a value of kind `ret` if given, then the lambdas it defines.
+fn verify_code(
+    code: &Code<'_>,
+    params: Vec<Kind>,
+    captures: Vec<Kind>,
+    ret: Option<Kind>,
+) -> Result<(), DecodeError> {
+    // Every local other than the parameters is written by a `StoreLocal`,
+    // and every instruction grows the stack by at most one value. Checked
+    // first, as the verifier allocates the locals of each path.
+    let header_error = if params.len() > code.num_locals {
+        Some("has fewer locals than parameters")
+    } else if code.num_locals - params.len() > code.instructions.len() {
+        Some("has more locals than it can store")
+    } else if code.max_stack_size > code.instructions.len() {
+        Some("has a larger stack than it can fill")
+    } else {
+        None
+    };
+    if let Some(reason) = header_error {
+        return Err(DecodeError::InvalidCode { index: 0, reason });
+    }
+    let operands = decode_operands(code)?;
+    let mut locals = vec![None; code.num_locals];
+    for (local, kind) in locals.iter_mut().zip(params) {
+        *local = Some(Slot { kind, id: None });
+    }
+    let mut verifier = Verifier {
+        code,
+        paths: vec![Vec::new(); code.instructions.len()],
+        inner_ids: Vec::new(),
+        captures,
+        ret,
+        lambda_captures: vec![None; code.lambdas.len()],
+    };
+    verifier.paths[0].push(State {
+        stack: Vec::new(),
+        locals,
+        handlers: Vec::new(),
+        facts: Vec::new(),
+    });
+
+    for (index, &instr) in code.instructions.iter().enumerate() {
+        // Unreachable instructions never run
+        for state in core::mem::take(&mut verifier.paths[index]) {
+            verifier.step(index, instr, operands[index], state)?;
+        }
+    }
+
+    for (lambda, captures) in code.lambdas.iter().zip(verifier.lambda_captures) {
+        let LambdaKind::Mono { code } = &lambda.kind else {
+            continue;
+        };
+        let (params, ret) =
+            Kind::signature(lambda.lambda_type).ok_or(DecodeError::InvalidCode {
+                index: 0,
+                reason: "lambda type is not a function",
+            })?;
+        // Lambdas that are never made into closures never run
+        let captures =
+            captures.unwrap_or_else(|| vec![Kind::Unknown; lambda.num_captures as usize]);
+        verify_code(code, params, captures, Some(ret))?;
+    }
+    Ok(())
+}
+
+struct Verifier<'c, 't> {
+    code: &'c Code<'t>,
+    /// Paths reaching each instruction that have not been followed yet.
+    paths: Vec<Vec<State>>,
+    /// Ids of the values inside options, by the id of the option.
+    inner_ids: Vec<(Id, Id)>,
+    /// Kinds of the captured values the code can load.
+    captures: Vec<Kind>,
+    /// Kind of the value the code returns, if known.
+    ret: Option<Kind>,
+    /// Kinds of the values captured by each lambda of the code, joined over
+    /// the closures made from it.
+    lambda_captures: Vec<Option<Vec<Kind>>>,
+}
+
+impl Verifier<'_, '_> {
+    /// Follow one path through the instruction at `index`.
+    fn step(
+        &mut self,
+        index: usize,
+        instr: Instruction,
+        arg: usize,
+        mut state: State,
+    ) -> Result<(), DecodeError> {
+        let invalid = |reason| DecodeError::InvalidCode { index, reason };
+
+        let (pops, _) = self.code.stack_effect(instr, arg);
+        if state.stack.len() < pops {
+            return Err(invalid("pops more values than the stack holds"));
+        }
+        // A runtime error jumps to the innermost handler's fallback code,
+        // which starts by popping the handler
+        if let Some(&(fallback, depth)) = state.handlers.last()
+            && !(fallback == index && instr == Instruction::PopOtherwise)
+        {
+            if fallback <= index {
+                return Err(invalid("runs inside its own otherwise handler"));
+            }
+            if state.stack.len() - pops < depth {
+                return Err(invalid("pops values pushed before its otherwise handler"));
+            }
+            let mut on_error = state.clone();
+            on_error.stack.truncate(depth);
+            self.reach(index, fallback, on_error)?;
+        }
+
+        let target = index + 1 + arg;
+        use Instruction::*;
+        match instr {
+            WideArg(_) | Nop => {}
+            ConstLoad(_) => {
+                let ty = self
+                    .code
+                    .constant_types
+                    .get(arg)
+                    .ok_or(invalid("loads a constant that does not exist"))?;
+                let kind = Kind::of_type(ty);
+                if kind == Kind::Bool {
+                    let value = self.code.constants[arg].as_bool_unchecked();
+                    state.learn(Some(index), Fact::Bool(value));
+                }
+                state.push(kind, index);
+            }
+            ConstInt(_) | ConstUInt(_) => state.push(Kind::Int, index),
+            ConstBool(value) => {
+                state.learn(Some(index), Fact::Bool(value != 0));
+                state.push(Kind::Bool, index);
+            }
+            DupN(depth) => {
+                let slot = (state.stack.iter().rev())
+                    .nth(depth as usize)
+                    .ok_or(invalid("duplicates a value below the bottom of the stack"))?;
+                state.stack.push(slot.clone());
+            }
+            Pop => {
+                state.pop();
+            }
+            Swap => {
+                let len = state.stack.len();
+                state.stack.swap(len - 1, len - 2);
+            }
+            LoadLocal(_) => {
+                let slot = state
+                    .locals
+                    .get(arg)
+                    .ok_or(invalid("loads a local that does not exist"))?
+                    .clone()
+                    .ok_or(invalid("loads a local before storing it"))?;
+                state.stack.push(slot);
+            }
+            StoreLocal(_) => {
+                if arg >= state.locals.len() {
+                    return Err(invalid("stores a local that does not exist"));
+                }
+                state.locals[arg] = Some(state.pop());
+            }
+
+            IntBinOp(op) => {
+                if !b"+-*/%qrfm^".contains(&op) {
+                    return Err(invalid("unknown integer operator"));
+                }
+                pop_kinds(&mut state, &[Kind::Int, Kind::Int], invalid)?;
+                state.push(Kind::Int, index);
+            }
+            IntBitOp(op) => {
+                if !b"&|^<>".contains(&op) {
+                    return Err(invalid("unknown bitwise operator"));
+                }
+                pop_kinds(&mut state, &[Kind::Int, Kind::Int], invalid)?;
+                state.push(Kind::Int, index);
+            }
+            NegInt => {
+                pop_kinds(&mut state, &[Kind::Int], invalid)?;
+                state.push(Kind::Int, index);
+            }
+            IntCmpOp(ComparisonOp::In | ComparisonOp::NotIn)
+            | FloatCmpOp(ComparisonOp::In | ComparisonOp::NotIn) => {
+                return Err(invalid("numbers do not support `in`"));
+            }
+            IntCmpOp(_) => {
+                pop_kinds(&mut state, &[Kind::Int, Kind::Int], invalid)?;
+                state.push(Kind::Bool, index);
+            }
+            FloatBinOp(op) => {
+                if !b"+-*/%^".contains(&op) {
+                    return Err(invalid("unknown float operator"));
+                }
+                pop_kinds(&mut state, &[Kind::Float, Kind::Float], invalid)?;
+                state.push(Kind::Float, index);
+            }
+            NegFloat => {
+                pop_kinds(&mut state, &[Kind::Float], invalid)?;
+                state.push(Kind::Float, index);
+            }
+            FloatCmpOp(_) => {
+                pop_kinds(&mut state, &[Kind::Float, Kind::Float], invalid)?;
+                state.push(Kind::Bool, index);
+            }
+            And | Or => {
+                pop_kinds(&mut state, &[Kind::Bool, Kind::Bool], invalid)?;
+                state.push(Kind::Bool, index);
+            }
+            EqBool => {
+                let b = state.pop();
+                let a = state.pop();
+                if !a.kind.is(&Kind::Bool) || !b.kind.is(&Kind::Bool) {
+                    return Err(invalid("expects a Bool"));
+                }
+                let fact = match (state.fact(a.id), state.fact(b.id)) {
+                    (Some(Fact::Bool(a)), Some(Fact::Bool(b))) => Some(Fact::Bool(a == b)),
+                    (Some(Fact::Bool(value)), _) => b.id.map(|id| Fact::Equals(id, value)),
+                    (_, Some(Fact::Bool(value))) => a.id.map(|id| Fact::Equals(id, value)),
+                    _ => None,
+                };
+                if let Some(fact) = fact {
+                    state.learn(Some(index), fact);
+                }
+                state.push(Kind::Bool, index);
+            }
+            Not => {
+                let a = state.pop();
+                if !a.kind.is(&Kind::Bool) {
+                    return Err(invalid("expects a Bool"));
+                }
+                match state.fact(a.id) {
+                    Some(Fact::Bool(value)) => state.learn(Some(index), Fact::Bool(!value)),
+                    Some(Fact::Equals(id, value)) => {
+                        state.learn(Some(index), Fact::Equals(id, !value))
+                    }
+                    _ => true,
+                };
+                state.push(Kind::Bool, index);
+            }
+
+            StrGet => {
+                pop_kinds(&mut state, &[Kind::Str, Kind::Int], invalid)?;
+                state.push(Kind::Str, index);
+            }
+            StrSlice => {
+                pop_kinds(&mut state, &[Kind::Str, Kind::Int, Kind::Int], invalid)?;
+                state.push(Kind::Str, index);
+            }
+            StringCmpOp(_) => {
+                pop_kinds(&mut state, &[Kind::Str, Kind::Str], invalid)?;
+                state.push(Kind::Bool, index);
+            }
+            BytesGet => {
+                pop_kinds(&mut state, &[Kind::Bytes, Kind::Int], invalid)?;
+                state.push(Kind::Int, index);
+            }
+            BytesGetConst(_) => {
+                pop_kinds(&mut state, &[Kind::Bytes], invalid)?;
+                state.push(Kind::Int, index);
+            }
+            BytesCmpOp(_) => {
+                pop_kinds(&mut state, &[Kind::Bytes, Kind::Bytes], invalid)?;
+                state.push(Kind::Bool, index);
+            }
+
+            MakeArray(_) => {
+                let elements = state.stack.split_off(state.stack.len() - arg);
+                let elem = Kind::join_all(elements.into_iter().map(|slot| slot.kind));
+                state.push(Kind::Array(Box::new(elem)), index);
+            }
+            ArrayGet => {
+                pop_kinds(&mut state, &[Kind::Int], invalid)?;
+                let elem = pop_array(&mut state, invalid)?;
+                state.push(elem, index);
+            }
+            ArrayGetConst(_) => {
+                let elem = pop_array(&mut state, invalid)?;
+                state.push(elem, index);
+            }
+            ArraySlice => {
+                pop_kinds(&mut state, &[Kind::Int, Kind::Int], invalid)?;
+                let elem = pop_array(&mut state, invalid)?;
+                state.push(Kind::Array(Box::new(elem)), index);
+            }
+
+            // The VM compares map keys as raw integers
+            MakeMap(_) | MakeMapUnique(_) => {
+                let entries = state.stack.split_off(state.stack.len() - 2 * arg);
+                let mut values = Vec::new();
+                for pair in entries.chunks(2) {
+                    if !pair[0].kind.is(&Kind::Int) {
+                        return Err(invalid("expects an Int map key"));
+                    }
+                    values.push(pair[1].kind.clone());
+                }
+                let value = Kind::join_all(values);
+                state.push(Kind::Map(Box::new(Kind::Int), Box::new(value)), index);
+            }
+            MapGet => {
+                pop_kinds(&mut state, &[Kind::Int], invalid)?;
+                let value = pop_int_map(&mut state, invalid)?;
+                state.push(value, index);
+            }
+            MapHas => {
+                pop_int_map(&mut state, invalid)?;
+                pop_kinds(&mut state, &[Kind::Int], invalid)?;
+                state.push(Kind::Bool, index);
+            }
+
+            MakeRecord(_) => {
+                let fields = state.stack.split_off(state.stack.len() - arg);
+                let fields = fields.into_iter().map(|slot| slot.kind).collect();
+                state.push(Kind::Record(fields), index);
+            }
+            RecordGet(_) => {
+                let field = match state.pop().kind {
+                    Kind::Never => Kind::Never,
+                    Kind::Record(mut fields) if arg < fields.len() => fields.swap_remove(arg),
+                    _ => return Err(invalid("reads a field that does not exist")),
+                };
+                state.push(field, index);
+            }
+
+            MakeOption(0) => {
+                state.learn(Some(index), Fact::None);
+                state.push(Kind::Option(Box::new(Kind::Never)), index);
+            }
+            MakeOption(1) => {
+                let inner = state.pop().kind;
+                state.learn(Some(index), Fact::Some);
+                state.push(Kind::Option(Box::new(inner)), index);
+            }
+            MakeOption(_) => return Err(invalid("invalid option tag")),
+
+            JumpForward(_) => return self.reach(index, target, state),
+            PopJumpIfFalse(_) | PopJumpIfTrue(_) => {
+                let cond = state.pop();
+                if !cond.kind.is(&Kind::Bool) {
+                    return Err(invalid("expects a Bool"));
+                }
+                let jump_if = matches!(instr, PopJumpIfTrue(_));
+                let mut jumped = state.clone();
+                if jumped.learn(cond.id, Fact::Bool(jump_if)) {
+                    self.reach(index, target, jumped)?;
+                }
+                if !state.learn(cond.id, Fact::Bool(!jump_if)) {
+                    return Ok(());
+                }
+            }
+            MatchSomeOrJump(_) | MatchNoneOrJump(_) => {
+                let option = state.pop();
+                let inner = match option.kind {
+                    Kind::Never => Kind::Never,
+                    Kind::Option(inner) => *inner,
+                    _ => return Err(invalid("expects an Option")),
+                };
+                let (jump_if, fall_through_if) = match instr {
+                    MatchSomeOrJump(_) => (Fact::None, Fact::Some),
+                    _ => (Fact::Some, Fact::None),
+                };
+                let mut jumped = state.clone();
+                if jumped.learn(option.id, jump_if) {
+                    self.reach(index, target, jumped)?;
+                }
+                if !state.learn(option.id, fall_through_if) {
+                    return Ok(());
+                }
+                if let MatchSomeOrJump(_) = instr {
+                    let id = match option.id {
+                        Some(id) => self.inner_id(id),
+                        None => index,
+                    };
+                    state.push(inner, id);
+                }
+            }
+            PushOtherwise(_) => {
+                let depth = state.stack.len();
+                state.handlers.push((target, depth));
+            }
+            PopOtherwise => {
+                state
+                    .handlers
+                    .pop()
+                    .ok_or(invalid("pops an otherwise handler that was not pushed"))?;
+            }
+            PopOtherwiseAndJump(_) => {
+                state
+                    .handlers
+                    .pop()
+                    .ok_or(invalid("pops an otherwise handler that was not pushed"))?;
+                return self.reach(index, target, state);
+            }
+            Return | Halt => {
+                if state.stack.len() != 1 {
+                    return Err(invalid("returns with other than one value on the stack"));
+                }
+                if let Some(ret) = &self.ret
+                    && !state.stack[0].kind.is(ret)
+                {
+                    return Err(invalid("returns a value of the wrong kind"));
+                }
+                return Ok(());
+            }
+
+            Call(_) => {
+                let adapter = (self.code.adapters.get(arg))
+                    .ok_or(invalid("calls an adapter that does not exist"))?;
+                let function = state.pop();
+                let args = state
+                    .stack
+                    .split_off(state.stack.len() - adapter.param_types().len());
+                let params: Vec<Kind> = (adapter.param_types().iter())
+                    .map(|param| Kind::of_type(param))
+                    .collect();
+                for (position, (arg, param)) in args.iter().zip(&params).enumerate() {
+                    // Converted to the parameter type by the adapter
+                    let expected = match adapter.arg_types().get(position) {
+                        Some(arg_ty)
+                            if adapter
+                                .coercion()
+                                .allows(arg_ty, adapter.param_types()[position]) =>
+                        {
+                            Kind::of_type(arg_ty)
+                        }
+                        _ => param.clone(),
+                    };
+                    if !arg.kind.is(&expected) {
+                        return Err(invalid("passes an argument of the wrong kind"));
+                    }
+                }
+                // The instantiation is chosen by the argument types
+                let ret = match function.kind {
+                    Kind::Never => Kind::Never,
+                    Kind::Function(signatures) => {
+                        Kind::call_result(&signatures, &params).map_err(invalid)?
+                    }
+                    _ => return Err(invalid("calls a value that is not a function")),
+                };
+                state.push(ret, index);
+            }
+            CallGenericAdapter(_) => {
+                let spec = (self.code.generic_adapters.get(arg))
+                    .ok_or(invalid("calls an adapter that does not exist"))?
+                    .spec()
+                    .ok_or(invalid("calls an adapter that cannot be verified"))?;
+                let result = adapter_result(spec, &mut state, invalid)?;
+                state.push(result, index);
+            }
+            MakeClosure(_) => {
+                let lambda = (self.code.lambdas.get(arg))
+                    .ok_or(invalid("makes a closure from a lambda that does not exist"))?;
+                if !matches!(lambda.lambda_type, Type::Function { .. }) {
+                    return Err(invalid("makes a closure of a type that is not a function"));
+                }
+                if matches!(&lambda.kind, LambdaKind::Poly { monos } if monos.is_empty()) {
+                    return Err(invalid("makes a closure without instantiations"));
+                }
+                let captures: Vec<Kind> = (state
+                    .stack
+                    .split_off(state.stack.len() - lambda.num_captures as usize))
+                .into_iter()
+                .map(|slot| slot.kind)
+                .collect();
+                let instantiations = match &lambda.kind {
+                    LambdaKind::Mono { .. } => vec![arg],
+                    LambdaKind::Poly { monos } => monos.iter().map(|&mono| mono as usize).collect(),
+                };
+                let mut signatures = Vec::new();
+                for mono in instantiations {
+                    let lambda = match self.code.lambdas.get(mono) {
+                        Some(lambda) if matches!(lambda.kind, LambdaKind::Mono { .. }) => lambda,
+                        _ => return Err(invalid("makes a closure from a missing instantiation")),
+                    };
+                    signatures.push(
+                        Kind::signature(lambda.lambda_type)
+                            .ok_or(invalid("makes a closure of a type that is not a function"))?,
+                    );
+                    // The instantiations of a polymorphic lambda run with its captures
+                    let joined = match self.lambda_captures[mono].take() {
+                        Some(known) if known.len() == captures.len() => known
+                            .into_iter()
+                            .zip(&captures)
+                            .map(|(a, b)| a.join(b.clone()))
+                            .collect(),
+                        Some(_) => return Err(invalid("captures a different number of values")),
+                        None => captures.clone(),
+                    };
+                    self.lambda_captures[mono] = Some(joined);
+                }
+                state.push(Kind::Function(signatures), index);
+            }
+            LoadCapture(_) => {
+                let kind = (self.captures.get(arg))
+                    .ok_or(invalid("loads a capture that does not exist"))?
+                    .clone();
+                state.push(kind, index);
+            }
+            ArrayLen | ArrayConcat | ArrayAppend | MapLen | MapInsert | MapRemove | MapKeys
+            | MapValues | RecordMerge | StringFormat(_) | BytesSlice | StringToBytes
+            | BytesToString | Eq | NotEq | Breakpoint(_) | CheckLimits | Trace(_)
+            | InlineCache(_) => {
+                return Err(invalid("instruction is not supported by the VM"));
+            }
+        }
+
+        if state.stack.len() > self.code.max_stack_size {
+            return Err(invalid("exceeds the maximum stack size"));
+        }
+        // The last instruction is a `Return` or `Halt`, so there is a next one
+        self.reach(index, index + 1, state)
+    }
+
+    /// Id of the value inside the option `id`. The same option always holds
+    /// the same value.
+    fn inner_id(&mut self, id: Id) -> Id {
+        if let Some(&(_, inner)) = self.inner_ids.iter().find(|&&(outer, _)| outer == id) {
+            return inner;
+        }
+        let inner = self.code.instructions.len() + self.inner_ids.len();
+        self.inner_ids.push((id, inner));
+        inner
+    }
+
+    /// Record that `state` reaches `target` from the instruction at `from`.
+    fn reach(&mut self, from: usize, target: usize, mut state: State) -> Result<(), DecodeError> {
+        self.forget_dead_values(&mut state);
+        let paths = &mut self.paths[target];
+        if paths.contains(&state) {
+            return Ok(());
+        }
+        paths.push(state);
+        if paths.len() > MAX_PATHS {
+            let mut all = core::mem::take(paths).into_iter();
+            let first = all.next().expect("paths is not empty");
+            let joined = all.try_fold(first, join).ok_or(DecodeError::InvalidCode {
+                index: from,
+                reason: "reaches an instruction with different stack layouts",
+            })?;
+            paths.push(joined);
+        }
+        Ok(())
+    }
+
+    /// Drop the facts about values that are no longer on the stack or in the
+    /// locals, so that paths that only differ in them can be merged.
+    fn forget_dead_values(&self, state: &mut State) {
+        let mut live: Vec<Id> = (state.stack.iter())
+            .chain(state.locals.iter().flatten())
+            .filter_map(|slot| slot.id)
+            .collect();
+        let mut next = 0;
+        while next < live.len() {
+            let id = live[next];
+            next += 1;
+            if let Some(Fact::Equals(other, _)) = state.fact(Some(id)) {
+                live.push(other);
+            }
+            if let Some(&(_, inner)) = self.inner_ids.iter().find(|&&(outer, _)| outer == id) {
+                live.push(inner);
+            }
+        }
+        state.facts.retain(|(id, _)| live.contains(id));
+    }
+}
+
+/// Join two paths into one that covers both, or `None` if their stacks or
+/// handlers differ in shape.
+fn join(a: State, b: State) -> Option<State> {
+    if a.stack.len() != b.stack.len() || a.handlers != b.handlers {
+        return None;
+    }
+    let join_slots = |a: Slot, b: Slot| Slot {
+        id: if a.id == b.id { a.id } else { None },
+        kind: a.kind.join(b.kind),
+    };
+    Some(State {
+        stack: (a.stack.into_iter().zip(b.stack))
+            .map(|(a, b)| join_slots(a, b))
+            .collect(),
+        locals: (a.locals.into_iter().zip(b.locals))
+            .map(|(a, b)| Some(join_slots(a?, b?)))
+            .collect(),
+        handlers: a.handlers,
+        facts: (a.facts.into_iter())
+            .filter(|fact| b.facts.contains(fact))
+            .collect(),
+    })
+}
+
+/// Check the layout of the instructions and decode their full operands,
+/// with any `WideArg` prefixes applied.
+///
+/// Jump targets must be inside the code, and not between a prefix and its
+/// instruction. The code must end in `Return` or `Halt`.
+fn decode_operands(code: &Code<'_>) -> Result<Vec<usize>, DecodeError> {
+    let len = code.instructions.len();
+    if !matches!(
+        code.instructions.last(),
+        Some(Instruction::Return | Instruction::Halt)
+    ) {
+        return Err(DecodeError::InvalidCode {
+            index: len.saturating_sub(1),
+            reason: "code does not end in Return or Halt",
+        });
+    }
+
+    let mut operands = Vec::with_capacity(len);
+    let mut prefixes = 0;
+    let mut wide_arg = 0;
+    for (index, &instr) in code.instructions.iter().enumerate() {
+        let invalid = |reason| DecodeError::InvalidCode { index, reason };
+        if prefixes > 0 && !is_wide(instr) {
+            return Err(invalid(
+                "WideArg prefix on an instruction without an operand",
+            ));
+        }
+        let [_, operand] = instr.to_bytes();
+        if let Instruction::WideArg(high) = instr {
+            prefixes += 1;
+            if prefixes > MAX_WIDE_PREFIXES {
+                return Err(invalid("too many WideArg prefixes"));
+            }
+            wide_arg = (wide_arg | high as usize) << 8;
+            operands.push(0);
+            continue;
+        }
+        let arg = wide_arg | operand as usize;
+        prefixes = 0;
+        wide_arg = 0;
+
+        if get_jump_offset(&instr).is_some() {
+            let target = index + 1 + arg;
+            if target >= len {
+                return Err(invalid("jumps past the end of the code"));
+            }
+            if target > 0 && matches!(code.instructions[target - 1], Instruction::WideArg(_)) {
+                return Err(invalid(
+                    "jumps between a WideArg prefix and its instruction",
+                ));
+            }
+        }
+        operands.push(arg);
+    }
+    Ok(operands)
+}
+
+/// Whether the VM applies `WideArg` prefixes to the operand of `instr`.
+fn is_wide(instr: Instruction) -> bool {
+    use Instruction::*;
+    matches!(
+        instr,
+        WideArg(_)
+            | ConstLoad(_)
+            | LoadLocal(_)
+            | StoreLocal(_)
+            | LoadCapture(_)
+            | JumpForward(_)
+            | PopJumpIfFalse(_)
+            | PopJumpIfTrue(_)
+            | PushOtherwise(_)
+            | PopOtherwiseAndJump(_)
+            | MatchSomeOrJump(_)
+            | MatchNoneOrJump(_)
+            | Call(_)
+            | CallGenericAdapter(_)
+            | MakeClosure(_)
+            | MakeArray(_)
+            | ArrayGetConst(_)
+            | MakeMap(_)
+            | MakeMapUnique(_)
+            | MakeRecord(_)
+            | RecordGet(_)
+            | BytesGetConst(_)
+    )
+}
+
+/// Pop values of the given kinds, the last one from the top.
+fn pop_kinds(
+    state: &mut State,
+    kinds: &[Kind],
+    invalid: impl Fn(&'static str) -> DecodeError,
+) -> Result<(), DecodeError> {
+    let operands = state.stack.split_off(state.stack.len() - kinds.len());
+    for (operand, kind) in operands.iter().zip(kinds) {
+        if !operand.kind.is(kind) {
+            return Err(invalid(match kind {
+                Kind::Int => "expects an Int",
+                Kind::Float => "expects a Float",
+                Kind::Bool => "expects a Bool",
+                Kind::Str => "expects a Str",
+                _ => "expects Bytes",
+            }));
+        }
+    }
+    Ok(())
+}
+
+/// Pop the arguments of a generic adapter, returning the kind of its result.
+fn adapter_result(
+    spec: AdapterSpec<'_>,
+    state: &mut State,
+    invalid: impl Fn(&'static str) -> DecodeError + Copy,
+) -> Result<Kind, DecodeError> {
+    let expect = |slot: Slot, ty: &Type| {
+        if slot.kind.is(&Kind::of_type(ty)) {
+            Ok(())
+        } else {
+            Err(invalid("passes an adapter an argument of the wrong kind"))
+        }
+    };
+    Ok(match spec {
+        AdapterSpec::ArrayContains { element_type, .. } => {
+            let elem = pop_array(state, invalid)?;
+            if !elem.is(&Kind::of_type(element_type)) {
+                return Err(invalid("passes an adapter an argument of the wrong kind"));
+            }
+            expect(state.pop(), element_type)?;
+            Kind::Bool
+        }
+        AdapterSpec::Cast {
+            source_type,
+            target_type,
+        } => {
+            expect(state.pop(), source_type)?;
+            Kind::of_type(target_type)
+        }
+        AdapterSpec::FormatStr { expr_types, .. } => {
+            let args = state.stack.split_off(state.stack.len() - expr_types.len());
+            for (arg, ty) in args.into_iter().zip(expr_types) {
+                expect(arg, ty)?;
+            }
+            Kind::Str
+        }
+        AdapterSpec::MakeMap {
+            key_type,
+            num_pairs,
+            ..
+        } => {
+            let entries = state.stack.split_off(state.stack.len() - 2 * num_pairs);
+            let mut values = Vec::new();
+            for pair in entries.chunks(2) {
+                expect(pair[0].clone(), key_type)?;
+                values.push(pair[1].kind.clone());
+            }
+            Kind::Map(
+                Box::new(Kind::of_type(key_type)),
+                Box::new(Kind::join_all(values)),
+            )
+        }
+        AdapterSpec::MapGet { key_type } => {
+            expect(state.pop(), key_type)?;
+            pop_map(state, key_type, invalid)?
+        }
+        AdapterSpec::MapContains { key_type, .. } => {
+            pop_map(state, key_type, invalid)?;
+            expect(state.pop(), key_type)?;
+            Kind::Bool
+        }
+        AdapterSpec::OptionCompare { option_type, .. } => {
+            expect(state.pop(), option_type)?;
+            expect(state.pop(), option_type)?;
+            Kind::Bool
+        }
+    })
+}
+
+/// Pop a map with keys of type `key_type`, returning the kind of its values.
+fn pop_map(
+    state: &mut State,
+    key_type: &Type,
+    invalid: impl Fn(&'static str) -> DecodeError,
+) -> Result<Kind, DecodeError> {
+    match state.pop().kind {
+        Kind::Never => Ok(Kind::Never),
+        Kind::Map(key, value) if key.is(&Kind::of_type(key_type)) => Ok(*value),
+        _ => Err(invalid("expects a Map with keys of the adapter's type")),
+    }
+}
+
+/// Pop an array, returning the kind of its elements.
+fn pop_array(
+    state: &mut State,
+    invalid: impl Fn(&'static str) -> DecodeError,
+) -> Result<Kind, DecodeError> {
+    match state.pop().kind {
+        Kind::Never => Ok(Kind::Never),
+        Kind::Array(elem) => Ok(*elem),
+        _ => Err(invalid("expects an Array")),
+    }
+}
+
+/// Pop a map with `Int` keys, returning the kind of its values.
+fn pop_int_map(
+    state: &mut State,
+    invalid: impl Fn(&'static str) -> DecodeError,
+) -> Result<Kind, DecodeError> {
+    match state.pop().kind {
+        Kind::Never => Ok(Kind::Never),
+        Kind::Map(key, value) if key.is(&Kind::Int) => Ok(*value),
+        _ => Err(invalid("expects a Map with Int keys")),
+    }
+}
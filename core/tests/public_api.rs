@@ -4,8 +4,13 @@
 //! parsing, type checking, and evaluation.
 
 use bumpalo::Bump;
-use melbi_core::api::{CompileOptions, CompileOptionsOverride, Engine, EngineOptions};
+use melbi_core::api::{
+    ArgumentCoercion, CompilationStats, CompileOptions, CompileOptionsOverride, DuplicateKeyPolicy,
+    Engine, EngineOptions, Error, InfixOperator, IntegerDivision, LanguageVersion,
+    OperatorPrecedence, Severity,
+};
 use melbi_core::evaluator::ExecutionError;
+use melbi_core::parser::AttributeValue;
 use melbi_core::values::{FfiContext, NativeFunction};
 use melbi_core::values::dynamic::Value;
 
@@ -305,6 +310,7 @@ fn test_engine_options_max_depth() {
         default_run_options: RunOptions {
             max_depth: 5,
             max_iterations: None, // Unlimited
+            ..Default::default()
         },
     };
     let engine = Engine::new(options, &arena, |arena, type_mgr, env| {
@@ -343,6 +349,159 @@ fn test_engine_options_max_depth() {
         .expect("execution should succeed");
 }
 
+#[test]
+fn test_max_depth_exceeded_reports_location() {
+    use melbi_core::api::RunOptionsOverride;
+
+    let arena = Bump::new();
+    let engine = Engine::new(EngineOptions::default(), &arena, |_, _, _| {});
+
+    let mut source = String::from("1");
+    for _ in 0..50 {
+        source = format!("1 + ({})", source);
+    }
+    let expr = engine
+        .compile(Default::default(), &source, &[])
+        .expect("compilation should succeed");
+
+    let val_arena = Bump::new();
+    let run_options = RunOptionsOverride {
+        max_depth: Some(20),
+        ..Default::default()
+    };
+    let err = expr
+        .run(run_options, &val_arena, &[])
+        .expect_err("evaluation should exceed max_depth");
+
+    let (diagnostic, error_source) = match err {
+        Error::ResourceExceeded { diagnostic, source } => (diagnostic, source),
+        other => panic!("Expected ResourceExceeded error, got: {:?}", other),
+    };
+    assert_eq!(diagnostic.code.as_deref(), Some("R005"));
+    assert!(diagnostic.message.contains("exceeds maximum of 20"));
+    assert_eq!(error_source, source);
+    // The span points at the nested expression where the limit was hit,
+    // not at the whole expression.
+    assert!(diagnostic.span.0.start > 0);
+    assert!(diagnostic.span.0.end <= source.len());
+}
+
+#[test]
+fn test_run_options_max_steps() {
+    use melbi_core::api::RunOptionsOverride;
+
+    let arena = Bump::new();
+    let engine = Engine::new(EngineOptions::default(), &arena, |arena, type_mgr, env| {
+        let iterate = melbi_core::stdlib::build_iterate(arena, type_mgr).unwrap();
+        env.register("Iterate", iterate).unwrap();
+    });
+
+    // Steps taken inside the lambda count too: 6 for the call itself, plus
+    // 6 per iteration of the body.
+    let expr = engine
+        .compile(
+            Default::default(),
+            "Iterate([0], 100, (xs) => [xs[0] + 1])",
+            &[],
+        )
+        .expect("compilation should succeed");
+    let with_limit = |max_steps| RunOptionsOverride {
+        max_steps: Some(Some(max_steps)),
+        ..Default::default()
+    };
+
+    let val_arena = Bump::new();
+    let result = expr.run(with_limit(606), &val_arena, &[]).unwrap();
+    assert_eq!(
+        result.as_array().unwrap().get(0).unwrap().as_int().unwrap(),
+        100
+    );
+
+    let err = expr
+        .run(with_limit(605), &val_arena, &[])
+        .expect_err("evaluation should exceed max_steps");
+    match err {
+        Error::ResourceExceeded { diagnostic, .. } => {
+            assert_eq!(diagnostic.code.as_deref(), Some("R014"));
+            assert!(diagnostic.message.contains("605"));
+        }
+        other => panic!("Expected ResourceExceeded error, got: {:?}", other),
+    }
+
+    // The limit cannot be caught with `otherwise`
+    let expr = engine
+        .compile(
+            Default::default(),
+            "Iterate([0], 100, (xs) => [xs[0] + 1]) otherwise []",
+            &[],
+        )
+        .unwrap();
+    let result = expr.run(with_limit(100), &val_arena, &[]);
+    assert!(matches!(result, Err(Error::ResourceExceeded { .. })));
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_run_options_deadline() {
+    use melbi_core::api::RunOptionsOverride;
+    use std::time::{Duration, Instant};
+
+    let arena = Bump::new();
+    let engine = Engine::new(EngineOptions::default(), &arena, |arena, type_mgr, env| {
+        let iterate = melbi_core::stdlib::build_iterate(arena, type_mgr).unwrap();
+        env.register("Iterate", iterate).unwrap();
+    });
+    let with_deadline = |deadline| RunOptionsOverride {
+        deadline: Some(Some(deadline)),
+        ..Default::default()
+    };
+    let val_arena = Bump::new();
+
+    // Would run for hours without a deadline.
+    let busy = engine
+        .compile(
+            Default::default(),
+            "Iterate([0], 1000000, (xs) => Iterate(xs, 1000000, (ys) => [ys[0] + 1])) otherwise []",
+            &[],
+        )
+        .unwrap();
+    let started = Instant::now();
+    let err = busy
+        .run(
+            with_deadline(started + Duration::from_millis(50)),
+            &val_arena,
+            &[],
+        )
+        .expect_err("evaluation should exceed the deadline");
+    match err {
+        Error::ResourceExceeded { diagnostic, .. } => {
+            assert_eq!(diagnostic.code.as_deref(), Some("R015"));
+        }
+        other => panic!("Expected ResourceExceeded error, got: {:?}", other),
+    }
+    assert!(started.elapsed() < Duration::from_secs(10));
+
+    // Expressions that finish in time are unaffected.
+    let quick = engine
+        .compile(
+            Default::default(),
+            "Iterate([0], 100, (xs) => [xs[0] + 1])",
+            &[],
+        )
+        .unwrap();
+    let result = quick
+        .run(
+            with_deadline(Instant::now() + Duration::from_secs(60)),
+            &val_arena,
+            &[],
+        )
+        .unwrap();
+    assert_eq!(
+        result.as_array().unwrap().get(0).unwrap().as_int().unwrap(),
+        100
+    );
+}
+
 #[test]
 fn test_error_duplicate_registration() {
     let arena = Bump::new();
@@ -377,6 +536,112 @@ fn test_error_duplicate_registration() {
     );
 }
 
+#[test]
+fn test_lazy_global_is_built_on_first_use() {
+    let builds = std::cell::Cell::new(0);
+    let arena = Bump::new();
+    let engine = Engine::new(EngineOptions::default(), &arena, |arena, type_mgr, env| {
+        let builds = &builds;
+        let ty = type_mgr.record(vec![("limit", type_mgr.int())]);
+        env.register_lazy("Config", ty, move || {
+            builds.set(builds.get() + 1);
+            Value::record(arena, ty, &[("limit", Value::int(type_mgr, 100))]).unwrap()
+        })
+        .unwrap();
+    });
+    let int_ty = engine.type_manager().int();
+    let val_arena = Bump::new();
+
+    // Compiling and running rules that don't reference the global never builds it.
+    let unrelated = engine.compile(Default::default(), "1 + 1", &[]).unwrap();
+    unrelated.run(Default::default(), &val_arena, &[]).unwrap();
+    let shadowed = engine
+        .compile(Default::default(), "Config + 1", &[("Config", int_ty)])
+        .unwrap();
+    let arg = Value::int(engine.type_manager(), 1);
+    shadowed
+        .run(Default::default(), &val_arena, &[arg])
+        .unwrap();
+    let uses_config = engine
+        .compile(Default::default(), "Config.limit * 2", &[])
+        .unwrap();
+    // Neither does reporting its compilation stats.
+    uses_config.compilation_stats().unwrap();
+    assert_eq!(builds.get(), 0);
+
+    // The first run builds it, later runs reuse the cached value.
+    for _ in 0..3 {
+        let result = uses_config
+            .run(Default::default(), &val_arena, &[])
+            .unwrap();
+        assert_eq!(result.as_int().unwrap(), 200);
+    }
+    assert_eq!(builds.get(), 1);
+
+    // The cache is shared by all expressions compiled with the engine.
+    let other = engine
+        .compile(Default::default(), "Config.limit", &[])
+        .unwrap();
+    assert_eq!(
+        other
+            .run(Default::default(), &val_arena, &[])
+            .unwrap()
+            .as_int()
+            .unwrap(),
+        100
+    );
+    assert_eq!(builds.get(), 1);
+}
+
+#[test]
+fn test_compiling_many_rules_reuses_global_scope() {
+    // Bytes allocated in the engine's arena by compiling 1000 small rules
+    // against an engine with `global_count` globals.
+    fn compile_rules(global_count: usize) -> usize {
+        let arena = Bump::new();
+        let engine = Engine::new(EngineOptions::default(), &arena, |_arena, type_mgr, env| {
+            for index in 0..global_count {
+                env.register(&format!("Global{index}"), Value::int(type_mgr, 1))
+                    .unwrap();
+            }
+        });
+        let before = arena.allocated_bytes();
+        for rule in 0..1000 {
+            let source = arena.alloc_str(&format!("Global0 + {rule}"));
+            engine.compile(Default::default(), source, &[]).unwrap();
+        }
+        arena.allocated_bytes() - before
+    }
+
+    // Rebuilding the global scope on each compile would allocate an entry
+    // per global per rule, tens of megabytes here.
+    let few_globals = compile_rules(1);
+    let many_globals = compile_rules(1000);
+    assert!(
+        many_globals < 2 * few_globals,
+        "compiling against 1000 globals allocated {many_globals} bytes, \
+         against 1 global {few_globals} bytes"
+    );
+}
+
+#[test]
+fn test_lazy_global_with_wrong_type_fails_at_run_time() {
+    let arena = Bump::new();
+    let engine = Engine::new(EngineOptions::default(), &arena, |_arena, type_mgr, env| {
+        env.register_lazy("limit", type_mgr.int(), || Value::float(type_mgr, 1.0))
+            .unwrap();
+        assert!(
+            env.register_lazy("limit", type_mgr.int(), || Value::int(type_mgr, 1))
+                .is_err()
+        );
+    });
+
+    let expr = engine.compile(Default::default(), "limit", &[]).unwrap();
+    let val_arena = Bump::new();
+    let err = expr.run(Default::default(), &val_arena, &[]).unwrap_err();
+    assert!(matches!(err, Error::Api(message) if message.contains("'limit'")));
+}
+
 #[test]
 fn test_access_expression_metadata() {
     let arena = Bump::new();
@@ -395,6 +660,7 @@ fn test_access_expression_metadata() {
         .expect("compilation should succeed");
 
     // Access metadata
+    #[allow(deprecated)]
     let params = expr.params();
     assert_eq!(params.len(), 3);
     assert_eq!(params[0].0, "x");
@@ -404,3 +670,943 @@ fn test_access_expression_metadata() {
     let return_type = expr.return_type();
     assert!(core::ptr::eq(return_type, int_ty));
 }
+
+#[test]
+fn test_return_type_before_running() {
+    let arena = Bump::new();
+    let engine = Engine::new(EngineOptions::default(), &arena, |_, _, _| {});
+    let type_mgr = engine.type_manager();
+
+    let expr = engine.compile(Default::default(), "1 + 2", &[]).unwrap();
+    assert_eq!(expr.return_type(), type_mgr.int());
+
+    let expr = engine
+        .compile(
+            Default::default(),
+            "{ name = \"melbi\", tags = [1, 2] }",
+            &[],
+        )
+        .unwrap();
+    let expected = type_mgr.record(vec![
+        ("name", type_mgr.str()),
+        ("tags", type_mgr.array(type_mgr.int())),
+    ]);
+    assert_eq!(expr.return_type(), expected);
+}
+
+#[test]
+fn test_parameters_with_record_type() {
+    let arena = Bump::new();
+    let engine = Engine::new(EngineOptions::default(), &arena, |_, _, _| {});
+    let type_mgr = engine.type_manager();
+    let event_ty = type_mgr.record(vec![("kind", type_mgr.str()), ("score", type_mgr.int())]);
+
+    let expr = engine
+        .compile(
+            Default::default(),
+            "event.score > limit",
+            &[("event", event_ty), ("limit", type_mgr.int())],
+        )
+        .unwrap();
+
+    let params = expr.parameters();
+    assert_eq!(params.len(), 2);
+    assert_eq!(params[0].0, "event");
+    assert!(core::ptr::eq(params[0].1, event_ty));
+    assert_eq!(params[0].1.to_string(), "Record[kind: Str, score: Int]");
+    assert_eq!(params[1].0, "limit");
+    assert!(core::ptr::eq(params[1].1, type_mgr.int()));
+}
+
+#[test]
+fn test_pretty_signature() {
+    let arena = Bump::new();
+    let engine = Engine::new(EngineOptions::default(), &arena, |_, _, _| {});
+    let type_mgr = engine.type_manager();
+    let event_ty = type_mgr.record(vec![("to", type_mgr.str())]);
+
+    let expr = engine
+        .compile(
+            Default::default(),
+            "event.to == \"admin\"",
+            &[("event", event_ty)],
+        )
+        .unwrap();
+    assert_eq!(expr.pretty_signature(), "(event: Record[to: Str]) => Bool");
+
+    // Type variables are renumbered in order of appearance.
+    let expr = engine
+        .compile(Default::default(), "(x) => [x]", &[])
+        .unwrap();
+    assert_eq!(expr.pretty_signature(), "() => (_0) => Array[_0]");
+}
+
+#[test]
+fn test_estimated_cost() {
+    let arena = Bump::new();
+    let engine = Engine::new(EngineOptions::default(), &arena, |arena, type_mgr, env| {
+        let math = melbi_core::stdlib::build_math_package(arena, type_mgr).unwrap();
+        env.register("Math", math).unwrap();
+    });
+
+    let constant = engine
+        .compile(Default::default(), "42", &[])
+        .expect("compilation should succeed");
+    let calls = engine
+        .compile(
+            Default::default(),
+            "Math.Sqrt(Math.Abs(Math.Sin(1.0)) + Math.Pow(Math.Cos(2.0), 2.0))",
+            &[],
+        )
+        .expect("compilation should succeed");
+    let single_call = engine
+        .compile(Default::default(), "Math.Sqrt(2.0)", &[])
+        .expect("compilation should succeed");
+    let lambda = engine
+        .compile(Default::default(), "(x) => Math.Sqrt(x)", &[])
+        .expect("compilation should succeed");
+
+    assert!(constant.estimated_cost() > 0);
+    assert!(calls.estimated_cost() > single_call.estimated_cost());
+    assert!(single_call.estimated_cost() > constant.estimated_cost());
+    // Lambda bodies are weighted as if run multiple times.
+    assert!(lambda.estimated_cost() > single_call.estimated_cost());
+}
+
+#[test]
+fn test_compilation_stats() {
+    let arena = Bump::new();
+    let engine = Engine::new(EngineOptions::default(), &arena, |arena, type_mgr, env| {
+        let math = melbi_core::stdlib::build_math_package(arena, type_mgr).unwrap();
+        env.register("Math", math).unwrap();
+    });
+    let float_ty = engine.type_manager().float();
+
+    let expr = engine
+        .compile(
+            Default::default(),
+            "Math.Sqrt(x) + y * 2.0 where { y = ((v) => v + 1.0)(x) }",
+            &[("x", float_ty)],
+        )
+        .expect("compilation should succeed");
+
+    // Top-level code (constants: Math, 2.0):
+    //   LoadLocal(0) MakeClosure(0) Call(0) StoreLocal(1)
+    //   LoadLocal(0) ConstLoad(0) RecordGet(Sqrt) Call(1)
+    //   LoadLocal(1) ConstLoad(1) FloatBinOp(*) FloatBinOp(+) Return
+    // Lambda (constants: 1.0):
+    //   LoadLocal(0) ConstLoad(0) FloatBinOp(+) Return
+    let stats = expr.compilation_stats().expect("stats should be available");
+    assert_eq!(
+        stats,
+        CompilationStats {
+            instruction_count: 17,
+            num_locals: 2,
+            constant_pool_size: 3,
+            max_stack_depth: 3,
+            call_sites: 2,
+        }
+    );
+}
+
+#[test]
+fn test_is_deterministic() {
+    fn now<'types, 'arena>(
+        ctx: &FfiContext<'types, 'arena>,
+        _args: &[Value<'types, 'arena>],
+    ) -> Result<Value<'types, 'arena>, ExecutionError> {
+        Ok(Value::int(ctx.type_mgr(), 1_700_000_000))
+    }
+
+    fn double<'types, 'arena>(
+        ctx: &FfiContext<'types, 'arena>,
+        args: &[Value<'types, 'arena>],
+    ) -> Result<Value<'types, 'arena>, ExecutionError> {
+        let n = args[0].as_int().expect("argument should be int");
+        Ok(Value::int(ctx.type_mgr(), n * 2))
+    }
+
+    let arena = Bump::new();
+    let engine = Engine::new(EngineOptions::default(), &arena, |arena, type_mgr, env| {
+        let math = melbi_core::stdlib::build_math_package(arena, type_mgr).unwrap();
+        env.register("Math", math).unwrap();
+        let array = melbi_core::stdlib::build_array_package(arena, type_mgr).unwrap();
+        env.register("Array", array).unwrap();
+
+        // Host callbacks are non-deterministic unless declared otherwise
+        let int_ty = type_mgr.int();
+        let now_fn = NativeFunction::new(type_mgr.function(&[], int_ty), now);
+        let time = Value::record_builder(type_mgr)
+            .field("Now", Value::function(arena, now_fn).unwrap())
+            .build(arena)
+            .unwrap();
+        env.register("Time", time).unwrap();
+
+        let double_fn =
+            NativeFunction::new(type_mgr.function(&[int_ty], int_ty), double).deterministic();
+        env.register("double", Value::function(arena, double_fn).unwrap())
+            .unwrap();
+    });
+    let int_ty = engine.type_manager().int();
+    let callback_ty = engine.type_manager().function(&[int_ty], int_ty);
+
+    let is_deterministic = |source: &'static str, params: &[(&'static str, _)]| {
+        engine
+            .compile(Default::default(), source, params)
+            .expect("compilation should succeed")
+            .is_deterministic()
+    };
+
+    let float_ty = engine.type_manager().float();
+    assert!(is_deterministic(
+        "Math.Sqrt(x) + Math.PI",
+        &[("x", float_ty)]
+    ));
+    assert!(is_deterministic("Array.Map([1, 2], (x) => double(x))", &[]));
+    assert!(!is_deterministic("Time.Now() + 1", &[]));
+    assert!(!is_deterministic("Math.Random()", &[]));
+    // Reached through a lambda or a local binding
+    assert!(!is_deterministic(
+        "Array.Map([1], (x) => Math.Random())",
+        &[]
+    ));
+    assert!(!is_deterministic("f() where { f = Time.Now }", &[]));
+    // Passing a whole package along may reach any of its functions
+    assert!(!is_deterministic("[Time][0].Now()", &[]));
+    // Functions passed as parameters are host callbacks
+    assert!(!is_deterministic(
+        "callback(1)",
+        &[("callback", callback_ty)]
+    ));
+}
+
+#[test]
+fn test_compiled_artifact_runs_on_another_thread() {
+    fn init<'a>(
+        _arena: &'a Bump,
+        type_mgr: &'a melbi_core::types::manager::TypeManager<'a>,
+        env: &mut melbi_core::api::EnvironmentBuilder<'a>,
+    ) {
+        env.register("offset", Value::int(type_mgr, 40)).unwrap();
+    }
+
+    let artifact = {
+        let arena = Bump::new();
+        let engine = Engine::new(EngineOptions::default(), &arena, init);
+        let int_ty = engine.type_manager().int();
+        engine
+            .compile(Default::default(), "offset + x", &[("x", int_ty)])
+            .expect("compilation should succeed")
+            .to_artifact()
+            .expect("artifact should be created")
+    };
+    assert_eq!(artifact.global_names().collect::<Vec<_>>(), ["offset"]);
+
+    let result = std::thread::spawn(move || {
+        let arena = Bump::new();
+        let engine = Engine::new(EngineOptions::default(), &arena, init);
+        let expr = artifact.bind(&engine).expect("binding should succeed");
+
+        let val_arena = Bump::new();
+        let x = Value::int(engine.type_manager(), 2);
+        expr.run(Default::default(), &val_arena, &[x])
+            .expect("execution should succeed")
+            .as_int()
+            .unwrap()
+    })
+    .join()
+    .unwrap();
+
+    assert_eq!(result, 42);
+}
+
+#[test]
+fn test_compiled_artifact_rejects_incompatible_environment() {
+    let arena = Bump::new();
+    let engine = Engine::new(EngineOptions::default(), &arena, |_arena, type_mgr, env| {
+        env.register("offset", Value::int(type_mgr, 40)).unwrap();
+    });
+    let artifact = engine
+        .compile(Default::default(), "offset + 1", &[])
+        .unwrap()
+        .to_artifact()
+        .unwrap();
+
+    let empty_arena = Bump::new();
+    let empty = Engine::new(EngineOptions::default(), &empty_arena, |_, _, _| {});
+    assert!(artifact.bind(&empty).is_err());
+
+    let float_arena = Bump::new();
+    let float_engine = Engine::new(
+        EngineOptions::default(),
+        &float_arena,
+        |_arena, type_mgr, env| {
+            env.register("offset", Value::float(type_mgr, 40.0)).unwrap();
+        },
+    );
+    assert!(artifact.bind(&float_engine).is_err());
+}
+
+#[test]
+fn test_compiled_artifact_binds_globals_by_name() {
+    let arena = Bump::new();
+    let engine = Engine::new(EngineOptions::default(), &arena, |_arena, type_mgr, env| {
+        env.register("offset", Value::int(type_mgr, 40)).unwrap();
+    });
+    let int_ty = engine.type_manager().int();
+    let artifact = engine
+        .compile(
+            Default::default(),
+            "@priority(3) offset + x",
+            &[("x", int_ty)],
+        )
+        .unwrap()
+        .to_artifact()
+        .unwrap();
+
+    // The code takes the global from the target engine's environment
+    // instead of holding the value it was compiled against.
+    let other_arena = Bump::new();
+    let other = Engine::new(
+        EngineOptions::default(),
+        &other_arena,
+        |_arena, type_mgr, env| {
+            env.register("offset", Value::int(type_mgr, 100)).unwrap();
+        },
+    );
+    let expr = artifact.bind(&other).unwrap();
+    assert_eq!(expr.attribute("priority"), Some(&AttributeValue::Int(3)));
+    assert_eq!(expr.source(), artifact.source());
+    // One local for the parameter and one for the global.
+    assert_eq!(expr.compilation_stats().unwrap().num_locals, 2);
+
+    let val_arena = Bump::new();
+    let x = Value::int(other.type_manager(), 1);
+    let result = expr.run(Default::default(), &val_arena, &[x]).unwrap();
+    assert_eq!(result.as_int().unwrap(), 101);
+}
+
+#[test]
+fn test_compiled_artifact_keeps_its_compile_options() {
+    let arena = Bump::new();
+    let engine = Engine::new(EngineOptions::default(), &arena, |_, _, _| {});
+    let options = CompileOptionsOverride {
+        float_division: Some(true),
+        ..Default::default()
+    };
+    let artifact = engine
+        .compile(options, "7 / 2", &[])
+        .unwrap()
+        .to_artifact()
+        .unwrap();
+
+    // The target engine defaults to integer division; binding must not
+    // fall back to it.
+    let other_arena = Bump::new();
+    let other = Engine::new(EngineOptions::default(), &other_arena, |_, _, _| {});
+    let expr = artifact.bind(&other).unwrap();
+    assert!(expr.compile_options().float_division);
+
+    let val_arena = Bump::new();
+    let result = expr.run(Default::default(), &val_arena, &[]).unwrap();
+    assert_eq!(result.as_float().unwrap(), 3.5);
+}
+
+#[test]
+fn test_compile_module_with_cross_references() {
+    let arena = Bump::new();
+    let engine = Engine::new(EngineOptions::default(), &arena, |_arena, type_mgr, env| {
+        env.register("rate", Value::int(type_mgr, 3)).unwrap();
+    });
+    let int_ty = engine.type_manager().int();
+
+    // `total` is listed before `subtotal`, which it depends on.
+    let rules = engine
+        .compile_module(
+            Default::default(),
+            &[
+                ("total", "subtotal + 1 // plus fee"),
+                ("subtotal", "price * rate"),
+            ],
+            &[("price", int_ty)],
+        )
+        .unwrap();
+    assert_eq!(rules.len(), 2);
+
+    let val_arena = Bump::new();
+    let price = Value::int(engine.type_manager(), 10);
+    let subtotal = rules["subtotal"]
+        .run(Default::default(), &val_arena, &[price])
+        .unwrap();
+    assert_eq!(subtotal.as_int().unwrap(), 30);
+    let total = rules["total"]
+        .run(Default::default(), &val_arena, &[price])
+        .unwrap();
+    assert_eq!(total.as_int().unwrap(), 31);
+}
+
+#[test]
+fn test_compile_module_rules_share_max_steps() {
+    use melbi_core::api::RunOptionsOverride;
+
+    let arena = Bump::new();
+    let engine = Engine::new(EngineOptions::default(), &arena, |arena, type_mgr, env| {
+        let iterate = melbi_core::stdlib::build_iterate(arena, type_mgr).unwrap();
+        env.register("Iterate", iterate).unwrap();
+    });
+
+    // Each rule takes about 600 steps on its own
+    let rules = engine
+        .compile_module(
+            Default::default(),
+            &[
+                ("a", "Iterate([0], 100, (xs) => [xs[0] + 1])"),
+                ("b", "Iterate(a, 100, (xs) => [xs[0] + 1])"),
+                ("c", "Iterate(b, 100, (xs) => [xs[0] + 1])"),
+                ("d", "Iterate(c, 100, (xs) => [xs[0] + 1])"),
+            ],
+            &[],
+        )
+        .unwrap();
+    let bind = |rule: &str| rules[rule].to_artifact().unwrap().bind(&engine).unwrap();
+    let limit = RunOptionsOverride {
+        max_steps: Some(Some(1100)),
+        ..Default::default()
+    };
+
+    let val_arena = Bump::new();
+    for first in [&rules["a"], &bind("a")] {
+        assert!(first.run(limit, &val_arena, &[]).is_ok());
+    }
+    // The steps of the rules `d` depends on count against its limit
+    for last in [&rules["d"], &bind("d")] {
+        let result = last.run(limit, &val_arena, &[]);
+        assert!(matches!(result, Err(Error::ResourceExceeded { .. })));
+    }
+}
+
+#[test]
+fn test_attributes_on_compiled_expression() {
+    let arena = Bump::new();
+    let engine = Engine::new(EngineOptions::default(), &arena, |_, _, _| {});
+    let int_ty = engine.type_manager().int();
+
+    let expr = engine
+        .compile(
+            Default::default(),
+            "@priority(10) @name(\"spam-filter\")\nscore * 2",
+            &[("score", int_ty)],
+        )
+        .unwrap();
+    assert_eq!(expr.attributes().len(), 2);
+    assert_eq!(expr.attribute("priority"), Some(&AttributeValue::Int(10)));
+    assert_eq!(
+        expr.attribute("name"),
+        Some(&AttributeValue::Str("spam-filter"))
+    );
+    assert_eq!(expr.attribute("missing"), None);
+
+    // Attributes do not affect evaluation.
+    let val_arena = Bump::new();
+    let score = Value::int(engine.type_manager(), 21);
+    let result = expr.run(Default::default(), &val_arena, &[score]).unwrap();
+    assert_eq!(result.as_int().unwrap(), 42);
+
+    // In a module, each rule keeps its own attributes only.
+    let rules = engine
+        .compile_module(
+            Default::default(),
+            &[
+                ("total", "@priority(2) base + 1"),
+                ("base", "@priority(1) 41"),
+            ],
+            &[],
+        )
+        .unwrap();
+    assert_eq!(
+        rules["total"].attribute("priority"),
+        Some(&AttributeValue::Int(2))
+    );
+    assert_eq!(
+        rules["base"].attribute("priority"),
+        Some(&AttributeValue::Int(1))
+    );
+    let result = rules["total"]
+        .run(Default::default(), &val_arena, &[])
+        .unwrap();
+    assert_eq!(result.as_int().unwrap(), 42);
+}
+
+#[test]
+fn test_compile_module_with_type_aliases() {
+    let arena = Bump::new();
+    let engine = Engine::new(EngineOptions::default(), &arena, |_, _, _| {});
+
+    // Aliases are local to the rule declaring them, even when other rules
+    // reference it or declare an alias of the same name.
+    let rules = engine
+        .compile_module(
+            Default::default(),
+            &[
+                (
+                    "origin",
+                    "@priority(1) type Point = Record[x: Int, y: Int]; { x = 0, y = 0 } as Point",
+                ),
+                ("shifted", "type Point = Int; origin.x + 1 as Point"),
+                ("answer", "shifted + origin.y + 41"),
+            ],
+            &[],
+        )
+        .unwrap();
+    assert_eq!(
+        rules["origin"].attribute("priority"),
+        Some(&AttributeValue::Int(1))
+    );
+
+    let val_arena = Bump::new();
+    let result = rules["answer"]
+        .run(Default::default(), &val_arena, &[])
+        .unwrap();
+    assert_eq!(result.as_int().unwrap(), 42);
+}
+
+#[test]
+fn test_compile_module_errors() {
+    let arena = Bump::new();
+    let engine = Engine::new(EngineOptions::default(), &arena, |_, _, _| {});
+
+    let cycle = engine.compile_module(Default::default(), &[("a", "b + 1"), ("b", "a + 1")], &[]);
+    assert!(matches!(cycle, Err(Error::Api(message)) if message.contains("a -> b -> a")));
+
+    let duplicate = engine.compile_module(Default::default(), &[("a", "1"), ("a", "2")], &[]);
+    assert!(matches!(duplicate, Err(Error::Api(_))));
+
+    let invalid = engine.compile_module(Default::default(), &[("a", "1"), ("b", "a + true")], &[]);
+    assert!(matches!(invalid, Err(Error::Compilation { .. })));
+
+    let int_ty = engine.type_manager().int();
+    let shadowing = engine.compile_module(Default::default(), &[("x", "1")], &[("x", int_ty)]);
+    assert!(matches!(shadowing, Err(Error::Api(message)) if message.contains("parameter")));
+}
+
+#[test]
+fn test_compile_module_errors_point_into_each_rule() {
+    let arena = Bump::new();
+    let engine = Engine::new(EngineOptions::default(), &arena, |_, _, _| {});
+
+    // Type errors are reported against the rule's own source.
+    let result = engine.compile_module(
+        Default::default(),
+        &[("base", "@priority(1) 40"), ("bad", "base + true")],
+        &[],
+    );
+    match result {
+        Err(Error::Compilation {
+            diagnostics,
+            source,
+        }) => {
+            assert_eq!(source, "base + true");
+            assert!(diagnostics.iter().all(|d| d.span.0.end <= source.len()));
+        }
+        other => panic!("Expected compilation error, got {:?}", other.map(|_| ())),
+    }
+
+    // So are runtime errors in a rule another one depends on.
+    let rules = engine
+        .compile_module(
+            Default::default(),
+            &[("half", "x / 0"), ("answer", "half + 1")],
+            &[("x", engine.type_manager().int())],
+        )
+        .unwrap();
+    assert_eq!(rules["answer"].source(), "half + 1");
+    let val_arena = Bump::new();
+    let x = Value::int(engine.type_manager(), 84);
+    match rules["answer"].run(Default::default(), &val_arena, &[x]) {
+        Err(Error::Runtime { source, .. }) => assert_eq!(source, "x / 0"),
+        other => panic!("Expected runtime error, got {:?}", other.map(|_| ())),
+    }
+}
+
+#[test]
+fn test_compile_module_with_polymorphic_rules() {
+    let arena = Bump::new();
+    let engine = Engine::new(EngineOptions::default(), &arena, |_, _, _| {});
+    let val_arena = Bump::new();
+
+    // A polymorphic rule can be used at several types, like a where binding.
+    let rules = engine
+        .compile_module(
+            Default::default(),
+            &[
+                ("id", "(x) => x"),
+                ("add", "(a, b) => a + b"),
+                (
+                    "answer",
+                    r#"{ n = id(40) + add(1, 1), s = id("a"), f = add(0.5, 1.0) }"#,
+                ),
+            ],
+            &[],
+        )
+        .unwrap();
+    let result = rules["answer"]
+        .run(Default::default(), &val_arena, &[])
+        .unwrap();
+    let record = result.as_record().unwrap();
+    assert_eq!(record.get("n").unwrap().as_int().unwrap(), 42);
+    assert_eq!(record.get("s").unwrap().as_str().unwrap(), "a");
+    assert_eq!(record.get("f").unwrap().as_float().unwrap(), 1.5);
+
+    // Each use is still checked against the rule's constraints, and the
+    // error points at the rule's name where it is used.
+    let result = engine.compile_module(
+        Default::default(),
+        &[("add", "(a, b) => a + b"), ("bad", r#"add("a", "b")"#)],
+        &[],
+    );
+    match result {
+        Err(Error::Compilation {
+            diagnostics,
+            source,
+        }) => {
+            assert_eq!(source, r#"add("a", "b")"#);
+            assert_eq!(diagnostics[0].span.0, 0..3);
+        }
+        other => panic!("Expected compilation error, got {:?}", other.map(|_| ())),
+    }
+}
+
+#[test]
+fn test_compile_module_rules_to_artifacts() {
+    let arena = Bump::new();
+    let engine = Engine::new(EngineOptions::default(), &arena, |_arena, type_mgr, env| {
+        env.register("offset", Value::int(type_mgr, 2)).unwrap();
+    });
+    let int_ty = engine.type_manager().int();
+    let rules = engine
+        .compile_module(
+            Default::default(),
+            &[
+                ("answer", "left + right"),
+                ("left", "base + offset"),
+                ("right", "base - offset"),
+                ("base", "x * 2"),
+            ],
+            &[("x", int_ty)],
+        )
+        .unwrap();
+    let artifact = rules["answer"].to_artifact().unwrap();
+    assert_eq!(artifact.global_names().collect::<Vec<_>>(), ["offset"]);
+
+    let other_arena = Bump::new();
+    let other = Engine::new(EngineOptions::default(), &other_arena, |_arena, type_mgr, env| {
+        env.register("offset", Value::int(type_mgr, 2)).unwrap();
+    });
+    let expr = artifact.bind(&other).unwrap();
+    let val_arena = Bump::new();
+    let x = Value::int(other.type_manager(), 21);
+    let result = expr.run(Default::default(), &val_arena, &[x]).unwrap();
+    assert_eq!(result.as_int().unwrap(), 84);
+}
+
+#[test]
+fn test_compile_streams_each_diagnostic() {
+    let arena = Bump::new();
+    let engine = Engine::new(EngineOptions::default(), &arena, |_, _, _| {});
+    let source = "{ a = true + false, b = x * x, c = b\"a\" - b\"b\" } where { x = {} }";
+
+    let mut streamed = Vec::new();
+    let result = engine.compile_with_diagnostics(Default::default(), source, &[], |diagnostic| {
+        streamed.push(diagnostic.span.clone());
+    });
+
+    // The callback sees every error, in source order, as it is found
+    assert_eq!(streamed.len(), 3);
+    assert!(
+        streamed
+            .windows(2)
+            .all(|pair| pair[0].0.start < pair[1].0.start)
+    );
+    match result {
+        Err(Error::Compilation { diagnostics, .. }) => {
+            let spans: Vec<_> = diagnostics.iter().map(|d| d.span.clone()).collect();
+            assert_eq!(spans, streamed);
+        }
+        other => panic!("Expected compilation error, got {:?}", other.map(|_| ())),
+    }
+
+    // Successful compilations report nothing
+    let mut count = 0;
+    let result = engine.compile_with_diagnostics(Default::default(), "1 + 2", &[], |_| count += 1);
+    assert!(result.is_ok());
+    assert_eq!(count, 0);
+}
+
+#[test]
+fn test_compile_collecting_diagnostics() {
+    let arena = Bump::new();
+    let engine = Engine::new(EngineOptions::default(), &arena, |_, _, _| {});
+
+    // Errors produce no expression, but every diagnostic
+    let source = "{ a = true + false, b = b\"a\" - b\"b\", c = 3 }";
+    let (expr, analysis, diagnostics) =
+        engine.compile_collecting_diagnostics(Default::default(), source, &[]);
+    assert!(expr.is_none());
+    assert_eq!(diagnostics.len(), 2);
+    assert!(
+        diagnostics
+            .iter()
+            .all(|diagnostic| diagnostic.severity == Severity::Error)
+    );
+
+    // The sub-expressions around both errors are still typed
+    let analysis = analysis.expect("the source parses");
+    assert!(analysis.result.is_err());
+    let type_at = |text: &str| analysis.type_at(source.find(text).unwrap()).unwrap();
+    assert_eq!(type_at("true").to_string(), "Bool");
+    assert_eq!(type_at("b\"a\"").to_string(), "Bytes");
+    assert_eq!(type_at("3").to_string(), "Int");
+
+    // Syntax errors are reported the same way, with nothing typed
+    let (expr, analysis, diagnostics) =
+        engine.compile_collecting_diagnostics(Default::default(), "1 +", &[]);
+    assert!(expr.is_none());
+    assert!(analysis.is_none());
+    assert_eq!(diagnostics.len(), 1);
+
+    // Valid sources produce the expression and no diagnostics
+    let (expr, analysis, diagnostics) =
+        engine.compile_collecting_diagnostics(Default::default(), "1 + 2", &[]);
+    assert!(analysis.is_none());
+    assert!(diagnostics.is_empty());
+    let value_arena = Bump::new();
+    let value = expr.unwrap().run(Default::default(), &value_arena, &[]);
+    assert_eq!(value.unwrap().as_int().unwrap(), 3);
+
+    // Warnings come along with a successfully compiled expression
+    let options = CompileOptionsOverride {
+        warn_unused_parameters: Some(true),
+        ..Default::default()
+    };
+    let (expr, _analysis, diagnostics) =
+        engine.compile_collecting_diagnostics(options, "((x, y) => x)(1, 2)", &[]);
+    assert!(expr.is_some());
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(diagnostics[0].severity, Severity::Warning);
+}
+
+#[test]
+fn test_duplicate_map_keys_policy() {
+    let arena = Bump::new();
+    let engine = Engine::new(EngineOptions::default(), &arena, |_, _, _| {});
+    let val_arena = Bump::new();
+    let strict = CompileOptionsOverride {
+        duplicate_map_keys: Some(DuplicateKeyPolicy::Error),
+        ..Default::default()
+    };
+
+    // By default the last entry wins.
+    let expr = engine
+        .compile(Default::default(), "{1: 10, 1: 20}[1]", &[])
+        .unwrap();
+    let result = expr.run(Default::default(), &val_arena, &[]).unwrap();
+    assert_eq!(result.as_int().unwrap(), 20);
+
+    // Repeated literal keys are rejected at compile time.
+    let literal = engine.compile(strict, "{1: 10, 1: 20}", &[]);
+    assert!(matches!(literal, Err(Error::Compilation { .. })));
+
+    // Colliding computed keys fail at runtime.
+    let expr = engine
+        .compile(strict, "{a: 10, b: 20} where { a = 1, b = 1 }", &[])
+        .unwrap();
+    let result = expr.run(Default::default(), &val_arena, &[]);
+    match result {
+        Err(Error::Runtime { diagnostic, .. }) => {
+            assert_eq!(diagnostic.code.as_deref(), Some("R011"));
+        }
+        other => panic!("Expected runtime error, got {:?}", other.map(|_| ())),
+    }
+
+    // Maps built inside lambdas follow the same policy.
+    let expr = engine
+        .compile(strict, "((k) => {k: 10, 1: 20})(1) otherwise {}", &[])
+        .unwrap();
+    let result = expr.run(Default::default(), &val_arena, &[]).unwrap();
+    assert!(result.as_map().unwrap().is_empty());
+}
+
+#[test]
+fn test_argument_coercion_policy() {
+    let arena = Bump::new();
+    let engine = Engine::new(EngineOptions::default(), &arena, |arena, type_mgr, env| {
+        fn half<'types, 'arena>(
+            ctx: &FfiContext<'types, 'arena>,
+            args: &[Value<'types, 'arena>],
+        ) -> Result<Value<'types, 'arena>, ExecutionError> {
+            let x = args[0].as_float().expect("argument should be float");
+            Ok(Value::float(ctx.type_mgr(), x / 2.0))
+        }
+
+        let float_ty = type_mgr.float();
+        let half_ty = type_mgr.function(&[float_ty], float_ty);
+        let half_fn = NativeFunction::new(half_ty, half);
+        env.register("half", Value::function(arena, half_fn).unwrap())
+            .unwrap();
+    });
+    let val_arena = Bump::new();
+    let lenient = CompileOptionsOverride {
+        argument_coercion: Some(ArgumentCoercion::Lenient),
+        ..Default::default()
+    };
+
+    // By default an Int argument for a Float parameter is a type error.
+    let strict = engine.compile(Default::default(), "half(5)", &[]);
+    assert!(matches!(strict, Err(Error::Compilation { .. })));
+
+    // Under the lenient policy it is converted before the call.
+    let expr = engine.compile(lenient, "half(5)", &[]).unwrap();
+    let result = expr.run(Default::default(), &val_arena, &[]).unwrap();
+    assert_eq!(result.as_float().unwrap(), 2.5);
+
+    // Other mismatches are still rejected.
+    let mismatch = engine.compile(lenient, "half(\"5\")", &[]);
+    assert!(matches!(mismatch, Err(Error::Compilation { .. })));
+}
+
+#[test]
+fn test_language_version() {
+    let arena = Bump::new();
+    let engine = Engine::new(EngineOptions::default(), &arena, |_, _, _| {});
+    let val_arena = Bump::new();
+    let v1 = CompileOptionsOverride {
+        language_version: Some(LanguageVersion::V1),
+        ..Default::default()
+    };
+    let source = "21 |> double where { double = (x) => x * 2 }";
+
+    // The pipe operator is accepted by the current version.
+    let expr = engine.compile(Default::default(), source, &[]).unwrap();
+    let result = expr.run(Default::default(), &val_arena, &[]).unwrap();
+    assert_eq!(result.as_int().unwrap(), 42);
+
+    // Version 1 predates it.
+    match engine.compile(v1, source, &[]) {
+        Err(Error::Compilation { diagnostics, .. }) => {
+            assert_eq!(diagnostics.len(), 1);
+            assert!(
+                diagnostics[0]
+                    .message
+                    .contains("requires language version 2"),
+                "Unexpected message: {}",
+                diagnostics[0].message
+            );
+            assert_eq!(diagnostics[0].span.0, 3..5);
+        }
+        other => panic!("Expected compilation error, got {:?}", other.map(|_| ())),
+    }
+
+    // Syntax available in version 1 still compiles.
+    let expr = engine
+        .compile(v1, "double(21) where { double = (x) => x * 2 }", &[])
+        .unwrap();
+    let result = expr.run(Default::default(), &val_arena, &[]).unwrap();
+    assert_eq!(result.as_int().unwrap(), 42);
+}
+
+#[test]
+fn test_float_division_of_lambda_operands() {
+    let arena = Bump::new();
+    let engine = Engine::new(EngineOptions::default(), &arena, |_, _, _| {});
+    let val_arena = Bump::new();
+    let options = CompileOptionsOverride {
+        float_division: Some(true),
+        ..Default::default()
+    };
+
+    for source in [
+        "((a, b) => a / b)(5, 2)",
+        "((a) => a / 2)(5)",
+        "half(5) where { half = (a) => a / 2 }",
+    ] {
+        let expr = engine.compile(options, source, &[]).unwrap();
+        let result = expr.run(Default::default(), &val_arena, &[]).unwrap();
+        assert_eq!(result.as_float().unwrap(), 2.5, "{}", source);
+    }
+}
+
+#[test]
+fn test_mixed_numeric_comparison_of_lambda_params() {
+    let arena = Bump::new();
+    let engine = Engine::new(EngineOptions::default(), &arena, |_, _, _| {});
+    let val_arena = Bump::new();
+    let options = CompileOptionsOverride {
+        mixed_numeric_comparison: Some(true),
+        ..Default::default()
+    };
+
+    for (source, expected) in [
+        ("((a) => a < 2.5)(2)", true),
+        ("((a, b) => a < b)(2, 2.5)", true),
+        ("lt(3, 2.5) where { lt = (a, b) => a < b }", false),
+    ] {
+        let expr = engine.compile(options, source, &[]).unwrap();
+        let result = expr.run(Default::default(), &val_arena, &[]).unwrap();
+        assert_eq!(result.as_bool().unwrap(), expected, "{}", source);
+    }
+}
+
+#[test]
+fn test_integer_division_rounding() {
+    let arena = Bump::new();
+    let engine = Engine::new(EngineOptions::default(), &arena, |_, _, _| {});
+    let val_arena = Bump::new();
+    let int = engine.type_manager().int();
+    let params = [("a", int), ("b", int)];
+    let args = [
+        Value::int(engine.type_manager(), -7),
+        Value::int(engine.type_manager(), 2),
+    ];
+    let divide = |integer_division| {
+        let options = CompileOptionsOverride {
+            integer_division: Some(integer_division),
+            ..Default::default()
+        };
+        let expr = engine.compile(options, "[a / b, a % b]", &params).unwrap();
+        let result = expr.run(Default::default(), &val_arena, &args).unwrap();
+        let array = result.as_array().unwrap();
+        (
+            array.get(0).unwrap().as_int().unwrap(),
+            array.get(1).unwrap().as_int().unwrap(),
+        )
+    };
+
+    assert_eq!(divide(IntegerDivision::Euclidean), (-4, 1));
+    assert_eq!(divide(IntegerDivision::Truncated), (-3, -1));
+    assert_eq!(divide(IntegerDivision::Floored), (-4, 1));
+}
+
+#[test]
+fn test_operator_precedence_override() {
+    let arena = Bump::new();
+    let engine = Engine::new(EngineOptions::default(), &arena, |_, _, _| {});
+    let val_arena = Bump::new();
+    let additive_first = CompileOptionsOverride {
+        operator_precedence: Some(
+            OperatorPrecedence::DEFAULT
+                .with_level(InfixOperator::Add, 135)
+                .unwrap(),
+        ),
+        ..Default::default()
+    };
+
+    let expr = engine
+        .compile(Default::default(), "1 + 2 * 3", &[])
+        .unwrap();
+    let result = expr.run(Default::default(), &val_arena, &[]).unwrap();
+    assert_eq!(result.as_int().unwrap(), 7);
+
+    let expr = engine.compile(additive_first, "1 + 2 * 3", &[]).unwrap();
+    let result = expr.run(Default::default(), &val_arena, &[]).unwrap();
+    assert_eq!(result.as_int().unwrap(), 9);
+}
@@ -3,6 +3,8 @@ use std::string::FromUtf8Error;
 use thiserror::Error;
 use topiary_core::{FormatterError, Operation, TopiaryQuery};
 
+mod record_fields;
+
 #[derive(Error, Debug, Diagnostic)]
 #[error("format error")]
 pub enum FormatError {
@@ -31,6 +33,20 @@ pub enum FormatError {
 
 const QUERY: &str = include_str!("../../topiary-queries/queries/melbi.scm");
 
+/// Options for [`format_with_options`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct FormatOptions {
+    /// Skip the check that the AST of the formatted source is identical to the input. This is
+    /// intended for working around current formatter limitations.
+    pub skip_idempotence: bool,
+
+    /// Accept source code with syntax errors instead of rejecting it.
+    pub tolerate_parsing_errors: bool,
+
+    /// Reorder record fields by name. By default fields keep the order they were written in.
+    pub sort_record_fields: bool,
+}
+
 /// Format Melbi source code.
 ///
 /// # Arguments
@@ -56,6 +72,43 @@ pub fn format(
     skip_idempotence: bool,
     tolerate_parsing_errors: bool,
 ) -> Result<String> {
+    format_with_options(
+        input,
+        &FormatOptions {
+            skip_idempotence,
+            tolerate_parsing_errors,
+            ..Default::default()
+        },
+    )
+}
+
+/// Format Melbi source code with the given options.
+///
+/// # Examples
+///
+/// ```
+/// # use melbi_fmt::{FormatOptions, format_with_options};
+/// let source = "{b = 1, a = 2}";
+/// assert_eq!(
+///     format_with_options(source, &FormatOptions::default()).unwrap(),
+///     "{ b = 1, a = 2 }"
+/// );
+///
+/// let options = FormatOptions {
+///     sort_record_fields: true,
+///     ..Default::default()
+/// };
+/// assert_eq!(
+///     format_with_options(source, &options).unwrap(),
+///     "{ a = 2, b = 1 }"
+/// );
+/// ```
+pub fn format_with_options(input: &str, options: &FormatOptions) -> Result<String> {
+    let FormatOptions {
+        skip_idempotence,
+        tolerate_parsing_errors,
+        sort_record_fields,
+    } = *options;
     let mut output = Vec::new();
 
     let grammar = topiary_tree_sitter_facade::Language::from(tree_sitter_melbi::LANGUAGE);
@@ -123,7 +176,10 @@ pub fn format(
         })?;
     }
 
-    let output = String::from_utf8(output).map_err(FormatError::UTF8)?;
+    let mut output = String::from_utf8(output).map_err(FormatError::UTF8)?;
+    if sort_record_fields {
+        output = record_fields::sort_record_fields(&output);
+    }
 
     // Final cleanup of result. If we received an input not ending in a newline, also return an
     // output without newline. We do not want to force a newline since we e.g., could be formatting
@@ -1,5 +1,5 @@
 use clap::Parser;
-use melbi_fmt::format;
+use melbi_fmt::{FormatOptions, format_with_options};
 use miette::{Context, Diagnostic, Result, ensure};
 use rayon::iter::{IntoParallelRefIterator, ParallelIterator};
 use std::{io::Read, path::PathBuf};
@@ -22,6 +22,9 @@ struct Args {
 
     #[clap(long, short, help = "format file in place")]
     inplace: bool,
+
+    #[clap(long, help = "sort record fields by name")]
+    sort_record_fields: bool,
 }
 
 #[derive(Error, Debug, Diagnostic)]
@@ -43,9 +46,13 @@ fn main() -> Result<()> {
         )
     }))?;
 
+    let options = FormatOptions {
+        skip_idempotence: args.skip_idempotence,
+        tolerate_parsing_errors: !args.reject_parse_errors,
+        sort_record_fields: args.sort_record_fields,
+    };
     let format = |code: &str, source: &str| {
-        format(code, args.skip_idempotence, !args.reject_parse_errors)
-            .wrap_err(format!("while formatting '{source}'"))
+        format_with_options(code, &options).wrap_err(format!("while formatting '{source}'"))
     };
 
     if args.input_files.is_empty() {
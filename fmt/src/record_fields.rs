@@ -0,0 +1,88 @@
+//! Canonical ordering of record fields.
+//!
+//! Records are unordered: `{ b = 1, a = 2 }` and `{ a = 2, b = 1 }` are the
+//! same value. By default the formatter keeps the author's order, and
+//! [`FormatOptions::sort_record_fields`] reorders fields by name instead.
+//!
+//! Sorting runs on the syntax tree of the formatted output. The `binding`
+//! nodes of each `record` move as a whole while the text between them stays
+//! in place, so the layout chosen by the formatter (one line or one field per
+//! line) is kept. Records with comments between their fields are left as
+//! written, since a comment cannot reliably be attributed to a single field.
+//! `where` bindings are `binding` nodes too, but are never reordered.
+//!
+//! [`FormatOptions::sort_record_fields`]: crate::FormatOptions::sort_record_fields
+
+use tree_sitter::{Node, Parser};
+
+/// Reorder the fields of every record literal in `source` by name.
+pub(crate) fn sort_record_fields(source: &str) -> String {
+    let mut parser = Parser::new();
+    parser
+        .set_language(&tree_sitter_melbi::LANGUAGE.into())
+        .expect("the Melbi grammar should load");
+    let Some(tree) = parser.parse(source, None) else {
+        return source.to_string();
+    };
+
+    let root = tree.root_node();
+    let mut output = String::with_capacity(source.len());
+    output.push_str(&source[..root.start_byte()]);
+    write_sorted(root, source, &mut output);
+    output.push_str(&source[root.end_byte()..]);
+    output
+}
+
+/// Append the text of `node` to `output`, with the fields of the records in
+/// it sorted.
+fn write_sorted(node: Node, source: &str, output: &mut String) {
+    let mut sorted = sorted_fields(node, source).map(Vec::into_iter);
+    let mut position = node.start_byte();
+    let mut cursor = node.walk();
+    for child in node.children(&mut cursor) {
+        output.push_str(&source[position..child.start_byte()]);
+        position = child.end_byte();
+        // Each field takes the place of the field at its sorted position.
+        let child = match &mut sorted {
+            Some(fields) if child.kind() == "binding" => fields.next().unwrap(),
+            _ => child,
+        };
+        write_sorted(child, source, output);
+    }
+    output.push_str(&source[position..node.end_byte()]);
+}
+
+/// The fields of `node` in name order, if it is the `binding_list` of a
+/// record whose fields are not sorted yet.
+///
+/// Returns `None` for other nodes, for records with comments or syntax
+/// errors between their fields, and for records with fewer than two fields.
+fn sorted_fields<'tree>(node: Node<'tree>, source: &str) -> Option<Vec<Node<'tree>>> {
+    if node.kind() != "binding_list" || node.parent()?.kind() != "record" {
+        return None;
+    }
+    let mut cursor = node.walk();
+    let mut fields = Vec::new();
+    for child in node.named_children(&mut cursor) {
+        if child.kind() != "binding" {
+            return None;
+        }
+        fields.push((field_name(child, source)?, child));
+    }
+    if fields.len() < 2 || fields.is_sorted_by_key(|(name, _)| *name) {
+        return None;
+    }
+    fields.sort_by_key(|(name, _)| *name);
+    Some(fields.into_iter().map(|(_, field)| field).collect())
+}
+
+/// The name of a record field, without the backticks of quoted names.
+fn field_name<'source>(field: Node, source: &'source str) -> Option<&'source str> {
+    let name = field.child_by_field_name("name")?;
+    let text = &source[name.byte_range()];
+    Some(
+        text.strip_prefix('`')
+            .and_then(|quoted| quoted.strip_suffix('`'))
+            .unwrap_or(text),
+    )
+}
@@ -1,4 +1,5 @@
 use bumpalo::Bump;
+use melbi_core::syntax::position::{LineColumn, line_column_to_offset, offset_to_line_column};
 use tower_lsp::lsp_types::*;
 
 use crate::semantic_tokens as st;
@@ -90,13 +91,11 @@ impl DocumentState {
     /// Recursively collect syntax errors from tree-sitter parse tree
     fn collect_syntax_errors(&self, node: tree_sitter::Node, diagnostics: &mut Vec<Diagnostic>) {
         if node.is_error() || node.is_missing() {
-            let start = node.start_position();
-            let end = node.end_position();
-
+            // Tree-sitter columns are in bytes, so convert from byte offsets
             diagnostics.push(Diagnostic {
                 range: Range::new(
-                    Position::new(start.row as u32, start.column as u32),
-                    Position::new(end.row as u32, end.column as u32),
+                    self.offset_to_position(node.start_byte()),
+                    self.offset_to_position(node.end_byte()),
                 ),
                 severity: Some(DiagnosticSeverity::ERROR),
                 code: None,
@@ -180,54 +179,18 @@ impl DocumentState {
         }
     }
 
-    /// Convert byte offset to LSP Position
-    fn offset_to_position(&self, offset: usize) -> Position {
-        let mut line = 0;
-        let mut col = 0;
-        let mut current_offset = 0;
-
-        for ch in self.source.chars() {
-            if current_offset >= offset {
-                break;
-            }
-            if ch == '\n' {
-                line += 1;
-                col = 0;
-            } else {
-                col += 1;
-            }
-            current_offset += ch.len_utf8();
-        }
-
-        Position::new(line, col)
+    /// Convert byte offset to LSP Position (UTF-16 columns)
+    pub fn offset_to_position(&self, offset: usize) -> Position {
+        let position = offset_to_line_column(&self.source, offset);
+        Position::new(position.line, position.column)
     }
 
-    /// Convert LSP Position to byte offset
-    fn position_to_offset(&self, position: Position) -> Option<usize> {
-        let mut offset = 0;
-        let mut current_line = 0;
-        let mut current_col = 0;
-
-        for ch in self.source.chars() {
-            if current_line == position.line && current_col == position.character {
-                return Some(offset);
-            }
-
-            if ch == '\n' {
-                current_line += 1;
-                current_col = 0;
-            } else {
-                current_col += 1;
-            }
-            offset += ch.len_utf8();
-        }
-
-        // If we reached the end and match the position, return the offset
-        if current_line == position.line && current_col == position.character {
-            Some(offset)
-        } else {
-            None
-        }
+    /// Convert LSP Position (UTF-16 columns) to byte offset
+    pub fn position_to_offset(&self, position: Position) -> Option<usize> {
+        line_column_to_offset(
+            &self.source,
+            LineColumn::new(position.line, position.character),
+        )
     }
 
     /// Get hover information at a position
@@ -329,6 +292,14 @@ impl DocumentState {
             ExprInner::Index { value, index, .. } => self
                 .find_expr_at_offset(value, ann, offset)
                 .or_else(|| self.find_expr_at_offset(index, ann, offset)),
+            ExprInner::Slice { value, start, end } => {
+                self.find_expr_at_offset(value, ann, offset).or_else(|| {
+                    start
+                        .iter()
+                        .chain(end.iter())
+                        .find_map(|bound| self.find_expr_at_offset(bound, ann, offset))
+                })
+            }
             ExprInner::Field { value, .. } => self.find_expr_at_offset(value, ann, offset),
             ExprInner::Cast { expr: inner, .. } => self.find_expr_at_offset(inner, ann, offset),
             ExprInner::Lambda { body, .. } => self.find_expr_at_offset(body, ann, offset),
@@ -359,6 +330,11 @@ impl DocumentState {
             } => self
                 .find_expr_at_offset(primary, ann, offset)
                 .or_else(|| self.find_expr_at_offset(fallback, ann, offset)),
+            ExprInner::Coalesce {
+                option, default, ..
+            } => self
+                .find_expr_at_offset(option, ann, offset)
+                .or_else(|| self.find_expr_at_offset(default, ann, offset)),
             ExprInner::Record { fields, .. } => fields
                 .iter()
                 .find_map(|(_, field_expr)| self.find_expr_at_offset(field_expr, ann, offset)),
@@ -624,6 +600,12 @@ impl DocumentState {
                 self.collect_identifiers_in_scope(primary, ann, offset, completions, seen);
                 self.collect_identifiers_in_scope(fallback, ann, offset, completions, seen);
             }
+            ExprInner::Coalesce {
+                option, default, ..
+            } => {
+                self.collect_identifiers_in_scope(option, ann, offset, completions, seen);
+                self.collect_identifiers_in_scope(default, ann, offset, completions, seen);
+            }
             _ => {
                 // For other expression types, we don't need to recurse for completion purposes
             }
@@ -674,7 +656,7 @@ impl DocumentState {
 
     fn collect_semantic_tokens(&self, node: tree_sitter::Node, tokens: &mut Vec<SemanticToken>) {
         let kind = node.kind();
-        let start = node.start_position();
+        let start = self.offset_to_position(node.start_byte());
 
         // Check if we're inside a number suffix - if so, highlight the whole thing as a number
         let is_in_suffix = node
@@ -754,10 +736,12 @@ impl DocumentState {
         };
 
         if let Some(token_type_idx) = token_type {
-            let length = (node.end_byte() - node.start_byte()) as u32;
+            let length = self.source[node.start_byte()..node.end_byte()]
+                .encode_utf16()
+                .count() as u32;
             tokens.push(SemanticToken {
-                delta_line: start.row as u32,
-                delta_start: start.column as u32,
+                delta_line: start.line,
+                delta_start: start.character,
                 length,
                 token_type: token_type_idx,
                 token_modifiers_bitset: 0,
@@ -1,4 +1,5 @@
 use dashmap::DashMap;
+use melbi_core::syntax::position::offset_to_line_column;
 use tower_lsp::jsonrpc::Result;
 use tower_lsp::lsp_types::*;
 use tower_lsp::{Client, LanguageServer, LspService, Server};
@@ -186,14 +187,10 @@ impl LanguageServer for Backend {
                 }
 
                 // Calculate the range of the entire document
-                // Count actual lines (including empty ones) and get the length of the last line
-                let line_count = source.chars().filter(|&c| c == '\n').count();
-                let last_line_start = source.rfind('\n').map(|pos| pos + 1).unwrap_or(0);
-                let last_line_len = source.len() - last_line_start;
-
+                let end = offset_to_line_column(&source, source.len());
                 let range = Range {
                     start: Position::new(0, 0),
-                    end: Position::new(line_count as u32, last_line_len as u32),
+                    end: Position::new(end.line, end.column),
                 };
 
                 Ok(Some(vec![TextEdit {
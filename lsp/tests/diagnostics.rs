@@ -111,3 +111,18 @@ fn test_suffix_expression_type_checking() {
     assert!(diagnostics.is_empty() || has_only_type_errors,
             "Should parse suffix expression: got {:?}", diagnostics);
 }
+
+#[test]
+fn test_positions_use_utf16_columns() {
+    let source = "\"é😀\" + x\n\"😀\" + y";
+    let doc = DocumentState::new(source.to_string());
+
+    // 'é' is one UTF-16 code unit and '😀' is two
+    let x_offset = source.find('x').unwrap();
+    assert_eq!(doc.offset_to_position(x_offset), Position::new(0, 8));
+    assert_eq!(doc.position_to_offset(Position::new(0, 8)), Some(x_offset));
+
+    let y_offset = source.find('y').unwrap();
+    assert_eq!(doc.offset_to_position(y_offset), Position::new(1, 7));
+    assert_eq!(doc.position_to_offset(Position::new(1, 7)), Some(y_offset));
+}
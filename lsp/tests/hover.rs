@@ -128,3 +128,13 @@ fn test_hover_on_nested_expression() {
     // Inner literal, so no hover
     assert!(hover.is_none());
 }
+
+#[test]
+fn test_hover_after_non_ascii_text() {
+    // 'é' is one UTF-16 code unit and '😀' is two, so `y` is at column 17
+    let mut doc = DocumentState::new(r#"{ s = "é😀", t = y } where { y = 42 }"#.to_string());
+    doc.analyze();
+
+    let hover = doc.hover_at_position(Position::new(0, 17));
+    assert_eq!(hover.as_deref(), Some("```melbi\nInt\n```"));
+}
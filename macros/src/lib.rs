@@ -34,6 +34,13 @@ mod melbi_fn;
 ///
 /// - `name`: The Melbi function name (string literal). This becomes the struct name.
 ///
+/// # Optional Attributes
+///
+/// - `deterministic`: Whether the function always returns the same result for
+///   the same arguments (boolean literal, defaults to `true`). Set it to
+///   `false` for functions such as `Math.Random`, so that expressions calling
+///   them are not reported as deterministic.
+///
 /// # Parameters
 ///
 /// Functions can accept any type that implements the `Bridge` trait:
@@ -11,9 +11,9 @@ use syn::{
 pub fn melbi_fn_impl(attr: TokenStream, item: TokenStream) -> TokenStream {
     let input_fn = parse_macro_input!(item as ItemFn);
 
-    // Parse the attribute to extract the Melbi function name
-    let melbi_name = match parse_attribute(attr) {
-        Ok(name) => name,
+    // Parse the attribute to extract the Melbi function name and flags
+    let attribute = match parse_attribute(attr) {
+        Ok(attribute) => attribute,
         Err(err) => return err.to_compile_error().into(),
     };
 
@@ -24,12 +24,21 @@ pub fn melbi_fn_impl(attr: TokenStream, item: TokenStream) -> TokenStream {
     };
 
     // Generate all the code
-    match generate_code(&melbi_name, &sig_info, &input_fn) {
+    match generate_code(&attribute, &sig_info, &input_fn) {
         Ok(tokens) => tokens.into(),
         Err(err) => err.to_compile_error().into(),
     }
 }
 
+/// Options given in the `#[melbi_fn(...)]` attribute
+struct MelbiFnAttribute {
+    /// The Melbi function name
+    name: String,
+    /// Whether the function always returns the same result for the same
+    /// arguments (defaults to `true`)
+    deterministic: bool,
+}
+
 /// How the function receives context resources (arena, type_mgr, etc.)
 #[derive(Debug, Clone, Copy, PartialEq)]
 enum ContextMode {
@@ -295,41 +304,74 @@ where
 }
 
 /// Parse the attribute to extract the name parameter
-fn parse_attribute(attr: TokenStream) -> syn::Result<String> {
-    // When used as #[melbi_fn(name = "FunctionName")], attr contains just: name = "FunctionName"
-    // Parse it as a NameValue meta
-    let meta = syn::parse::<Meta>(attr)?;
+fn parse_attribute(attr: TokenStream) -> syn::Result<MelbiFnAttribute> {
+    // When used as #[melbi_fn(name = "FunctionName", deterministic = false)], attr
+    // contains the comma-separated list of NameValue metas
+    let metas = syn::parse::Parser::parse(
+        syn::punctuated::Punctuated::<Meta, syn::Token![,]>::parse_terminated,
+        attr,
+    )?;
 
-    if let Meta::NameValue(nv) = meta {
+    let mut name = None;
+    let mut deterministic = true;
+    for meta in metas {
+        let Meta::NameValue(nv) = meta else {
+            return Err(syn::Error::new_spanned(
+                meta,
+                "expected attribute format: #[melbi_fn(name = \"FunctionName\")]",
+            ));
+        };
         if nv.path.is_ident("name") {
-            if let Expr::Lit(expr_lit) = &nv.value {
-                if let Lit::Str(lit) = &expr_lit.lit {
-                    return Ok(lit.value());
+            match &nv.value {
+                Expr::Lit(syn::ExprLit {
+                    lit: Lit::Str(lit), ..
+                }) => name = Some(lit.value()),
+                _ => {
+                    return Err(syn::Error::new_spanned(
+                        &nv.value,
+                        "name attribute must be a string literal",
+                    ));
                 }
             }
+        } else if nv.path.is_ident("deterministic") {
+            match &nv.value {
+                Expr::Lit(syn::ExprLit {
+                    lit: Lit::Bool(lit), ..
+                }) => deterministic = lit.value,
+                _ => {
+                    return Err(syn::Error::new_spanned(
+                        &nv.value,
+                        "deterministic attribute must be a boolean literal",
+                    ));
+                }
+            }
+        } else {
             return Err(syn::Error::new_spanned(
-                &nv.value,
-                "name attribute must be a string literal",
+                nv.path,
+                "expected 'name' or 'deterministic' attribute",
             ));
         }
-        return Err(syn::Error::new_spanned(
-            nv.path,
-            "expected 'name' attribute",
-        ));
     }
 
-    Err(syn::Error::new_spanned(
-        meta,
-        "expected attribute format: #[melbi_fn(name = \"FunctionName\")]",
-    ))
+    let Some(name) = name else {
+        return Err(syn::Error::new(
+            proc_macro2::Span::call_site(),
+            "expected attribute format: #[melbi_fn(name = \"FunctionName\")]",
+        ));
+    };
+    Ok(MelbiFnAttribute {
+        name,
+        deterministic,
+    })
 }
 
 /// Generate all the code: impl function, struct, and trait implementations
 fn generate_code(
-    melbi_name: &str,
+    attribute: &MelbiFnAttribute,
     sig_info: &SignatureInfo,
     input_fn: &ItemFn,
 ) -> syn::Result<TokenStream2> {
+    let melbi_name = attribute.name.as_str();
     let struct_name = syn::Ident::new(melbi_name, proc_macro2::Span::call_site());
 
     // Extract components
@@ -376,7 +418,7 @@ fn generate_code(
     let function_impl = generate_function_impl(
         &struct_name,
         sig_info,
-        melbi_name,
+        attribute,
         &param_names,
         &param_types,
         return_type,
@@ -525,11 +567,13 @@ fn generate_user_fn_call(
 fn generate_function_impl(
     struct_name: &syn::Ident,
     sig_info: &SignatureInfo,
-    melbi_name: &str,
+    attribute: &MelbiFnAttribute,
     param_names: &[&syn::Ident],
     param_types: &[&Box<Type>],
     return_type: &Type,
 ) -> syn::Result<TokenStream2> {
+    let melbi_name = attribute.name.as_str();
+    let deterministic = attribute.deterministic;
     let impl_fn_name = &sig_info.fn_name;
     let has_generics = !sig_info.generics.params.is_empty();
     let arity = param_names.len();
@@ -595,6 +639,10 @@ fn generate_function_impl(
                     self.fn_type
                 }
 
+                fn is_deterministic(&self) -> bool {
+                    #deterministic
+                }
+
                 unsafe fn call_unchecked(
                     &self,
                     ctx: & ::melbi_core::values::function::FfiContext< #type_mgr_lifetime, #arena_lifetime >,
@@ -624,6 +672,10 @@ fn generate_function_impl(
                     self.fn_type
                 }
 
+                fn is_deterministic(&self) -> bool {
+                    #deterministic
+                }
+
                 unsafe fn call_unchecked(
                     &self,
                     ctx: & ::melbi_core::values::function::FfiContext<'types, 'arena>,
@@ -34,6 +34,21 @@ fn string_upper<'a>(arena: &'a Bump, _type_mgr: &'a TypeManager, s: Str<'a>) ->
     Str::from_str(arena, &upper)
 }
 
+/// Draws a number from the execution's generator, so it is not deterministic
+#[melbi_fn(name = "Roll", deterministic = false)]
+fn roll_function(ctx: &FfiContext) -> f64 {
+    ctx.random().next_float()
+}
+
+#[test]
+fn test_deterministic_attribute() {
+    let arena = Bump::new();
+    let type_mgr = TypeManager::new(&arena);
+
+    assert!(Add::new(type_mgr).is_deterministic());
+    assert!(!Roll::new(type_mgr).is_deterministic());
+}
+
 #[test]
 fn test_macro_generates_struct() {
     let arena = Bump::new();
@@ -5,6 +5,7 @@ use melbi_core::api::{
 };
 use melbi_core::parser::Span;
 use melbi_core::stdlib;
+use melbi_core::syntax::position::offset_to_utf16;
 use melbi_core::values::dynamic::Value;
 use serde::Serialize;
 use wasm_bindgen::prelude::*;
@@ -73,10 +74,10 @@ impl PlaygroundEngine {
                     Ok(value) => {
                         WorkerResponse::ok(EvaluationSuccess::from_value(value, duration_ms))
                     }
-                    Err(err) => WorkerResponse::err(err),
+                    Err(err) => WorkerResponse::err(err, source),
                 }
             }
-            Err(err) => WorkerResponse::err(err),
+            Err(err) => WorkerResponse::err(err, source),
         }
     }
 }
@@ -93,9 +94,9 @@ impl<T> WorkerResponse<T> {
         WorkerResponse::Ok { data }
     }
 
-    fn err(error: Error) -> Self {
+    fn err(error: Error, source: &str) -> Self {
         WorkerResponse::Err {
-            error: WorkerError::from(error),
+            error: WorkerError::new(error, source),
         }
     }
 }
@@ -123,6 +124,8 @@ pub struct RelatedInfoPayload {
     message: String,
 }
 
+/// Span of a diagnostic, in UTF-16 code units from the start of the source
+/// (the offsets JavaScript strings and Monaco models use).
 #[derive(Serialize)]
 pub struct RangePayload {
     start: usize,
@@ -150,8 +153,8 @@ impl EvaluationSuccess {
     }
 }
 
-impl From<Error> for WorkerError {
-    fn from(err: Error) -> Self {
+impl WorkerError {
+    fn new(err: Error, source: &str) -> Self {
         match err {
             Error::Api(message) => WorkerError {
                 kind: "api",
@@ -167,55 +170,55 @@ impl From<Error> for WorkerError {
                 diagnostics: Some(
                     diagnostics
                         .into_iter()
-                        .map(DiagnosticPayload::from)
+                        .map(|diagnostic| DiagnosticPayload::new(diagnostic, source))
                         .collect(),
                 ),
             },
             Error::Runtime { diagnostic, .. } => WorkerError {
                 kind: "runtime",
                 message: diagnostic.message.clone(),
-                diagnostics: Some(vec![DiagnosticPayload::from(diagnostic)]),
+                diagnostics: Some(vec![DiagnosticPayload::new(diagnostic, source)]),
             },
-            Error::ResourceExceeded(message) => WorkerError {
+            Error::ResourceExceeded { diagnostic, .. } => WorkerError {
                 kind: "resource_exceeded",
-                message,
-                diagnostics: None,
+                message: diagnostic.message.clone(),
+                diagnostics: Some(vec![DiagnosticPayload::new(diagnostic, source)]),
             },
         }
     }
 }
 
-impl From<CoreDiagnostic> for DiagnosticPayload {
-    fn from(diag: CoreDiagnostic) -> Self {
+impl DiagnosticPayload {
+    fn new(diag: CoreDiagnostic, source: &str) -> Self {
         Self {
             severity: severity_to_str(diag.severity),
             message: diag.message,
-            span: RangePayload::from(diag.span),
+            span: RangePayload::new(diag.span, source),
             help: diag.help.get(0).map(|s| s.clone()),
             code: diag.code,
             related: diag
                 .related
                 .into_iter()
-                .map(RelatedInfoPayload::from)
+                .map(|info| RelatedInfoPayload::new(info, source))
                 .collect(),
         }
     }
 }
 
-impl From<RelatedInfo> for RelatedInfoPayload {
-    fn from(info: RelatedInfo) -> Self {
+impl RelatedInfoPayload {
+    fn new(info: RelatedInfo, source: &str) -> Self {
         Self {
-            span: RangePayload::from(info.span),
+            span: RangePayload::new(info.span, source),
             message: info.message,
         }
     }
 }
 
-impl From<Span> for RangePayload {
-    fn from(span: Span) -> Self {
+impl RangePayload {
+    fn new(span: Span, source: &str) -> Self {
         RangePayload {
-            start: span.0.start,
-            end: span.0.end,
+            start: offset_to_utf16(source, span.0.start),
+            end: offset_to_utf16(source, span.0.end),
         }
     }
 }
@@ -250,4 +253,19 @@ mod tests {
             WorkerResponse::Err { error } => panic!("evaluation failed: {}", error.message),
         }
     }
+
+    #[test]
+    fn reports_spans_in_utf16_code_units() {
+        let engine = PlaygroundEngine::new();
+        // 'é' is one UTF-16 code unit and '😀' is two, but 2 and 4 bytes
+        let source = r#"{ s = "é😀", t = missing }"#;
+        match engine.evaluate_internal(source) {
+            WorkerResponse::Ok { .. } => panic!("expected an undefined variable error"),
+            WorkerResponse::Err { error } => {
+                let diagnostics = error.diagnostics.expect("should have diagnostics");
+                assert_eq!(diagnostics[0].span.start, 17);
+                assert_eq!(diagnostics[0].span.end, 24);
+            }
+        }
+    }
 }
@@ -152,7 +152,8 @@ impl std::error::Error for ValidationError {}
 impl From<melbi_core::values::from_raw::TypeError> for ValidationError {
     fn from(err: melbi_core::values::from_raw::TypeError) -> Self {
         match err {
-            melbi_core::values::from_raw::TypeError::Mismatch => {
+            melbi_core::values::from_raw::TypeError::Mismatch
+            | melbi_core::values::from_raw::TypeError::MissingField(_) => {
                 ValidationError::TypeMismatch { param_index: 0 }
             } // Adjust param_index as needed
             melbi_core::values::from_raw::TypeError::IndexOutOfBounds => {
@@ -82,12 +82,9 @@ pub fn render_error_to(
             diagnostics,
             source,
         } => render_diagnostics(source, diagnostics, writer, config),
-        Error::Runtime { diagnostic, source } => {
+        Error::Runtime { diagnostic, source } | Error::ResourceExceeded { diagnostic, source } => {
             render_diagnostics(source, &[diagnostic.clone()], writer, config)
         }
-        Error::ResourceExceeded(msg) => {
-            writeln!(writer, "Resource limit exceeded: {}", msg)
-        }
         Error::Api(msg) => {
             writeln!(writer, "API error: {}", msg)
         }
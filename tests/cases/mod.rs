@@ -92,6 +92,23 @@ macro_rules! handle_case {
         }
     };
 
+    ([$($attrs:meta)*] formatted_sorted, $expected:tt) => {
+        $(#[$attrs])*
+        #[test]
+        fn validate_formatted_sorted() {
+            let options = melbi_fmt::FormatOptions {
+                sort_record_fields: true,
+                ..Default::default()
+            };
+            let formatted = melbi_fmt::format_with_options(input(), &options);
+            let result = formatted
+                .as_ref()
+                .map(|s| s.as_str())
+                .map_err(|e| e.downcast_ref::<melbi_fmt::FormatError>().unwrap());
+            assert_case!(result, $expected);
+        }
+    };
+
     ([$($attrs:meta)*] error, $expected:tt) => {
         $(#[$attrs])*
         #[test]
@@ -124,6 +141,30 @@ macro_rules! handle_case {
         }
     };
 
+    // Codes and messages of all diagnostics, e.g. `{ vec![("E002", "Undefined variable 'x'")] }`
+    ([$($attrs:meta)*] diagnostics, $expected:tt) => {
+        $(#[$attrs])*
+        #[test]
+        fn validate_diagnostics() {
+            let arena = bumpalo::Bump::new();
+            let engine = melbi::Engine::new(melbi::EngineOptions::default(), &arena, |_, _, _| {});
+            let (_expr, _analysis, diagnostics) =
+                engine.compile_collecting_diagnostics(Default::default(), input(), &[]);
+
+            let actual: Vec<(&str, &str)> = diagnostics
+                .iter()
+                .map(|diagnostic| {
+                    (
+                        diagnostic.code.as_deref().unwrap_or(""),
+                        diagnostic.message.as_str(),
+                    )
+                })
+                .collect();
+            let result: Result<Vec<(&str, &str)>, ()> = Ok(actual);
+            assert_case!(result, $expected);
+        }
+    };
+
     // Generic case for unknown field names
     ([$($attrs:meta)*] $field_name:ident, $expected:tt) => {
         compile_error!(concat!("Unknown test case field: ", stringify!($field_name)));
@@ -0,0 +1,59 @@
+/*
+ * Diagnostic Snapshot Tests
+ *
+ * Tests that verify the codes and messages of all diagnostics reported for
+ * a source, so message regressions are caught without rendering them.
+ */
+
+mod cases;
+
+test_case! {
+    name: if_condition_must_be_boolean,
+    input: "if 1 then 0 else 0",
+    diagnostics: { vec![("E001", "Type mismatch: expected Bool, found Int")] },
+}
+
+test_case! {
+    name: undefined_variable,
+    input: "x + 1",
+    diagnostics: { vec![("E002", "Undefined variable 'x'")] },
+}
+
+test_case! {
+    name: wrong_number_of_arguments,
+    input: "((x, y) => x + y)(1)",
+    diagnostics: { vec![("E008", "Function parameter count mismatch: expected 2, found 1")] },
+}
+
+test_case! {
+    name: non_indexable_type,
+    input: "42[0]",
+    diagnostics: { vec![("E009", "Cannot index into non-indexable type 'Int'")] },
+}
+
+test_case! {
+    name: unknown_record_field,
+    input: "{ name = 1 }.nmae",
+    diagnostics: { vec![("E010", "Record does not have field 'nmae'. Available fields: name")] },
+}
+
+test_case! {
+    name: every_error_is_reported,
+    input: r#"{ a = true + false, b = b"a" - b"b" }"#,
+    diagnostics: { vec![
+        ("E005", "Type 'Bool' does not implement Numeric"),
+        ("E005", "Type 'Bytes' does not implement Numeric"),
+    ] },
+}
+
+test_case! {
+    name: syntax_error,
+    input: "1 +",
+    diagnostics: { vec![("P001", "Expected expression, literal or identifier, found unexpected token")] },
+}
+
+test_case! {
+    name: valid_source_has_no_diagnostics,
+    input: "1 + 2",
+    diagnostics: { Ok(diagnostics) if diagnostics.is_empty() },
+}
@@ -8,6 +8,8 @@
  * Last reviewed: October 15, 2025
  */
 
+use melbi_core::parser::Expr;
+
 mod cases;
 
 test_case! {
@@ -108,3 +110,53 @@ if true then x + y + z
 else a * b - c"#.trim_start() },
 }
 // Multi-line branches
+
+test_case! {
+    name: else_if_chain,
+    input: { "if a then x else if b then y else z" },
+    ast: {
+        Ok(Expr::If {
+            cond: Expr::Ident("a"),
+            then_branch: Expr::Ident("x"),
+            else_branch: Expr::If {
+                cond: Expr::Ident("b"),
+                then_branch: Expr::Ident("y"),
+                else_branch: Expr::Ident("z"),
+            },
+        })
+    },
+    formatted: { "if a then x else if b then y else z" },
+}
+// `else if` needs no parentheses
+
+test_case! {
+    name: else_if_chain_multiline,
+    input: { r#"
+if score >= 90 then "gold"
+else if score >= 70 then "silver"
+else if score >= 50 then "bronze"
+else "none""#.trim_start() },
+    formatted: { r#"
+if score >= 90 then "gold"
+else if score >= 70 then "silver"
+else if score >= 50 then "bronze"
+else "none""#.trim_start() },
+}
+// Tiered rules keep one tier per line
+
+test_case! {
+    name: else_if_chain_cascading_indentation,
+    input: { r#"
+if score >= 90 then "gold"
+else
+    if score >= 70 then "silver"
+    else
+        if score >= 50 then "bronze"
+        else "none""#.trim_start() },
+    formatted: { r#"
+if score >= 90 then "gold"
+else if score >= 70 then "silver"
+else if score >= 50 then "bronze"
+else "none""#.trim_start() },
+}
+// Nested ifs in else branches are flattened into aligned lines
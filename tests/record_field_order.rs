@@ -0,0 +1,56 @@
+//! Record field order in formatted output: kept as written by default, or
+//! sorted by name with `FormatOptions::sort_record_fields`.
+
+mod cases;
+
+test_case! {
+    name: single_line_record,
+    input: { "{b = 1, a = 2}" },
+    formatted: { "{ b = 1, a = 2 }" },
+    formatted_sorted: { "{ a = 2, b = 1 }" },
+}
+
+test_case! {
+    name: multi_line_record,
+    input: { r#"
+{b = 1,
+a = 2}"#.trim_start() },
+    formatted: { r#"
+{
+    b = 1,
+    a = 2,
+}"#.trim_start() },
+    formatted_sorted: { r#"
+{
+    a = 2,
+    b = 1,
+}"#.trim_start() },
+}
+
+test_case! {
+    name: nested_records,
+    input: { "{b = {y = 1, x = 2}, a = 3}" },
+    formatted: { "{ b = { y = 1, x = 2 }, a = 3 }" },
+    formatted_sorted: { "{ a = 3, b = { x = 2, y = 1 } }" },
+}
+
+test_case! {
+    name: where_bindings_keep_order,
+    input: { "{b = c, a = 2} where {c = 1, a = c}" },
+    formatted: { "{ b = c, a = 2 } where { c = 1, a = c }" },
+    formatted_sorted: { "{ a = 2, b = c } where { c = 1, a = c }" },
+}
+
+test_case! {
+    name: record_with_comments_keeps_order,
+    input: { r#"
+{
+    b = 1, // second
+    a = 2,
+}"#.trim_start() },
+    formatted_sorted: { r#"
+{
+    b = 1, // second
+    a = 2,
+}"#.trim_start() },
+}
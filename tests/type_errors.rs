@@ -186,7 +186,7 @@ test_case! {
    │
    │ Help 1: Ord is required for comparison operations (<, >, <=, >=)
    │
-   │ Help 2: Ord is implemented for: Int, Float, Str, Bytes
+   │ Help 2: Ord is implemented for: Int, Float, Str, Bytes, Option (if the element is Ord)
 ───╯
 "#.trim_start() },
 }
@@ -258,7 +258,7 @@ test_case! {
    │
    │ Help 1: Indexable is required for indexing operations (value[index])
    │
-   │ Help 2: Indexable is implemented for: Array, Map, Bytes
+   │ Help 2: Indexable is implemented for: Array, Map, Bytes, Str
 ───╯
 "#.trim_start() },
 }
@@ -278,7 +278,7 @@ test_case! {
    │
    │ Help 1: Indexable is required for indexing operations (value[index])
    │
-   │ Help 2: Indexable is implemented for: Array, Map, Bytes
+   │ Help 2: Indexable is implemented for: Array, Map, Bytes, Str
 ───╯
 "#.trim_start() },
 }
@@ -340,7 +340,7 @@ test_case! {
    │
    │ Help 1: Indexable is required for indexing operations (value[index])
    │
-   │ Help 2: Indexable is implemented for: Array, Map, Bytes
+   │ Help 2: Indexable is implemented for: Array, Map, Bytes, Str
 ───╯
 "#.trim_start() },
 }
@@ -376,7 +376,7 @@ where {
    │
    │ Help 1: Indexable is required for indexing operations (value[index])
    │
-   │ Help 2: Indexable is implemented for: Array, Map, Bytes
+   │ Help 2: Indexable is implemented for: Array, Map, Bytes, Str
 ───╯
 "#.trim_start() },
 }
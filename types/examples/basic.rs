@@ -12,7 +12,7 @@ fn main() {
     println!("=== Melbi Types Example ===\n");
 
     // Example 1: Using BoxBuilder (simple, Rc-based)
-    println!("1. BoxBuilder (reference counting):");
+    println!("1. BoxBuilder (owned allocations):");
     let box_builder = BoxBuilder::new();
 
     let int_ty = TypeKind::Scalar(Scalar::Int).intern(box_builder);
@@ -1,10 +1,11 @@
 use crate::ir::{TyData, TypeBuilder, TypeKind};
-use alloc::rc::Rc;
+use alloc::boxed::Box;
 use alloc::vec::Vec;
 
-/// Interner that uses reference counting (no deduplication).
+/// Interner that uses owned heap allocations (no deduplication).
 ///
-/// Types are allocated with `Rc` and no interning is performed.
+/// Types are allocated with `Box` and no interning is performed, so cloning a
+/// type copies it. Use [`RcBuilder`](crate::RcBuilder) to share types instead.
 /// This is useful for:
 /// - Testing (simpler than arena)
 /// - Situations where deduplication isn't needed
@@ -34,11 +35,11 @@ impl BoxBuilder {
 
 impl TypeBuilder for BoxBuilder {
     type TypeView = crate::Ty<Self>;
-    type InternedTy = Rc<TyData<Self>>;
-    type InternedStr = Rc<str>;
-    type InternedTypes = Rc<[crate::Ty<Self>]>;
-    type InternedFieldTypes = Rc<[(Rc<str>, crate::Ty<Self>)]>;
-    type InternedSymbolParts = Rc<[Rc<str>]>;
+    type InternedTy = Box<TyData<Self>>;
+    type InternedStr = Box<str>;
+    type InternedTypes = Box<[crate::Ty<Self>]>;
+    type InternedFieldTypes = Box<[(Box<str>, crate::Ty<Self>)]>;
+    type InternedSymbolParts = Box<[Box<str>]>;
 
     // ========================================================================
     // High-level type constructors
@@ -108,7 +109,7 @@ impl TypeBuilder for BoxBuilder {
         let flags = kind.compute_flags(self);
 
         // Wrap in TyData and allocate
-        Rc::new(TyData { kind, flags })
+        Box::new(TyData { kind, flags })
     }
 
     fn ty_data(self, ty: &Self::InternedTy) -> &TyData<Self> {
@@ -131,10 +132,10 @@ impl TypeBuilder for BoxBuilder {
         self,
         data: impl IntoIterator<Item = (impl AsRef<str>, crate::Ty<Self>)>,
     ) -> Self::InternedFieldTypes {
-        // Intern all field name strings as Rc<str>
-        let mut fields: Vec<(Rc<str>, crate::Ty<Self>)> = data
+        // Intern all field name strings as Box<str>
+        let mut fields: Vec<(Box<str>, crate::Ty<Self>)> = data
             .into_iter()
-            .map(|(name, ty)| (Rc::from(name.as_ref()), ty))
+            .map(|(name, ty)| (Box::from(name.as_ref()), ty))
             .collect();
 
         // Sort by field name for canonical representation
@@ -154,10 +155,10 @@ impl TypeBuilder for BoxBuilder {
         self,
         data: impl IntoIterator<Item = impl AsRef<str>>,
     ) -> Self::InternedSymbolParts {
-        // Intern all strings as Rc<str>
-        let mut parts: Vec<Rc<str>> = data
+        // Intern all strings as Box<str>
+        let mut parts: Vec<Box<str>> = data
             .into_iter()
-            .map(|part| Rc::from(part.as_ref()))
+            .map(|part| Box::from(part.as_ref()))
             .collect();
 
         // Sort for canonical representation
@@ -0,0 +1,265 @@
+use crate::ir::{TyData, TypeBuilder, TypeKind};
+use alloc::vec::Vec;
+use bumpalo::Bump;
+use core::cell::RefCell;
+use core::fmt;
+use hashbrown::{DefaultHashBuilder, HashSet};
+
+/// Interner that uses arena allocation with hash-consing.
+///
+/// Unlike [`ArenaBuilder`](crate::ArenaBuilder), structurally equal types are
+/// deduplicated: building `array(int())` twice returns the same node. Since
+/// every node is unique, two types from the same builder are equal exactly
+/// when their handles point to the same node, so `==` on handles (and
+/// `types_equal` between them) is a pointer comparison.
+///
+/// The interner's table lives in the arena, and is freed with it.
+///
+/// # Example
+///
+/// ```
+/// use melbi_types::{TypeBuilder, InternBuilder};
+/// use bumpalo::Bump;
+///
+/// let arena = Bump::new();
+/// let builder = InternBuilder::new(&arena);
+///
+/// let first = builder.array(builder.int());
+/// let second = builder.array(builder.int());
+/// assert_eq!(first, second);
+/// assert_eq!(builder.unique_types(), 2); // Int and Array[Int]
+/// ```
+#[derive(Copy, Clone)]
+pub struct InternBuilder<'arena> {
+    interner: &'arena TypeInterner<'arena>,
+}
+
+/// Table of the unique nodes built so far.
+struct TypeInterner<'arena> {
+    arena: &'arena Bump,
+    types:
+        RefCell<HashSet<&'arena TyData<InternBuilder<'arena>>, DefaultHashBuilder, &'arena Bump>>,
+}
+
+/// Handle to a hash-consed node.
+///
+/// Equality and hashing use the node's address: children of a node are
+/// already unique, so comparing them by address is enough to deduplicate
+/// their parent.
+#[derive(Copy, Clone)]
+pub struct Interned<'arena>(&'arena TyData<InternBuilder<'arena>>);
+
+impl<'arena> PartialEq for Interned<'arena> {
+    fn eq(&self, other: &Self) -> bool {
+        core::ptr::eq(self.0, other.0)
+    }
+}
+
+impl<'arena> Eq for Interned<'arena> {}
+
+impl<'arena> core::hash::Hash for Interned<'arena> {
+    fn hash<H: core::hash::Hasher>(&self, state: &mut H) {
+        core::ptr::hash(self.0, state)
+    }
+}
+
+impl<'arena> fmt::Debug for Interned<'arena> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+// Two builders are equal if they share the same interner
+impl<'arena> PartialEq for InternBuilder<'arena> {
+    fn eq(&self, other: &Self) -> bool {
+        core::ptr::eq(self.interner, other.interner)
+    }
+}
+
+impl<'arena> Eq for InternBuilder<'arena> {}
+
+impl<'arena> core::hash::Hash for InternBuilder<'arena> {
+    fn hash<H: core::hash::Hasher>(&self, state: &mut H) {
+        core::ptr::hash(self.interner, state)
+    }
+}
+
+impl<'arena> fmt::Debug for InternBuilder<'arena> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("InternBuilder")
+            .field("unique_types", &self.unique_types())
+            .finish()
+    }
+}
+
+impl<'arena> InternBuilder<'arena> {
+    /// Create a new interning builder with an empty table in `arena`.
+    pub fn new(arena: &'arena Bump) -> Self {
+        let interner = arena.alloc(TypeInterner {
+            arena,
+            types: RefCell::new(HashSet::new_in(arena)),
+        });
+        Self { interner }
+    }
+
+    /// Number of unique type nodes built so far.
+    pub fn unique_types(self) -> usize {
+        self.interner.types.borrow().len()
+    }
+}
+
+impl<'arena> TypeBuilder for InternBuilder<'arena> {
+    type TypeView = crate::Ty<Self>;
+    type InternedTy = Interned<'arena>;
+    type InternedStr = &'arena str;
+    type InternedTypes = &'arena [crate::Ty<Self>];
+    type InternedFieldTypes = &'arena [(&'arena str, crate::Ty<Self>)];
+    type InternedSymbolParts = &'arena [&'arena str];
+
+    // ========================================================================
+    // High-level type constructors
+    // ========================================================================
+
+    fn type_var(self, id: u16) -> Self::TypeView {
+        TypeKind::TypeVar(id).intern(self)
+    }
+
+    fn int(self) -> Self::TypeView {
+        TypeKind::Scalar(crate::Scalar::Int).intern(self)
+    }
+
+    fn float(self) -> Self::TypeView {
+        TypeKind::Scalar(crate::Scalar::Float).intern(self)
+    }
+
+    fn bool(self) -> Self::TypeView {
+        TypeKind::Scalar(crate::Scalar::Bool).intern(self)
+    }
+
+    fn str(self) -> Self::TypeView {
+        TypeKind::Scalar(crate::Scalar::Str).intern(self)
+    }
+
+    fn bytes(self) -> Self::TypeView {
+        TypeKind::Scalar(crate::Scalar::Bytes).intern(self)
+    }
+
+    fn array(self, elem: Self::TypeView) -> Self::TypeView {
+        TypeKind::Array(elem).intern(self)
+    }
+
+    fn map(self, key: Self::TypeView, val: Self::TypeView) -> Self::TypeView {
+        TypeKind::Map(key, val).intern(self)
+    }
+
+    fn record(
+        self,
+        fields: impl IntoIterator<Item = (impl AsRef<str>, Self::TypeView)>,
+    ) -> Self::TypeView {
+        TypeKind::Record(self.intern_field_types(fields)).intern(self)
+    }
+
+    fn function(
+        self,
+        params: impl IntoIterator<Item = Self::TypeView>,
+        ret: Self::TypeView,
+    ) -> Self::TypeView {
+        TypeKind::Function {
+            params: self.intern_types(params),
+            ret,
+        }
+        .intern(self)
+    }
+
+    fn symbol(self, parts: impl IntoIterator<Item = impl AsRef<str>>) -> Self::TypeView {
+        TypeKind::Symbol(self.intern_symbol_parts(parts)).intern(self)
+    }
+
+    // ========================================================================
+    // Low-level internals
+    // ========================================================================
+
+    fn intern_ty(self, kind: TypeKind<Self>) -> Self::InternedTy {
+        // Compute flags from the type kind
+        let flags = kind.compute_flags(self);
+        let data = TyData { kind, flags };
+
+        // Reuse the existing node if an equal type was built before
+        let mut types = self.interner.types.borrow_mut();
+        if let Some(&existing) = types.get(&data) {
+            return Interned(existing);
+        }
+        let allocated: &'arena TyData<Self> = self.interner.arena.alloc(data);
+        types.insert(allocated);
+        Interned(allocated)
+    }
+
+    fn ty_data(self, ty: &Self::InternedTy) -> &TyData<Self> {
+        ty.0
+    }
+
+    fn intern_types<E>(self, data: impl IntoIterator<Item = E>) -> Self::InternedTypes
+    where
+        E: Into<crate::Ty<Self>>,
+    {
+        let types: Vec<_> = data.into_iter().map(|e| e.into()).collect();
+        self.interner.arena.alloc_slice_copy(&types)
+    }
+
+    fn types_data(self, types: &Self::InternedTypes) -> &[crate::Ty<Self>] {
+        types
+    }
+
+    fn intern_field_types(
+        self,
+        data: impl IntoIterator<Item = (impl AsRef<str>, crate::Ty<Self>)>,
+    ) -> Self::InternedFieldTypes {
+        let arena = self.interner.arena;
+
+        // Intern all field name strings in the arena
+        let mut interned_fields: Vec<(&'arena str, crate::Ty<Self>)> = data
+            .into_iter()
+            .map(|(name, ty)| {
+                let interned_name: &'arena str = arena.alloc_str(name.as_ref());
+                (interned_name, ty)
+            })
+            .collect();
+
+        // Sort by field name for canonical representation
+        interned_fields.sort_by_key(|(name, _)| *name);
+
+        arena.alloc_slice_copy(&interned_fields)
+    }
+
+    fn field_types_data(
+        self,
+        fields: &Self::InternedFieldTypes,
+    ) -> &[(Self::InternedStr, crate::Ty<Self>)] {
+        fields
+    }
+
+    fn intern_symbol_parts(
+        self,
+        data: impl IntoIterator<Item = impl AsRef<str>>,
+    ) -> Self::InternedSymbolParts {
+        let arena = self.interner.arena;
+
+        // Intern all strings in the arena
+        let mut interned_parts: Vec<&'arena str> = data
+            .into_iter()
+            .map(|part| {
+                let s: &'arena str = arena.alloc_str(part.as_ref());
+                s
+            })
+            .collect();
+
+        // Sort for canonical representation
+        interned_parts.sort();
+
+        arena.alloc_slice_copy(&interned_parts)
+    }
+
+    fn symbol_parts_data(self, parts: &Self::InternedSymbolParts) -> &[Self::InternedStr] {
+        parts
+    }
+}
@@ -11,10 +11,12 @@
 //! - **TypeView trait**: Unified view over types from different builders
 //! - **Generic algorithms**: Visitor and transformer patterns over types
 //! - **Display**: Pretty-printing support
+//! - **Hashing**: Structural hashing that is stable across builders
 
 pub mod builder;
 pub mod display;
 pub mod fold;
+pub mod hash;
 pub mod scalar;
 pub mod ty;
 pub mod view;
@@ -24,7 +26,8 @@ pub mod zip;
 pub use builder::TypeBuilder;
 pub use display::{TyDisplay, TypeFormatter, TypeKindDisplay};
 pub use fold::TypeFolder;
-pub use scalar::Scalar;
+pub use hash::{StableHasher, TypeHasher};
+pub use scalar::{CustomScalar, Scalar};
 pub use ty::{Ty, TyData, TypeKind};
 pub use view::{TypeChildren, TypeView, convert_ty, types_cmp, types_equal};
 pub use visit::{ClosureVisitor, TypeVisitor};
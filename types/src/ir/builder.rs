@@ -60,7 +60,7 @@ pub trait TypeBuilder: Copy + Clone + Debug + Eq {
     ///
     /// This is opaque to generic code - could be:
     /// - `&'arena TyData<B>` (arena)
-    /// - `Rc<TyData<B>>` (box/rc)
+    /// - `Box<TyData<B>>` or `Rc<TyData<B>>` (box/rc)
     /// - `TypeId` (encoded)
     type InternedTy: Clone + Debug + Eq + Hash;
 
@@ -1,4 +1,4 @@
-use super::{Scalar, TypeBuilder, TypeKind, TypeVisitor};
+use super::{TypeBuilder, TypeKind, TypeVisitor};
 use crate::TypeView;
 use alloc::string::String;
 use core::fmt::Write;
@@ -37,20 +37,8 @@ impl<B: TypeBuilder> TypeVisitor<B> for TypeFormatter<B> {
             TypeKind::TypeVar(id) => {
                 let _ = write!(self.output, "_{}", id);
             }
-            TypeKind::Scalar(Scalar::Int) => {
-                let _ = write!(self.output, "Int");
-            }
-            TypeKind::Scalar(Scalar::Bool) => {
-                let _ = write!(self.output, "Bool");
-            }
-            TypeKind::Scalar(Scalar::Float) => {
-                let _ = write!(self.output, "Float");
-            }
-            TypeKind::Scalar(Scalar::Str) => {
-                let _ = write!(self.output, "Str");
-            }
-            TypeKind::Scalar(Scalar::Bytes) => {
-                let _ = write!(self.output, "Bytes");
+            TypeKind::Scalar(scalar) => {
+                let _ = write!(self.output, "{}", scalar);
             }
             TypeKind::Array(elem) => {
                 let _ = write!(self.output, "Array[");
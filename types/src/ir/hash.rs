@@ -0,0 +1,184 @@
+use super::{Scalar, TypeBuilder, TypeKind, TypeVisitor};
+use crate::TypeView;
+use core::hash::Hasher;
+
+/// Deterministic 64-bit FNV-1a hasher.
+///
+/// Unlike the hashers in `std`, the output only depends on the bytes written:
+/// it is the same across runs, processes and platforms, so it can be stored
+/// or compared between builds. Integers are written in little-endian order.
+#[derive(Copy, Clone, Debug)]
+pub struct StableHasher {
+    state: u64,
+}
+
+impl StableHasher {
+    const OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+    const PRIME: u64 = 0x0000_0100_0000_01b3;
+
+    pub fn new() -> Self {
+        Self {
+            state: Self::OFFSET_BASIS,
+        }
+    }
+
+    /// Write a string prefixed by its length, so that adjacent strings
+    /// cannot run into each other.
+    pub fn write_str(&mut self, s: &str) {
+        self.write_usize(s.len());
+        self.write(s.as_bytes());
+    }
+}
+
+impl Default for StableHasher {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Hasher for StableHasher {
+    fn finish(&self) -> u64 {
+        self.state
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        for &byte in bytes {
+            self.state ^= u64::from(byte);
+            self.state = self.state.wrapping_mul(Self::PRIME);
+        }
+    }
+
+    fn write_u16(&mut self, n: u16) {
+        self.write(&n.to_le_bytes());
+    }
+
+    fn write_u32(&mut self, n: u32) {
+        self.write(&n.to_le_bytes());
+    }
+
+    fn write_u64(&mut self, n: u64) {
+        self.write(&n.to_le_bytes());
+    }
+
+    fn write_u128(&mut self, n: u128) {
+        self.write(&n.to_le_bytes());
+    }
+
+    fn write_usize(&mut self, n: usize) {
+        // Widen so 32-bit and 64-bit targets agree
+        self.write_u64(n as u64);
+    }
+}
+
+/// Visitor that computes a structural hash of a type.
+///
+/// The hash only depends on the structure of the type, never on how or where
+/// it was allocated, so the same type built with different builders (e.g.
+/// [`ArenaBuilder`](crate::ArenaBuilder) and [`BoxBuilder`](crate::BoxBuilder))
+/// hashes to the same value.
+pub struct TypeHasher<B: TypeBuilder> {
+    hasher: StableHasher,
+    builder: B,
+}
+
+impl<B: TypeBuilder> TypeHasher<B> {
+    pub fn new(builder: B) -> Self {
+        Self {
+            hasher: StableHasher::new(),
+            builder,
+        }
+    }
+
+    pub fn hash(ty: B::TypeView, builder: B) -> u64 {
+        let mut hasher = Self::new(builder);
+        hasher.visit(ty);
+        hasher.hasher.finish()
+    }
+}
+
+impl<B: TypeBuilder> TypeVisitor<B> for TypeHasher<B> {
+    fn builder(&self) -> B {
+        self.builder
+    }
+
+    fn visit(&mut self, ty: B::TypeView) {
+        let builder = self.builder;
+        match ty.view(builder) {
+            TypeKind::TypeVar(id) => {
+                self.hasher.write_u8(0);
+                self.hasher.write_u16(*id);
+            }
+            TypeKind::Scalar(scalar) => {
+                self.hasher.write_u8(1);
+                match scalar {
+                    Scalar::Bool => self.hasher.write_u8(0),
+                    Scalar::Int => self.hasher.write_u8(1),
+                    Scalar::Float => self.hasher.write_u8(2),
+                    Scalar::Str => self.hasher.write_u8(3),
+                    Scalar::Bytes => self.hasher.write_u8(4),
+                    Scalar::Custom(custom) => {
+                        self.hasher.write_u8(5);
+                        self.hasher.write_str(custom.name());
+                    }
+                }
+            }
+            TypeKind::Array(elem) => {
+                self.hasher.write_u8(2);
+                self.visit(elem.clone());
+            }
+            TypeKind::Map(key, val) => {
+                self.hasher.write_u8(3);
+                self.visit(key.clone());
+                self.visit(val.clone());
+            }
+            TypeKind::Record(fields) => {
+                self.hasher.write_u8(4);
+                let field_data = builder.field_types_data(fields);
+                self.hasher.write_usize(field_data.len());
+                for (name, field_ty) in field_data {
+                    self.hasher.write_str(name.as_ref());
+                    self.visit(field_ty.clone());
+                }
+            }
+            TypeKind::Function { params, ret } => {
+                self.hasher.write_u8(5);
+                let param_data = builder.types_data(params);
+                self.hasher.write_usize(param_data.len());
+                for param_ty in param_data {
+                    self.visit(param_ty.clone());
+                }
+                self.visit(ret.clone());
+            }
+            TypeKind::Symbol(parts) => {
+                self.hasher.write_u8(6);
+                let part_data = builder.symbol_parts_data(parts);
+                self.hasher.write_usize(part_data.len());
+                for part in part_data {
+                    self.hasher.write_str(part.as_ref());
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::BoxBuilder;
+
+    #[test]
+    fn test_stable_hasher_known_value() {
+        // FNV-1a reference value for "a"
+        let mut hasher = StableHasher::new();
+        hasher.write(b"a");
+        assert_eq!(hasher.finish(), 0xaf63_dc4c_8601_ec8c);
+    }
+
+    #[test]
+    fn test_hash_record_field_names() {
+        let builder = BoxBuilder::new();
+        let a = builder.record([("a", builder.int())]);
+        let b = builder.record([("b", builder.int())]);
+        assert_ne!(TypeHasher::hash(a, builder), TypeHasher::hash(b, builder));
+    }
+}
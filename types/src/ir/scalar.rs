@@ -3,6 +3,23 @@
 //! This module defines the Scalar enum which represents all primitive scalar types
 //! in Melbi's type system, following Chalk's pattern of consolidating scalar types
 //! into a single enum.
+//!
+//! Embedders can add their own primitive types (e.g. a UUID) through
+//! [`CustomScalar`], without changing this crate:
+//!
+//! ```
+//! use melbi_types::{BoxBuilder, CustomScalar, Scalar, TyDisplay, TypeKind};
+//!
+//! static UUID: CustomScalar = CustomScalar::new("Uuid").comparable();
+//!
+//! let builder = BoxBuilder::new();
+//! let uuid = TypeKind::Scalar(Scalar::Custom(&UUID)).intern(builder);
+//! assert_eq!(uuid.display(builder), "Uuid");
+//! ```
+
+use core::cmp::Ordering;
+use core::fmt;
+use core::hash::{Hash, Hasher};
 
 /// Scalar type variants
 ///
@@ -26,6 +43,9 @@ pub enum Scalar {
 
     /// Bytes type
     Bytes,
+
+    /// Embedder-defined scalar type
+    Custom(&'static CustomScalar),
 }
 
 impl Scalar {
@@ -36,8 +56,12 @@ impl Scalar {
 
     /// Returns true if this scalar supports comparison operations
     pub fn is_comparable(&self) -> bool {
-        // All scalars except Bytes are comparable
-        !matches!(self, Scalar::Bytes)
+        match self {
+            // All built-in scalars except Bytes are comparable
+            Scalar::Bytes => false,
+            Scalar::Custom(custom) => custom.is_comparable(),
+            _ => true,
+        }
     }
 
     /// Returns true if this scalar is a string-like type (Str or Bytes)
@@ -45,3 +69,84 @@ impl Scalar {
         matches!(self, Scalar::Str | Scalar::Bytes)
     }
 }
+
+impl fmt::Display for Scalar {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Scalar::Bool => write!(f, "Bool"),
+            Scalar::Int => write!(f, "Int"),
+            Scalar::Float => write!(f, "Float"),
+            Scalar::Str => write!(f, "Str"),
+            Scalar::Bytes => write!(f, "Bytes"),
+            Scalar::Custom(custom) => write!(f, "{}", custom.name()),
+        }
+    }
+}
+
+/// Definition of an embedder-provided scalar type.
+///
+/// Declare each custom scalar once as a `static` and use it through
+/// [`Scalar::Custom`]. The name identifies the type: it is used for display,
+/// and two custom scalars are the same type exactly when their names are
+/// equal, regardless of which `static` they come from.
+#[derive(Debug)]
+pub struct CustomScalar {
+    name: &'static str,
+    comparable: bool,
+}
+
+impl CustomScalar {
+    /// Define a scalar named `name`. It does not support comparison
+    /// operations unless marked [`comparable`](Self::comparable).
+    pub const fn new(name: &'static str) -> Self {
+        Self {
+            name,
+            comparable: false,
+        }
+    }
+
+    /// Mark this scalar as supporting comparison operations.
+    pub const fn comparable(self) -> Self {
+        Self {
+            comparable: true,
+            ..self
+        }
+    }
+
+    /// The type name, as written in type annotations and error messages.
+    pub const fn name(&self) -> &'static str {
+        self.name
+    }
+
+    /// Returns true if this scalar supports comparison operations.
+    pub const fn is_comparable(&self) -> bool {
+        self.comparable
+    }
+}
+
+// Custom scalars are identified by name only
+impl PartialEq for CustomScalar {
+    fn eq(&self, other: &Self) -> bool {
+        self.name == other.name
+    }
+}
+
+impl Eq for CustomScalar {}
+
+impl PartialOrd for CustomScalar {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for CustomScalar {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.name.cmp(other.name)
+    }
+}
+
+impl Hash for CustomScalar {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.name.hash(state);
+    }
+}
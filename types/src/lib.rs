@@ -25,14 +25,18 @@ pub mod ir;
 // Concrete builder implementations
 pub mod arena_builder;
 pub mod box_builder;
+pub mod intern_builder;
+pub mod rc_builder;
 
 // Re-export IR types for convenience
 pub use ir::{
-    ClosureVisitor, Scalar, Ty, TyData, TyDisplay, TypeBuilder, TypeChildren, TypeFolder,
-    TypeFormatter, TypeKind, TypeKindDisplay, TypeView, TypeVisitor, Zip, Zipper, convert_ty,
-    types_cmp, types_equal,
+    ClosureVisitor, CustomScalar, Scalar, StableHasher, Ty, TyData, TyDisplay, TypeBuilder, TypeChildren,
+    TypeFolder, TypeFormatter, TypeHasher, TypeKind, TypeKindDisplay, TypeView, TypeVisitor, Zip,
+    Zipper, convert_ty, types_cmp, types_equal,
 };
 
 // Re-export concrete builders
 pub use arena_builder::ArenaBuilder;
 pub use box_builder::BoxBuilder;
+pub use intern_builder::InternBuilder;
+pub use rc_builder::RcBuilder;
@@ -2,9 +2,11 @@
 
 use bumpalo::Bump;
 use melbi_types::{
-    ArenaBuilder, BoxBuilder, Scalar, TypeBuilder, TypeKind, convert_ty, types_cmp, types_equal,
+    ArenaBuilder, BoxBuilder, RcBuilder, Scalar, TypeBuilder, TypeHasher, TypeKind, convert_ty,
+    types_cmp, types_equal,
 };
 use std::cmp::Ordering;
+use std::rc::Rc;
 
 #[test]
 fn test_types_equal_scalars() {
@@ -368,3 +370,81 @@ fn test_round_trip_conversion() {
     // Both arena versions should be structurally equal
     assert!(types_equal(arr_arena, arena_int, arr_arena2, arena_int));
 }
+
+#[test]
+fn test_rc_builder_round_trip_conversion() {
+    let arena = Bump::new();
+    let arena_int = ArenaBuilder::new(&arena);
+    let rc_int = RcBuilder::new();
+
+    // Build Array[Int] with the high-level API
+    let arr_rc = rc_int.array(rc_int.int());
+    assert!(arr_rc.is_array(rc_int));
+
+    // Convert to arena and back
+    let arr_arena = convert_ty(arr_rc.clone(), rc_int, arena_int);
+    assert!(types_equal(arr_rc.clone(), rc_int, arr_arena, arena_int));
+    assert_eq!(
+        types_cmp(arr_rc.clone(), rc_int, arr_arena, arena_int),
+        Ordering::Equal
+    );
+
+    let arr_rc2 = convert_ty(arr_arena, arena_int, rc_int);
+    assert!(types_equal(arr_rc, rc_int, arr_rc2, rc_int));
+}
+
+#[test]
+fn test_rc_builder_shares_on_clone() {
+    let rc_int = RcBuilder::new();
+
+    let record = rc_int.record([("name", rc_int.str()), ("age", rc_int.int())]);
+    let copy = record.clone();
+
+    // Cloning a handle shares the node instead of copying it
+    assert!(Rc::ptr_eq(record.interned(), copy.interned()));
+
+    // Without interning, equal types built separately are distinct nodes
+    let other = rc_int.record([("age", rc_int.int()), ("name", rc_int.str())]);
+    assert!(!Rc::ptr_eq(record.interned(), other.interned()));
+    assert!(types_equal(record, rc_int, other, rc_int));
+}
+
+#[test]
+fn test_type_hasher_same_across_builders() {
+    let arena = Bump::new();
+    let arena_builder = ArenaBuilder::new(&arena);
+    let box_builder = BoxBuilder::new();
+
+    let arena_ty = arena_builder.function(
+        [arena_builder.record([
+            ("name", arena_builder.str()),
+            ("tags", arena_builder.array(arena_builder.str())),
+        ])],
+        arena_builder.map(arena_builder.str(), arena_builder.type_var(0)),
+    );
+    let box_ty = box_builder.function(
+        [box_builder.record([
+            ("tags", box_builder.array(box_builder.str())),
+            ("name", box_builder.str()),
+        ])],
+        box_builder.map(box_builder.str(), box_builder.type_var(0)),
+    );
+
+    assert_eq!(
+        TypeHasher::hash(arena_ty, arena_builder),
+        TypeHasher::hash(box_ty, box_builder)
+    );
+}
+
+#[test]
+fn test_type_hasher_distinguishes_types() {
+    let arena = Bump::new();
+    let builder = ArenaBuilder::new(&arena);
+
+    let array_int = builder.array(builder.int());
+    let array_str = builder.array(builder.str());
+    assert_ne!(
+        TypeHasher::hash(array_int, builder),
+        TypeHasher::hash(array_str, builder)
+    );
+}
@@ -5,8 +5,8 @@
 
 use bumpalo::Bump;
 use melbi_types::{
-    ArenaBuilder, BoxBuilder, Scalar, Ty, TyDisplay, TypeBuilder, TypeFolder, TypeKind, TypeView,
-    TypeVisitor,
+    ArenaBuilder, BoxBuilder, CustomScalar, InternBuilder, Scalar, Ty, TyDisplay, TypeBuilder,
+    TypeFolder, TypeKind, TypeView, TypeVisitor, types_equal,
 };
 
 #[test]
@@ -130,7 +130,8 @@ fn test_visitor_counting() {
                 | TypeKind::Function { .. }
                 | TypeKind::Symbol(_)
                 | TypeKind::Scalar(Scalar::Str)
-                | TypeKind::Scalar(Scalar::Bytes) => {}
+                | TypeKind::Scalar(Scalar::Bytes)
+                | TypeKind::Scalar(Scalar::Custom(_)) => {}
             }
             self.super_visit(ty);
         }
@@ -236,6 +237,65 @@ fn test_type_equality() {
     assert!(arr2.is_array(builder));
 }
 
+#[test]
+fn test_intern_builder_deduplicates_identical_types() {
+    let arena = Bump::new();
+    let builder = InternBuilder::new(&arena);
+
+    let arr1 = builder.array(builder.int());
+    let arr2 = builder.array(builder.int());
+    assert!(std::ptr::eq(arr1.data(builder), arr2.data(builder)));
+    assert_eq!(arr1, arr2);
+    assert!(types_equal(arr1, builder, arr2, builder));
+
+    // Records are canonicalized by field name before interning
+    let rec1 = builder.record([("name", builder.str()), ("age", builder.int())]);
+    let rec2 = builder.record([("age", builder.int()), ("name", builder.str())]);
+    assert_eq!(rec1, rec2);
+
+    let fn1 = builder.function([builder.int(), arr1], builder.bool());
+    let fn2 = builder.function([builder.int(), arr2], builder.bool());
+    assert_eq!(fn1, fn2);
+}
+
+#[test]
+fn test_intern_builder_distinguishes_different_types() {
+    let arena = Bump::new();
+    let builder = InternBuilder::new(&arena);
+
+    assert_ne!(builder.int(), builder.float());
+    assert_ne!(builder.array(builder.int()), builder.array(builder.float()));
+    assert_ne!(
+        builder.map(builder.str(), builder.int()),
+        builder.map(builder.int(), builder.str())
+    );
+    assert_ne!(builder.type_var(0), builder.type_var(1));
+    assert_ne!(
+        builder.function([builder.int()], builder.int()),
+        builder.function([builder.int(), builder.int()], builder.int())
+    );
+    assert_ne!(builder.symbol(["a", "b"]), builder.symbol(["a"]));
+
+    // Separate builders have separate tables
+    let other = InternBuilder::new(&arena);
+    assert_ne!(builder.int(), other.int());
+    assert!(types_equal(builder.int(), builder, other.int(), other));
+}
+
+#[test]
+fn test_intern_builder_unique_types() {
+    let arena = Bump::new();
+    let builder = InternBuilder::new(&arena);
+    assert_eq!(builder.unique_types(), 0);
+
+    builder.array(builder.int());
+    builder.array(builder.int());
+    assert_eq!(builder.unique_types(), 2); // Int and Array[Int]
+
+    builder.map(builder.int(), builder.array(builder.int()));
+    assert_eq!(builder.unique_types(), 3);
+}
+
 #[test]
 fn test_arena_multiple_types() {
     let arena = Bump::new();
@@ -673,3 +733,65 @@ fn test_arena_builder_with_new_variants() {
         TypeKind::Symbol(builder.intern_symbol_parts(vec!["alpha", "beta"])).intern(builder);
     assert!(symbol_ty.display(builder).contains("Symbol["));
 }
+
+static UUID: CustomScalar = CustomScalar::new("Uuid").comparable();
+static ULID: CustomScalar = CustomScalar::new("Ulid");
+
+#[test]
+fn test_custom_scalar_in_record() {
+    let arena = Bump::new();
+    let arena_builder = ArenaBuilder::new(&arena);
+    let box_builder = BoxBuilder::new();
+
+    let arena_record = arena_builder.record([
+        (
+            "id",
+            TypeKind::Scalar(Scalar::Custom(&UUID)).intern(arena_builder),
+        ),
+        ("name", arena_builder.str()),
+    ]);
+    let box_record = box_builder.record([
+        (
+            "id",
+            TypeKind::Scalar(Scalar::Custom(&UUID)).intern(box_builder),
+        ),
+        ("name", box_builder.str()),
+    ]);
+
+    assert_eq!(
+        arena_record.display(arena_builder),
+        "Record[id: Uuid, name: Str]"
+    );
+    assert!(types_equal(
+        arena_record,
+        arena_builder,
+        box_record,
+        box_builder
+    ));
+
+    let TypeKind::Record(fields) = arena_record.view(arena_builder) else {
+        panic!("Expected Record type");
+    };
+    let (_, id_ty) = &arena_builder.field_types_data(fields)[0];
+    let TypeKind::Scalar(id_scalar) = id_ty.view(arena_builder) else {
+        panic!("Expected Scalar type");
+    };
+    assert!(id_scalar.is_comparable());
+    assert!(!id_scalar.is_numeric());
+}
+
+#[test]
+fn test_custom_scalar_equality() {
+    let builder = BoxBuilder::new();
+    let uuid = TypeKind::Scalar(Scalar::Custom(&UUID)).intern(builder);
+    let ulid = TypeKind::Scalar(Scalar::Custom(&ULID)).intern(builder);
+
+    // Custom scalars are identified by name, not by which static defines them
+    static OTHER_UUID: CustomScalar = CustomScalar::new("Uuid");
+    let other_uuid = TypeKind::Scalar(Scalar::Custom(&OTHER_UUID)).intern(builder);
+
+    assert!(types_equal(uuid.clone(), builder, other_uuid, builder));
+    assert!(!types_equal(uuid.clone(), builder, ulid, builder));
+    assert!(!types_equal(uuid, builder, builder.str(), builder));
+    assert!(!Scalar::Custom(&ULID).is_comparable());
+}